@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::U256;
+use alloy::rpc::types::TransactionRequest;
+use criterion::{criterion_group, criterion_main, Criterion};
+use helios_core::execution::evm::Evm;
+use helios_core::types::BlockTag;
+
+mod fixtures;
+
+criterion_main!(call);
+criterion_group! {
+    name = call;
+    config = Criterion::default();
+    targets = bench_mock_call
+}
+
+/// Benchmark an `eth_call` transferring ether between two accounts of a generated chain,
+/// proving every account the call touches. Accounts stay cached between iterations, as they
+/// would for repeated calls against the same block.
+pub fn bench_mock_call(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+    let chain = fixtures::chain(64, 16);
+    let client = Arc::new(rt.block_on(fixtures::execution_client(&chain)));
+
+    // the generated token contract always reverts, so call between plain accounts
+    let accounts = chain
+        .accounts()
+        .into_iter()
+        .filter(|address| *address != chain.token())
+        .collect::<Vec<_>>();
+    let tx = TransactionRequest::default()
+        .with_from(accounts[0])
+        .with_to(accounts[1])
+        .with_value(U256::from(1));
+    let chain_id = chain.chain_id();
+    let fork_schedule = chain.fork_schedule();
+
+    c.bench_function("mock_call", |b| {
+        b.to_async(&rt).iter(|| async {
+            let mut evm = Evm::new(client.clone(), chain_id, fork_schedule, BlockTag::Latest);
+            evm.call(&tx).await.unwrap()
+        })
+    });
+}
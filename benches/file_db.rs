@@ -2,7 +2,7 @@ use alloy::primitives::b256;
 use criterion::{criterion_group, criterion_main, Criterion};
 use helios_ethereum::{
     config::Config,
-    database::{Database, FileDB},
+    database::{Database, FileDB, OpenDatabase},
 };
 use tempfile::tempdir;
 
@@ -0,0 +1,30 @@
+#![allow(dead_code)]
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::rpc::ChainRpc;
+use helios_ethereum::spec::Ethereum;
+
+/// Seed shared by every offline benchmark, so runs compare the same generated chain.
+pub const SEED: u64 = 0x6865_6c69_6f73;
+
+/// Builds a deterministic chain with `accounts` funded accounts and `txs_per_block`
+/// transactions in each of its blocks.
+pub fn chain(accounts: usize, txs_per_block: usize) -> MockChain {
+    ChainBuilder::new(SEED)
+        .accounts(accounts)
+        .txs_per_block(txs_per_block)
+        .length(4)
+        .build()
+}
+
+/// An execution client over `chain`, with every block of the chain already verified.
+pub async fn execution_client(chain: &MockChain) -> ExecutionClient<Ethereum, ChainRpc> {
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let state = State::unsynced(64, rpc.clone());
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+
+    ExecutionClient::with_rpc(rpc, state, chain.fork_schedule())
+}
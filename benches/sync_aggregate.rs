@@ -0,0 +1,84 @@
+use alloy::primitives::b256;
+use criterion::{criterion_group, criterion_main, Criterion};
+use helios_consensus_core::consensus_spec::MainnetConsensusSpec;
+use helios_consensus_core::types::{Bootstrap, FinalityUpdate, LightClientStore, Update};
+use helios_consensus_core::{
+    apply_bootstrap, apply_update, verify_bootstrap, verify_finality_update, verify_update,
+};
+use helios_ethereum::config::networks;
+use helios_ethereum::rpc::{mock_rpc::MockRpc, ConsensusRpc};
+
+mod harness;
+
+criterion_main!(sync_aggregate);
+criterion_group! {
+    name = sync_aggregate;
+    config = Criterion::default().sample_size(20);
+    targets = bench_verify_update, bench_verify_finality_update
+}
+
+type Spec = MainnetConsensusSpec;
+
+// upper bound on the current slot, the fixtures are far in the past
+const CURRENT_SLOT: u64 = u64::MAX;
+
+/// Client store bootstrapped from the recorded mainnet fixtures, with the mock consensus rpc
+/// serving updates from the same period.
+fn bootstrapped() -> (LightClientStore<Spec>, MockRpc) {
+    let checkpoint = b256!("5afc212a7924789b2bc86acad3ab3a6ffb1f6e97253ea50bee7f4f51422c9275");
+    let rpc = <MockRpc as ConsensusRpc<Spec>>::new("ethereum/testdata/");
+    let forks = networks::mainnet().forks;
+
+    let bootstrap: Bootstrap<Spec> = harness::await_future(rpc.get_bootstrap(checkpoint)).unwrap();
+    verify_bootstrap::<Spec>(&bootstrap, checkpoint, &forks).unwrap();
+
+    let mut store = LightClientStore::default();
+    apply_bootstrap(&mut store, &bootstrap);
+    (store, rpc)
+}
+
+/// Benchmark verifying a sync committee update, which checks the sync aggregate signature
+/// and the next committee branch.
+pub fn bench_verify_update(c: &mut Criterion) {
+    let config = networks::mainnet();
+    let (store, rpc) = bootstrapped();
+    let updates: Vec<Update<Spec>> = harness::await_future(rpc.get_updates(0, 1)).unwrap();
+    let update = &updates[0];
+
+    c.bench_function("verify_update", |b| {
+        b.iter(|| {
+            verify_update::<Spec>(
+                update,
+                CURRENT_SLOT,
+                &store,
+                config.chain.genesis_root,
+                &config.forks,
+            )
+            .unwrap()
+        })
+    });
+}
+
+/// Benchmark verifying a finality update against a store synced through the fixture updates.
+pub fn bench_verify_finality_update(c: &mut Criterion) {
+    let config = networks::mainnet();
+    let (mut store, rpc) = bootstrapped();
+    let updates: Vec<Update<Spec>> = harness::await_future(rpc.get_updates(0, 1)).unwrap();
+    for update in &updates {
+        apply_update(&mut store, update);
+    }
+    let update: FinalityUpdate<Spec> = harness::await_future(rpc.get_finality_update()).unwrap();
+
+    c.bench_function("verify_finality_update", |b| {
+        b.iter(|| {
+            verify_finality_update::<Spec>(
+                &update,
+                CURRENT_SLOT,
+                &store,
+                config.chain.genesis_root,
+                &config.forks,
+            )
+            .unwrap()
+        })
+    });
+}
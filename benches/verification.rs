@@ -0,0 +1,138 @@
+use alloy::rpc::types::TransactionReceipt;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use helios_core::execution::proof::{verify_account_proof, verify_storage_proof};
+use helios_core::execution::rpc::compat::{is_canonical_receipt, ResponseKind};
+use helios_core::network_spec::NetworkSpec;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+mod fixtures;
+
+criterion_main!(verification);
+criterion_group! {
+    name = verification;
+    config = Criterion::default();
+    targets = bench_account_proof, bench_storage_proof, bench_receipts_root, bench_receipt_decoding,
+        bench_block_hash
+}
+
+/// Benchmark account proof verification against tries of growing depth.
+pub fn bench_account_proof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("account_proof");
+    for accounts in [16, 256, 4096] {
+        let chain = fixtures::chain(accounts, 4);
+        let proof = chain.proof(chain.accounts()[0], &[]);
+        let state_root = chain.state_root();
+
+        group.bench_with_input(BenchmarkId::from_parameter(accounts), &proof, |b, proof| {
+            b.iter(|| verify_account_proof(proof, state_root).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark storage proof verification of the token contract, whose storage trie holds a
+/// balance slot per account.
+pub fn bench_storage_proof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("storage_proof");
+    for accounts in [16, 256, 4096] {
+        let chain = fixtures::chain(accounts, 4);
+        let token = chain.token();
+        let slots = chain
+            .storage(token)
+            .into_iter()
+            .map(|(slot, _)| slot)
+            .take(1)
+            .collect::<Vec<_>>();
+        let proof = chain.proof(token, &slots);
+
+        group.bench_with_input(BenchmarkId::from_parameter(accounts), &proof, |b, proof| {
+            b.iter(|| verify_storage_proof(proof).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark fetching a block's receipts and checking them against its receipts root.
+pub fn bench_receipts_root(c: &mut Criterion) {
+    let rt = tokio::runtime::Runtime::new().unwrap();
+
+    let mut group = c.benchmark_group("receipts_root");
+    for txs_per_block in [8, 512] {
+        let chain = fixtures::chain(64, txs_per_block);
+        let client = rt.block_on(fixtures::execution_client(&chain));
+
+        group.bench_function(BenchmarkId::from_parameter(txs_per_block), |b| {
+            b.to_async(&rt).iter(|| async {
+                client
+                    .get_block_receipts(BlockTag::Latest)
+                    .await
+                    .unwrap()
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark decoding the raw receipts of a 500 transaction block, through a normalized
+/// `Value` as every receipt used to be and through the borrowed canonical shape check.
+pub fn bench_receipt_decoding(c: &mut Criterion) {
+    let chain = fixtures::chain(64, 500);
+    let raw = chain
+        .receipts(chain.head().header.number)
+        .unwrap()
+        .iter()
+        .map(|receipt| serde_json::to_string(receipt).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("receipt_decoding");
+    group.bench_function("value", |b| {
+        b.iter(|| {
+            raw.iter()
+                .map(|raw| {
+                    let mut value = serde_json::from_str(raw).unwrap();
+                    ResponseKind::Receipt.normalize(&mut value);
+                    assert!(ResponseKind::Receipt.missing_fields(&value).is_empty());
+                    serde_json::from_value::<TransactionReceipt>(value).unwrap()
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| {
+            raw.iter()
+                .map(|raw| {
+                    assert!(is_canonical_receipt(raw));
+                    serde_json::from_str::<TransactionReceipt>(raw).unwrap()
+                })
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+/// Benchmark recomputing the block hash from the header, for each network spec.
+pub fn bench_block_hash(c: &mut Criterion) {
+    let chain = fixtures::chain(64, 64);
+    let block = chain.head().clone();
+
+    let mut group = c.benchmark_group("block_hash");
+    group.bench_function("ethereum", |b| {
+        b.iter(|| assert!(Ethereum::is_hash_valid(&block)))
+    });
+
+    #[cfg(feature = "opstack")]
+    {
+        use helios_opstack::spec::OpStack;
+
+        // the generated legacy transactions are valid op stack transactions as well
+        let block: <OpStack as alloy::network::Network>::BlockResponse =
+            serde_json::from_value(serde_json::to_value(&block).unwrap()).unwrap();
+        group.bench_function("opstack", |b| {
+            b.iter(|| assert!(OpStack::is_hash_valid(&block)))
+        });
+    }
+
+    group.finish();
+}
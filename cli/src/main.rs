@@ -1,51 +1,75 @@
+#[cfg(feature = "opstack")]
 use std::collections::HashMap;
-use std::net::{IpAddr, SocketAddr};
+#[cfg(feature = "ethereum")]
+use std::str::FromStr;
 use std::{
+    net::{IpAddr, SocketAddr},
     path::PathBuf,
     process::exit,
-    str::FromStr,
     sync::{Arc, Mutex},
 };
 
+#[cfg(feature = "opstack")]
 use alloy::primitives::hex;
 use alloy::primitives::B256;
 use clap::{Args, Parser, Subcommand};
 use dirs::home_dir;
 use eyre::Result;
-use figment::providers::Serialized;
-use figment::value::Value;
-use futures::executor::block_on;
+#[cfg(feature = "opstack")]
+use figment::{providers::Serialized, value::Value};
+#[cfg(feature = "ethereum")]
+use figment::{providers::Toml, Figment};
 use helios_core::client::Client;
+#[cfg(feature = "ethereum")]
+use helios_core::config::ConfigDuration;
 use helios_core::consensus::Consensus;
+#[cfg(feature = "ethereum")]
+use helios_core::execution::rpc::headers::{parse_header, HeaderConfig};
 use helios_core::network_spec::NetworkSpec;
-use helios_ethereum::config::{cli::CliConfig, Config as EthereumConfig};
-use helios_ethereum::database::FileDB;
-use helios_ethereum::{EthereumClient, EthereumClientBuilder};
+#[cfg(feature = "ethereum")]
+use helios_ethereum::{
+    config::{
+        checkpoints::{
+            parse_checkpoint, CheckpointFallback, CheckpointFallbackService, CheckpointSurvey,
+        },
+        cli::CliConfig,
+        networks::Network as EthereumNetwork,
+        Config as EthereumConfig,
+    },
+    database::EmbeddedDB,
+    EthereumClient, EthereumClientBuilder,
+};
+#[cfg(feature = "opstack")]
 use helios_opstack::{config::Config as OpStackConfig, OpStackClient, OpStackClientBuilder};
 use tracing::{error, info};
 use tracing_subscriber::filter::{EnvFilter, LevelFilter};
 use tracing_subscriber::FmtSubscriber;
 use url::Url;
 
+#[cfg(not(any(feature = "ethereum", feature = "opstack")))]
+compile_error!("helios-cli needs at least one of the `ethereum` and `opstack` features");
+
 #[tokio::main]
 async fn main() -> Result<()> {
     enable_tracer();
 
     let cli = Cli::parse();
     match cli.command {
+        #[cfg(feature = "ethereum")]
         Command::Ethereum(ethereum) => {
-            let mut client = ethereum.make_client();
+            let mut client = ethereum.make_client().await;
             start_client(&mut client).await;
-            register_shutdown_handler(client);
+            run_until_failed(client).await
         }
+        #[cfg(feature = "opstack")]
         Command::OpStack(opstack) => {
             let mut client = opstack.make_client();
             start_client(&mut client).await;
-            register_shutdown_handler(client);
+            run_until_failed(client).await
         }
+        #[cfg(feature = "ethereum")]
+        Command::Checkpoints(checkpoints) => exit(checkpoints.run().await),
     }
-
-    std::future::pending().await
 }
 
 fn enable_tracer() {
@@ -68,8 +92,21 @@ async fn start_client<N: NetworkSpec, C: Consensus<N::BlockResponse>>(client: &m
     }
 }
 
+/// Runs until ctrl-c shuts the client down, or until a task of the client keeps failing.
+async fn run_until_failed<N: NetworkSpec, C: Consensus<N::BlockResponse>>(
+    client: Client<N, C>,
+) -> ! {
+    let handle = client.handle();
+    register_shutdown_handler(client);
+
+    let err = handle.node().failed().await;
+    error!(target: "helios::runner", error = %err, "exiting");
+    exit(1);
+}
+
 fn register_shutdown_handler<N: NetworkSpec, C: Consensus<N::BlockResponse>>(client: Client<N, C>) {
-    let client = Arc::new(client);
+    let client = Mutex::new(Some(client));
+    let runtime = tokio::runtime::Handle::current();
     let shutdown_counter = Arc::new(Mutex::new(0));
 
     ctrlc::set_handler(move || {
@@ -89,10 +126,10 @@ fn register_shutdown_handler<N: NetworkSpec, C: Consensus<N::BlockResponse>>(cli
             3 - counter_value
         );
 
-        if counter_value == 1 {
-            let client = client.clone();
+        if let Some(mut client) = client.lock().unwrap().take() {
+            let runtime = runtime.clone();
             std::thread::spawn(move || {
-                block_on(client.shutdown());
+                runtime.block_on(client.shutdown());
                 exit(0);
             });
         }
@@ -110,21 +147,69 @@ struct Cli {
 
 #[derive(Subcommand)]
 enum Command {
+    #[cfg(feature = "ethereum")]
     #[clap(name = "ethereum")]
     Ethereum(EthereumArgs),
+    #[cfg(feature = "opstack")]
     #[clap(name = "opstack")]
     OpStack(OpStackArgs),
+    /// Query the checkpoint sync services of a network and check that they agree
+    #[cfg(feature = "ethereum")]
+    #[clap(name = "checkpoints")]
+    Checkpoints(CheckpointsArgs),
 }
 
+#[cfg(feature = "ethereum")]
 #[derive(Args)]
 struct EthereumArgs {
-    #[clap(short, long, default_value = "mainnet")]
+    #[clap(short, long, default_value = "mainnet", value_parser = parse_ethereum_network)]
     network: String,
     #[clap(short = 'b', long, env)]
     rpc_bind_ip: Option<IpAddr>,
     #[clap(short = 'p', long, env)]
     rpc_port: Option<u16>,
-    #[clap(short = 'w', long, env)]
+    #[clap(
+        long,
+        env,
+        requires = "rpc_tls_key",
+        help = "PEM certificate chain to serve the rpc over TLS"
+    )]
+    rpc_tls_cert: Option<PathBuf>,
+    #[clap(
+        long,
+        env,
+        requires = "rpc_tls_cert",
+        help = "PEM private key for --rpc-tls-cert"
+    )]
+    rpc_tls_key: Option<PathBuf>,
+    #[clap(
+        long,
+        env,
+        help = "Unix domain socket, or named pipe on Windows, to also serve the rpc on"
+    )]
+    ipc_path: Option<PathBuf>,
+    #[clap(
+        long,
+        env,
+        requires = "ipc_path",
+        value_parser = parse_permissions,
+        help = "Octal permissions of the ipc socket file, 600 by default"
+    )]
+    ipc_permissions: Option<u32>,
+    #[clap(
+        long,
+        env,
+        requires = "ipc_path",
+        help = "Serve the rpc over the ipc socket only, without the http listener"
+    )]
+    ipc_only: bool,
+    #[clap(
+        long,
+        env,
+        help = "Address to serve prometheus metrics on, such as 127.0.0.1:9102"
+    )]
+    metrics_address: Option<SocketAddr>,
+    #[clap(short = 'w', long, env, value_parser = parse_checkpoint)]
     checkpoint: Option<B256>,
     #[clap(short, long, env, value_parser = parse_url)]
     execution_rpc: Option<Url>,
@@ -136,23 +221,86 @@ struct EthereumArgs {
     fallback: Option<String>,
     #[clap(short = 'l', long, env)]
     load_external_fallback: bool,
+    #[clap(
+        long,
+        env,
+        conflicts_with = "load_external_fallback",
+        help = "Never bootstrap from the external fallback services, even on networks that do by default"
+    )]
+    no_external_fallback: bool,
+    #[clap(
+        long,
+        env,
+        help = "External fallback services that must agree on a checkpoint before it is used"
+    )]
+    fallback_quorum: Option<usize>,
     #[clap(short = 's', long, env)]
     strict_checkpoint_age: bool,
+    #[clap(
+        long,
+        env,
+        help = "Start without checking that the rpcs serve the chain of the network"
+    )]
+    offline: bool,
+    #[clap(long, env)]
+    premerge_passthrough: bool,
+    #[clap(
+        long,
+        env,
+        help = "Blocks under the latest that the helios_confirmed tag resolves to"
+    )]
+    confirmation_depth: Option<u64>,
+    #[clap(
+        long,
+        env,
+        help = "Retries of a rate limited execution rpc request, 0 to never retry"
+    )]
+    execution_max_retries: Option<u32>,
+    #[clap(
+        long,
+        env,
+        help = "Wait before the first retry of a rate limited request, such as 50ms"
+    )]
+    execution_initial_backoff: Option<ConfigDuration>,
+    #[clap(long, env, help = "Longest wait between two retries of a request")]
+    execution_max_backoff: Option<ConfigDuration>,
+    #[clap(
+        long,
+        env,
+        help = "Compute units per second the execution rpc allows, 0 for no limit"
+    )]
+    execution_compute_units: Option<u64>,
+    #[clap(
+        long = "execution-rpc-header",
+        value_name = "NAME=VALUE",
+        value_parser = parse_header,
+        help = "Header to send with every execution rpc request, repeatable"
+    )]
+    execution_rpc_headers: Vec<(String, String)>,
+    #[clap(
+        long = "broadcast-rpc",
+        value_parser = parse_url,
+        help = "Execution rpc raw transactions are also submitted to, repeatable"
+    )]
+    broadcast_rpcs: Vec<Url>,
 }
 
+#[cfg(feature = "ethereum")]
 impl EthereumArgs {
-    fn make_client(&self) -> EthereumClient<FileDB> {
-        let config_path = home_dir().unwrap().join(".helios/helios.toml");
+    async fn make_client(&self) -> EthereumClient<EmbeddedDB> {
         let cli_config = self.as_cli_config();
-        let config = EthereumConfig::from_file(&config_path, &self.network, &cli_config);
-
-        match EthereumClientBuilder::new()
-            .config(config)
-            .build::<FileDB>()
-        {
+        let config = EthereumConfig::from_file(&config_path(), &self.network, &cli_config);
+
+        let builder = EthereumClientBuilder::new().config(config);
+        let builder = if self.offline {
+            builder.offline()
+        } else {
+            builder
+        };
+        match builder.build_checked::<EmbeddedDB>().await {
             Ok(client) => client,
             Err(err) => {
-                error!(target: "helios::runner", error = %err);
+                eprintln!("helios: {err}");
                 exit(1);
             }
         }
@@ -169,13 +317,150 @@ impl EthereumArgs {
                 .map(|s| PathBuf::from_str(s).expect("cannot find data dir")),
             rpc_bind_ip: self.rpc_bind_ip,
             rpc_port: self.rpc_port,
+            rpc_tls_cert: self.rpc_tls_cert.clone(),
+            rpc_tls_key: self.rpc_tls_key.clone(),
+            ipc_path: self.ipc_path.clone(),
+            ipc_permissions: self.ipc_permissions,
+            ipc_only: true_or_none(self.ipc_only),
+            metrics_address: self.metrics_address,
             fallback: self.fallback.clone(),
-            load_external_fallback: true_or_none(self.load_external_fallback),
+            load_external_fallback: if self.no_external_fallback {
+                Some(false)
+            } else {
+                true_or_none(self.load_external_fallback)
+            },
+            fallback_quorum: self.fallback_quorum,
             strict_checkpoint_age: true_or_none(self.strict_checkpoint_age),
+            premerge_passthrough: true_or_none(self.premerge_passthrough),
+            confirmation_depth: self.confirmation_depth,
+            execution_max_retries: self.execution_max_retries,
+            execution_initial_backoff: self.execution_initial_backoff,
+            execution_max_backoff: self.execution_max_backoff,
+            execution_compute_units: self.execution_compute_units,
+            execution_headers: HeaderConfig {
+                headers: self.execution_rpc_headers.iter().cloned().collect(),
+                ..Default::default()
+            },
+            broadcast_rpcs: self.broadcast_rpcs.clone(),
         }
     }
 }
 
+#[cfg(feature = "ethereum")]
+#[derive(Args)]
+struct CheckpointsArgs {
+    #[clap(short, long, default_value = "mainnet", value_parser = parse_ethereum_network)]
+    network: String,
+    #[clap(
+        short = 'f',
+        long,
+        env,
+        help = "Checkpoint sync api to query besides the configured and listed ones"
+    )]
+    fallback: Option<String>,
+    #[clap(long, help = "Print the results as json")]
+    json: bool,
+}
+
+#[cfg(feature = "ethereum")]
+impl CheckpointsArgs {
+    /// Surveys the services, printing what each returned, and returns the exit code.
+    async fn run(&self) -> i32 {
+        let services = self.services().await;
+        if services.is_empty() {
+            error!(target: "helios::runner", "no checkpoint sync services for {}", self.network);
+            return 1;
+        }
+
+        let survey = CheckpointFallback::survey(&services).await;
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&survey).unwrap());
+        } else {
+            print_survey(&survey);
+        }
+
+        if survey.quorum {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// The fallback from the command line and config file, followed by the healthy services
+    /// of the community maintained list.
+    async fn services(&self) -> Vec<CheckpointFallbackService> {
+        let configured = Figment::from(Toml::file(config_path()))
+            .extract_inner::<String>(&format!("{}.fallback", self.network))
+            .ok();
+        let mut services = [("cli", &self.fallback), ("config", &configured)]
+            .into_iter()
+            .filter_map(|(name, endpoint)| {
+                Some(CheckpointFallbackService {
+                    endpoint: endpoint.clone()?,
+                    name: name.to_string(),
+                    state: true,
+                    ..Default::default()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        if let Ok(network) = EthereumNetwork::from_str(&self.network) {
+            match CheckpointFallback::new().build().await {
+                Ok(fallback) => {
+                    for service in fallback.get_healthy_fallback_services(&network) {
+                        if services.iter().all(|s| s.endpoint != service.endpoint) {
+                            services.push(service);
+                        }
+                    }
+                }
+                Err(err) => {
+                    error!(target: "helios::runner", error = %err, "could not load checkpoint sync services");
+                }
+            }
+        }
+
+        services
+    }
+}
+
+#[cfg(feature = "ethereum")]
+fn print_survey(survey: &CheckpointSurvey) {
+    println!(
+        "   {:<28} {:<68} {:>10} {:>8} {:>8}",
+        "source", "root", "slot", "age", "latency"
+    );
+    for report in &survey.reports {
+        let marker = match report.root {
+            None => "-",
+            Some(root) if Some(root) == survey.majority => "*",
+            Some(_) => "!",
+        };
+        let root = match (&report.root, &report.error) {
+            (Some(root), _) => root.to_string(),
+            (None, Some(err)) => err.clone(),
+            (None, None) => String::new(),
+        };
+        let slot = report.slot.map(|slot| slot.to_string()).unwrap_or_default();
+        let age = report
+            .age
+            .map(|age| format!("{}m", age / 60))
+            .unwrap_or_default();
+
+        println!(
+            " {marker} {:<28} {:<68} {:>10} {:>8} {:>6}ms",
+            report.name, root, slot, age, report.latency
+        );
+    }
+
+    println!();
+    match survey.majority {
+        Some(root) if survey.quorum => println!("quorum on {root} (*), outliers marked with !"),
+        Some(root) => println!("no quorum, the most common checkpoint is {root}"),
+        None => println!("no quorum, no service returned a checkpoint"),
+    }
+}
+
+#[cfg(feature = "opstack")]
 #[derive(Args, Debug)]
 struct OpStackArgs {
     #[clap(short, long)]
@@ -184,6 +469,26 @@ struct OpStackArgs {
     rpc_bind_ip: Option<IpAddr>,
     #[clap(short = 'p', long, env, default_value = "8545")]
     rpc_port: Option<u16>,
+    #[clap(
+        long,
+        env,
+        requires = "rpc_tls_key",
+        help = "PEM certificate chain to serve the rpc over TLS"
+    )]
+    rpc_tls_cert: Option<PathBuf>,
+    #[clap(
+        long,
+        env,
+        requires = "rpc_tls_cert",
+        help = "PEM private key for --rpc-tls-cert"
+    )]
+    rpc_tls_key: Option<PathBuf>,
+    #[clap(
+        long,
+        env,
+        help = "Unix domain socket, or named pipe on Windows, to also serve the rpc on"
+    )]
+    ipc_path: Option<PathBuf>,
     #[clap(short, long, env, value_parser = parse_url)]
     execution_rpc: Option<Url>,
     #[clap(short, long, env, value_parser = parse_url)]
@@ -202,13 +507,26 @@ struct OpStackArgs {
         help = "Enable fallback for weak subjectivity checkpoint. Use if --ethereum-checkpoint fails."
     )]
     load_external_fallback: bool,
+    #[clap(
+        long = "ethereum-execution-rpc",
+        env = "ETHEREUM_EXECUTION_RPC",
+        value_parser = parse_url,
+        help = "Execution rpc of the chosen Ethereum network. Helios checks the deposits of <NETWORK> blocks against the L1 receipts it serves when verify_unsafe_signer is set"
+    )]
+    l1_execution_rpc: Option<Url>,
+    #[clap(
+        long,
+        env,
+        help = "Reject blocks with deposits that could not be matched to a verified L1 event"
+    )]
+    strict_deposits: bool,
 }
 
+#[cfg(feature = "opstack")]
 impl OpStackArgs {
     fn make_client(&self) -> OpStackClient {
-        let config_path = home_dir().unwrap().join(".helios/helios.toml");
         let cli_provider = self.as_provider();
-        let config = OpStackConfig::from_file(&config_path, &self.network, cli_provider);
+        let config = OpStackConfig::from_file(&config_path(), &self.network, cli_provider);
 
         match OpStackClientBuilder::new().config(config).build() {
             Ok(client) => client,
@@ -243,6 +561,18 @@ impl OpStackArgs {
             user_dict.insert("rpc_port", Value::from(port));
         }
 
+        if let Some(cert) = &self.rpc_tls_cert {
+            user_dict.insert("rpc_tls_cert", Value::from(cert.to_str().unwrap()));
+        }
+
+        if let Some(key) = &self.rpc_tls_key {
+            user_dict.insert("rpc_tls_key", Value::from(key.to_str().unwrap()));
+        }
+
+        if let Some(path) = &self.ipc_path {
+            user_dict.insert("ipc_path", Value::from(path.to_str().unwrap()));
+        }
+
         if self.load_external_fallback {
             user_dict.insert("load_external_fallback", Value::from(true));
         }
@@ -251,10 +581,19 @@ impl OpStackArgs {
             user_dict.insert("checkpoint", Value::from(hex::encode(checkpoint)));
         }
 
+        if let Some(rpc) = &self.l1_execution_rpc {
+            user_dict.insert("l1_execution_rpc", Value::from(rpc.to_string()));
+        }
+
+        if self.strict_deposits {
+            user_dict.insert("strict_deposits", Value::from(true));
+        }
+
         Serialized::from(user_dict, &self.network)
     }
 }
 
+#[cfg(feature = "ethereum")]
 fn true_or_none(b: bool) -> Option<bool> {
     if b {
         Some(b)
@@ -263,6 +602,37 @@ fn true_or_none(b: bool) -> Option<bool> {
     }
 }
 
+fn config_path() -> PathBuf {
+    home_dir().unwrap().join(".helios/helios.toml")
+}
+
+/// Accepts the built in presets, and custom networks defined in the config file.
+#[cfg(feature = "ethereum")]
+fn parse_ethereum_network(s: &str) -> Result<String, String> {
+    let is_custom = || {
+        Figment::from(Toml::file(config_path()))
+            .find_value(s)
+            .is_ok()
+    };
+    if EthereumNetwork::from_str(s).is_ok() || is_custom() {
+        return Ok(s.to_string());
+    }
+
+    Err(format!(
+        "expected one of {}, or a network defined in {}",
+        EthereumNetwork::names().join(", "),
+        config_path().display()
+    ))
+}
+
 fn parse_url(s: &str) -> Result<Url, url::ParseError> {
     Url::parse(s)
 }
+
+fn parse_permissions(s: &str) -> Result<u32, String> {
+    let digits = s.strip_prefix("0o").unwrap_or(s);
+    match u32::from_str_radix(digits, 8) {
+        Ok(mode) if mode <= 0o777 => Ok(mode),
+        _ => Err(format!("{s} is not an octal file mode such as 600")),
+    }
+}
@@ -0,0 +1,75 @@
+//! Embeds build metadata reported by `helios_version`.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let commit = packaged_commit().or_else(git_commit);
+    println!(
+        "cargo:rustc-env=HELIOS_GIT_COMMIT={}",
+        commit.as_deref().unwrap_or("unknown")
+    );
+
+    let target = env::var("TARGET").unwrap_or_else(|_| "unknown".to_string());
+    println!("cargo:rustc-env=HELIOS_BUILD_TARGET={target}");
+
+    let profile = env::var("PROFILE").unwrap_or_else(|_| "unknown".to_string());
+    println!("cargo:rustc-env=HELIOS_BUILD_PROFILE={profile}");
+
+    let mut features = env::vars()
+        .filter_map(|(key, _)| key.strip_prefix("CARGO_FEATURE_").map(str::to_owned))
+        .map(|feature| feature.to_lowercase().replace('_', "-"))
+        .filter(|feature| feature != "default")
+        .map(|feature| format!("helios-core/{feature}"))
+        .collect::<Vec<_>>();
+    features.sort();
+    println!(
+        "cargo:rustc-env=HELIOS_BUILD_FEATURES={}",
+        features.join(",")
+    );
+}
+
+/// The commit recorded by `cargo package`, for builds of a published crate. Checked first,
+/// since a published crate may be built inside an unrelated git checkout.
+fn packaged_commit() -> Option<String> {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").ok()?;
+    let vcs_info =
+        fs::read_to_string(Path::new(&manifest_dir).join(".cargo_vcs_info.json")).ok()?;
+
+    let (_, rest) = vcs_info.split_once("\"sha1\"")?;
+    let (_, rest) = rest.split_once('"')?;
+    let (sha1, _) = rest.split_once('"')?;
+    Some(sha1.to_string())
+}
+
+/// The checked out commit, when building from a git checkout with git installed.
+fn git_commit() -> Option<String> {
+    let commit = git(&["rev-parse", "HEAD"])?;
+
+    // rebuild when the checkout moves to another commit
+    if let Some(git_dir) = git(&["rev-parse", "--git-dir"]) {
+        println!("cargo:rerun-if-changed={git_dir}/HEAD");
+        if let Some(head_ref) = git(&["symbolic-ref", "-q", "HEAD"]) {
+            let ref_path = Path::new(&git_dir).join(&head_ref);
+            if ref_path.exists() {
+                println!("cargo:rerun-if-changed={}", ref_path.display());
+            }
+        }
+    }
+
+    Some(commit)
+}
+
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    Some(stdout.trim().to_string()).filter(|out| !out.is_empty())
+}
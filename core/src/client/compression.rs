@@ -0,0 +1,105 @@
+//! Compression of rpc server responses and provider traffic.
+//!
+//! Responses are compressed with gzip or zstd, whichever the caller prefers among those it
+//! advertises in `Accept-Encoding`, once they are large enough for it to pay off. Provider
+//! clients advertise both and decompress what they receive. Event streams and websocket
+//! upgrades are never compressed.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::ByteSize;
+
+/// Responses smaller than this are sent as they are.
+pub const DEFAULT_COMPRESSION_MIN_SIZE: ByteSize = ByteSize::from_bytes(1 << 10);
+
+/// Compression settings, read from the `compression` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CompressionConfig {
+    /// Compress rpc server responses and request compressed provider responses, off unless
+    /// set.
+    pub enabled: bool,
+    /// Smallest rpc server response that is compressed, at most 64KB.
+    pub min_size: ByteSize,
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_size: DEFAULT_COMPRESSION_MIN_SIZE,
+        }
+    }
+}
+
+/// An http client for provider requests, advertising gzip and zstd and decompressing the
+/// responses unless `compression` is off.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn http_client(compression: bool) -> reqwest::Result<reqwest::Client> {
+    http_client_builder(compression).build()
+}
+
+/// A builder of [`http_client`], for callers configuring the client further.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn http_client_builder(compression: bool) -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder();
+    if compression {
+        builder
+    } else {
+        builder.no_gzip().no_zstd()
+    }
+}
+
+/// An http client for provider requests. Browsers negotiate the compression of fetch
+/// requests themselves.
+#[cfg(target_arch = "wasm32")]
+pub fn http_client(_compression: bool) -> reqwest::Result<reqwest::Client> {
+    Ok(reqwest::Client::new())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use server::{layer, CompressionPredicate};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    use http::StatusCode;
+    use tower_http::compression::predicate::{NotForContentType, Predicate, SizeAbove};
+    use tower_http::compression::CompressionLayer;
+
+    use super::CompressionConfig;
+
+    /// Decides which rpc server responses are compressed.
+    #[derive(Debug, Clone)]
+    pub struct CompressionPredicate {
+        enabled: bool,
+        size: SizeAbove,
+        events: NotForContentType,
+    }
+
+    impl Predicate for CompressionPredicate {
+        fn should_compress<B>(&self, response: &http::Response<B>) -> bool
+        where
+            B: http_body::Body,
+        {
+            // upgraded connections carry websocket frames, not the response body
+            self.enabled
+                && response.status() != StatusCode::SWITCHING_PROTOCOLS
+                && self.events.should_compress(response)
+                && self.size.should_compress(response)
+        }
+    }
+
+    /// The middleware compressing rpc server responses according to `config`. Only gzip and
+    /// zstd are offered, and nothing is compressed when `config` disables it.
+    pub fn layer(config: &CompressionConfig) -> CompressionLayer<CompressionPredicate> {
+        let min_size = config.min_size.as_bytes().min(u16::MAX as u64) as u16;
+        CompressionLayer::new()
+            .no_br()
+            .no_deflate()
+            .compress_when(CompressionPredicate {
+                enabled: config.enabled,
+                size: SizeAbove::new(min_size),
+                events: NotForContentType::SSE,
+            })
+    }
+}
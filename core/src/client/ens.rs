@@ -0,0 +1,257 @@
+//! ENS name resolution through verified calls.
+//!
+//! Every step is an `eth_call` executed by the local evm against proven state, all anchored
+//! to the same block: the registry is asked for the resolver of the name, and the resolver
+//! for the address. Names are resolved the ENSIP-10 way, walking up to the closest parent
+//! with a resolver, which is then only used for the name if it is an extended resolver. An
+//! extended resolver answering with the `OffchainLookup` revert of EIP-3668 keeps the
+//! answer offchain. The evm follows such lookups when CCIP-Read is enabled, otherwise they
+//! are reported as [`EnsError::RequiresCcipRead`].
+//!
+//! Names are lowercased before hashing, the full ENSIP-15 normalization is left to the
+//! caller.
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{keccak256, Address, Bytes, FixedBytes, B256};
+use alloy::sol_types::{sol, SolCall};
+use thiserror::Error;
+
+use crate::errors::ClientError;
+use crate::execution::ccip::OffchainLookup;
+use crate::execution::errors::EvmError;
+use crate::execution::evm::Evm;
+use crate::execution::rpc::ExecutionRpc;
+use crate::network_spec::NetworkSpec;
+
+/// Interface id of ENSIP-10 extended resolvers.
+const EXTENDED_RESOLVER: FixedBytes<4> = FixedBytes([0x90, 0x61, 0xb9, 0x23]);
+
+sol! {
+    function resolver(bytes32 node) external view returns (address);
+    function addr(bytes32 node) external view returns (address);
+    function name(bytes32 node) external view returns (string);
+    function supportsInterface(bytes4 interfaceId) external view returns (bool);
+    function resolve(bytes name, bytes data) external view returns (bytes);
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EnsError {
+    #[error("no ens registry is configured for this network")]
+    NoRegistry,
+    #[error("invalid ens name: {0}")]
+    InvalidName(String),
+    /// The resolver keeps the answer offchain, behind the gateways of an EIP-3668 lookup.
+    #[error("resolving {name} requires ccip-read from {}", .urls.join(", "))]
+    RequiresCcipRead {
+        name: String,
+        resolver: Address,
+        urls: Vec<String>,
+        call_data: Bytes,
+        callback: FixedBytes<4>,
+        extra_data: Bytes,
+    },
+}
+
+/// The ENSIP-1 namehash of `name`, after lowercasing it.
+pub fn namehash(name: &str) -> B256 {
+    let name = name.to_lowercase();
+    name.rsplit('.')
+        .filter(|label| !label.is_empty())
+        .fold(B256::ZERO, |node, label| {
+            keccak256([node.as_slice(), keccak256(label).as_slice()].concat())
+        })
+}
+
+/// The DNS wire format of `name` that ENSIP-10 resolvers take.
+pub fn dns_encode(name: &str) -> Result<Bytes, EnsError> {
+    let name = name.to_lowercase();
+    let mut out = Vec::with_capacity(name.len() + 2);
+    for label in name.split('.') {
+        if label.is_empty() || label.len() > 255 {
+            return Err(EnsError::InvalidName(name.clone()));
+        }
+        out.push(label.len() as u8);
+        out.extend_from_slice(label.as_bytes());
+    }
+    out.push(0);
+
+    Ok(out.into())
+}
+
+/// The name holding the primary name of `address`.
+pub fn reverse_name(address: Address) -> String {
+    format!("{}.addr.reverse", hex::encode(address))
+}
+
+/// Resolves names against the ENS registry at `registry`, through the calls of `evm`.
+pub struct EnsResolver<N: NetworkSpec, R: ExecutionRpc<N>> {
+    evm: Evm<N, R>,
+    registry: Address,
+}
+
+impl<N: NetworkSpec, R: ExecutionRpc<N>> EnsResolver<N, R> {
+    /// `evm` should be anchored to a block number, so the calls agree on the state.
+    pub fn new(evm: Evm<N, R>, registry: Address) -> Self {
+        Self { evm, registry }
+    }
+
+    /// The address `name` resolves to, or `None` if it is not registered, has no resolver
+    /// or no address set.
+    pub async fn resolve(&mut self, name: &str) -> Result<Option<Address>, ClientError> {
+        if name.split('.').any(str::is_empty) {
+            return Err(EnsError::InvalidName(name.to_string()).into());
+        }
+        let node = namehash(name);
+        let Some((resolver, exact)) = self.find_resolver(name).await? else {
+            return Ok(None);
+        };
+
+        let call = addrCall { node };
+        let extended = self
+            .call(
+                resolver,
+                &supportsInterfaceCall {
+                    interfaceId: EXTENDED_RESOLVER,
+                },
+            )
+            .await?
+            .is_some_and(|supported| supported._0);
+        let address = if extended {
+            self.resolve_extended(name, resolver, call).await?
+        } else if exact {
+            self.call(resolver, &call).await?.map(|address| address._0)
+        } else {
+            None
+        };
+
+        Ok(address.filter(|address| !address.is_zero()))
+    }
+
+    /// The primary name of `address`, if it has one that resolves back to it.
+    pub async fn lookup(&mut self, address: Address) -> Result<Option<String>, ClientError> {
+        let reverse = reverse_name(address);
+        let node = namehash(&reverse);
+        let Some(resolver) = self.resolver_of(node).await? else {
+            return Ok(None);
+        };
+        let Some(name) = self
+            .call(resolver, &nameCall { node })
+            .await?
+            .map(|name| name._0)
+        else {
+            return Ok(None);
+        };
+        if name.is_empty() {
+            return Ok(None);
+        }
+
+        // anyone can claim any name as theirs in the reverse record
+        let resolved = self.resolve(&name).await?;
+        Ok((resolved == Some(address)).then_some(name))
+    }
+
+    /// The resolver of `name` or of its closest parent that has one, and whether it is that of
+    /// `name` itself.
+    async fn find_resolver(&mut self, name: &str) -> Result<Option<(Address, bool)>, ClientError> {
+        let mut candidate = name;
+        loop {
+            if let Some(resolver) = self.resolver_of(namehash(candidate)).await? {
+                return Ok(Some((resolver, candidate.len() == name.len())));
+            }
+            match candidate.split_once('.') {
+                Some((_, parent)) => candidate = parent,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    async fn resolver_of(&mut self, node: B256) -> Result<Option<Address>, ClientError> {
+        let resolver = self.call(self.registry, &resolverCall { node }).await?;
+        Ok(resolver
+            .map(|resolver| resolver._0)
+            .filter(|resolver| !resolver.is_zero()))
+    }
+
+    async fn resolve_extended(
+        &mut self,
+        name: &str,
+        resolver: Address,
+        call: addrCall,
+    ) -> Result<Option<Address>, ClientError> {
+        let resolve = resolveCall {
+            name: dns_encode(name)?,
+            data: call.abi_encode().into(),
+        };
+        match self.evm.call(&request::<N>(resolver, &resolve)).await {
+            Ok(output) => {
+                let Ok(answer) = resolveCall::abi_decode_returns(&output, true) else {
+                    return Ok(None);
+                };
+                Ok(addrCall::abi_decode_returns(&answer._0, true)
+                    .ok()
+                    .map(|address| address._0))
+            }
+            // only reaches here when the evm does not follow offchain lookups itself
+            Err(EvmError::Revert(Some(data))) => {
+                let Some(lookup) = OffchainLookup::from_revert(&data, resolver) else {
+                    return Ok(None);
+                };
+                Err(EnsError::RequiresCcipRead {
+                    name: name.to_lowercase(),
+                    resolver,
+                    urls: lookup.urls,
+                    call_data: lookup.callData,
+                    callback: lookup.callbackFunction,
+                    extra_data: lookup.extraData,
+                }
+                .into())
+            }
+            Err(EvmError::Revert(None)) => Ok(None),
+            Err(err) => Err(ClientError::EvmError(err)),
+        }
+    }
+
+    /// Calls `to`, reading a revert or an answer that does not decode as no answer, as
+    /// contracts without the function give.
+    async fn call<C: SolCall>(
+        &mut self,
+        to: Address,
+        call: &C,
+    ) -> Result<Option<C::Return>, ClientError> {
+        match self.evm.call(&request::<N>(to, call)).await {
+            Ok(output) => Ok(C::abi_decode_returns(&output, true).ok()),
+            Err(EvmError::Revert(_)) => Ok(None),
+            Err(err) => Err(ClientError::EvmError(err)),
+        }
+    }
+}
+
+fn request<N: NetworkSpec>(to: Address, call: &impl SolCall) -> N::TransactionRequest {
+    N::TransactionRequest::default()
+        .with_to(to)
+        .with_input(call.abi_encode())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::b256;
+
+    use super::*;
+
+    #[test]
+    fn test_namehash() {
+        assert_eq!(namehash(""), B256::ZERO);
+        assert_eq!(
+            namehash("eth"),
+            b256!("93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+        );
+        assert_eq!(
+            namehash("Foo.ETH"),
+            b256!("de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+        );
+        assert_eq!(
+            dns_encode("foo.eth").unwrap().as_ref(),
+            b"\x03foo\x03eth\x00"
+        );
+    }
+}
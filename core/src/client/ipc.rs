@@ -0,0 +1,283 @@
+//! Serves the rpc over a Unix domain socket, or a named pipe on Windows, for tooling on the
+//! same host that should not need a TCP port.
+//!
+//! Requests are read one per line, single or batched, and each response is written as a
+//! line of its own. Subscription notifications are written to the connection that
+//! subscribed, in between the responses.
+
+use std::path::{Path, PathBuf};
+
+use eyre::Result;
+use jsonrpsee::core::server::Methods;
+use serde_json::value::RawValue;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc::{self, Receiver, Sender};
+use tokio::task::{JoinHandle, JoinSet};
+use tracing::{debug, info, warn};
+
+/// Permissions of the socket file unless configured otherwise, leaving it to the user
+/// running helios.
+pub const DEFAULT_IPC_PERMISSIONS: u32 = 0o600;
+
+/// Messages buffered for a connection, and notifications for each of its subscriptions,
+/// before the slowest reader holds up the others.
+const MESSAGE_BUFFER: usize = 64;
+
+const PARSE_ERROR: &str =
+    r#"{"jsonrpc":"2.0","error":{"code":-32700,"message":"Parse error"},"id":null}"#;
+
+/// Where the ipc socket is created, and with which permissions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpcConfig {
+    pub path: PathBuf,
+    /// Unix mode of the socket file, ignored on Windows.
+    pub permissions: u32,
+}
+
+impl IpcConfig {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            permissions: DEFAULT_IPC_PERMISSIONS,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum IpcError {
+    #[error("another process is listening on the ipc socket {0}")]
+    InUse(PathBuf),
+    #[error("{0} exists and is not a socket")]
+    NotASocket(PathBuf),
+    #[error("cannot create the ipc socket {0}: {1}")]
+    Bind(PathBuf, std::io::Error),
+}
+
+/// A running ipc listener, serving the same methods as the http server.
+pub struct IpcServer {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl IpcServer {
+    /// Creates the socket of `config` and serves `methods` on it. A socket file left behind
+    /// by an unclean shutdown is replaced, as long as nothing is listening on it.
+    pub async fn start(config: &IpcConfig, methods: Methods) -> Result<Self, IpcError> {
+        let task = listen(config, methods)?;
+        info!(target: "helios::rpc", path = %config.path.display(), "ipc server started");
+
+        Ok(Self {
+            path: config.path.clone(),
+            task,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Stops accepting connections, closes those already accepted and removes the socket
+    /// file.
+    pub async fn stop(self) {
+        // the accepted connections belong to the listener task and end with it
+        self.task.abort();
+        _ = self.task.await;
+
+        #[cfg(unix)]
+        if let Err(err) = std::fs::remove_file(&self.path) {
+            warn!(target: "helios::rpc", path = %self.path.display(), error = %err, "cannot remove the ipc socket");
+        }
+    }
+}
+
+#[cfg(unix)]
+fn listen(config: &IpcConfig, methods: Methods) -> Result<JoinHandle<()>, IpcError> {
+    use std::fs::{self, Permissions};
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+    use tokio::net::UnixListener;
+
+    let path = &config.path;
+    if let Ok(metadata) = fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(IpcError::NotASocket(path.clone()));
+        }
+        if std::os::unix::net::UnixStream::connect(path).is_ok() {
+            return Err(IpcError::InUse(path.clone()));
+        }
+
+        fs::remove_file(path).map_err(|err| IpcError::Bind(path.clone(), err))?;
+        info!(target: "helios::rpc", path = %path.display(), "removed the stale ipc socket of an earlier run");
+    }
+
+    // bound in a directory only the user can enter, so nobody connects before the
+    // permissions are set, then moved into place
+    let bind_err = |err| IpcError::Bind(path.clone(), err);
+    let dir = private_dir(path).map_err(bind_err)?;
+    let bound = dir.join("socket");
+    let listener = UnixListener::bind(&bound)
+        .and_then(|listener| {
+            fs::set_permissions(&bound, Permissions::from_mode(config.permissions))?;
+            fs::rename(&bound, path)?;
+            Ok(listener)
+        })
+        .map_err(bind_err);
+    _ = fs::remove_file(&bound);
+    _ = fs::remove_dir(&dir);
+    let listener = listener?;
+
+    Ok(tokio::spawn(async move {
+        let mut connections = JoinSet::new();
+        loop {
+            let stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(err) => {
+                    warn!(target: "helios::rpc", error = %err, "failed to accept ipc connection");
+                    continue;
+                }
+            };
+            while connections.try_join_next().is_some() {}
+            connections.spawn(serve_logged(stream, methods.clone()));
+        }
+    }))
+}
+
+/// Creates a directory next to `path` that only the user running helios can enter.
+#[cfg(unix)]
+fn private_dir(path: &Path) -> std::io::Result<PathBuf> {
+    use std::os::unix::fs::DirBuilderExt;
+
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let dir = path.with_file_name(format!(".{name}.{}", std::process::id()));
+    // left behind if an earlier process of the same id was killed while binding
+    _ = std::fs::remove_dir_all(&dir);
+    std::fs::DirBuilder::new().mode(0o700).create(&dir)?;
+    Ok(dir)
+}
+
+#[cfg(windows)]
+fn listen(config: &IpcConfig, methods: Methods) -> Result<JoinHandle<()>, IpcError> {
+    use tokio::net::windows::named_pipe::ServerOptions;
+
+    let path = config.path.clone();
+    let mut server = ServerOptions::new()
+        .first_pipe_instance(true)
+        .create(&path)
+        .map_err(|err| match err.kind() {
+            std::io::ErrorKind::PermissionDenied => IpcError::InUse(path.clone()),
+            _ => IpcError::Bind(path.clone(), err),
+        })?;
+
+    Ok(tokio::spawn(async move {
+        let mut connections = JoinSet::new();
+        loop {
+            if let Err(err) = server.connect().await {
+                warn!(target: "helios::rpc", error = %err, "failed to accept ipc connection");
+                continue;
+            }
+
+            // the next client connects to a new instance of the pipe
+            let next = match ServerOptions::new().create(&path) {
+                Ok(next) => next,
+                Err(err) => {
+                    warn!(target: "helios::rpc", error = %err, "cannot create the next ipc pipe instance, stopping");
+                    return;
+                }
+            };
+            let stream = std::mem::replace(&mut server, next);
+            while connections.try_join_next().is_some() {}
+            connections.spawn(serve_logged(stream, methods.clone()));
+        }
+    }))
+}
+
+async fn serve_logged<S: AsyncRead + AsyncWrite + Send + 'static>(stream: S, methods: Methods) {
+    if let Err(err) = serve_connection(stream, methods).await {
+        debug!(target: "helios::rpc", error = %err, "ipc connection closed");
+    }
+}
+
+/// Answers the requests of one connection in order until the client closes it.
+async fn serve_connection<S: AsyncRead + AsyncWrite + Send + 'static>(
+    stream: S,
+    methods: Methods,
+) -> Result<()> {
+    let (reader, mut writer) = tokio::io::split(stream);
+    let (messages, mut outgoing) = mpsc::channel::<String>(MESSAGE_BUFFER);
+    // the writer and the subscriptions are aborted when the connection is dropped, as on
+    // stop, and closing the connection ends its subscriptions
+    let mut writer_task = JoinSet::new();
+    writer_task.spawn(async move {
+        while let Some(message) = outgoing.recv().await {
+            let line = format!("{message}\n");
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut subscriptions = JoinSet::new();
+    let mut lines = BufReader::new(reader).lines();
+    let res = async {
+        while let Some(line) = lines.next_line().await? {
+            let request = line.trim();
+            if request.is_empty() {
+                continue;
+            }
+
+            let (response, notifications) = respond(&methods, request).await;
+            if messages.send(response).await.is_err() {
+                break;
+            }
+
+            // notifications follow the response that opened their subscription
+            while subscriptions.try_join_next().is_some() {}
+            for notifications in notifications {
+                subscriptions.spawn(forward(notifications, messages.clone()));
+            }
+        }
+        Ok(())
+    }
+    .await;
+
+    subscriptions.shutdown().await;
+    drop(messages);
+    _ = writer_task.join_next().await;
+    res
+}
+
+/// The response to a request or batch, and the notifications of any subscriptions it opened.
+async fn respond(methods: &Methods, request: &str) -> (String, Vec<Receiver<String>>) {
+    if !request.starts_with('[') {
+        let (response, notifications) = call(methods, request).await;
+        return (response, notifications.into_iter().collect());
+    }
+
+    let Ok(batch) = serde_json::from_str::<Vec<Box<RawValue>>>(request) else {
+        return (PARSE_ERROR.to_string(), Vec::new());
+    };
+    let mut responses = Vec::with_capacity(batch.len());
+    let mut subscriptions = Vec::new();
+    for call_request in batch {
+        let (response, notifications) = call(methods, call_request.get()).await;
+        responses.push(response);
+        subscriptions.extend(notifications);
+    }
+
+    (format!("[{}]", responses.join(",")), subscriptions)
+}
+
+async fn call(methods: &Methods, request: &str) -> (String, Option<Receiver<String>>) {
+    match methods.raw_json_request(request, MESSAGE_BUFFER).await {
+        Ok((response, notifications)) => (response.result, Some(notifications)),
+        Err(_) => (PARSE_ERROR.to_string(), None),
+    }
+}
+
+async fn forward(mut notifications: Receiver<String>, messages: Sender<String>) {
+    while let Some(notification) = notifications.recv().await {
+        if messages.send(notification).await.is_err() {
+            break;
+        }
+    }
+}
@@ -1,29 +1,71 @@
 use std::net::SocketAddr;
+use std::ops::Deref;
 use std::sync::Arc;
 use std::time::Duration;
 
+use alloy::eips::BlockId;
 use alloy::primitives::{Address, Bytes, B256, U256};
-use alloy::rpc::types::{Filter, FilterChanges, Log, SyncStatus};
+use alloy::rpc::types::{
+    AccessListResult, EIP1186AccountProofResponse, Filter, FilterChanges, Log,
+};
 use eyre::Result;
+use futures::Stream;
+use tokio::sync::broadcast;
 use tracing::{info, warn};
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::ipc::IpcConfig;
 use crate::client::node::Node;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::client::rpc::Rpc;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::tls::TlsConfig;
 use crate::consensus::Consensus;
+use crate::execution::asset_changes::AssetChangeReport;
+use crate::execution::bundle::{BundleOverrides, CallOutcome, SimulatedTransaction, StateOverride};
+use crate::execution::config::ExecutionConfig;
+use crate::execution::fee_history::FeeHistoryResponse;
+use crate::execution::log_pages::LogPage;
+use crate::execution::rpc::http_rpc::HttpRpc;
+use crate::execution::rpc::ExecutionRpc;
+use crate::execution::stream::{HeaderEvent, LagPolicy};
+use crate::execution::trace::{TraceOptions, TraceResult};
+use crate::execution::types::DeepReorg;
+use crate::execution::usage::UsageReport;
 use crate::fork_schedule::ForkSchedule;
 use crate::network_spec::NetworkSpec;
-use crate::time::interval;
-use crate::types::BlockTag;
-
+use crate::types::{
+    AnchoredCall, BeaconBlockMapping, BlockTag, DbStats, ExecutionProof, FilterParam,
+    FinalityEstimate, HealthReport, NetworkInfo, PrevRandao, PrevRandaoRange, ReorgEvent,
+    SyncCommitteeInfo, SyncState, VersionInfo,
+};
+
+pub mod compression;
+pub mod ens;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ipc;
 pub mod node;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod rpc;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod supervisor;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tls;
+pub mod trust;
 
+/// How long shutting down waits for the rpc requests in flight to finish.
+pub const SHUTDOWN_DEADLINE: Duration = Duration::from_secs(10);
+
+/// A light client, and the rpc server exposing it if enabled.
+///
+/// The verified methods are those of [`ClientHandle`], which the client dereferences to. Use
+/// [`Client::handle`] to share them with other tasks and threads.
 pub struct Client<N: NetworkSpec, C: Consensus<N::BlockResponse>> {
-    node: Arc<Node<N, C>>,
+    handle: ClientHandle<N, C>,
     #[cfg(not(target_arch = "wasm32"))]
     rpc: Option<Rpc<N, C>>,
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    metrics_address: Option<SocketAddr>,
 }
 
 impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Client<N, C> {
@@ -31,9 +73,28 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Client<N, C> {
         execution_rpc: &str,
         consensus: C,
         fork_schedule: ForkSchedule,
+        config: ExecutionConfig,
         #[cfg(not(target_arch = "wasm32"))] rpc_address: Option<SocketAddr>,
     ) -> Result<Self> {
-        let node = Node::new(execution_rpc, consensus, fork_schedule)?;
+        let execution_rpc = <HttpRpc<N> as ExecutionRpc<N>>::new(execution_rpc)?;
+        Ok(Self::with_execution_rpc(
+            execution_rpc,
+            consensus,
+            fork_schedule,
+            config,
+            #[cfg(not(target_arch = "wasm32"))]
+            rpc_address,
+        ))
+    }
+
+    pub fn with_execution_rpc(
+        execution_rpc: HttpRpc<N>,
+        consensus: C,
+        fork_schedule: ForkSchedule,
+        config: ExecutionConfig,
+        #[cfg(not(target_arch = "wasm32"))] rpc_address: Option<SocketAddr>,
+    ) -> Self {
+        let node = Node::with_execution_rpc(execution_rpc, consensus, fork_schedule, config);
         let node = Arc::new(node);
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -44,35 +105,290 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Client<N, C> {
             rpc = Some(Rpc::new(node.clone(), rpc_address));
         }
 
-        Ok(Client {
-            node,
+        Client {
+            handle: ClientHandle { node },
             #[cfg(not(target_arch = "wasm32"))]
             rpc,
-        })
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            metrics_address: None,
+        }
+    }
+
+    /// Serves the rpc over TLS. Has no effect if the rpc server is disabled.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_rpc_tls(mut self, tls: TlsConfig) -> Self {
+        self.rpc = self.rpc.map(|rpc| rpc.with_tls(tls));
+        self
+    }
+
+    /// Also serves the rpc over the ipc socket of `ipc`. Without an rpc address the server is
+    /// reachable over ipc alone.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_rpc_ipc(mut self, ipc: IpcConfig) -> Self {
+        self.rpc = Some(match self.rpc.take() {
+            Some(rpc) => rpc.with_ipc(ipc),
+            None => Rpc::ipc_only(self.handle.node.clone(), ipc),
+        });
+        self
+    }
+
+    /// Sets the verification policy of each rpc method. Has no effect if the rpc server is
+    /// disabled.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_rpc_policies(mut self, policies: trust::RpcPolicies) -> Self {
+        self.rpc = self.rpc.map(|rpc| rpc.with_policies(policies));
+        self
+    }
+
+    /// Sets how rpc server responses are compressed. Has no effect if the rpc server is
+    /// disabled.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_rpc_compression(mut self, compression: compression::CompressionConfig) -> Self {
+        self.rpc = self.rpc.map(|rpc| rpc.with_compression(compression));
+        self
+    }
+
+    /// Adds extra methods to the rpc server. Has no effect if the rpc server is disabled.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn with_rpc_methods(
+        mut self,
+        methods: impl Into<jsonrpsee::core::server::Methods>,
+    ) -> Result<Self> {
+        self.rpc = self.rpc.map(|rpc| rpc.with_methods(methods)).transpose()?;
+        Ok(self)
+    }
+
+    /// Serves the prometheus metrics of this client at `/metrics` on `addr` while it is
+    /// started.
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    pub fn with_metrics(mut self, addr: SocketAddr) -> Self {
+        self.metrics_address = Some(addr);
+        self
+    }
+
+    /// A cheap to clone handle to the verified methods of this client.
+    pub fn handle(&self) -> ClientHandle<N, C> {
+        self.handle.clone()
     }
 
     pub async fn start(&mut self) -> Result<()> {
+        let version = self.handle.node.version();
+        info!(
+            target: "helios::client",
+            version = %version.version,
+            commit = %version.git_commit,
+            target_triple = %version.target,
+            features = %version.features.join(","),
+            network = version.network.as_deref().unwrap_or("unknown"),
+            last_known_fork = version.last_known_fork.as_deref().unwrap_or("none"),
+            fork_version = ?version.fork_version,
+            "starting helios"
+        );
+
+        // resumes syncing if the client was shut down, and does nothing otherwise
+        self.handle.node.consensus.restart()?;
+        #[cfg(not(target_arch = "wasm32"))]
+        self.handle.node.supervisor().restart();
+
         #[cfg(not(target_arch = "wasm32"))]
         if let Some(rpc) = &mut self.rpc {
             rpc.start().await?;
         }
 
+        // the server is stopped for good on shutdown, so each start binds it again, keeping
+        // the port first bound
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        if let Some(addr) = self.metrics_address {
+            if let Some(metrics) = self.handle.node.execution.rpc.metrics() {
+                let addr =
+                    crate::metrics::serve(addr, self.handle.node.supervisor(), metrics.clone())?;
+                self.metrics_address = Some(addr);
+            }
+        }
+
         Ok(())
     }
 
+    /// Stops the rpc server, waiting up to [`SHUTDOWN_DEADLINE`] for the requests in flight,
+    /// and the metrics server, then shuts down the node as [`ClientHandle::shutdown`] does. The
+    /// client can be started again once this returns.
+    pub async fn shutdown(&mut self) {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(rpc) = &mut self.rpc {
+            rpc.stop(SHUTDOWN_DEADLINE).await;
+        }
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        crate::metrics::stop(self.handle.node.supervisor());
+
+        self.handle.shutdown().await;
+    }
+}
+
+impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Deref for Client<N, C> {
+    type Target = ClientHandle<N, C>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.handle
+    }
+}
+
+/// The verified methods of a [`Client`].
+///
+/// A handle is an [`Arc`] around the node, so clones are cheap and all of them share the same
+/// state. On native targets it is `Send + Sync` and the futures of its methods are `Send`, so
+/// it can be moved into tokio tasks, other threads or the state of an axum or tonic server.
+/// No lock is held across an await. On wasm the client is single threaded and neither is
+/// required.
+///
+/// ```no_run
+/// # use helios_core::client::Client;
+/// # use helios_core::consensus::Consensus;
+/// # use helios_core::network_spec::NetworkSpec;
+/// # async fn share<N: NetworkSpec, C: Consensus<N::BlockResponse>>(client: Client<N, C>) {
+/// let handle = client.handle();
+///
+/// let task = tokio::spawn({
+///     let handle = handle.clone();
+///     async move { handle.get_block_number().await }
+/// });
+/// let thread = std::thread::spawn(move || futures::executor::block_on(handle.chain_id()));
+///
+/// println!("block {} on chain {}", task.await.unwrap().unwrap(), thread.join().unwrap());
+/// # }
+/// ```
+pub struct ClientHandle<N: NetworkSpec, C: Consensus<N::BlockResponse>> {
+    node: Arc<Node<N, C>>,
+}
+
+impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Clone for ClientHandle<N, C> {
+    fn clone(&self) -> Self {
+        Self {
+            node: self.node.clone(),
+        }
+    }
+}
+
+impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> ClientHandle<N, C> {
+    /// The node backing this client, for building verified methods on top of it.
+    pub fn node(&self) -> &Arc<Node<N, C>> {
+        &self.node
+    }
+
+    /// Uninstalls the filters created through the client from the provider, stops syncing and
+    /// saves what is persisted. The rpc server, if any, is left running; use
+    /// [`Client::shutdown`] to stop it too.
     pub async fn shutdown(&self) {
         info!(target: "helios::client","shutting down");
+        // the supervised tasks wait for the next start rather than count as stuck
+        #[cfg(not(target_arch = "wasm32"))]
+        self.node.supervisor().shutdown();
+        self.node.execution.uninstall_filters().await;
         if let Err(err) = self.node.consensus.shutdown() {
             warn!(target: "helios::client", error = %err, "graceful shutdown failed");
         }
+        if let Some(usage) = self.node.execution.rpc.usage() {
+            if let Err(err) = usage.flush() {
+                warn!(target: "helios::client", error = %err, "could not save provider usage");
+            }
+        }
     }
 
     pub async fn call(&self, tx: &N::TransactionRequest, block: BlockTag) -> Result<Bytes> {
-        self.node.call(tx, block).await.map_err(|err| err.into())
+        self.node
+            .call(tx, block, StateOverride::default())
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Traces `tx` under the tracer of `options`, `callTracer` or the struct logger.
+    pub async fn trace_call(
+        &self,
+        tx: &N::TransactionRequest,
+        block: BlockTag,
+        options: &TraceOptions,
+    ) -> Result<TraceResult> {
+        self.node
+            .trace_call(tx, block, options)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    pub async fn call_with_header(
+        &self,
+        tx: &N::TransactionRequest,
+        header: N::HeaderResponse,
+    ) -> Result<AnchoredCall> {
+        self.node
+            .call_with_header(tx, header)
+            .await
+            .map_err(|err| err.into())
     }
 
     pub async fn estimate_gas(&self, tx: &N::TransactionRequest) -> Result<u64> {
-        self.node.estimate_gas(tx).await.map_err(|err| err.into())
+        self.node
+            .estimate_gas(tx, BlockTag::Latest, StateOverride::default())
+            .await
+            .map_err(|err| err.into())
+    }
+
+    pub async fn create_access_list(
+        &self,
+        tx: &N::TransactionRequest,
+        block: BlockTag,
+    ) -> Result<AccessListResult> {
+        self.node
+            .create_access_list(tx, block)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    /// Resolves an ENS name with verified calls. Names kept offchain are resolved through
+    /// their gateways, or fail with
+    /// [`EnsError::RequiresCcipRead`](crate::client::ens::EnsError::RequiresCcipRead) if
+    /// CCIP-Read is disabled.
+    pub async fn resolve_ens(&self, name: &str) -> Result<Option<Address>> {
+        self.node.resolve_ens(name).await.map_err(|err| err.into())
+    }
+
+    /// The verified primary ENS name of `address`.
+    pub async fn lookup_ens(&self, address: Address) -> Result<Option<String>> {
+        self.node
+            .lookup_ens(address)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    pub async fn simulate_with_asset_changes(
+        &self,
+        tx: &N::TransactionRequest,
+    ) -> Result<AssetChangeReport> {
+        self.node
+            .simulate_with_asset_changes(tx)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    pub async fn simulate_bundle(
+        &self,
+        txs: &[N::TransactionRequest],
+        block: BlockTag,
+        overrides: &BundleOverrides,
+    ) -> Result<Vec<SimulatedTransaction>> {
+        self.node
+            .simulate_bundle(txs, block, overrides)
+            .await
+            .map_err(|err| err.into())
+    }
+
+    pub async fn call_many(
+        &self,
+        txs: &[N::TransactionRequest],
+        block: BlockTag,
+    ) -> Result<Vec<CallOutcome>> {
+        self.node
+            .call_many(txs, block)
+            .await
+            .map_err(|err| err.into())
     }
 
     pub async fn get_balance(&self, address: Address, block: BlockTag) -> Result<U256> {
@@ -107,6 +423,32 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Client<N, C> {
         self.node.get_storage_at(address, slot, block).await
     }
 
+    /// The verified account and storage proofs of `address` at `block`, as `eth_getProof`
+    /// returns them.
+    pub async fn get_proof(
+        &self,
+        address: Address,
+        slots: &[B256],
+        block: BlockTag,
+    ) -> Result<EIP1186AccountProofResponse> {
+        self.node.get_proof(address, slots, block).await
+    }
+
+    /// A bundle of the proofs of `address` at `block` with the headers they are anchored to,
+    /// which [`verify_execution_proof`](crate::execution::proof::verify_execution_proof)
+    /// checks without a client.
+    pub async fn get_execution_proof(
+        &self,
+        address: Address,
+        slots: &[B256],
+        block: BlockTag,
+        include_code: bool,
+    ) -> Result<ExecutionProof> {
+        self.node
+            .get_execution_proof(address, slots, block, include_code)
+            .await
+    }
+
     pub async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<B256> {
         self.node.send_raw_transaction(bytes).await
     }
@@ -118,6 +460,19 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Client<N, C> {
         self.node.get_transaction_receipt(tx_hash).await
     }
 
+    /// Waits until the receipt of a transaction is verified with its block `confirmations`
+    /// deep, failing once `timeout` passes or the transaction is dropped.
+    pub async fn wait_for_transaction_receipt(
+        &self,
+        tx_hash: B256,
+        confirmations: u64,
+        timeout: Duration,
+    ) -> Result<N::ReceiptResponse> {
+        self.node
+            .wait_for_transaction_receipt(tx_hash, confirmations, timeout)
+            .await
+    }
+
     pub async fn get_block_receipts(
         &self,
         block: BlockTag,
@@ -133,6 +488,23 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Client<N, C> {
         self.node.get_logs(filter).await
     }
 
+    /// The filter of a filter parameter, with the ends given as `helios_confirmed` at the
+    /// confirmed block.
+    pub async fn resolve_filter(&self, filter: FilterParam) -> Result<Filter> {
+        self.node.resolve_filter(filter).await
+    }
+
+    /// A page of at most `page_size` logs matching `filter`, resuming at `cursor` if given.
+    /// Pass the cursor of each page to the next call until it comes back empty.
+    pub async fn get_logs_paged(
+        &self,
+        filter: &Filter,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<LogPage> {
+        self.node.get_logs_paged(filter, cursor, page_size).await
+    }
+
     pub async fn get_filter_changes(&self, filter_id: U256) -> Result<FilterChanges> {
         self.node.get_filter_changes(filter_id).await
     }
@@ -162,13 +534,27 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Client<N, C> {
     }
 
     pub async fn get_priority_fee(&self) -> Result<U256> {
-        self.node.get_priority_fee()
+        self.node.get_priority_fee().await
     }
 
     pub async fn blob_base_fee(&self, block: BlockTag) -> Result<U256> {
         self.node.blob_base_fee(block).await
     }
 
+    /// The base fees and gas used ratios of the `block_count` blocks up to `newest`, verified
+    /// against their headers, with the rewards at `reward_percentiles` as the provider reports
+    /// them.
+    pub async fn get_fee_history(
+        &self,
+        block_count: u64,
+        newest: BlockTag,
+        reward_percentiles: &[f64],
+    ) -> Result<FeeHistoryResponse> {
+        self.node
+            .get_fee_history(block_count, newest, reward_percentiles)
+            .await
+    }
+
     pub async fn get_block_number(&self) -> Result<U256> {
         self.node.get_block_number().await
     }
@@ -185,6 +571,12 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Client<N, C> {
         self.node.get_block_by_number(block, full_tx).await
     }
 
+    /// The block `confirmation_depth` blocks under the latest, which is only reorged away by
+    /// a reorg deeper than that.
+    pub async fn get_confirmed_block(&self, full_tx: bool) -> Result<Option<N::BlockResponse>> {
+        self.node.get_confirmed_block(full_tx).await
+    }
+
     pub async fn get_block_by_hash(
         &self,
         hash: B256,
@@ -217,7 +609,87 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Client<N, C> {
         self.node.chain_id()
     }
 
-    pub async fn syncing(&self) -> Result<SyncStatus> {
+    pub fn version(&self) -> VersionInfo {
+        self.node.version()
+    }
+
+    pub fn get_network_info(&self) -> NetworkInfo {
+        self.node.get_network_info()
+    }
+
+    pub async fn health(&self) -> HealthReport {
+        self.node.health()
+    }
+
+    pub async fn get_db_stats(&self) -> Result<Option<DbStats>> {
+        self.node.get_db_stats().map_err(|err| err.into())
+    }
+
+    pub async fn get_sync_committee(&self, period: u64) -> Result<Option<SyncCommitteeInfo>> {
+        self.node
+            .get_sync_committee(period)
+            .map_err(|err| err.into())
+    }
+
+    pub async fn get_sync_committee_periods(&self) -> Result<Vec<u64>> {
+        self.node
+            .get_sync_committee_periods()
+            .map_err(|err| err.into())
+    }
+
+    /// A block orphaned by a recent reorg, kept for lookups by hash within the configured
+    /// orphan cache limits.
+    pub async fn get_orphaned_block(&self, hash: B256, full_tx: bool) -> Option<N::BlockResponse> {
+        self.node.get_orphaned_block(hash, full_tx)
+    }
+
+    /// The most recent reorgs of the verified chain, oldest first.
+    pub async fn get_reorg_history(&self) -> Vec<ReorgEvent> {
+        self.node.get_reorg_history()
+    }
+
+    /// When a recent block, or the block including a recent transaction, is expected to be
+    /// finalized, from the finality delays the light client observed.
+    pub async fn estimate_finality_time(&self, hash: B256) -> Result<FinalityEstimate> {
+        self.node.estimate_finality_time(hash).await
+    }
+
+    pub async fn get_usage(&self) -> Option<UsageReport> {
+        self.node.get_usage()
+    }
+
+    pub async fn get_prev_randao(&self, block: BlockTag) -> Result<PrevRandao> {
+        self.node.get_prev_randao(block).await
+    }
+
+    pub async fn get_prev_randao_range(&self, start: u64, end: u64) -> Result<PrevRandaoRange> {
+        self.node.get_prev_randao_range(start, end).await
+    }
+
+    pub async fn get_beacon_header_by_execution_block(
+        &self,
+        block: BlockId,
+    ) -> Result<BeaconBlockMapping> {
+        self.node.get_beacon_header_by_execution_block(block).await
+    }
+
+    pub async fn get_execution_block_by_slot(&self, slot: u64) -> Result<BeaconBlockMapping> {
+        self.node.get_execution_block_by_slot(slot).await
+    }
+
+    pub async fn get_raw_header(&self, block: BlockId) -> Result<Bytes> {
+        self.node.get_raw_header(block).await
+    }
+
+    pub async fn get_raw_block(&self, block: BlockId) -> Result<Bytes> {
+        self.node.get_raw_block(block).await
+    }
+
+    pub async fn get_raw_receipts(&self, block: BlockId) -> Result<Vec<Bytes>> {
+        self.node.get_raw_receipts(block).await
+    }
+
+    pub async fn syncing(&self) -> Result<SyncState> {
         self.node.syncing().await
     }
 
@@ -225,13 +697,95 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Client<N, C> {
         self.node.get_coinbase().await
     }
 
+    /// Streams the header of every new verified head.
+    ///
+    /// The stream is backed by a bounded channel, and `policy` decides what happens to a
+    /// consumer that falls behind it. It ends when the client shuts down.
+    ///
+    /// ```no_run
+    /// # use alloy::network::primitives::HeaderResponse;
+    /// # use futures::StreamExt;
+    /// # use helios_core::client::Client;
+    /// # use helios_core::consensus::Consensus;
+    /// # use helios_core::execution::stream::{HeaderEvent, LagPolicy};
+    /// # use helios_core::network_spec::NetworkSpec;
+    /// # async fn follow<N: NetworkSpec, C: Consensus<N::BlockResponse>>(client: &Client<N, C>) {
+    /// let mut heads = std::pin::pin!(client.block_stream(LagPolicy::Skip));
+    /// while let Some(event) = heads.next().await {
+    ///     match event {
+    ///         HeaderEvent::Header(header) => println!("new head {}", header.hash()),
+    ///         HeaderEvent::Gap { missed } => println!("fell behind by {missed} heads"),
+    ///         HeaderEvent::Lagged { .. } => unreachable!("only sent with LagPolicy::Fail"),
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn block_stream(
+        &self,
+        policy: LagPolicy,
+    ) -> impl Stream<Item = HeaderEvent<N::HeaderResponse>> {
+        self.node.execution.block_stream(policy)
+    }
+
+    /// Streams the header of every newly finalized block, like [`ClientHandle::block_stream`].
+    ///
+    /// ```no_run
+    /// # use alloy::consensus::BlockHeader;
+    /// # use futures::StreamExt;
+    /// # use helios_core::client::Client;
+    /// # use helios_core::consensus::Consensus;
+    /// # use helios_core::execution::stream::{HeaderEvent, LagPolicy};
+    /// # use helios_core::network_spec::NetworkSpec;
+    /// # async fn follow<N: NetworkSpec, C: Consensus<N::BlockResponse>>(client: &Client<N, C>) {
+    /// let mut finalized = std::pin::pin!(client.finality_stream(LagPolicy::Fail));
+    /// while let Some(HeaderEvent::Header(header)) = finalized.next().await {
+    ///     println!("finalized {}", header.number());
+    /// }
+    /// # }
+    /// ```
+    pub fn finality_stream(
+        &self,
+        policy: LagPolicy,
+    ) -> impl Stream<Item = HeaderEvent<N::HeaderResponse>> {
+        self.node.execution.finality_stream(policy)
+    }
+
+    /// Receives every reorg that orphans the block `helios_confirmed` resolved to, which
+    /// applications relying on the confirmation depth may need to unwind.
+    pub fn deep_reorgs(&self) -> broadcast::Receiver<DeepReorg> {
+        self.node.execution.deep_reorgs()
+    }
+
+    /// Resolves once [`ClientHandle::syncing`] reports the client synced, checking again
+    /// whenever the state applies a head rather than polling.
     pub async fn wait_synced(&self) {
-        let mut interval = interval(Duration::from_millis(100));
+        let mut progress = self.node.execution.sync_progress().subscribe();
         loop {
-            interval.tick().await;
-            if let Ok(SyncStatus::None) = self.syncing().await {
-                break;
+            if let Ok(SyncState::Synced) = self.syncing().await {
+                return;
+            }
+            // the state outlives the node, so the tracker is never dropped while waiting
+            if progress.changed().await.is_err() {
+                return;
             }
         }
     }
 }
+
+// fails to compile if the client stops being shareable between threads, or a lock held
+// across an await makes the futures of its methods !Send
+#[cfg(not(target_arch = "wasm32"))]
+#[allow(dead_code)]
+fn assert_thread_safe<N: NetworkSpec, C: Consensus<N::BlockResponse>>(
+    handle: ClientHandle<N, C>,
+    tx: N::TransactionRequest,
+    filter: Filter,
+) {
+    fn is_send_sync<T: Send + Sync>() {}
+    fn is_send<T: Send>(_: T) {}
+    is_send_sync::<Client<N, C>>();
+    is_send_sync::<ClientHandle<N, C>>();
+    is_send(handle.get_balance(Address::ZERO, BlockTag::Latest));
+    is_send(handle.call(&tx, BlockTag::Latest));
+    is_send(handle.get_logs(&filter));
+}
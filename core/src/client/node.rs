@@ -1,60 +1,216 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use alloy::consensus::BlockHeader;
-use alloy::network::BlockResponse;
-use alloy::primitives::{Address, Bytes, B256, U256};
-use alloy::rpc::types::{Filter, FilterChanges, Log, SyncInfo, SyncStatus};
+use alloy::eips::BlockId;
+use alloy::network::{primitives::HeaderResponse, BlockResponse, TransactionResponse};
+use alloy::primitives::{Address, Bytes, B256, U256, U64};
+use alloy::rpc::types::{
+    AccessListResult, BlockTransactions, EIP1186AccountProofResponse, Filter, FilterChanges, Log,
+};
 use eyre::{eyre, Result};
-
+#[cfg(not(target_arch = "wasm32"))]
+use futures::future::{BoxFuture, FutureExt};
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::{mpsc, watch, Mutex};
+use tracing::warn;
+
+use crate::client::ens::{EnsError, EnsResolver};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::supervisor::{Heartbeat, Supervisor, SupervisorConfig};
 use crate::consensus::Consensus;
 use crate::errors::ClientError;
+use crate::execution::asset_changes::AssetChangeReport;
+use crate::execution::bundle::{BundleOverrides, CallOutcome, SimulatedTransaction, StateOverride};
+use crate::execution::ccip::CcipGateway;
+use crate::execution::code_cache::CodeCache;
+use crate::execution::config::ExecutionConfig;
 use crate::execution::constants::MAX_STATE_HISTORY_LENGTH;
+use crate::execution::errors::{EvmError, ExecutionError};
 use crate::execution::evm::Evm;
+use crate::execution::fee_history::FeeHistoryResponse;
+use crate::execution::log_pages::LogPage;
+use crate::execution::rpc::headers::HeaderConfig;
 use crate::execution::rpc::http_rpc::HttpRpc;
+use crate::execution::rpc::rate_limit::RateLimitConfig;
+use crate::execution::rpc::retry::RetryConfig;
+use crate::execution::rpc::timeout::HttpClientConfig;
+use crate::execution::rpc::ExecutionRpc;
 use crate::execution::state::State;
+use crate::execution::trace::{TraceOptions, TraceResult};
+use crate::execution::types::StateAnchor;
+use crate::execution::usage::UsageReport;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::execution::usage::{attribute, RequestCategory};
 use crate::execution::ExecutionClient;
 use crate::fork_schedule::ForkSchedule;
 use crate::network_spec::NetworkSpec;
-use crate::time::{SystemTime, UNIX_EPOCH};
-use crate::types::BlockTag;
+use crate::time::{timeout, SystemTime, UNIX_EPOCH};
+use crate::types::{
+    AnchoredCall, BeaconBlockMapping, BlockTag, DbStats, ExecutionProof, FilterParam,
+    FinalityEstimate, FinalityTiming, HeadFeed, HealthReport, NetworkInfo, PrevRandao,
+    PrevRandaoRange, ReorgEvent, SyncCommitteeInfo, SyncState, SyncingInfo, VersionInfo,
+};
+
+/// How long a `latest` read trailing the verified head waits for the state to apply it.
+const HEAD_CATCH_UP_TIMEOUT: Duration = Duration::from_millis(500);
 
 pub struct Node<N: NetworkSpec, C: Consensus<N::BlockResponse>> {
     pub consensus: C,
     pub execution: Arc<ExecutionClient<N, HttpRpc<N>>>,
     fork_schedule: ForkSchedule,
+    skip_transaction_checks: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    supervisor: Supervisor,
 }
 
 impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
     pub fn new(
         execution_rpc: &str,
-        mut consensus: C,
+        consensus: C,
         fork_schedule: ForkSchedule,
+        config: ExecutionConfig,
     ) -> Result<Self, ClientError> {
+        let execution_rpc = <HttpRpc<N> as ExecutionRpc<N>>::new(execution_rpc)
+            .map_err(ClientError::InternalError)?;
+        Ok(Self::with_execution_rpc(
+            execution_rpc,
+            consensus,
+            fork_schedule,
+            config,
+        ))
+    }
+
+    /// Creates a node using an already constructed execution rpc, sharing its provider with
+    /// any other clones of it.
+    pub fn with_execution_rpc(
+        execution_rpc: HttpRpc<N>,
+        consensus: C,
+        fork_schedule: ForkSchedule,
+        config: ExecutionConfig,
+    ) -> Self {
+        Self::with_supervisor_config(
+            execution_rpc,
+            consensus,
+            fork_schedule,
+            config,
+            #[cfg(not(target_arch = "wasm32"))]
+            SupervisorConfig::default(),
+        )
+    }
+
+    /// Creates a node whose long running tasks are restarted according to `supervisor`.
+    pub fn with_supervisor_config(
+        execution_rpc: HttpRpc<N>,
+        mut consensus: C,
+        fork_schedule: ForkSchedule,
+        config: ExecutionConfig,
+        #[cfg(not(target_arch = "wasm32"))] supervisor: SupervisorConfig,
+    ) -> Self {
         let block_recv = consensus.block_recv().unwrap();
         let finalized_block_recv = consensus.finalized_block_recv().unwrap();
 
-        let state = State::new(
-            block_recv,
-            finalized_block_recv,
-            MAX_STATE_HISTORY_LENGTH,
-            execution_rpc,
+        #[cfg(not(target_arch = "wasm32"))]
+        let supervisor = Supervisor::new(supervisor);
+        #[cfg(not(target_arch = "wasm32"))]
+        consensus.supervise(&supervisor);
+        // configured before the sync takes its clone, which keeps the depth set now
+        let state = State::unsynced(MAX_STATE_HISTORY_LENGTH, execution_rpc.clone())
+            .with_confirmation_depth(config.confirmation_depth)
+            .with_orphan_cache(config.orphan_cache)
+            .with_backfill(config.backfill)
+            .with_receipt_cache(config.logs.receipt_cache_blocks);
+        // stored mappings only answer for blocks seen before a restart, so the client runs
+        // without them if the database fails, and the sync must not hold the state yet
+        if let Some(db) = &config.database {
+            if let Err(err) = state.persist_beacon_mappings(db.clone()) {
+                warn!(target: "helios::execution", "beacon mappings not stored: {}", err);
+            }
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        supervisor.supervise(
+            "sync",
+            sync_task(state.clone(), block_recv, finalized_block_recv),
         );
+        #[cfg(target_arch = "wasm32")]
+        let state = state.follow(block_recv, finalized_block_recv);
+
+        if let Some(beacon_mapping_recv) = consensus.beacon_mapping_recv() {
+            state.track_beacon_mappings(beacon_mapping_recv);
+        }
+        if let Some(safe_block_recv) = consensus.safe_block_recv() {
+            state.track_safe_blocks(safe_block_recv);
+        }
+        if let Some(head_feed_recv) = consensus.head_feed_recv() {
+            state.track_head_feed(head_feed_recv);
+        }
+
+        // stored headers and code only save refetches, so the client runs without them if
+        // the database fails
+        if let Some(db) = config.header_store.as_ref().or(config.database.as_ref()) {
+            if let Err(err) = state.persist_historical_headers(db.clone()) {
+                warn!(target: "helios::execution", "verified headers not stored: {}", err);
+            }
+        }
+        let code_cache = match config.database {
+            Some(db) if config.code_cache.dir.is_none() => {
+                CodeCache::with_database(db, config.code_cache.max_size.as_bytes())
+            }
+            _ => CodeCache::open(&config.code_cache),
+        }
+        .unwrap_or_else(|err| {
+            warn!(target: "helios::execution", "code cache disabled: {}", err);
+            CodeCache::disabled()
+        });
+
+        // an endpoint failing to parse only loses its copy of broadcast transactions, and
+        // one rejecting it is not asked again, since a resent transaction can only repeat
+        // its rejection or confuse the nonce tracking of the endpoint
+        let broadcast_rpcs = config
+            .broadcast_rpcs
+            .iter()
+            .filter_map(|url| match broadcast_rpc(url, &config.broadcast_rate_limit) {
+                Ok(rpc) => Some(rpc),
+                Err(err) => {
+                    warn!(target: "helios::execution", "broadcast rpc {} skipped: {}", url, err);
+                    None
+                }
+            })
+            .collect();
+
+        let ccip_read = config
+            .ccip_read
+            .then(|| CcipGateway::new(config.ccip_read_timeout));
+
         let execution = Arc::new(
-            ExecutionClient::new(execution_rpc, state, fork_schedule)
-                .map_err(ClientError::InternalError)?,
+            ExecutionClient::with_rpc(execution_rpc, state, fork_schedule)
+                .with_broadcast_rpcs(broadcast_rpcs)
+                .with_ccip_read(ccip_read)
+                .with_premerge_passthrough(config.premerge_passthrough)
+                .with_log_verification(config.logs)
+                .with_fee_history(config.fee_history)
+                .with_historical_state(config.historical_state)
+                .with_gas_price(config.gas_price)
+                .with_evm_config(config.evm)
+                .with_payload_limits(config.payload_limits)
+                .with_code_cache(code_cache),
         );
 
-        Ok(Node {
+        Node {
             consensus,
             execution,
             fork_schedule,
-        })
+            skip_transaction_checks: config.skip_transaction_checks,
+            #[cfg(not(target_arch = "wasm32"))]
+            supervisor,
+        }
     }
 
     pub async fn call(
         &self,
         tx: &N::TransactionRequest,
         block: BlockTag,
+        overrides: StateOverride,
     ) -> Result<Bytes, ClientError> {
         self.check_blocktag_age(&block).await?;
 
@@ -63,11 +219,141 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
             self.chain_id(),
             self.fork_schedule,
             block,
-        );
+        )
+        .with_state_overrides(overrides);
         evm.call(tx).await.map_err(ClientError::EvmError)
     }
 
-    pub async fn estimate_gas(&self, tx: &N::TransactionRequest) -> Result<u64, ClientError> {
+    pub async fn trace_call(
+        &self,
+        tx: &N::TransactionRequest,
+        block: BlockTag,
+        options: &TraceOptions,
+    ) -> Result<TraceResult, ClientError> {
+        self.check_blocktag_age(&block).await?;
+
+        let mut evm = Evm::new(
+            self.execution.clone(),
+            self.chain_id(),
+            self.fork_schedule,
+            block,
+        );
+        evm.trace_call(tx, options)
+            .await
+            .map_err(ClientError::EvmError)
+    }
+
+    pub async fn create_access_list(
+        &self,
+        tx: &N::TransactionRequest,
+        block: BlockTag,
+    ) -> Result<AccessListResult, ClientError> {
+        self.check_blocktag_age(&block).await?;
+
+        let mut evm = Evm::new(
+            self.execution.clone(),
+            self.chain_id(),
+            self.fork_schedule,
+            block,
+        );
+        evm.create_access_list(tx)
+            .await
+            .map_err(ClientError::EvmError)
+    }
+
+    /// Runs a call against the state of a header the caller vouches for, such as one taken
+    /// from an archive. The header is only checked to hash to its own `hash` field, so the
+    /// result is labelled as anchored to user supplied trust, unless the header is that of a
+    /// block verified through consensus. State is still proven against its state root, which
+    /// needs an archive execution provider for old blocks, and `BLOCKHASH` only resolves the
+    /// parent of a header trusted through the caller alone.
+    pub async fn call_with_header(
+        &self,
+        tx: &N::TransactionRequest,
+        header: N::HeaderResponse,
+    ) -> Result<AnchoredCall, ClientError> {
+        let anchor = self
+            .execution
+            .verify_supplied_header(header)
+            .await
+            .map_err(|err| ClientError::EvmError(EvmError::RpcError(err)))?;
+        let (header, trust) = anchor
+            .verified()
+            .ok_or_else(|| ClientError::InternalError(eyre!("supplied header not verified")))?;
+
+        let block_number = header.number();
+        let block_hash = header.hash();
+        let state_root = header.state_root();
+
+        let mut evm = Evm::with_anchor(
+            self.execution.clone(),
+            self.chain_id(),
+            self.fork_schedule,
+            anchor,
+        );
+        let output = evm.call(tx).await.map_err(ClientError::EvmError)?;
+
+        Ok(AnchoredCall {
+            output,
+            block_number,
+            block_hash,
+            state_root,
+            trust,
+        })
+    }
+
+    pub async fn estimate_gas(
+        &self,
+        tx: &N::TransactionRequest,
+        block: BlockTag,
+        overrides: StateOverride,
+    ) -> Result<u64, ClientError> {
+        self.check_blocktag_age(&block).await?;
+
+        let mut evm = Evm::new(
+            self.execution.clone(),
+            self.chain_id(),
+            self.fork_schedule,
+            block,
+        )
+        .with_state_overrides(overrides);
+
+        evm.estimate_gas(tx).await.map_err(ClientError::EvmError)
+    }
+
+    /// Resolves an ENS name through verified calls to the registry and the resolver.
+    pub async fn resolve_ens(&self, name: &str) -> Result<Option<Address>, ClientError> {
+        self.ens_resolver().await?.resolve(name).await
+    }
+
+    /// The primary ENS name of `address`, if its reverse record resolves back to it.
+    pub async fn lookup_ens(&self, address: Address) -> Result<Option<String>, ClientError> {
+        self.ens_resolver().await?.lookup(address).await
+    }
+
+    /// A resolver running its calls at the latest block as of now.
+    async fn ens_resolver(&self) -> Result<EnsResolver<N, HttpRpc<N>>, ClientError> {
+        let registry = self.consensus.ens_registry().ok_or(EnsError::NoRegistry)?;
+        self.check_head_age().await?;
+        let latest = self
+            .execution
+            .get_block(BlockTag::Latest, false)
+            .await
+            .ok_or(ClientError::BlockNotFound(BlockTag::Latest))?;
+
+        let evm = Evm::new(
+            self.execution.clone(),
+            self.chain_id(),
+            self.fork_schedule,
+            BlockTag::Number(latest.header().number()),
+        );
+        Ok(EnsResolver::new(evm, registry))
+    }
+
+    pub async fn simulate_with_asset_changes(
+        &self,
+        tx: &N::TransactionRequest,
+    ) -> Result<AssetChangeReport, ClientError> {
         self.check_head_age().await?;
 
         let mut evm = Evm::new(
@@ -77,7 +363,46 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
             BlockTag::Latest,
         );
 
-        evm.estimate_gas(tx).await.map_err(ClientError::EvmError)
+        evm.simulate_asset_changes(tx)
+            .await
+            .map_err(ClientError::EvmError)
+    }
+
+    pub async fn simulate_bundle(
+        &self,
+        txs: &[N::TransactionRequest],
+        tag: BlockTag,
+        overrides: &BundleOverrides,
+    ) -> Result<Vec<SimulatedTransaction>, ClientError> {
+        self.check_blocktag_age(&tag).await?;
+
+        let mut evm = Evm::new(
+            self.execution.clone(),
+            self.chain_id(),
+            self.fork_schedule,
+            tag,
+        );
+
+        evm.simulate_bundle(txs, overrides)
+            .await
+            .map_err(ClientError::EvmError)
+    }
+
+    pub async fn call_many(
+        &self,
+        txs: &[N::TransactionRequest],
+        tag: BlockTag,
+    ) -> Result<Vec<CallOutcome>, ClientError> {
+        self.check_blocktag_age(&tag).await?;
+
+        let mut evm = Evm::new(
+            self.execution.clone(),
+            self.chain_id(),
+            self.fork_schedule,
+            tag,
+        );
+
+        evm.call_many(txs).await.map_err(ClientError::EvmError)
     }
 
     pub async fn get_balance(&self, address: Address, tag: BlockTag) -> Result<U256> {
@@ -125,7 +450,39 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
         self.execution.get_storage_at(address, slot, tag).await
     }
 
+    /// The account and storage proofs of `address` at `tag`, verified against the state root
+    /// of the block before they are returned.
+    pub async fn get_proof(
+        &self,
+        address: Address,
+        slots: &[B256],
+        tag: BlockTag,
+    ) -> Result<EIP1186AccountProofResponse> {
+        self.check_blocktag_age(&tag).await?;
+
+        self.execution.get_proof(address, slots, tag).await
+    }
+
+    pub async fn get_execution_proof(
+        &self,
+        address: Address,
+        slots: &[B256],
+        tag: BlockTag,
+        include_code: bool,
+    ) -> Result<ExecutionProof> {
+        self.check_blocktag_age(&tag).await?;
+
+        self.execution
+            .get_execution_proof(address, slots, tag, include_code)
+            .await
+    }
+
     pub async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<B256> {
+        if !self.skip_transaction_checks {
+            self.execution
+                .check_raw_transaction(bytes, self.chain_id())
+                .await?;
+        }
         self.execution.send_raw_transaction(bytes).await
     }
 
@@ -136,6 +493,17 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
         self.execution.get_transaction_receipt(tx_hash).await
     }
 
+    pub async fn wait_for_transaction_receipt(
+        &self,
+        tx_hash: B256,
+        confirmations: u64,
+        wait: Duration,
+    ) -> Result<N::ReceiptResponse> {
+        self.execution
+            .wait_for_transaction_receipt(tx_hash, confirmations, wait)
+            .await
+    }
+
     pub async fn get_block_receipts(
         &self,
         block: BlockTag,
@@ -176,6 +544,63 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
         self.execution.get_logs(filter).await
     }
 
+    /// The filter of `filter`, with the ends given as `helios_confirmed` at the confirmed
+    /// block.
+    pub async fn resolve_filter(&self, filter: FilterParam) -> Result<Filter> {
+        if !filter.names_confirmed() {
+            return Ok(filter.filter);
+        }
+        let confirmed = self
+            .get_confirmed_block(false)
+            .await?
+            .ok_or(ExecutionError::BlockNotFound(BlockTag::Confirmed))?;
+        Ok(filter.resolve(confirmed.header().number()))
+    }
+
+    pub async fn get_logs_paged(
+        &self,
+        filter: &Filter,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<LogPage> {
+        self.execution
+            .get_logs_paged(filter, cursor, page_size)
+            .await
+    }
+
+    pub async fn get_prev_randao(&self, tag: BlockTag) -> Result<PrevRandao> {
+        self.check_blocktag_age(&tag).await?;
+
+        self.execution.get_prev_randao(tag).await
+    }
+
+    pub async fn get_prev_randao_range(&self, start: u64, end: u64) -> Result<PrevRandaoRange> {
+        self.execution.get_prev_randao_range(start, end).await
+    }
+
+    pub async fn get_beacon_header_by_execution_block(
+        &self,
+        block: BlockId,
+    ) -> Result<BeaconBlockMapping> {
+        self.execution.get_beacon_mapping_by_block(block).await
+    }
+
+    pub async fn get_execution_block_by_slot(&self, slot: u64) -> Result<BeaconBlockMapping> {
+        self.execution.get_beacon_mapping_by_slot(slot).await
+    }
+
+    pub async fn get_raw_header(&self, block: BlockId) -> Result<Bytes> {
+        self.execution.get_raw_header(block).await
+    }
+
+    pub async fn get_raw_block(&self, block: BlockId) -> Result<Bytes> {
+        self.execution.get_raw_block(block).await
+    }
+
+    pub async fn get_raw_receipts(&self, block: BlockId) -> Result<Vec<Bytes>> {
+        self.execution.get_raw_receipts(block).await
+    }
+
     pub async fn client_version(&self) -> String {
         let helios_version = std::env!("CARGO_PKG_VERSION");
         format!("helios-{}", helios_version)
@@ -205,28 +630,30 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
         self.execution.new_pending_transaction_filter().await
     }
 
-    // assumes tip of 1 gwei to prevent having to prove out every tx in the block
     pub async fn get_gas_price(&self) -> Result<U256> {
         self.check_head_age().await?;
-
-        let block = self
-            .execution
-            .get_block(BlockTag::Latest, false)
-            .await
-            .ok_or(eyre!(ClientError::BlockNotFound(BlockTag::Latest)))?;
-        let base_fee = block.header().base_fee_per_gas().unwrap_or(0_u64);
-        let tip = 10_u64.pow(9);
-        Ok(U256::from(base_fee + tip))
+        Ok(U256::from(self.execution.get_gas_price().await?))
     }
 
     pub async fn blob_base_fee(&self, block: BlockTag) -> Result<U256> {
-        Ok(self.execution.blob_base_fee(block).await)
+        self.execution.blob_base_fee(block).await
+    }
+
+    pub async fn get_fee_history(
+        &self,
+        block_count: u64,
+        newest: BlockTag,
+        reward_percentiles: &[f64],
+    ) -> Result<FeeHistoryResponse> {
+        self.check_blocktag_age(&newest).await?;
+        self.execution
+            .get_fee_history(block_count, newest, reward_percentiles)
+            .await
     }
 
-    // assumes tip of 1 gwei to prevent having to prove out every tx in the block
-    pub fn get_priority_fee(&self) -> Result<U256> {
-        let tip = U256::from(10_u64.pow(9));
-        Ok(tip)
+    pub async fn get_priority_fee(&self) -> Result<U256> {
+        self.check_head_age().await?;
+        Ok(U256::from(self.execution.get_priority_fee().await?))
     }
 
     pub async fn get_block_number(&self) -> Result<U256> {
@@ -247,8 +674,60 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
     ) -> Result<Option<N::BlockResponse>> {
         self.check_blocktag_age(&tag).await?;
 
-        let block = self.execution.get_block(tag, full_tx).await;
-        Ok(block)
+        if tag == BlockTag::Latest {
+            return self.get_latest_block(full_tx).await;
+        }
+        self.execution.get_block_by_number(tag, full_tx).await
+    }
+
+    /// The latest block of the state, checked against the head of the consensus client.
+    ///
+    /// The consensus client publishes each head it verifies before the state has applied
+    /// it, so for a moment the state's latest block can trail the head. A read in that
+    /// window waits once for the state to catch up, and otherwise answers with the verified
+    /// head itself.
+    async fn get_latest_block(&self, full_tx: bool) -> Result<Option<N::BlockResponse>> {
+        let mut state_heads = self.execution.head_updates();
+        let mut waited = false;
+
+        loop {
+            // the state only ever catches up to the head, so read the head first
+            let head = self.consensus.head();
+            let block = self
+                .execution
+                .get_block_by_number(BlockTag::Latest, full_tx)
+                .await?;
+            let Some(mut head) = head else {
+                return Ok(block);
+            };
+
+            let block_hash = block.as_ref().map(|block| block.header().hash());
+            if block_hash == Some(head.header().hash()) {
+                return Ok(block);
+            }
+            if !waited {
+                waited = true;
+                _ = timeout(HEAD_CATCH_UP_TIMEOUT, state_heads.changed()).await;
+                continue;
+            }
+
+            warn!(
+                target: "helios::client",
+                head = %head.header().hash(),
+                state = ?block_hash,
+                "latest block of the state disagrees with the verified head, serving the head"
+            );
+            if !full_tx {
+                *head.transactions_mut() =
+                    BlockTransactions::Hashes(head.transactions().hashes().collect());
+            }
+            return Ok(Some(head));
+        }
+    }
+
+    /// The block `confirmation_depth` blocks under the latest, see [`BlockTag::Confirmed`].
+    pub async fn get_confirmed_block(&self, full_tx: bool) -> Result<Option<N::BlockResponse>> {
+        self.get_block_by_number(BlockTag::Confirmed, full_tx).await
     }
 
     pub async fn get_block_by_hash(
@@ -256,28 +735,166 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
         hash: B256,
         full_tx: bool,
     ) -> Result<Option<N::BlockResponse>> {
-        let block = self.execution.get_block_by_hash(hash, full_tx).await;
-        Ok(block)
+        self.execution
+            .get_block_by_hash_or_pre_merge(hash, full_tx)
+            .await
     }
 
     pub fn chain_id(&self) -> u64 {
         self.consensus.chain_id()
     }
 
-    pub async fn syncing(&self) -> Result<SyncStatus> {
-        if self.check_head_age().await.is_ok() {
-            Ok(SyncStatus::None)
+    pub fn get_network_info(&self) -> NetworkInfo {
+        self.consensus.network_info()
+    }
+
+    pub fn version(&self) -> VersionInfo {
+        VersionInfo::new(&self.consensus.network_info())
+            .with_features(self.consensus.build_features())
+    }
+
+    /// The supervisor restarting the tasks of this node, which servers started on top of it
+    /// run under too.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn supervisor(&self) -> &Supervisor {
+        &self.supervisor
+    }
+
+    /// Waits until a supervised task keeps failing and is given up on, returning why. The
+    /// node no longer syncs, or serves what that task served, from then on.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn failed(&self) -> ClientError {
+        ClientError::InternalError(self.supervisor.failed().await)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn health(&self) -> HealthReport {
+        HealthReport {
+            clock_drift_ms: self.consensus.clock_drift(),
+            ..self.head_health(self.supervisor.health())
+        }
+    }
+
+    /// Tasks are not supervised on wasm, where a panic takes the whole client down.
+    #[cfg(target_arch = "wasm32")]
+    pub fn health(&self) -> HealthReport {
+        HealthReport {
+            clock_drift_ms: self.consensus.clock_drift(),
+            ..self.head_health(HealthReport::default())
+        }
+    }
+
+    fn head_health(&self, report: HealthReport) -> HealthReport {
+        let head = self.consensus.head();
+        HealthReport {
+            head_block_number: head.as_ref().map(|head| head.header().number()),
+            head_block_hash: head.as_ref().map(|head| head.header().hash()),
+            ..report
+        }
+    }
+
+    pub fn get_db_stats(&self) -> Result<Option<DbStats>, ClientError> {
+        self.consensus
+            .db_stats()
+            .map_err(ClientError::InternalError)
+    }
+
+    pub fn get_sync_committee(
+        &self,
+        period: u64,
+    ) -> Result<Option<SyncCommitteeInfo>, ClientError> {
+        self.consensus
+            .sync_committee(period)
+            .map_err(ClientError::InternalError)
+    }
+
+    pub fn get_sync_committee_periods(&self) -> Result<Vec<u64>, ClientError> {
+        self.consensus
+            .sync_committee_periods()
+            .map_err(ClientError::InternalError)
+    }
+
+    /// A block orphaned by a recent reorg, which [Node::get_block_by_hash] no longer finds.
+    pub fn get_orphaned_block(&self, hash: B256, full_tx: bool) -> Option<N::BlockResponse> {
+        self.execution.get_orphaned_block(hash, full_tx)
+    }
+
+    pub fn get_reorg_history(&self) -> Vec<ReorgEvent> {
+        self.execution.reorg_history()
+    }
+
+    /// When the block with `hash`, or the block including the transaction with `hash`, is
+    /// expected to be finalized. Only blocks still in the verified history are found, others
+    /// fail as not found.
+    pub async fn estimate_finality_time(&self, hash: B256) -> Result<FinalityEstimate> {
+        let block_hash = match self.execution.get_transaction(hash).await {
+            Some(tx) => tx.block_hash().unwrap_or(hash),
+            None => hash,
+        };
+        let block = self
+            .execution
+            .get_block_by_hash(block_hash, false)
+            .await
+            .ok_or(ExecutionError::BlockHashNotFound(block_hash))?;
+
+        let number = block.header().number();
+        let finalized = self
+            .execution
+            .get_block(BlockTag::Finalized, false)
+            .await
+            .is_some_and(|finalized| finalized.header().number() >= number);
+        let timing = if finalized {
+            FinalityTiming::finalized()
         } else {
-            let latest_synced_block = self.get_block_number().await.unwrap_or(U256::ZERO);
-            let highest_block = self.consensus.expected_highest_block();
-
-            Ok(SyncStatus::Info(Box::new(SyncInfo {
-                current_block: latest_synced_block,
-                highest_block: U256::from(highest_block),
-                starting_block: U256::ZERO,
-                ..Default::default()
-            })))
+            self.consensus
+                .finality_timing(block.header().timestamp())
+                .ok_or(eyre!(
+                    "finality estimates are not supported on this network"
+                ))?
+        };
+
+        Ok(FinalityEstimate {
+            block_number: number,
+            block_hash: block.header().hash(),
+            timing,
+        })
+    }
+
+    pub fn get_usage(&self) -> Option<UsageReport> {
+        self.execution.rpc.usage().map(|usage| usage.report())
+    }
+
+    /// Synced once the state applied every head delivered, the latest of them verified by
+    /// consensus, that head is recent, the blocks behind the first head are backfilled and
+    /// heads keep arriving.
+    pub async fn syncing(&self) -> Result<SyncState> {
+        let progress = self.execution.sync_progress().progress();
+        // consensus may have verified a head it is yet to deliver
+        let highest_block = self
+            .consensus
+            .head()
+            .map(|head| head.header().number())
+            .max(progress.highest_block);
+
+        let applied = progress.current_block.is_some()
+            && progress.current_block >= highest_block
+            && progress.backfill.is_none()
+            && progress.head_feed == HeadFeed::Live;
+        if applied && self.check_head_age().await.is_ok() {
+            return Ok(SyncState::Synced);
         }
+
+        Ok(SyncState::Syncing(Box::new(SyncingInfo {
+            starting_block: U64::from(progress.starting_block.unwrap_or_default()),
+            current_block: U64::from(progress.current_block.unwrap_or_default()),
+            highest_block: U64::from(highest_block.unwrap_or_default()),
+            latest_finalized_block: progress.finalized_block.map(U64::from),
+            seconds_since_last_update: progress
+                .updated_at
+                .map(|updated_at| updated_at.elapsed().as_secs()),
+            backfill: progress.backfill,
+            head_feed: (progress.head_feed != HeadFeed::Live).then_some(progress.head_feed),
+        })))
     }
 
     pub async fn get_coinbase(&self) -> Result<Address> {
@@ -293,6 +910,11 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
     }
 
     async fn check_head_age(&self) -> Result<(), ClientError> {
+        // the head is knowingly served at the safe block, which is not expected to be recent
+        if self.execution.sync_progress().progress().head_feed == HeadFeed::SafeOnly {
+            return Ok(());
+        }
+
         let timestamp = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_else(|_| panic!("unreachable"))
@@ -314,11 +936,82 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Node<N, C> {
         Ok(())
     }
 
-    async fn check_blocktag_age(&self, block: &BlockTag) -> Result<(), ClientError> {
+    /// Fails reads at a tag following the head while the head is too old to be trusted as
+    /// the latest block.
+    pub async fn check_blocktag_age(&self, block: &BlockTag) -> Result<(), ClientError> {
         match block {
-            BlockTag::Latest => self.check_head_age().await,
-            BlockTag::Finalized => Ok(()),
+            // the confirmed block trails the head, so it is as stale as the head is
+            BlockTag::Latest | BlockTag::Pending | BlockTag::Confirmed => {
+                self.check_head_age().await
+            }
+            BlockTag::Finalized | BlockTag::Safe => Ok(()),
             BlockTag::Number(_) => Ok(()),
         }
     }
 }
+
+/// Applies the blocks delivered by the consensus client to `state`. Each run picks up the
+/// channels where the previous one left them, so a restart loses no blocks.
+///
+/// Only applying a block or a finality update counts as a beat, so a consensus client that
+/// stops delivering blocks, as much as an update that never returns, shows up as a stuck
+/// sync.
+#[cfg(not(target_arch = "wasm32"))]
+fn sync_task<N: NetworkSpec>(
+    state: State<N, HttpRpc<N>>,
+    block_recv: mpsc::Receiver<N::BlockResponse>,
+    finalized_block_recv: watch::Receiver<Option<N::BlockResponse>>,
+) -> impl Fn(Heartbeat) -> BoxFuture<'static, Result<()>> + Send + Sync + 'static {
+    let block_recv = Arc::new(Mutex::new(block_recv));
+    let finalized_block_recv = Arc::new(Mutex::new(finalized_block_recv));
+
+    move |heartbeat| {
+        let state = state.clone();
+        let block_recv = block_recv.clone();
+        let finalized_block_recv = finalized_block_recv.clone();
+
+        let sync = async move {
+            let mut block_recv = block_recv.lock().await;
+            let mut finalized_block_recv = finalized_block_recv.lock().await;
+
+            let closed = loop {
+                tokio::select! {
+                    block = block_recv.recv() => {
+                        let Some(block) = block else {
+                            break eyre!("consensus block channel closed");
+                        };
+                        state.push_block(block).await;
+                    }
+                    changed = finalized_block_recv.changed() => {
+                        if changed.is_err() {
+                            break eyre!("consensus finality channel closed");
+                        }
+                        let block = finalized_block_recv.borrow_and_update().clone();
+                        if let Some(block) = block {
+                            state.push_finalized_block(block).await;
+                        }
+                    }
+                }
+                heartbeat.beat();
+            };
+
+            // a closed channel stays closed, so no restart brings the streams more headers
+            state.close_header_streams();
+            Err(closed)
+        };
+        attribute(RequestCategory::Sync, sync).boxed()
+    }
+}
+
+/// Connects to a broadcast rpc like [`ExecutionRpc::new`] does, but never retrying a
+/// request and holding requests to a budget of `rate_limit` of its own.
+fn broadcast_rpc<N: NetworkSpec>(url: &str, rate_limit: &RateLimitConfig) -> Result<HttpRpc<N>> {
+    HttpRpc::connect(
+        url,
+        true,
+        RetryConfig::disabled(),
+        rate_limit,
+        HttpClientConfig::default(),
+        &HeaderConfig::default(),
+    )
+}
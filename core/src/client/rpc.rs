@@ -1,51 +1,243 @@
-use std::{fmt::Display, net::SocketAddr, sync::Arc};
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
+use alloy::eips::BlockId;
 use alloy::network::{BlockResponse, ReceiptResponse, TransactionResponse};
 use alloy::primitives::{Address, Bytes, B256, U256, U64};
 use alloy::rpc::json_rpc::RpcObject;
-use alloy::rpc::types::{Filter, FilterChanges, Log, SyncStatus};
+use alloy::rpc::types::{AccessListResult, EIP1186AccountProofResponse, FilterChanges, Log};
 use eyre::Result;
 use jsonrpsee::{
-    core::{async_trait, server::Methods},
+    core::{async_trait, server::Methods, SubscriptionResult},
     proc_macros::rpc,
-    server::{ServerBuilder, ServerHandle},
-    types::error::{ErrorObject, ErrorObjectOwned},
+    server::{RpcModule, ServerBuilder, ServerHandle},
+    types::error::{ErrorObject, ErrorObjectOwned, INTERNAL_ERROR_CODE},
+    PendingSubscriptionSink, SubscriptionMessage, SubscriptionSink,
 };
-use tracing::info;
+use serde::Serialize;
+use tokio::select;
+use tokio::time::timeout;
+use tower::ServiceBuilder;
+use tracing::{info, warn};
 
+use crate::client::compression::{self, CompressionConfig};
+use crate::client::ipc::{IpcConfig, IpcServer};
 use crate::client::node::Node;
+use crate::client::tls::{TlsConfig, TlsListener, TlsTerminator};
+use crate::client::trust::{Capabilities, Policy, RpcPolicies, TRUST_MATRIX};
 use crate::consensus::Consensus;
+use crate::errors::ServerError;
+use crate::execution::asset_changes::AssetChangeReport;
+use crate::execution::bundle::{BundleOverrides, CallOutcome, SimulatedTransaction, StateOverride};
+use crate::execution::constants::{
+    DEFAULT_RECEIPT_CONFIRMATIONS, DEFAULT_RECEIPT_TIMEOUT, MAX_SUBSCRIPTION_BACKFILL,
+};
+use crate::execution::errors::ExecutionError;
+use crate::execution::fee_history::FeeHistoryResponse;
+use crate::execution::log_pages::LogPage;
+use crate::execution::pins::RetentionReason;
+use crate::execution::rpc::http_rpc::HttpRpc;
+use crate::execution::subscription::{
+    SubscriptionCursor, SubscriptionEvent, SubscriptionItem, SubscriptionParams, SubscriptionTopic,
+};
+use crate::execution::trace::{TraceOptions, TraceResult};
+use crate::execution::usage::UsageReport;
+use crate::execution::ExecutionClient;
 use crate::network_spec::NetworkSpec;
-use crate::types::BlockTag;
+use crate::types::{
+    AnchoredCall, BeaconBlockMapping, BlockByHash, BlockTag, DbStats, ExecutionProof, FilterParam,
+    FinalityEstimate, HealthReport, NetworkInfo, NewestBlock, PrevRandao, PrevRandaoRange,
+    ReorgEvent, SyncCommitteeInfo, SyncState, VersionInfo,
+};
+
+/// Name of the task watching the http server under the supervisor of the node.
+const RPC_TASK: &str = "rpc";
 
 pub struct Rpc<N: NetworkSpec, C: Consensus<N::BlockResponse>> {
     node: Arc<Node<N, C>>,
-    handle: Option<ServerHandle>,
-    address: SocketAddr,
+    /// The http server, replaced should the supervisor bind its address again.
+    handle: Arc<Mutex<Option<ServerHandle>>>,
+    tls_listener: Option<TlsListener>,
+    ipc_server: Option<IpcServer>,
+    address: Option<SocketAddr>,
+    tls: Option<TlsConfig>,
+    ipc: Option<IpcConfig>,
+    extra_methods: Methods,
+    policies: RpcPolicies,
+    compression: CompressionConfig,
 }
 
 impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Rpc<N, C> {
     pub fn new(node: Arc<Node<N, C>>, address: SocketAddr) -> Self {
         Rpc {
             node,
-            handle: None,
-            address,
+            handle: Arc::default(),
+            tls_listener: None,
+            ipc_server: None,
+            address: Some(address),
+            tls: None,
+            ipc: None,
+            extra_methods: Methods::new(),
+            policies: RpcPolicies::default(),
+            compression: CompressionConfig::default(),
         }
     }
 
-    pub async fn start(&mut self) -> Result<SocketAddr> {
-        let rpc_inner = RpcInner {
+    /// A server reachable only over the ipc socket of `ipc`, without an http listener.
+    pub fn ipc_only(node: Arc<Node<N, C>>, ipc: IpcConfig) -> Self {
+        Rpc {
+            address: None,
+            ..Self::new(node, SocketAddr::from((Ipv4Addr::LOCALHOST, 0)))
+        }
+        .with_ipc(ipc)
+    }
+
+    /// Also serves the rpc over the ipc socket of `ipc`.
+    pub fn with_ipc(mut self, ipc: IpcConfig) -> Self {
+        self.ipc = Some(ipc);
+        self
+    }
+
+    /// Serves the rpc over TLS only, using the given certificate and key.
+    pub fn with_tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+
+    /// Serves `methods` alongside the built in namespaces, failing on name collisions.
+    pub fn with_methods(mut self, methods: impl Into<Methods>) -> Result<Self> {
+        self.extra_methods.merge(methods)?;
+        Ok(self)
+    }
+
+    /// Serves each method according to its verification policy, checked when the server
+    /// starts.
+    pub fn with_policies(mut self, policies: RpcPolicies) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    /// Compresses large responses for callers that accept it, unless `compression` disables
+    /// it.
+    pub fn with_compression(mut self, compression: CompressionConfig) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// The name of every method the server answers, as registered with it, including those
+    /// added through [`Rpc::with_methods`].
+    pub fn method_names(&self) -> Result<Vec<&'static str>> {
+        let methods = methods(self.inner(), self.extra_methods.clone())?;
+        Ok(methods.method_names().collect())
+    }
+
+    /// Starts the listeners, returning the http address unless the server is ipc only.
+    pub async fn start(&mut self) -> Result<Option<SocketAddr>> {
+        let methods = methods(self.inner(), self.extra_methods.clone())?;
+
+        if let Some(ipc) = &self.ipc {
+            self.ipc_server = Some(IpcServer::start(ipc, methods.clone()).await?);
+        }
+        let Some(address) = self.address else {
+            return Ok(None);
+        };
+
+        let res = self.start_http(address, methods).await;
+        if res.is_err() {
+            if let Some(ipc_server) = self.ipc_server.take() {
+                ipc_server.stop().await;
+            }
+        }
+        res.map(Some)
+    }
+
+    fn inner(&self) -> RpcInner<N, C> {
+        RpcInner {
             node: self.node.clone(),
-            address: self.address,
+            policies: Arc::new(self.policies.clone()),
+        }
+    }
+
+    async fn start_http(&mut self, address: SocketAddr, methods: Methods) -> Result<SocketAddr> {
+        let Some(tls) = self.tls.clone() else {
+            let (handle, addr) = serve_http(address, methods.clone(), &self.compression).await?;
+            *self.handle.lock().unwrap() = Some(handle);
+            self.supervise_http(addr, methods);
+
+            info!(target: "helios::rpc", "rpc server started at {}", addr);
+            return Ok(addr);
         };
 
-        let (handle, addr) = start(rpc_inner).await?;
-        self.handle = Some(handle);
+        // load the key material first so bad files fail before anything is listening
+        let terminator = TlsTerminator::new(tls)?;
 
-        info!(target: "helios::rpc", "rpc server started at {}", addr);
+        // the plaintext server is only reachable through the tls listener
+        let plaintext = SocketAddr::from((Ipv4Addr::LOCALHOST, 0));
+        let (handle, upstream) = serve_http(plaintext, methods.clone(), &self.compression).await?;
+        *self.handle.lock().unwrap() = Some(handle);
+        self.supervise_http(upstream, methods);
+
+        let listener = terminator.serve(address, upstream).await?;
+        let addr = listener.local_addr();
+        self.tls_listener = Some(listener);
+        info!(target: "helios::rpc", "rpc server started at {} (tls)", addr);
 
         Ok(addr)
     }
+
+    /// Stops the server, giving requests in flight until `deadline` to finish. The port is
+    /// released once it returns, unless requests were still running at the deadline, and
+    /// the server can be started again. The ipc socket file is removed.
+    pub async fn stop(&mut self, deadline: Duration) {
+        if let Some(ipc_server) = self.ipc_server.take() {
+            ipc_server.stop().await;
+        }
+        if let Some(listener) = self.tls_listener.take() {
+            listener.stop().await;
+        }
+        self.node.supervisor().stop(RPC_TASK);
+        let Some(handle) = self.handle.lock().unwrap().take() else {
+            return;
+        };
+
+        // a server whose task already ended is stopped
+        _ = handle.stop();
+        if timeout(deadline, handle.stopped()).await.is_err() {
+            warn!(target: "helios::rpc", ?deadline, "requests still in flight at the shutdown deadline, abandoning them");
+        } else {
+            info!(target: "helios::rpc", "rpc server stopped");
+        }
+    }
+}
+
+impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Rpc<N, C> {
+    /// Watches the http server under the supervisor of the node, binding `addr` again should
+    /// the server stop without being asked to.
+    fn supervise_http(&self, addr: SocketAddr, methods: Methods) {
+        let slot = self.handle.clone();
+        let compression = self.compression;
+        self.node.supervisor().supervise_service(RPC_TASK, move || {
+            let (slot, methods) = (slot.clone(), methods.clone());
+            async move {
+                let current = slot.lock().unwrap().clone();
+                let handle = match current {
+                    Some(handle) if !handle.is_stopped() => handle,
+                    _ => {
+                        let (handle, _) = serve_http(addr, methods, &compression).await?;
+                        info!(target: "helios::rpc", "rpc server restarted at {}", addr);
+                        *slot.lock().unwrap() = Some(handle.clone());
+                        handle
+                    }
+                };
+
+                handle.stopped().await;
+                eyre::bail!("rpc server stopped")
+            }
+        });
+    }
 }
 
 #[rpc(server, namespace = "eth")]
@@ -54,6 +246,7 @@ trait EthRpc<
     TXR: RpcObject,
     R: ReceiptResponse + RpcObject,
     B: BlockResponse + RpcObject,
+    H: RpcObject,
 >
 {
     #[method(name = "getBalance")]
@@ -81,9 +274,25 @@ trait EthRpc<
     #[method(name = "getCode")]
     async fn get_code(&self, address: Address, block: BlockTag) -> Result<Bytes, ErrorObjectOwned>;
     #[method(name = "call")]
-    async fn call(&self, tx: TXR, block: BlockTag) -> Result<Bytes, ErrorObjectOwned>;
+    async fn call(
+        &self,
+        tx: TXR,
+        block: BlockTag,
+        state_overrides: Option<StateOverride>,
+    ) -> Result<Bytes, ErrorObjectOwned>;
     #[method(name = "estimateGas")]
-    async fn estimate_gas(&self, tx: TXR) -> Result<U64, ErrorObjectOwned>;
+    async fn estimate_gas(
+        &self,
+        tx: TXR,
+        block: Option<BlockTag>,
+        state_overrides: Option<StateOverride>,
+    ) -> Result<U64, ErrorObjectOwned>;
+    #[method(name = "createAccessList")]
+    async fn create_access_list(
+        &self,
+        tx: TXR,
+        block: Option<BlockTag>,
+    ) -> Result<AccessListResult, ErrorObjectOwned>;
     #[method(name = "chainId")]
     async fn chain_id(&self) -> Result<U64, ErrorObjectOwned>;
     #[method(name = "gasPrice")]
@@ -92,6 +301,13 @@ trait EthRpc<
     async fn max_priority_fee_per_gas(&self) -> Result<U256, ErrorObjectOwned>;
     #[method(name = "blobBaseFee")]
     async fn blob_base_fee(&self, block: BlockTag) -> Result<U256, ErrorObjectOwned>;
+    #[method(name = "feeHistory")]
+    async fn fee_history(
+        &self,
+        block_count: U64,
+        newest_block: NewestBlock,
+        reward_percentiles: Option<Vec<f64>>,
+    ) -> Result<FeeHistoryResponse, ErrorObjectOwned>;
     #[method(name = "blockNumber")]
     async fn block_number(&self) -> Result<U64, ErrorObjectOwned>;
     #[method(name = "getBlockByNumber")]
@@ -105,7 +321,7 @@ trait EthRpc<
         &self,
         hash: B256,
         full_tx: bool,
-    ) -> Result<Option<B>, ErrorObjectOwned>;
+    ) -> Result<Option<BlockByHash<B>>, ErrorObjectOwned>;
     #[method(name = "sendRawTransaction")]
     async fn send_raw_transaction(&self, bytes: Bytes) -> Result<B256, ErrorObjectOwned>;
     #[method(name = "getTransactionReceipt")]
@@ -128,7 +344,7 @@ trait EthRpc<
         index: U64,
     ) -> Result<Option<TX>, ErrorObjectOwned>;
     #[method(name = "getLogs")]
-    async fn get_logs(&self, filter: Filter) -> Result<Vec<Log>, ErrorObjectOwned>;
+    async fn get_logs(&self, filter: FilterParam) -> Result<Vec<Log>, ErrorObjectOwned>;
     #[method(name = "getFilterChanges")]
     async fn get_filter_changes(&self, filter_id: U256) -> Result<FilterChanges, ErrorObjectOwned>;
     #[method(name = "getFilterLogs")]
@@ -136,7 +352,7 @@ trait EthRpc<
     #[method(name = "uninstallFilter")]
     async fn uninstall_filter(&self, filter_id: U256) -> Result<bool, ErrorObjectOwned>;
     #[method(name = "newFilter")]
-    async fn new_filter(&self, filter: Filter) -> Result<U256, ErrorObjectOwned>;
+    async fn new_filter(&self, filter: FilterParam) -> Result<U256, ErrorObjectOwned>;
     #[method(name = "newBlockFilter")]
     async fn new_block_filter(&self) -> Result<U256, ErrorObjectOwned>;
     #[method(name = "newPendingTransactionFilter")]
@@ -148,10 +364,23 @@ trait EthRpc<
         slot: U256,
         block: BlockTag,
     ) -> Result<B256, ErrorObjectOwned>;
+    #[method(name = "getProof")]
+    async fn get_proof(
+        &self,
+        address: Address,
+        slots: Vec<U256>,
+        block: BlockTag,
+    ) -> Result<EIP1186AccountProofResponse, ErrorObjectOwned>;
     #[method(name = "coinbase")]
     async fn coinbase(&self) -> Result<Address, ErrorObjectOwned>;
     #[method(name = "syncing")]
-    async fn syncing(&self) -> Result<SyncStatus, ErrorObjectOwned>;
+    async fn syncing(&self) -> Result<SyncState, ErrorObjectOwned>;
+    #[subscription(
+        name = "subscribe" => "subscription",
+        unsubscribe = "unsubscribe",
+        item = SubscriptionItem<H>
+    )]
+    async fn subscribe(&self, kind: String, filter: Option<FilterParam>) -> SubscriptionResult;
 }
 
 #[rpc(client, server, namespace = "net")]
@@ -166,16 +395,135 @@ trait Web3Rpc {
     async fn client_version(&self) -> Result<String, ErrorObjectOwned>;
 }
 
+#[rpc(server, namespace = "debug")]
+trait DebugRpc<TXR: RpcObject> {
+    #[method(name = "getRawHeader")]
+    async fn get_raw_header(&self, block: BlockId) -> Result<Bytes, ErrorObjectOwned>;
+    #[method(name = "getRawBlock")]
+    async fn get_raw_block(&self, block: BlockId) -> Result<Bytes, ErrorObjectOwned>;
+    #[method(name = "getRawReceipts")]
+    async fn get_raw_receipts(&self, block: BlockId) -> Result<Vec<Bytes>, ErrorObjectOwned>;
+    #[method(name = "traceCall")]
+    async fn trace_call(
+        &self,
+        tx: TXR,
+        block: BlockTag,
+        options: Option<TraceOptions>,
+    ) -> Result<TraceResult, ErrorObjectOwned>;
+}
+
+#[rpc(server, namespace = "helios")]
+trait HeliosRpc<TXR: RpcObject, H: RpcObject, B: RpcObject, R: RpcObject> {
+    #[method(name = "getNetworkInfo")]
+    async fn get_network_info(&self) -> Result<NetworkInfo, ErrorObjectOwned>;
+    #[method(name = "version")]
+    async fn version(&self) -> Result<VersionInfo, ErrorObjectOwned>;
+    #[method(name = "health")]
+    async fn health(&self) -> Result<HealthReport, ErrorObjectOwned>;
+    #[method(name = "dbStats")]
+    async fn db_stats(&self) -> Result<Option<DbStats>, ErrorObjectOwned>;
+    #[method(name = "getSyncCommittee")]
+    async fn get_sync_committee(
+        &self,
+        period: U64,
+    ) -> Result<Option<SyncCommitteeInfo>, ErrorObjectOwned>;
+    #[method(name = "getSyncCommitteePeriods")]
+    async fn get_sync_committee_periods(&self) -> Result<Vec<u64>, ErrorObjectOwned>;
+    #[method(name = "capabilities")]
+    async fn capabilities(&self) -> Result<Capabilities, ErrorObjectOwned>;
+    #[method(name = "getUsage")]
+    async fn get_usage(&self) -> Result<Option<UsageReport>, ErrorObjectOwned>;
+    #[method(name = "getReorgHistory")]
+    async fn get_reorg_history(&self) -> Result<Vec<ReorgEvent>, ErrorObjectOwned>;
+    #[method(name = "estimateFinalityTime")]
+    async fn estimate_finality_time(
+        &self,
+        hash: B256,
+    ) -> Result<FinalityEstimate, ErrorObjectOwned>;
+    #[method(name = "getConfirmedBlock")]
+    async fn get_confirmed_block(&self, full_tx: bool) -> Result<Option<B>, ErrorObjectOwned>;
+    #[method(name = "getPrevRandao")]
+    async fn get_prev_randao(&self, block: BlockTag) -> Result<PrevRandao, ErrorObjectOwned>;
+    #[method(name = "getPrevRandaoRange")]
+    async fn get_prev_randao_range(
+        &self,
+        start: U64,
+        end: U64,
+    ) -> Result<PrevRandaoRange, ErrorObjectOwned>;
+    #[method(name = "getBeaconHeaderByExecutionBlock")]
+    async fn get_beacon_header_by_execution_block(
+        &self,
+        block: BlockId,
+    ) -> Result<BeaconBlockMapping, ErrorObjectOwned>;
+    #[method(name = "getExecutionBlockBySlot")]
+    async fn get_execution_block_by_slot(
+        &self,
+        slot: U64,
+    ) -> Result<BeaconBlockMapping, ErrorObjectOwned>;
+    #[method(name = "getExecutionProof")]
+    async fn get_execution_proof(
+        &self,
+        address: Address,
+        slots: Vec<U256>,
+        block: BlockTag,
+        include_code: Option<bool>,
+    ) -> Result<ExecutionProof, ErrorObjectOwned>;
+    #[method(name = "getLogsPaged")]
+    async fn get_logs_paged(
+        &self,
+        filter: FilterParam,
+        cursor: Option<String>,
+        page_size: U64,
+    ) -> Result<LogPage, ErrorObjectOwned>;
+    #[method(name = "simulateWithAssetChanges")]
+    async fn simulate_with_asset_changes(
+        &self,
+        tx: TXR,
+    ) -> Result<AssetChangeReport, ErrorObjectOwned>;
+    #[method(name = "simulateBundle")]
+    async fn simulate_bundle(
+        &self,
+        txs: Vec<TXR>,
+        block: BlockTag,
+        overrides: Option<BundleOverrides>,
+    ) -> Result<Vec<SimulatedTransaction>, ErrorObjectOwned>;
+    #[method(name = "callMany")]
+    async fn call_many(
+        &self,
+        txs: Vec<TXR>,
+        block: BlockTag,
+    ) -> Result<Vec<CallOutcome>, ErrorObjectOwned>;
+    #[method(name = "callWithHeader")]
+    async fn call_with_header(&self, tx: TXR, header: H) -> Result<AnchoredCall, ErrorObjectOwned>;
+    #[method(name = "waitForTransactionReceipt")]
+    async fn wait_for_transaction_receipt(
+        &self,
+        hash: B256,
+        confirmations: Option<u64>,
+        timeout: Option<u64>,
+    ) -> Result<R, ErrorObjectOwned>;
+    #[subscription(
+        name = "subscribe" => "subscription",
+        unsubscribe = "unsubscribe",
+        item = SubscriptionEvent<H>
+    )]
+    async fn subscribe(
+        &self,
+        kind: String,
+        params: Option<SubscriptionParams>,
+    ) -> SubscriptionResult;
+}
+
 struct RpcInner<N: NetworkSpec, C: Consensus<N::BlockResponse>> {
     node: Arc<Node<N, C>>,
-    address: SocketAddr,
+    policies: Arc<RpcPolicies>,
 }
 
 impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Clone for RpcInner<N, C> {
     fn clone(&self) -> Self {
         Self {
             node: self.node.clone(),
-            address: self.address,
+            policies: self.policies.clone(),
         }
     }
 }
@@ -187,6 +535,7 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>>
         N::TransactionRequest,
         N::ReceiptResponse,
         N::BlockResponse,
+        N::HeaderResponse,
     > for RpcInner<N, C>
 {
     async fn get_balance(
@@ -237,16 +586,38 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>>
         &self,
         tx: N::TransactionRequest,
         block: BlockTag,
+        state_overrides: Option<StateOverride>,
     ) -> Result<Bytes, ErrorObjectOwned> {
-        convert_err(self.node.call(&tx, block).await)
+        let overrides = state_overrides.unwrap_or_default();
+        convert_err(self.node.call(&tx, block, overrides).await)
     }
 
-    async fn estimate_gas(&self, tx: N::TransactionRequest) -> Result<U64, ErrorObjectOwned> {
-        let res = self.node.estimate_gas(&tx).await.map(U64::from);
+    async fn estimate_gas(
+        &self,
+        tx: N::TransactionRequest,
+        block: Option<BlockTag>,
+        state_overrides: Option<StateOverride>,
+    ) -> Result<U64, ErrorObjectOwned> {
+        let block = block.unwrap_or(BlockTag::Latest);
+        let overrides = state_overrides.unwrap_or_default();
+        let res = self
+            .node
+            .estimate_gas(&tx, block, overrides)
+            .await
+            .map(U64::from);
 
         convert_err(res)
     }
 
+    async fn create_access_list(
+        &self,
+        tx: N::TransactionRequest,
+        block: Option<BlockTag>,
+    ) -> Result<AccessListResult, ErrorObjectOwned> {
+        let block = block.unwrap_or(BlockTag::Latest);
+        convert_err(self.node.create_access_list(&tx, block).await)
+    }
+
     async fn chain_id(&self) -> Result<U64, ErrorObjectOwned> {
         Ok(U64::from(self.node.chain_id()))
     }
@@ -256,13 +627,27 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>>
     }
 
     async fn max_priority_fee_per_gas(&self) -> Result<U256, ErrorObjectOwned> {
-        convert_err(self.node.get_priority_fee())
+        convert_err(self.node.get_priority_fee().await)
     }
 
     async fn blob_base_fee(&self, block: BlockTag) -> Result<U256, ErrorObjectOwned> {
         convert_err(self.node.blob_base_fee(block).await)
     }
 
+    async fn fee_history(
+        &self,
+        block_count: U64,
+        newest_block: NewestBlock,
+        reward_percentiles: Option<Vec<f64>>,
+    ) -> Result<FeeHistoryResponse, ErrorObjectOwned> {
+        let reward_percentiles = reward_percentiles.unwrap_or_default();
+        convert_err(
+            self.node
+                .get_fee_history(block_count.to(), newest_block.0, &reward_percentiles)
+                .await,
+        )
+    }
+
     async fn block_number(&self) -> Result<U64, ErrorObjectOwned> {
         convert_err(self.node.get_block_number().await).map(U64::from)
     }
@@ -279,8 +664,22 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>>
         &self,
         hash: B256,
         full_tx: bool,
-    ) -> Result<Option<N::BlockResponse>, ErrorObjectOwned> {
-        convert_err(self.node.get_block_by_hash(hash, full_tx).await)
+    ) -> Result<Option<BlockByHash<N::BlockResponse>>, ErrorObjectOwned> {
+        let block = convert_err(self.node.get_block_by_hash(hash, full_tx).await)?;
+        let block = match block {
+            Some(block) => Some(BlockByHash {
+                block,
+                orphaned: false,
+            }),
+            None => self
+                .node
+                .get_orphaned_block(hash, full_tx)
+                .map(|block| BlockByHash {
+                    block,
+                    orphaned: true,
+                }),
+        };
+        Ok(block)
     }
 
     async fn send_raw_transaction(&self, bytes: Bytes) -> Result<B256, ErrorObjectOwned> {
@@ -335,11 +734,12 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>>
         convert_err(self.node.get_coinbase().await)
     }
 
-    async fn syncing(&self) -> Result<SyncStatus, ErrorObjectOwned> {
+    async fn syncing(&self) -> Result<SyncState, ErrorObjectOwned> {
         convert_err(self.node.syncing().await)
     }
 
-    async fn get_logs(&self, filter: Filter) -> Result<Vec<Log>, ErrorObjectOwned> {
+    async fn get_logs(&self, filter: FilterParam) -> Result<Vec<Log>, ErrorObjectOwned> {
+        let filter = convert_err(self.node.resolve_filter(filter).await)?;
         convert_err(self.node.get_logs(&filter).await)
     }
 
@@ -355,7 +755,8 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>>
         convert_err(self.node.uninstall_filter(filter_id).await)
     }
 
-    async fn new_filter(&self, filter: Filter) -> Result<U256, ErrorObjectOwned> {
+    async fn new_filter(&self, filter: FilterParam) -> Result<U256, ErrorObjectOwned> {
+        let filter = convert_err(self.node.resolve_filter(filter).await)?;
         convert_err(self.node.new_filter(&filter).await)
     }
 
@@ -375,6 +776,40 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>>
     ) -> Result<B256, ErrorObjectOwned> {
         convert_err(self.node.get_storage_at(address, slot, block).await)
     }
+
+    async fn get_proof(
+        &self,
+        address: Address,
+        slots: Vec<U256>,
+        block: BlockTag,
+    ) -> Result<EIP1186AccountProofResponse, ErrorObjectOwned> {
+        let slots = slots.into_iter().map(B256::from).collect::<Vec<_>>();
+        convert_err(self.node.get_proof(address, &slots, block).await)
+    }
+
+    async fn subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        kind: String,
+        filter: Option<FilterParam>,
+    ) -> SubscriptionResult {
+        // block ranges are ignored, every subscription starts at the verified head
+        let topic = match kind.as_str() {
+            "newHeads" => SubscriptionTopic::NewHeads,
+            "logs" => {
+                SubscriptionTopic::Logs(filter.map(|filter| filter.filter).unwrap_or_default())
+            }
+            kind => {
+                let msg = format!("unsupported subscription: {kind}");
+                pending.reject(subscription_err(&msg)).await;
+                return Ok(());
+            }
+        };
+
+        let sink = pending.accept().await?;
+        let execution = self.node.execution.clone();
+        deliver_events(execution, sink, topic, None, SubscriptionEvent::into_item).await
+    }
 }
 
 #[async_trait]
@@ -391,26 +826,469 @@ impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> Web3RpcServer for RpcInner<
     }
 }
 
-async fn start<N: NetworkSpec, C: Consensus<N::BlockResponse>>(
-    rpc: RpcInner<N, C>,
+#[async_trait]
+impl<N: NetworkSpec, C: Consensus<N::BlockResponse>> DebugRpcServer<N::TransactionRequest>
+    for RpcInner<N, C>
+{
+    async fn get_raw_header(&self, block: BlockId) -> Result<Bytes, ErrorObjectOwned> {
+        convert_err(self.node.get_raw_header(block).await)
+    }
+
+    async fn get_raw_block(&self, block: BlockId) -> Result<Bytes, ErrorObjectOwned> {
+        convert_err(self.node.get_raw_block(block).await)
+    }
+
+    async fn get_raw_receipts(&self, block: BlockId) -> Result<Vec<Bytes>, ErrorObjectOwned> {
+        convert_err(self.node.get_raw_receipts(block).await)
+    }
+
+    async fn trace_call(
+        &self,
+        tx: N::TransactionRequest,
+        block: BlockTag,
+        options: Option<TraceOptions>,
+    ) -> Result<TraceResult, ErrorObjectOwned> {
+        let options = options.unwrap_or_default();
+        convert_err(self.node.trace_call(&tx, block, &options).await)
+    }
+}
+
+#[async_trait]
+impl<N: NetworkSpec, C: Consensus<N::BlockResponse>>
+    HeliosRpcServer<N::TransactionRequest, N::HeaderResponse, N::BlockResponse, N::ReceiptResponse>
+    for RpcInner<N, C>
+{
+    async fn get_network_info(&self) -> Result<NetworkInfo, ErrorObjectOwned> {
+        Ok(self.node.get_network_info())
+    }
+
+    async fn version(&self) -> Result<VersionInfo, ErrorObjectOwned> {
+        Ok(self.node.version())
+    }
+
+    async fn health(&self) -> Result<HealthReport, ErrorObjectOwned> {
+        Ok(self.node.health())
+    }
+
+    async fn db_stats(&self) -> Result<Option<DbStats>, ErrorObjectOwned> {
+        convert_err(self.node.get_db_stats())
+    }
+
+    async fn get_sync_committee(
+        &self,
+        period: U64,
+    ) -> Result<Option<SyncCommitteeInfo>, ErrorObjectOwned> {
+        convert_err(self.node.get_sync_committee(period.to()))
+    }
+
+    async fn get_sync_committee_periods(&self) -> Result<Vec<u64>, ErrorObjectOwned> {
+        convert_err(self.node.get_sync_committee_periods())
+    }
+
+    async fn capabilities(&self) -> Result<Capabilities, ErrorObjectOwned> {
+        Ok(self.policies.capabilities())
+    }
+
+    async fn get_usage(&self) -> Result<Option<UsageReport>, ErrorObjectOwned> {
+        Ok(self.node.get_usage())
+    }
+
+    async fn get_reorg_history(&self) -> Result<Vec<ReorgEvent>, ErrorObjectOwned> {
+        Ok(self.node.get_reorg_history())
+    }
+
+    async fn estimate_finality_time(
+        &self,
+        hash: B256,
+    ) -> Result<FinalityEstimate, ErrorObjectOwned> {
+        convert_err(self.node.estimate_finality_time(hash).await)
+    }
+
+    async fn get_confirmed_block(
+        &self,
+        full_tx: bool,
+    ) -> Result<Option<N::BlockResponse>, ErrorObjectOwned> {
+        convert_err(self.node.get_confirmed_block(full_tx).await)
+    }
+
+    async fn get_prev_randao(&self, block: BlockTag) -> Result<PrevRandao, ErrorObjectOwned> {
+        convert_err(self.node.get_prev_randao(block).await)
+    }
+
+    async fn get_prev_randao_range(
+        &self,
+        start: U64,
+        end: U64,
+    ) -> Result<PrevRandaoRange, ErrorObjectOwned> {
+        convert_err(self.node.get_prev_randao_range(start.to(), end.to()).await)
+    }
+
+    async fn get_beacon_header_by_execution_block(
+        &self,
+        block: BlockId,
+    ) -> Result<BeaconBlockMapping, ErrorObjectOwned> {
+        convert_err(self.node.get_beacon_header_by_execution_block(block).await)
+    }
+
+    async fn get_execution_block_by_slot(
+        &self,
+        slot: U64,
+    ) -> Result<BeaconBlockMapping, ErrorObjectOwned> {
+        convert_err(self.node.get_execution_block_by_slot(slot.to()).await)
+    }
+
+    async fn get_execution_proof(
+        &self,
+        address: Address,
+        slots: Vec<U256>,
+        block: BlockTag,
+        include_code: Option<bool>,
+    ) -> Result<ExecutionProof, ErrorObjectOwned> {
+        let slots = slots.into_iter().map(B256::from).collect::<Vec<_>>();
+        let include_code = include_code.unwrap_or(false);
+        convert_err(
+            self.node
+                .get_execution_proof(address, &slots, block, include_code)
+                .await,
+        )
+    }
+
+    async fn get_logs_paged(
+        &self,
+        filter: FilterParam,
+        cursor: Option<String>,
+        page_size: U64,
+    ) -> Result<LogPage, ErrorObjectOwned> {
+        let page_size = page_size.saturating_to();
+        let filter = convert_err(self.node.resolve_filter(filter).await)?;
+        convert_err(
+            self.node
+                .get_logs_paged(&filter, cursor.as_deref(), page_size)
+                .await,
+        )
+    }
+
+    async fn simulate_with_asset_changes(
+        &self,
+        tx: N::TransactionRequest,
+    ) -> Result<AssetChangeReport, ErrorObjectOwned> {
+        convert_err(self.node.simulate_with_asset_changes(&tx).await)
+    }
+
+    async fn simulate_bundle(
+        &self,
+        txs: Vec<N::TransactionRequest>,
+        block: BlockTag,
+        overrides: Option<BundleOverrides>,
+    ) -> Result<Vec<SimulatedTransaction>, ErrorObjectOwned> {
+        let overrides = overrides.unwrap_or_default();
+        convert_err(self.node.simulate_bundle(&txs, block, &overrides).await)
+    }
+
+    async fn call_many(
+        &self,
+        txs: Vec<N::TransactionRequest>,
+        block: BlockTag,
+    ) -> Result<Vec<CallOutcome>, ErrorObjectOwned> {
+        convert_err(self.node.call_many(&txs, block).await)
+    }
+
+    async fn call_with_header(
+        &self,
+        tx: N::TransactionRequest,
+        header: N::HeaderResponse,
+    ) -> Result<AnchoredCall, ErrorObjectOwned> {
+        convert_err(self.node.call_with_header(&tx, header).await)
+    }
+
+    async fn wait_for_transaction_receipt(
+        &self,
+        hash: B256,
+        confirmations: Option<u64>,
+        timeout: Option<u64>,
+    ) -> Result<N::ReceiptResponse, ErrorObjectOwned> {
+        let confirmations = confirmations.unwrap_or(DEFAULT_RECEIPT_CONFIRMATIONS);
+        let timeout = timeout.map_or(DEFAULT_RECEIPT_TIMEOUT, Duration::from_secs);
+        convert_err(
+            self.node
+                .wait_for_transaction_receipt(hash, confirmations, timeout)
+                .await,
+        )
+    }
+
+    async fn subscribe(
+        &self,
+        pending: PendingSubscriptionSink,
+        kind: String,
+        params: Option<SubscriptionParams>,
+    ) -> SubscriptionResult {
+        let params = params.unwrap_or_default();
+        let topic = match (kind.as_str(), params.filter) {
+            ("newHeads", _) => SubscriptionTopic::NewHeads,
+            ("logs", Some(filter)) => SubscriptionTopic::Logs(filter),
+            ("logs", None) => {
+                pending
+                    .reject(subscription_err("logs filter required"))
+                    .await;
+                return Ok(());
+            }
+            (kind, _) => {
+                let msg = format!("unsupported subscription: {kind}");
+                pending.reject(subscription_err(&msg)).await;
+                return Ok(());
+            }
+        };
+
+        let sink = pending.accept().await?;
+        let execution = self.node.execution.clone();
+        deliver_events(execution, sink, topic, params.cursor, Ok).await
+    }
+}
+
+/// Sends the verified events of `topic` after `cursor` as the head advances, each as the
+/// notification `notification` maps it to, until the subscriber goes away or the client
+/// stops. An event without a notification ends the subscription with its error. The
+/// subscription is dropped with the sink either way.
+async fn deliver_events<N: NetworkSpec, T: Serialize>(
+    execution: Arc<ExecutionClient<N, HttpRpc<N>>>,
+    sink: SubscriptionSink,
+    topic: SubscriptionTopic,
+    mut cursor: Option<SubscriptionCursor>,
+    notification: impl Fn(SubscriptionEvent<N::HeaderResponse>) -> Result<T, ExecutionError>,
+) -> SubscriptionResult {
+    let mut heads = execution.head_updates();
+    let mut _cursor_pin = None;
+
+    loop {
+        let batch = execution
+            .subscription_events(&topic, cursor, MAX_SUBSCRIPTION_BACKFILL)
+            .await;
+
+        match batch {
+            Ok(batch) => {
+                for event in batch.events {
+                    let event = notification(event)?;
+                    sink.send(SubscriptionMessage::from_json(&event)?).await?;
+                }
+                cursor = batch.cursor;
+
+                // keeps the cursor block around to tell whether it is still canonical on the
+                // next head, dropping the pin of the previous one
+                _cursor_pin = match cursor {
+                    Some(cursor) => {
+                        let tag = BlockTag::Number(cursor.block_number);
+                        match execution.pin_block(tag, RetentionReason::Cursor).await {
+                            Ok(pin) => Some(pin).filter(|pin| pin.hash() == cursor.block_hash),
+                            // orphaned since the batch was read, which the next one replays
+                            Err(ExecutionError::BlockNotFound(_)) => None,
+                            Err(err) => return Err(err.into()),
+                        }
+                    }
+                    None => None,
+                };
+            }
+            // the cursor is left as is, so the next head retries the same blocks
+            Err(err) => warn!(target: "helios::rpc", "subscription catch up failed: {}", err),
+        }
+
+        select! {
+            _ = sink.closed() => break,
+            changed = heads.changed() => if changed.is_err() { break },
+        }
+    }
+
+    Ok(())
+}
+
+fn subscription_err(msg: &str) -> ErrorObjectOwned {
+    ErrorObject::owned(1, msg, None::<()>)
+}
+
+async fn serve_http(
+    address: SocketAddr,
+    methods: Methods,
+    compression: &CompressionConfig,
 ) -> Result<(ServerHandle, SocketAddr)> {
-    let server = ServerBuilder::default().build(rpc.address).await?;
+    let server = ServerBuilder::default()
+        .set_middleware(ServiceBuilder::new().layer(compression::layer(compression)))
+        .build(address)
+        .await?;
     let addr = server.local_addr()?;
 
+    Ok((server.start(methods), addr))
+}
+
+/// The methods of every namespace, under their verification policies.
+fn methods<N: NetworkSpec, C: Consensus<N::BlockResponse>>(
+    rpc: RpcInner<N, C>,
+    extra_methods: Methods,
+) -> Result<Methods> {
     let mut methods = Methods::new();
     let eth_methods: Methods = EthRpcServer::into_rpc(rpc.clone()).into();
     let net_methods: Methods = NetRpcServer::into_rpc(rpc.clone()).into();
-    let web3_methods: Methods = Web3RpcServer::into_rpc(rpc).into();
+    let web3_methods: Methods = Web3RpcServer::into_rpc(rpc.clone()).into();
+    let debug_methods: Methods = DebugRpcServer::into_rpc(rpc.clone()).into();
+    let policies = rpc.policies.clone();
+    let helios_methods: Methods = HeliosRpcServer::into_rpc(rpc).into();
 
     methods.merge(eth_methods)?;
     methods.merge(net_methods)?;
     methods.merge(web3_methods)?;
+    methods.merge(debug_methods)?;
+    methods.merge(helios_methods)?;
+    methods.merge(extra_methods)?;
+    apply_policies(&mut methods, &policies)?;
 
-    let handle = server.start(methods);
+    Ok(methods)
+}
+
+/// Validates `policies` against the registered methods, then replaces each unverified method
+/// under a `verified-only` policy with one that refuses the call, and each under `annotate`
+/// with one that warns of every call before serving it.
+fn apply_policies(methods: &mut Methods, policies: &RpcPolicies) -> Result<()> {
+    policies.validate(|method| methods.method(method).is_some())?;
+    // removing a method copies the map, so this keeps the handlers being replaced
+    let original = methods.clone();
+
+    for &(method, trust) in TRUST_MATRIX {
+        if trust.is_verified() || methods.method(method).is_none() {
+            continue;
+        }
+
+        match policies.effective(method) {
+            Policy::VerifiedOnly => {
+                methods.remove(method);
+                let mut refused = RpcModule::new(());
+                refused.register_method(method, move |_, _| -> Result<(), ErrorObjectOwned> {
+                    let msg = format!("{method} is unverified and its rpc policy is verified-only");
+                    Err(ErrorObject::owned(1, msg, None::<()>))
+                })?;
+                methods.merge(refused)?;
+            }
+            Policy::Annotate => {
+                methods.remove(method);
+                let mut annotated = RpcModule::new(());
+                let original = original.clone();
+                annotated.register_async_method(method, move |params, _| {
+                    let original = original.clone();
+                    async move { serve_annotated(&original, method, params.as_str()).await }
+                })?;
+                methods.merge(annotated)?;
+            }
+            Policy::Passthrough => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Serves a call to the unverified `method` from its handler among `methods`, warning that
+/// the answer comes unverified from the execution rpc.
+async fn serve_annotated(
+    methods: &Methods,
+    method: &str,
+    params: Option<&str>,
+) -> Result<serde_json::Value, ErrorObjectOwned> {
+    warn!(target: "helios::rpc", method, "served unverified from the execution rpc");
+
+    let request = format!(
+        r#"{{"jsonrpc":"2.0","id":0,"method":"{method}","params":{}}}"#,
+        params.unwrap_or("[]")
+    );
+    let internal = |msg: String| ErrorObject::owned(INTERNAL_ERROR_CODE, msg, None::<()>);
+    let (response, _) = methods
+        .raw_json_request(&request, 1)
+        .await
+        .map_err(|err| internal(err.to_string()))?;
+    let mut response = serde_json::from_str::<serde_json::Value>(&response.result)
+        .map_err(|err| internal(err.to_string()))?;
+
+    match response.get("error") {
+        Some(error) => Err(ErrorObject::owned(
+            error["code"]
+                .as_i64()
+                .map_or(INTERNAL_ERROR_CODE, |code| code as i32),
+            error["message"].as_str().unwrap_or_default().to_string(),
+            error.get("data").cloned(),
+        )),
+        None => Ok(response["result"].take()),
+    }
+}
 
-    Ok((handle, addr))
+/// Serves errors with the code of their [`ServerError`] class, and reverts with their data.
+fn convert_err<T, E: Into<ServerError>>(res: Result<T, E>) -> Result<T, ErrorObjectOwned> {
+    res.map_err(|err| err.into().into())
 }
 
-fn convert_err<T, E: Display>(res: Result<T, E>) -> Result<T, ErrorObjectOwned> {
-    res.map_err(|err| ErrorObject::owned(1, err.to_string(), None::<()>))
+#[cfg(test)]
+mod tests {
+    use jsonrpsee::rpc_params;
+
+    use super::*;
+
+    fn registered() -> Methods {
+        let mut module = RpcModule::new(());
+        for method in ["eth_getBalance", "eth_sendRawTransaction", "eth_newFilter"] {
+            module
+                .register_method(method, |_, _| -> Result<u64, ErrorObjectOwned> { Ok(1) })
+                .unwrap();
+        }
+        module.into()
+    }
+
+    async fn served(methods: &Methods, method: &str) -> bool {
+        methods.call::<_, u64>(method, rpc_params![]).await.is_ok()
+    }
+
+    #[tokio::test]
+    async fn test_policies_enforced() {
+        let mut policies = RpcPolicies {
+            strict: true,
+            ..Default::default()
+        };
+        policies
+            .policies
+            .insert("eth_newFilter".to_string(), Policy::Passthrough);
+
+        let mut methods = registered();
+        apply_policies(&mut methods, &policies).unwrap();
+        assert!(served(&methods, "eth_getBalance").await);
+        assert!(!served(&methods, "eth_sendRawTransaction").await);
+        assert!(served(&methods, "eth_newFilter").await);
+
+        // without the strict flag only the override refuses
+        policies.strict = false;
+        policies
+            .policies
+            .insert("eth_newFilter".to_string(), Policy::VerifiedOnly);
+
+        let mut methods = registered();
+        apply_policies(&mut methods, &policies).unwrap();
+        assert!(served(&methods, "eth_sendRawTransaction").await);
+        assert!(!served(&methods, "eth_newFilter").await);
+    }
+
+    #[tokio::test]
+    async fn test_annotated_methods_served() {
+        let mut methods = registered();
+        apply_policies(&mut methods, &RpcPolicies::default()).unwrap();
+
+        // replaced by a handler warning of the call, which answers like the original
+        let answer = methods
+            .call::<_, u64>("eth_sendRawTransaction", rpc_params![])
+            .await
+            .unwrap();
+        assert_eq!(answer, 1);
+    }
+
+    #[test]
+    fn test_policy_for_unregistered_method_rejected() {
+        let mut policies = RpcPolicies::default();
+        policies
+            .policies
+            .insert("eth_getWork".to_string(), Policy::Passthrough);
+
+        let err = apply_policies(&mut registered(), &policies).unwrap_err();
+        assert!(err.to_string().contains("eth_getWork"), "{err}");
+    }
 }
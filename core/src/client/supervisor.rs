@@ -0,0 +1,586 @@
+//! Restarts long running tasks that panic, exit or stop reporting heartbeats.
+//!
+//! A supervised task is started from a factory, so a restart gets a fresh future while the
+//! state it works on, captured by the factory, is kept. Restarts back off exponentially, and
+//! a task that keeps failing is treated as fatal rather than left in a loop: the supervisor
+//! gives up on it and reports the failure through [`Supervisor::failed`].
+//!
+//! Tasks doing a recurring job beat their [`Heartbeat`] whenever they make progress. Services
+//! such as servers, which may rightly sit idle for any length of time, are only restarted
+//! when they exit or panic.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use eyre::{eyre, Report, Result};
+use tokio::sync::watch;
+use tokio::task::JoinError;
+use tokio::time::{interval, sleep, Instant};
+use tracing::{error, info, warn};
+
+use crate::time::{SystemTime, UNIX_EPOCH};
+use crate::types::{HealthReport, TaskFailure, TaskHealth};
+
+#[derive(Debug, Clone, Copy)]
+pub struct SupervisorConfig {
+    /// How long a task may go without a heartbeat before it is considered stuck.
+    pub heartbeat_timeout: Duration,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Consecutive failures after which the supervisor gives up on a task.
+    pub max_consecutive_failures: u32,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_timeout: Duration::from_secs(300),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            max_consecutive_failures: 5,
+        }
+    }
+}
+
+/// Handed to a supervised task, which calls [Heartbeat::beat] to show it is making progress.
+#[derive(Debug, Clone)]
+pub struct Heartbeat {
+    started: Instant,
+    last_beat_ms: Arc<AtomicU64>,
+}
+
+impl Heartbeat {
+    fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            last_beat_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn beat(&self) {
+        let now = self.started.elapsed().as_millis() as u64;
+        self.last_beat_ms.store(now, Ordering::Relaxed);
+    }
+
+    fn since_last_beat(&self) -> Duration {
+        let last = Duration::from_millis(self.last_beat_ms.load(Ordering::Relaxed));
+        self.started.elapsed().saturating_sub(last)
+    }
+}
+
+type FatalHandler = Arc<dyn Fn(&'static str) + Send + Sync>;
+
+#[derive(Clone)]
+pub struct Supervisor {
+    config: SupervisorConfig,
+    tasks: Arc<Mutex<BTreeMap<&'static str, TaskEntry>>>,
+    on_fatal: FatalHandler,
+    /// Whether the tasks are shut down until [`Supervisor::restart`].
+    paused: Arc<watch::Sender<bool>>,
+    /// The first task given up on, and why.
+    fatal: Arc<watch::Sender<Option<(&'static str, TaskFailure)>>>,
+}
+
+#[derive(Default)]
+struct TaskEntry {
+    heartbeat: Option<Heartbeat>,
+    restarts: u64,
+    consecutive_failures: u32,
+    last_failure: Option<TaskFailure>,
+    stop: Option<watch::Sender<bool>>,
+}
+
+/// How one run of a task ended.
+enum Outcome {
+    Failed(String),
+    Paused,
+    Stopped,
+}
+
+impl Supervisor {
+    /// Creates a supervisor that logs a task it gives up on, leaving it to whoever awaits
+    /// [`Supervisor::failed`] to act on.
+    pub fn new(config: SupervisorConfig) -> Self {
+        Self::with_fatal_handler(config, |task| {
+            error!(target: "helios::supervisor", task, "task keeps failing, giving up on it");
+        })
+    }
+
+    pub fn with_fatal_handler(
+        config: SupervisorConfig,
+        on_fatal: impl Fn(&'static str) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            config,
+            tasks: Default::default(),
+            on_fatal: Arc::new(on_fatal),
+            paused: Arc::new(watch::channel(false).0),
+            fatal: Arc::new(watch::channel(None).0),
+        }
+    }
+
+    /// Runs the task built by `start` until it fails, then starts a new one. The task is
+    /// considered stuck once it goes [`SupervisorConfig::heartbeat_timeout`] without a beat.
+    pub fn supervise<F, Fut>(&self, name: &'static str, start: F)
+    where
+        F: Fn(Heartbeat) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.spawn(name, Some(self.config.heartbeat_timeout), start);
+    }
+
+    /// Runs the service built by `start`, starting a new one whenever it exits or panics.
+    pub fn supervise_service<F, Fut>(&self, name: &'static str, start: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        self.spawn(name, None, move |_| start());
+    }
+
+    /// Stops the task `name` for good and forgets it.
+    pub fn stop(&self, name: &'static str) {
+        if let Some(task) = self.tasks.lock().unwrap().remove(name) {
+            if let Some(stop) = task.stop {
+                stop.send_replace(true);
+            }
+        }
+    }
+
+    /// Cancels every task until [`Supervisor::restart`], without counting it as a failure.
+    pub fn shutdown(&self) {
+        self.paused.send_replace(true);
+    }
+
+    /// Starts the tasks again after [`Supervisor::shutdown`].
+    pub fn restart(&self) {
+        self.paused.send_replace(false);
+    }
+
+    /// Waits until a task is given up on, returning the error it failed with.
+    pub async fn failed(&self) -> Report {
+        let mut fatal = self.fatal.subscribe();
+        let Ok(fatal) = fatal.wait_for(Option::is_some).await else {
+            return eyre!("supervisor dropped");
+        };
+        let (task, failure) = fatal.clone().expect("waited for a failure");
+        eyre!("task {task} keeps failing, last {}", failure.reason)
+    }
+
+    fn spawn<F, Fut>(&self, name: &'static str, heartbeat_timeout: Option<Duration>, start: F)
+    where
+        F: Fn(Heartbeat) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let (stop_send, mut stop_recv) = watch::channel(false);
+        if let Some(previous) = self.tasks.lock().unwrap().insert(
+            name,
+            TaskEntry {
+                stop: Some(stop_send),
+                ..Default::default()
+            },
+        ) {
+            if let Some(stop) = previous.stop {
+                stop.send_replace(true);
+            }
+        }
+
+        let supervisor = self.clone();
+        tokio::spawn(async move {
+            let mut paused = supervisor.paused.subscribe();
+            loop {
+                // shut down tasks start again once restarted, unless stopped meanwhile
+                tokio::select! {
+                    resumed = paused.wait_for(|paused| !paused) => if resumed.is_err() { return },
+                    _ = stop_recv.wait_for(|stop| *stop) => return,
+                }
+
+                let heartbeat = Heartbeat::new();
+                supervisor.update(name, |task| task.heartbeat = Some(heartbeat.clone()));
+
+                let started = Instant::now();
+                let run = supervisor.run(
+                    start(heartbeat.clone()),
+                    &heartbeat,
+                    heartbeat_timeout,
+                    &mut stop_recv,
+                    &mut paused,
+                );
+                let reason = match run.await {
+                    Outcome::Failed(reason) => reason,
+                    Outcome::Paused => {
+                        supervisor.update(name, |task| task.heartbeat = None);
+                        info!(target: "helios::supervisor", task = name, "task shut down");
+                        continue;
+                    }
+                    Outcome::Stopped => return,
+                };
+                let stable = started.elapsed() >= supervisor.config.heartbeat_timeout;
+
+                let failures = supervisor.update(name, |task| {
+                    task.heartbeat = None;
+                    task.consecutive_failures = if stable {
+                        1
+                    } else {
+                        task.consecutive_failures + 1
+                    };
+                    task.last_failure = Some(TaskFailure {
+                        reason: reason.clone(),
+                        at: unix_now(),
+                    });
+                    task.consecutive_failures
+                });
+
+                if failures >= supervisor.config.max_consecutive_failures {
+                    error!(target: "helios::supervisor", task = name, reason = %reason, failures, "giving up on task");
+                    (supervisor.on_fatal)(name);
+                    let failure = TaskFailure {
+                        reason,
+                        at: unix_now(),
+                    };
+                    supervisor.fatal.send_if_modified(|fatal| {
+                        let first = fatal.is_none();
+                        if first {
+                            *fatal = Some((name, failure));
+                        }
+                        first
+                    });
+                    return;
+                }
+
+                let backoff = supervisor.backoff(failures);
+                warn!(
+                    target: "helios::supervisor",
+                    task = name,
+                    reason = %reason,
+                    failures,
+                    "restarting task in {:?}",
+                    backoff
+                );
+                tokio::select! {
+                    _ = sleep(backoff) => {}
+                    _ = stop_recv.wait_for(|stop| *stop) => return,
+                }
+
+                supervisor.update(name, |task| task.restarts += 1);
+                info!(target: "helios::supervisor", task = name, "restarting task");
+            }
+        });
+    }
+
+    pub fn health(&self) -> HealthReport {
+        let tasks = self
+            .tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, task)| TaskHealth {
+                name: name.to_string(),
+                running: task.heartbeat.is_some(),
+                restarts: task.restarts,
+                consecutive_failures: task.consecutive_failures,
+                last_failure: task.last_failure.clone(),
+                last_heartbeat_ms: task
+                    .heartbeat
+                    .as_ref()
+                    .map(|heartbeat| heartbeat.since_last_beat().as_millis() as u64),
+            })
+            .collect();
+
+        HealthReport {
+            tasks,
+            ..Default::default()
+        }
+    }
+
+    /// Drives one run of a task, returning how it ended. The task is cancelled when it is
+    /// stuck, stopped or shut down.
+    async fn run(
+        &self,
+        task: impl Future<Output = Result<()>> + Send + 'static,
+        heartbeat: &Heartbeat,
+        heartbeat_timeout: Option<Duration>,
+        stop: &mut watch::Receiver<bool>,
+        paused: &mut watch::Receiver<bool>,
+    ) -> Outcome {
+        let mut handle = tokio::spawn(task);
+        // services are never beaten, so their check never fires
+        let period = heartbeat_timeout.map(|timeout| timeout / 4);
+        let mut check = interval(period.unwrap_or(Duration::from_secs(3600)));
+
+        let outcome = loop {
+            tokio::select! {
+                res = &mut handle => {
+                    return Outcome::Failed(match res {
+                        Ok(Ok(())) => "exited".to_string(),
+                        Ok(Err(err)) => format!("failed: {err}"),
+                        Err(err) => join_error_reason(err),
+                    });
+                }
+                _ = stop.wait_for(|stop| *stop) => break Outcome::Stopped,
+                _ = paused.wait_for(|paused| *paused) => break Outcome::Paused,
+                _ = check.tick() => {
+                    let Some(timeout) = heartbeat_timeout else {
+                        continue;
+                    };
+                    if heartbeat.since_last_beat() > timeout {
+                        break Outcome::Failed(format!("no heartbeat for {timeout:?}"));
+                    }
+                }
+            }
+        };
+        handle.abort();
+        outcome
+    }
+
+    fn backoff(&self, failures: u32) -> Duration {
+        let doublings = failures.saturating_sub(1).min(16);
+        self.config
+            .initial_backoff
+            .saturating_mul(1 << doublings)
+            .min(self.config.max_backoff)
+    }
+
+    fn update<T>(&self, name: &'static str, f: impl FnOnce(&mut TaskEntry) -> T) -> T {
+        let mut tasks = self.tasks.lock().unwrap();
+        f(tasks.entry(name).or_default())
+    }
+}
+
+fn join_error_reason(err: JoinError) -> String {
+    if !err.is_panic() {
+        return "cancelled".to_string();
+    }
+
+    let payload: Box<dyn Any + Send> = err.into_panic();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+
+    format!("panicked: {message}")
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use tokio::sync::oneshot;
+
+    use super::*;
+
+    fn config() -> SupervisorConfig {
+        SupervisorConfig {
+            heartbeat_timeout: Duration::from_millis(200),
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_millis(40),
+            max_consecutive_failures: 3,
+        }
+    }
+
+    fn task<'a>(report: &'a HealthReport, name: &str) -> &'a TaskHealth {
+        report.tasks.iter().find(|task| task.name == name).unwrap()
+    }
+
+    /// Beats until cancelled, counting the beats.
+    async fn steady(heartbeat: Heartbeat, beats: Arc<AtomicU32>) -> Result<()> {
+        loop {
+            heartbeat.beat();
+            beats.fetch_add(1, Ordering::Relaxed);
+            sleep(Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_panicking_task_restarted_without_touching_others() {
+        let supervisor = Supervisor::with_fatal_handler(config(), |_| panic!("not fatal"));
+
+        let starts = Arc::new(AtomicU32::new(0));
+        let starts_ref = starts.clone();
+        supervisor.supervise("consensus", move |heartbeat| {
+            let run = starts_ref.fetch_add(1, Ordering::Relaxed);
+            async move {
+                heartbeat.beat();
+                if run == 0 {
+                    panic!("bad update");
+                }
+                std::future::pending().await
+            }
+        });
+
+        let beats = Arc::new(AtomicU32::new(0));
+        let beats_ref = beats.clone();
+        supervisor.supervise("rpc", move |heartbeat| steady(heartbeat, beats_ref.clone()));
+
+        sleep(Duration::from_millis(100)).await;
+        let before = beats.load(Ordering::Relaxed);
+        sleep(Duration::from_millis(50)).await;
+        assert!(beats.load(Ordering::Relaxed) > before);
+
+        let report = supervisor.health();
+        let consensus = task(&report, "consensus");
+        assert!(consensus.running);
+        assert_eq!(starts.load(Ordering::Relaxed), 2);
+        assert_eq!(consensus.restarts, 1);
+        assert_eq!(
+            consensus.last_failure.as_ref().unwrap().reason,
+            "panicked: bad update"
+        );
+
+        let rpc = task(&report, "rpc");
+        assert!(rpc.running);
+        assert_eq!(rpc.restarts, 0);
+        assert!(rpc.last_failure.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stuck_task_restarted_after_missed_heartbeats() {
+        let supervisor = Supervisor::with_fatal_handler(config(), |_| panic!("not fatal"));
+
+        let starts = Arc::new(AtomicU32::new(0));
+        let starts_ref = starts.clone();
+        supervisor.supervise("consensus", move |heartbeat| {
+            let run = starts_ref.fetch_add(1, Ordering::Relaxed);
+            async move {
+                if run > 0 {
+                    loop {
+                        heartbeat.beat();
+                        sleep(Duration::from_millis(10)).await;
+                    }
+                }
+                // deadlocked, never beats
+                std::future::pending().await
+            }
+        });
+
+        sleep(Duration::from_millis(400)).await;
+
+        let report = supervisor.health();
+        let consensus = task(&report, "consensus");
+        assert_eq!(starts.load(Ordering::Relaxed), 2);
+        assert!(consensus.running);
+        assert!(consensus.last_heartbeat_ms.unwrap() < 200);
+        assert!(consensus
+            .last_failure
+            .as_ref()
+            .unwrap()
+            .reason
+            .starts_with("no heartbeat"));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_failures_escalate() {
+        let (fatal_send, fatal_recv) = oneshot::channel();
+        let fatal_send = Mutex::new(Some(fatal_send));
+        let supervisor = Supervisor::with_fatal_handler(config(), move |task| {
+            if let Some(send) = fatal_send.lock().unwrap().take() {
+                _ = send.send(task);
+            }
+        });
+
+        supervisor.supervise("consensus", |_| async {
+            eyre::bail!("consensus channel closed")
+        });
+
+        let task_name = tokio::time::timeout(Duration::from_secs(1), fatal_recv)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(task_name, "consensus");
+
+        let report = supervisor.health();
+        let consensus = task(&report, "consensus");
+        assert!(!consensus.running);
+        assert_eq!(consensus.restarts, 2);
+        assert_eq!(consensus.consecutive_failures, 3);
+        assert_eq!(
+            consensus.last_failure.as_ref().unwrap().reason,
+            "failed: consensus channel closed"
+        );
+
+        let err = supervisor.failed().await;
+        assert_eq!(
+            err.to_string(),
+            "task consensus keeps failing, last failed: consensus channel closed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shut_down_tasks_neither_fail_nor_run() {
+        let supervisor = Supervisor::with_fatal_handler(config(), |_| panic!("not fatal"));
+        let beats = Arc::new(AtomicU32::new(0));
+        let beats_ref = beats.clone();
+        supervisor.supervise("sync", move |heartbeat| {
+            steady(heartbeat, beats_ref.clone())
+        });
+        supervisor.supervise_service("rpc", || std::future::pending());
+
+        sleep(Duration::from_millis(50)).await;
+        supervisor.shutdown();
+        sleep(Duration::from_millis(50)).await;
+        let before = beats.load(Ordering::Relaxed);
+        // long past the heartbeat timeout
+        sleep(Duration::from_millis(400)).await;
+        assert_eq!(beats.load(Ordering::Relaxed), before);
+
+        let report = supervisor.health();
+        for name in ["sync", "rpc"] {
+            let task = task(&report, name);
+            assert!(!task.running);
+            assert_eq!(task.consecutive_failures, 0);
+            assert!(task.last_failure.is_none());
+        }
+
+        supervisor.restart();
+        sleep(Duration::from_millis(50)).await;
+        assert!(beats.load(Ordering::Relaxed) > before);
+        assert!(task(&supervisor.health(), "sync").running);
+
+        supervisor.stop("rpc");
+        assert!(supervisor
+            .health()
+            .tasks
+            .iter()
+            .all(|task| task.name != "rpc"));
+    }
+
+    #[tokio::test]
+    async fn test_idle_service_not_restarted() {
+        let supervisor = Supervisor::with_fatal_handler(config(), |_| panic!("not fatal"));
+        let starts = Arc::new(AtomicU32::new(0));
+        let starts_ref = starts.clone();
+        supervisor.supervise_service("metrics", move || {
+            starts_ref.fetch_add(1, Ordering::Relaxed);
+            std::future::pending()
+        });
+
+        sleep(Duration::from_millis(400)).await;
+        assert_eq!(starts.load(Ordering::Relaxed), 1);
+        assert!(task(&supervisor.health(), "metrics").last_failure.is_none());
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let supervisor = Supervisor::with_fatal_handler(config(), |_| {});
+        let backoffs = (1..=4)
+            .map(|failures| supervisor.backoff(failures))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            backoffs,
+            [10, 20, 40, 40].map(Duration::from_millis).to_vec()
+        );
+    }
+}
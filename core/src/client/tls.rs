@@ -0,0 +1,297 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime};
+
+use eyre::Result;
+use rustls::crypto::ring::{default_provider, sign::any_supported_type};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use rustls::ServerConfig;
+use thiserror::Error;
+use tokio::io::copy_bidirectional;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::select;
+use tokio::task::{JoinHandle, JoinSet};
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, info, warn};
+
+/// How often the certificate files are checked for changes.
+const CERT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// PEM encoded certificate chain and private key used to serve the rpc over TLS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+#[derive(Debug, Error)]
+pub enum TlsError {
+    #[error("cannot read tls file {0}: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("no certificate found in {0}")]
+    NoCertificate(PathBuf),
+    #[error("no private key found in {0}")]
+    NoPrivateKey(PathBuf),
+    #[error("unsupported private key in {0}: {1}")]
+    UnsupportedKey(PathBuf, rustls::Error),
+    #[error("private key {key} does not match certificate {cert}")]
+    KeyMismatch { cert: PathBuf, key: PathBuf },
+    #[error("invalid tls configuration: {0}")]
+    Config(rustls::Error),
+}
+
+/// Terminates TLS in front of the rpc server.
+///
+/// Decrypted connections are forwarded byte for byte to the plaintext server, so both
+/// http requests and websocket subscriptions work unchanged over the TLS listener. The
+/// server of jsonrpsee cannot accept TLS streams itself, so the plaintext server listens on
+/// a loopback port: TLS protects the callers on the network, while processes on the same host
+/// can still reach the plaintext port. The server sees every caller at a loopback address,
+/// so each connection is logged with the peer and the upstream address it is forwarded from.
+pub struct TlsTerminator {
+    resolver: Arc<ReloadingCertResolver>,
+    acceptor: TlsAcceptor,
+}
+
+impl TlsTerminator {
+    /// Loads the key material, failing if it is unreadable or inconsistent.
+    pub fn new(config: TlsConfig) -> Result<Self, TlsError> {
+        let resolver = Arc::new(ReloadingCertResolver::new(config)?);
+
+        let server_config = ServerConfig::builder_with_provider(Arc::new(default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(TlsError::Config)?
+            .with_no_client_auth()
+            .with_cert_resolver(resolver.clone());
+
+        Ok(Self {
+            resolver,
+            acceptor: TlsAcceptor::from(Arc::new(server_config)),
+        })
+    }
+
+    /// Reloads the certificate and key from disk, keeping the current ones on failure.
+    pub fn reload(&self) -> Result<(), TlsError> {
+        self.resolver.reload()
+    }
+
+    /// Listens on `address` and forwards every TLS connection to `upstream`. Returns the
+    /// bound address.
+    pub async fn start(self, address: SocketAddr, upstream: SocketAddr) -> Result<SocketAddr> {
+        Ok(self.serve(address, upstream).await?.local_addr())
+    }
+
+    /// Like [`TlsTerminator::start`], returning a handle that stops the listener.
+    pub async fn serve(self, address: SocketAddr, upstream: SocketAddr) -> Result<TlsListener> {
+        let listener = TcpListener::bind(address).await?;
+        let local_addr = listener.local_addr()?;
+
+        let mut tasks = spawn_reload_tasks(self.resolver.clone());
+
+        let acceptor = self.acceptor;
+        tasks.push(tokio::spawn(async move {
+            // dropped with the task as the listener stops, closing every connection
+            let mut connections = JoinSet::new();
+            loop {
+                let accepted = select! {
+                    accepted = listener.accept() => accepted,
+                    Some(_) = connections.join_next() => continue,
+                };
+                let (stream, peer) = match accepted {
+                    Ok(conn) => conn,
+                    Err(err) => {
+                        warn!(target: "helios::rpc", error = %err, "failed to accept connection");
+                        continue;
+                    }
+                };
+
+                let acceptor = acceptor.clone();
+                connections.spawn(async move {
+                    if let Err(err) = forward(acceptor, stream, peer, upstream).await {
+                        debug!(target: "helios::rpc", %peer, error = %err, "tls connection closed");
+                    }
+                });
+            }
+        }));
+
+        Ok(TlsListener { local_addr, tasks })
+    }
+}
+
+/// A running [`TlsTerminator`].
+pub struct TlsListener {
+    local_addr: SocketAddr,
+    tasks: Vec<JoinHandle<()>>,
+}
+
+impl TlsListener {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Stops accepting connections and reloading the certificate, and closes the connections
+    /// being forwarded, releasing the port once it returns.
+    pub async fn stop(self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+        for task in self.tasks {
+            _ = task.await;
+        }
+    }
+}
+
+async fn forward(
+    acceptor: TlsAcceptor,
+    stream: TcpStream,
+    peer: SocketAddr,
+    upstream: SocketAddr,
+) -> Result<()> {
+    let mut tls_stream = acceptor.accept(stream).await?;
+    let mut upstream = TcpStream::connect(upstream).await?;
+    debug!(target: "helios::rpc", %peer, forwarded_from = %upstream.local_addr()?, "tls connection accepted");
+    copy_bidirectional(&mut tls_stream, &mut upstream).await?;
+    Ok(())
+}
+
+/// Reloads on SIGHUP and whenever the certificate or key file is modified.
+fn spawn_reload_tasks(resolver: Arc<ReloadingCertResolver>) -> Vec<JoinHandle<()>> {
+    let mut tasks = Vec::new();
+
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let resolver = resolver.clone();
+        match signal(SignalKind::hangup()) {
+            Ok(mut hangup) => {
+                tasks.push(tokio::spawn(async move {
+                    while hangup.recv().await.is_some() {
+                        info!(target: "helios::rpc", "received SIGHUP, reloading tls certificate");
+                        resolver.reload_logged();
+                    }
+                }));
+            }
+            Err(err) => {
+                warn!(target: "helios::rpc", error = %err, "cannot listen for SIGHUP");
+            }
+        }
+    }
+
+    tasks.push(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(CERT_POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            if resolver.files_changed() {
+                info!(target: "helios::rpc", "tls certificate changed on disk, reloading");
+                resolver.reload_logged();
+            }
+        }
+    }));
+
+    tasks
+}
+
+#[derive(Debug)]
+struct ReloadingCertResolver {
+    config: TlsConfig,
+    current: RwLock<Arc<CertifiedKey>>,
+    modified: RwLock<Option<SystemTime>>,
+}
+
+impl ReloadingCertResolver {
+    fn new(config: TlsConfig) -> Result<Self, TlsError> {
+        let key = load_certified_key(&config)?;
+        let modified = last_modified(&config);
+
+        Ok(Self {
+            config,
+            current: RwLock::new(Arc::new(key)),
+            modified: RwLock::new(modified),
+        })
+    }
+
+    fn reload(&self) -> Result<(), TlsError> {
+        let modified = last_modified(&self.config);
+        let key = load_certified_key(&self.config)?;
+
+        *self.current.write().unwrap() = Arc::new(key);
+        *self.modified.write().unwrap() = modified;
+        Ok(())
+    }
+
+    fn reload_logged(&self) {
+        match self.reload() {
+            Ok(()) => info!(target: "helios::rpc", "tls certificate reloaded"),
+            Err(err) => {
+                warn!(target: "helios::rpc", error = %err, "tls reload failed, keeping previous certificate")
+            }
+        }
+    }
+
+    fn files_changed(&self) -> bool {
+        let modified = last_modified(&self.config);
+        modified.is_some() && modified != *self.modified.read().unwrap()
+    }
+}
+
+impl ResolvesServerCert for ReloadingCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap().clone())
+    }
+}
+
+fn load_certified_key(config: &TlsConfig) -> Result<CertifiedKey, TlsError> {
+    let certs = load_certs(&config.cert_path)?;
+    let key = load_private_key(&config.key_path)?;
+    let signing_key = any_supported_type(&key)
+        .map_err(|err| TlsError::UnsupportedKey(config.key_path.clone(), err))?;
+
+    let certified_key = CertifiedKey::new(certs, signing_key);
+    match certified_key.keys_match() {
+        // rustls can't tell for some key types, the handshake will fail if they differ
+        Ok(()) | Err(rustls::Error::InconsistentKeys(rustls::InconsistentKeys::Unknown)) => {
+            Ok(certified_key)
+        }
+        Err(_) => Err(TlsError::KeyMismatch {
+            cert: config.cert_path.clone(),
+            key: config.key_path.clone(),
+        }),
+    }
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, TlsError> {
+    let mut reader = open(path)?;
+    let certs = rustls_pemfile::certs(&mut reader)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| TlsError::Read(path.to_path_buf(), err))?;
+
+    if certs.is_empty() {
+        return Err(TlsError::NoCertificate(path.to_path_buf()));
+    }
+
+    Ok(certs)
+}
+
+fn load_private_key(path: &Path) -> Result<PrivateKeyDer<'static>, TlsError> {
+    let mut reader = open(path)?;
+    rustls_pemfile::private_key(&mut reader)
+        .map_err(|err| TlsError::Read(path.to_path_buf(), err))?
+        .ok_or_else(|| TlsError::NoPrivateKey(path.to_path_buf()))
+}
+
+fn open(path: &Path) -> Result<BufReader<File>, TlsError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|err| TlsError::Read(path.to_path_buf(), err))
+}
+
+fn last_modified(config: &TlsConfig) -> Option<SystemTime> {
+    let modified = |path: &Path| path.metadata().and_then(|meta| meta.modified()).ok();
+    modified(&config.cert_path).max(modified(&config.key_path))
+}
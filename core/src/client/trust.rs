@@ -0,0 +1,369 @@
+//! How far the answer of each RPC method can be trusted, and where it comes from.
+//!
+//! [`TRUST_MATRIX`] is the reference for which methods are answered by helios itself and
+//! which rely on the execution provider. Tests check it against the methods the server
+//! registers, and call the local methods through the server with a provider that panics when
+//! reached, so the table cannot silently drift from the handlers.
+//!
+//! [`RpcPolicies`] decides per method whether the rpc server serves answers of a given trust,
+//! from the global strict flag and the overrides in the `[rpc.policies]` config table.
+
+use std::collections::BTreeMap;
+
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+/// Where an RPC method gets its answer from once the client is synced.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Trust {
+    /// Read from the validated client configuration.
+    Config,
+    /// Read from blocks verified through consensus and held in local state.
+    VerifiedBlock,
+    /// Proven against a verified state root, and cached per block so repeat reads of the
+    /// same account do not reach the provider.
+    CachedProof,
+    /// Fetched from the provider on every request and verified against a verified block.
+    Proof,
+    /// Computed by the local EVM, fetching and proving the state it touches.
+    Executed,
+    /// Forwarded to the provider and returned as is.
+    Unverified,
+    /// Produced by helios without reference to chain data.
+    Local,
+}
+
+impl Trust {
+    /// Whether the answer is checked against consensus, or needs no checking at all.
+    pub const fn is_verified(self) -> bool {
+        !matches!(self, Trust::Unverified)
+    }
+
+    /// Whether a primed, synced client answers without calling the execution provider.
+    pub const fn is_local(self) -> bool {
+        matches!(
+            self,
+            Trust::Config | Trust::VerifiedBlock | Trust::CachedProof | Trust::Local
+        )
+    }
+}
+
+/// The trust of every method served over RPC, by its full method name.
+pub const TRUST_MATRIX: &[(&str, Trust)] = &[
+    ("eth_chainId", Trust::Config),
+    ("eth_blockNumber", Trust::VerifiedBlock),
+    ("eth_getBalance", Trust::CachedProof),
+    ("eth_getTransactionCount", Trust::CachedProof),
+    ("eth_getCode", Trust::CachedProof),
+    ("eth_getStorageAt", Trust::Proof),
+    ("eth_getProof", Trust::Proof),
+    // pre-merge blocks and logs are unverified when premerge_passthrough is set
+    ("eth_getBlockByNumber", Trust::VerifiedBlock),
+    ("eth_getBlockByHash", Trust::VerifiedBlock),
+    ("eth_getBlockTransactionCountByHash", Trust::VerifiedBlock),
+    ("eth_getBlockTransactionCountByNumber", Trust::VerifiedBlock),
+    ("eth_getTransactionByHash", Trust::VerifiedBlock),
+    (
+        "eth_getTransactionByBlockHashAndIndex",
+        Trust::VerifiedBlock,
+    ),
+    (
+        "eth_getTransactionByBlockNumberAndIndex",
+        Trust::VerifiedBlock,
+    ),
+    ("eth_getTransactionReceipt", Trust::Proof),
+    ("eth_getBlockReceipts", Trust::Proof),
+    ("eth_getLogs", Trust::Proof),
+    ("eth_getFilterLogs", Trust::Proof),
+    ("eth_subscribe", Trust::Proof),
+    ("eth_unsubscribe", Trust::Local),
+    // pending transaction filters return the provider's hashes unchecked
+    ("eth_getFilterChanges", Trust::Unverified),
+    ("eth_newFilter", Trust::Unverified),
+    ("eth_newBlockFilter", Trust::Unverified),
+    ("eth_newPendingTransactionFilter", Trust::Unverified),
+    ("eth_uninstallFilter", Trust::Unverified),
+    ("eth_call", Trust::Executed),
+    ("eth_estimateGas", Trust::Executed),
+    ("eth_createAccessList", Trust::Executed),
+    // the base fee is verified, the priority fee is suggested from the reward percentiles
+    // of the fee history, which are as reported
+    ("eth_gasPrice", Trust::Unverified),
+    ("eth_maxPriorityFeePerGas", Trust::Unverified),
+    ("eth_blobBaseFee", Trust::VerifiedBlock),
+    // base fees and gas used ratios are checked, reward percentiles are as reported
+    ("eth_feeHistory", Trust::Proof),
+    ("eth_coinbase", Trust::VerifiedBlock),
+    ("eth_syncing", Trust::VerifiedBlock),
+    ("eth_sendRawTransaction", Trust::Unverified),
+    // encoded on demand from the same blocks and receipts the roots were checked against
+    ("debug_getRawHeader", Trust::VerifiedBlock),
+    ("debug_getRawBlock", Trust::VerifiedBlock),
+    ("debug_getRawReceipts", Trust::Proof),
+    ("debug_traceCall", Trust::Executed),
+    ("net_version", Trust::Config),
+    ("web3_clientVersion", Trust::Local),
+    ("helios_getNetworkInfo", Trust::Config),
+    ("helios_version", Trust::Local),
+    ("helios_health", Trust::Local),
+    ("helios_dbStats", Trust::Local),
+    // committees the light client verified, read back from its database
+    ("helios_getSyncCommittee", Trust::VerifiedBlock),
+    ("helios_getSyncCommitteePeriods", Trust::Local),
+    ("helios_capabilities", Trust::Config),
+    ("helios_getUsage", Trust::Local),
+    // reorgs between verified blocks, as the light client saw them
+    ("helios_getReorgHistory", Trust::VerifiedBlock),
+    // a prediction, but from verified blocks and the finality the light client observed
+    ("helios_estimateFinalityTime", Trust::VerifiedBlock),
+    // verified like the head, but only as final as the confirmation depth assumes
+    ("helios_getConfirmedBlock", Trust::VerifiedBlock),
+    ("helios_getPrevRandao", Trust::VerifiedBlock),
+    ("helios_getPrevRandaoRange", Trust::VerifiedBlock),
+    (
+        "helios_getBeaconHeaderByExecutionBlock",
+        Trust::VerifiedBlock,
+    ),
+    ("helios_getExecutionBlockBySlot", Trust::VerifiedBlock),
+    ("helios_getExecutionProof", Trust::Proof),
+    ("helios_getLogsPaged", Trust::Proof),
+    ("helios_simulateBundle", Trust::Executed),
+    ("helios_callMany", Trust::Executed),
+    ("helios_waitForTransactionReceipt", Trust::Proof),
+    ("helios_simulateWithAssetChanges", Trust::Executed),
+    // heads come from verified blocks, logs are checked against their receipts
+    ("helios_subscribe", Trust::Proof),
+    ("helios_unsubscribe", Trust::Local),
+    // state is proven, but against a header the caller vouches for
+    ("helios_callWithHeader", Trust::Executed),
+];
+
+/// Looks up the trust of an RPC method, such as `eth_getBalance`.
+pub fn trust_of(method: &str) -> Option<Trust> {
+    TRUST_MATRIX
+        .iter()
+        .find(|(name, _)| *name == method)
+        .map(|(_, trust)| *trust)
+}
+
+/// How the rpc server treats the answers of a method.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Policy {
+    /// Only served when the answer is verified, so unverified methods return an error.
+    VerifiedOnly,
+    /// Served, logging a warning on every call to an unverified method.
+    Annotate,
+    /// Served as is, without the warnings.
+    Passthrough,
+}
+
+impl Policy {
+    /// Whether a method answering with `trust` is served under this policy.
+    pub const fn serves(self, trust: Trust) -> bool {
+        !matches!(self, Policy::VerifiedOnly) || trust.is_verified()
+    }
+}
+
+/// The verification policy of every rpc method, read from the `rpc` config table.
+///
+/// The global `strict` flag sets the policy of methods without an override, `verified-only`
+/// when set and `annotate` otherwise. Overrides in `policies` take precedence either way.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RpcPolicies {
+    /// Off by default, so unverified methods are served unless configured otherwise.
+    pub strict: bool,
+    pub policies: BTreeMap<String, Policy>,
+}
+
+impl RpcPolicies {
+    /// The policy of methods without an override.
+    pub const fn default_policy(&self) -> Policy {
+        if self.strict {
+            Policy::VerifiedOnly
+        } else {
+            Policy::Annotate
+        }
+    }
+
+    /// The policy `method` is served under.
+    pub fn effective(&self, method: &str) -> Policy {
+        self.policies
+            .get(method)
+            .copied()
+            .unwrap_or_else(|| self.default_policy())
+    }
+
+    /// Checks that every override names a method that is both served, according to
+    /// `is_registered`, and listed in the [`TRUST_MATRIX`].
+    pub fn validate(&self, is_registered: impl Fn(&str) -> bool) -> Result<()> {
+        for method in self.policies.keys() {
+            if !is_registered(method) {
+                return Err(eyre!("rpc policy for unknown method {method}"));
+            }
+            if trust_of(method).is_none() {
+                return Err(eyre!(
+                    "rpc policy for {method}, which has no trust classification"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// The effective policy of every classified method.
+    pub fn capabilities(&self) -> Capabilities {
+        let methods = TRUST_MATRIX
+            .iter()
+            .map(|&(method, trust)| {
+                let policy = self.effective(method);
+                MethodCapability {
+                    method: method.to_string(),
+                    trust,
+                    policy,
+                    served: policy.serves(trust),
+                }
+            })
+            .collect();
+
+        Capabilities {
+            strict: self.strict,
+            methods,
+        }
+    }
+}
+
+/// The answer of `helios_capabilities`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Capabilities {
+    pub strict: bool,
+    pub methods: Vec<MethodCapability>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodCapability {
+    pub method: String,
+    pub trust: Trust,
+    pub policy: Policy,
+    /// Whether calls are answered, rather than refused by a `verified-only` policy.
+    pub served: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_overrides(strict: bool, overrides: &[(&str, Policy)]) -> RpcPolicies {
+        RpcPolicies {
+            strict,
+            policies: overrides
+                .iter()
+                .map(|(method, policy)| (method.to_string(), *policy))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_trust_matrix_entries() {
+        for (i, (method, _)) in TRUST_MATRIX.iter().enumerate() {
+            let duplicate = TRUST_MATRIX[i + 1..]
+                .iter()
+                .any(|(other, _)| other == method);
+            assert!(!duplicate, "{method} listed twice");
+        }
+
+        assert_eq!(trust_of("eth_chainId"), Some(Trust::Config));
+        assert_eq!(trust_of("eth_getStorageAt"), Some(Trust::Proof));
+        assert!(!trust_of("eth_call").unwrap().is_local());
+        assert!(!trust_of("eth_sendRawTransaction").unwrap().is_verified());
+        assert_eq!(trust_of("eth_mining"), None);
+    }
+
+    #[test]
+    fn test_local_methods() {
+        for method in [
+            "eth_chainId",
+            "eth_blockNumber",
+            "eth_getBalance",
+            "eth_getTransactionCount",
+            "eth_getCode",
+            "eth_getBlockByNumber",
+            "eth_getBlockByHash",
+            "eth_getTransactionByHash",
+            "eth_coinbase",
+            "eth_blobBaseFee",
+            "helios_getPrevRandao",
+        ] {
+            assert!(trust_of(method).unwrap().is_local(), "{method}");
+        }
+    }
+
+    #[test]
+    fn test_overrides_take_precedence_over_strict_flag() {
+        let overrides = [
+            ("eth_getBalance", Policy::VerifiedOnly),
+            ("eth_sendRawTransaction", Policy::Passthrough),
+        ];
+
+        let strict = with_overrides(true, &overrides);
+        assert_eq!(strict.effective("eth_call"), Policy::VerifiedOnly);
+        assert_eq!(strict.effective("eth_getBalance"), Policy::VerifiedOnly);
+        assert_eq!(
+            strict.effective("eth_sendRawTransaction"),
+            Policy::Passthrough
+        );
+        assert_eq!(strict.effective("eth_newFilter"), Policy::VerifiedOnly);
+
+        let lenient = with_overrides(false, &overrides);
+        assert_eq!(lenient.effective("eth_call"), Policy::Annotate);
+        assert_eq!(lenient.effective("eth_getBalance"), Policy::VerifiedOnly);
+        assert_eq!(
+            lenient.effective("eth_sendRawTransaction"),
+            Policy::Passthrough
+        );
+        assert_eq!(lenient.effective("eth_newFilter"), Policy::Annotate);
+    }
+
+    #[test]
+    fn test_capabilities_report_effective_policy() {
+        let policies = with_overrides(true, &[("eth_sendRawTransaction", Policy::Annotate)]);
+        let capabilities = policies.capabilities();
+        assert_eq!(capabilities.methods.len(), TRUST_MATRIX.len());
+
+        let find = |method: &str| {
+            capabilities
+                .methods
+                .iter()
+                .find(|capability| capability.method == method)
+                .unwrap()
+        };
+        assert_eq!(find("eth_getBalance").policy, Policy::VerifiedOnly);
+        assert!(find("eth_getBalance").served);
+        assert!(!find("eth_newFilter").served);
+        assert_eq!(find("eth_sendRawTransaction").policy, Policy::Annotate);
+        assert!(find("eth_sendRawTransaction").served);
+    }
+
+    #[test]
+    fn test_policy_names() {
+        let policies: RpcPolicies = serde_json::from_str(
+            r#"{"strict": true, "policies": {"eth_call": "verified-only", "eth_newFilter": "passthrough"}}"#,
+        )
+        .unwrap();
+        assert_eq!(policies.effective("eth_newFilter"), Policy::Passthrough);
+        assert!(serde_json::from_str::<Policy>(r#""strict""#).is_err());
+
+        assert!(policies.validate(|_| true).is_ok());
+
+        // without a config table every method is served
+        let defaults: RpcPolicies = serde_json::from_str("{}").unwrap();
+        assert!(!defaults.strict);
+        assert_eq!(defaults.effective("eth_newFilter"), Policy::Annotate);
+
+        let unclassified = with_overrides(false, &[("eth_mining", Policy::Annotate)]);
+        assert!(unclassified.validate(|_| true).is_err());
+    }
+}
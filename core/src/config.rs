@@ -0,0 +1,329 @@
+//! Value types for configuration files.
+//!
+//! Durations and sizes are written with a unit, such as `"500ms"` or `"64MB"`, and serialize
+//! back to the same form. Plain integers are still accepted for options that predate units,
+//! and are read as seconds and bytes respectively.
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use thiserror::Error;
+
+const DURATION_FORMS: &str =
+    "an integer number of seconds, or an integer with one of the units ms, s, m, h, d (e.g. \"500ms\", \"10s\", \"2h\")";
+const SIZE_FORMS: &str =
+    "an integer number of bytes, or an integer with one of the units B, KB, MB, GB (e.g. \"512KB\", \"64MB\")";
+
+const DURATION_UNITS: &[(&str, u64)] = &[
+    ("d", 86_400_000),
+    ("h", 3_600_000),
+    ("m", 60_000),
+    ("s", 1_000),
+    ("ms", 1),
+];
+
+// sizes are binary multiples, the IEC spellings are accepted as aliases
+const SIZE_UNITS: &[(&str, u64)] = &[("GB", 1 << 30), ("MB", 1 << 20), ("KB", 1 << 10), ("B", 1)];
+const SIZE_ALIASES: &[(&str, &str)] = &[("GiB", "GB"), ("MiB", "MB"), ("KiB", "KB")];
+
+/// A duration in a config file, with millisecond precision.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ConfigDuration(Duration);
+
+impl ConfigDuration {
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(Duration::from_millis(millis))
+    }
+
+    pub const fn as_duration(&self) -> Duration {
+        self.0
+    }
+
+    pub const fn as_secs(&self) -> u64 {
+        self.0.as_secs()
+    }
+}
+
+impl From<Duration> for ConfigDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<ConfigDuration> for Duration {
+    fn from(duration: ConfigDuration) -> Self {
+        duration.0
+    }
+}
+
+impl FromStr for ConfigDuration {
+    type Err = UnitParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let millis = parse_with_units(s, DURATION_UNITS, &[], 1_000)
+            .ok_or_else(|| UnitParseError::new(s, DURATION_FORMS))?;
+        Ok(Self::from_millis(millis))
+    }
+}
+
+impl Display for ConfigDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_with_units(f, self.0.as_millis() as u64, DURATION_UNITS)
+    }
+}
+
+/// A size in bytes in a config file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    pub const fn from_bytes(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    pub const fn from_mb(mb: u64) -> Self {
+        Self(mb << 20)
+    }
+
+    pub const fn as_bytes(&self) -> u64 {
+        self.0
+    }
+
+    /// The size as a `usize`, saturating on targets where it does not fit.
+    pub fn as_usize(&self) -> usize {
+        usize::try_from(self.0).unwrap_or(usize::MAX)
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = UnitParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_with_units(s, SIZE_UNITS, SIZE_ALIASES, 1)
+            .map(Self)
+            .ok_or_else(|| UnitParseError::new(s, SIZE_FORMS))
+    }
+}
+
+impl Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        format_with_units(f, self.0, SIZE_UNITS)
+    }
+}
+
+/// A config value that is neither a plain integer nor an integer with a known unit.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("invalid value {value:?}, expected {expected}")]
+pub struct UnitParseError {
+    value: String,
+    expected: &'static str,
+}
+
+impl UnitParseError {
+    fn new(value: &str, expected: &'static str) -> Self {
+        Self {
+            value: value.to_string(),
+            expected,
+        }
+    }
+}
+
+/// Parses `<integer><unit>`, or a bare integer scaled by `default_scale`.
+fn parse_with_units(
+    s: &str,
+    units: &[(&str, u64)],
+    aliases: &[(&str, &str)],
+    default_scale: u64,
+) -> Option<u64> {
+    let s = s.trim();
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (digits, unit) = s.split_at(split);
+    let value = digits.parse::<u64>().ok()?;
+
+    let unit = unit.trim_start();
+    if unit.is_empty() {
+        return value.checked_mul(default_scale);
+    }
+
+    let unit = aliases
+        .iter()
+        .find(|(alias, _)| *alias == unit)
+        .map_or(unit, |(_, unit)| *unit);
+    let (_, scale) = units.iter().find(|(name, _)| *name == unit)?;
+    value.checked_mul(*scale)
+}
+
+/// Writes `value` in the largest unit that represents it exactly.
+fn format_with_units(f: &mut fmt::Formatter<'_>, value: u64, units: &[(&str, u64)]) -> fmt::Result {
+    let (name, scale) = units
+        .iter()
+        .find(|(_, scale)| value % scale == 0 && value != 0)
+        .unwrap_or(&units[units.len() - 1]);
+    write!(f, "{}{}", value / scale, name)
+}
+
+macro_rules! impl_unit_serde {
+    ($ty:ty, $default_scale:expr, $expected:expr) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct UnitVisitor;
+
+                impl Visitor<'_> for UnitVisitor {
+                    type Value = $ty;
+
+                    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str($expected)
+                    }
+
+                    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+                        let scaled: fn(u64) -> Option<$ty> = $default_scale;
+                        scaled(value).ok_or_else(|| E::custom(format!("{value} is out of range")))
+                    }
+
+                    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+                        let value = u64::try_from(value)
+                            .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))?;
+                        self.visit_u64(value)
+                    }
+
+                    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                        value.parse().map_err(E::custom)
+                    }
+                }
+
+                deserializer.deserialize_any(UnitVisitor)
+            }
+        }
+    };
+}
+
+impl_unit_serde!(
+    ConfigDuration,
+    |secs| secs.checked_mul(1_000).map(ConfigDuration::from_millis),
+    DURATION_FORMS
+);
+impl_unit_serde!(ByteSize, |bytes| Some(ByteSize(bytes)), SIZE_FORMS);
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_duration_forms() {
+        let cases = [
+            ("500ms", Duration::from_millis(500)),
+            ("10s", Duration::from_secs(10)),
+            ("3m", Duration::from_secs(180)),
+            ("2h", Duration::from_secs(7_200)),
+            ("14d", Duration::from_secs(1_209_600)),
+            ("86400", Duration::from_secs(86_400)),
+            ("0", Duration::ZERO),
+            (" 5 s ", Duration::from_secs(5)),
+        ];
+
+        for (input, expected) in cases {
+            let parsed = input.parse::<ConfigDuration>().unwrap();
+            assert_eq!(parsed.as_duration(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_duration_display_uses_largest_exact_unit() {
+        let cases = [
+            (ConfigDuration::from_millis(1_500), "1500ms"),
+            (ConfigDuration::from_secs(90), "90s"),
+            (ConfigDuration::from_secs(120), "2m"),
+            (ConfigDuration::from_secs(7_200), "2h"),
+            (ConfigDuration::from_secs(1_209_600), "14d"),
+            (ConfigDuration::from_secs(0), "0ms"),
+        ];
+
+        for (duration, expected) in cases {
+            assert_eq!(duration.to_string(), expected);
+            assert_eq!(expected.parse::<ConfigDuration>().unwrap(), duration);
+        }
+    }
+
+    #[test]
+    fn test_size_forms() {
+        let cases = [
+            ("512B", 512),
+            ("4KB", 4 << 10),
+            ("64MB", 64 << 20),
+            ("2GB", 2 << 30),
+            ("64MiB", 64 << 20),
+            ("1KiB", 1 << 10),
+            ("1048576", 1 << 20),
+        ];
+
+        for (input, expected) in cases {
+            let parsed = input.parse::<ByteSize>().unwrap();
+            assert_eq!(parsed.as_bytes(), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_size_display_uses_largest_exact_unit() {
+        let cases = [
+            (ByteSize::from_bytes(1_000), "1000B"),
+            (ByteSize::from_bytes(4 << 10), "4KB"),
+            (ByteSize::from_mb(64), "64MB"),
+            (ByteSize::from_mb(2048), "2GB"),
+            (ByteSize::from_bytes(0), "0B"),
+        ];
+
+        for (size, expected) in cases {
+            assert_eq!(size.to_string(), expected);
+            assert_eq!(expected.parse::<ByteSize>().unwrap(), size);
+        }
+    }
+
+    #[test]
+    fn test_invalid_values_list_accepted_forms() {
+        for input in ["", "10x", "1.5s", "-3s", "s", "10 seconds"] {
+            let err = input.parse::<ConfigDuration>().unwrap_err().to_string();
+            assert!(err.contains("\"500ms\""), "{input}: {err}");
+        }
+
+        for input in ["64mb", "1TB", "MB", "0x10"] {
+            let err = input.parse::<ByteSize>().unwrap_err().to_string();
+            assert!(err.contains("\"64MB\""), "{input}: {err}");
+        }
+
+        assert!("18446744073709551615d".parse::<ConfigDuration>().is_err());
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let duration: ConfigDuration = serde_json::from_value(json!("10s")).unwrap();
+        assert_eq!(serde_json::to_value(duration).unwrap(), json!("10s"));
+
+        // plain integers predate units and are read as seconds
+        let duration: ConfigDuration = serde_json::from_value(json!(86_400)).unwrap();
+        assert_eq!(serde_json::to_value(duration).unwrap(), json!("1d"));
+
+        let size: ByteSize = serde_json::from_value(json!("64MB")).unwrap();
+        assert_eq!(serde_json::to_value(size).unwrap(), json!("64MB"));
+
+        let size: ByteSize = serde_json::from_value(json!(1_024)).unwrap();
+        assert_eq!(serde_json::to_value(size).unwrap(), json!("1KB"));
+
+        assert!(serde_json::from_value::<ConfigDuration>(json!(-1)).is_err());
+        assert!(serde_json::from_value::<ByteSize>(json!(true)).is_err());
+    }
+}
@@ -1,15 +1,81 @@
 use alloy::network::{primitives::HeaderResponse, BlockResponse, TransactionResponse};
+use alloy::primitives::Address;
 use eyre::Result;
 use serde::Serialize;
 use tokio::sync::{mpsc, watch};
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::supervisor::Supervisor;
+use crate::types::{
+    BeaconBlockMapping, DbStats, FinalityTiming, HeadFeed, NetworkInfo, SyncCommitteeInfo,
+};
+
 pub trait Consensus<
     B: BlockResponse<Transaction: TransactionResponse, Header: HeaderResponse> + Serialize,
 >: Sync + Send + 'static
 {
     fn block_recv(&mut self) -> Option<mpsc::Receiver<B>>;
     fn finalized_block_recv(&mut self) -> Option<watch::Receiver<Option<B>>>;
+    /// Blocks safe from reorgs short of finality, such as the justified checkpoint, for
+    /// clients that track them. Without them `safe` resolves to the finalized block.
+    fn safe_block_recv(&mut self) -> Option<watch::Receiver<Option<B>>> {
+        None
+    }
+    /// Beacon blocks backing the delivered execution blocks, for chains that have them.
+    fn beacon_mapping_recv(&mut self) -> Option<mpsc::Receiver<BeaconBlockMapping>> {
+        None
+    }
+    /// Whether heads keep arriving, for clients that watch for them to stall.
+    fn head_feed_recv(&mut self) -> Option<watch::Receiver<HeadFeed>> {
+        None
+    }
+    /// The latest head verified, which the state may still be about to apply, for clients
+    /// that keep it.
+    fn head(&self) -> Option<B> {
+        None
+    }
     fn expected_highest_block(&self) -> u64;
     fn chain_id(&self) -> u64;
+    fn network_info(&self) -> NetworkInfo;
+    /// Cargo features the crate of this client was built with, as `crate/feature`.
+    fn build_features(&self) -> Vec<String> {
+        Vec::new()
+    }
+    /// Usage of the persistent database, for clients that keep one.
+    fn db_stats(&self) -> Result<Option<DbStats>> {
+        Ok(None)
+    }
+    /// The persisted sync committee of `period`, for chains that have them.
+    fn sync_committee(&self, _period: u64) -> Result<Option<SyncCommitteeInfo>> {
+        Ok(None)
+    }
+    /// Periods with a persisted sync committee, in ascending order.
+    fn sync_committee_periods(&self) -> Result<Vec<u64>> {
+        Ok(Vec::new())
+    }
+    /// The ENS registry names are resolved against, where the network has one.
+    fn ens_registry(&self) -> Option<Address> {
+        None
+    }
+    /// Estimated milliseconds the local clock runs ahead of the network, negative when
+    /// behind, for clients that measure it.
+    fn clock_drift(&self) -> Option<i64> {
+        None
+    }
+    /// When a block produced at unix time `timestamp` is expected to be finalized, for
+    /// clients that follow finality closely enough to tell.
+    fn finality_timing(&self, _timestamp: u64) -> Option<FinalityTiming> {
+        None
+    }
+    /// Starts the background tasks of the client that the node should restart when they
+    /// panic or exit, for clients that have any.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn supervise(&self, _supervisor: &Supervisor) {}
+    /// Stops syncing and saves what the client persists. Blocks verified before the call may
+    /// still be delivered.
     fn shutdown(&self) -> Result<()>;
+    /// Resumes syncing after [`Consensus::shutdown`], for clients that can.
+    fn restart(&self) -> Result<()> {
+        Ok(())
+    }
 }
@@ -0,0 +1,173 @@
+//! A [Database] of plain files, one directory per namespace and one file per entry named
+//! after its hex key.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use eyre::Result;
+
+use super::{check_namespace, Database, EntryInfo};
+
+const PIN_SUFFIX: &str = ".pinned";
+const PARTIAL_SUFFIX: &str = ".partial";
+
+#[derive(Debug, Clone)]
+pub struct FileStore {
+    dir: PathBuf,
+}
+
+impl FileStore {
+    /// A store in `dir`, which is created on the first write.
+    pub fn open(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn namespace_dir(&self, namespace: &str) -> Result<PathBuf> {
+        check_namespace(namespace)?;
+        Ok(self.dir.join(namespace))
+    }
+}
+
+impl Database for FileStore {
+    /// Values are written aside and renamed into place, so a crash never leaves a partial
+    /// entry behind.
+    fn put(&self, namespace: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        let dir = self.namespace_dir(namespace)?;
+        fs::create_dir_all(&dir)?;
+
+        let path = dir.join(hex::encode(key));
+        let partial = dir.join(hex::encode(key) + PARTIAL_SUFFIX);
+        let written = fs::write(&partial, value).and_then(|_| fs::rename(&partial, &path));
+        if let Err(err) = written {
+            let _ = fs::remove_file(partial);
+            return Err(err.into());
+        }
+        Ok(())
+    }
+
+    fn get(&self, namespace: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let path = self.namespace_dir(namespace)?.join(hex::encode(key));
+        match fs::read(path) {
+            Ok(value) => Ok(Some(value)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn delete(&self, namespace: &str, keys: &[Vec<u8>]) -> Result<()> {
+        let dir = self.namespace_dir(namespace)?;
+        for key in keys {
+            let name = hex::encode(key);
+            remove_if_exists(dir.join(&name))?;
+            remove_if_exists(dir.join(name + PIN_SUFFIX))?;
+        }
+        Ok(())
+    }
+
+    fn set_pinned(&self, namespace: &str, key: &[u8], pinned: bool) -> Result<()> {
+        let dir = self.namespace_dir(namespace)?;
+        let marker = dir.join(hex::encode(key) + PIN_SUFFIX);
+        if pinned {
+            fs::create_dir_all(&dir)?;
+            fs::write(marker, [])?;
+            Ok(())
+        } else {
+            remove_if_exists(marker)
+        }
+    }
+
+    /// Sizes are file lengths and ages file modification times, so both are only as precise
+    /// as the filesystem keeps them. Files left over from interrupted writes are removed.
+    fn entries(&self, namespace: &str) -> Result<Vec<EntryInfo>> {
+        let dir = self.namespace_dir(namespace)?;
+        let read_dir = match fs::read_dir(&dir) {
+            Ok(read_dir) => read_dir,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut files = Vec::new();
+        let mut pins = HashSet::new();
+        for file in read_dir {
+            let file = file?;
+            let name = file.file_name().to_string_lossy().into_owned();
+            if name.ends_with(PARTIAL_SUFFIX) {
+                let _ = fs::remove_file(file.path());
+                continue;
+            }
+            match name.strip_suffix(PIN_SUFFIX) {
+                Some(pinned) => {
+                    pins.insert(pinned.to_string());
+                }
+                None => files.push((name, file.metadata()?)),
+            }
+        }
+
+        let entries = files
+            .into_iter()
+            .filter_map(|(name, metadata)| {
+                let key = hex::decode(&name).ok()?;
+                let written_at = metadata
+                    .modified()
+                    .ok()?
+                    .duration_since(UNIX_EPOCH)
+                    .ok()?
+                    .as_millis() as u64;
+
+                Some(EntryInfo {
+                    key,
+                    size: metadata.len(),
+                    written_at,
+                    pinned: pins.contains(&name),
+                })
+            })
+            .collect();
+
+        Ok(entries)
+    }
+}
+
+fn remove_if_exists(path: PathBuf) -> Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => Ok(()),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::database::check_conformance;
+
+    #[test]
+    fn test_conformance() {
+        let dir = tempfile::tempdir().unwrap();
+        check_conformance(&FileStore::open(dir.path()));
+    }
+
+    #[test]
+    fn test_interrupted_write_cleaned_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileStore::open(dir.path());
+        store.put("code", &[1], &[1]).unwrap();
+
+        let partial = dir.path().join("code").join("02.partial");
+        fs::write(&partial, [2]).unwrap();
+        let keys = store
+            .entries("code")
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.key)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, [vec![1]]);
+        assert!(!partial.exists());
+    }
+}
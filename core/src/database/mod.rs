@@ -0,0 +1,177 @@
+//! Namespaced key-value persistence shared by every client.
+//!
+//! Everything a client keeps across restarts goes through a [Database]: the checkpoint it
+//! resumes from, cached contract code and the entries each client declares namespaces for.
+//! Values are opaque bytes grouped into namespaces, so a backend only has to store bytes
+//! under byte keys. The filesystem and an embedded store back native clients, while wasm
+//! clients bring a browser backed one.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use alloy::primitives::B256;
+use eyre::Result;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod file;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod redb;
+
+/// The namespace holding the latest checkpoint, under [`LATEST_CHECKPOINT_KEY`].
+pub const LATEST_CHECKPOINT: &str = "latest_checkpoint";
+pub const LATEST_CHECKPOINT_KEY: &[u8] = b"latest";
+
+/// A finalized checkpoint and the slot of its block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub root: B256,
+    pub slot: u64,
+}
+
+/// A stored entry, as seen by garbage collection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryInfo {
+    pub key: Vec<u8>,
+    pub size: u64,
+    /// Unix time in milliseconds.
+    pub written_at: u64,
+    pub pinned: bool,
+}
+
+pub trait Database: Debug + Send + Sync + 'static {
+    /// Stores `value` under `key` in `namespace`, replacing any earlier value.
+    fn put(&self, namespace: &str, key: &[u8], value: &[u8]) -> Result<()>;
+    fn get(&self, namespace: &str, key: &[u8]) -> Result<Option<Vec<u8>>>;
+    /// Deletes `keys` from `namespace` together with their pins. Missing keys are skipped.
+    fn delete(&self, namespace: &str, keys: &[Vec<u8>]) -> Result<()>;
+    /// Pinned entries count towards a namespace's usage but are never garbage collected. A
+    /// key can be pinned before it is written.
+    fn set_pinned(&self, namespace: &str, key: &[u8], pinned: bool) -> Result<()>;
+    /// Lists the entries of `namespace` with what garbage collection needs to know of them.
+    fn entries(&self, namespace: &str) -> Result<Vec<EntryInfo>>;
+
+    fn save_checkpoint(&self, checkpoint: B256) -> Result<()> {
+        self.put(
+            LATEST_CHECKPOINT,
+            LATEST_CHECKPOINT_KEY,
+            checkpoint.as_slice(),
+        )
+    }
+
+    fn load_checkpoint(&self) -> Result<B256> {
+        match self.get(LATEST_CHECKPOINT, LATEST_CHECKPOINT_KEY)? {
+            Some(value) => decode_checkpoint(&value)
+                .map(|(root, _)| root)
+                .ok_or_else(|| eyre::eyre!("malformed checkpoint")),
+            None => eyre::bail!("checkpoint not found"),
+        }
+    }
+
+    /// Saves `checkpoint` together with its slot, so that the next start can tell how recent
+    /// it is.
+    fn save_checkpoint_with_slot(&self, checkpoint: Checkpoint) -> Result<()> {
+        self.put(
+            LATEST_CHECKPOINT,
+            LATEST_CHECKPOINT_KEY,
+            &encode_checkpoint(checkpoint),
+        )
+    }
+
+    /// The checkpoint last saved with its slot, if any.
+    fn load_checkpoint_with_slot(&self) -> Result<Option<Checkpoint>> {
+        let value = self.get(LATEST_CHECKPOINT, LATEST_CHECKPOINT_KEY)?;
+        Ok(value
+            .as_deref()
+            .and_then(decode_checkpoint)
+            .and_then(|(root, slot)| Some(Checkpoint { root, slot: slot? })))
+    }
+}
+
+impl<D: Database + ?Sized> Database for Arc<D> {
+    fn put(&self, namespace: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        (**self).put(namespace, key, value)
+    }
+
+    fn get(&self, namespace: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        (**self).get(namespace, key)
+    }
+
+    fn delete(&self, namespace: &str, keys: &[Vec<u8>]) -> Result<()> {
+        (**self).delete(namespace, keys)
+    }
+
+    fn set_pinned(&self, namespace: &str, key: &[u8], pinned: bool) -> Result<()> {
+        (**self).set_pinned(namespace, key, pinned)
+    }
+
+    fn entries(&self, namespace: &str) -> Result<Vec<EntryInfo>> {
+        (**self).entries(namespace)
+    }
+
+    fn save_checkpoint(&self, checkpoint: B256) -> Result<()> {
+        (**self).save_checkpoint(checkpoint)
+    }
+
+    fn load_checkpoint(&self) -> Result<B256> {
+        (**self).load_checkpoint()
+    }
+
+    fn save_checkpoint_with_slot(&self, checkpoint: Checkpoint) -> Result<()> {
+        (**self).save_checkpoint_with_slot(checkpoint)
+    }
+
+    fn load_checkpoint_with_slot(&self) -> Result<Option<Checkpoint>> {
+        (**self).load_checkpoint_with_slot()
+    }
+}
+
+/// Runs `f`, which reads or writes a database, on the blocking thread pool, since backends
+/// block on their I/O. `None` if the runtime shut down before it ran.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    tokio::task::spawn_blocking(f).await.ok()
+}
+
+/// Wasm has no threads to move `f` to, but none of its backends block either.
+#[cfg(target_arch = "wasm32")]
+pub(crate) async fn blocking<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    Some(f())
+}
+
+/// Namespaces name directories and tables, so they are kept to ascii alphanumerics,
+/// underscores and dashes.
+pub fn check_namespace(namespace: &str) -> Result<()> {
+    let valid = !namespace.is_empty()
+        && namespace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+
+    if !valid {
+        eyre::bail!("invalid database namespace {namespace:?}");
+    }
+    Ok(())
+}
+
+/// The root of a checkpoint followed by its slot, the layout the checkpoint file has always
+/// had.
+pub fn encode_checkpoint(checkpoint: Checkpoint) -> Vec<u8> {
+    let mut value = checkpoint.root.to_vec();
+    value.extend(checkpoint.slot.to_be_bytes());
+    value
+}
+
+/// A checkpoint root and, when it was saved with one, its slot.
+pub fn decode_checkpoint(value: &[u8]) -> Option<(B256, Option<u64>)> {
+    match value.len() {
+        32 => Some((B256::from_slice(value), None)),
+        40 => Some((
+            B256::from_slice(&value[..32]),
+            Some(u64::from_be_bytes(value[32..].try_into().unwrap())),
+        )),
+        _ => None,
+    }
+}
@@ -0,0 +1,206 @@
+//! A [Database] in a single embedded redb file, for long running native clients that would
+//! otherwise keep thousands of small files.
+//!
+//! Each namespace is a table of values prefixed by the time they were written, with the
+//! pins of a namespace kept in a table of their own. Every write is its own transaction, so
+//! an entry is either written in full or not at all.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ::redb::{ReadableTable, ReadableTableMetadata, TableDefinition, TableError};
+use eyre::Result;
+
+use super::{check_namespace, Database, EntryInfo};
+
+type Table<'a> = TableDefinition<'a, &'static [u8], &'static [u8]>;
+
+/// Open files by path. Redb locks the file it opens, so the clients and stores of one process
+/// share a handle instead.
+static OPEN: OnceLock<Mutex<HashMap<PathBuf, Weak<::redb::Database>>>> = OnceLock::new();
+
+#[derive(Clone)]
+pub struct RedbStore {
+    path: PathBuf,
+    db: Arc<::redb::Database>,
+}
+
+impl std::fmt::Debug for RedbStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedbStore")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RedbStore {
+    /// Opens the store at `path`, creating the file and its directory if needed.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let path = match (path.parent(), path.file_name()) {
+            (Some(dir), Some(name)) if !dir.as_os_str().is_empty() => {
+                dir.canonicalize()?.join(name)
+            }
+            _ => path.to_path_buf(),
+        };
+
+        let mut open = OPEN.get_or_init(Mutex::default).lock().unwrap();
+        let db = match open.get(&path).and_then(Weak::upgrade) {
+            Some(db) => db,
+            None => {
+                let db = Arc::new(::redb::Database::create(&path)?);
+                open.insert(path.clone(), Arc::downgrade(&db));
+                db
+            }
+        };
+
+        Ok(Self { path, db })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write(
+        &self,
+        table: &str,
+        f: impl FnOnce(&mut ::redb::Table<'_, &'static [u8], &'static [u8]>) -> Result<()>,
+    ) -> Result<()> {
+        let txn = self.db.begin_write()?;
+        {
+            let mut table = txn.open_table(Table::new(table))?;
+            f(&mut table)?;
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    /// Reads from `table`, which is empty until something is first written to it.
+    fn read<T: Default>(
+        &self,
+        table: &str,
+        f: impl FnOnce(&::redb::ReadOnlyTable<&'static [u8], &'static [u8]>) -> Result<T>,
+    ) -> Result<T> {
+        let txn = self.db.begin_read()?;
+        match txn.open_table(Table::new(table)) {
+            Ok(table) => f(&table),
+            Err(TableError::TableDoesNotExist(_)) => Ok(T::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+impl Database for RedbStore {
+    fn put(&self, namespace: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        check_namespace(namespace)?;
+        let mut stored = now_millis().to_be_bytes().to_vec();
+        stored.extend_from_slice(value);
+        self.write(namespace, |table| {
+            table.insert(key, stored.as_slice())?;
+            Ok(())
+        })
+    }
+
+    fn get(&self, namespace: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        check_namespace(namespace)?;
+        self.read(namespace, |table| {
+            Ok(table.get(key)?.map(|value| value.value()[8..].to_vec()))
+        })
+    }
+
+    fn delete(&self, namespace: &str, keys: &[Vec<u8>]) -> Result<()> {
+        check_namespace(namespace)?;
+        let txn = self.db.begin_write()?;
+        {
+            let mut values = txn.open_table(Table::new(namespace))?;
+            let mut pins = txn.open_table(Table::new(&pins_table(namespace)))?;
+            for key in keys {
+                values.remove(key.as_slice())?;
+                pins.remove(key.as_slice())?;
+            }
+        }
+        txn.commit()?;
+        Ok(())
+    }
+
+    fn set_pinned(&self, namespace: &str, key: &[u8], pinned: bool) -> Result<()> {
+        check_namespace(namespace)?;
+        self.write(&pins_table(namespace), |pins| {
+            if pinned {
+                pins.insert(key, [0u8; 0].as_slice())?;
+            } else {
+                pins.remove(key)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Sizes are those of the values as given, without the timestamp kept beside them.
+    fn entries(&self, namespace: &str) -> Result<Vec<EntryInfo>> {
+        check_namespace(namespace)?;
+        let pinned = self.read(&pins_table(namespace), |pins| {
+            let mut pinned = Vec::with_capacity(pins.len()? as usize);
+            for pin in pins.iter()? {
+                pinned.push(pin?.0.value().to_vec());
+            }
+            Ok(pinned)
+        })?;
+
+        self.read(namespace, |table| {
+            let mut entries = Vec::with_capacity(table.len()? as usize);
+            for entry in table.iter()? {
+                let (key, value) = entry?;
+                let (key, value) = (key.value(), value.value());
+                entries.push(EntryInfo {
+                    key: key.to_vec(),
+                    size: value.len().saturating_sub(8) as u64,
+                    written_at: u64::from_be_bytes(value[..8].try_into()?),
+                    pinned: pinned.iter().any(|pin| pin == key),
+                });
+            }
+            Ok(entries)
+        })
+    }
+}
+
+fn pins_table(namespace: &str) -> String {
+    format!("{namespace}/pinned")
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::database::check_conformance;
+
+    #[test]
+    fn test_conformance() {
+        let dir = tempfile::tempdir().unwrap();
+        check_conformance(&RedbStore::open(dir.path().join("helios.redb")).unwrap());
+    }
+
+    #[test]
+    fn test_reopened_in_process_shares_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("helios.redb");
+        let first = RedbStore::open(&path).unwrap();
+        let second = RedbStore::open(&path).unwrap();
+
+        first.put("usage", &[1], &[2]).unwrap();
+        assert_eq!(second.get("usage", &[1]).unwrap(), Some(vec![2]));
+
+        drop((first, second));
+        let reopened = RedbStore::open(&path).unwrap();
+        assert_eq!(reopened.get("usage", &[1]).unwrap(), Some(vec![2]));
+    }
+}
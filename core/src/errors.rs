@@ -1,14 +1,16 @@
+use alloy::primitives::Bytes;
 use eyre::Report;
 use thiserror::Error;
 
 use crate::{
-    execution::errors::{EvmError, ExecutionError},
+    client::ens::EnsError,
+    execution::errors::{block_not_found, EvmError, ExecutionError},
     types::BlockTag,
 };
 
 #[derive(Debug, Error)]
 pub enum ClientError {
-    #[error("block not found: {0}")]
+    #[error("{}", block_not_found(.0))]
     BlockNotFound(BlockTag),
     #[error("out of sync: {0} seconds behind")]
     OutOfSync(u64),
@@ -16,6 +18,8 @@ pub enum ClientError {
     ExecutionError(ExecutionError),
     #[error("evm error: {0}")]
     EvmError(EvmError),
+    #[error("ens error: {0}")]
+    EnsError(EnsError),
     #[error("consensus error: {0}")]
     ConsensusError(Report),
     #[error("internal error: {0}")]
@@ -31,6 +35,12 @@ impl From<ExecutionError> for ClientError {
     }
 }
 
+impl From<EnsError> for ClientError {
+    fn from(value: EnsError) -> Self {
+        ClientError::EnsError(value)
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 impl From<ClientError> for jsonrpsee::core::Error {
     fn from(value: ClientError) -> Self {
@@ -38,18 +48,237 @@ impl From<ClientError> for jsonrpsee::core::Error {
     }
 }
 
+/// A failed request to an upstream provider.
 #[derive(Debug, Error)]
 #[error("rpc error on method: {method}, message: {error}")]
-pub struct RpcError<E: ToString> {
+pub struct RpcError {
     method: String,
-    error: E,
+    error: String,
 }
 
-impl<E: ToString> RpcError<E> {
-    pub fn new(method: &str, err: E) -> Self {
+impl RpcError {
+    pub fn new<E: ToString>(method: &str, err: E) -> Self {
         Self {
             method: method.to_string(),
-            error: err,
+            error: err.to_string(),
+        }
+    }
+
+    /// The provider method that failed.
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+}
+
+/// Error codes of the JSON-RPC server besides those of the JSON-RPC spec.
+pub mod codes {
+    /// A call reverted, with the revert data in the error data.
+    pub const EXECUTION_REVERTED: i32 = 3;
+    /// A call halted without revert data, or a transaction was refused, as geth reports them.
+    pub const SERVER_ERROR: i32 = -32000;
+    pub const RESOURCE_NOT_FOUND: i32 = -32001;
+    /// The client is not yet synced or consensus cannot serve the request.
+    pub const RESOURCE_UNAVAILABLE: i32 = -32002;
+    pub const LIMIT_EXCEEDED: i32 = -32005;
+    /// The execution provider failed to answer.
+    pub const UPSTREAM_ERROR: i32 = -32010;
+    /// The execution provider answered with data that does not verify against consensus.
+    pub const VERIFICATION_FAILED: i32 = -32011;
+    pub const INVALID_PARAMS: i32 = -32602;
+    pub const INTERNAL_ERROR: i32 = -32603;
+}
+
+/// An error as served by the JSON-RPC server, classified by the code clients switch on.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ServerError {
+    #[error("{message}")]
+    Reverted { message: String, data: Bytes },
+    #[error("{0}")]
+    Failed(String),
+    #[error("{0}")]
+    NotFound(String),
+    #[error("{0}")]
+    Unavailable(String),
+    #[error("{0}")]
+    LimitExceeded(String),
+    #[error("{0}")]
+    Upstream(String),
+    #[error("{0}")]
+    Verification(String),
+    #[error("{0}")]
+    InvalidParams(String),
+    #[error("{0}")]
+    Internal(String),
+}
+
+impl ServerError {
+    pub fn code(&self) -> i32 {
+        match self {
+            ServerError::Reverted { .. } => codes::EXECUTION_REVERTED,
+            ServerError::Failed(_) => codes::SERVER_ERROR,
+            ServerError::NotFound(_) => codes::RESOURCE_NOT_FOUND,
+            ServerError::Unavailable(_) => codes::RESOURCE_UNAVAILABLE,
+            ServerError::LimitExceeded(_) => codes::LIMIT_EXCEEDED,
+            ServerError::Upstream(_) => codes::UPSTREAM_ERROR,
+            ServerError::Verification(_) => codes::VERIFICATION_FAILED,
+            ServerError::InvalidParams(_) => codes::INVALID_PARAMS,
+            ServerError::Internal(_) => codes::INTERNAL_ERROR,
+        }
+    }
+
+    /// The revert data of a reverted call, served as the error data.
+    pub fn data(&self) -> Option<&Bytes> {
+        match self {
+            ServerError::Reverted { data, .. } => Some(data),
+            _ => None,
         }
     }
+
+    fn from_client(err: &ClientError, message: String) -> Self {
+        match err {
+            ClientError::BlockNotFound(_) => ServerError::NotFound(message),
+            ClientError::OutOfSync(_) | ClientError::ConsensusError(_) => {
+                ServerError::Unavailable(message)
+            }
+            ClientError::ExecutionError(err) => Self::from_execution(err, message),
+            // served with the message of the evm, as geth words it
+            ClientError::EvmError(err) => Self::from_evm(err, err.to_string()),
+            ClientError::EnsError(EnsError::InvalidName(_)) => ServerError::InvalidParams(message),
+            ClientError::EnsError(_) => ServerError::Failed(message),
+            ClientError::InternalError(err) => Self::from_report(err, message),
+        }
+    }
+
+    fn from_evm(err: &EvmError, message: String) -> Self {
+        match err {
+            EvmError::Revert(Some(data)) => ServerError::Reverted {
+                message,
+                data: data.clone(),
+            },
+            EvmError::Revert(None) | EvmError::InvalidTransaction(_) | EvmError::CcipRead(_) => {
+                ServerError::Failed(message)
+            }
+            EvmError::RpcError(err) => Self::from_report(err, message),
+            EvmError::UnsupportedTracer(_) | EvmError::UnsupportedBlobTransaction(_) => {
+                ServerError::InvalidParams(message)
+            }
+            EvmError::TraceTooLarge(_) | EvmError::TooManyCalls(_) | EvmError::MissingState(..) => {
+                ServerError::LimitExceeded(message)
+            }
+            EvmError::Generic(_) => ServerError::Internal(message),
+        }
+    }
+
+    fn from_execution(err: &ExecutionError, message: String) -> Self {
+        use ExecutionError::*;
+
+        match err {
+            InvalidAccountProof(..)
+            | InvalidStorageProof(..)
+            | CodeHashMismatch(..)
+            | ReceiptRootMismatch(_)
+            | ReceiptMismatch(_)
+            | InconsistentReceipt(..)
+            | MissingLog(..)
+            | OmittedLogs(_)
+            | IncorrectRpcNetwork()
+            | InvalidHeaderHash(_)
+            | BlockHashMismatch(..)
+            | BlockNumberMismatch(..)
+            | BlockBodyMismatch(..)
+            | TransactionHashMismatch(..)
+            | MisplacedTransaction(..)
+            | InvalidProofHeader(_)
+            | UntrustedProof(..)
+            | BlockReceiptsRootMismatch(_)
+            | FeeHistoryRangeMismatch(..)
+            | FeeHistoryMismatch(..)
+            | LogFilterMismatch()
+            | BrokenHeaderChain(..)
+            | IncompleteProviderResponse { .. } => ServerError::Verification(message),
+            BlockNotFound(_)
+            | BlockHashNotFound(_)
+            | FilterNotFound(_)
+            | NoReceiptForTransaction(_)
+            | NoReceiptsForBlock(_)
+            | UnverifiedReceiptBlock(_)
+            | BlockOutsideRetainedWindow(..)
+            | MissingPrevRandao(_)
+            | HistoricalDepthExceeded(..)
+            | SlotOutsideRetainedWindow(..)
+            | NoBeaconBlockForSlot(_)
+            | NoBeaconBlockForBlock(_)
+            | NoAttestedDescendant(_)
+            | UnanchoredBlockHash(_)
+            | PreMergeBlock(..)
+            | MissedSubscriptionEvents(..) => ServerError::NotFound(message),
+            HistoricalStatePruned(_) | ProviderPayloadTooLarge(..) | UnmeasuredPayload(_) => {
+                ServerError::Upstream(message)
+            }
+            TooManyLogsToProve(..) | TooManyLogBlocks(..) | PinBudgetExceeded(_) => {
+                ServerError::LimitExceeded(message)
+            }
+            InvalidBlockRange(..)
+            | InvalidLogCursor(_)
+            | StaleLogCursor(..)
+            | InvalidLogPageSize(..)
+            | InvalidPercentile(_)
+            | ConflictingStateOverride(_)
+            | BlockNotEncodable(_)
+            | UnsupportedProofVersion(..) => ServerError::InvalidParams(message),
+            InvalidRawTransaction(_)
+            | InvalidChainId(..)
+            | NonceTooLow(..)
+            | InsufficientFunds(..)
+            | BroadcastRejected(_)
+            | TransactionDropped(..)
+            | ReceiptTimeout(..)
+            | BlobGasNotPriced(_) => ServerError::Failed(message),
+        }
+    }
+
+    /// Classifies by the first error of the chain this crate knows, which is where the
+    /// failure came from whatever context was added on the way up.
+    fn from_report(err: &Report, message: String) -> Self {
+        for cause in err.chain() {
+            if let Some(err) = cause.downcast_ref::<ClientError>() {
+                return Self::from_client(err, message);
+            }
+            if let Some(err) = cause.downcast_ref::<EvmError>() {
+                return Self::from_evm(err, message);
+            }
+            if let Some(err) = cause.downcast_ref::<ExecutionError>() {
+                return Self::from_execution(err, message);
+            }
+            if cause.downcast_ref::<RpcError>().is_some() {
+                return ServerError::Upstream(message);
+            }
+        }
+        ServerError::Internal(message)
+    }
+}
+
+impl From<ClientError> for ServerError {
+    fn from(err: ClientError) -> Self {
+        let message = err.to_string();
+        Self::from_client(&err, message)
+    }
+}
+
+impl From<Report> for ServerError {
+    fn from(err: Report) -> Self {
+        let message = err.to_string();
+        Self::from_report(&err, message)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl From<ServerError> for jsonrpsee::types::error::ErrorObjectOwned {
+    fn from(err: ServerError) -> Self {
+        jsonrpsee::types::error::ErrorObject::owned(
+            err.code(),
+            err.to_string(),
+            err.data().cloned(),
+        )
+    }
 }
@@ -0,0 +1,378 @@
+use alloy::primitives::{b256, Address, Log, B256, I256, U256};
+use alloy::sol_types::SolValue;
+use serde::{Deserialize, Serialize};
+
+// keccak256("Transfer(address,address,uint256)")
+const TRANSFER_TOPIC: B256 =
+    b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+// keccak256("Approval(address,address,uint256)")
+const APPROVAL_TOPIC: B256 =
+    b256!("8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925");
+// keccak256("ApprovalForAll(address,address,bool)")
+const APPROVAL_FOR_ALL_TOPIC: B256 =
+    b256!("17307eab39ab6107e8899845ad3d59bd9653f200f220920489ca2b5937696c31");
+
+/// Selector of `name()`.
+pub const NAME_SELECTOR: [u8; 4] = [0x06, 0xfd, 0xde, 0x03];
+/// Selector of `symbol()`.
+pub const SYMBOL_SELECTOR: [u8; 4] = [0x95, 0xd8, 0x9b, 0x41];
+/// Selector of `decimals()`.
+pub const DECIMALS_SELECTOR: [u8; 4] = [0x31, 0x3c, 0xe5, 0x67];
+
+/// Result of simulating a transaction, summarizing how it affects the sender's assets.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetChangeReport {
+    pub success: bool,
+    pub gas_used: u64,
+    /// Change of the sender's ether balance, including the transaction fee.
+    pub eth_delta: I256,
+    pub erc20_changes: Vec<Erc20Change>,
+    pub erc721_changes: Vec<Erc721Change>,
+    pub approvals: Vec<Approval>,
+    /// Metadata of every token in the changes and approvals, read through the verified state.
+    pub tokens: Vec<TokenMetadata>,
+    pub revert_reason: Option<String>,
+}
+
+impl AssetChangeReport {
+    /// The tokens the changes and approvals name, each once, in the order they appear.
+    pub fn token_addresses(&self) -> Vec<Address> {
+        let mut tokens = Vec::new();
+        let named = self
+            .erc20_changes
+            .iter()
+            .map(|change| change.token)
+            .chain(self.erc721_changes.iter().map(|change| change.token))
+            .chain(self.approvals.iter().map(|approval| approval.token));
+        for token in named {
+            if !tokens.contains(&token) {
+                tokens.push(token);
+            }
+        }
+        tokens
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Erc20Change {
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+    pub amount: U256,
+    /// Signed change of the sender's token balance caused by this transfer.
+    pub delta: I256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Erc721Change {
+    pub token: Address,
+    pub from: Address,
+    pub to: Address,
+    pub token_id: U256,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Approval {
+    pub token: Address,
+    pub owner: Address,
+    pub spender: Address,
+    /// Approved amount for ERC-20 approvals.
+    pub amount: Option<U256>,
+    /// Approved token for ERC-721 approvals.
+    pub token_id: Option<U256>,
+    /// Set for `ApprovalForAll` events.
+    pub approved_for_all: Option<bool>,
+}
+
+/// What a token reports about itself. A field is left out when the token does not implement
+/// its getter or answers with something that does not decode.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenMetadata {
+    pub token: Address,
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub decimals: Option<u8>,
+}
+
+impl TokenMetadata {
+    /// Decodes the answers of the `name()`, `symbol()` and `decimals()` getters of `token`,
+    /// each `None` if the getter reverted.
+    pub fn decode(
+        token: Address,
+        name: Option<&[u8]>,
+        symbol: Option<&[u8]>,
+        decimals: Option<&[u8]>,
+    ) -> Self {
+        Self {
+            token,
+            name: name.and_then(decode_text),
+            symbol: symbol.and_then(decode_text),
+            decimals: decimals.and_then(decode_decimals),
+        }
+    }
+}
+
+/// Reads a string getter's answer, which older tokens return as a `bytes32` rather than an
+/// ABI encoded string.
+fn decode_text(output: &[u8]) -> Option<String> {
+    if let Ok(text) = String::abi_decode(output, true) {
+        return Some(text);
+    }
+    if output.len() != 32 {
+        return None;
+    }
+    let end = output.iter().position(|&byte| byte == 0).unwrap_or(32);
+    String::from_utf8(output[..end].to_vec()).ok()
+}
+
+fn decode_decimals(output: &[u8]) -> Option<u8> {
+    let decimals = U256::abi_decode(output, true).ok()?;
+    u8::try_from(decimals).ok()
+}
+
+/// Collects the token transfers and approvals in `logs` that involve `sender`.
+///
+/// ERC-20 and ERC-721 share the `Transfer` and `Approval` signatures and are told apart by
+/// the number of indexed topics, since ERC-721 indexes the token id.
+pub fn collect_asset_changes(sender: Address, logs: &[Log], report: &mut AssetChangeReport) {
+    for log in logs {
+        let topics = log.topics();
+        let data = log.data.data.as_ref();
+
+        match (topics.first(), topics.len()) {
+            (Some(sig), 3) if *sig == TRANSFER_TOPIC && data.len() == 32 => {
+                let from = topic_address(&topics[1]);
+                let to = topic_address(&topics[2]);
+                if from != sender && to != sender {
+                    continue;
+                }
+
+                let amount = U256::from_be_slice(data);
+                let magnitude = I256::try_from(amount).unwrap_or(I256::MAX);
+                let delta = match (from == sender, to == sender) {
+                    (true, true) => I256::ZERO,
+                    (true, false) => -magnitude,
+                    _ => magnitude,
+                };
+
+                report.erc20_changes.push(Erc20Change {
+                    token: log.address,
+                    from,
+                    to,
+                    amount,
+                    delta,
+                });
+            }
+            (Some(sig), 4) if *sig == TRANSFER_TOPIC => {
+                let from = topic_address(&topics[1]);
+                let to = topic_address(&topics[2]);
+                if from != sender && to != sender {
+                    continue;
+                }
+
+                report.erc721_changes.push(Erc721Change {
+                    token: log.address,
+                    from,
+                    to,
+                    token_id: topics[3].into(),
+                });
+            }
+            (Some(sig), 3) if *sig == APPROVAL_TOPIC && data.len() == 32 => {
+                let owner = topic_address(&topics[1]);
+                if owner == sender {
+                    report.approvals.push(Approval {
+                        token: log.address,
+                        owner,
+                        spender: topic_address(&topics[2]),
+                        amount: Some(U256::from_be_slice(data)),
+                        token_id: None,
+                        approved_for_all: None,
+                    });
+                }
+            }
+            (Some(sig), 4) if *sig == APPROVAL_TOPIC => {
+                let owner = topic_address(&topics[1]);
+                if owner == sender {
+                    report.approvals.push(Approval {
+                        token: log.address,
+                        owner,
+                        spender: topic_address(&topics[2]),
+                        amount: None,
+                        token_id: Some(topics[3].into()),
+                        approved_for_all: None,
+                    });
+                }
+            }
+            (Some(sig), 3) if *sig == APPROVAL_FOR_ALL_TOPIC && data.len() == 32 => {
+                let owner = topic_address(&topics[1]);
+                if owner == sender {
+                    report.approvals.push(Approval {
+                        token: log.address,
+                        owner,
+                        spender: topic_address(&topics[2]),
+                        amount: None,
+                        token_id: None,
+                        approved_for_all: Some(data[31] != 0),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn topic_address(topic: &B256) -> Address {
+    Address::from_word(*topic)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, Bytes, LogData};
+
+    use super::*;
+
+    const SENDER: Address = address!("00000000000000000000000000000000000000aa");
+    const OTHER: Address = address!("00000000000000000000000000000000000000bb");
+    const TOKEN_A: Address = address!("00000000000000000000000000000000000000c1");
+    const TOKEN_B: Address = address!("00000000000000000000000000000000000000c2");
+
+    fn log(token: Address, topics: Vec<B256>, data: Vec<u8>) -> Log {
+        Log {
+            address: token,
+            data: LogData::new_unchecked(topics, Bytes::from(data)),
+        }
+    }
+
+    fn erc20_transfer(token: Address, from: Address, to: Address, amount: u64) -> Log {
+        log(
+            token,
+            vec![TRANSFER_TOPIC, from.into_word(), to.into_word()],
+            U256::from(amount).to_be_bytes_vec(),
+        )
+    }
+
+    #[test]
+    fn test_simple_transfer() {
+        let mut report = AssetChangeReport::default();
+        let logs = vec![
+            erc20_transfer(TOKEN_A, SENDER, OTHER, 100),
+            // unrelated transfer between other accounts is ignored
+            erc20_transfer(TOKEN_A, OTHER, TOKEN_B, 5),
+        ];
+
+        collect_asset_changes(SENDER, &logs, &mut report);
+
+        assert_eq!(report.erc20_changes.len(), 1);
+        assert_eq!(report.erc20_changes[0].amount, U256::from(100));
+        assert_eq!(
+            report.erc20_changes[0].delta,
+            I256::try_from(-100i64).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_swap() {
+        let mut report = AssetChangeReport::default();
+        let logs = vec![
+            erc20_transfer(TOKEN_A, SENDER, OTHER, 1_000),
+            erc20_transfer(TOKEN_B, OTHER, SENDER, 42),
+        ];
+
+        collect_asset_changes(SENDER, &logs, &mut report);
+
+        let deltas = report
+            .erc20_changes
+            .iter()
+            .map(|change| (change.token, change.delta))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            deltas,
+            vec![
+                (TOKEN_A, I256::try_from(-1_000i64).unwrap()),
+                (TOKEN_B, I256::try_from(42i64).unwrap()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_approval() {
+        let mut report = AssetChangeReport::default();
+        let logs = vec![
+            log(
+                TOKEN_A,
+                vec![APPROVAL_TOPIC, SENDER.into_word(), OTHER.into_word()],
+                U256::MAX.to_be_bytes_vec(),
+            ),
+            log(
+                TOKEN_B,
+                vec![
+                    APPROVAL_FOR_ALL_TOPIC,
+                    SENDER.into_word(),
+                    OTHER.into_word(),
+                ],
+                U256::from(1).to_be_bytes_vec(),
+            ),
+        ];
+
+        collect_asset_changes(SENDER, &logs, &mut report);
+
+        assert_eq!(report.approvals.len(), 2);
+        assert_eq!(report.approvals[0].spender, OTHER);
+        assert_eq!(report.approvals[0].amount, Some(U256::MAX));
+        assert_eq!(report.approvals[1].approved_for_all, Some(true));
+        assert!(report.erc20_changes.is_empty());
+    }
+
+    #[test]
+    fn test_token_metadata_decoding() {
+        let name = "Token".to_string().abi_encode();
+        let mut symbol = [0u8; 32];
+        symbol[..3].copy_from_slice(b"MKR");
+        let decimals = U256::from(18).abi_encode();
+
+        let metadata = TokenMetadata::decode(TOKEN_A, Some(&name), Some(&symbol), Some(&decimals));
+        assert_eq!(metadata.name.as_deref(), Some("Token"));
+        assert_eq!(metadata.symbol.as_deref(), Some("MKR"));
+        assert_eq!(metadata.decimals, Some(18));
+
+        // a getter that reverted, and answers that do not fit their type
+        let metadata = TokenMetadata::decode(
+            TOKEN_A,
+            None,
+            Some(&[0xff; 7]),
+            Some(&U256::from(256).abi_encode()),
+        );
+        assert_eq!(
+            metadata,
+            TokenMetadata {
+                token: TOKEN_A,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_erc721_transfer() {
+        let mut report = AssetChangeReport::default();
+        let logs = vec![log(
+            TOKEN_A,
+            vec![
+                TRANSFER_TOPIC,
+                OTHER.into_word(),
+                SENDER.into_word(),
+                U256::from(7).into(),
+            ],
+            vec![],
+        )];
+
+        collect_asset_changes(SENDER, &logs, &mut report);
+
+        assert!(report.erc20_changes.is_empty());
+        assert_eq!(report.erc721_changes[0].token_id, U256::from(7));
+    }
+}
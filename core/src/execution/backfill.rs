@@ -0,0 +1,140 @@
+//! The blocks behind the first head applied after startup, fetched concurrently.
+//!
+//! Consensus only delivers heads, so a freshly started state holds a single block until the
+//! recent window is filled in behind it. Those blocks are fetched by number, several at a
+//! time, and each is only taken once it hashes to the parent hash of the verified block
+//! above it. Blocks that fail to fetch are retried on their own, so one flaky request does
+//! not restart the whole backfill, and a block served from another branch is fetched once
+//! more by the hash expected of it.
+//!
+//! On wasm the fetches share the page's thread but are still in flight together. The waits
+//! between retries run on browser timers, which background tabs throttle, so retries there
+//! may take much longer than configured.
+
+use std::future::Future;
+use std::time::Duration;
+
+use alloy::network::{primitives::HeaderResponse, BlockResponse};
+use alloy::primitives::B256;
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::config::ConfigDuration;
+use crate::network_spec::NetworkSpec;
+use crate::time::sleep;
+use crate::types::BlockTag;
+
+use super::constants::{
+    DEFAULT_BACKFILL_CONCURRENCY, DEFAULT_BACKFILL_RETRIES, DEFAULT_BACKFILL_RETRY_BACKOFF,
+};
+use super::limits::PayloadLimits;
+use super::rpc::ExecutionRpc;
+
+/// How the window behind the first head is backfilled, read from the `backfill` config
+/// table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackfillConfig {
+    /// Blocks fetched at once, zero to not backfill at all.
+    pub concurrency: usize,
+    /// Retries of a block that failed to fetch before the backfill stops at it.
+    pub max_retries: u32,
+    /// Wait before the first retry of a block, doubled for each retry after it.
+    pub retry_backoff: ConfigDuration,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_BACKFILL_CONCURRENCY,
+            max_retries: DEFAULT_BACKFILL_RETRIES,
+            retry_backoff: DEFAULT_BACKFILL_RETRY_BACKOFF.into(),
+        }
+    }
+}
+
+impl BackfillConfig {
+    /// Never backfills, leaving the window to fill up as heads arrive.
+    pub fn disabled() -> Self {
+        Self {
+            concurrency: 0,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.concurrency > 0
+    }
+
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.checked_pow(retry).unwrap_or(u32::MAX);
+        self.retry_backoff.as_duration().saturating_mul(factor)
+    }
+}
+
+/// Block `number` as the provider has it, or none once every retry failed.
+pub(crate) async fn fetch_by_number<N: NetworkSpec, R: ExecutionRpc<N>>(
+    rpc: &R,
+    number: u64,
+    config: &BackfillConfig,
+    limits: &PayloadLimits,
+) -> Option<N::BlockResponse> {
+    retrying(config, number, || async move {
+        let block = rpc
+            .get_block_by_number(BlockTag::Number(number), true)
+            .await?
+            .ok_or_else(|| eyre!("block {number} not found"))?;
+        checked::<N>(block, limits)
+    })
+    .await
+}
+
+/// The block of `hash`, for when the block fetched by number is not the expected parent.
+pub(crate) async fn fetch_by_hash<N: NetworkSpec, R: ExecutionRpc<N>>(
+    rpc: &R,
+    number: u64,
+    hash: B256,
+    config: &BackfillConfig,
+    limits: &PayloadLimits,
+) -> Option<N::BlockResponse> {
+    retrying(config, number, || async move {
+        checked::<N>(rpc.get_block(hash, true).await?, limits)
+    })
+    .await
+}
+
+/// Rejects blocks over the payload limits or whose header does not hash to the hash they
+/// claim, which are retried like any failed request.
+fn checked<N: NetworkSpec>(
+    block: N::BlockResponse,
+    limits: &PayloadLimits,
+) -> Result<N::BlockResponse> {
+    limits.check_block(&block)?;
+    if !N::is_hash_valid(&block) {
+        return Err(eyre!("invalid hash for block {}", block.header().hash()));
+    }
+    Ok(block)
+}
+
+async fn retrying<T, F, Fut>(config: &BackfillConfig, number: u64, mut fetch: F) -> Option<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut retry = 0;
+    loop {
+        match fetch().await {
+            Ok(value) => return Some(value),
+            Err(err) if retry < config.max_retries => {
+                debug!(target: "helios::execution", block = number, %err, "retrying backfill");
+                sleep(config.backoff(retry)).await;
+                retry += 1;
+            }
+            Err(err) => {
+                warn!(target: "helios::execution", block = number, %err, "backfill failed");
+                return None;
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Blob gas pricing of EIP-4844, with the blob schedule EIP-7691 raised in prague.
+//!
+//! The blob base fee of a block follows from the excess blob gas in its header, and the
+//! excess of the next block from the excess and blob gas used of its parent, so both are
+//! verified along with the header they are read from.
+
+use alloy::eips::eip4844::{fake_exponential, DATA_GAS_PER_BLOB};
+
+use crate::fork_schedule::ForkSchedule;
+
+/// The lowest blob base fee, paid while the blob gas used stays below the target.
+const MIN_BLOB_BASE_FEE: u128 = 1;
+
+/// The blob schedule of a fork.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlobParams {
+    /// Blob gas used per block above which the blob base fee rises.
+    pub target_blob_gas: u64,
+    pub max_blob_gas: u64,
+    /// How quickly the blob base fee follows the excess blob gas.
+    pub update_fraction: u128,
+}
+
+impl BlobParams {
+    pub const CANCUN: Self = Self {
+        target_blob_gas: 3 * DATA_GAS_PER_BLOB,
+        max_blob_gas: 6 * DATA_GAS_PER_BLOB,
+        update_fraction: 3_338_477,
+    };
+
+    pub const PRAGUE: Self = Self {
+        target_blob_gas: 6 * DATA_GAS_PER_BLOB,
+        max_blob_gas: 9 * DATA_GAS_PER_BLOB,
+        update_fraction: 5_007_716,
+    };
+
+    /// The schedule of the block produced at `timestamp`.
+    pub fn active_at(timestamp: u64, fork_schedule: &ForkSchedule) -> Self {
+        if timestamp >= fork_schedule.prague_timestamp {
+            Self::PRAGUE
+        } else {
+            Self::CANCUN
+        }
+    }
+
+    /// The blob base fee of a block with `excess_blob_gas`.
+    pub fn base_fee(&self, excess_blob_gas: u64) -> u128 {
+        fake_exponential(
+            MIN_BLOB_BASE_FEE,
+            excess_blob_gas as u128,
+            self.update_fraction,
+        )
+    }
+
+    /// The excess blob gas of the child of a block with `excess_blob_gas` that used
+    /// `blob_gas_used`.
+    pub fn next_excess_blob_gas(&self, excess_blob_gas: u64, blob_gas_used: u64) -> u64 {
+        (excess_blob_gas + blob_gas_used).saturating_sub(self.target_blob_gas)
+    }
+
+    /// The share of the blob gas a block may use that it did use, as reported in
+    /// `eth_feeHistory`.
+    pub fn used_ratio(&self, blob_gas_used: u64) -> f64 {
+        blob_gas_used as f64 / self.max_blob_gas as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_fee_vectors() {
+        let cancun = [
+            (0, 1),
+            (2_314_057, 1),
+            (2_314_058, 2),
+            (10 * 1024 * 1024, 23),
+        ];
+        for (excess, fee) in cancun {
+            assert_eq!(BlobParams::CANCUN.base_fee(excess), fee, "{excess}");
+        }
+
+        let prague = [
+            (0, 1),
+            (3_471_086, 1),
+            (3_471_087, 2),
+            (10 * 1024 * 1024, 8),
+        ];
+        for (excess, fee) in prague {
+            assert_eq!(BlobParams::PRAGUE.base_fee(excess), fee, "{excess}");
+        }
+    }
+
+    #[test]
+    fn test_next_excess_blob_gas() {
+        let params = BlobParams::CANCUN;
+        // a block at the target leaves the excess as it was
+        assert_eq!(
+            params.next_excess_blob_gas(1000, params.target_blob_gas),
+            1000
+        );
+        assert_eq!(
+            params.next_excess_blob_gas(1000, params.max_blob_gas),
+            1000 + 3 * DATA_GAS_PER_BLOB
+        );
+        assert_eq!(params.next_excess_blob_gas(1000, 0), 0);
+        assert_eq!(params.used_ratio(params.target_blob_gas), 0.5);
+
+        let schedule = ForkSchedule {
+            prague_timestamp: 100,
+            ..Default::default()
+        };
+        assert_eq!(BlobParams::active_at(99, &schedule), BlobParams::CANCUN);
+        assert_eq!(BlobParams::active_at(100, &schedule), BlobParams::PRAGUE);
+    }
+}
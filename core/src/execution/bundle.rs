@@ -0,0 +1,131 @@
+//! Types of `helios_simulateBundle`, which runs dependent transactions one after another on
+//! top of the verified state, as [`Evm::simulate_bundle`] does, and of `helios_callMany`,
+//! which runs independent calls against the same state. The account overrides are also
+//! those `eth_call` and `eth_estimateGas` take.
+//!
+//! [`Evm::simulate_bundle`]: crate::execution::evm::Evm::simulate_bundle
+
+use std::collections::HashMap;
+
+use alloy::primitives::{Address, Bytes, Log, B256, U256, U64};
+use revm::primitives::BlockEnv;
+use serde::{Deserialize, Serialize};
+
+/// Changes to an account applied before the first transaction runs. Fields left out keep
+/// their verified value.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    pub balance: Option<U256>,
+    pub nonce: Option<U64>,
+    pub code: Option<Bytes>,
+    /// Replaces the whole storage of the account, reading every other slot as zero. Cannot
+    /// be set together with `state_diff`.
+    pub state: Option<HashMap<B256, B256>>,
+    /// Replaces only the given slots.
+    pub state_diff: Option<HashMap<B256, B256>>,
+}
+
+impl AccountOverride {
+    /// Lays `other` over this override, the fields it sets taking precedence. Storage it
+    /// replaces whole drops the slots set here, while the slots it sets are added to them.
+    pub fn merge(&mut self, other: &AccountOverride) {
+        self.balance = other.balance.or(self.balance);
+        self.nonce = other.nonce.or(self.nonce);
+        self.code = other.code.clone().or(self.code.take());
+
+        match (&other.state, &other.state_diff) {
+            (Some(_), Some(_)) => {
+                // left for applying the overrides to reject
+                self.state = other.state.clone();
+                self.state_diff = other.state_diff.clone();
+            }
+            (Some(state), None) => {
+                self.state = Some(state.clone());
+                self.state_diff = None;
+            }
+            (None, Some(diff)) => match &mut self.state {
+                Some(state) => state.extend(diff),
+                None => self
+                    .state_diff
+                    .get_or_insert_with(HashMap::new)
+                    .extend(diff),
+            },
+            (None, None) => {}
+        }
+    }
+}
+
+/// Account overrides by address, the state override parameter of `eth_call`.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// Fields of the block the transactions execute in, in place of those of the anchor block.
+/// The state they start from is still that of the anchor.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockOverrides {
+    pub number: Option<U64>,
+    pub time: Option<U64>,
+    pub gas_limit: Option<U64>,
+    pub fee_recipient: Option<Address>,
+    pub prev_randao: Option<B256>,
+    pub base_fee_per_gas: Option<U256>,
+}
+
+impl BlockOverrides {
+    pub fn apply(&self, block: &mut BlockEnv) {
+        if let Some(number) = self.number {
+            block.number = U256::from(number);
+        }
+        if let Some(time) = self.time {
+            block.timestamp = U256::from(time);
+        }
+        if let Some(gas_limit) = self.gas_limit {
+            block.gas_limit = U256::from(gas_limit);
+        }
+        if let Some(fee_recipient) = self.fee_recipient {
+            block.coinbase = fee_recipient;
+        }
+        if let Some(prev_randao) = self.prev_randao {
+            block.prevrandao = Some(prev_randao);
+        }
+        if let Some(base_fee) = self.base_fee_per_gas {
+            block.basefee = base_fee;
+        }
+    }
+}
+
+/// The overrides a bundle is simulated with.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BundleOverrides {
+    pub state_overrides: StateOverride,
+    pub block_overrides: Option<BlockOverrides>,
+}
+
+/// Outcome of one transaction of a bundle.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedTransaction {
+    pub success: bool,
+    /// Output of a successful transaction, or the revert data of one that reverted.
+    pub return_data: Bytes,
+    pub gas_used: u64,
+    /// Logs emitted by a successful transaction, in order.
+    pub logs: Vec<Log>,
+    pub revert_reason: Option<String>,
+}
+
+/// Outcome of one call of a `helios_callMany` batch.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallOutcome {
+    pub success: bool,
+    /// Output of a successful call, or the revert data of one that reverted.
+    pub return_data: Bytes,
+    pub gas_used: u64,
+    pub revert_reason: Option<String>,
+    /// Why the call could not be executed at all, such as state failing to verify. The other
+    /// fields are then left empty.
+    pub error: Option<String>,
+}
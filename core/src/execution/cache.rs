@@ -0,0 +1,209 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use alloy::primitives::{Address, B256};
+
+use super::types::Account;
+
+/// Accounts already proven against the state root of a block, keyed by block hash.
+///
+/// Entries are only inserted after their proof verifies, and a block hash pins its state
+/// root, so a hit can be served without asking the execution provider again. The oldest
+/// entry is dropped once `capacity` is reached. Clones share the same entries.
+#[derive(Clone)]
+pub struct AccountCache {
+    inner: Arc<Mutex<Inner>>,
+    capacity: usize,
+}
+
+#[derive(Default)]
+struct Inner {
+    accounts: HashMap<(B256, Address), Account>,
+    order: VecDeque<(B256, Address)>,
+}
+
+impl AccountCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::default(),
+            capacity,
+        }
+    }
+
+    pub fn get(&self, block_hash: B256, address: Address) -> Option<Account> {
+        let inner = self.inner.lock().unwrap();
+        inner.accounts.get(&(block_hash, address)).cloned()
+    }
+
+    pub fn insert(&self, block_hash: B256, address: Address, account: Account) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        let key = (block_hash, address);
+        if inner.accounts.insert(key, account).is_some() {
+            return;
+        }
+
+        inner.order.push_back(key);
+        while inner.order.len() > self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.accounts.remove(&oldest);
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().accounts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Receipts of verified blocks, keyed by block hash.
+///
+/// Entries are only inserted once the receipts match the receipts root of their block, and
+/// the state removes them with the block, so a reorg never leaves receipts of an orphaned
+/// block behind. The least recently used block is dropped once `capacity` is reached.
+pub struct ReceiptCache<R> {
+    receipts: HashMap<B256, Vec<R>>,
+    order: VecDeque<B256>,
+    capacity: usize,
+}
+
+impl<R: Clone> ReceiptCache<R> {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            receipts: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+        }
+    }
+
+    /// Keeps the receipts of at most `capacity` blocks, dropping the least recently used.
+    pub fn resize(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.shrink();
+    }
+
+    pub fn get(&mut self, block_hash: B256) -> Option<Vec<R>> {
+        let receipts = self.receipts.get(&block_hash)?.clone();
+        self.touch(block_hash);
+        Some(receipts)
+    }
+
+    pub fn insert(&mut self, block_hash: B256, receipts: Vec<R>) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.receipts.insert(block_hash, receipts).is_some() {
+            self.touch(block_hash);
+        } else {
+            self.order.push_back(block_hash);
+            self.shrink();
+        }
+    }
+
+    pub fn remove(&mut self, block_hash: B256) {
+        if self.receipts.remove(&block_hash).is_some() {
+            self.order.retain(|hash| *hash != block_hash);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.receipts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn touch(&mut self, block_hash: B256) {
+        if let Some(position) = self.order.iter().position(|hash| *hash == block_hash) {
+            self.order.remove(position);
+            self.order.push_back(block_hash);
+        }
+    }
+
+    fn shrink(&mut self) {
+        while self.order.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.receipts.remove(&oldest);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+
+    use super::*;
+
+    fn account(balance: u64) -> Account {
+        Account {
+            balance: U256::from(balance),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_keyed_by_block() {
+        let cache = AccountCache::new(8);
+        let address = Address::repeat_byte(1);
+        cache.insert(B256::repeat_byte(1), address, account(1));
+        cache.insert(B256::repeat_byte(2), address, account(2));
+
+        let balance = |block| cache.get(block, address).map(|account| account.balance);
+        assert_eq!(balance(B256::repeat_byte(1)), Some(U256::from(1)));
+        assert_eq!(balance(B256::repeat_byte(2)), Some(U256::from(2)));
+        assert_eq!(balance(B256::repeat_byte(3)), None);
+    }
+
+    #[test]
+    fn test_evicts_oldest() {
+        let cache = AccountCache::new(2);
+        let block = B256::ZERO;
+        for byte in 1..=3 {
+            cache.insert(block, Address::repeat_byte(byte), account(byte.into()));
+        }
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(block, Address::repeat_byte(1)).is_none());
+        assert!(cache.get(block, Address::repeat_byte(3)).is_some());
+    }
+
+    #[test]
+    fn test_zero_capacity_disables() {
+        let cache = AccountCache::new(0);
+        cache.insert(B256::ZERO, Address::ZERO, account(1));
+        assert!(cache.is_empty());
+
+        let mut receipts = ReceiptCache::new(0);
+        receipts.insert(B256::ZERO, vec![1]);
+        assert!(receipts.is_empty());
+    }
+
+    #[test]
+    fn test_receipts_evict_least_recently_used() {
+        let mut cache = ReceiptCache::new(2);
+        cache.insert(B256::repeat_byte(1), vec![1]);
+        cache.insert(B256::repeat_byte(2), vec![2]);
+        // reading the first block keeps it over the second
+        assert_eq!(cache.get(B256::repeat_byte(1)), Some(vec![1]));
+        cache.insert(B256::repeat_byte(3), vec![3]);
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(B256::repeat_byte(2)).is_none());
+        assert!(cache.get(B256::repeat_byte(1)).is_some());
+
+        cache.remove(B256::repeat_byte(1));
+        assert!(cache.get(B256::repeat_byte(1)).is_none());
+        cache.resize(0);
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,283 @@
+//! CCIP-Read (EIP-3668) for verified calls.
+//!
+//! A contract keeping its answer offchain reverts with `OffchainLookup`, naming the gateways
+//! serving it, and expects the caller to fetch the answer and pass it to a callback, which
+//! checks it. Gateways are tried in the order listed. A url naming `{data}` is fetched with a
+//! GET, any other is posted the sender and call data as JSON. A failing gateway passes the
+//! lookup to the next one, except for a 4xx answer, which rejects the lookup as a whole.
+//!
+//! The answers of the gateways are not verified here, that is left to the callback, which
+//! runs in the verified evm like the call it continues. The urls come from the contract
+//! though, so only https gateways on public hosts are fetched unless local gateways are
+//! allowed, keeping a contract from making the client probe its own network. Hosts are
+//! checked as named, as resolved and on every redirect.
+
+use std::net::IpAddr;
+use std::time::Duration;
+
+use alloy::primitives::{Address, Bytes};
+use alloy::sol_types::{sol, SolError, SolValue};
+use reqwest::Url;
+use serde::Deserialize;
+use serde_json::json;
+use thiserror::Error;
+
+use crate::execution::constants::{MAX_GATEWAY_REDIRECTS, MAX_GATEWAY_RESPONSE_SIZE};
+use crate::time::timeout;
+
+sol! {
+    #[derive(Debug, PartialEq, Eq)]
+    error OffchainLookup(
+        address sender,
+        string[] urls,
+        bytes callData,
+        bytes4 callbackFunction,
+        bytes extraData
+    );
+}
+
+impl OffchainLookup {
+    /// The lookup `revert` asks for, if it is one raised by the called contract `to`.
+    /// Lookups raised by contracts further down the call are plain reverts.
+    pub fn from_revert(revert: &[u8], to: Address) -> Option<Self> {
+        Self::abi_decode(revert, true)
+            .ok()
+            .filter(|lookup| lookup.sender == to)
+    }
+
+    /// The input of the callback, passing it the gateway `response` and the extra data.
+    pub fn callback(&self, response: Bytes) -> Bytes {
+        let args = (response, self.extraData.clone()).abi_encode_params();
+        [self.callbackFunction.as_slice(), &args].concat().into()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CcipError {
+    #[error("call exceeded {0} offchain lookups")]
+    TooManyLookups(usize),
+    #[error("gateway {0} rejected the offchain lookup: {1}")]
+    Rejected(String, String),
+    #[error("no gateway answered the offchain lookup: {}", .0.join(", "))]
+    GatewaysFailed(Vec<String>),
+}
+
+#[derive(Deserialize)]
+struct GatewayResponse {
+    data: Bytes,
+}
+
+/// Fetches the answers of offchain lookups from their gateways.
+#[derive(Debug, Clone)]
+pub struct CcipGateway {
+    timeout: Duration,
+    allow_local: bool,
+    #[cfg(not(target_arch = "wasm32"))]
+    client: reqwest::Client,
+}
+
+impl CcipGateway {
+    /// Gives each gateway `timeout` to answer, fetching from https gateways on public hosts
+    /// only.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            allow_local: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            client: public_client(),
+        }
+    }
+
+    /// Also fetches from plain http gateways and from loopback and private hosts, for
+    /// gateways run next to the client.
+    pub fn allow_local_gateways(mut self) -> Self {
+        self.allow_local = true;
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            self.client = reqwest::Client::new();
+        }
+        self
+    }
+
+    /// The answer to `lookup` of the first of its gateways giving one.
+    pub async fn fetch(&self, lookup: &OffchainLookup) -> Result<Bytes, CcipError> {
+        let sender = format!("{:#x}", lookup.sender);
+        let data = lookup.callData.to_string();
+
+        let mut errors = Vec::with_capacity(lookup.urls.len());
+        for template in &lookup.urls {
+            let url = template
+                .replace("{sender}", &sender)
+                .replace("{data}", &data);
+            let body = (!template.contains("{data}"))
+                .then(|| json!({ "data": data, "sender": sender }).to_string());
+            if !self.allow_local {
+                if let Err(err) = check_public_url(&url) {
+                    errors.push(format!("{url}: refused: {err}"));
+                    continue;
+                }
+            }
+
+            let (status, response) = match timeout(self.timeout, self.send(&url, body)).await {
+                Ok(Ok(response)) => response,
+                Ok(Err(err)) => {
+                    errors.push(format!("{url}: {err}"));
+                    continue;
+                }
+                Err(_) => {
+                    errors.push(format!("{url}: timed out"));
+                    continue;
+                }
+            };
+            let response = String::from_utf8_lossy(&response);
+            match status {
+                200..=299 => match serde_json::from_str::<GatewayResponse>(&response) {
+                    Ok(response) => return Ok(response.data),
+                    Err(err) => errors.push(format!("{url}: invalid response: {err}")),
+                },
+                400..=499 => return Err(CcipError::Rejected(url, response.into_owned())),
+                status => errors.push(format!("{url}: status {status}")),
+            }
+        }
+
+        Err(CcipError::GatewaysFailed(errors))
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    async fn send(&self, url: &str, body: Option<String>) -> Result<(u16, Vec<u8>), String> {
+        let request = match body {
+            Some(body) => self
+                .client
+                .post(url)
+                .header("content-type", "application/json")
+                .body(body),
+            None => self.client.get(url),
+        };
+        let mut response = request.send().await.map_err(|err| err.to_string())?;
+        let status = response.status().as_u16();
+        let too_large = || format!("response over {MAX_GATEWAY_RESPONSE_SIZE} bytes");
+        if response
+            .content_length()
+            .is_some_and(|length| length > MAX_GATEWAY_RESPONSE_SIZE as u64)
+        {
+            return Err(too_large());
+        }
+
+        // read in chunks, so a gateway not announcing its length is cut off at the limit too
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|err| err.to_string())? {
+            if body.len() + chunk.len() > MAX_GATEWAY_RESPONSE_SIZE {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok((status, body))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    async fn send(&self, url: &str, body: Option<String>) -> Result<(u16, Vec<u8>), String> {
+        use crate::execution::rpc::fetch::fetch_bounded;
+
+        fetch_bounded(url, body.as_deref(), &[], MAX_GATEWAY_RESPONSE_SIZE)
+            .await
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Checks `url` is an https url on a host that is neither loopback nor private.
+fn check_public_url(url: &str) -> Result<(), String> {
+    let url = Url::parse(url).map_err(|err| err.to_string())?;
+    if url.scheme() != "https" {
+        return Err(format!("{} is not https", url.scheme()));
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    let public = match host.trim_matches(['[', ']']).parse::<IpAddr>() {
+        Ok(ip) => is_public(ip),
+        Err(_) => {
+            let domain = host.trim_end_matches('.').to_ascii_lowercase();
+            !domain.is_empty() && domain != "localhost" && !domain.ends_with(".localhost")
+        }
+    };
+    match public {
+        true => Ok(()),
+        false => Err(format!("{host} is not a public host")),
+    }
+}
+
+/// Whether `ip` is routed on the public internet, rather than being a loopback, private,
+/// link local or otherwise reserved address.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || first == 0
+                // shared address space of carrier grade nats
+                || (first == 100 && second & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    // unique local and link local addresses
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// A client connecting to public addresses only, and following redirects to public https
+/// urls only.
+#[cfg(not(target_arch = "wasm32"))]
+fn public_client() -> reqwest::Client {
+    use std::sync::Arc;
+
+    use reqwest::redirect::Policy;
+
+    let redirects = Policy::custom(|attempt| {
+        if attempt.previous().len() >= MAX_GATEWAY_REDIRECTS {
+            return attempt.error(format!("more than {MAX_GATEWAY_REDIRECTS} redirects"));
+        }
+        match check_public_url(attempt.url().as_str()) {
+            Ok(()) => attempt.follow(),
+            Err(err) => attempt.error(err),
+        }
+    });
+
+    reqwest::Client::builder()
+        .dns_resolver(Arc::new(PublicResolver))
+        .redirect(redirects)
+        .build()
+        .unwrap_or_default()
+}
+
+/// Resolves hosts to their public addresses only, so a public name pointing to a private
+/// address is refused as well.
+#[cfg(not(target_arch = "wasm32"))]
+struct PublicResolver;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect::<Vec<_>>();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
@@ -0,0 +1,348 @@
+//! Contract code kept in a [Database] across restarts, keyed by code hash.
+//!
+//! Code is content addressed by the hash a verified account proof commits to, so an entry
+//! never goes stale and can be kept for as long as there is room. Entries live in the
+//! [`CODE`] namespace, in a file store in the configured directory unless the embedder
+//! supplied a database, and reads hash the code again before it is used, so a corrupted or
+//! edited entry only costs a refetch from the provider. Once the entries outgrow the size
+//! limit the least recently used ones are deleted. Without a directory or a database, and so
+//! by default on wasm, the cache holds nothing.
+//!
+//! Reads and writes of entries go through the blocking thread pool, so a slow disk holds
+//! up the request waiting on it rather than the runtime.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use alloy::primitives::{keccak256, B256};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::config::ByteSize;
+use crate::database::{blocking, Database};
+
+use super::constants::DEFAULT_CODE_CACHE_SIZE;
+
+/// The namespace cached code is kept in.
+pub const CODE: &str = "code";
+
+/// Where contract code is cached and how much of it, read from the `code_cache` config
+/// table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CodeCacheConfig {
+    /// Directory holding the cached code, the cache is disabled without one.
+    pub dir: Option<PathBuf>,
+    /// Code kept before the least recently used entries are evicted.
+    pub max_size: ByteSize,
+}
+
+impl Default for CodeCacheConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            max_size: ByteSize::from_bytes(DEFAULT_CODE_CACHE_SIZE),
+        }
+    }
+}
+
+/// Contract code cached in a database by code hash. Clones share the same entries.
+#[derive(Debug, Clone, Default)]
+pub struct CodeCache {
+    stored: Option<Arc<StoredCache>>,
+}
+
+#[derive(Debug)]
+struct StoredCache {
+    db: Arc<dyn Database>,
+    max_size: u64,
+    index: Mutex<Index>,
+}
+
+/// Sizes and recency of the stored entries, which only this process is assumed to change.
+#[derive(Debug, Default)]
+struct Index {
+    entries: HashMap<B256, (u64, u64)>,
+    /// Entries by the tick of their last use, least recent first.
+    order: BTreeMap<u64, B256>,
+    tick: u64,
+    size: u64,
+}
+
+impl CodeCache {
+    /// A cache that holds nothing.
+    pub fn disabled() -> Self {
+        Self::default()
+    }
+
+    /// Opens the cache in a file store in the configured directory, creating it if needed.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn open(config: &CodeCacheConfig) -> Result<Self> {
+        let Some(dir) = &config.dir else {
+            return Ok(Self::disabled());
+        };
+        let db = crate::database::file::FileStore::open(dir);
+        Self::with_database(Arc::new(db), config.max_size.as_bytes())
+    }
+
+    /// Wasm has no filesystem to cache on, so the cache stays disabled unless it is given a
+    /// database.
+    #[cfg(target_arch = "wasm32")]
+    pub fn open(_config: &CodeCacheConfig) -> Result<Self> {
+        Ok(Self::disabled())
+    }
+
+    /// Caches code in the [`CODE`] namespace of `db`, keeping up to `max_size` bytes of it.
+    /// Entries already there are taken over, oldest first by write time.
+    pub fn with_database(db: Arc<dyn Database>, max_size: u64) -> Result<Self> {
+        let mut existing = db
+            .entries(CODE)?
+            .into_iter()
+            .filter_map(|entry| {
+                let hash = B256::try_from(entry.key.as_slice()).ok()?;
+                Some((entry.written_at, hash, entry.size))
+            })
+            .collect::<Vec<_>>();
+        existing.sort();
+
+        let stored = StoredCache {
+            db,
+            max_size,
+            index: Mutex::default(),
+        };
+        {
+            let mut index = stored.index.lock().unwrap();
+            for (_, hash, size) in existing {
+                index.insert(hash, size);
+            }
+        }
+        stored.evict();
+
+        Ok(Self {
+            stored: Some(Arc::new(stored)),
+        })
+    }
+
+    /// The cached code hashing to `code_hash`. Entries that no longer do are deleted and
+    /// reported as missing.
+    pub async fn get(&self, code_hash: B256) -> Option<Vec<u8>> {
+        let stored = self.stored.clone()?;
+        blocking(move || stored.get(code_hash)).await.flatten()
+    }
+
+    /// Caches `code` under `code_hash`, which the caller has verified it hashes to.
+    pub async fn put(&self, code_hash: B256, code: &[u8]) {
+        if let Some(stored) = self.stored.clone() {
+            let code = code.to_vec();
+            blocking(move || stored.put(code_hash, &code)).await;
+        }
+    }
+
+    /// Bytes of code currently cached.
+    pub fn size(&self) -> u64 {
+        self.stored
+            .as_ref()
+            .map_or(0, |stored| stored.index.lock().unwrap().size)
+    }
+}
+
+impl StoredCache {
+    fn get(&self, code_hash: B256) -> Option<Vec<u8>> {
+        if !self.index.lock().unwrap().touch(code_hash) {
+            return None;
+        }
+
+        match self.db.get(CODE, code_hash.as_slice()) {
+            Ok(Some(code)) if keccak256(&code) == code_hash => Some(code),
+            res => {
+                if matches!(res, Ok(Some(_))) {
+                    warn!(
+                        target: "helios::execution",
+                        %code_hash,
+                        "cached code is corrupted, refetching"
+                    );
+                }
+                self.index.lock().unwrap().remove(code_hash);
+                let _ = self.db.delete(CODE, &[code_hash.to_vec()]);
+                None
+            }
+        }
+    }
+
+    fn put(&self, code_hash: B256, code: &[u8]) {
+        let size = code.len() as u64;
+        if size > self.max_size || self.index.lock().unwrap().touch(code_hash) {
+            return;
+        }
+
+        if let Err(err) = self.db.put(CODE, code_hash.as_slice(), code) {
+            warn!(target: "helios::execution", %code_hash, "could not cache code: {}", err);
+            return;
+        }
+
+        self.index.lock().unwrap().insert(code_hash, size);
+        self.evict();
+    }
+
+    /// Deletes the least recently used entries until the rest fit in the size limit.
+    fn evict(&self) {
+        let evicted = {
+            let mut index = self.index.lock().unwrap();
+            let mut evicted = Vec::new();
+            while index.size > self.max_size {
+                let Some((_, hash)) = index.order.pop_first() else {
+                    break;
+                };
+                index.remove(hash);
+                evicted.push(hash.to_vec());
+            }
+            evicted
+        };
+
+        if !evicted.is_empty() {
+            let _ = self.db.delete(CODE, &evicted);
+        }
+    }
+}
+
+impl Index {
+    fn insert(&mut self, hash: B256, size: u64) {
+        self.tick += 1;
+        if let Some((old_size, old_tick)) = self.entries.insert(hash, (size, self.tick)) {
+            self.order.remove(&old_tick);
+            self.size -= old_size;
+        }
+        self.order.insert(self.tick, hash);
+        self.size += size;
+    }
+
+    /// Marks `hash` as just used, returning whether it is cached.
+    fn touch(&mut self, hash: B256) -> bool {
+        self.tick += 1;
+        let tick = self.tick;
+        let Some((_, last_used)) = self.entries.get_mut(&hash) else {
+            return false;
+        };
+        self.order.remove(last_used);
+        *last_used = tick;
+        self.order.insert(tick, hash);
+        true
+    }
+
+    fn remove(&mut self, hash: B256) {
+        if let Some((size, tick)) = self.entries.remove(&hash) {
+            self.order.remove(&tick);
+            self.size -= size;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::*;
+    use crate::database::redb::RedbStore;
+
+    fn entry_path(dir: &Path, code_hash: B256) -> PathBuf {
+        dir.join(CODE).join(alloy::hex::encode(code_hash))
+    }
+
+    fn open(dir: &Path, max_size: u64) -> CodeCache {
+        CodeCache::open(&CodeCacheConfig {
+            dir: Some(dir.to_path_buf()),
+            max_size: ByteSize::from_bytes(max_size),
+        })
+        .unwrap()
+    }
+
+    fn code(byte: u8, len: usize) -> (B256, Vec<u8>) {
+        let code = vec![byte; len];
+        (keccak256(&code), code)
+    }
+
+    #[tokio::test]
+    async fn test_hit_survives_reopening() {
+        let dir = tempfile::tempdir().unwrap();
+        let (hash, code) = code(1, 100);
+
+        let cache = open(dir.path(), 1_000);
+        assert_eq!(cache.get(hash).await, None);
+        cache.put(hash, &code).await;
+        assert_eq!(cache.get(hash).await, Some(code.clone()));
+
+        let reopened = open(dir.path(), 1_000);
+        assert_eq!(reopened.get(hash).await, Some(code));
+        assert_eq!(reopened.size(), 100);
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_entry_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let (hash, code) = code(2, 100);
+
+        let cache = open(dir.path(), 1_000);
+        cache.put(hash, &code).await;
+        fs::write(entry_path(dir.path(), hash), [0xfe; 100]).unwrap();
+
+        assert_eq!(cache.get(hash).await, None);
+        assert!(!entry_path(dir.path(), hash).exists());
+        assert_eq!(cache.size(), 0);
+
+        // the refetched code is cached again
+        cache.put(hash, &code).await;
+        assert_eq!(cache.get(hash).await, Some(code));
+    }
+
+    #[tokio::test]
+    async fn test_least_recently_used_evicted() {
+        let dir = tempfile::tempdir().unwrap();
+        let (a, code_a) = code(3, 400);
+        let (b, code_b) = code(4, 400);
+        let (c, code_c) = code(5, 400);
+
+        let cache = open(dir.path(), 1_000);
+        cache.put(a, &code_a).await;
+        cache.put(b, &code_b).await;
+        // reading the first entry leaves the second as the least recently used
+        assert!(cache.get(a).await.is_some());
+        cache.put(c, &code_c).await;
+
+        assert_eq!(cache.size(), 800);
+        assert_eq!(cache.get(b).await, None);
+        assert!(!entry_path(dir.path(), b).exists());
+        assert_eq!(cache.get(a).await, Some(code_a));
+        assert_eq!(cache.get(c).await, Some(code_c));
+
+        // code larger than the whole cache is never stored
+        let (big, big_code) = code(6, 2_000);
+        cache.put(big, &big_code).await;
+        assert_eq!(cache.get(big).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_cached_in_supplied_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let db: Arc<dyn Database> = Arc::new(RedbStore::open(dir.path().join("db")).unwrap());
+        let (hash, code) = code(8, 100);
+
+        let cache = CodeCache::with_database(db.clone(), 1_000).unwrap();
+        cache.put(hash, &code).await;
+        assert_eq!(db.get(CODE, hash.as_slice()).unwrap(), Some(code.clone()));
+
+        let reopened = CodeCache::with_database(db, 1_000).unwrap();
+        assert_eq!(reopened.size(), 100);
+        assert_eq!(reopened.get(hash).await, Some(code));
+    }
+
+    #[tokio::test]
+    async fn test_disabled_without_dir() {
+        let cache = CodeCache::open(&CodeCacheConfig::default()).unwrap();
+        let (hash, code) = code(7, 10);
+        cache.put(hash, &code).await;
+        assert_eq!(cache.get(hash).await, None);
+    }
+}
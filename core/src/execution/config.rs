@@ -0,0 +1,88 @@
+//! How the execution client verifies and serves what the provider returns, gathered from the
+//! execution related tables of the network config and handed to the node when it is built.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::database::Database;
+
+use super::backfill::BackfillConfig;
+use super::code_cache::CodeCacheConfig;
+use super::constants::{DEFAULT_CCIP_READ_TIMEOUT, DEFAULT_CONFIRMATION_DEPTH};
+use super::evm::EvmConfig;
+use super::fee_history::{FeeHistoryConfig, GasPriceConfig};
+use super::historical::HistoricalStateConfig;
+use super::limits::PayloadLimits;
+use super::log_filter::LogVerificationConfig;
+use super::orphans::OrphanCacheConfig;
+use super::rpc::rate_limit::RateLimitConfig;
+
+/// The settings of the execution client of a node.
+#[derive(Debug, Clone)]
+pub struct ExecutionConfig {
+    /// Whether data from before the merge is served unverified instead of rejected.
+    pub premerge_passthrough: bool,
+    /// Blocks under the latest that `helios_confirmed` resolves to.
+    pub confirmation_depth: u64,
+    /// How the recent blocks behind the first head are fetched on startup.
+    pub backfill: BackfillConfig,
+    /// How many blocks orphaned by reorgs stay available by hash, and for how long.
+    pub orphan_cache: OrphanCacheConfig,
+    /// How `eth_getLogs` answers are verified.
+    pub logs: LogVerificationConfig,
+    /// Whether fee history ranges reaching before the verified blocks are clamped.
+    pub fee_history: FeeHistoryConfig,
+    /// How many recent blocks, and which percentile of their rewards, gas prices are
+    /// suggested from.
+    pub gas_price: GasPriceConfig,
+    /// How far back state of blocks before those held in state is served.
+    pub historical_state: HistoricalStateConfig,
+    /// How often calls may fetch state their access list left out.
+    pub evm: EvmConfig,
+    /// How large the payloads the provider returns may be.
+    pub payload_limits: PayloadLimits,
+    /// Execution rpcs raw transactions are submitted to besides the provider.
+    pub broadcast_rpcs: Vec<String>,
+    /// The request budget each of the broadcast rpcs is held to, apart from the provider's.
+    pub broadcast_rate_limit: RateLimitConfig,
+    /// Sends raw transactions without checking them against the verified state first.
+    pub skip_transaction_checks: bool,
+    /// Follows the offchain lookups of calls through their gateways.
+    pub ccip_read: bool,
+    /// How long each CCIP-Read gateway is given to answer.
+    pub ccip_read_timeout: Duration,
+    /// Where fetched contract code is cached across restarts.
+    pub code_cache: CodeCacheConfig,
+    /// A database the embedder supplied, which holds cached contract code unless the code
+    /// cache is given a directory of its own, and the verified headers without a header
+    /// store.
+    pub database: Option<Arc<dyn Database>>,
+    /// Where headers verified back from the window are kept across restarts, usually the
+    /// database the client keeps its checkpoints in.
+    pub header_store: Option<Arc<dyn Database>>,
+}
+
+impl Default for ExecutionConfig {
+    fn default() -> Self {
+        Self {
+            premerge_passthrough: false,
+            confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
+            backfill: BackfillConfig::default(),
+            orphan_cache: OrphanCacheConfig::default(),
+            logs: LogVerificationConfig::default(),
+            fee_history: FeeHistoryConfig::default(),
+            gas_price: GasPriceConfig::default(),
+            historical_state: HistoricalStateConfig::default(),
+            evm: EvmConfig::default(),
+            payload_limits: PayloadLimits::default(),
+            broadcast_rpcs: Vec::new(),
+            broadcast_rate_limit: RateLimitConfig::default(),
+            skip_transaction_checks: false,
+            ccip_read: false,
+            ccip_read_timeout: DEFAULT_CCIP_READ_TIMEOUT,
+            code_cache: CodeCacheConfig::default(),
+            database: None,
+            header_store: None,
+        }
+    }
+}
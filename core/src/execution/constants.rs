@@ -1,7 +1,123 @@
+use std::time::Duration;
+
 pub const PARALLEL_QUERY_BATCH_SIZE: usize = 20;
 
 // We currently limit the max number of logs to fetch,
 // to avoid blocking the client for too long.
 pub const MAX_SUPPORTED_LOGS_NUMBER: usize = 5;
 
+// Blocks an `eth_getLogs` filter may span unless configured otherwise.
+pub const DEFAULT_MAX_LOG_BLOCKS: u64 = 64;
+
+// Maximum number of logs a page of `helios_getLogsPaged` holds.
+pub const MAX_LOG_PAGE_SIZE: usize = 10_000;
+
+// Blocks whose receipts are fetched together while a page of logs is filled.
+pub const LOG_PAGE_CHUNK_BLOCKS: u64 = 16;
+
+// Blocks a page of `helios_getLogsPaged` scans, those its bloom rules out included, before it
+// ends with a cursor.
+pub const MAX_LOG_PAGE_SCANNED_BLOCKS: u64 = 4096;
+
+// Bytes of contract code cached on disk unless configured otherwise.
+pub const DEFAULT_CODE_CACHE_SIZE: u64 = 256 * 1024 * 1024;
+
 pub const MAX_STATE_HISTORY_LENGTH: usize = 64;
+
+// Distinct blocks pins can hold in the state cache at once, on top of its recent blocks.
+pub const DEFAULT_PIN_BUDGET: usize = 16;
+
+// Blocks before the oldest verified one that historical state reaches unless configured
+// otherwise.
+pub const DEFAULT_MAX_HISTORICAL_DEPTH: u64 = 8192;
+
+// Batches of headers a walk back from the window fetches ahead of those it has verified.
+pub const HISTORICAL_WALK_BATCHES: usize = 4;
+
+// Maximum number of verified accounts kept for repeat balance, nonce and code reads.
+pub const MAX_ACCOUNT_CACHE_SIZE: usize = 4096;
+
+// Blocks whose verified receipts are kept for repeat log and receipt queries unless configured
+// otherwise.
+pub const DEFAULT_RECEIPT_CACHE_BLOCKS: usize = 64;
+
+// Maximum number of blocks returned by a single prevRandao range query, half the state window
+// so that a range over all of it is paged.
+pub const MAX_PREV_RANDAO_RANGE: u64 = MAX_STATE_HISTORY_LENGTH as u64 / 2;
+
+// Maximum number of blocks a resumed subscription catches up on before reporting a gap.
+pub const MAX_SUBSCRIPTION_BACKFILL: u64 = 64;
+
+// Number of verified headers buffered for each header stream consumer before it lags.
+pub const HEADER_STREAM_CAPACITY: usize = 64;
+
+// Time between saves of the accounted provider usage.
+pub const USAGE_FLUSH_INTERVAL: Duration = Duration::from_secs(60);
+
+// Days of usage the projected monthly cost extrapolates from.
+pub const USAGE_RATE_WINDOW_DAYS: u64 = 7;
+
+// Latest verified blocks whose rewards gas prices are suggested from unless configured
+// otherwise.
+pub const DEFAULT_GAS_PRICE_BLOCKS: u64 = 20;
+
+// Percentile of the priority fees paid in a block that gas prices are suggested from unless
+// configured otherwise.
+pub const DEFAULT_GAS_PRICE_PERCENTILE: f64 = 60.0;
+
+// Priority fee suggested when none of the recent verified blocks had transactions.
+pub const DEFAULT_PRIORITY_FEE: u128 = 1_000_000_000;
+
+// Verified confirmations `helios_waitForTransactionReceipt` waits for unless asked otherwise.
+pub const DEFAULT_RECEIPT_CONFIRMATIONS: u64 = 1;
+
+// Time `helios_waitForTransactionReceipt` waits unless asked otherwise.
+pub const DEFAULT_RECEIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+// Blocks under the latest that `helios_confirmed` resolves to unless configured otherwise.
+pub const DEFAULT_CONFIRMATION_DEPTH: u64 = 4;
+
+// Number of deep reorgs buffered for each consumer of the deep reorg stream before it lags.
+pub const DEEP_REORG_STREAM_CAPACITY: usize = 16;
+
+// Orphaned blocks kept for lookups by hash unless configured otherwise.
+pub const DEFAULT_ORPHAN_CACHE_SIZE: usize = 16;
+
+// Time orphaned blocks stay available unless configured otherwise.
+pub const DEFAULT_ORPHAN_CACHE_TTL: Duration = Duration::from_secs(3_600);
+
+// Number of reorgs listed by `helios_getReorgHistory`.
+pub const MAX_REORG_HISTORY: usize = 64;
+
+// Offchain lookups a single call may follow, as EIP-3668 recommends.
+pub const MAX_OFFCHAIN_LOOKUPS: usize = 4;
+
+// Time each CCIP-Read gateway is given to answer unless configured otherwise.
+pub const DEFAULT_CCIP_READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Largest gateway response read for an offchain lookup.
+pub const MAX_GATEWAY_RESPONSE_SIZE: usize = 4 * 1024 * 1024;
+
+// Redirects followed for a gateway request.
+pub const MAX_GATEWAY_REDIRECTS: usize = 5;
+
+// Calls a single `helios_callMany` request may batch.
+pub const MAX_CALL_MANY_CALLS: usize = 256;
+
+// Rounds a call may fetch the state its access list left out before failing, unless
+// configured otherwise. Each round fetches all the state the run before it was missing, so
+// only chains of reads, where one value names the next slot, take several.
+pub const DEFAULT_MAX_STATE_REFETCHES: usize = 32;
+
+// Bytes of json the struct logs of `debug_traceCall` may take before the trace fails, the
+// stack of each step being what makes them large.
+pub const MAX_STRUCT_LOG_BYTES: usize = 32 * 1024 * 1024;
+
+// Blocks the startup backfill fetches at once unless configured otherwise.
+pub const DEFAULT_BACKFILL_CONCURRENCY: usize = 8;
+
+// Retries of a block the startup backfill failed to fetch unless configured otherwise.
+pub const DEFAULT_BACKFILL_RETRIES: u32 = 3;
+
+// Wait before the first retry of a backfilled block unless configured otherwise.
+pub const DEFAULT_BACKFILL_RETRY_BACKOFF: Duration = Duration::from_millis(250);
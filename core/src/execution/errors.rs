@@ -3,6 +3,7 @@ use alloy::sol_types::decode_revert_reason;
 use eyre::Report;
 use thiserror::Error;
 
+use crate::execution::ccip::CcipError;
 use crate::types::BlockTag;
 
 #[derive(Debug, Error)]
@@ -15,6 +16,12 @@ pub enum ExecutionError {
     CodeHashMismatch(Address, B256, B256),
     #[error("receipt root mismatch for tx: {0}")]
     ReceiptRootMismatch(B256),
+    #[error("receipt for tx {0} does not match the verified receipt at its index")]
+    ReceiptMismatch(B256),
+    #[error("cannot verify receipt for unverified block, tx: {0}")]
+    UnverifiedReceiptBlock(B256),
+    #[error("provider returned an inconsistent receipt for tx {0}: {1}")]
+    InconsistentReceipt(B256, String),
     #[error("could not prove receipt for tx: {0}")]
     NoReceiptForTransaction(B256),
     #[error("could not prove receipts for block: {0}")]
@@ -23,16 +30,121 @@ pub enum ExecutionError {
     MissingLog(B256, U256),
     #[error("too many logs to prove: {0}, current limit is: {1}")]
     TooManyLogsToProve(usize, usize),
+    #[error("filter spans {0} blocks, current limit is: {1}")]
+    TooManyLogBlocks(u64, u64),
+    #[error("provider left out {0} logs matching the filter")]
+    OmittedLogs(usize),
+    #[error("invalid log cursor: {0}")]
+    InvalidLogCursor(String),
+    #[error("log cursor is on block {0}, which a reorg replaced, restart from block {1}")]
+    StaleLogCursor(u64, u64),
+    #[error("subscription missed the events of blocks {0} to {1}, subscribe again")]
+    MissedSubscriptionEvents(u64, u64),
+    #[error("page size {0} is not between 1 and {1}")]
+    InvalidLogPageSize(usize, usize),
+    #[error("reward percentile {0} is not between 0 and 100")]
+    InvalidPercentile(f64),
     #[error("execution rpc is for the incorrect network")]
     IncorrectRpcNetwork(),
-    #[error("block not found: {0}")]
+    #[error("{}", block_not_found(.0))]
     BlockNotFound(BlockTag),
+    #[error("block not found: {0}")]
+    BlockHashNotFound(B256),
+    #[error("cannot encode block {0}, its response lacks the uncle headers or transactions")]
+    BlockNotEncodable(B256),
+    #[error("header does not hash to its hash field: {0}")]
+    InvalidHeaderHash(B256),
+    #[error("hash of block {0} is unknown, a supplied header only vouches for its parent")]
+    UnanchoredBlockHash(u64),
+    #[error("provider returned block {1} when asked for {0}")]
+    BlockHashMismatch(B256, B256),
+    #[error("provider returned block number {1} when asked for block {0}")]
+    BlockNumberMismatch(u64, u64),
+    #[error("body of block {0} does not match its {1}")]
+    BlockBodyMismatch(B256, &'static str),
+    #[error("transaction at index {1} of block {0} does not hash to {2}")]
+    TransactionHashMismatch(B256, usize, B256),
+    #[error("transaction {1} at index {2} of block {0} claims another place")]
+    MisplacedTransaction(B256, B256, usize),
+    #[error("execution proof version {0} is not supported, expected {1}")]
+    UnsupportedProofVersion(u64, u64),
+    #[error("execution proof header does not decode: {0}")]
+    InvalidProofHeader(String),
+    #[error("execution proof does not link block {0} to the trusted root: {1}")]
+    UntrustedProof(B256, &'static str),
+    #[error("no block held from {0} up carries an attested beacon block to prove it by")]
+    NoAttestedDescendant(u64),
     #[error("receipts root mismatch for block: {0}")]
     BlockReceiptsRootMismatch(BlockTag),
+    #[error("provider returned a fee history not covering blocks {0} to {1}")]
+    FeeHistoryRangeMismatch(u64, u64),
+    #[error("fee history {1} of block {0} does not match its verified header")]
+    FeeHistoryMismatch(u64, &'static str),
+    #[error("block {0} does not price blob gas, there are no blob transactions before cancun or on this network")]
+    BlobGasNotPriced(u64),
     #[error("filter not found: 0x{0:x}")]
     FilterNotFound(U256),
     #[error("log does not match filter")]
     LogFilterMismatch(),
+    #[error("block {0} is outside the retained window [{1}, {2}]")]
+    BlockOutsideRetainedWindow(u64, u64, u64),
+    #[error("header of block {0} carries no prevRandao")]
+    MissingPrevRandao(u64),
+    #[error("block {0} is more than {2} blocks before the oldest verified block {1}")]
+    HistoricalDepthExceeded(u64, u64, u64),
+    #[error("header of block {0} does not match the parent hash {1} its child commits to")]
+    BrokenHeaderChain(u64, B256),
+    #[error("provider no longer holds the state of block {0}, an archive provider is needed to serve it")]
+    HistoricalStatePruned(u64),
+    #[error("slot {0} is outside the retained window [{1}, {2}]")]
+    SlotOutsideRetainedWindow(u64, u64, u64),
+    #[error("no verified beacon block for slot {0}")]
+    NoBeaconBlockForSlot(u64),
+    #[error("no verified beacon block for execution block {0}")]
+    NoBeaconBlockForBlock(B256),
+    #[error("block {0} is before the merge at block {1} and cannot be verified by the light client, enable premerge_passthrough to serve it unverified")]
+    PreMergeBlock(u64, u64),
+    #[error("cannot pin more than {0} blocks, release a pin or raise the history length")]
+    PinBudgetExceeded(usize),
+    #[error("no endpoint accepted the transaction: {}", .0.join("; "))]
+    BroadcastRejected(Vec<String>),
+    #[error("transaction {0} was dropped, its nonce {1} is used by another transaction")]
+    TransactionDropped(B256, u64),
+    #[error("transaction {0} did not reach {1} verified confirmations before the timeout")]
+    ReceiptTimeout(B256, u64),
+    #[error("invalid raw transaction: {0}")]
+    InvalidRawTransaction(String),
+    #[error("invalid chain id: have {0}, want {1}")]
+    InvalidChainId(u64, u64),
+    #[error("nonce too low: next nonce {0}, tx nonce {1}")]
+    NonceTooLow(u64, u64),
+    #[error("insufficient funds for gas * price + value: balance {0}, tx cost {1}")]
+    InsufficientFunds(U256, U256),
+    #[error("account {0} has both a state and a stateDiff override")]
+    ConflictingStateOverride(Address),
+    #[error("invalid block range: {0} to {1}")]
+    InvalidBlockRange(u64, u64),
+    #[error("provider payload too large: {0} is {1}, limit is {2}")]
+    ProviderPayloadTooLarge(&'static str, usize, usize),
+    #[error("could not measure the {0} of a provider payload")]
+    UnmeasuredPayload(&'static str),
+    #[error("incomplete {kind} from provider ({client}), missing: {}", .missing.join(", "))]
+    IncompleteProviderResponse {
+        kind: &'static str,
+        missing: Vec<String>,
+        client: String,
+    },
+}
+
+/// Until consensus delivers a safe or finalized block the safe tag has nothing to resolve
+/// to, which is a matter of waiting rather than a missing block.
+pub(crate) fn block_not_found(tag: &BlockTag) -> String {
+    match tag {
+        BlockTag::Safe => {
+            "safe block not yet available, no safe or finalized block is verified".to_string()
+        }
+        tag => format!("block not found: {tag}"),
+    }
 }
 
 /// Errors that can occur during evm.rs calls
@@ -44,8 +156,29 @@ pub enum EvmError {
     #[error("evm error: {0:?}")]
     Generic(String),
 
+    #[error("invalid transaction: {0}")]
+    InvalidTransaction(String),
+
     #[error("rpc error: {0:?}")]
     RpcError(Report),
+
+    #[error("ccip-read error: {0}")]
+    CcipRead(#[from] CcipError),
+
+    #[error("unsupported tracer: {0}, only callTracer and the struct logger are available")]
+    UnsupportedTracer(String),
+
+    #[error("trace exceeds {0} bytes of struct logs, set a limit or use the callTracer")]
+    TraceTooLarge(usize),
+
+    #[error("too many calls, at most {0} can be batched")]
+    TooManyCalls(usize),
+
+    #[error("cannot simulate blob transaction: {0}")]
+    UnsupportedBlobTransaction(String),
+
+    #[error("state still missing after {0} refetches: {1}")]
+    MissingState(usize, String),
 }
 
 fn display_revert(output: &Option<Bytes>) -> String {
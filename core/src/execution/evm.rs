@@ -1,37 +1,81 @@
-use std::{borrow::BorrowMut, collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    sync::Arc,
+};
 
 use alloy::{
     consensus::BlockHeader,
+    eips::eip4844::DATA_GAS_PER_BLOB,
     network::{primitives::HeaderResponse, BlockResponse, TransactionBuilder},
+    primitives::I256,
+    rpc::types::AccessListResult,
+    sol_types::decode_revert_reason,
 };
-use eyre::{Report, Result};
-use futures::future::join_all;
+use eyre::{Report, Result, WrapErr};
+use futures::{stream, StreamExt};
 use revm::{
+    inspector_handle_register,
     primitives::{
-        address, AccessListItem, AccountInfo, Address, Bytecode, Bytes, CfgEnv, Env,
-        ExecutionResult, ResultAndState, B256, U256,
+        address, keccak256, AccessList, AccessListItem, AccountInfo, Address, Bytecode, Bytes,
+        CfgEnv, EVMError, Env, EvmState as PostState, ExecutionResult, ResultAndState, B256, U256,
     },
-    Database, Evm as Revm,
+    Database, Evm as Revm, Inspector,
 };
+use serde::{Deserialize, Serialize};
 use tracing::trace;
 
 use crate::network_spec::NetworkSpec;
-use crate::types::BlockTag;
+use crate::types::{AnchorTrust, BlockTag};
 use crate::{
     execution::{
-        constants::PARALLEL_QUERY_BATCH_SIZE,
+        asset_changes::{
+            collect_asset_changes, AssetChangeReport, TokenMetadata, DECIMALS_SELECTOR,
+            NAME_SELECTOR, SYMBOL_SELECTOR,
+        },
+        blob::BlobParams,
+        bundle::{
+            AccountOverride, BlockOverrides, BundleOverrides, CallOutcome, SimulatedTransaction,
+            StateOverride,
+        },
+        ccip::{CcipError, OffchainLookup},
+        constants::{
+            DEFAULT_MAX_STATE_REFETCHES, MAX_CALL_MANY_CALLS, MAX_OFFCHAIN_LOOKUPS,
+            PARALLEL_QUERY_BATCH_SIZE,
+        },
         errors::{EvmError, ExecutionError},
+        pins::{BlockPin, RetentionReason},
         rpc::ExecutionRpc,
+        trace::{CoinbaseTracer, Restart, TraceOptions, TraceResult, Tracer},
+        types::{Account, StateAnchor},
         ExecutionClient,
     },
     fork_schedule::ForkSchedule,
 };
 
+/// How calls are executed over the verified state, read from the `evm` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EvmConfig {
+    /// Rounds an execution may fetch the state the prefetched access list left out and run
+    /// again, before the call fails.
+    pub max_state_refetches: usize,
+}
+
+impl Default for EvmConfig {
+    fn default() -> Self {
+        Self {
+            max_state_refetches: DEFAULT_MAX_STATE_REFETCHES,
+        }
+    }
+}
+
 pub struct Evm<N: NetworkSpec, R: ExecutionRpc<N>> {
     execution: Arc<ExecutionClient<N, R>>,
     chain_id: u64,
-    tag: BlockTag,
+    anchor: StateAnchor<N::HeaderResponse>,
     fork_schedule: ForkSchedule,
+    state_overrides: StateOverride,
 }
 
 impl<N: NetworkSpec, R: ExecutionRpc<N>> Evm<N, R> {
@@ -44,12 +88,98 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> Evm<N, R> {
         Evm {
             execution,
             chain_id,
-            tag,
+            anchor: StateAnchor::Tag(tag),
+            fork_schedule,
+            state_overrides: StateOverride::default(),
+        }
+    }
+
+    /// Executes against the state of a caller supplied header rather than a block verified
+    /// through consensus. State reads are still proven against the header's state root. The
+    /// header is checked to hash to its `hash` field once, when the first execution starts.
+    pub fn with_header(
+        execution: Arc<ExecutionClient<N, R>>,
+        chain_id: u64,
+        fork_schedule: ForkSchedule,
+        header: N::HeaderResponse,
+    ) -> Self {
+        Self::with_anchor(
+            execution,
+            chain_id,
+            fork_schedule,
+            StateAnchor::Header(header),
+        )
+    }
+
+    /// Executes against the state of `anchor`, such as a supplied header checked with
+    /// [`ExecutionClient::verify_supplied_header`] beforehand.
+    pub fn with_anchor(
+        execution: Arc<ExecutionClient<N, R>>,
+        chain_id: u64,
+        fork_schedule: ForkSchedule,
+        anchor: StateAnchor<N::HeaderResponse>,
+    ) -> Self {
+        Evm {
+            execution,
+            chain_id,
+            anchor,
             fork_schedule,
+            state_overrides: StateOverride::default(),
         }
     }
 
+    /// Executes calls on top of the verified state with `overrides` applied, as the state
+    /// override parameter of `eth_call` does.
+    pub fn with_state_overrides(mut self, overrides: StateOverride) -> Self {
+        self.state_overrides = overrides;
+        self
+    }
+
+    /// Calls through the verified state, following the offchain lookups of the called
+    /// contract if the client has a CCIP-Read gateway.
     pub async fn call(&mut self, tx: &N::TransactionRequest) -> Result<Bytes, EvmError> {
+        let Some(gateway) = self.execution.ccip_gateway().cloned() else {
+            return self.call_once(tx).await;
+        };
+        let Some(to) = tx.to() else {
+            return self.call_once(tx).await;
+        };
+
+        let mut tx = tx.clone();
+        let mut lookups = 0;
+        loop {
+            let revert = match self.call_once(&tx).await {
+                Err(EvmError::Revert(Some(revert))) => revert,
+                result => return result,
+            };
+            let Some(lookup) = OffchainLookup::from_revert(&revert, to) else {
+                return Err(EvmError::Revert(Some(revert)));
+            };
+            if lookups == MAX_OFFCHAIN_LOOKUPS {
+                return Err(CcipError::TooManyLookups(MAX_OFFCHAIN_LOOKUPS).into());
+            }
+            lookups += 1;
+
+            trace!(target: "helios::evm", "offchain lookup {} of {}", lookups, to);
+            let response = gateway.fetch(&lookup).await?;
+            tx.set_input(lookup.callback(response));
+        }
+    }
+
+    /// Calls a getter taking no arguments through the verified state. A getter that reverts
+    /// answers `None`, while state that fails to prove stays an error.
+    async fn view(&mut self, to: Address, selector: [u8; 4]) -> Result<Option<Bytes>, EvmError> {
+        let tx = N::TransactionRequest::default()
+            .with_to(to)
+            .with_input(selector.to_vec());
+        match self.call_once(&tx).await {
+            Ok(output) => Ok(Some(output)),
+            Err(EvmError::Revert(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn call_once(&mut self, tx: &N::TransactionRequest) -> Result<Bytes, EvmError> {
         let tx = self.call_inner(tx).await?;
 
         match tx.result {
@@ -71,42 +201,387 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> Evm<N, R> {
         }
     }
 
+    /// Builds the access list of the transaction from the accounts and storage slots its
+    /// execution touches. As in geth, the sender, the recipient and precompiles are only
+    /// listed for the slots read from them, being warm from the start of the transaction
+    /// anyway. The coinbase is listed once the execution reaches it, not for being paid the
+    /// fees. The gas used is that of executing again with the derived list attached.
+    pub async fn create_access_list(
+        &mut self,
+        tx: &N::TransactionRequest,
+    ) -> Result<AccessListResult, EvmError> {
+        let Some(sender) = tx.from() else {
+            return Err(EvmError::InvalidTransaction(
+                "a sender is required to create an access list".to_string(),
+            ));
+        };
+        self.settle_anchor().await?;
+        let _pin = self.pin_anchor().await?;
+
+        let coinbase = self
+            .execution
+            .anchor_header(&self.anchor)
+            .await
+            .map_err(EvmError::RpcError)?
+            .beneficiary();
+        let mut db = ProofDB::new(self.anchor.clone(), self.execution.clone());
+        db.state
+            .apply_overrides(&self.state_overrides)
+            .await
+            .map_err(EvmError::RpcError)?;
+        let (ResultAndState { result, state }, tracer, _) = self
+            .transact_traced(&mut db, tx, CoinbaseTracer::new(coinbase))
+            .await?;
+
+        let mut warm = vec![sender];
+        warm.extend(tx.to());
+        if !tracer.touched() {
+            warm.push(coinbase);
+        }
+        let access_list = touched_access_list(&state, &warm);
+
+        let result = if tx.access_list() == Some(&access_list) {
+            result
+        } else {
+            let mut tx = tx.clone();
+            tx.set_access_list(access_list.clone());
+            self.call_inner(&tx).await?.result
+        };
+
+        Ok(AccessListResult {
+            access_list,
+            gas_used: U256::from(result.gas_used()),
+            error: revert_reason(&result),
+        })
+    }
+
+    /// Simulates the transaction and reports how it changes the sender's ether and token
+    /// holdings. Token changes are derived from the `Transfer` and `Approval` events emitted
+    /// during execution, so they cover proxies as well as direct token calls. The metadata of
+    /// the tokens named is read through the verified state at the same block.
+    pub async fn simulate_asset_changes(
+        &mut self,
+        tx: &N::TransactionRequest,
+    ) -> Result<AssetChangeReport, EvmError> {
+        let Some(sender) = tx.from() else {
+            return Err(EvmError::InvalidTransaction(
+                "a sender is required to report its asset changes".to_string(),
+            ));
+        };
+        self.settle_anchor().await?;
+        let balance_before = self
+            .execution
+            .get_account_at(sender, None, &self.anchor)
+            .await
+            .map_err(EvmError::RpcError)?
+            .balance;
+
+        let ResultAndState { result, state } = self.call_inner(tx).await?;
+
+        // the sender is always part of the post state, even if the call reverts,
+        // since the nonce is bumped and fees are charged
+        let balance_after = state
+            .get(&sender)
+            .map(|account| account.info.balance)
+            .unwrap_or(balance_before);
+
+        let mut report = AssetChangeReport {
+            success: result.is_success(),
+            gas_used: result.gas_used(),
+            eth_delta: signed_delta(balance_before, balance_after),
+            revert_reason: revert_reason(&result),
+            ..Default::default()
+        };
+        if let ExecutionResult::Success { logs, .. } = result {
+            collect_asset_changes(sender, &logs, &mut report);
+        }
+
+        for token in report.token_addresses() {
+            let name = self.view(token, NAME_SELECTOR).await?;
+            let symbol = self.view(token, SYMBOL_SELECTOR).await?;
+            let decimals = self.view(token, DECIMALS_SELECTOR).await?;
+            report.tokens.push(TokenMetadata::decode(
+                token,
+                name.as_deref(),
+                symbol.as_deref(),
+                decimals.as_deref(),
+            ));
+        }
+
+        Ok(report)
+    }
+
+    /// Executes `txs` in order, each on top of the state the ones before it left, starting
+    /// from the verified state with the `overrides` applied. State written by an earlier
+    /// transaction is read from memory, while anything else is proven as for a call. A
+    /// transaction that reverts does not end the bundle; like on chain, it leaves only its
+    /// nonce and fee behind.
+    pub async fn simulate_bundle(
+        &mut self,
+        txs: &[N::TransactionRequest],
+        overrides: &BundleOverrides,
+    ) -> Result<Vec<SimulatedTransaction>, EvmError> {
+        self.settle_anchor().await?;
+        let _pin = self.pin_anchor().await?;
+
+        let mut db = ProofDB::new(self.anchor.clone(), self.execution.clone());
+        db.state
+            .apply_overrides(&overrides.state_overrides)
+            .await
+            .map_err(EvmError::RpcError)?;
+
+        let mut results = Vec::with_capacity(txs.len());
+        for tx in txs {
+            let ResultAndState { result, state } = self
+                .transact(&mut db, tx, overrides.block_overrides.as_ref())
+                .await?;
+            db.state.commit(state);
+
+            results.push(SimulatedTransaction {
+                success: result.is_success(),
+                gas_used: result.gas_used(),
+                revert_reason: revert_reason(&result),
+                return_data: result.output().cloned().unwrap_or_default(),
+                logs: result.into_logs(),
+            });
+        }
+
+        Ok(results)
+    }
+
+    /// Executes independent `txs` against one snapshot of the verified state, as
+    /// `helios_callMany` does. The state all of them are expected to touch is proven up front
+    /// in one batch, and state proven for one call is not fetched again for the next, but each
+    /// call starts from the snapshot and never sees what another wrote. A call that fails is
+    /// reported in its outcome without ending the batch. Offchain lookups are not followed.
+    pub async fn call_many(
+        &mut self,
+        txs: &[N::TransactionRequest],
+    ) -> Result<Vec<CallOutcome>, EvmError> {
+        if txs.len() > MAX_CALL_MANY_CALLS {
+            return Err(EvmError::TooManyCalls(MAX_CALL_MANY_CALLS));
+        }
+        self.settle_anchor().await?;
+        let _pin = self.pin_anchor().await?;
+
+        let mut db = ProofDB::new(self.anchor.clone(), self.execution.clone());
+        db.state
+            .apply_overrides(&self.state_overrides)
+            .await
+            .map_err(EvmError::RpcError)?;
+        _ = db.state.prefetch_states(txs).await;
+
+        let mut outcomes = Vec::with_capacity(txs.len());
+        for tx in txs {
+            // the post state is dropped rather than committed
+            let outcome = match self.execute(&mut db, tx, None).await {
+                Ok(ResultAndState { result, .. }) => CallOutcome {
+                    success: result.is_success(),
+                    gas_used: result.gas_used(),
+                    revert_reason: revert_reason(&result),
+                    return_data: result.output().cloned().unwrap_or_default(),
+                    error: None,
+                },
+                Err(err) => CallOutcome {
+                    error: Some(err.to_string()),
+                    ..Default::default()
+                },
+            };
+            outcomes.push(outcome);
+        }
+
+        Ok(outcomes)
+    }
+
+    /// Executes `tx` once under the tracer of `options`, as `debug_traceCall` does. The state
+    /// overrides of the options are laid over those of this evm, account by account. Offchain
+    /// lookups are not followed, the trace ends with the `OffchainLookup` revert.
+    pub async fn trace_call(
+        &mut self,
+        tx: &N::TransactionRequest,
+        options: &TraceOptions,
+    ) -> Result<TraceResult, EvmError> {
+        let tracer = Tracer::new(options)?;
+        self.settle_anchor().await?;
+        let _pin = self.pin_anchor().await?;
+
+        let mut overrides = self.state_overrides.clone();
+        for (address, account) in options.state_overrides.iter().flatten() {
+            overrides.entry(*address).or_default().merge(account);
+        }
+        let mut db = ProofDB::new(self.anchor.clone(), self.execution.clone());
+        db.state
+            .apply_overrides(&overrides)
+            .await
+            .map_err(EvmError::RpcError)?;
+
+        let (ResultAndState { result, .. }, tracer, gas_limit) =
+            self.transact_traced(&mut db, tx, tracer).await?;
+        tracer.finish(&result, gas_limit)
+    }
+
     async fn call_inner(&mut self, tx: &N::TransactionRequest) -> Result<ResultAndState, EvmError> {
-        let mut db = ProofDB::new(self.tag, self.execution.clone());
+        self.settle_anchor().await?;
+        let _pin = self.pin_anchor().await?;
+
+        let mut db = ProofDB::new(self.anchor.clone(), self.execution.clone());
+        db.state
+            .apply_overrides(&self.state_overrides)
+            .await
+            .map_err(EvmError::RpcError)?;
+        self.transact(&mut db, tx, None).await
+    }
+
+    /// Proofs for a numbered block are fetched lazily, so it must outlive the execution in
+    /// state. A block that cannot be pinned fails the execution rather than risk its state
+    /// going missing halfway.
+    async fn pin_anchor(&self) -> Result<Option<BlockPin>, EvmError> {
+        match self.anchor {
+            StateAnchor::Tag(tag @ BlockTag::Number(_)) => self
+                .execution
+                .pin_block(tag, RetentionReason::Anchor)
+                .await
+                .map(Some)
+                .map_err(|err| EvmError::RpcError(err.into())),
+            _ => Ok(None),
+        }
+    }
+
+    /// Prefetches the state `tx` is expected to touch and executes it against `db`.
+    async fn transact(
+        &self,
+        db: &mut ProofDB<N, R>,
+        tx: &N::TransactionRequest,
+        block: Option<&BlockOverrides>,
+    ) -> Result<ResultAndState, EvmError> {
         _ = db.state.prefetch_state(tx).await;
+        self.execute(db, tx, block).await
+    }
 
-        let env = Box::new(self.get_env(tx, self.tag).await);
+    /// Executes `tx` against `db`, fetching the state it reaches that `db` is missing.
+    async fn execute(
+        &self,
+        db: &mut ProofDB<N, R>,
+        tx: &N::TransactionRequest,
+        block: Option<&BlockOverrides>,
+    ) -> Result<ResultAndState, EvmError> {
+        let env = Box::new(self.get_env(tx, block).await?);
         let evm = Revm::builder().with_db(db).with_env(env).build();
         let mut ctx = evm.into_context_with_handler_cfg();
 
+        let max_refetches = self.execution.evm_config().max_state_refetches;
+        let mut refetches = 0;
         let tx_res = loop {
-            let db = ctx.context.evm.db.borrow_mut();
-            if db.state.needs_update() {
-                db.state.update_state().await.unwrap();
-            }
+            let db = &mut ctx.context.evm.db;
+            db.state.refetch(&mut refetches, max_refetches).await?;
 
             let mut evm = Revm::builder().with_context_with_handler_cfg(ctx).build();
             let res = evm.transact();
             ctx = evm.into_context_with_handler_cfg();
 
-            let db = ctx.context.evm.db.borrow_mut();
-            let needs_update = db.state.needs_update();
+            // an execution that read missing state ran over placeholders, so it is run again
+            if !ctx.context.evm.db.state.needs_update() {
+                break res;
+            }
+        };
+
+        tx_res.map_err(evm_error)
+    }
+
+    /// Executes `tx` against `db` like [`Self::transact`], with `tracer` inspecting it.
+    /// Returns the tracer along with the gas limit of the transaction.
+    async fn transact_traced<T>(
+        &self,
+        db: &mut ProofDB<N, R>,
+        tx: &N::TransactionRequest,
+        tracer: T,
+    ) -> Result<(ResultAndState, T, u64), EvmError>
+    where
+        T: Restart + for<'a> Inspector<&'a mut ProofDB<N, R>>,
+    {
+        _ = db.state.prefetch_state(tx).await;
 
-            if res.is_ok() || !needs_update {
+        let env = Box::new(self.get_env(tx, None).await?);
+        let gas_limit = env.tx.gas_limit;
+        let evm = Revm::builder()
+            .with_db(db)
+            .with_external_context(tracer)
+            .with_env(env)
+            .append_handler_register(inspector_handle_register)
+            .build();
+        let mut ctx = evm.into_context_with_handler_cfg();
+
+        let max_refetches = self.execution.evm_config().max_state_refetches;
+        let mut refetches = 0;
+        let tx_res = loop {
+            let db = &mut ctx.context.evm.db;
+            db.state.refetch(&mut refetches, max_refetches).await?;
+            // an execution cut short by missing state is traced again from the start
+            ctx.context.external.reset();
+
+            let mut evm = Revm::builder()
+                .with_context_with_handler_cfg(ctx)
+                .append_handler_register(inspector_handle_register)
+                .build();
+            let res = evm.transact();
+            ctx = evm.into_context_with_handler_cfg();
+
+            // an execution that read missing state ran over placeholders, so it is run again
+            if !ctx.context.evm.db.state.needs_update() {
                 break res;
             }
         };
 
-        tx_res.map_err(|_| EvmError::Generic("evm error".to_string()))
+        let res = tx_res.map_err(evm_error)?;
+        Ok((res, ctx.context.external, gas_limit))
     }
 
-    async fn get_env(&self, tx: &N::TransactionRequest, tag: BlockTag) -> Env {
-        let block = self
+    /// Anchors a call at the confirmed block to the block it resolves to now, since the tag
+    /// moves with the head, and checks a supplied header once for the reads to come.
+    async fn settle_anchor(&mut self) -> Result<(), EvmError> {
+        match &self.anchor {
+            StateAnchor::Tag(BlockTag::Confirmed) => {
+                let header = self
+                    .execution
+                    .anchor_header(&self.anchor)
+                    .await
+                    .map_err(EvmError::RpcError)?;
+                self.anchor = StateAnchor::Tag(BlockTag::Number(header.number()));
+            }
+            // a block before those held in state executes against its verified header, as
+            // the state cannot resolve or pin it by number
+            StateAnchor::Tag(BlockTag::Number(number))
+                if self.execution.is_historical(*number).await =>
+            {
+                let header = self
+                    .execution
+                    .get_verified_header(*number)
+                    .await
+                    .map_err(EvmError::RpcError)?;
+                self.anchor = StateAnchor::Verified(header, AnchorTrust::Consensus);
+            }
+            StateAnchor::Header(header) => {
+                self.anchor = self
+                    .execution
+                    .verify_supplied_header(header.clone())
+                    .await
+                    .map_err(EvmError::RpcError)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn get_env(
+        &self,
+        tx: &N::TransactionRequest,
+        block: Option<&BlockOverrides>,
+    ) -> Result<Env, EvmError> {
+        let header = self
             .execution
-            .get_block(tag, false)
+            .anchor_header(&self.anchor)
             .await
-            .ok_or(ExecutionError::BlockNotFound(tag))
-            .unwrap();
+            .map_err(EvmError::RpcError)?;
 
         let mut cfg = CfgEnv::default();
         cfg.chain_id = self.chain_id;
@@ -114,11 +589,22 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> Evm<N, R> {
         cfg.disable_eip3607 = true;
         cfg.disable_base_fee = true;
 
-        Env {
+        let mut block_env = N::block_env(&header, &self.fork_schedule);
+        if let Some(overrides) = block {
+            overrides.apply(&mut block_env);
+        }
+
+        let mut env = Env {
             tx: N::tx_env(tx),
-            block: N::block_env(&block, &self.fork_schedule),
+            block: block_env,
             cfg,
+        };
+        if !env.tx.blob_hashes.is_empty() || env.tx.max_fee_per_blob_gas.is_some() {
+            let params = N::blob_params(&header, &self.fork_schedule);
+            check_blob_transaction(&mut env, params, header.number())?;
         }
+
+        Ok(env)
     }
 }
 
@@ -127,200 +613,383 @@ struct ProofDB<N: NetworkSpec, R: ExecutionRpc<N>> {
 }
 
 impl<N: NetworkSpec, R: ExecutionRpc<N>> ProofDB<N, R> {
-    pub fn new(tag: BlockTag, execution: Arc<ExecutionClient<N, R>>) -> Self {
-        let state = EvmState::new(execution.clone(), tag);
+    pub fn new(
+        anchor: StateAnchor<N::HeaderResponse>,
+        execution: Arc<ExecutionClient<N, R>>,
+    ) -> Self {
+        let state = EvmState::new(execution.clone(), anchor);
         ProofDB { state }
     }
 }
 
+#[derive(PartialEq, Eq)]
 enum StateAccess {
     Basic(Address),
     BlockHash(u64),
     Storage(Address, U256),
 }
 
+impl fmt::Display for StateAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Basic(address) => write!(f, "account {address}"),
+            Self::BlockHash(number) => write!(f, "hash of block {number}"),
+            Self::Storage(address, slot) => write!(f, "slot {} of {address}", B256::from(*slot)),
+        }
+    }
+}
+
 struct EvmState<N: NetworkSpec, R: ExecutionRpc<N>> {
     basic: HashMap<Address, AccountInfo>,
     block_hash: HashMap<u64, B256>,
     storage: HashMap<Address, HashMap<U256, U256>>,
-    block: BlockTag,
-    access: Option<StateAccess>,
+    /// Accounts whose storage is fully known in `storage`, with missing slots reading zero.
+    cleared: HashSet<Address>,
+    anchor: StateAnchor<N::HeaderResponse>,
+    /// The state the last execution read without holding it, in the order it was read.
+    missing: Vec<StateAccess>,
     execution: Arc<ExecutionClient<N, R>>,
 }
 
 impl<N: NetworkSpec, R: ExecutionRpc<N>> EvmState<N, R> {
-    pub fn new(execution: Arc<ExecutionClient<N, R>>, block: BlockTag) -> Self {
+    pub fn new(
+        execution: Arc<ExecutionClient<N, R>>,
+        anchor: StateAnchor<N::HeaderResponse>,
+    ) -> Self {
         Self {
             execution,
-            block,
+            anchor,
             basic: HashMap::new(),
             storage: HashMap::new(),
+            cleared: HashSet::new(),
             block_hash: HashMap::new(),
-            access: None,
+            missing: Vec::new(),
         }
     }
 
+    /// Fetches all the state the last execution was missing at once: the accounts in
+    /// batches, each proven with the slots read from it, followed by the block hashes.
     pub async fn update_state(&mut self) -> Result<()> {
-        if let Some(access) = &self.access.take() {
+        let mut accounts = HashMap::<Address, Vec<B256>>::new();
+        let mut blocks = Vec::new();
+        for access in std::mem::take(&mut self.missing) {
             match access {
                 StateAccess::Basic(address) => {
-                    let account = self
-                        .execution
-                        .get_account(*address, None, self.block)
-                        .await?;
-
-                    self.basic.insert(
-                        *address,
-                        AccountInfo::new(
-                            account.balance,
-                            account.nonce,
-                            account.code_hash,
-                            Bytecode::new_raw(account.code.into()),
-                        ),
-                    );
+                    accounts.entry(address).or_default();
                 }
                 StateAccess::Storage(address, slot) => {
-                    let slot_bytes = B256::from(*slot);
-                    let account = self
-                        .execution
-                        .get_account(*address, Some(&[slot_bytes]), self.block)
-                        .await?;
-
-                    let storage = self.storage.entry(*address).or_default();
-                    let value = *account.slots.get(&slot_bytes).unwrap();
-                    storage.insert(*slot, value);
-                }
-                StateAccess::BlockHash(number) => {
-                    let tag = BlockTag::Number(*number);
-                    let block = self
-                        .execution
-                        .get_block(tag, false)
-                        .await
-                        .ok_or(ExecutionError::BlockNotFound(tag))?;
-
-                    self.block_hash.insert(*number, block.header().hash());
+                    accounts.entry(address).or_default().push(slot.into())
                 }
+                StateAccess::BlockHash(number) => blocks.push(number),
             }
         }
 
+        let requests = accounts.into_iter().collect::<Vec<_>>();
+        for chunk in requests.chunks(PARALLEL_QUERY_BATCH_SIZE) {
+            let accounts = self.execution.get_accounts_at(chunk, &self.anchor).await?;
+            for ((address, _), account) in chunk.iter().zip(accounts) {
+                let account = account.wrap_err_with(|| format!("fetching account {address}"))?;
+                self.insert_account(*address, account);
+            }
+        }
+
+        for number in blocks {
+            self.fetch_block_hash(number).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn fetch_block_hash(&mut self, number: u64) -> Result<()> {
+        match &self.anchor {
+            StateAnchor::Header(header) | StateAnchor::Verified(header, _)
+                if header.number() == number + 1 =>
+            {
+                self.block_hash.insert(number, header.parent_hash());
+                return Ok(());
+            }
+            // a supplied header only vouches for its parent, and the verified chain may not
+            // be the one it is on
+            StateAnchor::Header(_) | StateAnchor::Verified(_, AnchorTrust::UserSuppliedHeader) => {
+                return Err(ExecutionError::UnanchoredBlockHash(number).into());
+            }
+            _ => {}
+        }
+
+        let tag = BlockTag::Number(number);
+        let block = self
+            .execution
+            .get_block(tag, false)
+            .await
+            .ok_or(ExecutionError::BlockNotFound(tag))?;
+
+        self.block_hash.insert(number, block.header().hash());
         Ok(())
     }
 
     pub fn needs_update(&self) -> bool {
-        self.access.is_some()
+        !self.missing.is_empty()
+    }
+
+    /// Fetches the state the last execution read without holding it, as one of at most `max`
+    /// rounds counted in `refetches`. Past the limit the call fails naming the state still
+    /// missing.
+    pub async fn refetch(&mut self, refetches: &mut usize, max: usize) -> Result<(), EvmError> {
+        if self.missing.is_empty() {
+            return Ok(());
+        }
+        let missing = self
+            .missing
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if *refetches >= max {
+            return Err(EvmError::MissingState(*refetches, missing));
+        }
+        *refetches += 1;
+
+        trace!(target: "helios::evm", "refetch {} of {}: {}", refetches, max, missing);
+        self.update_state()
+            .await
+            .wrap_err_with(|| format!("fetching {missing}"))
+            .map_err(EvmError::RpcError)
+    }
+
+    /// Proves `address` with `slots` against the state root of the anchor and adds them to
+    /// the state, keeping what is already held.
+    pub async fn fetch_account(&mut self, address: Address, slots: &[B256]) -> Result<()> {
+        let slots = (!slots.is_empty()).then_some(slots);
+        let account = self
+            .execution
+            .get_account_at(address, slots, &self.anchor)
+            .await?;
+        self.insert_account(address, account);
+
+        Ok(())
     }
 
-    pub fn get_basic(&mut self, address: Address) -> Result<AccountInfo> {
+    /// Adds a proven account to the state, keeping what is already held, such as overrides
+    /// or the state an earlier transaction wrote.
+    fn insert_account(&mut self, address: Address, account: Account) {
+        self.basic.entry(address).or_insert_with(|| {
+            AccountInfo::new(
+                account.balance,
+                account.nonce,
+                account.code_hash,
+                Bytecode::new_raw(account.code.into()),
+            )
+        });
+        if !self.cleared.contains(&address) {
+            let storage = self.storage.entry(address).or_default();
+            for (slot, value) in account.slots {
+                storage.entry(slot.into()).or_insert(value);
+            }
+        }
+    }
+
+    /// Records that the execution read `access` without the state holding it. The execution
+    /// carries on over an empty placeholder, so one run finds all it is missing.
+    fn miss(&mut self, access: StateAccess) {
+        if !self.missing.contains(&access) {
+            self.missing.push(access);
+        }
+    }
+
+    pub fn get_basic(&mut self, address: Address) -> AccountInfo {
         if let Some(account) = self.basic.get(&address) {
-            Ok(account.clone())
-        } else {
-            self.access = Some(StateAccess::Basic(address));
-            eyre::bail!("state missing");
+            return account.clone();
         }
+        self.miss(StateAccess::Basic(address));
+        AccountInfo::default()
     }
 
-    pub fn get_storage(&mut self, address: Address, slot: U256) -> Result<U256> {
-        let storage = self.storage.entry(address).or_default();
-        if let Some(slot) = storage.get(&slot) {
-            Ok(*slot)
-        } else {
-            self.access = Some(StateAccess::Storage(address, slot));
-            eyre::bail!("state missing");
+    pub fn get_storage(&mut self, address: Address, slot: U256) -> U256 {
+        if let Some(value) = self
+            .storage
+            .get(&address)
+            .and_then(|storage| storage.get(&slot))
+        {
+            return *value;
+        }
+        if !self.cleared.contains(&address) {
+            self.miss(StateAccess::Storage(address, slot));
         }
+        U256::ZERO
     }
 
-    pub fn get_block_hash(&mut self, block: u64) -> Result<B256> {
+    fn holds_slot(&self, address: Address, slot: U256) -> bool {
+        self.cleared.contains(&address)
+            || self
+                .storage
+                .get(&address)
+                .is_some_and(|storage| storage.contains_key(&slot))
+    }
+
+    pub fn get_block_hash(&mut self, block: u64) -> B256 {
         if let Some(hash) = self.block_hash.get(&block) {
-            Ok(*hash)
-        } else {
-            self.access = Some(StateAccess::BlockHash(block));
-            eyre::bail!("state missing");
+            return *hash;
         }
+        self.miss(StateAccess::BlockHash(block));
+        B256::ZERO
     }
 
     pub async fn prefetch_state(&mut self, tx: &N::TransactionRequest) -> Result<()> {
-        let mut list = self
-            .execution
-            .rpc
-            .create_access_list(tx, self.block)
-            .await
-            .map_err(EvmError::RpcError)?
-            .0;
+        self.prefetch_states(std::slice::from_ref(tx)).await
+    }
 
-        let from_access_entry = AccessListItem {
-            address: tx.from().unwrap_or_default(),
-            storage_keys: Vec::default(),
-        };
+    /// Fetches the state `txs` are expected to touch in batches: the union of the access
+    /// lists the provider creates for them, requested concurrently, along with their senders,
+    /// recipients and the block producer. At most [`PARALLEL_QUERY_BATCH_SIZE`] access lists
+    /// are requested at once. Transactions without an access list find their state lazily,
+    /// and when none has one nothing is fetched.
+    pub async fn prefetch_states(&mut self, txs: &[N::TransactionRequest]) -> Result<()> {
+        let block = self.anchor.block_tag();
+        let rpc = &self.execution.rpc;
+        let lists = stream::iter(txs)
+            .map(|tx| async move { (tx, rpc.create_access_list(tx, block).await) })
+            .buffer_unordered(PARALLEL_QUERY_BATCH_SIZE)
+            .collect::<Vec<_>>()
+            .await;
 
-        let to_access_entry = AccessListItem {
-            address: tx.to().unwrap_or_default(),
-            storage_keys: Vec::default(),
-        };
+        let mut union = HashMap::<Address, HashSet<B256>>::new();
+        let mut failure = None;
+        for (tx, list) in lists {
+            let list = match list {
+                Ok(list) => list,
+                Err(err) => {
+                    failure.get_or_insert(err);
+                    continue;
+                }
+            };
+            for item in list.0 {
+                union
+                    .entry(item.address)
+                    .or_default()
+                    .extend(item.storage_keys);
+            }
+            // a contract creation has no recipient to fetch
+            for address in tx.from().into_iter().chain(tx.to()) {
+                union.entry(address).or_default();
+            }
+        }
+        if union.is_empty() {
+            return failure.map_or(Ok(()), |err| Err(EvmError::RpcError(err).into()));
+        }
 
         let coinbase = self
             .execution
-            .get_block(self.block, false)
-            .await
-            .ok_or(ExecutionError::BlockNotFound(self.block))?
-            .header()
+            .anchor_header(&self.anchor)
+            .await?
             .beneficiary();
-        let producer_access_entry = AccessListItem {
-            address: coinbase,
-            storage_keys: Vec::default(),
-        };
+        union.entry(coinbase).or_default();
 
-        let list_addresses = list.iter().map(|elem| elem.address).collect::<Vec<_>>();
+        // accounts failing to fetch are left for the evm to request once it reaches them,
+        // and state already held, such as overrides, is not fetched again
+        let requests = union
+            .into_iter()
+            .filter_map(|(address, slots)| {
+                let slots = slots
+                    .into_iter()
+                    .filter(|slot| !self.holds_slot(address, (*slot).into()))
+                    .collect::<Vec<_>>();
+                let held = self.basic.contains_key(&address) && slots.is_empty();
+                (!held).then_some((address, slots))
+            })
+            .collect::<Vec<_>>();
+        let mut account_map = HashMap::new();
+        for chunk in requests.chunks(PARALLEL_QUERY_BATCH_SIZE) {
+            let Ok(accounts) = self.execution.get_accounts_at(chunk, &self.anchor).await else {
+                continue;
+            };
+            for ((address, _), account) in chunk.iter().zip(accounts) {
+                if let Ok(account) = account {
+                    account_map.insert(*address, account);
+                }
+            }
+        }
 
-        if !list_addresses.contains(&from_access_entry.address) {
-            list.push(from_access_entry)
+        // state already held, such as that written by an earlier transaction, is kept
+        for (address, account) in account_map {
+            self.insert_account(address, account);
         }
 
-        if !list_addresses.contains(&to_access_entry.address) {
-            list.push(to_access_entry)
+        Ok(())
+    }
+
+    /// Writes the state a transaction left, so the next one executes on top of it.
+    pub fn commit(&mut self, state: PostState) {
+        for (address, account) in state {
+            if !account.is_touched() {
+                continue;
+            }
+
+            if account.is_selfdestructed() || account.is_created() {
+                self.cleared.insert(address);
+                self.storage.remove(&address);
+            }
+            if account.is_selfdestructed() {
+                self.basic.insert(address, AccountInfo::default());
+                continue;
+            }
+
+            self.basic.insert(address, account.info);
+            let storage = self.storage.entry(address).or_default();
+            for (slot, value) in account.storage {
+                storage.insert(slot, value.present_value());
+            }
         }
+    }
 
-        if !list_addresses.contains(&producer_access_entry.address) {
-            list.push(producer_access_entry)
+    /// Applies each override over the verified state of its account, failing before any is
+    /// applied if one sets both `state` and `stateDiff`.
+    pub async fn apply_overrides(&mut self, overrides: &StateOverride) -> Result<()> {
+        if let Some(address) = overrides
+            .iter()
+            .find(|(_, account)| account.state.is_some() && account.state_diff.is_some())
+            .map(|(address, _)| *address)
+        {
+            return Err(ExecutionError::ConflictingStateOverride(address).into());
         }
 
-        let mut account_map = HashMap::new();
-        for chunk in list.chunks(PARALLEL_QUERY_BATCH_SIZE) {
-            let account_chunk_futs = chunk.iter().map(|account| {
-                let account_fut = self.execution.get_account(
-                    account.address,
-                    Some(account.storage_keys.as_slice()),
-                    self.block,
-                );
-                async move { (account.address, account_fut.await) }
-            });
+        for (address, account) in overrides {
+            self.apply_override(*address, account).await?;
+        }
 
-            let account_chunk = join_all(account_chunk_futs).await;
+        Ok(())
+    }
 
-            account_chunk
-                .into_iter()
-                .filter(|i| i.1.is_ok())
-                .for_each(|(key, value)| {
-                    account_map.insert(key, value.ok().unwrap());
-                });
+    /// Applies `account` over the verified state of `address`. The account is only proven
+    /// when the override leaves some of its fields to the verified state.
+    async fn apply_override(&mut self, address: Address, account: &AccountOverride) -> Result<()> {
+        let replaced =
+            account.balance.is_some() && account.nonce.is_some() && account.code.is_some();
+        if replaced {
+            self.basic.entry(address).or_default();
+        } else if !self.basic.contains_key(&address) {
+            self.fetch_account(address, &[]).await?;
         }
 
-        for (address, account) in account_map {
-            self.basic.insert(
-                address,
-                AccountInfo::new(
-                    account.balance,
-                    account.nonce,
-                    account.code_hash,
-                    Bytecode::new_raw(account.code.into()),
-                ),
-            );
+        let info = self.basic.get_mut(&address).unwrap();
+        if let Some(balance) = account.balance {
+            info.balance = balance;
+        }
+        if let Some(nonce) = account.nonce {
+            info.nonce = nonce.to();
+        }
+        if let Some(code) = &account.code {
+            info.code_hash = keccak256(code);
+            info.code = Some(Bytecode::new_raw(code.clone()));
+        }
 
-            for (slot, value) in account.slots {
-                self.storage
-                    .entry(address)
-                    .or_default()
-                    .insert(slot.into(), value);
-            }
+        if account.state.is_some() {
+            self.cleared.insert(address);
+            self.storage.insert(address, HashMap::new());
+        }
+        let storage = self.storage.entry(address).or_default();
+        let slots = account.state.iter().chain(&account.state_diff).flatten();
+        for (slot, value) in slots {
+            storage.insert((*slot).into(), (*value).into());
         }
 
         Ok(())
@@ -341,17 +1010,17 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> Database for ProofDB<N, R> {
             hex::encode(address.as_slice())
         );
 
-        Ok(Some(self.state.get_basic(address)?))
+        Ok(Some(self.state.get_basic(address)))
     }
 
     fn block_hash(&mut self, number: u64) -> Result<B256, Report> {
         trace!(target: "helios::evm", "fetch block hash for block={:?}", number);
-        self.state.get_block_hash(number)
+        Ok(self.state.get_block_hash(number))
     }
 
     fn storage(&mut self, address: Address, slot: U256) -> Result<U256, Report> {
         trace!(target: "helios::evm", "fetch evm state for address={:?}, slot={}", address, slot);
-        self.state.get_storage(address, slot)
+        Ok(self.state.get_storage(address, slot))
     }
 
     fn code_by_hash(&mut self, _code_hash: B256) -> Result<Bytecode, Report> {
@@ -359,6 +1028,99 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> Database for ProofDB<N, R> {
     }
 }
 
+/// The accounts in `state` with the slots loaded from each, leaving out the `warm` ones and
+/// precompiles unless slots were loaded from them. Sorted so reruns compare equal.
+fn touched_access_list(state: &PostState, warm: &[Address]) -> AccessList {
+    let mut items = state
+        .iter()
+        .filter_map(|(address, account)| {
+            let mut storage_keys = account
+                .storage
+                .keys()
+                .map(|slot| B256::from(*slot))
+                .collect::<Vec<_>>();
+            storage_keys.sort();
+
+            let warm = warm.contains(address) || is_precompile(address);
+            if warm && storage_keys.is_empty() {
+                return None;
+            }
+            Some(AccessListItem {
+                address: *address,
+                storage_keys,
+            })
+        })
+        .collect::<Vec<_>>();
+    items.sort_by_key(|item| item.address);
+
+    AccessList(items)
+}
+
+/// Keeps the cause of a failed execution, so a state proof that did not verify is reported
+/// as such rather than as a failure of the evm.
+fn evm_error(err: EVMError<Report>) -> EvmError {
+    match err {
+        EVMError::Database(err) => EvmError::RpcError(err),
+        EVMError::Transaction(err) => EvmError::InvalidTransaction(err.to_string()),
+        err => EvmError::Generic(err.to_string()),
+    }
+}
+
+/// Rejects a blob transaction the evm cannot execute at the block of `env` with the reason,
+/// where revm would fail with a generic error. Without a blob fee cap the blob base fee is
+/// taken as zero, as geth does for calls, so the sender need not hold the blob fees.
+fn check_blob_transaction(
+    env: &mut Env,
+    params: Option<BlobParams>,
+    number: u64,
+) -> Result<(), EvmError> {
+    let unsupported = |reason: String| Err(EvmError::UnsupportedBlobTransaction(reason));
+    let Some(params) = params else {
+        return unsupported(format!(
+            "blob transactions are not supported at block {number}"
+        ));
+    };
+    if env.tx.transact_to.is_create() {
+        return unsupported("blob transactions cannot create contracts".to_string());
+    }
+
+    let blobs = env.tx.blob_hashes.len() as u64;
+    if blobs == 0 {
+        return unsupported("no blob versioned hashes or sidecar given".to_string());
+    }
+    let max_blobs = params.max_blob_gas / DATA_GAS_PER_BLOB;
+    if blobs > max_blobs {
+        return unsupported(format!(
+            "{blobs} blobs exceed the limit of {max_blobs} per block"
+        ));
+    }
+
+    if env.tx.max_fee_per_blob_gas.is_none() {
+        env.tx.max_fee_per_blob_gas = Some(U256::ZERO);
+        if let Some(blob_price) = &mut env.block.blob_excess_gas_and_price {
+            blob_price.blob_gasprice = 0;
+        }
+    }
+    Ok(())
+}
+
+/// Why an unsuccessful execution failed, decoding the revert data when it can.
+fn revert_reason(result: &ExecutionResult) -> Option<String> {
+    match result {
+        ExecutionResult::Success { .. } => None,
+        ExecutionResult::Revert { output, .. } => {
+            Some(decode_revert_reason(output.as_ref()).unwrap_or(hex::encode(output)))
+        }
+        ExecutionResult::Halt { reason, .. } => Some(format!("{:?}", reason)),
+    }
+}
+
+fn signed_delta(before: U256, after: U256) -> I256 {
+    let before = I256::try_from(before).unwrap_or(I256::MAX);
+    let after = I256::try_from(after).unwrap_or(I256::MAX);
+    after.saturating_sub(before)
+}
+
 fn is_precompile(address: &Address) -> bool {
     address.le(&address!("0000000000000000000000000000000000000009")) && address.gt(&Address::ZERO)
 }
@@ -0,0 +1,242 @@
+//! Verification of `eth_feeHistory` answers against the verified headers.
+//!
+//! The base fee and gas used ratio of a block follow from its header, so the entries of
+//! blocks held in state are checked exactly. The trailing base fee, that of the block after
+//! the newest, is checked against the next header when there is one and otherwise against
+//! the EIP-1559 update rule of the network. The blob base fee and blob gas used ratio follow
+//! from the blob fields of the header in the same way, on networks that price blob gas, and
+//! are filled in from the headers when the provider leaves them out. Reward percentiles
+//! would take every transaction and receipt of each block to recompute, so they are served
+//! as the provider reports them.
+//!
+//! Ranges reaching further back than the verified blocks are clamped to them, unless
+//! [`FeeHistoryConfig::allow_partial`] is set. The older entries are then served as reported
+//! and [`FeeHistoryResponse::verified_range`] tells which blocks were checked.
+//!
+//! Suggested gas prices come from the fee history of the latest verified blocks, the base
+//! fee from the latest header and the priority fee from the rewards, per [`GasPriceConfig`].
+
+use alloy::consensus::BlockHeader;
+use alloy::rpc::types::FeeHistory;
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use super::blob::BlobParams;
+use super::constants::{DEFAULT_GAS_PRICE_BLOCKS, DEFAULT_GAS_PRICE_PERCENTILE};
+use super::errors::ExecutionError;
+
+// Gas used ratios are floats, which providers may round differently.
+const RATIO_TOLERANCE: f64 = 1e-9;
+
+/// How fee history ranges outside the verified blocks are answered, read from the
+/// `fee_history` config table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeHistoryConfig {
+    /// Serves the whole requested range, with the entries of blocks before the verified ones
+    /// as reported, instead of clamping the range to the verified blocks.
+    pub allow_partial: bool,
+}
+
+/// How `eth_gasPrice` and `eth_maxPriorityFeePerGas` are suggested, read from the
+/// `gas_price` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GasPriceConfig {
+    /// Latest verified blocks the priority fee is suggested from, fewer while fewer are
+    /// verified.
+    pub blocks: u64,
+    /// Percentile of the priority fees paid in each block, between 0 and 100.
+    #[serde(deserialize_with = "deserialize_percentile")]
+    pub percentile: f64,
+}
+
+fn deserialize_percentile<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f64, D::Error> {
+    let percentile = f64::deserialize(deserializer)?;
+    if !is_percentile(percentile) {
+        return Err(de::Error::custom(ExecutionError::InvalidPercentile(
+            percentile,
+        )));
+    }
+    Ok(percentile)
+}
+
+/// Whether `percentile` lies between 0 and 100, as `eth_feeHistory` takes them.
+pub fn is_percentile(percentile: f64) -> bool {
+    (0.0..=100.0).contains(&percentile)
+}
+
+impl Default for GasPriceConfig {
+    fn default() -> Self {
+        Self {
+            blocks: DEFAULT_GAS_PRICE_BLOCKS,
+            percentile: DEFAULT_GAS_PRICE_PERCENTILE,
+        }
+    }
+}
+
+/// The priority fee to suggest from `history`, fetched with a single reward percentile: the
+/// median of the rewards of the blocks that had transactions, or `None` if none had.
+pub fn suggested_priority_fee(history: &FeeHistory) -> Option<u128> {
+    let rewards = history.reward.as_deref().unwrap_or_default();
+    let mut fees = rewards
+        .iter()
+        .zip(&history.gas_used_ratio)
+        .filter(|(_, ratio)| **ratio > 0.0)
+        .filter_map(|(reward, _)| reward.first().copied())
+        .collect::<Vec<_>>();
+    fees.sort_unstable();
+    fees.get(fees.len() / 2).copied()
+}
+
+/// The blob fee entries of a block, as its verified header determines them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlobFees {
+    pub base_fee: u128,
+    pub used_ratio: f64,
+}
+
+impl BlobFees {
+    /// The entries of the block of `header` under `params`, or `None` if the header lacks
+    /// the blob fields.
+    pub fn of<H: BlockHeader>(params: &BlobParams, header: &H) -> Option<Self> {
+        Some(Self {
+            base_fee: params.base_fee(header.excess_blob_gas()?),
+            used_ratio: params.used_ratio(header.blob_gas_used()?),
+        })
+    }
+}
+
+/// The answer of `eth_feeHistory`, which only differs from the provider's shape for partial
+/// results.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistoryResponse {
+    #[serde(flatten)]
+    pub history: FeeHistory,
+    /// The first and last block whose entries were verified, present only when the history
+    /// starts before them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verified_range: Option<[u64; 2]>,
+}
+
+/// Checks the entries of `history` for `headers`, consecutive verified headers ending with
+/// the newest block of the history. The history may start at `oldest` at the earliest, and
+/// `next_base_fee` is the verified base fee of the block after the newest, if known. The
+/// blob entries are checked against `blob_fees`, those of each header where blob gas is
+/// priced, and `next_blob_base_fee`, if the provider reports them.
+pub fn verify_fee_history<H: BlockHeader>(
+    history: &FeeHistory,
+    oldest: u64,
+    headers: &[H],
+    next_base_fee: Option<u64>,
+    blob_fees: &[Option<BlobFees>],
+    next_blob_base_fee: Option<u128>,
+) -> Result<(), ExecutionError> {
+    let (Some(first), Some(newest)) = (headers.first(), headers.last()) else {
+        return Ok(());
+    };
+    let (first, newest) = (first.number(), newest.number());
+
+    let covered = history.oldest_block >= oldest
+        && history.oldest_block <= first
+        && history.gas_used_ratio.len() as u64 == newest + 1 - history.oldest_block
+        && history.base_fee_per_gas.len() == history.gas_used_ratio.len() + 1;
+    if !covered {
+        return Err(ExecutionError::FeeHistoryRangeMismatch(first, newest));
+    }
+
+    for header in headers {
+        let index = (header.number() - history.oldest_block) as usize;
+        let base_fee = header.base_fee_per_gas().unwrap_or_default();
+        if history.base_fee_per_gas[index] != base_fee as u128 {
+            return Err(ExecutionError::FeeHistoryMismatch(
+                header.number(),
+                "baseFeePerGas",
+            ));
+        }
+
+        let ratio = match header.gas_limit() {
+            0 => 0.0,
+            gas_limit => header.gas_used() as f64 / gas_limit as f64,
+        };
+        if (history.gas_used_ratio[index] - ratio).abs() > RATIO_TOLERANCE {
+            return Err(ExecutionError::FeeHistoryMismatch(
+                header.number(),
+                "gasUsedRatio",
+            ));
+        }
+    }
+
+    if let Some(next_base_fee) = next_base_fee {
+        if history.base_fee_per_gas.last() != Some(&(next_base_fee as u128)) {
+            return Err(ExecutionError::FeeHistoryMismatch(
+                newest + 1,
+                "baseFeePerGas",
+            ));
+        }
+    }
+
+    if history.base_fee_per_blob_gas.is_empty() && history.blob_gas_used_ratio.is_empty() {
+        return Ok(());
+    }
+    let covered = history.base_fee_per_blob_gas.len() == history.base_fee_per_gas.len()
+        && history.blob_gas_used_ratio.len() == history.gas_used_ratio.len();
+    if !covered {
+        return Err(ExecutionError::FeeHistoryRangeMismatch(first, newest));
+    }
+
+    for (header, fees) in headers.iter().zip(blob_fees) {
+        let Some(fees) = fees else {
+            continue;
+        };
+        let index = (header.number() - history.oldest_block) as usize;
+        if history.base_fee_per_blob_gas[index] != fees.base_fee {
+            return Err(ExecutionError::FeeHistoryMismatch(
+                header.number(),
+                "baseFeePerBlobGas",
+            ));
+        }
+        if (history.blob_gas_used_ratio[index] - fees.used_ratio).abs() > RATIO_TOLERANCE {
+            return Err(ExecutionError::FeeHistoryMismatch(
+                header.number(),
+                "blobGasUsedRatio",
+            ));
+        }
+    }
+
+    if let Some(next_blob_base_fee) = next_blob_base_fee {
+        if history.base_fee_per_blob_gas.last() != Some(&next_blob_base_fee) {
+            return Err(ExecutionError::FeeHistoryMismatch(
+                newest + 1,
+                "baseFeePerBlobGas",
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Fills in the blob entries of a checked `history` the provider left out, when it starts
+/// at the first of the headers `blob_fees` are given for and each of them prices blob gas.
+pub fn fill_blob_fees(
+    history: &mut FeeHistory,
+    first: u64,
+    blob_fees: &[Option<BlobFees>],
+    next_blob_base_fee: Option<u128>,
+) {
+    let missing =
+        history.base_fee_per_blob_gas.is_empty() && history.blob_gas_used_ratio.is_empty();
+    if !missing || history.oldest_block != first {
+        return;
+    }
+    let (Some(fees), Some(next_blob_base_fee)) = (
+        blob_fees.iter().copied().collect::<Option<Vec<_>>>(),
+        next_blob_base_fee,
+    ) else {
+        return;
+    };
+
+    history.base_fee_per_blob_gas = fees.iter().map(|fees| fees.base_fee).collect();
+    history.base_fee_per_blob_gas.push(next_blob_base_fee);
+    history.blob_gas_used_ratio = fees.iter().map(|fees| fees.used_ratio).collect();
+}
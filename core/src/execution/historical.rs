@@ -0,0 +1,306 @@
+//! State of blocks older than those held in state, served by proving it against headers
+//! verified on demand.
+//!
+//! A block that left the window is still committed to by the parent hash of its child, so
+//! walking the parent hashes back from the oldest verified block reaches it through headers
+//! that each hash to what the next one commits to. Its state root then anchors `eth_getProof`
+//! and `eth_getCode` like that of any verified block. Headers verified this way are kept, so
+//! a later walk stops at the closest one, and with a [Database] they are kept in its
+//! [`VERIFIED_HEADERS`] namespace too, so a restarted client does not walk them again. They
+//! are as trusted as the checkpoint kept in the same database. Blocks leaving the window
+//! once headers were walked keep their headers the same way, so the walked ones stay linked
+//! to it, and a header no longer linking to the one or block after it goes with all before.
+//!
+//! Only archive providers keep the state of old blocks. Full nodes prune it, which surfaces
+//! as [`ExecutionError::HistoricalStatePruned`] rather than the provider's own message.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use alloy::consensus::BlockHeader;
+use alloy::network::primitives::HeaderResponse;
+use alloy::primitives::B256;
+use eyre::{Report, Result};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+use super::constants::DEFAULT_MAX_HISTORICAL_DEPTH;
+use super::errors::ExecutionError;
+use crate::database::{blocking, Database};
+use crate::network_spec::NetworkSpec;
+
+/// The namespace verified historical headers are kept in, keyed by big endian block number.
+pub const VERIFIED_HEADERS: &str = "verified_headers";
+
+// Parts of the messages geth, erigon, reth and nethermind answer with for pruned state.
+const PRUNED_STATE_MARKERS: &[&str] = &[
+    "missing trie node",
+    "state not available",
+    "state is not available",
+    "historical state",
+    "pruned",
+    "header not found",
+    "distance to target block exceeds",
+];
+
+/// How far back state is served, read from the `historical_state` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HistoricalStateConfig {
+    /// Serves state of blocks before the verified ones, instead of failing to find them.
+    pub enabled: bool,
+    /// Number of blocks before the oldest verified one the header walk may go back.
+    pub max_depth: u64,
+}
+
+impl Default for HistoricalStateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_depth: DEFAULT_MAX_HISTORICAL_DEPTH,
+        }
+    }
+}
+
+/// Checks that `parents`, headers fetched for the blocks right before `child` from the
+/// closest down, hash link back from it.
+pub fn verify_header_chain<N: NetworkSpec>(
+    child: &N::HeaderResponse,
+    parents: &[N::HeaderResponse],
+) -> Result<(), ExecutionError> {
+    let mut child = child;
+    for parent in parents {
+        if parent.number() + 1 != child.number() {
+            return Err(ExecutionError::BrokenHeaderChain(
+                child.number() - 1,
+                child.parent_hash(),
+            ));
+        }
+        if !N::is_header_hash_valid(parent) {
+            return Err(ExecutionError::InvalidHeaderHash(parent.hash()));
+        }
+        if parent.hash() != child.parent_hash() {
+            return Err(ExecutionError::BrokenHeaderChain(
+                parent.number(),
+                child.parent_hash(),
+            ));
+        }
+        child = parent;
+    }
+
+    Ok(())
+}
+
+/// Headers of blocks before the window of a state, verified by walking parent hashes back
+/// from it, by block number.
+pub struct HeaderStore<N: NetworkSpec> {
+    headers: BTreeMap<u64, N::HeaderResponse>,
+    /// Blocks before the window headers are kept for.
+    max_depth: u64,
+    writes: Option<mpsc::UnboundedSender<NumberedWrite>>,
+}
+
+/// A change to entries keyed by big endian number, applied in order off the state lock.
+pub(crate) enum NumberedWrite {
+    Put(u64, Vec<u8>),
+    Delete(Vec<u64>),
+}
+
+impl<N: NetworkSpec> Default for HeaderStore<N> {
+    fn default() -> Self {
+        Self {
+            headers: BTreeMap::new(),
+            max_depth: DEFAULT_MAX_HISTORICAL_DEPTH,
+            writes: None,
+        }
+    }
+}
+
+impl<N: NetworkSpec> HeaderStore<N> {
+    /// Keeps headers in the [`VERIFIED_HEADERS`] namespace of `db` as well, taking over those
+    /// already there. Entries that do not decode to a header of their number are deleted.
+    pub fn persist(&mut self, db: Arc<dyn Database>) -> Result<()> {
+        let mut malformed = Vec::new();
+        for entry in db.entries(VERIFIED_HEADERS)? {
+            match stored_header::<N>(db.as_ref(), &entry.key)? {
+                Some(header) => {
+                    self.headers.insert(header.number(), header);
+                }
+                None => malformed.push(entry.key),
+            }
+        }
+        if !malformed.is_empty() {
+            warn!(
+                target: "helios::execution",
+                count = malformed.len(),
+                "dropping malformed stored headers"
+            );
+            db.delete(VERIFIED_HEADERS, &malformed)?;
+        }
+
+        self.writes = Some(spawn_writer(db, VERIFIED_HEADERS));
+        Ok(())
+    }
+
+    pub fn get(&self, number: u64) -> Option<&N::HeaderResponse> {
+        self.headers.get(&number)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    pub fn max_depth(&self) -> u64 {
+        self.max_depth
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: u64) {
+        self.max_depth = max_depth;
+    }
+
+    /// The header of the closest block after `number`.
+    pub fn first_after(&self, number: u64) -> Option<&N::HeaderResponse> {
+        self.headers
+            .range(number + 1..)
+            .next()
+            .map(|(_, header)| header)
+    }
+
+    pub fn insert(&mut self, header: N::HeaderResponse) {
+        let number = header.number();
+        match serde_json::to_vec(&header) {
+            Ok(value) => self.write(NumberedWrite::Put(number, value)),
+            Err(err) => warn!(target: "helios::execution", number, "header not stored: {}", err),
+        }
+        self.headers.insert(number, header);
+    }
+
+    /// Drops the headers of block `end` and later.
+    pub fn drop_from(&mut self, end: u64) {
+        let dropped = self.headers.split_off(&end);
+        self.delete(dropped.into_keys().collect());
+    }
+
+    /// Drops the headers of blocks before `start`.
+    pub fn drop_before(&mut self, start: u64) {
+        let kept = self.headers.split_off(&start);
+        let dropped = std::mem::replace(&mut self.headers, kept);
+        self.delete(dropped.into_keys().collect());
+    }
+
+    /// Drops the first header, from the latest down, that does not hash to the parent hash of
+    /// the header after it, along with all before it. The latest header is checked against
+    /// `oldest`, the oldest block of the window, by its number and parent hash. A header with
+    /// none after it, as across the gap a restart leaves, has nothing to be checked against
+    /// and is kept.
+    pub fn drop_unlinked(&mut self, oldest: (u64, B256)) {
+        let mut child = oldest;
+        let broken = self.headers.iter().rev().find_map(|(number, header)| {
+            let unlinked = number + 1 == child.0 && header.hash() != child.1;
+            child = (*number, header.parent_hash());
+            unlinked.then_some(*number)
+        });
+        if let Some(number) = broken {
+            self.drop_before(number + 1);
+        }
+    }
+
+    fn delete(&self, numbers: Vec<u64>) {
+        if !numbers.is_empty() {
+            self.write(NumberedWrite::Delete(numbers));
+        }
+    }
+
+    fn write(&self, write: NumberedWrite) {
+        if let Some(writes) = &self.writes {
+            _ = writes.send(write);
+        }
+    }
+}
+
+/// The header stored under `key`, if it is one of the block the key names.
+fn stored_header<N: NetworkSpec>(
+    db: &dyn Database,
+    key: &[u8],
+) -> Result<Option<N::HeaderResponse>> {
+    let Ok(number) = <[u8; 8]>::try_from(key).map(u64::from_be_bytes) else {
+        return Ok(None);
+    };
+    let Some(value) = db.get(VERIFIED_HEADERS, key)? else {
+        return Ok(None);
+    };
+    let header = serde_json::from_slice::<N::HeaderResponse>(&value).ok();
+    Ok(header.filter(|header| header.number() == number && N::is_header_hash_valid(header)))
+}
+
+/// Applies the writes sent on the returned channel to `namespace` of `db` in order, until it
+/// is dropped. A failed write only costs a refetch on the next start, so it is logged and
+/// skipped.
+pub(crate) fn spawn_writer(
+    db: Arc<dyn Database>,
+    namespace: &'static str,
+) -> mpsc::UnboundedSender<NumberedWrite> {
+    let (send, mut recv) = mpsc::unbounded_channel();
+    let writer = async move {
+        while let Some(write) = recv.recv().await {
+            let db = db.clone();
+            let res = blocking(move || match write {
+                NumberedWrite::Put(number, value) => {
+                    db.put(namespace, &number.to_be_bytes(), &value)
+                }
+                NumberedWrite::Delete(numbers) => {
+                    let keys = numbers
+                        .into_iter()
+                        .map(|number| number.to_be_bytes().to_vec())
+                        .collect::<Vec<_>>();
+                    db.delete(namespace, &keys)
+                }
+            })
+            .await;
+            if let Some(Err(err)) = res {
+                warn!(target: "helios::execution", namespace, "could not store entries: {}", err);
+            }
+        }
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::spawn(writer);
+    #[cfg(target_arch = "wasm32")]
+    wasm_bindgen_futures::spawn_local(writer);
+
+    send
+}
+
+/// Replaces a provider error fetching the state of block `number` that says the state is
+/// pruned with [`ExecutionError::HistoricalStatePruned`], passing others through.
+pub fn pruned_state_error(number: u64, err: Report) -> Report {
+    let message = format!("{err:#}").to_lowercase();
+    if PRUNED_STATE_MARKERS
+        .iter()
+        .any(|marker| message.contains(marker))
+    {
+        ExecutionError::HistoricalStatePruned(number).into()
+    } else {
+        err
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use eyre::eyre;
+
+    use super::*;
+
+    #[test]
+    fn test_pruned_state_error() {
+        let pruned = pruned_state_error(7, eyre!("missing trie node 1f2e3d (path )"));
+        assert!(matches!(
+            pruned.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::HistoricalStatePruned(7))
+        ));
+
+        let other = pruned_state_error(7, eyre!("connection reset by peer"));
+        assert!(other.downcast_ref::<ExecutionError>().is_none());
+    }
+}
@@ -0,0 +1,271 @@
+use std::io;
+
+use alloy::primitives::Bytes;
+use alloy::rpc::types::{EIP1186AccountProofResponse, Log};
+use serde::{Deserialize, Serialize};
+
+use crate::config::ByteSize;
+use crate::network_spec::NetworkSpec;
+
+use super::errors::ExecutionError;
+
+/// Upper bounds on the size of payloads returned by an untrusted execution provider.
+///
+/// These are checked right after a response is decoded and before any verification work
+/// is done, so that an oversized payload is rejected without walking proofs or tries. Read
+/// from the `payload_limits` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PayloadLimits {
+    /// Maximum number of nodes in a single account or storage proof.
+    pub max_proof_nodes: usize,
+    /// Maximum size of a single proof node.
+    pub max_proof_node_size: ByteSize,
+    /// Maximum number of receipts returned for a single block.
+    pub max_receipts_per_block: usize,
+    /// Maximum number of logs contained in a single receipt.
+    pub max_logs_per_receipt: usize,
+    /// Maximum size of the data of a single log, in receipts and `eth_getLogs` answers
+    /// alike.
+    pub max_log_data_size: ByteSize,
+    /// Maximum JSON encoded size of a block fetched from the provider.
+    pub max_block_body_size: ByteSize,
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        Self {
+            // a MPT path is at most 64 nibbles deep, plus leaf/extension nodes
+            max_proof_nodes: 128,
+            // a full branch node is ~532 bytes, leave room for large leaves
+            max_proof_node_size: ByteSize::from_bytes(4 * 1024),
+            max_receipts_per_block: 20_000,
+            max_logs_per_receipt: 10_000,
+            // a block's worth of gas spent on log data alone stays under 4 MiB
+            max_log_data_size: ByteSize::from_mb(4),
+            max_block_body_size: ByteSize::from_mb(64),
+        }
+    }
+}
+
+impl PayloadLimits {
+    /// Checks the account proof and all storage proofs of an `eth_getProof` response.
+    pub fn check_proof(&self, proof: &EIP1186AccountProofResponse) -> Result<(), ExecutionError> {
+        self.check_proof_nodes("account proof", &proof.account_proof)?;
+        for storage_proof in &proof.storage_proof {
+            self.check_proof_nodes("storage proof", &storage_proof.proof)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the number of receipts in a block and the number of logs in each receipt.
+    pub fn check_receipts<N: NetworkSpec>(
+        &self,
+        receipts: &[N::ReceiptResponse],
+    ) -> Result<(), ExecutionError> {
+        check_limit(
+            "receipts per block",
+            receipts.len(),
+            self.max_receipts_per_block,
+        )?;
+        for receipt in receipts {
+            self.check_receipt::<N>(receipt)?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks the number of logs in a single receipt and the size of their data.
+    pub fn check_receipt<N: NetworkSpec>(
+        &self,
+        receipt: &N::ReceiptResponse,
+    ) -> Result<(), ExecutionError> {
+        let logs = N::receipt_logs(receipt);
+        check_limit("logs per receipt", logs.len(), self.max_logs_per_receipt)?;
+        self.check_logs(&logs)
+    }
+
+    /// Checks the size of the data of each log of an `eth_getLogs` answer.
+    pub fn check_logs(&self, logs: &[Log]) -> Result<(), ExecutionError> {
+        let largest = logs.iter().map(|log| log.data().data.len()).max();
+        check_limit(
+            "log data size",
+            largest.unwrap_or(0),
+            self.max_log_data_size.as_usize(),
+        )
+    }
+
+    /// Checks the encoded size of a block returned by the provider. The encoding stops once
+    /// it passes the limit, and a block that cannot be encoded at all is rejected too.
+    pub fn check_block<B: Serialize>(&self, block: &B) -> Result<(), ExecutionError> {
+        let mut counter = SizeCounter {
+            size: 0,
+            limit: self.max_block_body_size.as_usize(),
+        };
+        match serde_json::to_writer(&mut counter, block) {
+            Ok(()) => Ok(()),
+            Err(_) if counter.size > counter.limit => Err(ExecutionError::ProviderPayloadTooLarge(
+                "block body size",
+                counter.size,
+                counter.limit,
+            )),
+            Err(_) => Err(ExecutionError::UnmeasuredPayload("block body size")),
+        }
+    }
+
+    fn check_proof_nodes(&self, kind: &'static str, nodes: &[Bytes]) -> Result<(), ExecutionError> {
+        check_limit(kind, nodes.len(), self.max_proof_nodes)?;
+        let largest = nodes.iter().map(|node| node.len()).max().unwrap_or(0);
+        check_limit(
+            "proof node size",
+            largest,
+            self.max_proof_node_size.as_usize(),
+        )
+    }
+}
+
+/// Counts the bytes of an encoding, failing it once they pass `limit`.
+struct SizeCounter {
+    size: usize,
+    limit: usize,
+}
+
+impl io::Write for SizeCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.size = self.size.saturating_add(buf.len());
+        if self.size > self.limit {
+            return Err(io::Error::other("payload over limit"));
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn check_limit(kind: &'static str, size: usize, limit: usize) -> Result<(), ExecutionError> {
+    if size > limit {
+        Err(ExecutionError::ProviderPayloadTooLarge(kind, size, limit))
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns true if the error was caused by an oversized provider payload.
+pub fn is_payload_too_large(err: &eyre::Report) -> bool {
+    matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::ProviderPayloadTooLarge(..))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{B256, U256};
+    use alloy::rpc::types::EIP1186StorageProof;
+
+    use super::*;
+
+    fn proof_with_nodes(count: usize, size: usize) -> EIP1186AccountProofResponse {
+        EIP1186AccountProofResponse {
+            account_proof: vec![Bytes::from(vec![0u8; size]); count],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_proof_within_limits() {
+        let limits = PayloadLimits::default();
+        limits.check_proof(&proof_with_nodes(10, 532)).unwrap();
+    }
+
+    #[test]
+    fn test_proof_too_many_nodes() {
+        let limits = PayloadLimits::default();
+        let err = limits
+            .check_proof(&proof_with_nodes(limits.max_proof_nodes + 1, 32))
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ExecutionError::ProviderPayloadTooLarge("account proof", _, _)
+        ));
+    }
+
+    #[test]
+    fn test_proof_node_too_large() {
+        let limits = PayloadLimits::default();
+        let err = limits
+            .check_proof(&proof_with_nodes(
+                1,
+                limits.max_proof_node_size.as_usize() + 1,
+            ))
+            .unwrap_err();
+
+        assert!(matches!(
+            err,
+            ExecutionError::ProviderPayloadTooLarge("proof node size", _, _)
+        ));
+    }
+
+    #[test]
+    fn test_storage_proof_too_many_nodes() {
+        let limits = PayloadLimits {
+            max_proof_nodes: 2,
+            ..Default::default()
+        };
+        let mut proof = proof_with_nodes(1, 32);
+        proof.storage_proof.push(EIP1186StorageProof {
+            key: B256::ZERO.into(),
+            value: U256::ZERO,
+            proof: vec![Bytes::new(); 3],
+        });
+
+        let err = limits.check_proof(&proof).unwrap_err();
+        assert!(matches!(
+            err,
+            ExecutionError::ProviderPayloadTooLarge("storage proof", 3, 2)
+        ));
+    }
+
+    #[test]
+    fn test_block_too_large() {
+        let limits = PayloadLimits {
+            max_block_body_size: ByteSize::from_bytes(16),
+            ..Default::default()
+        };
+        let block = vec![0u8; 64];
+
+        assert!(matches!(
+            limits.check_block(&block),
+            Err(ExecutionError::ProviderPayloadTooLarge(
+                "block body size",
+                _,
+                16
+            ))
+        ));
+        assert!(limits.check_block(&0u8).is_ok());
+    }
+
+    #[test]
+    fn test_unencodable_block_rejected() {
+        struct Unencodable;
+        impl Serialize for Unencodable {
+            fn serialize<S: serde::Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+                Err(serde::ser::Error::custom("unencodable"))
+            }
+        }
+
+        // a block of unknown size is not let through, however high the limit
+        let limits = PayloadLimits {
+            max_block_body_size: ByteSize::from_bytes(u64::MAX),
+            ..Default::default()
+        };
+        assert!(matches!(
+            limits.check_block(&Unencodable),
+            Err(ExecutionError::UnmeasuredPayload("block body size"))
+        ));
+    }
+}
@@ -0,0 +1,297 @@
+//! Exact matching of logs against `eth_getLogs` filters.
+//!
+//! Providers do not all agree on the finer points of filters, such as nested alternatives
+//! or wildcards between constrained positions. The matcher here is applied to the logs of
+//! verified receipts, so answers follow the filter semantics whatever the provider made of
+//! them.
+
+use alloy::primitives::{Bloom, BloomInput, Log as PrimitiveLog, B256};
+use alloy::rpc::types::{Filter, Log, Topic};
+use serde::{Deserialize, Serialize};
+
+use super::constants::{DEFAULT_MAX_LOG_BLOCKS, DEFAULT_RECEIPT_CACHE_BLOCKS};
+
+/// How `eth_getLogs` answers are verified, read from the `logs` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogVerificationConfig {
+    /// Blocks a filter may span, as the receipts of each may have to be fetched.
+    pub max_blocks: u64,
+    /// Rejects answers that left out matching logs, instead of serving those from the
+    /// verified receipts.
+    pub strict: bool,
+    /// Blocks whose verified receipts are kept, so overlapping filters fetch each block once.
+    pub receipt_cache_blocks: usize,
+}
+
+impl Default for LogVerificationConfig {
+    fn default() -> Self {
+        Self {
+            max_blocks: DEFAULT_MAX_LOG_BLOCKS,
+            strict: false,
+            receipt_cache_blocks: DEFAULT_RECEIPT_CACHE_BLOCKS,
+        }
+    }
+}
+
+/// Whether `log` falls within the block range, addresses and topics of `filter`.
+pub fn log_matches_filter(log: &Log, filter: &Filter) -> bool {
+    log_in_range(log, filter) && log_matches_content(&log.inner, filter)
+}
+
+/// Whether `log` was emitted in a block the filter covers. Logs without a block hash or
+/// number are outside any range that constrains them.
+pub fn log_in_range(log: &Log, filter: &Filter) -> bool {
+    if let Some(block_hash) = filter.get_block_hash() {
+        if log.block_hash != Some(block_hash) {
+            return false;
+        }
+    }
+    if let Some(from_block) = filter.get_from_block() {
+        if !log.block_number.is_some_and(|number| number >= from_block) {
+            return false;
+        }
+    }
+    if let Some(to_block) = filter.get_to_block() {
+        if !log.block_number.is_some_and(|number| number <= to_block) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether the address and topics of `log` satisfy `filter`, wherever it was emitted.
+pub fn log_matches_content(log: &PrimitiveLog, filter: &Filter) -> bool {
+    filter.address.matches(&log.address) && topics_match(&filter.topics, log.data.topics())
+}
+
+/// Whether `topics` satisfy each position of `filter`.
+///
+/// An empty position is a wildcard, matching any topic or none at all, which is what both a
+/// `null` and an empty list of alternatives parse to. Any other position holds alternatives,
+/// one of which the topic at that position must equal, so a log with fewer topics than the
+/// last constrained position never matches.
+pub fn topics_match(filter: &[Topic], topics: &[B256]) -> bool {
+    filter.iter().enumerate().all(|(position, alternatives)| {
+        alternatives.is_empty()
+            || topics
+                .get(position)
+                .is_some_and(|topic| alternatives.matches(topic))
+    })
+}
+
+/// Whether a block with `bloom` may hold a log matching the addresses and topics of
+/// `filter`. Blooms have false positives but no false negatives, so `false` rules the block
+/// out, and a zero bloom rules out every filter since the block has no logs at all.
+pub fn bloom_may_match(bloom: &Bloom, filter: &Filter) -> bool {
+    if *bloom == Bloom::ZERO {
+        return false;
+    }
+
+    let contains = |input: &[u8]| bloom.contains_input(BloomInput::Raw(input));
+    let address = filter.address.is_empty()
+        || filter
+            .address
+            .iter()
+            .any(|address| contains(address.as_slice()));
+    let topics = filter.topics.iter().all(|alternatives| {
+        alternatives.is_empty() || alternatives.iter().any(|topic| contains(topic.as_slice()))
+    });
+    address && topics
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{address, Address, LogData};
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    const A: B256 = B256::repeat_byte(0xaa);
+    const B: B256 = B256::repeat_byte(0xbb);
+    const C: B256 = B256::repeat_byte(0xcc);
+    const D: B256 = B256::repeat_byte(0xdd);
+    const X: B256 = B256::repeat_byte(0xee);
+
+    const TOKEN: Address = address!("00000000000000000000000000000000000000a1");
+    const OTHER: Address = address!("00000000000000000000000000000000000000b2");
+
+    /// The logs each topic filter shape is matched against.
+    const LOG_TOPICS: [&[B256]; 6] = [&[], &[A], &[A, B], &[A, B, C], &[A, B, C, D], &[X, B, C]];
+
+    fn log(address: Address, topics: &[B256]) -> Log {
+        Log {
+            inner: PrimitiveLog {
+                address,
+                data: LogData::new_unchecked(topics.to_vec(), Default::default()),
+            },
+            block_hash: Some(B256::repeat_byte(0x01)),
+            block_number: Some(100),
+            ..Default::default()
+        }
+    }
+
+    /// A filter parsed from the json an rpc caller sends, so nested and `null` positions
+    /// take the shape they do in practice.
+    fn filter(params: Value) -> Filter {
+        serde_json::from_value(params).unwrap()
+    }
+
+    #[test]
+    fn test_topic_filter_shapes() {
+        // each shape, and whether it matches each entry of LOG_TOPICS
+        let cases = [
+            (json!([]), [true, true, true, true, true, true]),
+            (json!([null]), [true, true, true, true, true, true]),
+            (json!([null, null, null, null]), [true; 6]),
+            (json!([[]]), [true; 6]),
+            (json!([A]), [false, true, true, true, true, false]),
+            (json!([[A]]), [false, true, true, true, true, false]),
+            (json!([[A, X]]), [false, true, true, true, true, true]),
+            (json!([C]), [false; 6]),
+            (json!([null, B]), [false, false, true, true, true, true]),
+            (json!([[], B]), [false, false, true, true, true, true]),
+            (json!([A, B]), [false, false, true, true, true, false]),
+            (json!([A, [B, D]]), [false, false, true, true, true, false]),
+            (json!([null, [C, D]]), [false; 6]),
+            (
+                json!([A, null, C]),
+                [false, false, false, true, true, false],
+            ),
+            (
+                json!([[A, X], null, C]),
+                [false, false, false, true, true, true],
+            ),
+            (
+                json!([X, null, C]),
+                [false, false, false, false, false, true],
+            ),
+            (
+                json!([null, null, null, D]),
+                [false, false, false, false, true, false],
+            ),
+            (
+                json!([null, null, C, null]),
+                [false, false, false, true, true, true],
+            ),
+            (
+                json!([A, B, C, D]),
+                [false, false, false, false, true, false],
+            ),
+            (
+                json!([[A, X], [B], [C, D], null]),
+                [false, false, false, true, true, true],
+            ),
+            (json!([[A, X], [B], [C, D], [X]]), [false; 6]),
+        ];
+
+        for (topics, expected) in cases {
+            let filter = filter(json!({ "topics": topics }));
+            for (log_topics, expected) in LOG_TOPICS.iter().zip(expected) {
+                assert_eq!(
+                    topics_match(&filter.topics, log_topics),
+                    expected,
+                    "filter {topics} against log topics {log_topics:?}"
+                );
+                assert_eq!(
+                    log_matches_filter(&log(TOKEN, log_topics), &filter),
+                    expected,
+                    "filter {topics} against log topics {log_topics:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_address_filter_shapes() {
+        let token = log(TOKEN, &[A]);
+        let other = log(OTHER, &[A]);
+
+        let cases = [
+            (json!({}), [true, true]),
+            (json!({ "address": TOKEN }), [true, false]),
+            (json!({ "address": [TOKEN] }), [true, false]),
+            (json!({ "address": [TOKEN, OTHER] }), [true, true]),
+            (json!({ "address": [] }), [true, true]),
+            (json!({ "address": OTHER, "topics": [A] }), [false, true]),
+            (json!({ "address": TOKEN, "topics": [B] }), [false, false]),
+        ];
+
+        for (params, [matches_token, matches_other]) in cases {
+            let filter = filter(params.clone());
+            assert_eq!(
+                log_matches_filter(&token, &filter),
+                matches_token,
+                "{params}"
+            );
+            assert_eq!(
+                log_matches_filter(&other, &filter),
+                matches_other,
+                "{params}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_block_range() {
+        let log = log(TOKEN, &[A]);
+        let hash = log.block_hash.unwrap();
+
+        assert!(log_matches_filter(
+            &log,
+            &Filter::new().from_block(100).to_block(100)
+        ));
+        assert!(log_matches_filter(&log, &Filter::new().from_block(90)));
+        assert!(!log_matches_filter(&log, &Filter::new().from_block(101)));
+        assert!(!log_matches_filter(&log, &Filter::new().to_block(99)));
+        assert!(log_matches_filter(&log, &Filter::new().at_block_hash(hash)));
+        assert!(!log_matches_filter(
+            &log,
+            &Filter::new().at_block_hash(B256::repeat_byte(0x02))
+        ));
+
+        // a pending log carries no block, so it is outside any range
+        let pending = Log {
+            block_hash: None,
+            block_number: None,
+            ..log
+        };
+        assert!(log_matches_filter(&pending, &Filter::new()));
+        assert!(!log_matches_filter(&pending, &Filter::new().from_block(0)));
+        assert!(!log_matches_filter(
+            &pending,
+            &Filter::new().at_block_hash(hash)
+        ));
+    }
+
+    #[test]
+    fn test_bloom_admits_every_matching_log() {
+        let mut bloom = Bloom::ZERO;
+        bloom.accrue_log(&log(TOKEN, &[A, B, C]).inner);
+
+        for topics in [
+            json!([]),
+            json!([A]),
+            json!([[A, X], null, C]),
+            json!([null, B]),
+        ] {
+            let filter = filter(json!({ "address": TOKEN, "topics": topics }));
+            assert!(bloom_may_match(&bloom, &filter), "{topics}");
+        }
+
+        for params in [
+            json!({ "address": OTHER }),
+            json!({ "topics": [X] }),
+            json!({ "topics": [A, null, null, D] }),
+            json!({ "address": [OTHER, TOKEN], "topics": [[X, D]] }),
+        ] {
+            assert!(
+                !bloom_may_match(&bloom, &filter(params.clone())),
+                "{params}"
+            );
+        }
+
+        assert!(bloom_may_match(&bloom, &Filter::new()));
+        assert!(!bloom_may_match(&Bloom::ZERO, &Filter::new()));
+    }
+}
@@ -0,0 +1,98 @@
+//! Paging through the verified logs of a block range for `helios_getLogsPaged`.
+//!
+//! A single `eth_getLogs` answer has to fit the block span and proof limits, so indexers
+//! exporting a longer range walk it a page at a time instead. Each page ends with an opaque
+//! cursor naming the block and log index the next page starts at, along with the hash of
+//! that block. Blocks are hash linked, so a cursor whose block is still canonical proves
+//! that every log delivered before it is too. One whose block a reorg replaced is refused,
+//! naming the block to restart from.
+
+use alloy::hex;
+use alloy::primitives::B256;
+use alloy::rpc::types::Log;
+use serde::{Deserialize, Serialize};
+
+use super::errors::ExecutionError;
+
+const CURSOR_LENGTH: usize = 48;
+
+/// Where the next page of logs starts: at the log with index `log_index` or above in block
+/// `block`, whose hash was `block_hash` when the cursor was made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogCursor {
+    pub block: u64,
+    pub log_index: u64,
+    pub block_hash: B256,
+}
+
+impl LogCursor {
+    /// The cursor as served, to be handed back unchanged.
+    pub fn encode(&self) -> String {
+        let mut bytes = Vec::with_capacity(CURSOR_LENGTH);
+        bytes.extend_from_slice(&self.block.to_be_bytes());
+        bytes.extend_from_slice(&self.log_index.to_be_bytes());
+        bytes.extend_from_slice(self.block_hash.as_slice());
+        hex::encode_prefixed(bytes)
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self, ExecutionError> {
+        let invalid = || ExecutionError::InvalidLogCursor(cursor.to_string());
+        let bytes = hex::decode(cursor).map_err(|_| invalid())?;
+        if bytes.len() != CURSOR_LENGTH {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            block: u64::from_be_bytes(bytes[..8].try_into().unwrap()),
+            log_index: u64::from_be_bytes(bytes[8..16].try_into().unwrap()),
+            block_hash: B256::from_slice(&bytes[16..]),
+        })
+    }
+}
+
+/// A page of the logs matching a filter, as served by `helios_getLogsPaged`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogPage {
+    pub logs: Vec<Log>,
+    /// Resumes the same filter after the last log of the page, none once its range is
+    /// exhausted.
+    pub cursor: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = LogCursor {
+            block: 21_000_000,
+            log_index: 17,
+            block_hash: B256::repeat_byte(0xab),
+        };
+        let encoded = cursor.encode();
+        assert_eq!(encoded.len(), 2 + 2 * CURSOR_LENGTH);
+        assert_eq!(LogCursor::decode(&encoded).unwrap(), cursor);
+    }
+
+    #[test]
+    fn test_malformed_cursor_rejected() {
+        let encoded = LogCursor {
+            block: 1,
+            log_index: 0,
+            block_hash: B256::ZERO,
+        }
+        .encode();
+
+        for cursor in ["", "0x", "not hex", &encoded[..encoded.len() - 2]] {
+            assert!(
+                matches!(
+                    LogCursor::decode(cursor),
+                    Err(ExecutionError::InvalidLogCursor(_))
+                ),
+                "{cursor}"
+            );
+        }
+    }
+}
@@ -1,51 +1,216 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use std::{iter, slice};
 
-use alloy::consensus::BlockHeader;
+use alloy::consensus::{BlockHeader, Transaction as _};
+use alloy::eips::{BlockId, BlockNumberOrTag};
 use alloy::network::primitives::HeaderResponse;
-use alloy::network::{BlockResponse, ReceiptResponse};
-use alloy::primitives::{keccak256, Address, B256, U256};
-use alloy::rlp;
-use alloy::rpc::types::{BlockTransactions, Filter, FilterChanges, Log};
-use alloy_trie::root::ordered_trie_root_with_encoder;
+use alloy::network::{BlockResponse, ReceiptResponse, TransactionResponse};
+use alloy::primitives::{keccak256, Address, Bytes, B256, U256};
+use alloy::rpc::types::{
+    BlockTransactions, EIP1186AccountProofResponse, FeeHistory, Filter, FilterBlockOption,
+    FilterChanges, Log,
+};
+use alloy_trie::EMPTY_ROOT_HASH;
 use eyre::Result;
-use futures::future::try_join_all;
-use revm::primitives::{BlobExcessGasAndPrice, KECCAK_EMPTY};
-use tracing::warn;
+use futures::future::{join_all, try_join_all};
+use futures::stream::FuturesUnordered;
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use revm::primitives::KECCAK_EMPTY;
+use tokio::sync::{broadcast, watch};
+use tracing::{debug, warn};
 
 use crate::fork_schedule::ForkSchedule;
 use crate::network_spec::NetworkSpec;
-use crate::types::BlockTag;
-
-use self::constants::MAX_SUPPORTED_LOGS_NUMBER;
+use crate::time::timeout;
+use crate::types::{
+    AnchorTrust, BeaconBlockMapping, BlockTag, ExecutionProof, PrevRandao, PrevRandaoRange,
+    ReorgEvent, EXECUTION_PROOF_VERSION,
+};
+
+use self::cache::AccountCache;
+use self::ccip::CcipGateway;
+use self::code_cache::CodeCache;
+use self::constants::{
+    DEFAULT_PRIORITY_FEE, HISTORICAL_WALK_BATCHES, LOG_PAGE_CHUNK_BLOCKS, MAX_ACCOUNT_CACHE_SIZE,
+    MAX_LOG_PAGE_SCANNED_BLOCKS, MAX_LOG_PAGE_SIZE, MAX_PREV_RANDAO_RANGE,
+    MAX_SUPPORTED_LOGS_NUMBER, PARALLEL_QUERY_BATCH_SIZE,
+};
 use self::errors::ExecutionError;
-use self::proof::{verify_account_proof, verify_storage_proof};
+use self::evm::EvmConfig;
+use self::fee_history::{
+    fill_blob_fees, is_percentile, suggested_priority_fee, verify_fee_history, BlobFees,
+    FeeHistoryConfig, FeeHistoryResponse, GasPriceConfig,
+};
+use self::historical::{pruned_state_error, verify_header_chain, HistoricalStateConfig};
+use self::limits::PayloadLimits;
+use self::log_filter::{
+    bloom_may_match, log_matches_content, log_matches_filter, LogVerificationConfig,
+};
+use self::log_pages::{LogCursor, LogPage};
+use self::pins::{BlockPin, RetentionReason};
+use self::preflight::check_transaction;
+use self::progress::SyncTracker;
+use self::proof::{receipts_root, verify_account_proof, verify_storage_proof};
 use self::rpc::ExecutionRpc;
 use self::state::{FilterType, State};
-use self::types::Account;
-
+use self::stream::{HeaderEvent, LagPolicy};
+use self::subscription::{
+    SubscriptionBatch, SubscriptionCursor, SubscriptionEvent, SubscriptionTopic,
+};
+use self::types::{Account, DeepReorg, StateAnchor};
+
+pub mod asset_changes;
+pub mod backfill;
+pub mod blob;
+pub mod bundle;
+pub mod cache;
+pub mod ccip;
+pub mod code_cache;
+pub mod config;
 pub mod constants;
 pub mod errors;
 pub mod evm;
+pub mod fee_history;
+pub mod historical;
+pub mod limits;
+pub mod log_filter;
+pub mod log_pages;
+pub mod orphans;
+pub mod pins;
+pub mod preflight;
+pub mod progress;
 pub mod proof;
 pub mod rpc;
 pub mod state;
+pub mod stream;
+pub mod subscription;
+pub mod trace;
 pub mod types;
+pub mod usage;
 
 #[derive(Clone)]
 pub struct ExecutionClient<N: NetworkSpec, R: ExecutionRpc<N>> {
     pub rpc: R,
     state: State<N, R>,
     fork_schedule: ForkSchedule,
+    limits: PayloadLimits,
+    accounts: AccountCache,
+    code: CodeCache,
+    premerge_passthrough: bool,
+    logs: LogVerificationConfig,
+    fee_history: FeeHistoryConfig,
+    historical: HistoricalStateConfig,
+    gas_price: GasPriceConfig,
+    broadcast: Vec<R>,
+    ccip_read: Option<CcipGateway>,
+    evm: EvmConfig,
+    // the priority fee last suggested and the hash of the head it was suggested at
+    priority_fee: Arc<Mutex<Option<(B256, u128)>>>,
 }
 
 impl<N: NetworkSpec, R: ExecutionRpc<N>> ExecutionClient<N, R> {
     pub fn new(rpc: &str, state: State<N, R>, fork_schedule: ForkSchedule) -> Result<Self> {
         let rpc: R = ExecutionRpc::new(rpc)?;
-        Ok(ExecutionClient::<N, R> {
+        Ok(Self::with_rpc(rpc, state, fork_schedule))
+    }
+
+    pub fn with_rpc(rpc: R, state: State<N, R>, fork_schedule: ForkSchedule) -> Self {
+        ExecutionClient::<N, R> {
             rpc,
             state,
             fork_schedule,
-        })
+            limits: PayloadLimits::default(),
+            accounts: AccountCache::new(MAX_ACCOUNT_CACHE_SIZE),
+            code: CodeCache::disabled(),
+            premerge_passthrough: false,
+            logs: LogVerificationConfig::default(),
+            fee_history: FeeHistoryConfig::default(),
+            historical: HistoricalStateConfig::default(),
+            gas_price: GasPriceConfig::default(),
+            broadcast: Vec::new(),
+            ccip_read: None,
+            evm: EvmConfig::default(),
+            priority_fee: Arc::default(),
+        }
+    }
+
+    /// Overrides the limits applied to payloads returned by the execution provider, the
+    /// blocks the state backfills included.
+    pub fn with_payload_limits(mut self, limits: PayloadLimits) -> Self {
+        self.state = self.state.with_payload_limits(limits);
+        self.limits = limits;
+        self
+    }
+
+    /// Serves blocks and logs from before the merge unverified from the execution provider,
+    /// instead of failing with [`ExecutionError::PreMergeBlock`]. State is never served.
+    pub fn with_premerge_passthrough(mut self, enabled: bool) -> Self {
+        self.premerge_passthrough = enabled;
+        self
+    }
+
+    /// Looks contract code up in `cache` before fetching it, and keeps what is fetched there.
+    pub fn with_code_cache(mut self, cache: CodeCache) -> Self {
+        self.code = cache;
+        self
+    }
+
+    /// Overrides how many blocks a log filter may span and whether answers leaving out logs
+    /// are rejected.
+    pub fn with_log_verification(mut self, config: LogVerificationConfig) -> Self {
+        self.logs = config;
+        self
+    }
+
+    /// Overrides whether fee history ranges reaching before the verified blocks are clamped
+    /// or served partially verified.
+    pub fn with_fee_history(mut self, config: FeeHistoryConfig) -> Self {
+        self.fee_history = config;
+        self
+    }
+
+    /// Overrides whether, and how far back, state of blocks before those held in state is
+    /// served.
+    pub fn with_historical_state(mut self, config: HistoricalStateConfig) -> Self {
+        self.historical = config;
+        self
+    }
+
+    /// Overrides how many blocks, and which percentile of their rewards, gas prices are
+    /// suggested from.
+    pub fn with_gas_price(mut self, config: GasPriceConfig) -> Self {
+        self.gas_price = config;
+        self
+    }
+
+    /// Also submits raw transactions to `rpcs`, so a transaction the provider drops still
+    /// reaches the network.
+    pub fn with_broadcast_rpcs(mut self, rpcs: Vec<R>) -> Self {
+        self.broadcast = rpcs;
+        self
+    }
+
+    /// Follows the offchain lookups of calls through `gateway`, instead of returning them as
+    /// reverts.
+    pub fn with_ccip_read(mut self, gateway: Option<CcipGateway>) -> Self {
+        self.ccip_read = gateway;
+        self
+    }
+
+    pub fn ccip_gateway(&self) -> Option<&CcipGateway> {
+        self.ccip_read.as_ref()
+    }
+
+    /// Overrides how often calls may fetch state their access list left out.
+    pub fn with_evm_config(mut self, config: EvmConfig) -> Self {
+        self.evm = config;
+        self
+    }
+
+    pub fn evm_config(&self) -> EvmConfig {
+        self.evm
     }
 
     pub async fn check_rpc(&self, chain_id: u64) -> Result<()> {
@@ -61,247 +226,1750 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> ExecutionClient<N, R> {
         address: Address,
         slots: Option<&[B256]>,
         tag: BlockTag,
+    ) -> Result<Account> {
+        self.get_account_at(address, slots, &StateAnchor::Tag(tag))
+            .await
+    }
+
+    /// Fetches and verifies an account against the state root of `anchor`.
+    ///
+    /// Accounts read without storage slots are cached per block once verified, so balance,
+    /// nonce and code lookups are answered locally after the first read at a block.
+    pub async fn get_account_at(
+        &self,
+        address: Address,
+        slots: Option<&[B256]>,
+        anchor: &StateAnchor<N::HeaderResponse>,
     ) -> Result<Account> {
         let slots = slots.unwrap_or(&[]);
+        let header = self.anchor_header(anchor).await?;
+        if slots.is_empty() {
+            if let Some(account) = self.accounts.get(header.hash(), address) {
+                return Ok(account);
+            }
+        }
+
+        let (proof, slot_map) = self.get_block_proof(&header, address, slots).await?;
+        let code = match self.cached_code(&proof).await {
+            Some(code) => code,
+            None if has_code(&proof) => self
+                .rpc
+                .get_code(address, header.number())
+                .await
+                .map_err(|err| pruned_state_error(header.number(), err))?,
+            None => Vec::new(),
+        };
+        let account = proven_account(proof, slot_map, code)
+            .inspect_err(|_| self.verification_failed("code"))?;
+        self.cache_code(&account).await;
+
+        if slots.is_empty() {
+            self.accounts
+                .insert(header.hash(), address, account.clone());
+        }
+
+        Ok(account)
+    }
+
+    /// Fetches and verifies several accounts against the state root of `anchor`, in the
+    /// order asked for.
+    ///
+    /// The proofs of the accounts not cached are requested together, followed by the code of
+    /// those that have any, so the round trips do not grow with the number of accounts. An
+    /// account failing to fetch or verify fails alone, and if the code batch fails as a whole
+    /// each code is fetched on its own.
+    pub async fn get_accounts_at(
+        &self,
+        accounts: &[(Address, Vec<B256>)],
+        anchor: &StateAnchor<N::HeaderResponse>,
+    ) -> Result<Vec<Result<Account>>> {
+        let header = self.anchor_header(anchor).await?;
+        let mut results = accounts
+            .iter()
+            .map(|(address, slots)| {
+                let cached = slots
+                    .is_empty()
+                    .then(|| self.accounts.get(header.hash(), *address));
+                cached.flatten().map(Ok)
+            })
+            .collect::<Vec<Option<Result<Account>>>>();
+
+        let missing = (0..accounts.len())
+            .filter(|i| results[*i].is_none())
+            .collect::<Vec<_>>();
+        let requests = missing
+            .iter()
+            .map(|i| accounts[*i].clone())
+            .collect::<Vec<_>>();
+        let proofs = self
+            .rpc
+            .get_proofs(&requests, header.number().into())
+            .await
+            .map_err(|err| pruned_state_error(header.number(), err))?;
+
+        let mut proven = Vec::new();
+        for (i, proof) in missing.into_iter().zip(proofs) {
+            let proof = proof.map_err(|err| pruned_state_error(header.number(), err));
+            match proof.and_then(|proof| self.verify_proof(&header, proof)) {
+                Ok((proof, slot_map)) => proven.push((i, proof, slot_map)),
+                Err(err) => results[i] = Some(Err(err)),
+            }
+        }
+
+        let cached = join_all(proven.iter().map(|(_, proof, _)| self.cached_code(proof))).await;
+        let with_code = proven
+            .iter()
+            .zip(&cached)
+            .filter(|((_, proof, _), cached)| has_code(proof) && cached.is_none())
+            .map(|((i, ..), _)| accounts[*i].0)
+            .collect::<Vec<_>>();
+        // the proofs are verified by now, so a failed batch falls back to one request per
+        // account rather than failing them all
+        let codes = match self.rpc.get_codes(&with_code, header.number()).await {
+            Ok(codes) => codes,
+            Err(_) => {
+                let codes = with_code
+                    .iter()
+                    .map(|address| self.rpc.get_code(*address, header.number()));
+                join_all(codes).await
+            }
+        };
+        let mut codes = codes
+            .into_iter()
+            .map(|code| code.map_err(|err| pruned_state_error(header.number(), err)));
+
+        for ((i, proof, slot_map), cached) in proven.into_iter().zip(cached) {
+            let code = match cached {
+                Some(code) => Ok(code),
+                None if has_code(&proof) => codes
+                    .next()
+                    .unwrap_or_else(|| Err(eyre::eyre!("missing code of {}", accounts[i].0))),
+                None => Ok(Vec::new()),
+            };
+            let account = code.and_then(|code| {
+                proven_account(proof, slot_map, code)
+                    .inspect_err(|_| self.verification_failed("code"))
+            });
+
+            if let Ok(account) = &account {
+                self.cache_code(account).await;
+                if accounts[i].1.is_empty() {
+                    self.accounts
+                        .insert(header.hash(), accounts[i].0, account.clone());
+                }
+            }
+            results[i] = Some(account);
+        }
+
+        Ok(results.into_iter().flatten().collect())
+    }
+
+    /// Fetches an `eth_getProof` response and verifies its account and storage proofs
+    /// against the state root of the block at `tag`.
+    pub async fn get_proof(
+        &self,
+        address: Address,
+        slots: &[B256],
+        tag: BlockTag,
+    ) -> Result<EIP1186AccountProofResponse> {
+        let header = self.anchor_header(&StateAnchor::Tag(tag)).await?;
+        let (proof, _) = self.get_block_proof(&header, address, slots).await?;
+        Ok(proof)
+    }
+
+    /// Bundles the verified proofs of `address` at `tag` with the header of the block and,
+    /// where the state still holds it, the beacon block it was trusted through, so the
+    /// state can be verified again without a client. A block only trusted as the ancestor of
+    /// an attested one comes with the headers leading up to that one. The code of the
+    /// account is included when `include_code` is set.
+    pub async fn get_execution_proof(
+        &self,
+        address: Address,
+        slots: &[B256],
+        tag: BlockTag,
+        include_code: bool,
+    ) -> Result<ExecutionProof> {
+        let header = self.anchor_header(&StateAnchor::Tag(tag)).await?;
+        let (account, slot_map) = self.get_block_proof(&header, address, slots).await?;
+
+        let code = if include_code {
+            let code = match self.cached_code(&account).await {
+                Some(code) => code,
+                None if has_code(&account) => self
+                    .rpc
+                    .get_code(address, header.number())
+                    .await
+                    .map_err(|err| pruned_state_error(header.number(), err))?,
+                None => Vec::new(),
+            };
+            let proven = proven_account(account.clone(), slot_map, code)
+                .inspect_err(|_| self.verification_failed("code"))?;
+            self.cache_code(&proven).await;
+            Some(proven.code.into())
+        } else {
+            None
+        };
+
+        let (beacon, descendants) = match self.state.get_beacon_mapping_by_hash(header.hash()).await
+        {
+            Some(mapping) if mapping.attestation.is_none() => {
+                let (mapping, descendants) = self.attested_descendant(header.number()).await?;
+                (Some(mapping), descendants)
+            }
+            beacon => (beacon, Vec::new()),
+        };
+
+        Ok(ExecutionProof {
+            version: EXECUTION_PROOF_VERSION,
+            header: N::encode_header(&header).into(),
+            beacon,
+            descendants,
+            account,
+            code,
+        })
+    }
+
+    /// The closest block after `number` the state holds an attested beacon block for, with
+    /// the encoded headers from the one after `number` up to it.
+    async fn attested_descendant(&self, number: u64) -> Result<(BeaconBlockMapping, Vec<Bytes>)> {
+        let latest = self.state.latest_block_number().await.unwrap_or(number);
+        let mut descendants = Vec::new();
+        for number in number + 1..=latest {
+            let Some(header) = self.state.get_verified_header(number).await else {
+                break;
+            };
+            descendants.push(N::encode_header(&header).into());
+            let mapping = self.state.get_beacon_mapping_by_hash(header.hash()).await;
+            if let Some(mapping) = mapping.filter(|mapping| mapping.attestation.is_some()) {
+                return Ok((mapping, descendants));
+            }
+        }
+        Err(ExecutionError::NoAttestedDescendant(number).into())
+    }
+
+    /// Keeps the block at `tag` cached until the returned pin is dropped, see
+    /// [State::pin_block].
+    pub async fn pin_block(
+        &self,
+        tag: BlockTag,
+        reason: RetentionReason,
+    ) -> Result<BlockPin, ExecutionError> {
+        self.state.pin_block(tag, reason).await
+    }
+
+    /// Why the block at `number` is still cached, or `None` if it is not.
+    pub async fn block_retention(&self, number: u64) -> Option<Vec<RetentionReason>> {
+        self.state.retention(number).await
+    }
+
+    /// Resolves the header of `anchor`, checking a supplied header hashes to its `hash`.
+    /// Anchors verified before are not checked again.
+    pub async fn anchor_header(
+        &self,
+        anchor: &StateAnchor<N::HeaderResponse>,
+    ) -> Result<N::HeaderResponse> {
+        match anchor {
+            StateAnchor::Tag(tag) => {
+                self.reject_pre_merge(*tag)?;
+                if let Some(block) = self.state.get_block(*tag).await {
+                    return Ok(block.header().clone());
+                }
+                match tag {
+                    BlockTag::Number(number) if self.is_historical(*number).await => {
+                        self.get_verified_header(*number).await
+                    }
+                    tag => Err(ExecutionError::BlockNotFound(*tag).into()),
+                }
+            }
+            StateAnchor::Header(header) => {
+                if !N::is_header_hash_valid(header) {
+                    self.verification_failed("block");
+                    return Err(ExecutionError::InvalidHeaderHash(header.hash()).into());
+                }
+                Ok(header.clone())
+            }
+            StateAnchor::Verified(header, _) => Ok(header.clone()),
+        }
+    }
+
+    /// Checks a header supplied by the caller once, returning it as an anchor reads do not
+    /// hash it again for. The header of a block held in state is trusted through consensus
+    /// like the block, any other only through the caller.
+    pub async fn verify_supplied_header(
+        &self,
+        header: N::HeaderResponse,
+    ) -> Result<StateAnchor<N::HeaderResponse>> {
+        let header = self.anchor_header(&StateAnchor::Header(header)).await?;
+        let trust = match self.state.get_block_by_hash(header.hash()).await {
+            Some(_) => AnchorTrust::Consensus,
+            None => AnchorTrust::UserSuppliedHeader,
+        };
+        Ok(StateAnchor::Verified(header, trust))
+    }
+
+    /// Fetches and verifies a proof at `header`, also returning the proven slot values.
+    async fn get_block_proof(
+        &self,
+        header: &N::HeaderResponse,
+        address: Address,
+        slots: &[B256],
+    ) -> Result<(EIP1186AccountProofResponse, HashMap<B256, U256>)> {
+        let proof = self
+            .rpc
+            .get_proof(address, slots, header.number().into())
+            .await
+            .map_err(|err| pruned_state_error(header.number(), err))?;
+        self.verify_proof(header, proof)
+    }
+
+    /// Whether block `number` comes before the blocks held in state, its state then being
+    /// served against a header verified back from them.
+    pub async fn is_historical(&self, number: u64) -> bool {
+        self.historical.enabled
+            && self
+                .state
+                .oldest_block_number()
+                .await
+                .is_some_and(|oldest| number < oldest)
+            && self
+                .state
+                .get_block(BlockTag::Number(number))
+                .await
+                .is_none()
+    }
+
+    /// Returns the verified header of block `number`. The header of a block before those
+    /// held in state is fetched with those between it and the closest verified header
+    /// after it, each of which must hash to the parent hash of the next. At most
+    /// [`HISTORICAL_WALK_BATCHES`] batches are fetched ahead of those verified, and each
+    /// verified batch is kept, so a walk cut short resumes from where it got to.
+    pub async fn get_verified_header(&self, number: u64) -> Result<N::HeaderResponse> {
+        if let Some(header) = self.state.get_verified_header(number).await {
+            return Ok(header);
+        }
+        if !self.is_historical(number).await {
+            return Err(self.outside_retained_window(number).await.into());
+        }
+
+        let oldest = self.state.oldest_block_number().await.unwrap_or_default();
+        let max_depth = self.historical.max_depth;
+        if oldest - number > max_depth {
+            return Err(ExecutionError::HistoricalDepthExceeded(number, oldest, max_depth).into());
+        }
+
+        let mut child = self
+            .state
+            .closest_verified_header_after(number)
+            .await
+            .ok_or(ExecutionError::BlockNotFound(BlockTag::Number(number)))?;
+        let numbers = (number..child.number()).rev().collect::<Vec<_>>();
+        let mut batches = stream::iter(numbers.chunks(PARALLEL_QUERY_BATCH_SIZE))
+            .map(|chunk| async move {
+                let blocks = try_join_all(chunk.iter().map(|number| {
+                    self.rpc
+                        .get_block_by_number(BlockTag::Number(*number), false)
+                }))
+                .await?;
+                let parents = chunk
+                    .iter()
+                    .zip(blocks)
+                    .map(|(number, block)| {
+                        block
+                            .map(|block| block.header().clone())
+                            .ok_or(ExecutionError::BlockNotFound(BlockTag::Number(*number)))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Result::<_>::Ok(parents)
+            })
+            .buffered(HISTORICAL_WALK_BATCHES);
+
+        while let Some(parents) = batches.try_next().await? {
+            verify_header_chain::<N>(&child, &parents)?;
+            child = parents.last().unwrap().clone();
+            self.state
+                .insert_historical_headers(parents, max_depth)
+                .await;
+        }
+        Ok(child)
+    }
+
+    /// Verifies a proof against the state root of `header`, returning it with the proven
+    /// slot values.
+    fn verify_proof(
+        &self,
+        header: &N::HeaderResponse,
+        proof: EIP1186AccountProofResponse,
+    ) -> Result<(EIP1186AccountProofResponse, HashMap<B256, U256>)> {
+        self.limits.check_proof(&proof)?;
+
+        // Verify the account proof
+        verify_account_proof(&proof, header.state_root())
+            .inspect_err(|_| self.verification_failed("account"))?;
+        // Verify the storage proofs, collecting the slot values
+        let slot_map =
+            verify_storage_proof(&proof).inspect_err(|_| self.verification_failed("storage"))?;
+
+        Ok((proof, slot_map))
+    }
+
+    pub async fn get_storage_at(
+        &self,
+        address: Address,
+        slot: U256,
+        block: BlockTag,
+    ) -> Result<B256> {
+        let storage_key = slot.into();
+
+        let account = self
+            .get_account(address, Some(&[storage_key]), block)
+            .await?;
+
+        let value = account.slots.get(&storage_key);
+        match value {
+            Some(value) => Ok((*value).into()),
+            None => Err(ExecutionError::InvalidStorageProof(address, storage_key).into()),
+        }
+    }
+
+    /// Checks a raw transaction against `chain_id` and the verified latest state of its
+    /// sender, see [`preflight`].
+    pub async fn check_raw_transaction(&self, bytes: &[u8], chain_id: u64) -> Result<()> {
+        let tx = N::decode_transaction(bytes)?;
+        let account = self.get_account(tx.sender, None, BlockTag::Latest).await?;
+        check_transaction(&tx, chain_id, &account)?;
+
+        Ok(())
+    }
+
+    /// Submits a raw transaction to the provider, and at the same time to every broadcast
+    /// rpc. It is sent as soon as one of them accepts it, and otherwise fails with the error
+    /// of each.
+    pub async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<B256> {
+        if self.broadcast.is_empty() {
+            return self.rpc.send_raw_transaction(bytes).await;
+        }
+
+        let mut sends = iter::once(&self.rpc)
+            .chain(&self.broadcast)
+            .enumerate()
+            .map(|(i, rpc)| {
+                let rpc = rpc.clone();
+                let bytes = bytes.to_vec();
+                async move { (i, rpc.send_raw_transaction(&bytes).await) }
+            })
+            .collect::<FuturesUnordered<_>>();
+
+        let mut errors = Vec::new();
+        while let Some((i, result)) = sends.next().await {
+            match result {
+                Ok(_) => {
+                    // the endpoints still deciding keep their copy of the transaction
+                    #[cfg(not(target_arch = "wasm32"))]
+                    let run = tokio::spawn;
+                    #[cfg(target_arch = "wasm32")]
+                    let run = wasm_bindgen_futures::spawn_local;
+                    run(async move { while sends.next().await.is_some() {} });
+
+                    // endpoints may report the hash as they like, the hash of the
+                    // encoding is the one receipts are looked up by
+                    return Ok(keccak256(bytes));
+                }
+                Err(err) if i == 0 => errors.push((i, format!("execution rpc: {err}"))),
+                Err(err) => errors.push((i, format!("broadcast rpc {i}: {err}"))),
+            }
+        }
+
+        // listed in endpoint order, however they completed
+        errors.sort_by_key(|(i, _)| *i);
+        let errors = errors.into_iter().map(|(_, err)| err).collect();
+        Err(ExecutionError::BroadcastRejected(errors).into())
+    }
+
+    /// Waits until the receipt of a transaction is verified and its block is `confirmations`
+    /// deep in the verified chain, checking again on every new head.
+    ///
+    /// A reorg replacing the including block, as told by the verified block held at its number,
+    /// resets the confirmations, which count from the block including the transaction again.
+    /// Once the nonce of the transaction is used in the verified state without it being
+    /// included, it can no longer be, and the wait fails with
+    /// [`ExecutionError::TransactionDropped`]. The sender and nonce are as the provider reports
+    /// them for the hash.
+    pub async fn wait_for_transaction_receipt(
+        &self,
+        hash: B256,
+        confirmations: u64,
+        wait: Duration,
+    ) -> Result<N::ReceiptResponse> {
+        let confirmations = confirmations.max(1);
+        let mut heads = self.state.head_updates();
+        let mut included = None;
+        let mut sender = None;
+
+        let waited = timeout(wait, async {
+            loop {
+                heads.mark_unchanged();
+
+                match self.get_transaction_receipt(hash).await {
+                    Ok(Some(receipt)) => {
+                        let number = receipt
+                            .block_number()
+                            .ok_or(ExecutionError::UnverifiedReceiptBlock(hash))?;
+                        // the verified block held at the number, unless a reorg replaced the
+                        // one the receipt was proven against since
+                        let block = self
+                            .state
+                            .get_block(BlockTag::Number(number))
+                            .await
+                            .map(|block| block.header().hash())
+                            .filter(|block| receipt.block_hash() == Some(*block));
+                        if let Some(block) = block {
+                            if included.is_some_and(|(_, included)| included != block) {
+                                warn!(target: "helios::execution", %hash, "including block replaced by a reorg, confirmations reset");
+                                included = None;
+                            }
+                            // confirmations count from the block now including the transaction
+                            let (number, _) = *included.get_or_insert((number, block));
+
+                            let latest = self.state.latest_block_number().await.unwrap_or(number);
+                            if latest + 1 >= number + confirmations {
+                                return Ok(receipt);
+                            }
+                        }
+                    }
+                    Ok(None) => {
+                        if included.take().is_some() {
+                            warn!(target: "helios::execution", %hash, "transaction removed from the chain by a reorg");
+                        }
+
+                        if sender.is_none() {
+                            sender = self
+                                .rpc
+                                .get_transaction(hash)
+                                .await?
+                                .map(|tx| (tx.from(), tx.nonce()));
+                        }
+                        if let Some((from, nonce)) = sender {
+                            let account = self.get_account(from, None, BlockTag::Latest).await?;
+                            // the provider may have seen the inclusion since it was asked
+                            if account.nonce > nonce
+                                && self.rpc.get_transaction_receipt(hash).await?.is_none()
+                            {
+                                return Err(ExecutionError::TransactionDropped(hash, nonce).into());
+                            }
+                        }
+                    }
+                    // the provider may be ahead of or on another branch than the verified chain
+                    Err(err) => {
+                        debug!(target: "helios::execution", %hash, "receipt not verified yet: {err}");
+                    }
+                }
+
+                if heads.changed().await.is_err() {
+                    return Err(eyre::eyre!("consensus stopped delivering blocks"));
+                }
+            }
+        })
+        .await;
+
+        waited.unwrap_or_else(|_| Err(ExecutionError::ReceiptTimeout(hash, confirmations).into()))
+    }
+
+    /// Fails with [`ExecutionError::PreMergeBlock`] for blocks before the merge.
+    fn reject_pre_merge(&self, tag: BlockTag) -> Result<(), ExecutionError> {
+        match tag {
+            BlockTag::Number(number) if self.fork_schedule.is_pre_merge(number) => Err(
+                ExecutionError::PreMergeBlock(number, self.fork_schedule.merge_block),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Looks up a block like [`Self::get_block`], failing for blocks before the merge unless
+    /// premerge passthrough is enabled, in which case they come unverified from the provider.
+    pub async fn get_block_by_number(
+        &self,
+        tag: BlockTag,
+        full_tx: bool,
+    ) -> Result<Option<N::BlockResponse>> {
+        if let Err(err) = self.reject_pre_merge(tag) {
+            if !self.premerge_passthrough {
+                return Err(err.into());
+            }
+
+            let Some(block) = self.rpc.get_block_by_number(tag, full_tx).await? else {
+                return Ok(None);
+            };
+            let number = block.header().number();
+            match tag {
+                BlockTag::Number(requested) if requested != number => {
+                    return Err(ExecutionError::BlockNumberMismatch(requested, number).into())
+                }
+                _ => {}
+            }
+            check_pre_merge_block::<N>(&block)
+                .inspect_err(|_| self.verification_failed("block"))?;
+
+            warn!(target: "helios::execution", "serving unverified pre-merge block {}", tag);
+            return Ok(Some(block));
+        }
+
+        Ok(self.get_block(tag, full_tx).await)
+    }
+
+    /// Looks up a block like [`Self::get_block_by_hash`]. With premerge passthrough enabled,
+    /// a block the state does not hold is looked up at the provider, and served unverified if
+    /// it is from before the merge.
+    pub async fn get_block_by_hash_or_pre_merge(
+        &self,
+        hash: B256,
+        full_tx: bool,
+    ) -> Result<Option<N::BlockResponse>> {
+        if let Some(block) = self.get_block_by_hash(hash, full_tx).await {
+            return Ok(Some(block));
+        }
+        if !self.premerge_passthrough {
+            return Ok(None);
+        }
+
+        // providers answer unknown hashes with an error or with null alike
+        let Ok(block) = self.rpc.get_block(hash, full_tx).await else {
+            return Ok(None);
+        };
+        if block.header().hash() != hash {
+            self.verification_failed("block");
+            return Err(ExecutionError::BlockHashMismatch(hash, block.header().hash()).into());
+        }
+        // a provider block after the merge is one the verified chain does not hold
+        if !self.fork_schedule.is_pre_merge(block.header().number()) {
+            return Ok(None);
+        }
+        check_pre_merge_block::<N>(&block).inspect_err(|_| self.verification_failed("block"))?;
+
+        warn!(target: "helios::execution", "serving unverified pre-merge block {}", hash);
+        Ok(Some(block))
+    }
+
+    pub async fn get_block(&self, tag: BlockTag, full_tx: bool) -> Option<N::BlockResponse> {
+        let block = self.state.get_block(tag).await;
+        if block.is_none() {
+            warn!(target: "helios::execution", "requested block not found in state: {}", tag);
+            return None;
+        }
+        let mut block = block.unwrap();
+
+        if !full_tx {
+            *block.transactions_mut() =
+                BlockTransactions::Hashes(block.transactions().hashes().collect());
+        }
+
+        Some(block)
+    }
+
+    /// Returns the blob base fee of a verified block, following from the excess blob gas in
+    /// its header.
+    pub async fn blob_base_fee(&self, tag: BlockTag) -> Result<U256> {
         let block = self
             .state
             .get_block(tag)
             .await
             .ok_or(ExecutionError::BlockNotFound(tag))?;
+        let header = block.header();
 
-        let proof = self
+        let params = N::blob_params(header, &self.fork_schedule);
+        let (Some(params), Some(excess_blob_gas)) = (params, header.excess_blob_gas()) else {
+            return Err(ExecutionError::BlobGasNotPriced(header.number()).into());
+        };
+        Ok(U256::from(params.base_fee(excess_blob_gas)))
+    }
+
+    pub async fn get_block_by_hash(&self, hash: B256, full_tx: bool) -> Option<N::BlockResponse> {
+        let block = self.state.get_block_by_hash(hash).await;
+        if block.is_none() {
+            warn!(target: "helios::execution", "requested block not found in state: {}", hash);
+            return None;
+        }
+        let mut block = block.unwrap();
+
+        if !full_tx {
+            *block.transactions_mut() =
+                BlockTransactions::Hashes(block.transactions().hashes().collect());
+        }
+
+        Some(block)
+    }
+
+    /// Returns the prevRandao of a block held in state. Blocks in state are either delivered
+    /// by the consensus client or hash linked to one, so the value is never taken from an
+    /// unverified provider response.
+    pub async fn get_prev_randao(&self, tag: BlockTag) -> Result<PrevRandao> {
+        let block = self.state.get_block(tag).await;
+        let block = match (block, tag) {
+            (Some(block), _) => block,
+            (None, BlockTag::Number(number)) => {
+                return Err(self.outside_retained_window(number).await.into())
+            }
+            (None, _) => return Err(ExecutionError::BlockNotFound(tag).into()),
+        };
+
+        prev_randao_of::<N>(&block)
+    }
+
+    /// Returns the prevRandao values for `start..=end`, truncated to at most
+    /// `MAX_PREV_RANDAO_RANGE` blocks per page. A block of the range missing from state fails
+    /// the page rather than leaving a hole in it.
+    pub async fn get_prev_randao_range(&self, start: u64, end: u64) -> Result<PrevRandaoRange> {
+        if start > end {
+            return Err(ExecutionError::InvalidBlockRange(start, end).into());
+        }
+
+        let oldest = self.state.oldest_block_number().await.unwrap_or(0);
+        let latest = self.state.latest_block_number().await.unwrap_or(0);
+        if start < oldest || end > latest {
+            let number = if start < oldest { start } else { end };
+            return Err(ExecutionError::BlockOutsideRetainedWindow(number, oldest, latest).into());
+        }
+
+        let page_end = end.min(start.saturating_add(MAX_PREV_RANDAO_RANGE - 1));
+        let blocks = self.state.get_blocks_in_range(start, page_end).await;
+        let mut held = blocks.iter().map(|block| block.header().number());
+        if let Some(missing) = (start..=page_end).find(|number| held.next() != Some(*number)) {
+            return Err(ExecutionError::BlockNotFound(BlockTag::Number(missing)).into());
+        }
+        let values = blocks
+            .iter()
+            .map(prev_randao_of::<N>)
+            .collect::<Result<_>>()?;
+
+        let next_block = (page_end < end).then_some(page_end + 1);
+        Ok(PrevRandaoRange { values, next_block })
+    }
+
+    /// Returns the verified beacon block that carried the execution block `block`.
+    pub async fn get_beacon_mapping_by_block(&self, block: BlockId) -> Result<BeaconBlockMapping> {
+        let hash = match block {
+            BlockId::Hash(hash) => hash.block_hash,
+            BlockId::Number(_) => self.verified_block(block).await?.header().hash(),
+        };
+
+        self.state
+            .get_beacon_mapping_by_hash(hash)
+            .await
+            .ok_or_else(|| ExecutionError::NoBeaconBlockForBlock(hash).into())
+    }
+
+    /// Returns the verified beacon block at `slot` with the execution block it carried.
+    pub async fn get_beacon_mapping_by_slot(&self, slot: u64) -> Result<BeaconBlockMapping> {
+        if let Some(mapping) = self.state.get_beacon_mapping_by_slot(slot).await {
+            return Ok(mapping);
+        }
+
+        let (oldest, latest) = self.state.beacon_slot_range().await.unwrap_or_default();
+        if (oldest..=latest).contains(&slot) {
+            Err(ExecutionError::NoBeaconBlockForSlot(slot).into())
+        } else {
+            Err(ExecutionError::SlotOutsideRetainedWindow(slot, oldest, latest).into())
+        }
+    }
+
+    async fn outside_retained_window(&self, number: u64) -> ExecutionError {
+        let oldest = self.state.oldest_block_number().await.unwrap_or(0);
+        let latest = self.state.latest_block_number().await.unwrap_or(0);
+        ExecutionError::BlockOutsideRetainedWindow(number, oldest, latest)
+    }
+
+    pub async fn get_transaction_by_block_hash_and_index(
+        &self,
+        block_hash: B256,
+        index: u64,
+    ) -> Option<N::TransactionResponse> {
+        self.state
+            .get_transaction_by_block_hash_and_index(block_hash, index)
+            .await
+    }
+
+    pub async fn get_transaction_by_block_number_and_index(
+        &self,
+        tag: BlockTag,
+        index: u64,
+    ) -> Option<N::TransactionResponse> {
+        self.state
+            .get_transaction_by_block_and_index(tag, index)
+            .await
+    }
+
+    /// Returns the receipt of a transaction, proven against the receipts root of its verified
+    /// block. Every receipt of the block is fetched, unless cached, to rebuild the root, and the
+    /// one at the transaction's index must encode to the same bytes as the receipt returned
+    /// for it. The fields the root leaves out, such as the block hash, sender and created
+    /// contract, must match the verified block and its transaction.
+    pub async fn get_transaction_receipt(
+        &self,
+        tx_hash: B256,
+    ) -> Result<Option<N::ReceiptResponse>> {
+        let Some(receipt) = self.rpc.get_transaction_receipt(tx_hash).await? else {
+            return Ok(None);
+        };
+        self.limits.check_receipt::<N>(&receipt)?;
+
+        let block_number = receipt
+            .block_number()
+            .ok_or(ExecutionError::UnverifiedReceiptBlock(tx_hash))?;
+        let tag = BlockTag::Number(block_number);
+
+        let Some(block) = self.state.get_block(tag).await else {
+            // blocks past the verified head may still be verified, older ones never will
+            let latest = self.state.latest_block_number().await;
+            if latest.map_or(true, |latest| block_number > latest) {
+                return Err(ExecutionError::UnverifiedReceiptBlock(tx_hash).into());
+            }
+            return Ok(None);
+        };
+
+        // Fetch all receipts in block, check root and inclusion
+        let hash = block.header().hash();
+        let receipts = match self.state.cached_receipts(hash) {
+            Some(receipts) => receipts,
+            None => {
+                let receipts = self
+                    .rpc
+                    .get_block_receipts(BlockId::hash(hash))
+                    .await?
+                    .ok_or(eyre::eyre!(ExecutionError::NoReceiptsForBlock(tag)))?;
+                self.limits.check_receipts::<N>(&receipts)?;
+
+                if receipts_root::<N>(&receipts) != block.header().receipts_root() {
+                    self.verification_failed("receipts");
+                    return Err(ExecutionError::ReceiptRootMismatch(tx_hash).into());
+                }
+                self.state.cache_receipts(hash, receipts.clone()).await;
+                receipts
+            }
+        };
+
+        let position = block
+            .transactions()
+            .hashes()
+            .position(|hash| hash == tx_hash)
+            .ok_or_else(|| {
+                ExecutionError::InconsistentReceipt(
+                    tx_hash,
+                    format!("transaction is not in block {block_number}"),
+                )
+            })?;
+
+        // Note: Some RPC providers return different response in `eth_getTransactionReceipt` vs `eth_getBlockReceipts`
+        // Primarily due to https://github.com/ethereum/execution-apis/issues/295 not finalized
+        // Which means that the basic equality check in N::receipt_contains can be flaky
+        // So as a fallback do equality check on encoded receipts as well
+        let verified = receipts
+            .get(position)
+            .ok_or(ExecutionError::ReceiptMismatch(tx_hash))?;
+        if !(N::receipt_contains(slice::from_ref(verified), &receipt)
+            || N::encode_receipt(verified) == N::encode_receipt(&receipt))
+        {
+            self.verification_failed("receipts");
+            return Err(ExecutionError::ReceiptMismatch(tx_hash).into());
+        }
+        ensure_receipt_consistent::<N>(&receipts, position, &receipt)
+            .and_then(|_| {
+                let tx = block
+                    .transactions()
+                    .as_transactions()
+                    .and_then(|txs| txs.get(position));
+                ensure_receipt_in_block::<N>(&receipt, hash, tx_hash, tx)
+            })
+            .inspect_err(|_| self.verification_failed("receipts"))?;
+
+        Ok(Some(receipt))
+    }
+
+    pub async fn get_block_receipts(
+        &self,
+        tag: BlockTag,
+    ) -> Result<Option<Vec<N::ReceiptResponse>>> {
+        let block = self.state.get_block(tag).await;
+        let block = if let Some(block) = block {
+            block
+        } else {
+            return Ok(None);
+        };
+
+        let tag = BlockTag::Number(block.header().number());
+
+        // some providers return null rather than an empty list for a block without
+        // transactions, so answer those from the header alone
+        if block.transactions().is_empty() {
+            if block.header().receipts_root() != EMPTY_ROOT_HASH {
+                self.verification_failed("receipts");
+                return Err(ExecutionError::BlockReceiptsRootMismatch(tag).into());
+            }
+            return Ok(Some(Vec::new()));
+        }
+
+        let receipts = self.header_receipts(block.header()).await?;
+        ensure_receipts_in_block::<N>(&block, &receipts)
+            .inspect_err(|_| self.verification_failed("receipts"))?;
+        Ok(Some(receipts))
+    }
+
+    /// The receipts of the block of `header`, proven against its receipts root. Receipts
+    /// verified for a block held in state before are served without asking the provider.
+    async fn header_receipts(&self, header: &N::HeaderResponse) -> Result<Vec<N::ReceiptResponse>> {
+        let hash = header.hash();
+        if let Some(receipts) = self.state.cached_receipts(hash) {
+            return Ok(receipts);
+        }
+
+        let tag = BlockTag::Number(header.number());
+        let receipts = self
             .rpc
-            .get_proof(address, slots, block.header().number().into())
+            .get_block_receipts(BlockId::hash(hash))
+            .await?
+            .ok_or(eyre::eyre!(ExecutionError::NoReceiptsForBlock(tag)))?;
+        self.limits.check_receipts::<N>(&receipts)?;
+
+        if receipts_root::<N>(&receipts) != header.receipts_root() {
+            self.verification_failed("receipts");
+            return Err(ExecutionError::BlockReceiptsRootMismatch(tag).into());
+        }
+        self.state.cache_receipts(hash, receipts.clone()).await;
+
+        Ok(receipts)
+    }
+
+    /// Returns the fee history of the `block_count` blocks up to `newest`, with the base fees,
+    /// gas used ratios and blob fees the provider reports checked against the verified
+    /// headers. Blocks past the verified head may not be on the verified chain at all, so the
+    /// range never extends beyond it.
+    pub async fn get_fee_history(
+        &self,
+        block_count: u64,
+        newest: BlockTag,
+        reward_percentiles: &[f64],
+    ) -> Result<FeeHistoryResponse> {
+        if let Some(percentile) = reward_percentiles.iter().find(|p| !is_percentile(**p)) {
+            return Err(ExecutionError::InvalidPercentile(*percentile).into());
+        }
+        if block_count == 0 {
+            return Ok(FeeHistoryResponse {
+                history: FeeHistory::default(),
+                verified_range: None,
+            });
+        }
+
+        let newest = match newest {
+            BlockTag::Number(number) => {
+                let latest = self.state.latest_block_number().await;
+                let latest = latest.ok_or(ExecutionError::BlockNotFound(BlockTag::Latest))?;
+                BlockTag::Number(number.min(latest))
+            }
+            tag => tag,
+        };
+        let newest = match (self.state.get_block(newest).await, newest) {
+            (Some(block), _) => block.header().clone(),
+            (None, BlockTag::Number(number)) => {
+                return Err(self.outside_retained_window(number).await.into())
+            }
+            (None, tag) => return Err(ExecutionError::BlockNotFound(tag).into()),
+        };
+        let newest_number = newest.number();
+        let oldest = (newest_number + 1).saturating_sub(block_count);
+
+        // the verified blocks hash linked back from the newest
+        let mut headers = vec![newest];
+        loop {
+            let child = headers.last().unwrap();
+            if child.number() == oldest {
+                break;
+            }
+            let parent_hash = child.parent_hash();
+            match self
+                .state
+                .get_block(BlockTag::Number(child.number() - 1))
+                .await
+            {
+                Some(parent) if parent.header().hash() == parent_hash => {
+                    headers.push(parent.header().clone())
+                }
+                _ => break,
+            }
+        }
+        headers.reverse();
+        let verified_from = headers[0].number();
+
+        let blob_fees = headers
+            .iter()
+            .map(|header| {
+                let params = N::blob_params(header, &self.fork_schedule)?;
+                BlobFees::of(&params, header)
+            })
+            .collect::<Vec<_>>();
+
+        let last = headers.last().unwrap();
+        let next = self
+            .state
+            .get_block(BlockTag::Number(newest_number + 1))
+            .await
+            .filter(|next| next.header().parent_hash() == last.hash());
+        let (next_base_fee, next_blob_base_fee) = match next {
+            Some(next) => {
+                let next = next.header();
+                let blob_base_fee = N::blob_params(next, &self.fork_schedule)
+                    .zip(next.excess_blob_gas())
+                    .map(|(params, excess)| params.base_fee(excess));
+                (next.base_fee_per_gas(), blob_base_fee)
+            }
+            None => {
+                let blob_base_fee = N::blob_params(last, &self.fork_schedule)
+                    .zip(last.excess_blob_gas().zip(last.blob_gas_used()))
+                    .map(|(params, (excess, used))| {
+                        params.base_fee(params.next_excess_blob_gas(excess, used))
+                    });
+                (N::next_base_fee(last), blob_base_fee)
+            }
+        };
+
+        let oldest = if self.fee_history.allow_partial {
+            oldest
+        } else {
+            verified_from
+        };
+        let mut history = self
+            .rpc
+            .get_fee_history(
+                newest_number + 1 - oldest,
+                newest_number,
+                reward_percentiles,
+            )
             .await?;
+        verify_fee_history(
+            &history,
+            oldest,
+            &headers,
+            next_base_fee,
+            &blob_fees,
+            next_blob_base_fee,
+        )?;
+        fill_blob_fees(&mut history, verified_from, &blob_fees, next_blob_base_fee);
+
+        let verified_range =
+            (history.oldest_block < verified_from).then_some([verified_from, newest_number]);
+        Ok(FeeHistoryResponse {
+            history,
+            verified_range,
+        })
+    }
 
-        // Verify the account proof
-        verify_account_proof(&proof, block.header().state_root())?;
-        // Verify the storage proofs, collecting the slot values
-        let slot_map = verify_storage_proof(&proof)?;
-        // Verify the code hash
-        let code = if proof.code_hash == KECCAK_EMPTY || proof.code_hash == B256::ZERO {
+    /// Suggests a priority fee from the configured percentile of the rewards paid in the
+    /// latest verified blocks, whose base fees are checked against their headers as for
+    /// [`ExecutionClient::get_fee_history`]. While fewer blocks are verified than configured,
+    /// as right after startup, the suggestion comes from those there are. The suggestion is
+    /// kept until the head changes.
+    pub async fn get_priority_fee(&self) -> Result<u128> {
+        let head = self
+            .state
+            .get_block(BlockTag::Latest)
+            .await
+            .ok_or(ExecutionError::BlockNotFound(BlockTag::Latest))?;
+        let head = head.header();
+        if let Some((hash, fee)) = *self.priority_fee.lock().unwrap() {
+            if hash == head.hash() {
+                return Ok(fee);
+            }
+        }
+
+        let oldest = self
+            .state
+            .oldest_block_number()
+            .await
+            .unwrap_or(head.number());
+        let block_count = self.gas_price.blocks.clamp(1, head.number() + 1 - oldest);
+        let fees = self
+            .get_fee_history(
+                block_count,
+                BlockTag::Number(head.number()),
+                &[self.gas_price.percentile],
+            )
+            .await?;
+        let fee = suggested_priority_fee(&fees.history).unwrap_or(DEFAULT_PRIORITY_FEE);
+
+        *self.priority_fee.lock().unwrap() = Some((head.hash(), fee));
+        Ok(fee)
+    }
+
+    /// Suggests a gas price, the base fee of the latest verified block with the suggested
+    /// priority fee on top.
+    pub async fn get_gas_price(&self) -> Result<u128> {
+        let head = self
+            .state
+            .get_block(BlockTag::Latest)
+            .await
+            .ok_or(ExecutionError::BlockNotFound(BlockTag::Latest))?;
+        let base_fee = head.header().base_fee_per_gas().unwrap_or_default();
+        Ok(base_fee as u128 + self.get_priority_fee().await?)
+    }
+
+    /// Returns the rlp encoded header of a verified block, the bytes its hash is computed
+    /// over.
+    pub async fn get_raw_header(&self, block: BlockId) -> Result<Bytes> {
+        let block = self.verified_block(block).await?;
+        Ok(N::encode_header(block.header()).into())
+    }
+
+    /// Returns the rlp encoding of a verified block, with the transactions and withdrawals
+    /// its roots were checked against.
+    pub async fn get_raw_block(&self, block: BlockId) -> Result<Bytes> {
+        let block = self.verified_block(block).await?;
+        let hash = block.header().hash();
+        N::encode_block(&block)
+            .map(Bytes::from)
+            .ok_or_else(|| ExecutionError::BlockNotEncodable(hash).into())
+    }
+
+    /// Returns the encoded receipts of a verified block, each as it goes into the receipts
+    /// root.
+    pub async fn get_raw_receipts(&self, block: BlockId) -> Result<Vec<Bytes>> {
+        // the receipts of the very block resolved, even if another replaced it since
+        let block = self.verified_block(block).await?;
+        let receipts = if block.transactions().is_empty() {
+            if block.header().receipts_root() != EMPTY_ROOT_HASH {
+                self.verification_failed("receipts");
+                let tag = BlockTag::Number(block.header().number());
+                return Err(ExecutionError::BlockReceiptsRootMismatch(tag).into());
+            }
             Vec::new()
         } else {
-            let code = self.rpc.get_code(address, block.header().number()).await?;
-            let code_hash = keccak256(&code);
+            self.header_receipts(block.header()).await?
+        };
 
-            if proof.code_hash != code_hash {
-                return Err(
-                    ExecutionError::CodeHashMismatch(address, code_hash, proof.code_hash).into(),
-                );
+        Ok(receipts
+            .iter()
+            .map(|receipt| N::encode_receipt(receipt).into())
+            .collect())
+    }
+
+    /// Looks up a block held in state by hash, number or tag.
+    async fn verified_block(&self, block: BlockId) -> Result<N::BlockResponse> {
+        let number = match block {
+            BlockId::Hash(hash) => {
+                let hash = hash.block_hash;
+                return self
+                    .state
+                    .get_block_by_hash(hash)
+                    .await
+                    .ok_or_else(|| ExecutionError::BlockHashNotFound(hash).into());
             }
+            BlockId::Number(number) => number,
+        };
 
-            code
+        let tag = match number {
+            BlockNumberOrTag::Number(number) => BlockTag::Number(number),
+            BlockNumberOrTag::Latest => BlockTag::Latest,
+            BlockNumberOrTag::Finalized => BlockTag::Finalized,
+            BlockNumberOrTag::Safe => BlockTag::Safe,
+            BlockNumberOrTag::Pending => BlockTag::Pending,
+            tag => eyre::bail!("unsupported block tag: {tag:?}"),
         };
 
-        Ok(Account {
-            balance: proof.balance,
-            nonce: proof.nonce,
-            code,
-            code_hash: proof.code_hash,
-            storage_hash: proof.storage_hash,
-            slots: slot_map,
-        })
+        match (self.state.get_block(tag).await, tag) {
+            (Some(block), _) => Ok(block),
+            (None, BlockTag::Number(number)) => {
+                Err(self.outside_retained_window(number).await.into())
+            }
+            (None, _) => Err(ExecutionError::BlockNotFound(tag).into()),
+        }
+    }
+
+    pub async fn get_transaction(&self, hash: B256) -> Option<N::TransactionResponse> {
+        self.state.get_transaction(hash).await
+    }
+
+    pub async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        let filter = self.resolve_filter_range(filter).await?;
+
+        if let (Some(from), Some(to)) = (filter.get_from_block(), filter.get_to_block()) {
+            let span = to.saturating_add(1).saturating_sub(from);
+            if span > self.logs.max_blocks {
+                return Err(ExecutionError::TooManyLogBlocks(span, self.logs.max_blocks).into());
+            }
+        }
+
+        let merge_block = self.fork_schedule.merge_block;
+        if let Some(from_block) = filter.get_from_block().filter(|from| *from < merge_block) {
+            if !self.premerge_passthrough {
+                return Err(ExecutionError::PreMergeBlock(from_block, merge_block).into());
+            }
+
+            // only the part of the range after the merge can be verified
+            let to_block = filter.get_to_block().unwrap_or(from_block);
+            let pre_merge = filter.clone().to_block(to_block.min(merge_block - 1));
+            warn!(
+                target: "helios::execution",
+                "serving unverified pre-merge logs from block {} to {}",
+                from_block,
+                to_block.min(merge_block - 1)
+            );
+            let mut logs = self.rpc.get_logs(&pre_merge).await?;
+            self.limits.check_logs(&logs)?;
+            ensure_logs_match_filter(&logs, &pre_merge)?;
+
+            if to_block >= merge_block {
+                let post_merge = filter.from_block(merge_block);
+                logs.extend(self.get_verified_logs(&post_merge).await?);
+            }
+            return Ok(logs);
+        }
+
+        self.get_verified_logs(&filter).await
+    }
+
+    /// Answers `filter` from the verified receipts of the blocks it covers, using the
+    /// provider's answer to find them.
+    ///
+    /// Every log the provider returns must appear in the receipts of its block, at the same
+    /// index and in the same transaction. The answer is then matched locally over the receipt
+    /// logs of each block held in state whose bloom admits the filter, placed by their position
+    /// in the block, so logs the provider returns outside the filter are dropped and
+    /// matching logs it left out are served from the receipts, which is reported since it
+    /// means the provider's answer was incomplete. In strict mode such an answer is rejected
+    /// instead.
+    async fn get_verified_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        let logs = self.rpc.get_logs(filter).await?;
+        self.limits.check_logs(&logs)?;
+        if logs.len() > MAX_SUPPORTED_LOGS_NUMBER {
+            return Err(
+                ExecutionError::TooManyLogsToProve(logs.len(), MAX_SUPPORTED_LOGS_NUMBER).into(),
+            );
+        }
+
+        let mut block_nums = self
+            .blocks_covered(filter)
+            .await
+            .iter()
+            .filter(|block| bloom_may_match(&block.header().logs_bloom(), filter))
+            .map(|block| block.header().number())
+            .collect::<BTreeSet<_>>();
+        for log in &logs {
+            block_nums.insert(log_block_number(log)?);
+        }
+        // logs the provider claims outside the range add blocks of their own
+        if block_nums.len() as u64 > self.logs.max_blocks {
+            return Err(ExecutionError::TooManyLogBlocks(
+                block_nums.len() as u64,
+                self.logs.max_blocks,
+            )
+            .into());
+        }
+
+        let verified = self.verified_logs(block_nums).await?;
+        ensure_logs_in_receipts(&logs, &verified)
+            .inspect_err(|_| self.verification_failed("logs"))?;
+
+        // every log returned is one of the verified logs, which are served in its place
+        let mut returned = logs
+            .iter()
+            .map(|log| (log.block_number, log.log_index))
+            .collect::<HashSet<_>>();
+        let mut omitted = 0;
+        let mut matching = Vec::new();
+        for log in verified {
+            if !log_matches_filter(&log, filter) {
+                continue;
+            }
+            if !returned.remove(&(log.block_number, log.log_index)) {
+                omitted += 1;
+            }
+            matching.push(log);
+        }
+
+        if omitted > 0 && self.logs.strict {
+            self.verification_failed("logs");
+            return Err(ExecutionError::OmittedLogs(omitted).into());
+        }
+        if omitted > 0 {
+            warn!(
+                target: "helios::execution",
+                omitted,
+                "provider inconsistency: eth_getLogs left out logs matching the filter, serving them from verified receipts"
+            );
+        }
+        if !returned.is_empty() {
+            debug!(
+                target: "helios::execution",
+                dropped = returned.len(),
+                "dropped logs returned by the provider outside the filter"
+            );
+        }
+
+        if matching.len() > MAX_SUPPORTED_LOGS_NUMBER {
+            return Err(ExecutionError::TooManyLogsToProve(
+                matching.len(),
+                MAX_SUPPORTED_LOGS_NUMBER,
+            )
+            .into());
+        }
+        Ok(matching)
     }
 
-    pub async fn get_storage_at(
+    /// Returns up to `page_size` logs matching `filter` from the verified receipts of the
+    /// blocks it covers, with a cursor to resume from while more may follow. A cursor whose
+    /// block a reorg replaced is refused, see [`log_pages`].
+    ///
+    /// Unlike [`Self::get_logs`] the range may span any number of blocks, as long as they are
+    /// held in state or reached by historical headers. A page ends early, with a cursor, once
+    /// the receipts of `max_blocks` blocks went into it.
+    pub async fn get_logs_paged(
         &self,
-        address: Address,
-        slot: U256,
-        block: BlockTag,
-    ) -> Result<B256> {
-        let storage_key = slot.into();
+        filter: &Filter,
+        cursor: Option<&str>,
+        page_size: usize,
+    ) -> Result<LogPage> {
+        if page_size == 0 || page_size > MAX_LOG_PAGE_SIZE {
+            return Err(ExecutionError::InvalidLogPageSize(page_size, MAX_LOG_PAGE_SIZE).into());
+        }
 
-        let account = self
-            .get_account(address, Some(&[storage_key]), block)
-            .await?;
+        let latest = self
+            .state
+            .latest_block_number()
+            .await
+            .ok_or(ExecutionError::BlockNotFound(BlockTag::Latest))?;
+        let filter = &self.resolve_filter_range(filter).await?;
+        let (from, to) = match filter.get_block_hash() {
+            Some(hash) => {
+                let block = self
+                    .state
+                    .get_block_by_hash(hash)
+                    .await
+                    .ok_or(ExecutionError::BlockHashNotFound(hash))?;
+                let number = block.header().number();
+                (number, number)
+            }
+            None => (
+                filter.get_from_block().unwrap_or(latest),
+                filter.get_to_block().unwrap_or(latest).min(latest),
+            ),
+        };
 
-        let value = account.slots.get(&storage_key);
-        match value {
-            Some(value) => Ok((*value).into()),
-            None => Err(ExecutionError::InvalidStorageProof(address, storage_key).into()),
+        let merge_block = self.fork_schedule.merge_block;
+        if from < merge_block {
+            return Err(ExecutionError::PreMergeBlock(from, merge_block).into());
         }
-    }
 
-    pub async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<B256> {
-        self.rpc.send_raw_transaction(bytes).await
-    }
+        let (mut number, mut skip) = (from, 0);
+        if let Some(cursor) = cursor {
+            let cursor = LogCursor::decode(cursor)?;
+            if cursor.block < from || cursor.block > to {
+                return Err(ExecutionError::InvalidLogCursor(format!(
+                    "block {} is outside the range of the filter",
+                    cursor.block
+                ))
+                .into());
+            }
 
-    pub async fn get_block(&self, tag: BlockTag, full_tx: bool) -> Option<N::BlockResponse> {
-        let block = self.state.get_block(tag).await;
-        if block.is_none() {
-            warn!(target: "helios::execution", "requested block not found in state: {}", tag);
-            return None;
+            // the block hash commits to every block before it, so nothing delivered changed
+            let header = self.get_verified_header(cursor.block).await?;
+            if header.hash() != cursor.block_hash {
+                let restart = self.reorg_point(cursor.block, cursor.block_hash).await;
+                return Err(ExecutionError::StaleLogCursor(cursor.block, restart).into());
+            }
+            (number, skip) = (cursor.block, cursor.log_index);
         }
-        let mut block = block.unwrap();
 
-        if !full_tx {
-            *block.transactions_mut() =
-                BlockTransactions::Hashes(block.transactions().hashes().collect());
+        let mut logs = Vec::new();
+        let (mut fetched, mut scanned) = (0, 0);
+        while number <= to {
+            let end = to.min(number.saturating_add(LOG_PAGE_CHUNK_BLOCKS - 1));
+            let mut headers = Vec::new();
+            for number in number..=end {
+                headers.push(self.get_verified_header(number).await?);
+            }
+            let receipts = try_join_all(headers.iter().map(|header| async move {
+                if header.receipts_root() == EMPTY_ROOT_HASH
+                    || !bloom_may_match(&header.logs_bloom(), filter)
+                {
+                    return Ok(None);
+                }
+                self.header_receipts(header).await.map(Some)
+            }))
+            .await?;
+
+            for (header, receipts) in headers.iter().zip(receipts) {
+                // indices follow from the position in the verified receipts, not the provider
+                let block_logs = receipts.iter().flatten().flat_map(N::receipt_logs);
+                for (log_index, mut log) in block_logs.enumerate() {
+                    let log_index = log_index as u64;
+                    log.log_index = Some(log_index);
+                    if log_index < skip || !log_matches_filter(&log, filter) {
+                        continue;
+                    }
+                    if logs.len() == page_size {
+                        let cursor = LogCursor {
+                            block: header.number(),
+                            log_index,
+                            block_hash: header.hash(),
+                        };
+                        return Ok(LogPage {
+                            logs,
+                            cursor: Some(cursor.encode()),
+                        });
+                    }
+                    logs.push(log);
+                }
+                fetched += receipts.is_some() as u64;
+                skip = 0;
+            }
+            scanned += end + 1 - number;
+            number = end + 1;
+
+            let exhausted =
+                fetched >= self.logs.max_blocks || scanned >= MAX_LOG_PAGE_SCANNED_BLOCKS;
+            if exhausted && number <= to {
+                let header = self.get_verified_header(number).await?;
+                let cursor = LogCursor {
+                    block: number,
+                    log_index: 0,
+                    block_hash: header.hash(),
+                };
+                return Ok(LogPage {
+                    logs,
+                    cursor: Some(cursor.encode()),
+                });
+            }
         }
 
-        Some(block)
+        Ok(LogPage { logs, cursor: None })
     }
 
-    pub async fn blob_base_fee(&self, block: BlockTag) -> U256 {
-        let block = self.state.get_block(block).await;
-        let Some(block) = block else {
-            warn!(target: "helios::execution", "requested block not found");
-            return U256::from(0);
+    /// The lowest block a reorg replaced below the orphaned block `hash` at `number`. Should
+    /// the orphan cache not hold every block it replaced, the restart is taken back to
+    /// [`Self::reorg_restart`] instead.
+    async fn reorg_point(&self, mut number: u64, mut hash: B256) -> u64 {
+        while let Some(orphan) = self.state.get_orphaned_block(hash) {
+            let parent = orphan.header().parent_hash();
+            let Some(below) = number.checked_sub(1) else {
+                return number;
+            };
+            match self.get_verified_header(below).await {
+                Ok(header) if header.hash() == parent => return number,
+                Ok(_) => (number, hash) = (below, parent),
+                Err(_) => break,
+            }
+        }
+        self.reorg_restart(number).await
+    }
+
+    /// A height at or below `number` no reorg replaced, for when the blocks a reorg orphaned
+    /// cannot be followed back to the fork: the block after the finalized one, or the oldest
+    /// block held before any is finalized.
+    async fn reorg_restart(&self, number: u64) -> u64 {
+        let restart = match self.state.get_block(BlockTag::Finalized).await {
+            Some(finalized) => finalized.header().number() + 1,
+            None => self.state.oldest_block_number().await.unwrap_or_default(),
         };
+        restart.min(number)
+    }
 
-        let parent_hash = block.header().parent_hash();
-        let parent_block = self.get_block_by_hash(parent_hash, false).await;
-        if parent_block.is_none() {
-            warn!(target: "helios::execution", "requested parent block not foundß");
-            return U256::from(0);
+    /// `filter` with the ends of its range resolved to the numbers of verified blocks, so a
+    /// range ending at `finalized` ends at the finalized block rather than the latest, and
+    /// the provider is never asked for logs of a block helios has not seen yet. Either end
+    /// left open is the latest block.
+    async fn resolve_filter_range(&self, filter: &Filter) -> Result<Filter> {
+        let FilterBlockOption::Range {
+            from_block,
+            to_block,
+        } = &filter.block_option
+        else {
+            return Ok(filter.clone());
         };
 
-        let excess_blob_gas = parent_block.unwrap().header().excess_blob_gas().unwrap();
-        let is_prague = block.header().timestamp() >= self.fork_schedule.prague_timestamp;
-        U256::from(BlobExcessGasAndPrice::new(excess_blob_gas, is_prague).blob_gasprice)
+        let from = self.filter_block(from_block.unwrap_or_default()).await?;
+        let to = self.filter_block(to_block.unwrap_or_default()).await?;
+        Ok(filter.clone().from_block(from).to_block(to))
     }
 
-    pub async fn get_block_by_hash(&self, hash: B256, full_tx: bool) -> Option<N::BlockResponse> {
-        let block = self.state.get_block_by_hash(hash).await;
-        if block.is_none() {
-            warn!(target: "helios::execution", "requested block not found in state: {}", hash);
-            return None;
+    /// The number of the verified block an end of a filter range names.
+    async fn filter_block(&self, block: BlockNumberOrTag) -> Result<u64> {
+        let tag = match block {
+            BlockNumberOrTag::Number(number) => return Ok(number),
+            BlockNumberOrTag::Earliest => return Ok(0),
+            BlockNumberOrTag::Latest | BlockNumberOrTag::Pending => BlockTag::Latest,
+            BlockNumberOrTag::Finalized => BlockTag::Finalized,
+            BlockNumberOrTag::Safe => BlockTag::Safe,
+        };
+        self.state
+            .get_block(tag)
+            .await
+            .map(|block| block.header().number())
+            .ok_or_else(|| ExecutionError::BlockNotFound(tag).into())
+    }
+
+    /// The blocks held in state within the range or at the block hash of `filter`.
+    async fn blocks_covered(&self, filter: &Filter) -> Vec<N::BlockResponse> {
+        if let Some(hash) = filter.get_block_hash() {
+            return self
+                .state
+                .get_block_by_hash(hash)
+                .await
+                .into_iter()
+                .collect();
         }
-        let mut block = block.unwrap();
 
-        if !full_tx {
-            *block.transactions_mut() =
-                BlockTransactions::Hashes(block.transactions().hashes().collect());
+        let from = filter.get_from_block().unwrap_or_default();
+        let to = match filter.get_to_block() {
+            Some(to) => to,
+            None => match self.state.latest_block_number().await {
+                Some(latest) => latest,
+                None => return Vec::new(),
+            },
+        };
+        if from > to {
+            return Vec::new();
         }
+        self.state.get_blocks_in_range(from, to).await
+    }
 
-        Some(block)
+    /// Notifies of every new head, once the state holds it.
+    pub fn head_updates(&self) -> watch::Receiver<Option<u64>> {
+        self.state.head_updates()
     }
 
-    pub async fn get_transaction_by_block_hash_and_index(
-        &self,
-        block_hash: B256,
-        index: u64,
-    ) -> Option<N::TransactionResponse> {
-        self.state
-            .get_transaction_by_block_hash_and_index(block_hash, index)
-            .await
+    /// How far the state has come in applying verified blocks, see [`State::sync_progress`].
+    pub fn sync_progress(&self) -> &SyncTracker {
+        self.state.sync_progress()
     }
 
-    pub async fn get_transaction_by_block_number_and_index(
+    /// Streams the header of every new head, see [`State::block_stream`].
+    pub fn block_stream(
         &self,
-        tag: BlockTag,
-        index: u64,
-    ) -> Option<N::TransactionResponse> {
-        self.state
-            .get_transaction_by_block_and_index(tag, index)
-            .await
+        policy: LagPolicy,
+    ) -> impl Stream<Item = HeaderEvent<N::HeaderResponse>> {
+        self.state.block_stream(policy)
     }
 
-    pub async fn get_transaction_receipt(
+    /// Streams the header of every newly finalized block, see [`State::finality_stream`].
+    pub fn finality_stream(
         &self,
-        tx_hash: B256,
-    ) -> Result<Option<N::ReceiptResponse>> {
-        let receipt = self.rpc.get_transaction_receipt(tx_hash).await?;
-        if receipt.is_none() {
-            return Ok(None);
-        }
-        let receipt = receipt.unwrap();
-
-        let block_number = receipt.block_number().unwrap();
-        let tag = BlockTag::Number(block_number);
-
-        let block = self.state.get_block(tag).await;
-        let block = if let Some(block) = block {
-            block
-        } else {
-            return Ok(None);
-        };
-
-        // Fetch all receipts in block, check root and inclusion
-        let receipts = self
-            .rpc
-            .get_block_receipts(tag)
-            .await?
-            .ok_or(eyre::eyre!(ExecutionError::NoReceiptsForBlock(tag)))?;
+        policy: LagPolicy,
+    ) -> impl Stream<Item = HeaderEvent<N::HeaderResponse>> {
+        self.state.finality_stream(policy)
+    }
 
-        let receipts_encoded = receipts.iter().map(N::encode_receipt).collect::<Vec<_>>();
-        let expected_receipt_root = ordered_trie_root(&receipts_encoded);
-
-        if expected_receipt_root != block.header().receipts_root()
-            // Note: Some RPC providers return different response in `eth_getTransactionReceipt` vs `eth_getBlockReceipts`
-            // Primarily due to https://github.com/ethereum/execution-apis/issues/295 not finalized
-            // Which means that the basic equality check in N::receipt_contains can be flaky
-            // So as a fallback do equality check on encoded receipts as well
-            || !(
-                N::receipt_contains(&receipts, &receipt)
-                || receipts_encoded.contains(&N::encode_receipt(&receipt))
-            )
-        {
-            return Err(ExecutionError::ReceiptRootMismatch(tx_hash).into());
+    /// A block orphaned by a recent reorg, see [`State::get_orphaned_block`].
+    pub fn get_orphaned_block(&self, hash: B256, full_tx: bool) -> Option<N::BlockResponse> {
+        let mut block = self.state.get_orphaned_block(hash)?;
+        if !full_tx {
+            *block.transactions_mut() =
+                BlockTransactions::Hashes(block.transactions().hashes().collect());
         }
+        Some(block)
+    }
 
-        Ok(Some(receipt))
+    /// The most recent reorgs, oldest first.
+    pub fn reorg_history(&self) -> Vec<ReorgEvent> {
+        self.state.reorg_history()
     }
 
-    pub async fn get_block_receipts(
+    /// Reorgs that orphan the confirmed block, see [`State::deep_reorgs`].
+    pub fn deep_reorgs(&self) -> broadcast::Receiver<DeepReorg> {
+        self.state.deep_reorgs()
+    }
+
+    /// Collects the verified events after `cursor` up to the latest block.
+    ///
+    /// A cursor on a block that is no longer canonical replays events from the fork point on
+    /// the current branch, found by walking the orphaned blocks back from the cursor. Logs
+    /// delivered from the orphaned blocks go first, marked as removed, for the orphans whose
+    /// receipts were verified before the reorg. Should the walk leave the orphan cache, events are
+    /// replayed from the block after the finalized one, which no reorg can replace. At most
+    /// `budget` blocks are caught up on, and blocks that are skipped or no longer retained are
+    /// reported as a gap. Without a cursor only later events are delivered, starting from the
+    /// returned cursor.
+    pub async fn subscription_events(
         &self,
-        tag: BlockTag,
-    ) -> Result<Option<Vec<N::ReceiptResponse>>> {
-        let block = self.state.get_block(tag).await;
-        let block = if let Some(block) = block {
-            block
-        } else {
-            return Ok(None);
+        topic: &SubscriptionTopic,
+        cursor: Option<SubscriptionCursor>,
+        budget: u64,
+    ) -> Result<SubscriptionBatch<N::HeaderResponse>> {
+        let latest = match self.state.get_block(BlockTag::Latest).await {
+            Some(block) => block.header().clone(),
+            None => {
+                return Ok(SubscriptionBatch {
+                    events: Vec::new(),
+                    cursor,
+                })
+            }
+        };
+        let oldest = self
+            .state
+            .oldest_block_number()
+            .await
+            .unwrap_or(latest.number());
+
+        let Some(cursor) = cursor else {
+            let cursor = SubscriptionCursor {
+                block_number: latest.number(),
+                block_hash: latest.hash(),
+                log_index: None,
+            };
+            return Ok(SubscriptionBatch {
+                events: Vec::new(),
+                cursor: Some(cursor),
+            });
         };
 
-        let tag = BlockTag::Number(block.header().number());
+        let canonical = self
+            .state
+            .get_block(BlockTag::Number(cursor.block_number))
+            .await
+            .is_some_and(|block| block.header().hash() == cursor.block_hash);
+
+        let mut events = Vec::new();
+        let (mut start, mut delivered_logs) = match (canonical, cursor.log_index) {
+            (true, None) => (cursor.block_number + 1, None),
+            (true, Some(index)) => (cursor.block_number, Some(index)),
+            (false, _) => {
+                let (fork_point, orphaned) = self.fork_point(&cursor).await;
+                if let SubscriptionTopic::Logs(filter) = topic {
+                    events.extend(self.removed_logs(filter, &orphaned, &cursor));
+                }
+                (fork_point, None)
+            }
+        };
 
-        let receipts = self
-            .rpc
-            .get_block_receipts(tag)
-            .await?
-            .ok_or(eyre::eyre!(ExecutionError::NoReceiptsForBlock(tag)))?;
+        let first = start
+            .max(oldest)
+            .max((latest.number() + 1).saturating_sub(budget));
+        if first > start && start <= latest.number() {
+            events.push(SubscriptionEvent::Gap {
+                from_block: start,
+                to_block: first - 1,
+            });
+            start = first;
+            delivered_logs = None;
+        }
 
-        let receipts_encoded = receipts.iter().map(N::encode_receipt).collect::<Vec<_>>();
-        let expected_receipt_root = ordered_trie_root(&receipts_encoded);
+        let mut next = cursor;
+        for number in start..=latest.number() {
+            let Some(block) = self.state.get_block(BlockTag::Number(number)).await else {
+                break;
+            };
+            let header = block.header();
+            let hash = header.hash();
+
+            match topic {
+                SubscriptionTopic::NewHeads => events.push(SubscriptionEvent::NewHead {
+                    cursor: SubscriptionCursor {
+                        block_number: number,
+                        block_hash: hash,
+                        log_index: None,
+                    },
+                    result: header.clone(),
+                }),
+                SubscriptionTopic::Logs(filter) => {
+                    let logs = self.get_logs(&filter.clone().at_block_hash(hash)).await?;
+                    for log in logs {
+                        let index = log.log_index.unwrap_or_default();
+                        if number == start && delivered_logs.is_some_and(|last| index <= last) {
+                            continue;
+                        }
+
+                        let cursor = SubscriptionCursor {
+                            block_number: number,
+                            block_hash: hash,
+                            log_index: Some(index),
+                        };
+                        events.push(SubscriptionEvent::Log {
+                            cursor,
+                            result: log,
+                        });
+                    }
+                }
+            }
 
-        if expected_receipt_root != block.header().receipts_root() {
-            return Err(ExecutionError::BlockReceiptsRootMismatch(tag).into());
+            next = SubscriptionCursor {
+                block_number: number,
+                block_hash: hash,
+                log_index: None,
+            };
         }
 
-        Ok(Some(receipts))
+        Ok(SubscriptionBatch {
+            events,
+            cursor: Some(next),
+        })
     }
 
-    pub async fn get_transaction(&self, hash: B256) -> Option<N::TransactionResponse> {
-        self.state.get_transaction(hash).await
+    /// The first height `cursor` shares with the current branch no longer, and the orphaned
+    /// blocks from there up to the cursor, oldest first. Without the whole run of orphans from
+    /// the fork point, the height after the finalized block is the latest that is certain to
+    /// be at or before the fork, and the orphans found are those next to the cursor.
+    async fn fork_point(&self, cursor: &SubscriptionCursor) -> (u64, Vec<N::BlockResponse>) {
+        let mut orphaned = Vec::new();
+        let mut hash = cursor.block_hash;
+        while let Some(block) = self.state.get_orphaned_block(hash) {
+            let number = block.header().number();
+            let parent_hash = block.header().parent_hash();
+            orphaned.push(block);
+
+            let parent = match number.checked_sub(1) {
+                Some(parent) => self.state.get_block(BlockTag::Number(parent)).await,
+                None => None,
+            };
+            if parent.is_some_and(|parent| parent.header().hash() == parent_hash) {
+                orphaned.reverse();
+                return (number, orphaned);
+            }
+            hash = parent_hash;
+        }
+
+        orphaned.reverse();
+        (self.reorg_restart(cursor.block_number).await, orphaned)
     }
 
-    pub async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
-        let filter = filter.clone();
+    /// The logs matching `filter` a subscriber at `cursor` was delivered from the `orphaned`
+    /// blocks, marked as removed, from the receipts verified for those blocks before.
+    fn removed_logs(
+        &self,
+        filter: &Filter,
+        orphaned: &[N::BlockResponse],
+        cursor: &SubscriptionCursor,
+    ) -> Vec<SubscriptionEvent<N::HeaderResponse>> {
+        let mut events = Vec::new();
+        for block in orphaned {
+            let header = block.header();
+            let Some(receipts) = self.state.orphaned_receipts(header.hash()) else {
+                debug!(
+                    target: "helios::execution",
+                    block = header.number(),
+                    "receipts of an orphaned block were never verified, its logs cannot be removed"
+                );
+                continue;
+            };
+
+            let logs = receipts.iter().flat_map(N::receipt_logs);
+            for (index, log) in logs.enumerate() {
+                let index = index as u64;
+                let delivered = header.hash() != cursor.block_hash
+                    || cursor.log_index.map_or(true, |last| index <= last);
+                if !delivered || !log_matches_content(&log.inner, filter) {
+                    continue;
+                }
 
-        // avoid fetching logs for a block helios hasn't seen yet
-        let filter = if filter.get_to_block().is_none() && filter.get_block_hash().is_none() {
-            let block = self.state.latest_block_number().await.unwrap();
-            let filter = filter.to_block(block);
-            if filter.get_from_block().is_none() {
-                filter.from_block(block)
-            } else {
-                filter
+                events.push(SubscriptionEvent::Log {
+                    cursor: SubscriptionCursor {
+                        block_number: header.number(),
+                        block_hash: header.hash(),
+                        log_index: Some(index),
+                    },
+                    result: Log {
+                        removed: true,
+                        log_index: Some(index),
+                        ..log
+                    },
+                });
             }
-        } else {
-            filter
-        };
-
-        let logs = self.rpc.get_logs(&filter).await?;
-        if logs.len() > MAX_SUPPORTED_LOGS_NUMBER {
-            return Err(
-                ExecutionError::TooManyLogsToProve(logs.len(), MAX_SUPPORTED_LOGS_NUMBER).into(),
-            );
         }
-        ensure_logs_match_filter(&logs, &filter)?;
-        self.verify_logs(&logs).await?;
-        Ok(logs)
+        events
     }
 
     pub async fn get_filter_changes(&self, filter_id: U256) -> Result<FilterChanges> {
@@ -323,9 +1991,15 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> ExecutionClient<N, R> {
                     )
                     .into());
                 }
-                ensure_logs_match_filter(logs, filter)?;
+                ensure_logs_match_filter(logs, filter)
+                    .inspect_err(|_| self.verification_failed("logs"))?;
                 self.verify_logs(logs).await?;
-                FilterChanges::Logs(logs.to_vec())
+                self.state.record_filter_logs(filter_id, logs).await;
+
+                // logs delivered before from blocks a reorg orphaned since go first, as removed
+                let mut changes = self.state.take_removed_logs(&filter_id).await;
+                changes.extend_from_slice(logs);
+                FilterChanges::Logs(changes)
             }
             Some(FilterType::NewBlock(last_block_num)) => {
                 let blocks = self
@@ -367,7 +2041,8 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> ExecutionClient<N, R> {
                     )
                     .into());
                 }
-                ensure_logs_match_filter(&logs, filter)?;
+                ensure_logs_match_filter(&logs, filter)
+                    .inspect_err(|_| self.verification_failed("logs"))?;
                 self.verify_logs(&logs).await?;
                 Ok(logs)
             }
@@ -384,6 +2059,17 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> ExecutionClient<N, R> {
         self.rpc.uninstall_filter(filter_id).await
     }
 
+    /// Uninstalls every filter created through this client from the provider. A filter the
+    /// provider fails to uninstall is only logged, as providers drop idle filters
+    /// eventually.
+    pub async fn uninstall_filters(&self) {
+        for filter_id in self.state.take_filters().await {
+            if let Err(err) = self.rpc.uninstall_filter(filter_id).await {
+                warn!(target: "helios::execution", %filter_id, error = %err, "could not uninstall filter");
+            }
+        }
+    }
+
     pub async fn new_filter(&self, filter: &Filter) -> Result<U256> {
         let filter = filter.clone();
 
@@ -436,108 +2122,296 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> ExecutionClient<N, R> {
     /// checking its inclusion in the corresponding transaction receipt
     /// and verifying the transaction receipt itself against the block's receipt root.
     async fn verify_logs(&self, logs: &[Log]) -> Result<()> {
-        // Collect all (unique) block numbers
         let block_nums = logs
             .iter()
-            .map(|log| {
-                log.block_number
-                    .ok_or_else(|| eyre::eyre!("block num not found in log"))
-            })
-            .collect::<Result<HashSet<_>, _>>()?;
-
-        // Collect all (proven) tx receipts for all block numbers
-        let blocks_receipts_fut = block_nums.into_iter().map(|block_num| async move {
-            let tag = BlockTag::Number(block_num);
-            let receipts = self.get_block_receipts(tag).await;
-            receipts?.ok_or_else(|| eyre::eyre!(ExecutionError::NoReceiptsForBlock(tag)))
-        });
-        let blocks_receipts = try_join_all(blocks_receipts_fut).await?;
-        let receipts = blocks_receipts.into_iter().flatten().collect::<Vec<_>>();
-
-        // Map tx hashes to encoded logs
-        let receipts_logs_encoded = receipts
-            .into_iter()
-            .filter_map(|receipt| {
-                let logs = N::receipt_logs(&receipt);
-                if logs.is_empty() {
-                    None
-                } else {
-                    let tx_hash = logs[0].transaction_hash.unwrap();
-                    let encoded_logs = logs
-                        .iter()
-                        .map(|l| rlp::encode(&l.inner))
-                        .collect::<Vec<_>>();
-                    Some((tx_hash, encoded_logs))
-                }
+            .map(log_block_number)
+            .collect::<Result<BTreeSet<_>>>()?;
+        let verified = self.verified_logs(block_nums).await?;
+        ensure_logs_in_receipts(logs, &verified).inspect_err(|_| self.verification_failed("logs"))
+    }
+
+    /// Counts a provider response of `kind` that failed to verify.
+    fn verification_failed(&self, _kind: &str) {
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        if let Some(metrics) = self.rpc.metrics() {
+            metrics.record_verification_failure(_kind);
+        }
+    }
+
+    /// The code of the account of `proof` from the code cache, if it has any.
+    async fn cached_code(&self, proof: &EIP1186AccountProofResponse) -> Option<Vec<u8>> {
+        if !has_code(proof) {
+            return None;
+        }
+        self.code.get(proof.code_hash).await
+    }
+
+    /// Keeps the code of a verified account in the code cache.
+    async fn cache_code(&self, account: &Account) {
+        if !account.code.is_empty() {
+            self.code.put(account.code_hash, &account.code).await;
+        }
+    }
+
+    /// The logs of each of `block_nums` in order, from their receipts proven against the
+    /// receipts roots of blocks held in state.
+    async fn verified_logs(&self, block_nums: BTreeSet<u64>) -> Result<Vec<Log>> {
+        // each block not cached costs a whole eth_getBlockReceipts, so only a batch of them
+        // is requested at once
+        let blocks = stream::iter(block_nums)
+            .map(|block_num| async move {
+                let tag = BlockTag::Number(block_num);
+                let receipts = self.get_block_receipts(tag).await?;
+                let receipts =
+                    receipts.ok_or_else(|| eyre::eyre!(ExecutionError::NoReceiptsForBlock(tag)))?;
+                Ok::<_, eyre::Report>(block_logs::<N>(block_num, &receipts))
             })
-            .collect::<HashMap<_, _>>();
+            .buffered(PARALLEL_QUERY_BATCH_SIZE)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(blocks.into_iter().flatten().collect())
+    }
+}
 
-        for log in logs {
-            // Check if the receipt contains the desired log
-            // Encoding logs for comparison
-            let tx_hash = log.transaction_hash.unwrap();
-            let log_encoded = rlp::encode(&log.inner);
-            let receipt_logs_encoded = receipts_logs_encoded.get(&tx_hash).unwrap();
+/// Whether the account of `proof` is a contract, with code to fetch.
+fn has_code(proof: &EIP1186AccountProofResponse) -> bool {
+    proof.code_hash != KECCAK_EMPTY && proof.code_hash != B256::ZERO
+}
 
-            if !receipt_logs_encoded.contains(&log_encoded) {
-                return Err(ExecutionError::MissingLog(
-                    tx_hash,
-                    U256::from(log.log_index.unwrap()),
-                )
-                .into());
-            }
+/// The account of a verified proof, checking that `code` hashes to its code hash.
+fn proven_account(
+    proof: EIP1186AccountProofResponse,
+    slots: HashMap<B256, U256>,
+    code: Vec<u8>,
+) -> Result<Account> {
+    if has_code(&proof) {
+        let code_hash = keccak256(&code);
+        if proof.code_hash != code_hash {
+            return Err(ExecutionError::CodeHashMismatch(
+                proof.address,
+                code_hash,
+                proof.code_hash,
+            )
+            .into());
         }
-        Ok(())
     }
+
+    Ok(Account {
+        balance: proof.balance,
+        nonce: proof.nonce,
+        code,
+        code_hash: proof.code_hash,
+        storage_hash: proof.storage_hash,
+        slots,
+    })
 }
 
-/// Compute a trie root of a collection of encoded items.
-/// Ref: https://github.com/alloy-rs/trie/blob/main/src/root.rs.
-fn ordered_trie_root(items: &[Vec<u8>]) -> B256 {
-    #[allow(clippy::ptr_arg)]
-    fn noop_encoder(item: &Vec<u8>, buffer: &mut Vec<u8>) {
-        buffer.extend_from_slice(item);
+/// Checks a block the provider served from before the merge for being self consistent, its
+/// header hashing to its hash field and its body matching the roots of the header. Nothing
+/// links it to the verified chain, so this only catches a provider that garbles the block.
+fn check_pre_merge_block<N: NetworkSpec>(block: &N::BlockResponse) -> Result<(), ExecutionError> {
+    if !N::is_header_hash_valid(block.header()) {
+        return Err(ExecutionError::InvalidHeaderHash(block.header().hash()));
     }
+    N::check_body(block)
+}
 
-    ordered_trie_root_with_encoder(items, noop_encoder)
+/// The prevRandao of `block`, which only a header without a mix hash lacks.
+fn prev_randao_of<N: NetworkSpec>(block: &N::BlockResponse) -> Result<PrevRandao> {
+    let header = block.header();
+    let prev_randao = header
+        .mix_hash()
+        .ok_or(ExecutionError::MissingPrevRandao(header.number()))?;
+    Ok(PrevRandao {
+        block_number: header.number(),
+        block_hash: header.hash(),
+        prev_randao,
+    })
 }
 
-/// Ensure that each log entry in the given array of logs match the given filter.
-fn ensure_logs_match_filter(logs: &[Log], filter: &Filter) -> Result<()> {
-    fn log_matches_filter(log: &Log, filter: &Filter) -> bool {
-        if let Some(block_hash) = filter.get_block_hash() {
-            if log.block_hash.unwrap() != block_hash {
-                return false;
-            }
-        }
-        if let Some(from_block) = filter.get_from_block() {
-            if log.block_number.unwrap() < from_block {
-                return false;
-            }
-        }
-        if let Some(to_block) = filter.get_to_block() {
-            if log.block_number.unwrap() > to_block {
-                return false;
-            }
+/// Checks `receipt` against its neighbours in the verified `receipts` of its block, given the
+/// `position` of its transaction. The receipts root commits to the receipts in order but not
+/// to transaction or log indices, so a receipt can match the root and still claim the wrong
+/// place in the block.
+fn ensure_receipt_consistent<N: NetworkSpec>(
+    receipts: &[N::ReceiptResponse],
+    position: usize,
+    receipt: &N::ReceiptResponse,
+) -> Result<(), ExecutionError> {
+    let inconsistent =
+        |reason: String| ExecutionError::InconsistentReceipt(receipt.transaction_hash(), reason);
+
+    if receipt.transaction_index() != Some(position as u64) {
+        return Err(inconsistent(format!(
+            "transaction index {:?}, expected {position}",
+            receipt.transaction_index()
+        )));
+    }
+
+    let cumulative_gas_used = receipt.cumulative_gas_used();
+    let previous = position
+        .checked_sub(1)
+        .and_then(|index| receipts.get(index));
+    let next = receipts.get(position + 1);
+    if previous.is_some_and(|previous| previous.cumulative_gas_used() > cumulative_gas_used)
+        || next.is_some_and(|next| next.cumulative_gas_used() < cumulative_gas_used)
+    {
+        return Err(inconsistent(format!(
+            "cumulative gas used {cumulative_gas_used} is out of order with its neighbours"
+        )));
+    }
+
+    // only the cumulative gas is committed to, the gas of the transaction is what it adds
+    let gas_used =
+        cumulative_gas_used - previous.map_or(0, |previous| previous.cumulative_gas_used());
+    if receipt.gas_used() != gas_used {
+        return Err(inconsistent(format!(
+            "gas used {}, expected {gas_used}",
+            receipt.gas_used()
+        )));
+    }
+
+    // log indices count up across the whole block
+    let first_log_index = receipts
+        .iter()
+        .take(position)
+        .map(|earlier| N::receipt_logs(earlier).len() as u64)
+        .sum::<u64>();
+    for (offset, log) in N::receipt_logs(receipt).iter().enumerate() {
+        let expected = first_log_index + offset as u64;
+        if log.log_index != Some(expected) {
+            return Err(inconsistent(format!(
+                "log index {:?}, expected {expected}",
+                log.log_index
+            )));
         }
-        if !filter.address.matches(&log.address()) {
-            return false;
+    }
+
+    Ok(())
+}
+
+/// Checks the fields of `receipt` that the receipts root leaves out against the verified block
+/// `block_hash` and the transaction `tx_hash` at its place in it, given as `tx` where the block
+/// carries full transactions. Without these a receipt matching the root could still claim
+/// another block, sender or created contract.
+fn ensure_receipt_in_block<N: NetworkSpec>(
+    receipt: &N::ReceiptResponse,
+    block_hash: B256,
+    tx_hash: B256,
+    tx: Option<&N::TransactionResponse>,
+) -> Result<(), ExecutionError> {
+    let inconsistent = |reason: String| ExecutionError::InconsistentReceipt(tx_hash, reason);
+
+    if receipt.transaction_hash() != tx_hash {
+        return Err(inconsistent(format!(
+            "transaction hash {}, expected {tx_hash}",
+            receipt.transaction_hash()
+        )));
+    }
+    if receipt.block_hash() != Some(block_hash) {
+        return Err(inconsistent(format!(
+            "block hash {:?}, expected {block_hash}",
+            receipt.block_hash()
+        )));
+    }
+    for log in N::receipt_logs(receipt) {
+        let misplaced = log.block_hash.is_some_and(|hash| hash != block_hash)
+            || log.transaction_hash.is_some_and(|hash| hash != tx_hash);
+        if misplaced {
+            return Err(inconsistent(format!(
+                "log {:?} is placed in block {:?} and transaction {:?}",
+                log.log_index, log.block_hash, log.transaction_hash
+            )));
         }
-        for (i, filter_topic) in filter.topics.iter().enumerate() {
-            if !filter_topic.is_empty() {
-                if let Some(log_topic) = log.topics().get(i) {
-                    if !filter_topic.matches(log_topic) {
-                        return false;
-                    }
-                } else {
-                    // if filter topic is not present in log, it's a mismatch
-                    return false;
-                }
-            }
+    }
+
+    let Some(tx) = tx else {
+        return Ok(());
+    };
+    if receipt.from() != tx.from() {
+        return Err(inconsistent(format!(
+            "sender {}, expected {}",
+            receipt.from(),
+            tx.from()
+        )));
+    }
+    if receipt.to() != tx.to() {
+        return Err(inconsistent(format!(
+            "recipient {:?}, expected {:?}",
+            receipt.to(),
+            tx.to()
+        )));
+    }
+    // a call creates no contract, a creation the one its sender and nonce determine
+    let created = match tx.to() {
+        Some(_) => Some(None),
+        None => N::created_address(tx).map(Some),
+    };
+    if let Some(created) = created.filter(|created| receipt.contract_address() != *created) {
+        return Err(inconsistent(format!(
+            "contract address {:?}, expected {created:?}",
+            receipt.contract_address()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks the verified `receipts` of `block` against its transactions, each receipt being
+/// that of the transaction at its place. The receipts root commits to neither, so without
+/// this the logs of a receipt could be claimed for another transaction.
+fn ensure_receipts_in_block<N: NetworkSpec>(
+    block: &N::BlockResponse,
+    receipts: &[N::ReceiptResponse],
+) -> Result<(), ExecutionError> {
+    let header = block.header();
+    let hashes = block.transactions().hashes().collect::<Vec<_>>();
+    if receipts.len() != hashes.len() {
+        return Err(ExecutionError::BlockReceiptsRootMismatch(BlockTag::Number(
+            header.number(),
+        )));
+    }
+
+    let txs = block.transactions().as_transactions();
+    for (position, (receipt, tx_hash)) in receipts.iter().zip(hashes).enumerate() {
+        if receipt.transaction_index() != Some(position as u64) {
+            return Err(ExecutionError::InconsistentReceipt(
+                tx_hash,
+                format!(
+                    "transaction index {:?}, expected {position}",
+                    receipt.transaction_index()
+                ),
+            ));
         }
-        true
+        let tx = txs.and_then(|txs| txs.get(position));
+        ensure_receipt_in_block::<N>(receipt, header.hash(), tx_hash, tx)?;
     }
+    Ok(())
+}
 
+/// The logs of the verified `receipts` of block `block_number`, placed by their position in
+/// the block rather than by the indices the provider gave them.
+fn block_logs<N: NetworkSpec>(block_number: u64, receipts: &[N::ReceiptResponse]) -> Vec<Log> {
+    let logs = receipts.iter().enumerate().flat_map(|(tx_index, receipt)| {
+        N::receipt_logs(receipt).into_iter().map(move |log| Log {
+            block_hash: receipt.block_hash(),
+            block_number: Some(block_number),
+            transaction_hash: Some(receipt.transaction_hash()),
+            transaction_index: Some(tx_index as u64),
+            removed: false,
+            ..log
+        })
+    });
+    logs.enumerate()
+        .map(|(log_index, log)| Log {
+            log_index: Some(log_index as u64),
+            ..log
+        })
+        .collect()
+}
+
+/// Ensure that each log entry in the given array of logs match the given filter.
+fn ensure_logs_match_filter(logs: &[Log], filter: &Filter) -> Result<()> {
     for log in logs {
         if !log_matches_filter(log, filter) {
             return Err(ExecutionError::LogFilterMismatch().into());
@@ -546,3 +2420,43 @@ fn ensure_logs_match_filter(logs: &[Log], filter: &Filter) -> Result<()> {
 
     Ok(())
 }
+
+fn log_block_number(log: &Log) -> Result<u64> {
+    log.block_number
+        .ok_or_else(|| eyre::eyre!("block num not found in log"))
+}
+
+/// Ensures each of `logs` is one of the `verified` logs of its block, with the same index,
+/// address, topics and data, and placed in the same transaction.
+fn ensure_logs_in_receipts(logs: &[Log], verified: &[Log]) -> Result<()> {
+    let verified = verified
+        .iter()
+        .map(|log| ((log.block_number, log.log_index), log))
+        .collect::<HashMap<_, _>>();
+
+    for log in logs {
+        let tx_hash = log
+            .transaction_hash
+            .ok_or_else(|| eyre::eyre!("tx hash not found in log"))?;
+
+        // a log claimed at an index its block does not reach, or in a block without any
+        // transactions, has no receipt to be found in. The timestamp is the only field the
+        // verified copy takes from the provider as well.
+        let included = verified
+            .get(&(log.block_number, log.log_index))
+            .is_some_and(|verified| {
+                Log {
+                    block_timestamp: log.block_timestamp,
+                    ..(*verified).clone()
+                } == *log
+            });
+        if !included {
+            return Err(ExecutionError::MissingLog(
+                tx_hash,
+                U256::from(log.log_index.unwrap_or_default()),
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,211 @@
+//! Blocks orphaned by reorgs, and the reorgs themselves, kept for a while after they happen.
+//!
+//! Orphaned blocks are evicted from state as soon as the new branch replaces them, and the
+//! provider may no longer serve them either. Keeping the most recent ones aside lets them be
+//! looked up by hash while debugging a reorg, without them ever resolving by number or tag.
+//! The receipts verified for an orphan are kept with it, so the logs delivered from it can be
+//! reported as removed.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use alloy::network::{primitives::HeaderResponse, BlockResponse};
+use alloy::primitives::B256;
+use serde::{Deserialize, Serialize};
+
+use crate::config::ConfigDuration;
+use crate::time::Instant;
+use crate::types::ReorgEvent;
+
+use super::constants::{DEFAULT_ORPHAN_CACHE_SIZE, DEFAULT_ORPHAN_CACHE_TTL, MAX_REORG_HISTORY};
+
+/// How many orphaned blocks are kept and for how long, read from the `orphan_cache` config
+/// table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrphanCacheConfig {
+    pub size: usize,
+    pub ttl: ConfigDuration,
+}
+
+impl Default for OrphanCacheConfig {
+    fn default() -> Self {
+        Self {
+            size: DEFAULT_ORPHAN_CACHE_SIZE,
+            ttl: DEFAULT_ORPHAN_CACHE_TTL.into(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Orphan<B, R> {
+    at: Instant,
+    block: B,
+    receipts: Option<R>,
+}
+
+/// Recently orphaned blocks, oldest first, and the most recent reorgs.
+#[derive(Debug)]
+pub(crate) struct ReorgLog<B, R = ()> {
+    config: OrphanCacheConfig,
+    orphans: VecDeque<Orphan<B, R>>,
+    reorgs: VecDeque<ReorgEvent>,
+}
+
+impl<B: BlockResponse, R> ReorgLog<B, R> {
+    pub fn new(config: OrphanCacheConfig) -> Self {
+        Self {
+            config,
+            orphans: VecDeque::new(),
+            reorgs: VecDeque::new(),
+        }
+    }
+
+    pub fn configure(&mut self, config: OrphanCacheConfig) {
+        self.config = config;
+        self.evict();
+    }
+
+    /// Keeps `block` aside with the `receipts` verified for it, evicting the oldest orphans
+    /// once more than the configured number are held.
+    pub fn orphan(&mut self, block: B, receipts: Option<R>) {
+        self.forget(block.header().hash());
+        self.orphans.push_back(Orphan {
+            at: Instant::now(),
+            block,
+            receipts,
+        });
+        self.evict();
+    }
+
+    /// Drops the orphan with `hash`, for blocks that became canonical again.
+    pub fn forget(&mut self, hash: B256) {
+        self.orphans
+            .retain(|orphan| orphan.block.header().hash() != hash);
+    }
+
+    pub fn get(&self, hash: B256) -> Option<&B> {
+        self.find(hash).map(|orphan| &orphan.block)
+    }
+
+    /// The receipts verified for the orphan with `hash` before the reorg replaced it.
+    pub fn receipts(&self, hash: B256) -> Option<&R> {
+        self.find(hash)?.receipts.as_ref()
+    }
+
+    fn find(&self, hash: B256) -> Option<&Orphan<B, R>> {
+        let ttl = self.ttl();
+        self.orphans
+            .iter()
+            .find(|orphan| orphan.block.header().hash() == hash && orphan.at.elapsed() < ttl)
+    }
+
+    pub fn record(&mut self, reorg: ReorgEvent) {
+        if self.reorgs.len() == MAX_REORG_HISTORY {
+            self.reorgs.pop_front();
+        }
+        self.reorgs.push_back(reorg);
+    }
+
+    /// Recorded reorgs, oldest first.
+    pub fn history(&self) -> Vec<ReorgEvent> {
+        self.reorgs.iter().cloned().collect()
+    }
+
+    fn ttl(&self) -> Duration {
+        self.config.ttl.as_duration()
+    }
+
+    fn evict(&mut self) {
+        let ttl = self.ttl();
+        self.orphans.retain(|orphan| orphan.at.elapsed() < ttl);
+        while self.orphans.len() > self.config.size {
+            self.orphans.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::rpc::types::{Block, Header};
+
+    use super::*;
+
+    fn block(number: u64) -> Block {
+        Block {
+            header: Header {
+                hash: B256::with_last_byte(number as u8),
+                inner: alloy::consensus::Header {
+                    number,
+                    ..Default::default()
+                },
+                total_difficulty: None,
+                size: None,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn reorg(depth: u64) -> ReorgEvent {
+        ReorgEvent {
+            depth,
+            fork_number: 10,
+            old_chain: Vec::new(),
+            new_chain: Vec::new(),
+            deep: false,
+            detected_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_oldest_orphans_evicted() {
+        let mut log = ReorgLog::<Block>::new(OrphanCacheConfig {
+            size: 2,
+            ..Default::default()
+        });
+        for number in 1..=3 {
+            log.orphan(block(number), None);
+        }
+
+        assert!(log.get(block(1).header.hash).is_none());
+        assert_eq!(log.get(block(2).header.hash).unwrap().header.number, 2);
+        assert_eq!(log.get(block(3).header.hash).unwrap().header.number, 3);
+
+        // orphaning a block again refreshes it
+        log.orphan(block(2), None);
+        log.orphan(block(4), None);
+        assert!(log.get(block(3).header.hash).is_none());
+        assert!(log.get(block(2).header.hash).is_some());
+
+        log.forget(block(2).header.hash);
+        assert!(log.get(block(2).header.hash).is_none());
+    }
+
+    #[test]
+    fn test_expired_orphans_not_served() {
+        let mut log = ReorgLog::new(OrphanCacheConfig::default());
+        log.orphan(block(1), Some(vec![1]));
+        assert!(log.get(block(1).header.hash).is_some());
+        assert_eq!(log.receipts(block(1).header.hash), Some(&vec![1]));
+
+        log.configure(OrphanCacheConfig {
+            ttl: Duration::ZERO.into(),
+            ..Default::default()
+        });
+        assert!(log.get(block(1).header.hash).is_none());
+        assert!(log.receipts(block(1).header.hash).is_none());
+    }
+
+    #[test]
+    fn test_reorg_history_bounded() {
+        let mut log = ReorgLog::<Block>::new(OrphanCacheConfig::default());
+        for depth in 0..MAX_REORG_HISTORY as u64 + 2 {
+            log.record(reorg(depth));
+        }
+
+        let history = log.history();
+        assert_eq!(history.len(), MAX_REORG_HISTORY);
+        assert_eq!(history.first().unwrap().depth, 2);
+        assert_eq!(history.last().unwrap().depth, MAX_REORG_HISTORY as u64 + 1);
+    }
+}
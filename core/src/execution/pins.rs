@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use alloy::primitives::B256;
+
+use super::errors::ExecutionError;
+
+/// Why a block is held in the state cache beyond what its recency alone would allow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RetentionReason {
+    /// Explicitly pinned by a caller.
+    Pin,
+    /// The state anchor of a call that is still executing.
+    Anchor,
+    /// The position of a subscription that has not caught up yet.
+    Cursor,
+    /// Not pinned, kept because it was used recently enough to fit the cache.
+    RecentlyUsed,
+}
+
+/// Keeps a cached block from being evicted until dropped.
+///
+/// Pins are held by block hash, so a reorg that replaces the block still removes it, and
+/// releases its pins along with it. Clones count as separate pins.
+#[derive(Debug)]
+pub struct BlockPin {
+    pins: Arc<Mutex<PinTable>>,
+    number: u64,
+    hash: B256,
+    reason: RetentionReason,
+    generation: u64,
+}
+
+impl BlockPin {
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    pub fn hash(&self) -> B256 {
+        self.hash
+    }
+
+    pub fn reason(&self) -> RetentionReason {
+        self.reason
+    }
+}
+
+impl Clone for BlockPin {
+    fn clone(&self) -> Self {
+        self.pins
+            .lock()
+            .unwrap()
+            .readd(self.hash, self.reason, self.generation);
+        Self {
+            pins: self.pins.clone(),
+            number: self.number,
+            hash: self.hash,
+            reason: self.reason,
+            generation: self.generation,
+        }
+    }
+}
+
+impl Drop for BlockPin {
+    fn drop(&mut self) {
+        self.pins
+            .lock()
+            .unwrap()
+            .remove(self.hash, self.reason, self.generation);
+    }
+}
+
+/// The pins of one block, counted per reason.
+#[derive(Debug)]
+struct Pinned {
+    reasons: HashMap<RetentionReason, usize>,
+    /// Tells the pins taken before the block was released from those taken after, in case
+    /// the block is held again, as after a reorg back to it.
+    generation: u64,
+}
+
+/// Active pins by block hash. At most `budget` distinct blocks can be pinned at once, and
+/// pinned blocks are held on top of the recent blocks the cache keeps, so pins never crowd
+/// out the blocks following the head.
+#[derive(Debug)]
+pub(crate) struct PinTable {
+    pinned: HashMap<B256, Pinned>,
+    budget: usize,
+    next_generation: u64,
+}
+
+impl PinTable {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            pinned: HashMap::new(),
+            budget,
+            next_generation: 0,
+        }
+    }
+
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+    }
+
+    /// Pins the block `hash` at `number`, failing if that would pin more blocks than the
+    /// budget allows. Further pins of an already pinned block always succeed.
+    pub fn pin(
+        pins: &Arc<Mutex<Self>>,
+        number: u64,
+        hash: B256,
+        reason: RetentionReason,
+    ) -> Result<BlockPin, ExecutionError> {
+        let mut table = pins.lock().unwrap();
+        if !table.pinned.contains_key(&hash) && table.pinned.len() >= table.budget {
+            return Err(ExecutionError::PinBudgetExceeded(table.budget));
+        }
+
+        let generation = table.add(hash, reason);
+        Ok(BlockPin {
+            pins: pins.clone(),
+            number,
+            hash,
+            reason,
+            generation,
+        })
+    }
+
+    /// Releases every pin of `hash`, whose block left the cache, freeing its slot of the
+    /// budget. Pins still held of it no longer count for anything.
+    pub fn release(&mut self, hash: &B256) {
+        self.pinned.remove(hash);
+    }
+
+    pub fn is_pinned(&self, hash: &B256) -> bool {
+        self.pinned.contains_key(hash)
+    }
+
+    /// The reasons `hash` is pinned for, in a stable order.
+    pub fn reasons(&self, hash: &B256) -> Vec<RetentionReason> {
+        let mut reasons = self
+            .pinned
+            .get(hash)
+            .map(|pinned| pinned.reasons.keys().copied().collect::<Vec<_>>())
+            .unwrap_or_default();
+        reasons.sort();
+        reasons
+    }
+
+    /// Adds a pin, returning the generation of the block's pins.
+    fn add(&mut self, hash: B256, reason: RetentionReason) -> u64 {
+        let next_generation = &mut self.next_generation;
+        let pinned = self.pinned.entry(hash).or_insert_with(|| {
+            *next_generation += 1;
+            Pinned {
+                reasons: HashMap::new(),
+                generation: *next_generation,
+            }
+        });
+        *pinned.reasons.entry(reason).or_default() += 1;
+        pinned.generation
+    }
+
+    /// Adds the clone of a pin of `generation`, unless the block was released since.
+    fn readd(&mut self, hash: B256, reason: RetentionReason, generation: u64) {
+        if let Some(pinned) = self
+            .pinned
+            .get_mut(&hash)
+            .filter(|pinned| pinned.generation == generation)
+        {
+            *pinned.reasons.entry(reason).or_default() += 1;
+        }
+    }
+
+    fn remove(&mut self, hash: B256, reason: RetentionReason, generation: u64) {
+        let Some(pinned) = self
+            .pinned
+            .get_mut(&hash)
+            .filter(|pinned| pinned.generation == generation)
+        else {
+            return;
+        };
+
+        if let Some(count) = pinned.reasons.get_mut(&reason) {
+            *count -= 1;
+            if *count == 0 {
+                pinned.reasons.remove(&reason);
+            }
+        }
+        if pinned.reasons.is_empty() {
+            self.pinned.remove(&hash);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Checks of raw transactions against the verified state before they are sent.
+//!
+//! A transaction the chain would refuse then fails with the reason, in the words geth uses
+//! for it, rather than whatever the provider answers. Some providers accept such
+//! transactions and drop them later, which would otherwise go unnoticed.
+//!
+//! Only what the signed transaction and the state of its sender decide is checked: the
+//! chain id, that the nonce is not used yet, and that the balance covers the most the
+//! transaction can cost. Fees below the current base fee or a nonce gap are left to the
+//! provider, as they only delay inclusion.
+
+use alloy::consensus::Transaction;
+use alloy::eips::eip4844::DATA_GAS_PER_BLOB;
+use alloy::primitives::{Address, U256};
+
+use super::errors::ExecutionError;
+use super::types::Account;
+
+/// The parts of a signed transaction the checks need, decoded by the network spec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawTransaction {
+    pub sender: Address,
+    /// `None` for legacy transactions signed without replay protection.
+    pub chain_id: Option<u64>,
+    pub nonce: u64,
+    /// Value plus gas and blob gas at the highest fees the transaction allows.
+    pub max_cost: U256,
+}
+
+impl RawTransaction {
+    pub fn new<T: Transaction>(tx: &T, sender: Address) -> Self {
+        let gas = U256::from(tx.gas_limit()) * U256::from(tx.max_fee_per_gas());
+        let blobs = tx.blob_versioned_hashes().map_or(0, <[_]>::len) as u64;
+        let blob_gas = U256::from(blobs * DATA_GAS_PER_BLOB)
+            * U256::from(tx.max_fee_per_blob_gas().unwrap_or_default());
+
+        Self {
+            sender,
+            chain_id: tx.chain_id(),
+            nonce: tx.nonce(),
+            max_cost: tx.value() + gas + blob_gas,
+        }
+    }
+}
+
+/// Checks `tx` against the chain id of the client and the verified `account` of its sender,
+/// in the order a node validates it.
+pub fn check_transaction(
+    tx: &RawTransaction,
+    chain_id: u64,
+    account: &Account,
+) -> Result<(), ExecutionError> {
+    if let Some(id) = tx.chain_id.filter(|id| *id != chain_id) {
+        return Err(ExecutionError::InvalidChainId(id, chain_id));
+    }
+    if tx.nonce < account.nonce {
+        return Err(ExecutionError::NonceTooLow(account.nonce, tx.nonce));
+    }
+    if account.balance < tx.max_cost {
+        return Err(ExecutionError::InsufficientFunds(
+            account.balance,
+            tx.max_cost,
+        ));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+//! How far the state has come in applying the blocks delivered by consensus, shared by the
+//! loop applying them and everyone reporting on it.
+
+use std::sync::Arc;
+
+use alloy::primitives::U64;
+use tokio::sync::watch;
+
+use crate::time::Instant;
+use crate::types::{BackfillProgress, HeadFeed};
+
+/// Block numbers the state went through while applying verified blocks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncProgress {
+    /// The first head delivered since the client started.
+    pub starting_block: Option<u64>,
+    /// The latest head applied to the state, its ancestors backfilled.
+    pub current_block: Option<u64>,
+    /// The latest head delivered, which may still be being applied.
+    pub highest_block: Option<u64>,
+    pub finalized_block: Option<u64>,
+    /// When the current block was applied.
+    pub updated_at: Option<Instant>,
+    /// The backfill of the blocks behind the first head, none before it starts and once it
+    /// ends, or once the window it stopped short of is filled.
+    pub backfill: Option<BackfillProgress>,
+    pub head_feed: HeadFeed,
+}
+
+impl SyncProgress {
+    /// Whether the backfill is still fetching blocks, rather than done or stopped.
+    pub fn is_backfilling(&self) -> bool {
+        self.backfill
+            .is_some_and(|backfill| backfill.stopped_at.is_none())
+    }
+}
+
+/// Records the [`SyncProgress`] of a state, for it and its clones.
+#[derive(Debug, Clone)]
+pub struct SyncTracker {
+    progress: Arc<watch::Sender<SyncProgress>>,
+}
+
+impl Default for SyncTracker {
+    fn default() -> Self {
+        Self {
+            progress: Arc::new(watch::channel(SyncProgress::default()).0),
+        }
+    }
+}
+
+impl SyncTracker {
+    pub fn progress(&self) -> SyncProgress {
+        self.progress.borrow().clone()
+    }
+
+    /// Notifies of every change to the progress.
+    pub fn subscribe(&self) -> watch::Receiver<SyncProgress> {
+        self.progress.subscribe()
+    }
+
+    /// A head was delivered, before it is applied.
+    pub fn head_delivered(&self, number: u64) {
+        self.progress.send_modify(|progress| {
+            progress.starting_block.get_or_insert(number);
+            progress.highest_block = Some(number);
+        });
+    }
+
+    pub fn head_applied(&self, number: u64) {
+        self.progress.send_modify(|progress| {
+            progress.current_block = Some(number);
+            progress.updated_at = Some(Instant::now());
+        });
+    }
+
+    /// The backfill behind the first head set out to fetch `target` blocks.
+    pub fn backfill_started(&self, target: u64) {
+        self.progress.send_modify(|progress| {
+            progress.backfill = Some(BackfillProgress {
+                target_blocks: U64::from(target),
+                fetched_blocks: U64::ZERO,
+                stopped_at: None,
+            })
+        });
+    }
+
+    pub fn block_backfilled(&self) {
+        self.progress.send_modify(|progress| {
+            if let Some(backfill) = &mut progress.backfill {
+                backfill.fetched_blocks += U64::from(1);
+            }
+        });
+    }
+
+    /// The backfill ended, with every block fetched or the window moved on.
+    pub fn backfill_finished(&self) {
+        self.progress
+            .send_modify(|progress| progress.backfill = None);
+    }
+
+    /// The backfill stopped at block `number`, which could not be fetched, leaving the
+    /// window partial.
+    pub fn backfill_stopped(&self, number: u64) {
+        self.progress.send_modify(|progress| {
+            if let Some(backfill) = &mut progress.backfill {
+                backfill.stopped_at = Some(U64::from(number));
+            }
+        });
+    }
+
+    /// Heads filled the window a stopped backfill left partial.
+    pub fn window_filled(&self) {
+        self.progress.send_if_modified(|progress| {
+            let stopped = progress
+                .backfill
+                .is_some_and(|backfill| backfill.stopped_at.is_some());
+            if stopped {
+                progress.backfill = None;
+            }
+            stopped
+        });
+    }
+
+    pub fn head_feed_changed(&self, head_feed: HeadFeed) {
+        self.progress
+            .send_modify(|progress| progress.head_feed = head_feed);
+    }
+
+    pub fn finalized(&self, number: u64) {
+        self.progress
+            .send_modify(|progress| progress.finalized_block = Some(number));
+    }
+}
@@ -1,40 +1,83 @@
 use std::collections::HashMap;
 
+use alloy::consensus::Header;
 use alloy::primitives::{keccak256, Bytes, B256, U256};
-use alloy::rlp;
+use alloy::rlp::{self, Decodable};
 use alloy::rpc::types::EIP1186AccountProofResponse;
 use alloy_trie::{
+    nodes::TrieNode,
     proof::verify_proof,
-    {Nibbles, TrieAccount},
+    root::ordered_trie_root_with_encoder,
+    {Nibbles, TrieAccount, EMPTY_ROOT_HASH},
 };
 use eyre::{eyre, Result};
+use revm::primitives::KECCAK_EMPTY;
+use sha2::{Digest, Sha256};
+
+use crate::network_spec::NetworkSpec;
+use crate::types::{BeaconBlockMapping, BeaconHeader, ExecutionProof, EXECUTION_PROOF_VERSION};
 
 use super::errors::ExecutionError;
 
 /// Verify a given `EIP1186AccountProofResponse`'s account proof against given state root.
+///
+/// An account claimed without nonce, balance, code or storage may be proven absent, or
+/// present with exactly those fields, as accounts left empty before EIP-161 still are. Any
+/// other account must be proven present with exactly the claimed fields.
 pub fn verify_account_proof(proof: &EIP1186AccountProofResponse, state_root: B256) -> Result<()> {
-    let account_key = proof.address;
+    check_account_proof(proof, state_root).map_err(|err| eyre!(err))
+}
+
+/// Verify a given `EIP1186AccountProofResponse`'s storage proof against the storage root.
+/// Also returns a map of storage slots.
+///
+/// Slots set to zero are deleted from the trie, so a zero value must be proven absent and
+/// any other value present with exactly that value. Must only be called once the account
+/// proof is verified.
+pub fn verify_storage_proof(proof: &EIP1186AccountProofResponse) -> Result<HashMap<B256, U256>> {
+    check_storage_proof(proof).map_err(|err| eyre!(err))
+}
+
+fn check_account_proof(
+    proof: &EIP1186AccountProofResponse,
+    state_root: B256,
+) -> Result<(), ExecutionError> {
     let account = TrieAccount {
         nonce: proof.nonce,
         balance: proof.balance,
         storage_root: proof.storage_hash,
         code_hash: proof.code_hash,
     };
+    let value = rlp::encode(&account);
 
-    verify_mpt_proof(state_root, account_key, account, &proof.account_proof)
-        .map_err(|_| eyre!(ExecutionError::InvalidAccountProof(proof.address)))
+    let present = prove(state_root, proof.address, Some(value), &proof.account_proof);
+    let proven = present.is_ok()
+        || (is_empty_account(&account)
+            && prove(state_root, proof.address, None, &proof.account_proof).is_ok());
+    if proven {
+        Ok(())
+    } else {
+        Err(ExecutionError::InvalidAccountProof(proof.address))
+    }
 }
 
-/// Verify a given `EIP1186AccountProofResponse`'s storage proof against the storage root.
-/// Also returns a map of storage slots.
-pub fn verify_storage_proof(proof: &EIP1186AccountProofResponse) -> Result<HashMap<B256, U256>> {
-    let mut slot_map = HashMap::with_capacity(proof.storage_proof.len());
+fn check_storage_proof(
+    proof: &EIP1186AccountProofResponse,
+) -> Result<HashMap<B256, U256>, ExecutionError> {
+    // some providers answer for absent accounts with a zero storage hash, which their account
+    // proof then showed to be empty storage
+    let storage_root = match proof.storage_hash {
+        B256::ZERO => EMPTY_ROOT_HASH,
+        storage_root => storage_root,
+    };
 
+    let mut slot_map = HashMap::with_capacity(proof.storage_proof.len());
     for storage_proof in &proof.storage_proof {
         let key = storage_proof.key.as_b256();
         let value = storage_proof.value;
 
-        verify_mpt_proof(proof.storage_hash, key, value, &storage_proof.proof)
+        let expected = (!value.is_zero()).then(|| rlp::encode(value));
+        prove(storage_root, key, expected, &storage_proof.proof)
             .map_err(|_| ExecutionError::InvalidStorageProof(proof.address, key))?;
 
         slot_map.insert(key, value);
@@ -43,6 +86,204 @@ pub fn verify_storage_proof(proof: &EIP1186AccountProofResponse) -> Result<HashM
     Ok(slot_map)
 }
 
+/// Whether `account` has no nonce, balance, code or storage, its hashes given either as those
+/// of empty code and storage or, as some providers answer for absent accounts, as zero.
+fn is_empty_account(account: &TrieAccount) -> bool {
+    account.nonce == 0
+        && account.balance.is_zero()
+        && [KECCAK_EMPTY, B256::ZERO].contains(&account.code_hash)
+        && [EMPTY_ROOT_HASH, B256::ZERO].contains(&account.storage_root)
+}
+
+/// Generalized indices of the execution block hash in a beacon block body, through an
+/// execution payload of 15 fields up to Capella and of 17 from Deneb.
+pub const EXECUTION_BLOCK_HASH_INDICES: [u64; 2] = [412, 812];
+/// Generalized indices of the finalized checkpoint root in the beacon state, before and from
+/// Electra.
+pub const FINALIZED_ROOT_INDICES: [u64; 2] = [105, 169];
+
+/// Verifies an [ExecutionProof] without a client, against `trusted_root`: a beacon block
+/// root the verifier trusts, such as one its own light client verified, or for chains without
+/// a beacon chain the hash of the execution block itself.
+///
+/// The beacon block carrying the execution block, or the last of its descendants, must either
+/// be the trusted one or be proven finalized by it. The sync committee signature of the bundle
+/// is not checked here, as that needs the committee; a verifier holding it checks the
+/// signature over the attested header and may then trust that header's root.
+pub fn verify_execution_proof(
+    proof: &ExecutionProof,
+    trusted_root: B256,
+) -> Result<(), ExecutionError> {
+    if proof.version != EXECUTION_PROOF_VERSION {
+        return Err(ExecutionError::UnsupportedProofVersion(
+            proof.version,
+            EXECUTION_PROOF_VERSION,
+        ));
+    }
+
+    let header = Header::decode(&mut proof.header.as_ref())
+        .map_err(|err| ExecutionError::InvalidProofHeader(err.to_string()))?;
+    let block_hash = keccak256(&proof.header);
+    // the descendants lead from the block up to the one the beacon block carries
+    let mut carried = block_hash;
+    for descendant in &proof.descendants {
+        let child = Header::decode(&mut descendant.as_ref())
+            .map_err(|err| ExecutionError::InvalidProofHeader(err.to_string()))?;
+        if child.parent_hash != carried {
+            return Err(ExecutionError::UntrustedProof(
+                block_hash,
+                "descendant is not a child of the block before it",
+            ));
+        }
+        carried = keccak256(descendant);
+    }
+    if carried != trusted_root {
+        let beacon = proof.beacon.as_ref().ok_or(ExecutionError::UntrustedProof(
+            block_hash,
+            "no beacon block",
+        ))?;
+        verify_beacon_link(beacon, carried, trusted_root)?;
+    }
+
+    // the checks behind the public proof helpers above, which keep the execution error
+    let account = &proof.account;
+    check_account_proof(account, header.state_root)?;
+    check_storage_proof(account)?;
+
+    if let Some(code) = &proof.code {
+        let code_hash = keccak256(code);
+        let expected = match account.code_hash {
+            B256::ZERO => KECCAK_EMPTY,
+            code_hash => code_hash,
+        };
+        if code_hash != expected {
+            return Err(ExecutionError::CodeHashMismatch(
+                account.address,
+                code_hash,
+                account.code_hash,
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `beacon` carries the execution block `block_hash` and is, or was finalized by,
+/// the beacon block `trusted_root`.
+fn verify_beacon_link(
+    beacon: &BeaconBlockMapping,
+    block_hash: B256,
+    trusted_root: B256,
+) -> Result<(), ExecutionError> {
+    let untrusted = |reason| ExecutionError::UntrustedProof(block_hash, reason);
+    if beacon.execution_block_hash != block_hash {
+        return Err(untrusted("beacon block carries another execution block"));
+    }
+
+    let attestation = beacon
+        .attestation
+        .as_ref()
+        .ok_or(untrusted("no attestation of the beacon block"))?;
+    let index = attestation.generalized_index;
+    if !EXECUTION_BLOCK_HASH_INDICES.contains(&index)
+        || !is_merkle_branch_valid(
+            block_hash,
+            &attestation.execution_branch,
+            index,
+            beacon.body_root,
+        )
+    {
+        return Err(untrusted("invalid execution branch"));
+    }
+
+    let root = beacon_header_root(&beacon.header());
+    if root == trusted_root {
+        return Ok(());
+    }
+
+    let finality = attestation
+        .finality
+        .as_ref()
+        .ok_or(untrusted("beacon block is not the trusted one"))?;
+    let index = finality.generalized_index;
+    if !FINALIZED_ROOT_INDICES.contains(&index)
+        || !is_merkle_branch_valid(
+            root,
+            &finality.branch,
+            index,
+            finality.attested_header.state_root,
+        )
+    {
+        return Err(untrusted("invalid finality branch"));
+    }
+    if beacon_header_root(&finality.attested_header) != trusted_root {
+        return Err(untrusted("attested header is not the trusted one"));
+    }
+
+    Ok(())
+}
+
+/// The SSZ hash tree root of a beacon block header, which is its beacon block root.
+pub fn beacon_header_root(header: &BeaconHeader) -> B256 {
+    let mut leaves = [B256::ZERO; 8];
+    leaves[0][..8].copy_from_slice(&header.slot.to_le_bytes());
+    leaves[1][..8].copy_from_slice(&header.proposer_index.to_le_bytes());
+    leaves[2] = header.parent_root;
+    leaves[3] = header.state_root;
+    leaves[4] = header.body_root;
+
+    let mut nodes = leaves.to_vec();
+    while nodes.len() > 1 {
+        nodes = nodes
+            .chunks(2)
+            .map(|pair| hash_pair(pair[0], pair[1]))
+            .collect();
+    }
+    nodes[0]
+}
+
+/// The root an SSZ merkle branch proves `leaf` against at `generalized_index`, or `None` if
+/// the branch is not as deep as the index.
+pub fn merkle_root(leaf: B256, branch: &[B256], generalized_index: u64) -> Option<B256> {
+    if branch.len() >= 64 || generalized_index >> branch.len() != 1 {
+        return None;
+    }
+
+    let root = branch
+        .iter()
+        .enumerate()
+        .fold(leaf, |node, (depth, sibling)| {
+            if (generalized_index >> depth) & 1 == 1 {
+                hash_pair(*sibling, node)
+            } else {
+                hash_pair(node, *sibling)
+            }
+        });
+    Some(root)
+}
+
+/// Whether `branch` proves `leaf` at `generalized_index` against `root`.
+pub fn is_merkle_branch_valid(
+    leaf: B256,
+    branch: &[B256],
+    generalized_index: u64,
+    root: B256,
+) -> bool {
+    merkle_root(leaf, branch, generalized_index) == Some(root)
+}
+
+fn hash_pair(left: B256, right: B256) -> B256 {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    B256::from_slice(&hasher.finalize())
+}
+
+/// Computes the receipts root of a block from all of its receipts, in order.
+pub fn receipts_root<N: NetworkSpec>(receipts: &[N::ReceiptResponse]) -> B256 {
+    ordered_trie_root_with_encoder(receipts, N::encode_receipt_into)
+}
+
 /// Verifies a MPT proof for a given key-value pair against the provided root hash.
 /// This function wraps `alloy_trie::proof::verify_proof` and checks
 /// if the value represents an empty account or slot to support exclusion proofs.
@@ -58,7 +299,6 @@ pub fn verify_mpt_proof<K: AsRef<[u8]>, V: rlp::Encodable>(
     raw_value: V,
     proof: &[Bytes],
 ) -> Result<()> {
-    let key = Nibbles::unpack(keccak256(raw_key));
     let value = rlp::encode(raw_value);
 
     let value = if is_empty_value(&value) {
@@ -67,9 +307,67 @@ pub fn verify_mpt_proof<K: AsRef<[u8]>, V: rlp::Encodable>(
         Some(value) // inclusion proof
     };
 
+    prove(root, raw_key, value, proof)
+}
+
+/// Proves `value` stored under the hash of `raw_key`, or nothing when it is `None`.
+fn prove<K: AsRef<[u8]>>(
+    root: B256,
+    raw_key: K,
+    value: Option<Vec<u8>>,
+    proof: &[Bytes],
+) -> Result<()> {
+    let key = Nibbles::unpack(keccak256(raw_key));
+    // `verify_proof` takes a proof stopping at a node it leaves out as proving the key absent
+    if value.is_none() && !reaches_key(&key, proof) {
+        return Err(eyre!("exclusion proof stops short of the key"));
+    }
+
     verify_proof(root, key, value, proof).map_err(|e| eyre!(e))
 }
 
+/// Whether the nodes of `proof` follow `key` as far as the trie goes: to a leaf, or to a node
+/// showing that nothing is stored under the key. Only the shape is checked here, that the
+/// nodes hash to one another is left to `verify_proof`.
+fn reaches_key(key: &Nibbles, proof: &[Bytes]) -> bool {
+    let mut walked = 0;
+    for (index, node) in proof.iter().enumerate() {
+        let last = index + 1 == proof.len();
+        let Ok(node) = TrieNode::decode(&mut node.as_ref()) else {
+            return false;
+        };
+
+        match node {
+            TrieNode::Branch(branch) => {
+                let Some(&nibble) = key.get(walked) else {
+                    return false;
+                };
+                // without a child for the key the path ends here, a child must come next
+                if !branch.state_mask.is_bit_set(nibble) {
+                    return last;
+                } else if last {
+                    return false;
+                }
+                walked += 1;
+            }
+            TrieNode::Extension(extension) => {
+                if !key[walked..].starts_with(&extension.key) {
+                    return last;
+                } else if last {
+                    return false;
+                }
+                walked += extension.key.len();
+            }
+            // the leaf holds the key or another one diverging from it
+            TrieNode::Leaf(_) => return last,
+            TrieNode::EmptyRoot => return proof.len() == 1,
+        }
+    }
+
+    // an empty proof stands for the empty trie
+    proof.is_empty()
+}
+
 /// Check if the value is an empty account or empty slot.
 fn is_empty_value(value: &[u8]) -> bool {
     let empty_account = TrieAccount::default();
@@ -87,3 +385,181 @@ fn is_empty_value(value: &[u8]) -> bool {
     let is_empty_account = value == empty_account || value == new_empty_account;
     is_empty_slot || is_empty_account
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use alloy::primitives::Address;
+    use alloy::rpc::types::EIP1186StorageProof;
+    use alloy_trie::{proof::ProofRetainer, HashBuilder};
+
+    use super::*;
+
+    /// A trie of `leaves` under the hashes of their keys, and the proofs of `targets` in it.
+    fn trie<K: AsRef<[u8]>>(leaves: &[(K, Vec<u8>)], targets: &[K]) -> (B256, Vec<Vec<Bytes>>) {
+        let leaves = leaves
+            .iter()
+            .map(|(key, value)| (keccak256(key), value.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let targets = targets
+            .iter()
+            .map(|target| Nibbles::unpack(keccak256(target)))
+            .collect::<Vec<_>>();
+
+        let retainer = ProofRetainer::new(targets.clone());
+        let mut builder = HashBuilder::default().with_proof_retainer(retainer);
+        for (key, value) in &leaves {
+            builder.add_leaf(Nibbles::unpack(key), value);
+        }
+
+        let root = builder.root();
+        let nodes = builder.take_proof_nodes();
+        let proofs = targets
+            .iter()
+            .map(|target| {
+                nodes
+                    .matching_nodes_sorted(target)
+                    .into_iter()
+                    .map(|(_, node)| node)
+                    .collect()
+            })
+            .collect();
+        (root, proofs)
+    }
+
+    fn slot(index: u8) -> B256 {
+        B256::with_last_byte(index)
+    }
+
+    /// A storage trie holding `index * 10` in each of `slots`, and the proofs of `targets`.
+    fn storage(slots: impl IntoIterator<Item = u8>, targets: &[u8]) -> (B256, Vec<Vec<Bytes>>) {
+        let leaves = slots
+            .into_iter()
+            .map(|index| (slot(index), rlp::encode(U256::from(index) * U256::from(10))))
+            .collect::<Vec<_>>();
+        let targets = targets.iter().map(|index| slot(*index)).collect::<Vec<_>>();
+        trie(&leaves, &targets)
+    }
+
+    fn storage_claim(
+        storage_hash: B256,
+        key: B256,
+        value: u64,
+        proof: Vec<Bytes>,
+    ) -> EIP1186AccountProofResponse {
+        EIP1186AccountProofResponse {
+            address: Address::repeat_byte(0x11),
+            balance: U256::ZERO,
+            code_hash: KECCAK_EMPTY,
+            nonce: 0,
+            storage_hash,
+            account_proof: vec![],
+            storage_proof: vec![EIP1186StorageProof {
+                key: key.into(),
+                value: U256::from(value),
+                proof,
+            }],
+        }
+    }
+
+    fn account_claim(
+        address: Address,
+        account: TrieAccount,
+        proof: Vec<Bytes>,
+    ) -> EIP1186AccountProofResponse {
+        EIP1186AccountProofResponse {
+            address,
+            balance: account.balance,
+            code_hash: account.code_hash,
+            nonce: account.nonce,
+            storage_hash: account.storage_root,
+            account_proof: proof,
+            storage_proof: vec![],
+        }
+    }
+
+    #[test]
+    fn test_storage_inclusion() {
+        let (root, proofs) = storage(1..=3, &[2]);
+        let proof = &proofs[0];
+
+        let values =
+            verify_storage_proof(&storage_claim(root, slot(2), 20, proof.clone())).unwrap();
+        assert_eq!(values[&slot(2)], U256::from(20));
+        assert!(verify_storage_proof(&storage_claim(root, slot(2), 21, proof.clone())).is_err());
+        // the slot is in the trie, so it is not zero
+        assert!(verify_storage_proof(&storage_claim(root, slot(2), 0, proof.clone())).is_err());
+    }
+
+    #[test]
+    fn test_cleared_slot_excluded() {
+        // slot 2 was written and then set to zero, which deletes it from the trie
+        let (root, proofs) = storage([1, 3], &[2]);
+        let proof = &proofs[0];
+
+        let values = verify_storage_proof(&storage_claim(root, slot(2), 0, proof.clone())).unwrap();
+        assert_eq!(values[&slot(2)], U256::ZERO);
+        assert!(verify_storage_proof(&storage_claim(root, slot(2), 20, proof.clone())).is_err());
+    }
+
+    #[test]
+    fn test_empty_storage() {
+        for storage_hash in [EMPTY_ROOT_HASH, B256::ZERO] {
+            verify_storage_proof(&storage_claim(storage_hash, slot(1), 0, vec![])).unwrap();
+            assert!(
+                verify_storage_proof(&storage_claim(storage_hash, slot(1), 10, vec![])).is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_forged_exclusion_rejected() {
+        let (root, proofs) = storage(1..=40, &[2, 99]);
+        let (included, excluded) = (&proofs[0], &proofs[1]);
+        verify_storage_proof(&storage_claim(root, slot(99), 0, excluded.clone())).unwrap();
+
+        // slot 2 holds 20, which none of these may pass off as zero
+        for proof in [included.clone(), included[..1].to_vec(), excluded.clone()] {
+            assert!(verify_storage_proof(&storage_claim(root, slot(2), 0, proof)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_empty_accounts() {
+        let (present, empty, absent) = (
+            Address::repeat_byte(1),
+            Address::repeat_byte(2),
+            Address::repeat_byte(3),
+        );
+        let account = TrieAccount {
+            nonce: 1,
+            ..Default::default()
+        };
+        let leaves = [
+            (present, rlp::encode(&account)),
+            (empty, rlp::encode(TrieAccount::default())),
+        ];
+        let (root, proofs) = trie(&leaves, &[present, empty, absent]);
+
+        verify_account_proof(&account_claim(present, account, proofs[0].clone()), root).unwrap();
+        let claim = account_claim(present, TrieAccount::default(), proofs[0].clone());
+        assert!(verify_account_proof(&claim, root).is_err());
+
+        // an empty account left in the trie is proven by its inclusion
+        let claim = account_claim(empty, TrieAccount::default(), proofs[1].clone());
+        verify_account_proof(&claim, root).unwrap();
+
+        let zero_hashes = TrieAccount {
+            storage_root: B256::ZERO,
+            code_hash: B256::ZERO,
+            ..Default::default()
+        };
+        for claimed in [TrieAccount::default(), zero_hashes] {
+            let claim = account_claim(absent, claimed, proofs[2].clone());
+            verify_account_proof(&claim, root).unwrap();
+        }
+        let claim = account_claim(absent, account, proofs[2].clone());
+        assert!(verify_account_proof(&claim, root).is_err());
+    }
+}
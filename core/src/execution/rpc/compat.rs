@@ -0,0 +1,401 @@
+//! Normalization of provider responses before they are deserialized.
+//!
+//! Execution clients disagree on the exact shape of transactions and receipts: some omit
+//! optional fields, some send `null` where others leave the key out, and signatures come
+//! with `v`, `yParity` or both. Responses are first rewritten into one canonical shape, and
+//! then checked for every field that takes part in hash or root verification, so that a
+//! missing field is reported instead of being silently defaulted by the deserializer.
+//!
+//! Most receipts already arrive in the canonical shape. [`is_canonical_receipt`] checks that
+//! on the raw response without building a [`Value`], so those are deserialized directly.
+
+use serde::{Deserialize, Deserializer};
+use serde_json::value::RawValue;
+use serde_json::{Map, Value};
+
+/// Optional transaction fields that some providers send as `null` rather than omitting.
+const NULLABLE_TRANSACTION_FIELDS: &[&str] = &[
+    "accessList",
+    "chainId",
+    "gasPrice",
+    "maxFeePerGas",
+    "maxPriorityFeePerGas",
+    "maxFeePerBlobGas",
+    "blobVersionedHashes",
+    "authorizationList",
+    "yParity",
+    "v",
+];
+
+/// Optional receipt fields that some providers send as `null` rather than omitting.
+const NULLABLE_RECEIPT_FIELDS: &[&str] = &["blobGasUsed", "blobGasPrice", "root", "status"];
+
+/// The provider response types that are normalized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseKind {
+    Transaction,
+    Receipt,
+}
+
+impl ResponseKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ResponseKind::Transaction => "transaction",
+            ResponseKind::Receipt => "receipt",
+        }
+    }
+
+    /// Rewrites known aliases and `null` optional fields into the canonical shape.
+    pub fn normalize(self, value: &mut Value) {
+        match self {
+            ResponseKind::Transaction => normalize_transaction(value),
+            ResponseKind::Receipt => normalize_receipt(value),
+        }
+    }
+
+    /// Whether a raw response needs neither normalizing nor reporting missing fields, so it
+    /// can skip the [`Value`] round trip. Transactions are always normalized.
+    pub fn is_canonical(self, raw: &str) -> bool {
+        match self {
+            ResponseKind::Transaction => false,
+            ResponseKind::Receipt => is_canonical_receipt(raw),
+        }
+    }
+
+    /// Lists the fields needed for verification that are absent from a normalized value.
+    pub fn missing_fields(self, value: &Value) -> Vec<String> {
+        match self {
+            ResponseKind::Transaction => missing_transaction_fields(value),
+            ResponseKind::Receipt => missing_receipt_fields(value),
+        }
+    }
+}
+
+pub fn normalize_transaction(tx: &mut Value) {
+    let Some(tx) = tx.as_object_mut() else {
+        return;
+    };
+
+    remove_nulls(tx, NULLABLE_TRANSACTION_FIELDS);
+    rename(tx, "data", "input");
+    rename(tx, "gasLimit", "gas");
+    tx.entry("type").or_insert_with(|| "0x0".into());
+
+    let tx_type = quantity(tx.get("type"));
+    if tx_type == Some(0) {
+        // legacy signatures only carry `v`, derive it back from the parity if needed
+        if !tx.contains_key("v") {
+            if let Some(parity) = quantity(tx.get("yParity")) {
+                let v = match quantity(tx.get("chainId")) {
+                    Some(chain_id) => parity + 35 + 2 * chain_id,
+                    None => parity + 27,
+                };
+                tx.insert("v".into(), hex(v));
+            }
+        }
+    } else if is_signed_type(tx_type) {
+        match (tx.contains_key("yParity"), quantity(tx.get("v"))) {
+            (false, Some(v)) => {
+                let parity = if v >= 27 { (v - 27) % 2 } else { v };
+                tx.insert("yParity".into(), hex(parity));
+            }
+            (true, None) => {
+                let parity = tx["yParity"].clone();
+                tx.insert("v".into(), parity);
+            }
+            _ => {}
+        }
+    }
+}
+
+pub fn normalize_receipt(receipt: &mut Value) {
+    let Some(receipt) = receipt.as_object_mut() else {
+        return;
+    };
+
+    remove_nulls(receipt, NULLABLE_RECEIPT_FIELDS);
+    receipt.entry("type").or_insert_with(|| "0x0".into());
+
+    if let Some(Value::Array(logs)) = receipt.get_mut("logs") {
+        for log in logs.iter_mut().filter_map(Value::as_object_mut) {
+            log.entry("removed").or_insert(Value::Bool(false));
+        }
+    }
+}
+
+pub fn missing_transaction_fields(tx: &Value) -> Vec<String> {
+    let mut required = vec!["hash", "type", "nonce", "gas", "value", "input"];
+
+    let tx_type = quantity(tx.get("type"));
+    if is_signed_type(tx_type) {
+        required.extend(["r", "s"]);
+    }
+
+    match tx_type {
+        Some(0) => required.extend(["gasPrice", "v"]),
+        Some(1) => required.extend(["chainId", "gasPrice", "accessList", "yParity"]),
+        Some(2) => required.extend([
+            "chainId",
+            "maxFeePerGas",
+            "maxPriorityFeePerGas",
+            "accessList",
+            "yParity",
+        ]),
+        Some(3) => required.extend([
+            "chainId",
+            "to",
+            "maxFeePerGas",
+            "maxPriorityFeePerGas",
+            "maxFeePerBlobGas",
+            "blobVersionedHashes",
+            "accessList",
+            "yParity",
+        ]),
+        Some(4) => required.extend([
+            "chainId",
+            "to",
+            "maxFeePerGas",
+            "maxPriorityFeePerGas",
+            "accessList",
+            "authorizationList",
+            "yParity",
+        ]),
+        _ => {}
+    }
+
+    missing(tx, &required)
+}
+
+pub fn missing_receipt_fields(receipt: &Value) -> Vec<String> {
+    let mut missing_fields = missing(
+        receipt,
+        &[
+            "type",
+            "transactionHash",
+            "blockHash",
+            "blockNumber",
+            "cumulativeGasUsed",
+            "logsBloom",
+            "logs",
+        ],
+    );
+
+    if receipt.get("status").is_none() && receipt.get("root").is_none() {
+        missing_fields.push("status".to_string());
+    }
+
+    if let Some(Value::Array(logs)) = receipt.get("logs") {
+        for (i, log) in logs.iter().enumerate() {
+            for field in missing(log, &["address", "topics", "data"]) {
+                missing_fields.push(format!("logs[{i}].{field}"));
+            }
+        }
+    }
+
+    missing_fields
+}
+
+/// The fields of a raw receipt that normalization touches or verification needs, borrowed
+/// from the response. A field that is present holds its raw json, `null` included.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptShape<'a> {
+    #[serde(borrow, default, rename = "type", deserialize_with = "present")]
+    tx_type: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    transaction_hash: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    block_hash: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    block_number: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    cumulative_gas_used: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    logs_bloom: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    status: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    root: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    blob_gas_used: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    blob_gas_price: Option<&'a RawValue>,
+    #[serde(borrow, default)]
+    logs: Option<Vec<LogShape<'a>>>,
+}
+
+#[derive(Deserialize)]
+struct LogShape<'a> {
+    #[serde(borrow, default, deserialize_with = "present")]
+    address: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    topics: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    data: Option<&'a RawValue>,
+    #[serde(borrow, default, deserialize_with = "present")]
+    removed: Option<&'a RawValue>,
+}
+
+/// Whether a raw receipt is already in the shape [`normalize_receipt`] produces and has every
+/// field [`missing_receipt_fields`] asks for. Anything unexpected, malformed json included,
+/// is left to the normalizing path to handle or report.
+pub fn is_canonical_receipt(raw: &str) -> bool {
+    let Ok(receipt) = serde_json::from_str::<ReceiptShape>(raw) else {
+        return false;
+    };
+
+    let required = [
+        receipt.tx_type,
+        receipt.transaction_hash,
+        receipt.block_hash,
+        receipt.block_number,
+        receipt.cumulative_gas_used,
+        receipt.logs_bloom,
+    ];
+    let nullable = [
+        receipt.status,
+        receipt.root,
+        receipt.blob_gas_used,
+        receipt.blob_gas_price,
+    ];
+    let Some(logs) = receipt.logs else {
+        return false;
+    };
+
+    required.into_iter().all(is_set)
+        && !nullable.into_iter().any(is_null)
+        && (is_set(receipt.status) || is_set(receipt.root))
+        && logs.iter().all(|log| {
+            [log.address, log.topics, log.data, log.removed]
+                .into_iter()
+                .all(is_set)
+        })
+}
+
+fn present<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<&'de RawValue>, D::Error> {
+    <&RawValue>::deserialize(deserializer).map(Some)
+}
+
+fn is_set(field: Option<&RawValue>) -> bool {
+    field.is_some_and(|raw| raw.get() != "null")
+}
+
+fn is_null(field: Option<&RawValue>) -> bool {
+    field.is_some_and(|raw| raw.get() == "null")
+}
+
+/// Signed transaction types. Unknown types, such as OP Stack deposits, carry no signature.
+fn is_signed_type(tx_type: Option<u64>) -> bool {
+    matches!(tx_type, Some(0..=4))
+}
+
+fn missing(value: &Value, fields: &[&str]) -> Vec<String> {
+    fields
+        .iter()
+        .filter(|field| value.get(**field).map_or(true, Value::is_null))
+        .map(|field| field.to_string())
+        .collect()
+}
+
+fn remove_nulls(object: &mut Map<String, Value>, fields: &[&str]) {
+    for field in fields {
+        if object.get(*field).is_some_and(Value::is_null) {
+            object.remove(*field);
+        }
+    }
+}
+
+fn rename(object: &mut Map<String, Value>, from: &str, to: &str) {
+    if let Some(value) = object.remove(from) {
+        object.entry(to).or_insert(value);
+    }
+}
+
+fn quantity(value: Option<&Value>) -> Option<u64> {
+    let value = value?.as_str()?;
+    let digits = value.strip_prefix("0x").unwrap_or(value);
+    u64::from_str_radix(digits, 16).ok()
+}
+
+fn hex(value: u64) -> Value {
+    Value::String(format!("0x{value:x}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_typed_parity_derived_from_v() {
+        let mut tx = json!({ "type": "0x2", "v": "0x1", "accessList": null });
+        normalize_transaction(&mut tx);
+
+        assert_eq!(tx["yParity"], "0x1");
+        assert!(tx.get("accessList").is_none());
+    }
+
+    #[test]
+    fn test_legacy_v_derived_from_parity() {
+        let mut tx = json!({ "type": "0x0", "yParity": "0x1", "chainId": "0x1" });
+        normalize_transaction(&mut tx);
+
+        assert_eq!(tx["v"], "0x26");
+    }
+
+    #[test]
+    fn test_missing_receipt_fields_listed() {
+        let receipt = json!({
+            "type": "0x2",
+            "transactionHash": "0x00",
+            "blockHash": "0x00",
+            "blockNumber": "0x1",
+            "cumulativeGasUsed": "0x1",
+            "logsBloom": null,
+            "logs": [{ "address": "0x00", "topics": [] }],
+        });
+
+        assert_eq!(
+            missing_receipt_fields(&receipt),
+            ["logsBloom", "status", "logs[0].data"]
+        );
+    }
+
+    #[test]
+    fn test_canonical_receipt_detected() {
+        let receipt = json!({
+            "type": "0x2",
+            "transactionHash": "0x00",
+            "blockHash": "0x00",
+            "blockNumber": "0x1",
+            "cumulativeGasUsed": "0x1",
+            "logsBloom": "0x00",
+            "status": "0x1",
+            "logs": [{ "address": "0x00", "topics": [], "data": "0x", "removed": false }],
+        });
+        assert!(is_canonical_receipt(&receipt.to_string()));
+
+        // each of these goes through normalization instead
+        for (field, value) in [
+            ("type", Value::Null),
+            ("blobGasUsed", Value::Null),
+            ("status", Value::Null),
+            (
+                "logs",
+                json!([{ "address": "0x00", "topics": [], "data": "0x" }]),
+            ),
+        ] {
+            let mut receipt = receipt.clone();
+            receipt[field] = value;
+            assert!(!is_canonical_receipt(&receipt.to_string()), "{field}");
+        }
+
+        let mut pre_byzantium = receipt.clone();
+        pre_byzantium.as_object_mut().unwrap().remove("status");
+        pre_byzantium["root"] = json!("0x00");
+        assert!(is_canonical_receipt(&pre_byzantium.to_string()));
+
+        assert!(!is_canonical_receipt("[]"));
+    }
+}
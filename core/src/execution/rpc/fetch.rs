@@ -0,0 +1,279 @@
+//! A fetch based transport for the execution provider on wasm.
+//!
+//! Large responses such as `eth_getLogs` over a wide range or `eth_getBlockReceipts` of a busy
+//! block used to be buffered whole on the JS side and then copied into wasm memory, peaking at
+//! several times the payload size and running browser tabs out of memory. Here the body is
+//! read from its `ReadableStream` chunk by chunk into a single buffer, sized from the
+//! `Content-Length` when the provider sends one, and the request is aborted as soon as the
+//! body grows past the response size limit.
+//!
+//! The tests run in a headless browser with `wasm-pack test --headless --chrome core`.
+
+use std::fmt;
+use std::task::{Context, Poll};
+
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket};
+use alloy::transports::utils::guess_local_url;
+use alloy::transports::{TransportError, TransportErrorKind, TransportFut};
+use js_sys::{Promise, Reflect, Uint8Array};
+use tower::Service;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    AbortController, Headers, ReadableStreamDefaultReader, Request, RequestInit, Response,
+};
+
+use crate::execution::errors::ExecutionError;
+
+#[wasm_bindgen]
+extern "C" {
+    // the global `fetch`, so the transport works in windows and workers alike
+    #[wasm_bindgen(js_name = fetch)]
+    fn fetch_with_request(input: &Request) -> Promise;
+}
+
+/// A JSON-RPC transport posting requests with `fetch` and streaming the responses.
+#[derive(Clone)]
+pub struct FetchTransport {
+    url: String,
+    max_response_size: usize,
+    headers: Vec<(String, String)>,
+}
+
+impl FetchTransport {
+    pub fn new(url: &str, max_response_size: usize) -> Self {
+        Self {
+            url: url.to_string(),
+            max_response_size,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Sends `headers` along with the content type of every request.
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn guess_local(&self) -> bool {
+        guess_local_url(&self.url)
+    }
+
+    async fn send(self, req: RequestPacket) -> Result<ResponsePacket, TransportError> {
+        let body = serde_json::to_string(&req).map_err(TransportError::ser_err)?;
+        let (status, body) = fetch_bounded(
+            &self.url,
+            Some(&body),
+            &self.headers,
+            self.max_response_size,
+        )
+        .await
+        .map_err(TransportErrorKind::custom)?;
+
+        if !(200..300).contains(&status) {
+            let body = String::from_utf8_lossy(&body).into_owned();
+            return Err(TransportErrorKind::http_error(status, body));
+        }
+
+        serde_json::from_slice(&body)
+            .map_err(|err| TransportError::deser_err(err, String::from_utf8_lossy(&body)))
+    }
+}
+
+// header values may be credentials
+impl fmt::Debug for FetchTransport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers = self
+            .headers
+            .iter()
+            .map(|(name, _)| name)
+            .collect::<Vec<_>>();
+        f.debug_struct("FetchTransport")
+            .field("url", &self.url)
+            .field("max_response_size", &self.max_response_size)
+            .field("headers", &headers)
+            .finish()
+    }
+}
+
+impl Service<RequestPacket> for FetchTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().send(req))
+    }
+}
+
+/// Errors fetching a response body.
+#[derive(Debug, thiserror::Error)]
+pub enum FetchError {
+    #[error("fetch failed: {0}")]
+    Js(String),
+    #[error(transparent)]
+    TooLarge(#[from] ExecutionError),
+}
+
+impl From<JsValue> for FetchError {
+    fn from(value: JsValue) -> Self {
+        FetchError::Js(format!("{value:?}"))
+    }
+}
+
+/// Fetches `url` with `headers`, posting `body` as JSON if given, and returns the status and
+/// the response body. Fails without reading further once the body exceeds `limit` bytes.
+///
+/// Without a body or headers the request stays simple enough to skip the CORS preflight.
+pub async fn fetch_bounded(
+    url: &str,
+    body: Option<&str>,
+    headers: &[(String, String)],
+    limit: usize,
+) -> Result<(u16, Vec<u8>), FetchError> {
+    let controller = AbortController::new()?;
+    let init = RequestInit::new();
+    init.set_signal(Some(&controller.signal()));
+    if body.is_some() || !headers.is_empty() {
+        let request_headers = Headers::new()?;
+        for (name, value) in headers {
+            request_headers.set(name, value)?;
+        }
+        if let Some(body) = body {
+            request_headers.set("content-type", "application/json")?;
+            init.set_method("POST");
+            init.set_body(&JsValue::from_str(body));
+        }
+        init.set_headers(&request_headers);
+    }
+
+    let request = Request::new_with_str_and_init(url, &init)?;
+    let response: Response = JsFuture::from(fetch_with_request(&request))
+        .await?
+        .dyn_into()?;
+    let status = response.status();
+    let body = read_body(&response, &controller, limit).await?;
+
+    Ok((status, body))
+}
+
+/// Reads the body of `response` into one buffer, aborting through `controller` once it
+/// exceeds `limit` bytes.
+async fn read_body(
+    response: &Response,
+    controller: &AbortController,
+    limit: usize,
+) -> Result<Vec<u8>, FetchError> {
+    let too_large = |size: usize| {
+        controller.abort();
+        ExecutionError::ProviderPayloadTooLarge("response size", size, limit)
+    };
+
+    let content_length = response
+        .headers()
+        .get("content-length")?
+        .and_then(|length| length.parse::<usize>().ok());
+    if let Some(length) = content_length.filter(|length| *length > limit) {
+        return Err(too_large(length).into());
+    }
+
+    let Some(stream) = response.body() else {
+        return Ok(Vec::new());
+    };
+    let reader: ReadableStreamDefaultReader = stream.get_reader().unchecked_into();
+    let mut body = Vec::with_capacity(content_length.unwrap_or_default());
+
+    loop {
+        let chunk = JsFuture::from(reader.read()).await?;
+        if Reflect::get(&chunk, &"done".into())?.is_truthy() {
+            break;
+        }
+
+        let chunk: Uint8Array = Reflect::get(&chunk, &"value".into())?.unchecked_into();
+        let start = body.len();
+        let size = start + chunk.length() as usize;
+        if size > limit {
+            let _ = reader.cancel();
+            return Err(too_large(size).into());
+        }
+
+        body.resize(size, 0);
+        chunk.copy_to(&mut body[start..]);
+    }
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use js_sys::{Array, WebAssembly};
+    use wasm_bindgen_test::*;
+    use web_sys::{Blob, Url};
+
+    use super::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    const MB: usize = 1024 * 1024;
+
+    /// A JSON-RPC `eth_getLogs` response of about `size` bytes, built as a blob so the
+    /// fixture itself is kept outside of wasm memory.
+    fn logs_response(size: usize) -> Blob {
+        let log = r#"{"address":"0xa1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1a1","topics":["0xdddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddddd"],"data":"0x","blockNumber":"0x64","logIndex":"0x0","removed":false},"#;
+        let parts = Array::new();
+        parts.push(&r#"{"jsonrpc":"2.0","id":1,"result":["#.into());
+        for _ in 0..size / log.len() {
+            parts.push(&log.into());
+        }
+        parts.push(&r#"{"removed":false}]}"#.into());
+
+        Blob::new_with_str_sequence(&parts).unwrap()
+    }
+
+    fn wasm_memory() -> usize {
+        let memory: WebAssembly::Memory = wasm_bindgen::memory().unchecked_into();
+        let buffer: js_sys::ArrayBuffer = memory.buffer().unchecked_into();
+        buffer.byte_length() as usize
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_large_response_read_within_memory_budget() {
+        let fixture = logs_response(64 * MB);
+        let url = Url::create_object_url_with_blob(&fixture).unwrap();
+
+        // wasm memory never shrinks, so its growth is the peak reached while reading
+        let before = wasm_memory();
+        let (status, body) = fetch_bounded(&url, None, &[], 128 * MB).await.unwrap();
+        let grown = wasm_memory() - before;
+
+        assert_eq!(status, 200);
+        assert_eq!(body.len(), fixture.size() as usize);
+        assert!(body.starts_with(br#"{"jsonrpc":"2.0""#) && body.ends_with(b"]}"));
+        assert!(grown < body.len() + 8 * MB, "grew by {grown} bytes");
+    }
+
+    #[wasm_bindgen_test]
+    async fn test_oversized_response_aborted() {
+        let fixture = logs_response(4 * MB);
+        let url = Url::create_object_url_with_blob(&fixture).unwrap();
+
+        // rejected from the content length without reading the body
+        let err = fetch_bounded(&url, None, &[], MB).await.unwrap_err();
+        assert!(matches!(
+            err,
+            FetchError::TooLarge(ExecutionError::ProviderPayloadTooLarge("response size", ..))
+        ));
+
+        // and while streaming when there is none
+        let response = Response::new_with_opt_blob(Some(&fixture)).unwrap();
+        let controller = AbortController::new().unwrap();
+        let err = read_body(&response, &controller, MB).await.unwrap_err();
+        assert!(matches!(err, FetchError::TooLarge(_)));
+        assert!(controller.signal().aborted());
+    }
+}
@@ -0,0 +1,186 @@
+//! Extra http headers sent with every execution provider request, for providers behind a
+//! gateway that authenticates or routes by header.
+//!
+//! On wasm any header other than the few the browser considers simple makes requests to
+//! another origin preflighted, which the provider then has to allow, so nothing is added
+//! unless configured.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+const REDACTED: &str = "<redacted>";
+
+/// Headers of provider requests, from the `execution_headers` config table.
+///
+/// Values are often credentials, so `Debug` prints the header names only.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeaderConfig {
+    /// Sent as given, by name.
+    pub headers: BTreeMap<String, String>,
+    /// Sent as `Authorization: Bearer <token>`, such as a JWT issued for an Engine API style
+    /// endpoint.
+    pub bearer_token: Option<String>,
+    /// Sent as `Authorization: Basic`.
+    pub basic_auth: Option<BasicAuth>,
+}
+
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BasicAuth {
+    pub username: String,
+    #[serde(default)]
+    pub password: Option<String>,
+}
+
+impl HeaderConfig {
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty() && self.bearer_token.is_none() && self.basic_auth.is_none()
+    }
+
+    /// The headers to send as name and value pairs. An `Authorization` header among
+    /// `headers` is replaced by the bearer token or basic credentials, of which only one may
+    /// be set.
+    pub fn resolve(&self) -> Result<Vec<(String, String)>> {
+        let authorization = match (&self.bearer_token, &self.basic_auth) {
+            (Some(_), Some(_)) => {
+                return Err(eyre!("bearer_token and basic_auth cannot be set together"))
+            }
+            (Some(token), None) => Some(format!("Bearer {token}")),
+            (None, Some(auth)) => {
+                let credentials = format!(
+                    "{}:{}",
+                    auth.username,
+                    auth.password.as_deref().unwrap_or_default()
+                );
+                Some(format!("Basic {}", STANDARD.encode(credentials)))
+            }
+            (None, None) => None,
+        };
+
+        let mut headers = self
+            .headers
+            .iter()
+            .filter(|(name, _)| {
+                authorization.is_none() || !name.eq_ignore_ascii_case("authorization")
+            })
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect::<Vec<_>>();
+        if let Some(authorization) = authorization {
+            headers.push(("Authorization".to_string(), authorization));
+        }
+
+        Ok(headers)
+    }
+
+    /// The headers as reqwest sends them, with every value marked sensitive so reqwest keeps
+    /// it out of its own logs.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn header_map(&self) -> Result<reqwest::header::HeaderMap> {
+        use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+
+        let mut map = HeaderMap::new();
+        for (name, value) in self.resolve()? {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| eyre!("invalid execution rpc header name {name}"))?;
+            let mut value = HeaderValue::from_str(&value)
+                .map_err(|_| eyre!("invalid value of execution rpc header {name}"))?;
+            value.set_sensitive(true);
+            map.insert(name, value);
+        }
+
+        Ok(map)
+    }
+}
+
+impl fmt::Debug for HeaderConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let headers = self
+            .headers
+            .keys()
+            .map(|name| (name, REDACTED))
+            .collect::<BTreeMap<_, _>>();
+        f.debug_struct("HeaderConfig")
+            .field("headers", &headers)
+            .field(
+                "bearer_token",
+                &self.bearer_token.as_ref().map(|_| REDACTED),
+            )
+            .field("basic_auth", &self.basic_auth)
+            .finish()
+    }
+}
+
+impl fmt::Debug for BasicAuth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BasicAuth")
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| REDACTED))
+            .finish()
+    }
+}
+
+/// Parses a header given as `NAME=VALUE`, as on the command line.
+pub fn parse_header(header: &str) -> Result<(String, String), String> {
+    match header.split_once('=') {
+        Some((name, value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err("expected NAME=VALUE".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic_auth_replaces_authorization_header() {
+        let config = HeaderConfig {
+            headers: BTreeMap::from([
+                ("authorization".to_string(), "Bearer stale".to_string()),
+                ("X-Org-Id".to_string(), "42".to_string()),
+            ]),
+            basic_auth: Some(BasicAuth {
+                username: "helios".to_string(),
+                password: Some("hunter2".to_string()),
+            }),
+            ..Default::default()
+        };
+
+        let headers = config.resolve().unwrap();
+        assert_eq!(
+            headers,
+            [
+                ("X-Org-Id".to_string(), "42".to_string()),
+                (
+                    "Authorization".to_string(),
+                    "Basic aGVsaW9zOmh1bnRlcjI=".to_string()
+                ),
+            ]
+        );
+
+        let both = HeaderConfig {
+            bearer_token: Some("token".to_string()),
+            ..config
+        };
+        assert!(both.resolve().is_err());
+    }
+
+    #[test]
+    fn test_debug_redacts_values() {
+        let config = HeaderConfig {
+            headers: BTreeMap::from([("X-Api-Key".to_string(), "secret-key".to_string())]),
+            bearer_token: Some("secret-token".to_string()),
+            basic_auth: None,
+        };
+
+        let debug = format!("{config:?}");
+        assert!(debug.contains("X-Api-Key"), "{debug}");
+        assert!(!debug.contains("secret"), "{debug}");
+    }
+}
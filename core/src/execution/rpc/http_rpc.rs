@@ -1,31 +1,203 @@
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+use std::sync::Arc;
+
 use alloy::eips::BlockNumberOrTag;
-use alloy::primitives::{Address, B256, U256};
+use alloy::network::{primitives::HeaderResponse, BlockResponse};
+use alloy::primitives::{Address, Bytes, B256, U256};
 use alloy::providers::{Provider, ProviderBuilder, RootProvider};
-use alloy::rpc::client::ClientBuilder;
+use alloy::rpc::client::{BatchRequest, ClientBuilder};
 use alloy::rpc::types::{
-    BlockId, EIP1186AccountProofResponse, FeeHistory, Filter, FilterChanges, Log,
+    BlockId, BlockTransactions, EIP1186AccountProofResponse, FeeHistory, Filter, FilterChanges, Log,
 };
+#[cfg(not(target_arch = "wasm32"))]
 use alloy::transports::http::Http;
-use alloy::transports::layers::{RetryBackoffLayer, RetryBackoffService};
+use alloy::transports::{BoxTransport, Transport};
 use async_trait::async_trait;
-use eyre::{eyre, Result};
-use reqwest::Client;
+use eyre::Result;
 use revm::primitives::AccessList;
+use serde::de::DeserializeOwned;
+use serde_json::value::RawValue;
+use serde_json::Value;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::client::compression::http_client_builder;
 use crate::errors::RpcError;
+use crate::execution::errors::ExecutionError;
+use crate::execution::usage::UsageMeter;
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+use crate::metrics::{Metrics, MetricsLayer};
 use crate::network_spec::NetworkSpec;
 use crate::types::BlockTag;
 
+use super::compat::ResponseKind;
+#[cfg(target_arch = "wasm32")]
+use super::fetch::FetchTransport;
+use super::headers::HeaderConfig;
+use super::rate_limit::{RateLimitConfig, RateLimitLayer};
+use super::retry::{RetryConfig, RetryLayer};
+use super::timeout::{HttpClientConfig, TimeoutLayer};
 use super::ExecutionRpc;
 
 pub struct HttpRpc<N: NetworkSpec> {
     url: String,
-    provider: RootProvider<RetryBackoffService<Http<Client>>, N>,
+    provider: RootProvider<BoxTransport, N>,
+    usage: UsageMeter,
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    metrics: Arc<Metrics>,
 }
 
+impl<N: NetworkSpec> HttpRpc<N> {
+    /// Creates an rpc from an externally constructed provider, allowing connection pools and
+    /// transport layers to be shared between several clients. The url is only kept for
+    /// identification, all requests go through the given provider.
+    pub fn from_provider<T: Transport + Clone>(url: &str, provider: RootProvider<T, N>) -> Self {
+        HttpRpc {
+            url: url.to_string(),
+            provider: provider.boxed(),
+            usage: UsageMeter::default(),
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            metrics: Arc::default(),
+        }
+    }
+
+    /// Connects to the provider at `rpc`, asking for compressed responses unless
+    /// `compression` is off, sending `headers` with every request, timing requests out and
+    /// pooling connections per `http`, holding requests to the budget of `rate_limit` and
+    /// retrying rate limited or timed out requests per `retry`. With the `metrics` feature
+    /// the requests are recorded into a registry of this rpc and its clones.
+    ///
+    /// On wasm responses are streamed with `fetch` instead, within the response size limit,
+    /// and the browser negotiates compression on its own.
+    pub fn connect(
+        rpc: &str,
+        compression: bool,
+        retry: RetryConfig,
+        rate_limit: &RateLimitConfig,
+        http: HttpClientConfig,
+        headers: &HeaderConfig,
+    ) -> Result<Self> {
+        #[cfg(not(target_arch = "wasm32"))]
+        let transport = {
+            let client = http_client_builder(compression)
+                .default_headers(headers.header_map()?)
+                .timeout(http.request_timeout.as_duration())
+                .connect_timeout(http.connect_timeout.as_duration())
+                .pool_max_idle_per_host(http.max_idle_connections)
+                .build()?;
+            Http::with_client(client, rpc.parse()?)
+        };
+        #[cfg(target_arch = "wasm32")]
+        let transport = {
+            let _ = compression;
+            FetchTransport::new(rpc, http.max_response_size.as_usize())
+                .with_headers(headers.resolve()?)
+        };
+        let is_local = transport.guess_local();
+        let retry = RetryLayer::new(retry);
+        let rate_limit = RateLimitLayer::new(rate_limit.clone());
+        let builder = ClientBuilder::default();
+
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        let metrics = Arc::new(Metrics::new());
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        let (builder, retry, rate_limit) = (
+            builder.layer(MetricsLayer::new(metrics.clone())),
+            retry.with_metrics(metrics.clone()),
+            rate_limit.with_metrics(metrics.clone()),
+        );
+
+        let client = builder
+            .layer(retry)
+            .layer(rate_limit)
+            .layer(TimeoutLayer::new(&http))
+            .transport(transport, is_local);
+
+        let provider = ProviderBuilder::new().network::<N>().on_client(client);
+        let rpc = HttpRpc::from_provider(rpc, provider);
+
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        let rpc = rpc.with_metrics(metrics);
+
+        Ok(rpc)
+    }
+
+    /// The url of the provider, as given.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Accounts requests with `usage` instead of a fresh meter. Clones made afterwards share
+    /// it.
+    pub fn with_usage(mut self, usage: UsageMeter) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Records verifications and sync progress into `metrics` instead of a fresh registry,
+    /// such as the one the layers of an externally constructed provider record into. Clones
+    /// made afterwards share it.
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Deserializes a raw provider response after normalizing it.
+    async fn decode<T: DeserializeOwned>(&self, kind: ResponseKind, mut value: Value) -> Result<T> {
+        self.normalize(kind, &mut value).await?;
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Deserializes a raw provider response, only building a [`Value`] to normalize it when
+    /// it is not in the canonical shape already.
+    async fn decode_raw<T: DeserializeOwned>(
+        &self,
+        kind: ResponseKind,
+        raw: &RawValue,
+    ) -> Result<T> {
+        if kind.is_canonical(raw.get()) {
+            return Ok(serde_json::from_str(raw.get())?);
+        }
+        self.decode(kind, serde_json::from_str(raw.get())?).await
+    }
+
+    /// Normalizes a raw provider response, failing with the list of missing fields rather
+    /// than letting them default.
+    async fn normalize(&self, kind: ResponseKind, value: &mut Value) -> Result<()> {
+        kind.normalize(value);
+
+        let missing = kind.missing_fields(value);
+        if !missing.is_empty() {
+            self.usage.record("web3_clientVersion");
+            let client = self
+                .provider
+                .get_client_version()
+                .await
+                .unwrap_or_else(|_| "unknown client".to_string());
+
+            return Err(ExecutionError::IncompleteProviderResponse {
+                kind: kind.name(),
+                missing,
+                client,
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Clones send their requests through the same provider, sharing its connection pool and
+/// retry layer.
 impl<N: NetworkSpec> Clone for HttpRpc<N> {
     fn clone(&self) -> Self {
-        Self::new(&self.url).unwrap()
+        Self {
+            url: self.url.clone(),
+            provider: self.provider.clone(),
+            usage: self.usage.clone(),
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            metrics: self.metrics.clone(),
+        }
     }
 }
 
@@ -33,16 +205,23 @@ impl<N: NetworkSpec> Clone for HttpRpc<N> {
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
     fn new(rpc: &str) -> Result<Self> {
-        let client = ClientBuilder::default()
-            .layer(RetryBackoffLayer::new(100, 50, 300))
-            .http(rpc.parse().unwrap());
+        HttpRpc::connect(
+            rpc,
+            true,
+            RetryConfig::default(),
+            &RateLimitConfig::default(),
+            HttpClientConfig::default(),
+            &HeaderConfig::default(),
+        )
+    }
 
-        let provider = ProviderBuilder::new().network::<N>().on_client(client);
+    fn usage(&self) -> Option<&UsageMeter> {
+        Some(&self.usage)
+    }
 
-        Ok(HttpRpc {
-            url: rpc.to_string(),
-            provider,
-        })
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    fn metrics(&self) -> Option<&Arc<Metrics>> {
+        Some(&self.metrics)
     }
 
     async fn get_proof(
@@ -51,6 +230,7 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
         slots: &[B256],
         block: BlockId,
     ) -> Result<EIP1186AccountProofResponse> {
+        self.usage.record("eth_getProof");
         let proof_response = self
             .provider
             .get_proof(address, slots.to_vec())
@@ -61,17 +241,52 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
         Ok(proof_response)
     }
 
+    /// Sends all proof requests as one json-rpc batch.
+    async fn get_proofs(
+        &self,
+        accounts: &[(Address, Vec<B256>)],
+        block: BlockId,
+    ) -> Result<Vec<Result<EIP1186AccountProofResponse>>> {
+        if accounts.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut batch = BatchRequest::new(self.provider.client());
+        let mut waiters = Vec::with_capacity(accounts.len());
+        for (address, slots) in accounts {
+            self.usage.record("eth_getProof");
+            let waiter = batch
+                .add_call::<_, EIP1186AccountProofResponse>(
+                    "eth_getProof",
+                    &(address, slots, block),
+                )
+                .map_err(|e| RpcError::new("get_proof", e))?;
+            waiters.push(waiter);
+        }
+        batch
+            .send()
+            .await
+            .map_err(|e| RpcError::new("get_proof", e))?;
+
+        let mut proofs = Vec::with_capacity(waiters.len());
+        for waiter in waiters {
+            proofs.push(
+                waiter
+                    .await
+                    .map_err(|e| RpcError::new("get_proof", e).into()),
+            );
+        }
+        Ok(proofs)
+    }
+
     async fn create_access_list(
         &self,
         tx: &N::TransactionRequest,
         block: BlockTag,
     ) -> Result<AccessList> {
-        let block = match block {
-            BlockTag::Latest => BlockId::latest(),
-            BlockTag::Finalized => BlockId::finalized(),
-            BlockTag::Number(num) => BlockId::number(num),
-        };
+        let block = BlockId::from(provider_tag(block, "create_access_list")?);
 
+        self.usage.record("eth_createAccessList");
         let list = self
             .provider
             .create_access_list(tx)
@@ -83,6 +298,7 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
     }
 
     async fn get_code(&self, address: Address, block: u64) -> Result<Vec<u8>> {
+        self.usage.record("eth_getCode");
         let code = self
             .provider
             .get_code_at(address)
@@ -93,7 +309,36 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
         Ok(code.to_vec())
     }
 
+    /// Sends all code requests as one json-rpc batch.
+    async fn get_codes(&self, addresses: &[Address], block: u64) -> Result<Vec<Result<Vec<u8>>>> {
+        if addresses.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut batch = BatchRequest::new(self.provider.client());
+        let mut waiters = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            self.usage.record("eth_getCode");
+            let waiter = batch
+                .add_call::<_, Bytes>("eth_getCode", &(address, BlockId::number(block)))
+                .map_err(|e| RpcError::new("get_code", e))?;
+            waiters.push(waiter);
+        }
+        batch
+            .send()
+            .await
+            .map_err(|e| RpcError::new("get_code", e))?;
+
+        let mut codes = Vec::with_capacity(waiters.len());
+        for waiter in waiters {
+            let code = waiter.await.map_err(|e| RpcError::new("get_code", e));
+            codes.push(code.map(|code| code.to_vec()).map_err(Into::into));
+        }
+        Ok(codes)
+    }
+
     async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<B256> {
+        self.usage.record("eth_sendRawTransaction");
         let tx = self
             .provider
             .send_raw_transaction(bytes)
@@ -104,41 +349,59 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
     }
 
     async fn get_transaction_receipt(&self, tx_hash: B256) -> Result<Option<N::ReceiptResponse>> {
+        self.usage.record("eth_getTransactionReceipt");
         let receipt = self
             .provider
-            .get_transaction_receipt(tx_hash)
+            .raw_request::<_, Option<Box<RawValue>>>("eth_getTransactionReceipt".into(), (tx_hash,))
             .await
             .map_err(|e| RpcError::new("get_transaction_receipt", e))?;
 
-        Ok(receipt)
+        match receipt {
+            Some(receipt) => Ok(Some(
+                self.decode_raw(ResponseKind::Receipt, &receipt).await?,
+            )),
+            None => Ok(None),
+        }
     }
 
-    async fn get_block_receipts(&self, block: BlockTag) -> Result<Option<Vec<N::ReceiptResponse>>> {
-        let block = match block {
-            BlockTag::Latest => BlockNumberOrTag::Latest,
-            BlockTag::Finalized => BlockNumberOrTag::Finalized,
-            BlockTag::Number(num) => BlockNumberOrTag::Number(num),
-        };
-
-        let block_id = BlockId::from(block);
+    async fn get_block_receipts(&self, block: BlockId) -> Result<Option<Vec<N::ReceiptResponse>>> {
+        self.usage.record("eth_getBlockReceipts");
         let receipts = self
             .provider
-            .get_block_receipts(block_id)
+            .raw_request::<_, Option<Box<RawValue>>>("eth_getBlockReceipts".into(), (block,))
             .await
             .map_err(|e| RpcError::new("get_block_receipts", e))?;
 
-        Ok(receipts)
+        let Some(receipts) = receipts else {
+            return Ok(None);
+        };
+
+        // the receipts borrow from the one response buffer until each is decoded
+        let receipts = serde_json::from_str::<Vec<&RawValue>>(receipts.get())?;
+        let mut decoded = Vec::with_capacity(receipts.len());
+        for receipt in receipts {
+            decoded.push(self.decode_raw(ResponseKind::Receipt, receipt).await?);
+        }
+
+        Ok(Some(decoded))
     }
 
     async fn get_transaction(&self, tx_hash: B256) -> Result<Option<N::TransactionResponse>> {
-        Ok(self
+        self.usage.record("eth_getTransactionByHash");
+        let tx = self
             .provider
-            .get_transaction_by_hash(tx_hash)
+            .raw_request::<_, Option<Value>>("eth_getTransactionByHash".into(), (tx_hash,))
             .await
-            .map_err(|e| RpcError::new("get_transaction", e))?)
+            .map_err(|e| RpcError::new("get_transaction", e))?;
+
+        match tx {
+            Some(tx) => Ok(Some(self.decode(ResponseKind::Transaction, tx).await?)),
+            None => Ok(None),
+        }
     }
 
     async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        self.usage.record("eth_getLogs");
         Ok(self
             .provider
             .get_logs(filter)
@@ -147,6 +410,7 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
     }
 
     async fn get_filter_changes(&self, filter_id: U256) -> Result<FilterChanges> {
+        self.usage.record("eth_getFilterChanges");
         Ok(self
             .provider
             .get_filter_changes_dyn(filter_id)
@@ -155,6 +419,7 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
     }
 
     async fn get_filter_logs(&self, filter_id: U256) -> Result<Vec<Log>> {
+        self.usage.record("eth_getFilterLogs");
         Ok(self
             .provider
             .raw_request("eth_getFilterLogs".into(), (filter_id,))
@@ -163,6 +428,7 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
     }
 
     async fn uninstall_filter(&self, filter_id: U256) -> Result<bool> {
+        self.usage.record("eth_uninstallFilter");
         Ok(self
             .provider
             .raw_request("eth_uninstallFilter".into(), (filter_id,))
@@ -171,6 +437,7 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
     }
 
     async fn new_filter(&self, filter: &Filter) -> Result<U256> {
+        self.usage.record("eth_newFilter");
         Ok(self
             .provider
             .new_filter(filter)
@@ -179,6 +446,7 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
     }
 
     async fn new_block_filter(&self) -> Result<U256> {
+        self.usage.record("eth_newBlockFilter");
         Ok(self
             .provider
             .new_block_filter()
@@ -187,6 +455,7 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
     }
 
     async fn new_pending_transaction_filter(&self) -> Result<U256> {
+        self.usage.record("eth_newPendingTransactionFilter");
         Ok(self
             .provider
             .new_pending_transactions_filter(false)
@@ -195,6 +464,7 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
     }
 
     async fn chain_id(&self) -> Result<u64> {
+        self.usage.record("eth_chainId");
         Ok(self
             .provider
             .get_chain_id()
@@ -208,6 +478,7 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
         last_block: u64,
         reward_percentiles: &[f64],
     ) -> Result<FeeHistory> {
+        self.usage.record("eth_feeHistory");
         Ok(self
             .provider
             .get_fee_history(block_count, last_block.into(), reward_percentiles)
@@ -215,10 +486,82 @@ impl<N: NetworkSpec> ExecutionRpc<N> for HttpRpc<N> {
             .map_err(|e| RpcError::new("fee_history", e))?)
     }
 
-    async fn get_block(&self, hash: B256) -> Result<N::BlockResponse> {
-        self.provider
-            .raw_request::<_, Option<N::BlockResponse>>("eth_getBlockByHash".into(), (hash, true))
-            .await?
-            .ok_or(eyre!("block not found"))
+    /// Checks that the header hashes to `hash` and that the transactions and withdrawals
+    /// match its roots. The full transactions are always fetched, as a list of hashes cannot
+    /// be checked against the root, and reduced to their recomputed hashes when only those
+    /// were asked for.
+    async fn get_block(&self, hash: B256, full_tx: bool) -> Result<N::BlockResponse> {
+        self.usage.record("eth_getBlockByHash");
+        let mut block = self
+            .provider
+            .raw_request::<_, Option<Value>>("eth_getBlockByHash".into(), (hash, true))
+            .await
+            .map_err(|e| RpcError::new("get_block", e))?
+            .ok_or(ExecutionError::BlockHashNotFound(hash))?;
+
+        // the transactions root is verified against these, so hold them to the same checks
+        if let Some(Value::Array(txs)) = block.get_mut("transactions") {
+            for tx in txs.iter_mut() {
+                self.normalize(ResponseKind::Transaction, tx).await?;
+            }
+        }
+
+        let mut block = serde_json::from_value::<N::BlockResponse>(block)?;
+        verify_block::<N>(hash, &block)?;
+        if !full_tx {
+            *block.transactions_mut() =
+                BlockTransactions::Hashes(block.transactions().hashes().collect());
+        }
+        Ok(block)
+    }
+
+    async fn get_block_by_number(
+        &self,
+        block: BlockTag,
+        full_tx: bool,
+    ) -> Result<Option<N::BlockResponse>> {
+        let block = provider_tag(block, "get_block_by_number")?;
+
+        self.usage.record("eth_getBlockByNumber");
+        let block = self
+            .provider
+            .raw_request::<_, Option<N::BlockResponse>>(
+                "eth_getBlockByNumber".into(),
+                (block, full_tx),
+            )
+            .await
+            .map_err(|e| RpcError::new("get_block_by_number", e))?;
+
+        Ok(block)
+    }
+}
+
+fn verify_block<N: NetworkSpec>(
+    hash: B256,
+    block: &N::BlockResponse,
+) -> Result<(), ExecutionError> {
+    let header = block.header();
+    if !N::is_header_hash_valid(header) {
+        return Err(ExecutionError::InvalidHeaderHash(header.hash()));
+    }
+    if header.hash() != hash {
+        return Err(ExecutionError::BlockHashMismatch(hash, header.hash()));
     }
+    N::check_body(block)
+}
+
+/// The provider's name for `block`. Pending state is never served, so `pending` asks for the
+/// latest block, and `helios_confirmed` is resolved against state as providers have no such
+/// tag.
+fn provider_tag(block: BlockTag, method: &str) -> Result<BlockNumberOrTag> {
+    Ok(match block {
+        BlockTag::Latest | BlockTag::Pending => BlockNumberOrTag::Latest,
+        BlockTag::Finalized => BlockNumberOrTag::Finalized,
+        BlockTag::Safe => BlockNumberOrTag::Safe,
+        BlockTag::Confirmed => eyre::bail!(
+            "{method} needs {} resolved to a block number",
+            BlockTag::Confirmed
+        ),
+        BlockTag::Number(num) => BlockNumberOrTag::Number(num),
+    })
 }
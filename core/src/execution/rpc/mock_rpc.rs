@@ -1,123 +1,319 @@
+//! An [`ExecutionRpc`] answering from json fixtures on disk, or from outcomes scripted per
+//! method.
+//!
+//! Scripted outcomes are taken in order, each by one call to its method, and a method whose
+//! outcomes ran out goes back to its fixture. This lets tests make a provider fail, stall or
+//! answer differently on a given call without a server in front of them. Every call is
+//! recorded, so tests can also check what was asked of the provider and when.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 use std::{fs::read_to_string, path::PathBuf, str::FromStr};
 
-use alloy::primitives::{Address, B256, U256};
+use alloy::primitives::{Address, Bytes, B256, U256};
 use alloy::rpc::types::{
     AccessList, BlockId, EIP1186AccountProofResponse, FeeHistory, Filter, FilterChanges, Log,
 };
 use async_trait::async_trait;
 use eyre::{eyre, Result};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
 
 use super::ExecutionRpc;
+use crate::errors::RpcError;
 use crate::network_spec::NetworkSpec;
+use crate::time::{sleep, Instant};
 use crate::types::BlockTag;
 
-#[derive(Clone)]
+/// The methods outcomes can be scripted for, named as in [`ExecutionRpc`].
+const METHODS: &[&str] = &[
+    "get_proof",
+    "create_access_list",
+    "get_code",
+    "send_raw_transaction",
+    "get_transaction_receipt",
+    "get_block_receipts",
+    "get_transaction",
+    "get_logs",
+    "get_filter_changes",
+    "get_filter_logs",
+    "uninstall_filter",
+    "new_filter",
+    "new_block_filter",
+    "new_pending_transaction_filter",
+    "chain_id",
+    "get_block",
+    "get_block_by_number",
+    "get_fee_history",
+];
+
+/// What one call to a scripted method does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    /// Answers with the json, read as the return type of the method.
+    Ok(Value),
+    /// Fails with an [`RpcError`] of the method carrying the message.
+    Err(String),
+    /// Waits before the call takes the next outcome, or its fixture if there is none.
+    Delay(Duration),
+}
+
+/// A call made to a [`MockRpc`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Call {
+    pub method: &'static str,
+    /// The arguments as a json array, in the order the method takes them.
+    pub args: Value,
+    pub at: Instant,
+}
+
+#[derive(Clone, Default)]
 pub struct MockRpc {
     path: PathBuf,
+    script: Arc<Mutex<HashMap<&'static str, VecDeque<Outcome>>>>,
+    calls: Arc<Mutex<Vec<Call>>>,
+}
+
+impl MockRpc {
+    pub fn builder() -> MockRpcBuilder {
+        MockRpcBuilder::default()
+    }
+
+    /// Every call made so far, including from clones, oldest first.
+    pub fn calls(&self) -> Vec<Call> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    /// The calls made so far to `method`, oldest first.
+    pub fn calls_to(&self, method: &str) -> Vec<Call> {
+        self.calls()
+            .into_iter()
+            .filter(|call| call.method == method)
+            .collect()
+    }
+
+    /// Records the call, then plays the scripted outcomes of `method` until one answers,
+    /// falling back to `fixture`.
+    async fn answer<T: DeserializeOwned>(
+        &self,
+        method: &'static str,
+        args: Value,
+        fixture: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        self.calls.lock().unwrap().push(Call {
+            method,
+            args,
+            at: Instant::now(),
+        });
+
+        loop {
+            let outcome = self
+                .script
+                .lock()
+                .unwrap()
+                .get_mut(method)
+                .and_then(VecDeque::pop_front);
+            match outcome {
+                Some(Outcome::Ok(value)) => return Ok(serde_json::from_value(value)?),
+                Some(Outcome::Err(message)) => return Err(RpcError::new(method, message).into()),
+                Some(Outcome::Delay(delay)) => sleep(delay).await,
+                None => return fixture(),
+            }
+        }
+    }
+
+    fn fixture<T: DeserializeOwned>(&self, file: &str) -> Result<T> {
+        let json = read_to_string(self.path.join(file))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    fn filter_id(&self) -> Result<U256> {
+        let id = read_to_string(self.path.join("filter_id.txt"))?;
+        Ok(U256::from_str(&id)?)
+    }
+}
+
+/// Sets up a [`MockRpc`]. Without fixtures, calls with no outcome left fail.
+#[derive(Default)]
+pub struct MockRpcBuilder {
+    path: PathBuf,
+    script: HashMap<&'static str, VecDeque<Outcome>>,
+}
+
+impl MockRpcBuilder {
+    /// Answers the calls that have no scripted outcome from the json files in `path`.
+    pub fn fixtures(mut self, path: impl Into<PathBuf>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Queues `outcomes` for the next calls to `method`, after any queued before.
+    ///
+    /// # Panics
+    ///
+    /// If `method` is not a method of [`ExecutionRpc`] that can be scripted.
+    pub fn script(
+        mut self,
+        method: &'static str,
+        outcomes: impl IntoIterator<Item = Outcome>,
+    ) -> Self {
+        assert!(
+            METHODS.contains(&method),
+            "cannot script unknown method {method}"
+        );
+        self.script.entry(method).or_default().extend(outcomes);
+        self
+    }
+
+    pub fn build(self) -> MockRpc {
+        MockRpc {
+            path: self.path,
+            script: Arc::new(Mutex::new(self.script)),
+            calls: Arc::default(),
+        }
+    }
 }
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 impl<N: NetworkSpec> ExecutionRpc<N> for MockRpc {
     fn new(rpc: &str) -> Result<Self> {
-        let path = PathBuf::from(rpc);
-        Ok(MockRpc { path })
+        Ok(MockRpc::builder().fixtures(rpc).build())
     }
 
     async fn get_proof(
         &self,
-        _address: Address,
-        _slots: &[B256],
-        _block: BlockId,
+        address: Address,
+        slots: &[B256],
+        block: BlockId,
     ) -> Result<EIP1186AccountProofResponse> {
-        let proof = read_to_string(self.path.join("proof.json"))?;
-        Ok(serde_json::from_str(&proof)?)
+        let args = json!([address, slots, block]);
+        self.answer("get_proof", args, || self.fixture("proof.json"))
+            .await
     }
 
     async fn create_access_list(
         &self,
-        _opts: &N::TransactionRequest,
-        _block: BlockTag,
+        opts: &N::TransactionRequest,
+        block: BlockTag,
     ) -> Result<AccessList> {
-        Err(eyre!("not implemented"))
+        let args = json!([opts, block.to_string()]);
+        self.answer("create_access_list", args, || Err(eyre!("not implemented")))
+            .await
     }
 
-    async fn get_code(&self, _address: Address, _block: u64) -> Result<Vec<u8>> {
-        let code = read_to_string(self.path.join("code.json"))?;
-        Ok(hex::decode(&code[2..code.len() - 1])?)
+    async fn get_code(&self, address: Address, block: u64) -> Result<Vec<u8>> {
+        let args = json!([address, block]);
+        let code: Bytes = self
+            .answer("get_code", args, || self.fixture("code.json"))
+            .await?;
+        Ok(code.into())
     }
 
-    async fn send_raw_transaction(&self, _bytes: &[u8]) -> Result<B256> {
-        Err(eyre!("not implemented"))
+    async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<B256> {
+        let args = json!([Bytes::copy_from_slice(bytes)]);
+        self.answer("send_raw_transaction", args, || {
+            Err(eyre!("not implemented"))
+        })
+        .await
     }
 
-    async fn get_transaction_receipt(&self, _tx_hash: B256) -> Result<Option<N::ReceiptResponse>> {
-        let receipt = read_to_string(self.path.join("receipt.json"))?;
-        Ok(serde_json::from_str(&receipt)?)
+    async fn get_transaction_receipt(&self, tx_hash: B256) -> Result<Option<N::ReceiptResponse>> {
+        self.answer("get_transaction_receipt", json!([tx_hash]), || {
+            self.fixture("receipt.json")
+        })
+        .await
     }
 
-    async fn get_block_receipts(
-        &self,
-        _block: BlockTag,
-    ) -> Result<Option<Vec<N::ReceiptResponse>>> {
-        let receipts = read_to_string(self.path.join("receipts.json"))?;
-        Ok(serde_json::from_str(&receipts)?)
+    async fn get_block_receipts(&self, block: BlockId) -> Result<Option<Vec<N::ReceiptResponse>>> {
+        let args = json!([block]);
+        self.answer("get_block_receipts", args, || self.fixture("receipts.json"))
+            .await
     }
 
-    async fn get_transaction(&self, _tx_hash: B256) -> Result<Option<N::TransactionResponse>> {
-        let tx = read_to_string(self.path.join("transaction.json"))?;
-        Ok(serde_json::from_str(&tx)?)
+    async fn get_transaction(&self, tx_hash: B256) -> Result<Option<N::TransactionResponse>> {
+        self.answer("get_transaction", json!([tx_hash]), || {
+            self.fixture("transaction.json")
+        })
+        .await
     }
 
-    async fn get_logs(&self, _filter: &Filter) -> Result<Vec<Log>> {
-        let logs = read_to_string(self.path.join("logs.json"))?;
-        Ok(serde_json::from_str(&logs)?)
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        self.answer("get_logs", json!([filter]), || self.fixture("logs.json"))
+            .await
     }
 
-    async fn get_filter_changes(&self, _filter_id: U256) -> Result<FilterChanges> {
-        let logs = read_to_string(self.path.join("logs.json"))?;
-        Ok(serde_json::from_str(&logs)?)
+    async fn get_filter_changes(&self, filter_id: U256) -> Result<FilterChanges> {
+        self.answer("get_filter_changes", json!([filter_id]), || {
+            self.fixture("logs.json")
+        })
+        .await
     }
 
-    async fn get_filter_logs(&self, _filter_id: U256) -> Result<Vec<Log>> {
-        let logs = read_to_string(self.path.join("logs.json"))?;
-        Ok(serde_json::from_str(&logs)?)
+    async fn get_filter_logs(&self, filter_id: U256) -> Result<Vec<Log>> {
+        self.answer("get_filter_logs", json!([filter_id]), || {
+            self.fixture("logs.json")
+        })
+        .await
     }
 
-    async fn uninstall_filter(&self, _filter_id: U256) -> Result<bool> {
-        Err(eyre!("not implemented"))
+    async fn uninstall_filter(&self, filter_id: U256) -> Result<bool> {
+        self.answer("uninstall_filter", json!([filter_id]), || {
+            Err(eyre!("not implemented"))
+        })
+        .await
     }
 
-    async fn new_filter(&self, _filter: &Filter) -> Result<U256> {
-        let id = read_to_string(self.path.join("filter_id.txt"))?;
-        Ok(U256::from_str(&id)?)
+    async fn new_filter(&self, filter: &Filter) -> Result<U256> {
+        self.answer("new_filter", json!([filter]), || self.filter_id())
+            .await
     }
 
     async fn new_block_filter(&self) -> Result<U256> {
-        let id = read_to_string(self.path.join("filter_id.txt"))?;
-        Ok(U256::from_str(&id)?)
+        self.answer("new_block_filter", json!([]), || self.filter_id())
+            .await
     }
 
     async fn new_pending_transaction_filter(&self) -> Result<U256> {
-        let id = read_to_string(self.path.join("filter_id.txt"))?;
-        Ok(U256::from_str(&id)?)
+        self.answer("new_pending_transaction_filter", json!([]), || {
+            self.filter_id()
+        })
+        .await
     }
 
     async fn chain_id(&self) -> Result<u64> {
-        Err(eyre!("not implemented"))
+        self.answer("chain_id", json!([]), || Err(eyre!("not implemented")))
+            .await
     }
 
-    async fn get_block(&self, _hash: B256) -> Result<N::BlockResponse> {
-        Err(eyre!("not implemented"))
+    async fn get_block(&self, hash: B256, full_tx: bool) -> Result<N::BlockResponse> {
+        self.answer("get_block", json!([hash, full_tx]), || {
+            Err(eyre!("not implemented"))
+        })
+        .await
+    }
+
+    async fn get_block_by_number(
+        &self,
+        block: BlockTag,
+        full_tx: bool,
+    ) -> Result<Option<N::BlockResponse>> {
+        let args = json!([block.to_string(), full_tx]);
+        self.answer("get_block_by_number", args, || {
+            Err(eyre!("not implemented"))
+        })
+        .await
     }
 
     async fn get_fee_history(
         &self,
-        _block_count: u64,
-        _last_block: u64,
-        _reward_percentiles: &[f64],
+        block_count: u64,
+        last_block: u64,
+        reward_percentiles: &[f64],
     ) -> Result<FeeHistory> {
-        let fee_history = read_to_string(self.path.join("fee_history.json"))?;
-        Ok(serde_json::from_str(&fee_history)?)
+        let args = json!([block_count, last_block, reward_percentiles]);
+        self.answer("get_fee_history", args, || self.fixture("fee_history.json"))
+            .await
     }
 }
@@ -1,15 +1,29 @@
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+use std::sync::Arc;
+
 use alloy::primitives::{Address, B256, U256};
 use alloy::rpc::types::{
     AccessList, BlockId, EIP1186AccountProofResponse, FeeHistory, Filter, FilterChanges, Log,
 };
 use async_trait::async_trait;
 use eyre::Result;
+use futures::future::join_all;
 
+use crate::execution::usage::UsageMeter;
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+use crate::metrics::Metrics;
 use crate::network_spec::NetworkSpec;
 use crate::types::BlockTag;
 
+pub mod compat;
+#[cfg(target_arch = "wasm32")]
+pub mod fetch;
+pub mod headers;
 pub mod http_rpc;
 pub mod mock_rpc;
+pub mod rate_limit;
+pub mod retry;
+pub mod timeout;
 
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
@@ -18,6 +32,18 @@ pub trait ExecutionRpc<N: NetworkSpec>: Send + Clone + Sync + 'static {
     where
         Self: Sized;
 
+    /// The accounting of the requests sent to the provider, for rpcs that keep one.
+    fn usage(&self) -> Option<&UsageMeter> {
+        None
+    }
+
+    /// The registry the client verifying this rpc's responses records into, for rpcs that
+    /// keep one.
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    fn metrics(&self) -> Option<&Arc<Metrics>> {
+        None
+    }
+
     async fn get_proof(
         &self,
         address: Address,
@@ -25,6 +51,22 @@ pub trait ExecutionRpc<N: NetworkSpec>: Send + Clone + Sync + 'static {
         block: BlockId,
     ) -> Result<EIP1186AccountProofResponse>;
 
+    /// Proofs of several accounts and their slots at `block`, in the order asked for.
+    ///
+    /// Each entry fails on its own, while an error for the whole call means none of the
+    /// proofs could be fetched. Rpcs able to send the requests together override this to
+    /// save a round trip per account.
+    async fn get_proofs(
+        &self,
+        accounts: &[(Address, Vec<B256>)],
+        block: BlockId,
+    ) -> Result<Vec<Result<EIP1186AccountProofResponse>>> {
+        let proofs = accounts
+            .iter()
+            .map(|(address, slots)| self.get_proof(*address, slots, block));
+        Ok(join_all(proofs).await)
+    }
+
     async fn create_access_list(
         &self,
         tx: &N::TransactionRequest,
@@ -32,9 +74,21 @@ pub trait ExecutionRpc<N: NetworkSpec>: Send + Clone + Sync + 'static {
     ) -> Result<AccessList>;
 
     async fn get_code(&self, address: Address, block: u64) -> Result<Vec<u8>>;
+
+    /// The code of several accounts at `block`, failing per entry like
+    /// [`ExecutionRpc::get_proofs`].
+    async fn get_codes(&self, addresses: &[Address], block: u64) -> Result<Vec<Result<Vec<u8>>>> {
+        let codes = addresses
+            .iter()
+            .map(|address| self.get_code(*address, block));
+        Ok(join_all(codes).await)
+    }
+
     async fn send_raw_transaction(&self, bytes: &[u8]) -> Result<B256>;
     async fn get_transaction_receipt(&self, tx_hash: B256) -> Result<Option<N::ReceiptResponse>>;
-    async fn get_block_receipts(&self, block: BlockTag) -> Result<Option<Vec<N::ReceiptResponse>>>;
+    /// The receipts of `block`, asked for by hash wherever the block is known so they cannot
+    /// come from another block at the same height.
+    async fn get_block_receipts(&self, block: BlockId) -> Result<Option<Vec<N::ReceiptResponse>>>;
     async fn get_transaction(&self, tx_hash: B256) -> Result<Option<N::TransactionResponse>>;
     async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>>;
     async fn get_filter_changes(&self, filter_id: U256) -> Result<FilterChanges>;
@@ -44,7 +98,13 @@ pub trait ExecutionRpc<N: NetworkSpec>: Send + Clone + Sync + 'static {
     async fn new_block_filter(&self) -> Result<U256>;
     async fn new_pending_transaction_filter(&self) -> Result<U256>;
     async fn chain_id(&self) -> Result<u64>;
-    async fn get_block(&self, hash: B256) -> Result<N::BlockResponse>;
+    /// The block of `hash`, with full transactions or only their hashes.
+    async fn get_block(&self, hash: B256, full_tx: bool) -> Result<N::BlockResponse>;
+    async fn get_block_by_number(
+        &self,
+        block: BlockTag,
+        full_tx: bool,
+    ) -> Result<Option<N::BlockResponse>>;
 
     async fn get_fee_history(
         &self,
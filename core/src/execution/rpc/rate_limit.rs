@@ -0,0 +1,403 @@
+//! A client side request budget toward the execution provider.
+//!
+//! Plans with a request quota throttle or bill whatever goes over it, and retrying their 429s
+//! only spends more of the quota. [`RateLimitConfig`] keeps helios under it instead with a
+//! token bucket refilling at the configured rate up to a burst. Each request takes the tokens
+//! its method weighs, and a request finding too few waits for them to refill. Waits are handed
+//! out in order of arrival, so a request that would wait longer than the configured maximum
+//! fails with [`RateBudgetExceeded`] right away rather than after sitting in the queue.
+//!
+//! The layer sits below the retry layer, so retries are paid for out of the same budget.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket, SerializedRequest};
+use alloy::transports::{Transport, TransportError, TransportErrorKind, TransportFut};
+use serde::{Deserialize, Serialize};
+use tower::{Layer, Service};
+use tracing::debug;
+
+use crate::config::ConfigDuration;
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+use crate::metrics::Metrics;
+use crate::time::{sleep, Instant};
+
+/// The request budget toward the provider, from the `execution_rate_limit` config table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RateLimitConfig {
+    /// Requests sent per second on average, zero for no limit.
+    pub requests_per_second: u32,
+    /// Requests that may be sent back to back once the budget has built up.
+    pub burst: u32,
+    /// Longest a request waits for the budget before failing with [`RateBudgetExceeded`].
+    pub max_wait: ConfigDuration,
+    /// How many requests a call to each method counts as, for methods a provider charges
+    /// more or less for. Methods not listed count as one.
+    pub weights: BTreeMap<String, u32>,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 0,
+            burst: 10,
+            max_wait: ConfigDuration::from_secs(5),
+            weights: BTreeMap::new(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.requests_per_second > 0
+    }
+
+    /// How many requests a call to `method` counts as.
+    fn weight(&self, method: &str) -> u32 {
+        self.weights.get(method).copied().unwrap_or(1)
+    }
+}
+
+/// A provider request turned away since it would have waited too long for the budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("rate budget exceeded: the request would wait {0:?} for the provider budget")]
+pub struct RateBudgetExceeded(pub Duration);
+
+/// Whether `err` is a request turned away by the request budget.
+pub fn is_rate_budget_exceeded(err: &TransportError) -> bool {
+    matches!(
+        err,
+        TransportError::Transport(TransportErrorKind::Custom(err)) if err.is::<RateBudgetExceeded>()
+    )
+}
+
+/// A transport layer holding requests back per a [`RateLimitConfig`].
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    config: RateLimitConfig,
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl RateLimitLayer {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            metrics: None,
+        }
+    }
+
+    /// Records the budget left, the requests waiting for it and those it turned away in
+    /// `metrics`.
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        let bucket = self.config.is_enabled().then(|| {
+            let bucket = Bucket::new(self.config.clone());
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            let bucket = Bucket {
+                metrics: self.metrics.clone(),
+                ..bucket
+            };
+            Arc::new(bucket)
+        });
+        RateLimitService { inner, bucket }
+    }
+}
+
+/// Delays the requests of the wrapped transport to stay within the budget. Clones share the
+/// bucket.
+#[derive(Debug, Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    bucket: Option<Arc<Bucket>>,
+}
+
+impl<S: Transport + Clone> Service<RequestPacket> for RateLimitService<S> {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let Some(bucket) = self.bucket.clone() else {
+            return inner.call(req);
+        };
+
+        Box::pin(async move {
+            let weight = requests(&req)
+                .iter()
+                .map(|req| bucket.config.weight(req.method()))
+                .sum();
+            let wait = match bucket.reserve(weight) {
+                Ok(wait) => wait,
+                Err(err) => {
+                    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+                    if let Some(metrics) = &bucket.metrics {
+                        metrics.record_rate_budget_exceeded(&req);
+                    }
+
+                    debug!(
+                        target: "helios::execution",
+                        wait_ms = err.0.as_millis() as u64,
+                        "provider request budget exhausted, failing the request"
+                    );
+                    return Err(TransportErrorKind::custom(err));
+                }
+            };
+
+            if !wait.is_zero() {
+                let _queued = bucket.enqueue();
+                sleep(wait).await;
+            }
+            inner.call(req).await
+        })
+    }
+}
+
+/// The token bucket shared by the clones of a service.
+///
+/// Rather than counting tokens it keeps the time at which the bucket is full again, which
+/// moves ahead by the refill time of each request's tokens. A request waits until no more
+/// than a burst's worth of refill time is ahead of it, keeping the arithmetic in whole
+/// durations.
+#[derive(Debug)]
+struct Bucket {
+    config: RateLimitConfig,
+    /// Time it takes to refill the token of one request.
+    interval: Duration,
+    full_at: Mutex<Instant>,
+    queued: AtomicI64,
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl Bucket {
+    fn new(config: RateLimitConfig) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / config.requests_per_second,
+            config,
+            full_at: Mutex::new(Instant::now()),
+            queued: AtomicI64::new(0),
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            metrics: None,
+        }
+    }
+
+    /// Takes the tokens of a request weighing `weight`, returning how long it waits for them
+    /// to refill. A request that would wait longer than the maximum takes nothing.
+    fn reserve(&self, weight: u32) -> Result<Duration, RateBudgetExceeded> {
+        let now = Instant::now();
+        let burst = self.interval * self.config.burst;
+        let mut full_at = self.full_at.lock().unwrap();
+        let full_at_after = (*full_at).max(now) + self.interval * weight;
+
+        let wait = full_at_after
+            .saturating_duration_since(now)
+            .saturating_sub(burst);
+        if wait > self.config.max_wait.as_duration() {
+            return Err(RateBudgetExceeded(wait));
+        }
+        *full_at = full_at_after;
+
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        if let Some(metrics) = &self.metrics {
+            let refilling = full_at_after.saturating_duration_since(now);
+            let available =
+                (burst.as_secs_f64() - refilling.as_secs_f64()) / self.interval.as_secs_f64();
+            metrics.record_rate_budget(available);
+        }
+
+        Ok(wait)
+    }
+
+    /// Counts a request as waiting for the budget until the returned guard is dropped.
+    fn enqueue(&self) -> Queued<'_> {
+        let _queued = self.queued.fetch_add(1, Ordering::SeqCst) + 1;
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        self.record_queue(_queued);
+        Queued(self)
+    }
+
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    fn record_queue(&self, queued: i64) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_rate_limit_queue(queued);
+        }
+    }
+}
+
+/// A request waiting for the budget, also when its caller drops it while waiting.
+struct Queued<'a>(&'a Bucket);
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        let _queued = self.0.queued.fetch_sub(1, Ordering::SeqCst) - 1;
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        self.0.record_queue(_queued);
+    }
+}
+
+fn requests(req: &RequestPacket) -> &[SerializedRequest] {
+    match req {
+        RequestPacket::Single(req) => std::slice::from_ref(req),
+        RequestPacket::Batch(reqs) => reqs,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::rpc::json_rpc::{Id, Request, Response, ResponsePayload};
+    use futures::future::join_all;
+    use serde_json::value::RawValue;
+
+    use super::*;
+    use crate::execution::rpc::retry::{RetryConfig, RetryLayer};
+
+    /// A transport answering every request, recording when each arrived.
+    #[derive(Clone, Default)]
+    struct RecordingTransport {
+        calls: Arc<Mutex<Vec<Instant>>>,
+    }
+
+    impl RecordingTransport {
+        /// When each request arrived, counted from `start`.
+        fn arrivals(&self, start: Instant) -> Vec<Duration> {
+            let calls = self.calls.lock().unwrap();
+            calls.iter().map(|call| *call - start).collect()
+        }
+    }
+
+    impl Service<RequestPacket> for RecordingTransport {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _req: RequestPacket) -> Self::Future {
+            self.calls.lock().unwrap().push(Instant::now());
+            Box::pin(async move {
+                Ok(ResponsePacket::Single(Response {
+                    id: Id::Number(1),
+                    payload: ResponsePayload::Success(
+                        RawValue::from_string("\"0x1\"".into()).unwrap(),
+                    ),
+                }))
+            })
+        }
+    }
+
+    fn request(method: &'static str) -> RequestPacket {
+        let request = Request::new(method, Id::Number(1), ());
+        RequestPacket::Single(request.serialize().unwrap())
+    }
+
+    fn config(requests_per_second: u32, burst: u32, max_wait: u64) -> RateLimitConfig {
+        RateLimitConfig {
+            requests_per_second,
+            burst,
+            max_wait: ConfigDuration::from_millis(max_wait),
+            weights: BTreeMap::new(),
+        }
+    }
+
+    fn millis(millis: [u64; 6]) -> Vec<Duration> {
+        millis.map(Duration::from_millis).to_vec()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_burst_spread_at_rate() {
+        let transport = RecordingTransport::default();
+        let service = RateLimitLayer::new(config(10, 2, 1_000)).layer(transport.clone());
+        let start = Instant::now();
+
+        let calls = (0..6).map(|_| service.clone().call(request("eth_chainId")));
+        for res in join_all(calls).await {
+            res.unwrap();
+        }
+        assert_eq!(
+            transport.arrivals(start),
+            millis([0, 0, 100, 200, 300, 400])
+        );
+
+        // a quiet period builds the burst up again
+        sleep(Duration::from_secs(1)).await;
+        let start = Instant::now();
+        let calls = (0..6).map(|_| service.clone().call(request("eth_chainId")));
+        for res in join_all(calls).await {
+            res.unwrap();
+        }
+        assert_eq!(
+            &transport.arrivals(start)[6..],
+            millis([0, 0, 100, 200, 300, 400])
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_weighted_methods() {
+        let transport = RecordingTransport::default();
+        let mut config = config(10, 5, 1_000);
+        config.weights.insert("eth_getProof".into(), 5);
+        config.weights.insert("eth_chainId".into(), 0);
+        let service = RateLimitLayer::new(config).layer(transport.clone());
+        let start = Instant::now();
+
+        let requests = ["eth_getProof", "eth_chainId", "eth_getProof", "eth_call"];
+        let calls = requests.map(|method| service.clone().call(request(method)));
+        for res in join_all(calls).await {
+            res.unwrap();
+        }
+        // the first proof spends the burst, and the second one refills it
+        let arrivals = transport.arrivals(start);
+        assert_eq!(arrivals, [0, 0, 500, 600].map(Duration::from_millis));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_saturated_budget_fails_without_retries() {
+        let transport = RecordingTransport::default();
+        let service = RateLimitLayer::new(config(1, 1, 2_000)).layer(transport.clone());
+        let service = RetryLayer::new(RetryConfig::default()).layer(service);
+
+        let calls = (0..4).map(|_| service.clone().call(request("eth_chainId")));
+        let results = join_all(calls).await;
+
+        assert!(results[..3].iter().all(Result::is_ok));
+        let err = results[3].as_ref().unwrap_err();
+        assert!(is_rate_budget_exceeded(err), "{err}");
+        assert!(err.to_string().contains("rate budget exceeded"), "{err}");
+        assert_eq!(transport.calls.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_disabled_sends_right_away() {
+        let transport = RecordingTransport::default();
+        let service = RateLimitLayer::new(RateLimitConfig::default()).layer(transport.clone());
+        let start = Instant::now();
+
+        let calls = (0..20).map(|_| service.clone().call(request("eth_chainId")));
+        join_all(calls).await;
+        let arrivals = transport.arrivals(start);
+        assert!(arrivals.iter().all(Duration::is_zero));
+    }
+}
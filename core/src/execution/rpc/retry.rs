@@ -0,0 +1,414 @@
+//! Retries of execution provider requests that were rate limited or timed out.
+//!
+//! Free tier providers answer bursts with 429s that clear up after a moment, while a node of
+//! one's own should rather fail fast. Requests that ran into the timeout of
+//! [`super::timeout`] are retried the same way, as a fresh request often goes through.
+//! Both are served by [`RetryConfig`], which defaults to retrying often with a short
+//! constant backoff.
+//!
+//! Requests sending a transaction are never retried, as one that timed out may still have
+//! gone through, and sending it again can only repeat its outcome or confuse the nonce
+//! tracking of the provider.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket, SerializedRequest};
+use alloy::transports::layers::{RateLimitRetryPolicy, RetryPolicy};
+use alloy::transports::{Transport, TransportError, TransportFut};
+use serde::{Deserialize, Serialize};
+use tower::{Layer, Service};
+use tracing::debug;
+
+use super::timeout::is_timeout;
+use crate::config::ConfigDuration;
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+use crate::metrics::Metrics;
+use crate::time::sleep;
+
+/// Compute units an average request is assumed to cost against the provider's budget.
+const AVERAGE_REQUEST_COST: u64 = 17;
+
+/// Methods that are never retried, see the module docs.
+const UNRETRIED_METHODS: &[&str] = &[
+    "eth_sendRawTransaction",
+    "eth_sendTransaction",
+    "eth_sendRawTransactionConditional",
+];
+
+/// Whether any request of `req` sends a transaction.
+fn sends_transaction(req: &RequestPacket) -> bool {
+    let sends = |req: &SerializedRequest| UNRETRIED_METHODS.contains(&req.method());
+    match req {
+        RequestPacket::Single(req) => sends(req),
+        RequestPacket::Batch(reqs) => reqs.iter().any(sends),
+    }
+}
+
+/// How rate limited and timed out provider requests are retried, from the `execution_retry`
+/// config table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Retries of a rate limited request before its error is returned, zero to never retry.
+    pub max_retries: u32,
+    /// Wait before the first retry, doubled for each retry after it.
+    pub initial_backoff: ConfigDuration,
+    /// Longest wait between two retries. Waits the provider asks for are honoured as given.
+    pub max_backoff: ConfigDuration,
+    /// Compute units per second the provider allows, spreading retries out when many
+    /// requests are retrying at once. Zero disables the spreading.
+    pub compute_units_per_second: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 100,
+            initial_backoff: ConfigDuration::from_millis(50),
+            max_backoff: ConfigDuration::from_millis(50),
+            compute_units_per_second: 300,
+        }
+    }
+}
+
+impl RetryConfig {
+    /// Never retries.
+    pub fn disabled() -> Self {
+        Self {
+            max_retries: 0,
+            ..Default::default()
+        }
+    }
+
+    /// The wait before retry number `retry`, counting from zero, unless the provider asks
+    /// for another.
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.checked_pow(retry).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .as_duration()
+            .saturating_mul(factor)
+            .min(self.max_backoff.as_duration())
+    }
+
+    /// The extra wait once `queued` requests are waiting to be retried, long enough for all
+    /// of them to fit in the compute unit budget.
+    fn budget_wait(&self, queued: u64) -> Duration {
+        let cost = queued.saturating_mul(AVERAGE_REQUEST_COST);
+        if self.compute_units_per_second == 0 || cost <= self.compute_units_per_second {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(cost as f64 / self.compute_units_per_second as f64)
+    }
+}
+
+/// A transport layer retrying requests per a [`RetryConfig`].
+#[derive(Debug, Clone)]
+pub struct RetryLayer {
+    config: RetryConfig,
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl RetryLayer {
+    pub fn new(config: RetryConfig) -> Self {
+        Self {
+            config,
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            metrics: None,
+        }
+    }
+
+    /// Counts the retries in `metrics`.
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+}
+
+impl<S> Layer<S> for RetryLayer {
+    type Service = RetryService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RetryService {
+            inner,
+            config: self.config,
+            policy: RateLimitRetryPolicy,
+            queued: Arc::default(),
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// Retries the requests of the wrapped transport that failed from rate limiting or timed
+/// out. Clones share the count of requests waiting to be retried.
+#[derive(Debug, Clone)]
+pub struct RetryService<S> {
+    inner: S,
+    config: RetryConfig,
+    policy: RateLimitRetryPolicy,
+    queued: Arc<AtomicU64>,
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl<S: Transport + Clone> Service<RequestPacket> for RetryService<S> {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // requests are sent through clones of the inner transport, which are always ready
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let mut this = self.clone();
+        let max_retries = if sends_transaction(&req) {
+            0
+        } else {
+            this.config.max_retries
+        };
+        Box::pin(async move {
+            let mut retry = 0;
+            loop {
+                let res = this.inner.call(req.clone()).await;
+                let (retryable, hint) = match &res {
+                    Ok(packet) => match packet.as_error() {
+                        Some(payload) => {
+                            let err = TransportError::ErrorResp(payload.clone());
+                            (
+                                this.policy.should_retry(&err),
+                                this.policy.backoff_hint(&err),
+                            )
+                        }
+                        None => (false, None),
+                    },
+                    Err(err) => (
+                        this.policy.should_retry(err) || is_timeout(err),
+                        this.policy.backoff_hint(err),
+                    ),
+                };
+                if !retryable || retry >= max_retries {
+                    return res;
+                }
+
+                #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+                if let Some(metrics) = &this.metrics {
+                    metrics.record_retry(&req);
+                }
+
+                let queued = this.queued.fetch_add(1, Ordering::SeqCst) + 1;
+                let wait = hint.unwrap_or_else(|| this.config.backoff(retry))
+                    + this.config.budget_wait(queued);
+                debug!(
+                    target: "helios::execution",
+                    retry = retry + 1,
+                    wait_ms = wait.as_millis() as u64,
+                    "provider rate limited the request, retrying"
+                );
+                sleep(wait).await;
+                this.queued.fetch_sub(1, Ordering::SeqCst);
+                retry += 1;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use alloy::rpc::json_rpc::{Id, Request, Response, ResponsePayload};
+    use alloy::transports::TransportErrorKind;
+    use serde_json::value::RawValue;
+
+    use super::*;
+    use crate::execution::rpc::timeout::RequestTimeout;
+    use crate::time::Instant;
+
+    /// A transport rate limiting the first `limited` requests, or timing them out, recording
+    /// when each arrived.
+    #[derive(Clone)]
+    struct LimitedTransport {
+        limited: usize,
+        timing_out: bool,
+        calls: Arc<Mutex<Vec<Instant>>>,
+    }
+
+    impl LimitedTransport {
+        fn new(limited: usize) -> Self {
+            Self {
+                limited,
+                timing_out: false,
+                calls: Arc::default(),
+            }
+        }
+
+        fn timing_out(limited: usize) -> Self {
+            Self {
+                timing_out: true,
+                ..Self::new(limited)
+            }
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.lock().unwrap().len()
+        }
+
+        fn waits(&self) -> Vec<Duration> {
+            let calls = self.calls.lock().unwrap();
+            calls.windows(2).map(|pair| pair[1] - pair[0]).collect()
+        }
+    }
+
+    impl Service<RequestPacket> for LimitedTransport {
+        type Response = ResponsePacket;
+        type Error = TransportError;
+        type Future = TransportFut<'static>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _req: RequestPacket) -> Self::Future {
+            let mut calls = self.calls.lock().unwrap();
+            calls.push(Instant::now());
+            let limited = calls.len() <= self.limited;
+            let timing_out = self.timing_out;
+
+            Box::pin(async move {
+                if limited && timing_out {
+                    return Err(TransportErrorKind::custom(RequestTimeout(
+                        Duration::from_secs(1),
+                    )));
+                }
+                if limited {
+                    return Err(TransportErrorKind::http_error(
+                        429,
+                        "too many requests".to_string(),
+                    ));
+                }
+                Ok(ResponsePacket::Single(Response {
+                    id: Id::Number(1),
+                    payload: ResponsePayload::Success(
+                        RawValue::from_string("\"0x1\"".into()).unwrap(),
+                    ),
+                }))
+            })
+        }
+    }
+
+    fn request() -> RequestPacket {
+        let request = Request::new("eth_chainId", Id::Number(1), ());
+        RequestPacket::Single(request.serialize().unwrap())
+    }
+
+    fn send_request() -> RequestPacket {
+        let request = Request::new("eth_sendRawTransaction", Id::Number(1), ("0x02",));
+        RequestPacket::Single(request.serialize().unwrap())
+    }
+
+    fn config(max_retries: u32, initial: u64, max: u64) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            initial_backoff: ConfigDuration::from_millis(initial),
+            max_backoff: ConfigDuration::from_millis(max),
+            compute_units_per_second: 0,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limited_request_retried_with_backoff() {
+        let transport = LimitedTransport::new(4);
+        let mut service = RetryLayer::new(config(5, 100, 500)).layer(transport.clone());
+
+        service.call(request()).await.unwrap();
+        assert_eq!(transport.calls(), 5);
+        let waits = transport.waits();
+        assert_eq!(
+            waits,
+            [100, 200, 400, 500].map(Duration::from_millis).to_vec()
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_exhausted() {
+        let transport = LimitedTransport::new(10);
+        let mut service = RetryLayer::new(config(3, 100, 100)).layer(transport.clone());
+
+        let err = service.call(request()).await.unwrap_err();
+        assert!(
+            matches!(err, TransportError::Transport(TransportErrorKind::HttpError(ref e)) if e.status == 429)
+        );
+        assert_eq!(transport.calls(), 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_retries_disabled() {
+        let transport = LimitedTransport::new(1);
+        let mut service = RetryLayer::new(RetryConfig::disabled()).layer(transport.clone());
+
+        assert!(service.call(request()).await.is_err());
+        assert_eq!(transport.calls(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timed_out_request_retried() {
+        let transport = LimitedTransport::timing_out(2);
+        let mut service = RetryLayer::new(config(5, 100, 100)).layer(transport.clone());
+
+        service.call(request()).await.unwrap();
+        assert_eq!(transport.calls(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sends_never_retried() {
+        for transport in [LimitedTransport::timing_out(1), LimitedTransport::new(1)] {
+            let mut service = RetryLayer::new(config(5, 100, 100)).layer(transport.clone());
+
+            let err = service.call(send_request()).await.unwrap_err();
+            assert_eq!(is_timeout(&err), transport.timing_out);
+            assert_eq!(transport.calls(), 1);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_clones_share_budget() {
+        let transport = LimitedTransport::new(2);
+        let config = RetryConfig {
+            compute_units_per_second: AVERAGE_REQUEST_COST,
+            ..config(1, 100, 100)
+        };
+        let service = RetryLayer::new(config).layer(transport.clone());
+        let (mut a, mut b) = (service.clone(), service);
+
+        let (res_a, res_b) = tokio::join!(a.call(request()), b.call(request()));
+        res_a.unwrap();
+        res_b.unwrap();
+
+        // the second retry waits for the first to fit into the budget
+        let calls = transport.calls.lock().unwrap();
+        assert_eq!(calls[2] - calls[0], Duration::from_millis(100));
+        assert_eq!(calls[3] - calls[0], Duration::from_millis(2_100));
+    }
+
+    #[test]
+    fn test_default_backoff_is_constant() {
+        let config = RetryConfig::default();
+        for retry in [0, 1, 10, 40] {
+            assert_eq!(config.backoff(retry), Duration::from_millis(50));
+        }
+    }
+
+    #[test]
+    fn test_budget_wait_spreads_queued_retries() {
+        let config = RetryConfig::default();
+        assert_eq!(config.budget_wait(17), Duration::ZERO);
+        assert_eq!(config.budget_wait(300), Duration::from_secs(17));
+    }
+}
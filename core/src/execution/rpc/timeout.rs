@@ -0,0 +1,134 @@
+//! Timeouts and connection limits of the http client behind the execution provider.
+//!
+//! A provider that accepts the connection but never answers used to stall the call waiting on
+//! it, and the retry layer with it since it only acts on requests that complete. On native
+//! targets reqwest enforces the timeouts, while on wasm, where they do not apply to `fetch`,
+//! the request is raced against a timer. Either way a request running out of time fails with
+//! [`RequestTimeout`], which the retry layer retries like a rate limited one.
+
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket};
+use alloy::transports::{Transport, TransportError, TransportErrorKind, TransportFut};
+use serde::{Deserialize, Serialize};
+use tower::{Layer, Service};
+
+use crate::config::{ByteSize, ConfigDuration};
+
+/// Timeouts and pooling of provider requests, from the `execution_http` config table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpClientConfig {
+    /// Longest a request may take from sending it to reading the whole response.
+    pub request_timeout: ConfigDuration,
+    /// Longest establishing a connection may take. Ignored on wasm, where the browser
+    /// connects.
+    pub connect_timeout: ConfigDuration,
+    /// Idle connections kept open to the provider for reuse. Ignored on wasm.
+    pub max_idle_connections: usize,
+    /// Maximum size of a single response body. Only enforced on wasm, where bodies are
+    /// streamed and the request is aborted once this is exceeded.
+    pub max_response_size: ByteSize,
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout: ConfigDuration::from_secs(10),
+            connect_timeout: ConfigDuration::from_secs(5),
+            max_idle_connections: 32,
+            max_response_size: ByteSize::from_mb(128),
+        }
+    }
+}
+
+/// A provider request that got no complete response in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("timeout: no response from the provider within {0:?}")]
+pub struct RequestTimeout(pub Duration);
+
+/// Whether `err` is a request running out of time.
+pub fn is_timeout(err: &TransportError) -> bool {
+    matches!(
+        err,
+        TransportError::Transport(TransportErrorKind::Custom(err)) if err.is::<RequestTimeout>()
+    )
+}
+
+/// A transport layer failing requests with [`RequestTimeout`] once they take too long.
+#[derive(Debug, Clone)]
+pub struct TimeoutLayer {
+    timeout: Duration,
+}
+
+impl TimeoutLayer {
+    pub fn new(config: &HttpClientConfig) -> Self {
+        Self {
+            timeout: config.request_timeout.as_duration(),
+        }
+    }
+}
+
+impl<S> Layer<S> for TimeoutLayer {
+    type Service = TimeoutService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimeoutService {
+            inner,
+            timeout: self.timeout,
+        }
+    }
+}
+
+/// Fails the requests of the wrapped transport that take longer than the timeout.
+#[derive(Debug, Clone)]
+pub struct TimeoutService<S> {
+    inner: S,
+    timeout: Duration,
+}
+
+impl<S: Transport + Clone> Service<RequestPacket> for TimeoutService<S> {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        Box::pin(call_timed(self.inner.clone(), req, self.timeout))
+    }
+}
+
+/// Reqwest times the request out itself, its error is only named here.
+#[cfg(not(target_arch = "wasm32"))]
+async fn call_timed<S: Transport>(
+    mut inner: S,
+    req: RequestPacket,
+    timeout: Duration,
+) -> Result<ResponsePacket, TransportError> {
+    inner.call(req).await.map_err(|err| match &err {
+        TransportError::Transport(TransportErrorKind::Custom(source))
+            if source
+                .downcast_ref::<reqwest::Error>()
+                .is_some_and(reqwest::Error::is_timeout) =>
+        {
+            TransportErrorKind::custom(RequestTimeout(timeout))
+        }
+        _ => err,
+    })
+}
+
+/// Races the request against a timer, dropping the fetch once it runs out.
+#[cfg(target_arch = "wasm32")]
+async fn call_timed<S: Transport>(
+    mut inner: S,
+    req: RequestPacket,
+    timeout: Duration,
+) -> Result<ResponsePacket, TransportError> {
+    crate::time::timeout(timeout, inner.call(req))
+        .await
+        .unwrap_or_else(|_| Err(TransportErrorKind::custom(RequestTimeout(timeout))))
+}
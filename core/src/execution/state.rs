@@ -1,56 +1,119 @@
 use std::{
     collections::{BTreeMap, HashMap},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
 };
 
 use alloy::{
     consensus::BlockHeader,
     network::{primitives::HeaderResponse, BlockResponse},
     primitives::{Address, B256, U256},
-    rpc::types::{BlockTransactions, Filter},
+    rpc::types::{BlockTransactions, Filter, Log},
 };
 use eyre::{eyre, Result};
+use futures::{stream, Stream, StreamExt};
 use tokio::{
     select,
-    sync::{mpsc::Receiver, watch, RwLock},
+    sync::{broadcast, mpsc, mpsc::Receiver, watch, RwLock},
 };
 use tracing::{info, warn};
 
+use crate::database::Database;
 use crate::network_spec::NetworkSpec;
-use crate::types::BlockTag;
+use crate::time::{SystemTime, UNIX_EPOCH};
+use crate::types::{BeaconBlockMapping, BlockTag, HeadFeed, ReorgEvent};
 
+use super::backfill::{fetch_by_hash, fetch_by_number, BackfillConfig};
+use super::cache::ReceiptCache;
+use super::constants::{
+    DEEP_REORG_STREAM_CAPACITY, DEFAULT_CONFIRMATION_DEPTH, DEFAULT_PIN_BUDGET,
+    DEFAULT_RECEIPT_CACHE_BLOCKS, HEADER_STREAM_CAPACITY,
+};
+use super::errors::ExecutionError;
+use super::historical::{spawn_writer, HeaderStore, NumberedWrite};
+use super::limits::PayloadLimits;
+use super::orphans::{OrphanCacheConfig, ReorgLog};
+use super::pins::{BlockPin, PinTable, RetentionReason};
+use super::progress::SyncTracker;
 use super::rpc::ExecutionRpc;
+use super::stream::{header_stream, HeaderEvent, LagPolicy};
+use super::types::DeepReorg;
+use super::usage::{attribute, RequestCategory};
+
+/// The namespace beacon block mappings are kept in, keyed by big endian slot.
+pub const BEACON_MAPPINGS: &str = "beacon_mappings";
 
 #[derive(Clone)]
 pub struct State<N: NetworkSpec, R: ExecutionRpc<N>> {
     inner: Arc<RwLock<Inner<N, R>>>,
+    head_recv: watch::Receiver<Option<u64>>,
+    headers: HeaderChannels<N::HeaderResponse>,
+    pins: Arc<Mutex<PinTable>>,
+    /// Blocks under the latest `helios_confirmed` resolves to through this state.
+    confirmation_depth: u64,
+    reorgs: Arc<Mutex<ReorgLog<N::BlockResponse, Vec<N::ReceiptResponse>>>>,
+    receipts: Arc<Mutex<ReceiptCache<N::ReceiptResponse>>>,
+    backfill: Arc<Mutex<BackfillConfig>>,
+    historical: Arc<Mutex<HeaderStore<N>>>,
+    limits: Arc<Mutex<PayloadLimits>>,
+    progress: SyncTracker,
 }
 
 impl<N: NetworkSpec, R: ExecutionRpc<N>> State<N, R> {
     pub fn new(
-        mut block_recv: Receiver<N::BlockResponse>,
-        mut finalized_block_recv: watch::Receiver<Option<N::BlockResponse>>,
+        block_recv: Receiver<N::BlockResponse>,
+        finalized_block_recv: watch::Receiver<Option<N::BlockResponse>>,
         history_length: usize,
         rpc: &str,
     ) -> Self {
         let rpc = R::new(rpc).unwrap();
-        let inner = Arc::new(RwLock::new(Inner::new(history_length, rpc)));
-        let inner_ref = inner.clone();
+        Self::with_rpc(block_recv, finalized_block_recv, history_length, rpc)
+    }
+
+    pub fn with_rpc(
+        block_recv: Receiver<N::BlockResponse>,
+        finalized_block_recv: watch::Receiver<Option<N::BlockResponse>>,
+        history_length: usize,
+        rpc: R,
+    ) -> Self {
+        Self::unsynced(history_length, rpc).follow(block_recv, finalized_block_recv)
+    }
+
+    /// Applies the blocks and finalized blocks arriving on the channels in a task of its
+    /// own, until the block channel closes. The task pushes through a clone of this state, so
+    /// it resolves and reports reorgs with the confirmation depth set by then.
+    pub fn follow(
+        self,
+        mut block_recv: Receiver<N::BlockResponse>,
+        mut finalized_block_recv: watch::Receiver<Option<N::BlockResponse>>,
+    ) -> Self {
+        let inner_ref = self.inner.clone();
+        let state_ref = self.clone();
 
         #[cfg(not(target_arch = "wasm32"))]
         let run = tokio::spawn;
         #[cfg(target_arch = "wasm32")]
         let run = wasm_bindgen_futures::spawn_local;
 
-        run(async move {
+        run(attribute(RequestCategory::Sync, async move {
+            let mut finalized_open = true;
             loop {
                 select! {
                     block = block_recv.recv() => {
-                        if let Some(block) = block {
-                            inner_ref.write().await.push_block(block).await;
+                        match block {
+                            Some(block) => state_ref.push_block(block).await,
+                            // the consensus client shut down
+                            None => break,
                         }
                     },
-                    _ = finalized_block_recv.changed() => {
+                    changed = finalized_block_recv.changed(), if finalized_open => {
+                        if changed.is_err() {
+                            finalized_open = false;
+                            continue;
+                        }
+
                         let block = finalized_block_recv.borrow_and_update().clone();
                         if let Some(block) = block {
                             inner_ref.write().await.push_finalized_block(block);
@@ -59,30 +122,442 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> State<N, R> {
                     },
                 }
             }
-        });
 
-        Self { inner }
+            inner_ref.read().await.headers.close();
+        }));
+
+        self
+    }
+
+    /// Creates a state that is only updated through [State::push_block] and
+    /// [State::push_finalized_block], for callers that drive it themselves. It does not
+    /// backfill behind the first head unless set up [with a backfill](State::with_backfill).
+    pub fn unsynced(history_length: usize, rpc: R) -> Self {
+        let (head_send, head_recv) = watch::channel(None);
+        let headers = HeaderChannels::new();
+        let pins = Arc::new(Mutex::new(PinTable::new(DEFAULT_PIN_BUDGET)));
+        let reorgs = Arc::new(Mutex::new(ReorgLog::new(OrphanCacheConfig::default())));
+        let receipts = Arc::new(Mutex::new(ReceiptCache::new(DEFAULT_RECEIPT_CACHE_BLOCKS)));
+        let historical = Arc::new(Mutex::new(HeaderStore::default()));
+        let limits = Arc::new(Mutex::new(PayloadLimits::default()));
+        let shared = Shared {
+            headers: headers.clone(),
+            pins: pins.clone(),
+            reorgs: reorgs.clone(),
+            receipts: receipts.clone(),
+            historical: historical.clone(),
+            limits: limits.clone(),
+        };
+        let inner = Inner::new(history_length, rpc, head_send, shared);
+        let progress = inner.progress.clone();
+        let inner = Arc::new(RwLock::new(inner));
+        Self {
+            inner,
+            head_recv,
+            headers,
+            pins,
+            confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
+            reorgs,
+            receipts,
+            backfill: Arc::new(Mutex::new(BackfillConfig::disabled())),
+            historical,
+            limits,
+            progress,
+        }
+    }
+
+    /// Resolves [`BlockTag::Confirmed`] to `depth` blocks under the latest, for this state
+    /// and the clones made from it afterwards, and reports the reorgs of the blocks it pushes
+    /// deeper than that. Depths beyond the retained history never resolve.
+    pub fn with_confirmation_depth(mut self, depth: u64) -> Self {
+        self.confirmation_depth = depth;
+        self
+    }
+
+    pub fn confirmation_depth(&self) -> u64 {
+        self.confirmation_depth
+    }
+
+    /// Keeps as many orphaned blocks, for as long, as `config` allows, for this state and its
+    /// clones.
+    pub fn with_orphan_cache(self, config: OrphanCacheConfig) -> Self {
+        self.reorgs.lock().unwrap().configure(config);
+        self
+    }
+
+    /// Lets pins hold up to `blocks` distinct blocks at once, for this state and its clones.
+    pub fn with_pin_budget(self, blocks: usize) -> Self {
+        self.pins.lock().unwrap().set_budget(blocks);
+        self
+    }
+
+    /// Keeps the verified receipts of up to `blocks` blocks, for this state and its clones.
+    pub fn with_receipt_cache(self, blocks: usize) -> Self {
+        self.receipts.lock().unwrap().resize(blocks);
+        self
+    }
+
+    /// Backfills the window behind the first head as `config` allows, for this state and its
+    /// clones.
+    pub fn with_backfill(self, config: BackfillConfig) -> Self {
+        *self.backfill.lock().unwrap() = config;
+        self
+    }
+
+    /// Rejects blocks fetched to backfill the window over `limits`, for this state and its
+    /// clones.
+    pub fn with_payload_limits(self, limits: PayloadLimits) -> Self {
+        *self.limits.lock().unwrap() = limits;
+        self
+    }
+
+    /// Keeps the headers verified back from the window in `db` too, for this state and its
+    /// clones, resuming from those an earlier run kept there.
+    pub fn persist_historical_headers(&self, db: Arc<dyn Database>) -> Result<()> {
+        self.historical.lock().unwrap().persist(db)
+    }
+
+    /// Keeps the beacon block mappings in the [`BEACON_MAPPINGS`] namespace of `db` too,
+    /// taking over those an earlier run kept there. Entries that do not decode to a mapping
+    /// of their slot are deleted. Only a state nothing follows yet can take them over.
+    pub fn persist_beacon_mappings(&self, db: Arc<dyn Database>) -> Result<()> {
+        let mut inner = self
+            .inner
+            .try_write()
+            .map_err(|_| eyre!("state already in use"))?;
+
+        let mut malformed = Vec::new();
+        for entry in db.entries(BEACON_MAPPINGS)? {
+            match stored_mapping(db.as_ref(), &entry.key)? {
+                Some(mapping) => inner.insert_beacon_mapping(mapping),
+                None => malformed.push(entry.key),
+            }
+        }
+        if !malformed.is_empty() {
+            warn!(
+                target: "helios::execution",
+                count = malformed.len(),
+                "dropping malformed stored beacon mappings"
+            );
+            db.delete(BEACON_MAPPINGS, &malformed)?;
+        }
+
+        inner.beacon_writes = Some(spawn_writer(db, BEACON_MAPPINGS));
+        inner.prune_beacon_mappings();
+        Ok(())
     }
 
     pub async fn push_block(&self, block: N::BlockResponse) {
-        self.inner.write().await.push_block(block).await;
+        let mut inner = self.inner.write().await;
+        let first = inner.blocks.is_empty();
+        inner.push_block(block, self.confirmation_depth).await;
+
+        let config = *self.backfill.lock().unwrap();
+        if !first || !config.is_enabled() {
+            return;
+        }
+        let Some((&number, head)) = inner.blocks.last_key_value() else {
+            return;
+        };
+        // the window keeps a slot for the head, and the chain may be shorter than the window
+        let target = (inner.history_length as u64).saturating_sub(1).min(number);
+        if target == 0 {
+            return;
+        }
+
+        // reported before the lock is released, so the head never looks synced without it
+        self.progress.backfill_started(target);
+        let parent_hash = head.header().parent_hash();
+        let rpc = inner.rpc.clone();
+        drop(inner);
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let run = tokio::spawn;
+        #[cfg(target_arch = "wasm32")]
+        let run = wasm_bindgen_futures::spawn_local;
+
+        let state = self.clone();
+        run(attribute(RequestCategory::Background, async move {
+            let stopped = state
+                .backfill_window(rpc, config, number, parent_hash, target)
+                .await;
+            match stopped {
+                Some(number) => state.progress.backfill_stopped(number),
+                None => state.progress.backfill_finished(),
+            }
+        }));
+    }
+
+    /// Fetches the `target` blocks under the head `number`, `config.concurrency` at a time,
+    /// and applies them highest first, so the state never holds a gap. Each block is only
+    /// applied once it is the parent of the one applied before it. A block is retried as
+    /// configured, and one the provider served from another branch is fetched once more by
+    /// the expected hash, without retries of its own.
+    ///
+    /// Stops at the first block that cannot be fetched, returning its number, or once heads
+    /// applied in the meantime moved the window on or filled it. Nothing is fetched while
+    /// background requests are throttled.
+    async fn backfill_window(
+        &self,
+        rpc: R,
+        config: BackfillConfig,
+        number: u64,
+        mut parent_hash: B256,
+        target: u64,
+    ) -> Option<u64> {
+        let throttled = rpc
+            .usage()
+            .is_some_and(|usage| usage.throttles(RequestCategory::Background));
+        if throttled {
+            return Some(number - 1);
+        }
+
+        let limits = *self.limits.lock().unwrap();
+        let mut fetches = stream::iter((number - target..number).rev())
+            .map(|number| {
+                let (rpc, limits) = (&rpc, &limits);
+                async move {
+                    let block = fetch_by_number::<N, R>(rpc, number, &config, limits).await;
+                    (number, block)
+                }
+            })
+            .buffered(config.concurrency);
+
+        let once = BackfillConfig {
+            max_retries: 0,
+            ..config
+        };
+        while let Some((number, block)) = fetches.next().await {
+            let block = match block {
+                Some(block) if block.header().hash() == parent_hash => Some(block),
+                Some(_) => fetch_by_hash::<N, R>(&rpc, number, parent_hash, &once, &limits).await,
+                None => None,
+            };
+            let Some(block) = block.filter(|block| block.header().hash() == parent_hash) else {
+                warn!(target: "helios::execution", block = number, "bad block backfill");
+                return Some(number);
+            };
+
+            parent_hash = block.header().parent_hash();
+            if !self.inner.write().await.extend_window(block) {
+                break;
+            }
+            self.progress.block_backfilled();
+        }
+        None
+    }
+
+    /// The receipts of the block `hash`, if they were verified while it was held.
+    pub fn cached_receipts(&self, hash: B256) -> Option<Vec<N::ReceiptResponse>> {
+        self.receipts.lock().unwrap().get(hash)
+    }
+
+    /// Keeps `receipts`, verified against the receipts root of the block `hash`, until the
+    /// block leaves the state. Receipts of a block a reorg replaced in the meantime are
+    /// dropped.
+    pub async fn cache_receipts(&self, hash: B256, receipts: Vec<N::ReceiptResponse>) {
+        let inner = self.inner.read().await;
+        if inner.hashes.contains_key(&hash) {
+            self.receipts.lock().unwrap().insert(hash, receipts);
+        }
+    }
+
+    pub async fn push_finalized_block(&self, block: N::BlockResponse) {
+        self.inner.write().await.push_finalized_block(block);
+    }
+
+    pub async fn push_safe_block(&self, block: N::BlockResponse) {
+        self.inner.write().await.safe_block = Some(block);
+    }
+
+    /// Resolves [`BlockTag::Safe`] to the blocks delivered on `recv` as they arrive.
+    pub fn track_safe_blocks(&self, mut recv: watch::Receiver<Option<N::BlockResponse>>) {
+        let inner = self.inner.clone();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let run = tokio::spawn;
+        #[cfg(target_arch = "wasm32")]
+        let run = wasm_bindgen_futures::spawn_local;
+
+        run(async move {
+            while recv.changed().await.is_ok() {
+                let block = recv.borrow_and_update().clone();
+                if block.is_some() {
+                    inner.write().await.safe_block = block;
+                }
+            }
+        });
+    }
+
+    /// Follows the state of the feed of heads delivered on `recv`, which in
+    /// [`HeadFeed::SafeOnly`] resolves the tags following the head to the safe block.
+    pub fn track_head_feed(&self, mut recv: watch::Receiver<HeadFeed>) {
+        let inner = self.inner.clone();
+        let progress = self.progress.clone();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let run = tokio::spawn;
+        #[cfg(target_arch = "wasm32")]
+        let run = wasm_bindgen_futures::spawn_local;
+
+        run(async move {
+            while recv.changed().await.is_ok() {
+                let head_feed = *recv.borrow_and_update();
+                let mut inner = inner.write().await;
+                inner.head_feed = head_feed;
+                progress.head_feed_changed(head_feed);
+
+                #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+                if let Some(metrics) = inner.rpc.metrics() {
+                    metrics.record_head_feed(head_feed);
+                }
+            }
+        });
+    }
+
+    /// Changes to the number of the latest block, after its ancestors are backfilled.
+    pub fn head_updates(&self) -> watch::Receiver<Option<u64>> {
+        self.head_recv.clone()
+    }
+
+    /// How far applying the delivered blocks has come.
+    pub fn sync_progress(&self) -> &SyncTracker {
+        &self.progress
+    }
+
+    /// Headers of blocks as they become the verified head. The stream ends once the
+    /// consensus client stops delivering blocks.
+    pub fn block_stream(
+        &self,
+        policy: LagPolicy,
+    ) -> impl Stream<Item = HeaderEvent<N::HeaderResponse>> {
+        header_stream(
+            self.headers.blocks.subscribe(),
+            self.headers.closed.subscribe(),
+            policy,
+        )
+    }
+
+    /// Headers of blocks as they are finalized. The stream ends once the consensus client
+    /// stops delivering blocks.
+    pub fn finality_stream(
+        &self,
+        policy: LagPolicy,
+    ) -> impl Stream<Item = HeaderEvent<N::HeaderResponse>> {
+        header_stream(
+            self.headers.finalized.subscribe(),
+            self.headers.closed.subscribe(),
+            policy,
+        )
+    }
+
+    /// Ends the header streams, for drivers of the state whose consensus client stopped
+    /// delivering blocks.
+    pub(crate) fn close_header_streams(&self) {
+        self.headers.close();
+    }
+
+    /// Reorgs that orphan the confirmed block, as they happen. Consumers falling more than
+    /// the channel capacity behind miss the oldest ones.
+    pub fn deep_reorgs(&self) -> broadcast::Receiver<DeepReorg> {
+        self.headers.deep_reorgs.subscribe()
+    }
+
+    /// Records the beacon block of every execution block delivered on `recv`.
+    pub fn track_beacon_mappings(&self, mut recv: Receiver<BeaconBlockMapping>) {
+        let inner = self.inner.clone();
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let run = tokio::spawn;
+        #[cfg(target_arch = "wasm32")]
+        let run = wasm_bindgen_futures::spawn_local;
+
+        run(async move {
+            while let Some(mapping) = recv.recv().await {
+                inner.write().await.push_beacon_mapping(mapping);
+            }
+        });
+    }
+
+    pub async fn push_beacon_mapping(&self, mapping: BeaconBlockMapping) {
+        self.inner.write().await.push_beacon_mapping(mapping);
+    }
+
+    // beacon mapping fetch
+
+    pub async fn get_beacon_mapping_by_hash(&self, hash: B256) -> Option<BeaconBlockMapping> {
+        let inner = self.inner.read().await;
+        inner
+            .beacon_slots
+            .get(&hash)
+            .and_then(|slot| inner.beacon_mappings.get(slot))
+            .filter(|mapping| inner.is_canonical(mapping))
+            .cloned()
+    }
+
+    pub async fn get_beacon_mapping_by_slot(&self, slot: u64) -> Option<BeaconBlockMapping> {
+        let inner = self.inner.read().await;
+        inner
+            .beacon_mappings
+            .get(&slot)
+            .filter(|mapping| inner.is_canonical(mapping))
+            .cloned()
+    }
+
+    /// The oldest and latest slots with a recorded beacon block.
+    pub async fn beacon_slot_range(&self) -> Option<(u64, u64)> {
+        let inner = self.inner.read().await;
+        let oldest = inner.beacon_mappings.first_key_value()?.0;
+        let latest = inner.beacon_mappings.last_key_value()?.0;
+        Some((*oldest, *latest))
+    }
+
+    // pinning
+
+    /// Keeps the block at `tag` cached until the returned pin is dropped.
+    ///
+    /// Unpinned blocks are evicted least recently used first once the cache holds more than
+    /// `history_length` of them. Pinned blocks are held on top of those, up to a budget of
+    /// their own, and pinning a further block fails. Blocks orphaned by a reorg are removed
+    /// regardless of their pins, which are released with them.
+    pub async fn pin_block(
+        &self,
+        tag: BlockTag,
+        reason: RetentionReason,
+    ) -> Result<BlockPin, ExecutionError> {
+        // the read lock keeps the block from being evicted before the pin is in place
+        let inner = self.inner.read().await;
+        let block = inner
+            .block(tag, self.confirmation_depth)
+            .ok_or(ExecutionError::BlockNotFound(tag))?;
+        PinTable::pin(
+            &self.pins,
+            block.header().number(),
+            block.header().hash(),
+            reason,
+        )
+    }
+
+    /// Why the block at `number` is still cached, or `None` if it is not.
+    pub async fn retention(&self, number: u64) -> Option<Vec<RetentionReason>> {
+        let inner = self.inner.read().await;
+        let hash = inner.blocks.get(&number)?.header().hash();
+        let reasons = self.pins.lock().unwrap().reasons(&hash);
+        if reasons.is_empty() {
+            Some(vec![RetentionReason::RecentlyUsed])
+        } else {
+            Some(reasons)
+        }
     }
 
     // full block fetch
 
     pub async fn get_block(&self, tag: BlockTag) -> Option<N::BlockResponse> {
-        match tag {
-            BlockTag::Latest => self
-                .inner
-                .read()
-                .await
-                .blocks
-                .last_key_value()
-                .map(|entry| entry.1)
-                .cloned(),
-            BlockTag::Finalized => self.inner.read().await.finalized_block.clone(),
-            BlockTag::Number(number) => self.inner.read().await.blocks.get(&number).cloned(),
-        }
+        self.inner
+            .read()
+            .await
+            .block(tag, self.confirmation_depth)
+            .cloned()
     }
 
     pub async fn get_block_by_hash(&self, hash: B256) -> Option<N::BlockResponse> {
@@ -90,10 +565,26 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> State<N, R> {
         inner
             .hashes
             .get(&hash)
-            .and_then(|number| inner.blocks.get(number))
+            .and_then(|number| inner.get(*number))
             .cloned()
     }
 
+    /// A block a reorg replaced within the orphan cache limits. Orphans never resolve by
+    /// number or tag.
+    pub fn get_orphaned_block(&self, hash: B256) -> Option<N::BlockResponse> {
+        self.reorgs.lock().unwrap().get(hash).cloned()
+    }
+
+    /// The receipts verified for the orphaned block `hash` before a reorg replaced it.
+    pub fn orphaned_receipts(&self, hash: B256) -> Option<Vec<N::ReceiptResponse>> {
+        self.reorgs.lock().unwrap().receipts(hash).cloned()
+    }
+
+    /// The most recent reorgs, oldest first.
+    pub fn reorg_history(&self) -> Vec<ReorgEvent> {
+        self.reorgs.lock().unwrap().history()
+    }
+
     pub async fn get_blocks_after(&self, tag: BlockTag) -> Vec<N::BlockResponse> {
         let start_block = self.get_block(tag).await;
         if start_block.is_none() {
@@ -111,6 +602,16 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> State<N, R> {
         blocks
     }
 
+    pub async fn get_blocks_in_range(&self, start: u64, end: u64) -> Vec<N::BlockResponse> {
+        self.inner
+            .read()
+            .await
+            .blocks
+            .range(start..=end)
+            .map(|(_, v)| v.clone())
+            .collect()
+    }
+
     // transaction fetch
 
     pub async fn get_transaction(&self, hash: B256) -> Option<N::TransactionResponse> {
@@ -120,8 +621,7 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> State<N, R> {
             .get(&hash)
             .and_then(|loc| {
                 inner
-                    .blocks
-                    .get(&loc.block)
+                    .get(loc.block)
                     .and_then(|block| match &block.transactions() {
                         BlockTransactions::Full(txs) => txs.get(loc.index),
                         BlockTransactions::Hashes(_) => unreachable!(),
@@ -140,7 +640,7 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> State<N, R> {
         inner
             .hashes
             .get(&block_hash)
-            .and_then(|number| inner.blocks.get(number))
+            .and_then(|number| inner.get(*number))
             .and_then(|block| match &block.transactions() {
                 BlockTransactions::Full(txs) => txs.get(index as usize),
                 BlockTransactions::Hashes(_) => unreachable!(),
@@ -195,52 +695,295 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> State<N, R> {
     }
 
     pub async fn remove_filter(&self, id: &U256) -> bool {
-        self.inner.write().await.filters.remove(id).is_some()
+        let mut inner = self.inner.write().await;
+        inner.filter_logs.remove(id);
+        inner.filters.remove(id).is_some()
     }
 
     pub async fn get_filter(&self, id: &U256) -> Option<FilterType> {
         self.inner.read().await.filters.get(id).cloned()
     }
 
+    /// Forgets every filter, returning their ids.
+    pub async fn take_filters(&self) -> Vec<U256> {
+        let mut inner = self.inner.write().await;
+        inner.filter_logs.clear();
+        std::mem::take(&mut inner.filters).into_keys().collect()
+    }
+
+    /// Keeps the logs a log filter delivered, to report them as removed if a reorg orphans
+    /// their block. Logs of blocks before the held ones are forgotten.
+    pub async fn record_filter_logs(&self, id: U256, logs: &[Log]) {
+        let mut inner = self.inner.write().await;
+        let oldest = inner.oldest_block_number().unwrap_or_default();
+        let delivered = &mut inner.filter_logs.entry(id).or_default().delivered;
+        delivered.extend_from_slice(logs);
+        delivered.retain(|log| log.block_number.is_some_and(|number| number >= oldest));
+    }
+
+    /// The logs a log filter delivered from blocks orphaned since, marked as removed, in the
+    /// order they were emitted.
+    pub async fn take_removed_logs(&self, id: &U256) -> Vec<Log> {
+        let mut inner = self.inner.write().await;
+        let Some(logs) = inner.filter_logs.get_mut(id) else {
+            return Vec::new();
+        };
+        let mut removed = std::mem::take(&mut logs.removed);
+        removed.sort_by_key(|log| (log.block_number, log.log_index));
+        removed
+    }
+
     // misc
 
+    /// The number of the block `latest` resolves to, which is the safe block while heads are
+    /// [served at it](HeadFeed::SafeOnly).
     pub async fn latest_block_number(&self) -> Option<u64> {
         let inner = self.inner.read().await;
-        inner.blocks.last_key_value().map(|entry| *entry.0)
+        inner
+            .block(BlockTag::Latest, self.confirmation_depth)
+            .map(|block| block.header().number())
     }
 
+    /// The oldest block of the retained window, the unbroken run of blocks ending at the
+    /// latest one. Older pinned blocks are still served but lie outside the window.
     pub async fn oldest_block_number(&self) -> Option<u64> {
+        self.inner.read().await.oldest_block_number()
+    }
+
+    /// The header of block `number` if it is verified, whether the block is held or its
+    /// header was verified back from the window with [`State::insert_historical_headers`].
+    pub async fn get_verified_header(&self, number: u64) -> Option<N::HeaderResponse> {
+        let inner = self.inner.read().await;
+        match inner.get(number) {
+            Some(block) => Some(block.header().clone()),
+            None => self.historical.lock().unwrap().get(number).cloned(),
+        }
+    }
+
+    /// The verified header closest after block `number`, from which parent hashes lead back
+    /// to it.
+    pub async fn closest_verified_header_after(&self, number: u64) -> Option<N::HeaderResponse> {
+        let inner = self.inner.read().await;
+        let block = inner.blocks.range(number + 1..).next();
+        let historical = self.historical.lock().unwrap();
+        match (block, historical.first_after(number)) {
+            (Some((held, _)), Some(header)) if header.number() < *held => Some(header.clone()),
+            (Some((_, block)), _) => Some(block.header().clone()),
+            (None, header) => header.cloned(),
+        }
+    }
+
+    /// Keeps headers hash linked back from a verified block for later lookups, dropping
+    /// those more than `max_depth` blocks before the oldest block of the window.
+    pub async fn insert_historical_headers(
+        &self,
+        headers: impl IntoIterator<Item = N::HeaderResponse>,
+        max_depth: u64,
+    ) {
         let inner = self.inner.read().await;
-        inner.blocks.first_key_value().map(|entry| *entry.0)
+        let Some(oldest) = inner.oldest_block_number() else {
+            return;
+        };
+        let mut historical = self.historical.lock().unwrap();
+        historical.set_max_depth(max_depth);
+        for header in headers {
+            if header.number() < oldest {
+                historical.insert(header);
+            }
+        }
+        historical.drop_before(oldest.saturating_sub(max_depth));
     }
 }
 
 struct Inner<N: NetworkSpec, R: ExecutionRpc<N>> {
     blocks: BTreeMap<u64, N::BlockResponse>,
+    /// Headers of blocks before the window, verified by walking parent hashes back from it.
+    historical: Arc<Mutex<HeaderStore<N>>>,
+    limits: Arc<Mutex<PayloadLimits>>,
     finalized_block: Option<N::BlockResponse>,
+    safe_block: Option<N::BlockResponse>,
+    head_feed: HeadFeed,
     hashes: HashMap<B256, u64>,
     txs: HashMap<B256, TransactionLocation>,
+    beacon_mappings: BTreeMap<u64, BeaconBlockMapping>,
+    beacon_slots: HashMap<B256, u64>,
+    beacon_writes: Option<mpsc::UnboundedSender<NumberedWrite>>,
     filters: HashMap<U256, FilterType>,
+    filter_logs: HashMap<U256, FilterLogs>,
     history_length: usize,
+    head_send: watch::Sender<Option<u64>>,
+    headers: HeaderChannels<N::HeaderResponse>,
+    pins: Arc<Mutex<PinTable>>,
+    reorgs: Arc<Mutex<ReorgLog<N::BlockResponse, Vec<N::ReceiptResponse>>>>,
+    receipts: Arc<Mutex<ReceiptCache<N::ReceiptResponse>>>,
+    progress: SyncTracker,
+    usage: BlockUsage,
     rpc: R,
 }
 
+/// When each cached block was last used, for least recently used eviction. Blocks are used
+/// when they become the head and whenever they are read, which only takes the read lock.
+#[derive(Default)]
+struct BlockUsage {
+    clock: AtomicU64,
+    last_used: Mutex<HashMap<u64, u64>>,
+}
+
+impl BlockUsage {
+    fn touch(&self, number: u64) {
+        let tick = self.clock.fetch_add(1, Ordering::Relaxed) + 1;
+        self.last_used.lock().unwrap().insert(number, tick);
+    }
+
+    /// Backfilled blocks that were never read count as used before all others.
+    fn last_used(&self, number: u64) -> u64 {
+        let last_used = self.last_used.lock().unwrap();
+        last_used.get(&number).copied().unwrap_or_default()
+    }
+
+    fn forget(&self, number: u64) {
+        self.last_used.lock().unwrap().remove(&number);
+    }
+}
+
+/// What a [`State`] shares with its [`Inner`], so the state can be configured without the
+/// lock.
+struct Shared<N: NetworkSpec> {
+    headers: HeaderChannels<N::HeaderResponse>,
+    pins: Arc<Mutex<PinTable>>,
+    reorgs: Arc<Mutex<ReorgLog<N::BlockResponse, Vec<N::ReceiptResponse>>>>,
+    receipts: Arc<Mutex<ReceiptCache<N::ReceiptResponse>>>,
+    historical: Arc<Mutex<HeaderStore<N>>>,
+    limits: Arc<Mutex<PayloadLimits>>,
+}
+
+/// Senders backing the header streams of a [`State`], shared by its clones.
+#[derive(Clone)]
+struct HeaderChannels<H> {
+    blocks: broadcast::Sender<H>,
+    finalized: broadcast::Sender<H>,
+    deep_reorgs: broadcast::Sender<DeepReorg>,
+    closed: Arc<watch::Sender<bool>>,
+}
+
+impl<H: Clone> HeaderChannels<H> {
+    fn new() -> Self {
+        let (blocks, _) = broadcast::channel(HEADER_STREAM_CAPACITY);
+        let (finalized, _) = broadcast::channel(HEADER_STREAM_CAPACITY);
+        let (deep_reorgs, _) = broadcast::channel(DEEP_REORG_STREAM_CAPACITY);
+        let (closed, _) = watch::channel(false);
+        Self {
+            blocks,
+            finalized,
+            deep_reorgs,
+            closed: Arc::new(closed),
+        }
+    }
+
+    fn close(&self) {
+        self.closed.send_replace(true);
+    }
+}
+
 impl<N: NetworkSpec, R: ExecutionRpc<N>> Inner<N, R> {
-    pub fn new(history_length: usize, rpc: R) -> Self {
+    pub fn new(
+        history_length: usize,
+        rpc: R,
+        head_send: watch::Sender<Option<u64>>,
+        shared: Shared<N>,
+    ) -> Self {
+        let Shared {
+            headers,
+            pins,
+            reorgs,
+            receipts,
+            historical,
+            limits,
+        } = shared;
         Self {
             history_length,
             blocks: BTreeMap::default(),
+            historical,
+            limits,
             finalized_block: None,
+            safe_block: None,
+            head_feed: HeadFeed::default(),
             hashes: HashMap::default(),
             txs: HashMap::default(),
+            beacon_mappings: BTreeMap::default(),
+            beacon_slots: HashMap::default(),
+            beacon_writes: None,
             filters: HashMap::default(),
+            filter_logs: HashMap::default(),
+            head_send,
+            headers,
+            pins,
+            reorgs,
+            receipts,
+            progress: SyncTracker::default(),
+            usage: BlockUsage::default(),
             rpc,
         }
     }
 
-    pub async fn push_block(&mut self, block: N::BlockResponse) {
+    /// The block `tag` names, with `helios_confirmed` `confirmation_depth` blocks under the
+    /// latest.
+    fn block(&self, tag: BlockTag, confirmation_depth: u64) -> Option<&N::BlockResponse> {
+        match tag {
+            // a stalled feed leaves the head stale, rather than serve it as fresh the tags
+            // following it fall back to the safe block
+            BlockTag::Latest | BlockTag::Pending | BlockTag::Confirmed
+                if self.head_feed == HeadFeed::SafeOnly =>
+            {
+                self.block(BlockTag::Safe, confirmation_depth)
+            }
+            BlockTag::Latest | BlockTag::Pending => {
+                self.blocks.last_key_value().map(|entry| entry.1)
+            }
+            BlockTag::Finalized => self.finalized_block.as_ref(),
+            // finality overtakes the safe block, and stands in for it when there is none
+            BlockTag::Safe => self
+                .safe_block
+                .iter()
+                .chain(&self.finalized_block)
+                .max_by_key(|block| block.header().number()),
+            BlockTag::Confirmed => self.get(self.confirmed_number(confirmation_depth)?),
+            BlockTag::Number(number) => self.get(number),
+        }
+    }
+
+    /// The height `helios_confirmed` resolves to, which moves with the head and so onto the
+    /// new branch after a reorg.
+    fn confirmed_number(&self, confirmation_depth: u64) -> Option<u64> {
+        let latest = *self.blocks.last_key_value()?.0;
+        latest.checked_sub(confirmation_depth)
+    }
+
+    fn get(&self, number: u64) -> Option<&N::BlockResponse> {
+        let block = self.blocks.get(&number)?;
+        self.usage.touch(number);
+        Some(block)
+    }
+
+    pub async fn push_block(&mut self, block: N::BlockResponse, confirmation_depth: u64) {
         let block_number = block.header().number();
+        self.progress.head_delivered(block_number);
+        let old_branch = self
+            .blocks
+            .iter()
+            .map(|(number, block)| (*number, block.header().hash()))
+            .collect::<Vec<_>>();
+        let old_confirmed = self
+            .confirmed_number(confirmation_depth)
+            .and_then(|number| self.blocks.get(&number))
+            .map(|block| block.header().hash());
+
         if self.try_insert_tip(block) {
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            if let Some(metrics) = self.rpc.metrics() {
+                metrics.record_latest_block(block_number);
+            }
+
             let mut n = block_number;
 
             loop {
@@ -260,22 +1003,137 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> Inner<N, R> {
 
             if let (Some(parent), Some(child)) = (link_parent, link_child) {
                 if child.header().parent_hash() != parent.header().hash() {
-                    warn!("detected block reorganization");
                     self.prune_before(n);
                 }
             }
 
+            self.report_reorg(&old_branch, old_confirmed, confirmation_depth);
+
             self.prune();
+            self.prune_beacon_mappings();
+            self.prune_historical();
+
+            self.head_send.send_replace(Some(block_number));
+            self.progress.head_applied(block_number);
+            if self.window_is_full() {
+                self.progress.window_filled();
+            }
+            if let Some(block) = self.blocks.get(&block_number) {
+                // without subscribers there is nobody to deliver to
+                _ = self.headers.blocks.send(block.header().clone());
+            }
         }
     }
 
+    /// Compares the blocks held before a new head to those held now, recording a reorg in
+    /// the history and reporting a [`DeepReorg`] when the previously confirmed block was
+    /// replaced.
+    ///
+    /// Only replaced blocks count towards the depth, along with the old blocks above a new
+    /// head lower than the old one. Blocks pruned because the new branch could not be
+    /// backfilled over them may or may not have been orphaned.
+    fn report_reorg(
+        &self,
+        old_branch: &[(u64, B256)],
+        old_confirmed: Option<B256>,
+        confirmation_depth: u64,
+    ) {
+        let Some(&(old_head_number, old_head)) = old_branch.last() else {
+            return;
+        };
+        let Some((&new_head_number, new_head)) = self.blocks.last_key_value() else {
+            return;
+        };
+        let new_head = new_head.header().hash();
+        let replaced = old_branch
+            .iter()
+            .find(|(number, hash)| match self.blocks.get(number) {
+                Some(block) => block.header().hash() != *hash,
+                // a branch shorter than the old one orphans the blocks above its head
+                None => *number > new_head_number,
+            });
+        let Some(&(lowest_replaced, _)) = replaced else {
+            return;
+        };
+
+        let depth = old_head_number + 1 - lowest_replaced;
+        warn!(target: "helios::execution", depth, "detected block reorganization");
+
+        let orphaned_confirmed = old_confirmed.filter(|_| depth > confirmation_depth);
+
+        // every old block from the lowest replaced one up descends from it
+        self.reorgs.lock().unwrap().record(ReorgEvent {
+            depth,
+            fork_number: lowest_replaced,
+            old_chain: old_branch
+                .iter()
+                .filter(|(number, _)| *number >= lowest_replaced)
+                .map(|(_, hash)| *hash)
+                .collect(),
+            new_chain: self
+                .blocks
+                .range(lowest_replaced..)
+                .map(|(_, block)| block.header().hash())
+                .collect(),
+            deep: orphaned_confirmed.is_some(),
+            detected_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|elapsed| elapsed.as_secs())
+                .unwrap_or_default(),
+        });
+
+        let Some(orphaned_confirmed) = orphaned_confirmed else {
+            return;
+        };
+
+        warn!(
+            target: "helios::execution",
+            depth,
+            confirmation_depth,
+            "reorg orphaned the confirmed block {orphaned_confirmed}, deeper than the confirmation depth assumes"
+        );
+        _ = self.headers.deep_reorgs.send(DeepReorg {
+            depth,
+            confirmation_depth,
+            orphaned_confirmed,
+            old_head,
+            new_head,
+        });
+    }
+
     fn try_insert_tip(&mut self, block: N::BlockResponse) -> bool {
-        if let Some((num, _)) = self.blocks.last_key_value() {
-            if num > &block.header().number() {
+        let number = block.header().number();
+        let hash = block.header().hash();
+        let latest = self.blocks.last_key_value().map(|(latest, _)| *latest);
+        if latest.is_some_and(|latest| latest > number) {
+            // a head below the latest block is only taken if it is on another branch, as told
+            // by the block held at its height or by the child held after it
+            let conflicts = match self.blocks.get(&number) {
+                Some(held) => held.header().hash() != hash,
+                None => self
+                    .blocks
+                    .get(&(number + 1))
+                    .is_some_and(|child| child.header().parent_hash() != hash),
+            };
+            if !conflicts {
                 return false;
             }
         }
 
+        // the block at its height is orphaned by this one, along with every block above it,
+        // which descend from that one
+        let orphaned = self
+            .blocks
+            .range(number..)
+            .filter(|(n, held)| **n > number || held.header().hash() != hash)
+            .map(|(n, _)| *n)
+            .rev()
+            .collect::<Vec<_>>();
+        for n in orphaned {
+            self.orphan_block(n);
+        }
+
+        self.reorgs.lock().unwrap().forget(block.header().hash());
         self.hashes
             .insert(block.header().hash(), block.header().number());
         block
@@ -290,29 +1148,117 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> Inner<N, R> {
                 self.txs.insert(tx, location);
             });
 
+        self.usage.touch(block.header().number());
         self.blocks.insert(block.header().number(), block);
         true
     }
 
+    /// Evicts the least recently used unpinned blocks until at most `history_length` of them
+    /// remain.
     fn prune(&mut self) {
-        while self.blocks.len() > self.history_length {
-            if let Some((number, _)) = self.blocks.first_key_value() {
-                self.remove_block(*number);
+        loop {
+            let pins = self.pins.lock().unwrap();
+            let unpinned = self
+                .blocks
+                .iter()
+                .filter(|(_, block)| !pins.is_pinned(&block.header().hash()))
+                .map(|(number, _)| *number)
+                .collect::<Vec<_>>();
+            drop(pins);
+            if unpinned.len() <= self.history_length {
+                break;
+            }
+
+            let victim = unpinned
+                .into_iter()
+                .min_by_key(|number| (self.usage.last_used(*number), *number));
+            let Some(number) = victim else {
+                break;
+            };
+            if let Some(block) = self.remove_block(number) {
+                self.keep_evicted_header(block);
             }
         }
     }
 
+    /// Keeps the header of a block evicted from under the window with the historical
+    /// headers, once there are any, so those stay linked to the window as it moves on.
+    fn keep_evicted_header(&self, block: N::BlockResponse) {
+        let below_window = self
+            .oldest_block_number()
+            .is_some_and(|oldest| block.header().number() < oldest);
+        let mut historical = self.historical.lock().unwrap();
+        if below_window && !historical.is_empty() {
+            historical.insert(block.header().clone());
+        }
+    }
+
+    /// Drops the historical headers the window now covers, those further back than the
+    /// walk may go, and those from the first no longer hash linked to the header or block
+    /// after it, as after a reorg deeper than the window.
+    fn prune_historical(&mut self) {
+        let Some(oldest) = self.oldest_block_number() else {
+            return;
+        };
+        let mut historical = self.historical.lock().unwrap();
+        historical.drop_from(oldest);
+        let max_depth = historical.max_depth();
+        historical.drop_before(oldest.saturating_sub(max_depth));
+
+        if let Some(block) = self.blocks.get(&oldest) {
+            historical.drop_unlinked((oldest, block.header().parent_hash()));
+        }
+    }
+
+    /// Whether the window holds as many blocks as it keeps, or every block down to genesis.
+    fn window_is_full(&self) -> bool {
+        let (Some(oldest), Some((latest, _))) =
+            (self.oldest_block_number(), self.blocks.last_key_value())
+        else {
+            return false;
+        };
+        oldest == 0 || latest - oldest + 1 >= self.history_length as u64
+    }
+
+    fn oldest_block_number(&self) -> Option<u64> {
+        let mut oldest = *self.blocks.last_key_value()?.0;
+        for number in self.blocks.keys().rev().skip(1) {
+            if number + 1 != oldest {
+                break;
+            }
+            oldest = *number;
+        }
+        Some(oldest)
+    }
+
+    /// Drops the blocks before `n`, which are no longer linked to it. The block before `n`
+    /// is kept in the orphan cache when the block at `n` names another parent. Those further
+    /// back may be ancestors of both branches, so they are only dropped.
     fn prune_before(&mut self, n: u64) {
+        let replaced = match (self.blocks.get(&n), self.blocks.get(&(n - 1))) {
+            (Some(child), Some(parent)) => child.header().parent_hash() != parent.header().hash(),
+            _ => false,
+        };
+        if replaced {
+            self.orphan_block(n - 1);
+        }
+
         while let Some((oldest, _)) = self.blocks.first_key_value() {
             let oldest = *oldest;
             if oldest < n {
-                self.blocks.remove(&oldest);
+                self.remove_block(oldest);
             } else {
                 break;
             }
         }
     }
 
+    /// Fetches the parent of the block at `n` if it is not held yet, or replaces the held one
+    /// when the block descends from another branch. Returns whether a block was fetched.
+    ///
+    /// Replacing orphaned parents walks the new branch back to where it forks off, so the
+    /// blocks both branches share stay cached through a reorg. Fetching stops at the oldest
+    /// held block.
     async fn backfill_behind(&mut self, n: u64) -> Result<bool> {
         if self.blocks.len() < 2 {
             return Ok(false);
@@ -320,45 +1266,253 @@ impl<N: NetworkSpec, R: ExecutionRpc<N>> Inner<N, R> {
 
         if let Some(block) = self.blocks.get(&n) {
             let prev = n - 1;
-            if !self.blocks.contains_key(&prev) {
-                let backfilled = self.rpc.get_block(block.header().parent_hash()).await?;
-
-                if N::is_hash_valid(&backfilled)
-                    && block.header().parent_hash() == backfilled.header().hash()
-                {
-                    info!("backfilled: block={}", backfilled.header().number());
-                    self.blocks.insert(backfilled.header().number(), backfilled);
-                    Ok(true)
-                } else {
-                    warn!("bad block backfill");
-                    Err(eyre!("bad backfill"))
+            let parent_hash = block.header().parent_hash();
+            match self.blocks.get(&prev) {
+                Some(parent) if parent.header().hash() == parent_hash => return Ok(false),
+                Some(_) => {
+                    // nothing older to link the new branch to
+                    if self.blocks.range(..prev).next().is_none() {
+                        return Ok(false);
+                    }
+                    self.orphan_block(prev);
                 }
+                None => {}
+            }
+
+            let throttled = self
+                .rpc
+                .usage()
+                .is_some_and(|usage| usage.throttles(RequestCategory::Background));
+            if throttled {
+                return Err(eyre!("background requests throttled"));
+            }
+
+            let backfilled = attribute(
+                RequestCategory::Background,
+                self.rpc.get_block(parent_hash, true),
+            )
+            .await?;
+            self.limits.lock().unwrap().check_block(&backfilled)?;
+
+            if N::is_hash_valid(&backfilled) && parent_hash == backfilled.header().hash() {
+                info!("backfilled: block={}", backfilled.header().number());
+                self.insert_backfilled(backfilled);
+                Ok(true)
             } else {
-                Ok(false)
+                warn!("bad block backfill");
+                Err(eyre!("bad backfill"))
             }
         } else {
             Ok(false)
         }
     }
 
+    /// Inserts `block` under the oldest held block if it is its parent and the window has
+    /// room for it. Returns whether it was inserted.
+    fn extend_window(&mut self, block: N::BlockResponse) -> bool {
+        if self.blocks.len() >= self.history_length {
+            return false;
+        }
+        let Some((_, oldest)) = self.blocks.first_key_value() else {
+            return false;
+        };
+        if oldest.header().parent_hash() != block.header().hash() {
+            return false;
+        }
+
+        info!("backfilled: block={}", block.header().number());
+        self.insert_backfilled(block);
+        self.prune_historical();
+        true
+    }
+
+    fn insert_backfilled(&mut self, block: N::BlockResponse) {
+        let number = block.header().number();
+        self.reorgs.lock().unwrap().forget(block.header().hash());
+        self.hashes.insert(block.header().hash(), number);
+        block
+            .transactions()
+            .hashes()
+            .enumerate()
+            .for_each(|(index, tx)| {
+                self.txs.insert(
+                    tx,
+                    TransactionLocation {
+                        block: number,
+                        index,
+                    },
+                );
+            });
+        self.blocks.insert(number, block);
+    }
+
     pub fn push_finalized_block(&mut self, block: N::BlockResponse) {
-        if let Some(old_block) = self.blocks.get(&block.header().number()) {
-            if old_block.header().hash() != block.header().hash() {
-                self.blocks = BTreeMap::new();
+        let number = block.header().number();
+        let conflicts = self
+            .blocks
+            .get(&number)
+            .is_some_and(|held| held.header().hash() != block.header().hash());
+        if conflicts {
+            // the block at its height and those above were replaced, while those below may be
+            // ancestors of the finalized block, so they are only dropped
+            for n in self.blocks.keys().rev().copied().collect::<Vec<_>>() {
+                if n >= number {
+                    self.orphan_block(n);
+                } else {
+                    self.remove_block(n);
+                }
             }
+            self.usage.last_used.get_mut().unwrap().clear();
         }
 
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        if let Some(metrics) = self.rpc.metrics() {
+            metrics.record_finalized_block(block.header().number());
+        }
+
+        _ = self.headers.finalized.send(block.header().clone());
+        self.progress.finalized(block.header().number());
         self.finalized_block = Some(block);
+        self.prune_beacon_mappings();
     }
 
-    fn remove_block(&mut self, number: u64) {
-        if let Some(block) = self.blocks.remove(&number) {
-            self.hashes.remove(&block.header().hash());
-            block.transactions().hashes().for_each(|tx| {
-                self.txs.remove(&tx);
-            });
+    fn push_beacon_mapping(&mut self, mapping: BeaconBlockMapping) {
+        match serde_json::to_vec(&mapping) {
+            Ok(value) => self.write_beacon_mapping(NumberedWrite::Put(mapping.slot, value)),
+            Err(err) => {
+                warn!(target: "helios::execution", slot = mapping.slot, "beacon mapping not stored: {}", err)
+            }
+        }
+        self.insert_beacon_mapping(mapping);
+        self.prune_beacon_mappings();
+    }
+
+    fn insert_beacon_mapping(&mut self, mapping: BeaconBlockMapping) {
+        if let Some(old) = self.beacon_mappings.insert(mapping.slot, mapping.clone()) {
+            self.beacon_slots.remove(&old.execution_block_hash);
+        }
+        self.beacon_slots
+            .insert(mapping.execution_block_hash, mapping.slot);
+    }
+
+    fn write_beacon_mapping(&self, write: NumberedWrite) {
+        if let Some(writes) = &self.beacon_writes {
+            _ = writes.send(write);
+        }
+    }
+
+    fn block_hash_at(&self, number: u64) -> Option<B256> {
+        if let Some(block) = self.blocks.get(&number) {
+            return Some(block.header().hash());
+        }
+
+        self.finalized_block
+            .as_ref()
+            .filter(|block| block.header().number() == number)
+            .map(|block| block.header().hash())
+    }
+
+    /// Whether the mapping's execution block is the one held in state at its height.
+    fn is_canonical(&self, mapping: &BeaconBlockMapping) -> bool {
+        self.block_hash_at(mapping.execution_block_number) == Some(mapping.execution_block_hash)
+    }
+
+    /// Mappings can arrive ahead of their block, so only those at heights that were already
+    /// passed without a matching block are stale.
+    fn is_stale(&self, mapping: &BeaconBlockMapping) -> bool {
+        let number = mapping.execution_block_number;
+        match self.block_hash_at(number) {
+            Some(hash) => hash != mapping.execution_block_hash,
+            None => {
+                let latest = self.blocks.last_key_value().map(|(number, _)| *number);
+                let finalized = self.finalized_block.as_ref().map(|b| b.header().number());
+                latest.max(finalized).is_some_and(|latest| number <= latest)
+            }
         }
     }
+
+    /// Drops mappings of orphaned blocks and of blocks that left the retained window, keeping
+    /// at most `history_length` of them.
+    fn prune_beacon_mappings(&mut self) {
+        let stale = self
+            .beacon_mappings
+            .values()
+            .filter(|mapping| self.is_stale(mapping))
+            .map(|mapping| mapping.slot)
+            .collect::<Vec<_>>();
+
+        for slot in stale {
+            self.remove_beacon_mapping(slot);
+        }
+
+        while self.beacon_mappings.len() > self.history_length {
+            let Some(slot) = self.beacon_mappings.keys().next().copied() else {
+                break;
+            };
+            self.remove_beacon_mapping(slot);
+        }
+    }
+
+    fn remove_beacon_mapping(&mut self, slot: u64) {
+        if let Some(mapping) = self.beacon_mappings.remove(&slot) {
+            self.beacon_slots.remove(&mapping.execution_block_hash);
+            self.write_beacon_mapping(NumberedWrite::Delete(vec![slot]));
+        }
+    }
+
+    fn remove_block(&mut self, number: u64) -> Option<N::BlockResponse> {
+        self.usage.forget(number);
+        let block = self.blocks.remove(&number)?;
+        self.hashes.remove(&block.header().hash());
+        self.receipts.lock().unwrap().remove(block.header().hash());
+        // eviction passes over pinned blocks, so only a reorg or a prune drops them
+        self.pins.lock().unwrap().release(&block.header().hash());
+        block.transactions().hashes().for_each(|tx| {
+            self.txs.remove(&tx);
+        });
+        Some(block)
+    }
+
+    /// Removes the block at `number`, which a reorg replaced, keeping it in the orphan cache.
+    fn orphan_block(&mut self, number: u64) {
+        let Some(hash) = self.blocks.get(&number).map(|block| block.header().hash()) else {
+            return;
+        };
+        let receipts = self.receipts.lock().unwrap().get(hash);
+        if let Some(block) = self.remove_block(number) {
+            self.rewind_filters(number, hash);
+            self.reorgs.lock().unwrap().orphan(block, receipts);
+        }
+    }
+
+    /// Flags the logs filters delivered from the orphaned block `hash` as removed, and moves
+    /// block filters back before `number` so they report the block replacing it.
+    fn rewind_filters(&mut self, number: u64, hash: B256) {
+        for logs in self.filter_logs.values_mut() {
+            let (removed, kept) = std::mem::take(&mut logs.delivered)
+                .into_iter()
+                .partition::<Vec<_>, _>(|log| log.block_hash == Some(hash));
+            logs.delivered = kept;
+            logs.removed.extend(removed.into_iter().map(|log| Log {
+                removed: true,
+                ..log
+            }));
+        }
+
+        for filter in self.filters.values_mut() {
+            if let FilterType::NewBlock(last) = filter {
+                *last = (*last).min(number.saturating_sub(1));
+            }
+        }
+    }
+}
+
+/// Logs a log filter delivered from held blocks, and those of them whose block was orphaned
+/// since, until they are delivered again as removed.
+#[derive(Default)]
+struct FilterLogs {
+    delivered: Vec<Log>,
+    removed: Vec<Log>,
 }
 
 struct TransactionLocation {
@@ -374,3 +1528,15 @@ pub enum FilterType {
     NewBlock(u64),
     PendingTransactions,
 }
+
+/// The mapping stored under `key`, if it is one of the slot the key names.
+fn stored_mapping(db: &dyn Database, key: &[u8]) -> Result<Option<BeaconBlockMapping>> {
+    let Ok(slot) = <[u8; 8]>::try_from(key).map(u64::from_be_bytes) else {
+        return Ok(None);
+    };
+    let Some(value) = db.get(BEACON_MAPPINGS, key)? else {
+        return Ok(None);
+    };
+    let mapping = serde_json::from_slice::<BeaconBlockMapping>(&value).ok();
+    Ok(mapping.filter(|mapping| mapping.slot == slot))
+}
@@ -0,0 +1,78 @@
+use futures::stream::{self, Stream};
+use tokio::select;
+use tokio::sync::broadcast::{
+    self,
+    error::{RecvError, TryRecvError},
+};
+use tokio::sync::watch;
+
+/// How a header stream treats a consumer that falls more than the channel capacity behind.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// Drop the oldest undelivered headers and yield a [`HeaderEvent::Gap`] in their place.
+    #[default]
+    Skip,
+    /// Yield a [`HeaderEvent::Lagged`] and end the stream.
+    Fail,
+}
+
+/// An item of a verified header stream.
+///
+/// Headers come in the order they were verified. After a reorg the stream continues with the
+/// head of the new branch, which consumers can detect by its parent hash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderEvent<H> {
+    Header(H),
+    /// `missed` headers were dropped because the consumer fell behind.
+    Gap {
+        missed: u64,
+    },
+    /// `missed` headers were dropped and the stream ended, under [`LagPolicy::Fail`].
+    Lagged {
+        missed: u64,
+    },
+}
+
+/// Streams headers from `headers` until the consumer lags under [`LagPolicy::Fail`], or the
+/// client shuts down. Headers already buffered at shutdown are still delivered.
+pub(crate) fn header_stream<H: Clone>(
+    headers: broadcast::Receiver<H>,
+    closed: watch::Receiver<bool>,
+    policy: LagPolicy,
+) -> impl Stream<Item = HeaderEvent<H>> {
+    stream::unfold(Some((headers, closed)), move |channels| async move {
+        let (mut headers, mut closed) = channels?;
+        loop {
+            let received = match headers.try_recv() {
+                Err(TryRecvError::Empty) => {
+                    if *closed.borrow_and_update() {
+                        return None;
+                    }
+
+                    select! {
+                        received = headers.recv() => received,
+                        changed = closed.changed() => {
+                            // a dropped sender means the state went away with the client
+                            if changed.is_err() {
+                                return None;
+                            }
+                            continue;
+                        }
+                    }
+                }
+                Err(TryRecvError::Closed) => Err(RecvError::Closed),
+                Err(TryRecvError::Lagged(missed)) => Err(RecvError::Lagged(missed)),
+                Ok(header) => Ok(header),
+            };
+
+            return match received {
+                Ok(header) => Some((HeaderEvent::Header(header), Some((headers, closed)))),
+                Err(RecvError::Lagged(missed)) => match policy {
+                    LagPolicy::Skip => Some((HeaderEvent::Gap { missed }, Some((headers, closed)))),
+                    LagPolicy::Fail => Some((HeaderEvent::Lagged { missed }, None)),
+                },
+                Err(RecvError::Closed) => None,
+            };
+        }
+    })
+}
@@ -0,0 +1,86 @@
+use alloy::primitives::B256;
+use alloy::rpc::types::{Filter, Log};
+use serde::{Deserialize, Serialize};
+
+use super::errors::ExecutionError;
+
+/// What a resumable subscription delivers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubscriptionTopic {
+    NewHeads,
+    Logs(Filter),
+}
+
+/// Position in the verified chain up to which a subscriber has seen events.
+///
+/// Without a `log_index` the whole block has been delivered. With one, the block's logs
+/// up to and including that index have been delivered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionCursor {
+    pub block_number: u64,
+    pub block_hash: B256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_index: Option<u64>,
+}
+
+/// Optional parameters of `helios_subscribe`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionParams {
+    /// Required for `logs` subscriptions. Block ranges are ignored.
+    pub filter: Option<Filter>,
+    /// The last cursor the subscriber received, to resume from.
+    pub cursor: Option<SubscriptionCursor>,
+}
+
+/// A notification of a resumable subscription.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SubscriptionEvent<B> {
+    NewHead {
+        cursor: SubscriptionCursor,
+        result: B,
+    },
+    Log {
+        cursor: SubscriptionCursor,
+        result: Log,
+    },
+    /// Events in `from_block..=to_block` could not be delivered, either because the blocks
+    /// are no longer retained or because catching up would exceed the backfill budget.
+    #[serde(rename_all = "camelCase")]
+    Gap { from_block: u64, to_block: u64 },
+}
+
+/// Events to deliver, and the cursor to resume from once they are delivered.
+#[derive(Debug, Clone)]
+pub struct SubscriptionBatch<B> {
+    pub events: Vec<SubscriptionEvent<B>>,
+    pub cursor: Option<SubscriptionCursor>,
+}
+
+/// A notification of an `eth_subscribe` subscription, which carries the header or log alone.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SubscriptionItem<B> {
+    Header(B),
+    Log(Log),
+}
+
+impl<B> SubscriptionEvent<B> {
+    /// The plain notification of the event. Logs of orphaned blocks pass as logs marked
+    /// removed. A gap has no plain notification, and is an error ending the subscription,
+    /// since its subscriber cannot tell events were missed otherwise.
+    pub fn into_item(self) -> Result<SubscriptionItem<B>, ExecutionError> {
+        match self {
+            Self::NewHead { result, .. } => Ok(SubscriptionItem::Header(result)),
+            Self::Log { result, .. } => Ok(SubscriptionItem::Log(result)),
+            Self::Gap {
+                from_block,
+                to_block,
+            } => Err(ExecutionError::MissedSubscriptionEvents(
+                from_block, to_block,
+            )),
+        }
+    }
+}
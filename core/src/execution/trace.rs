@@ -0,0 +1,580 @@
+//! Tracers for `debug_traceCall`, run as revm inspectors over the verified evm.
+//!
+//! The `callTracer` reports the tree of call frames the way geth does. Without a tracer the
+//! opcode level struct logger runs instead, which records every step of the execution and is
+//! capped at [`MAX_STRUCT_LOG_BYTES`] of encoded logs. `eth_createAccessList` runs the
+//! [`CoinbaseTracer`], since revm loads the coinbase to pay it whether or not the execution
+//! reaches it.
+
+use alloy::primitives::{Address, Bytes, U256, U64};
+use alloy::sol_types::decode_revert_reason;
+use revm::{
+    interpreter::{
+        opcode, CallInputs, CallOutcome, CallScheme, CreateInputs, CreateOutcome, CreateScheme,
+        InstructionResult, Interpreter, OpCode,
+    },
+    primitives::ExecutionResult,
+    Database, EvmContext, Inspector,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::execution::bundle::StateOverride;
+use crate::execution::constants::MAX_STRUCT_LOG_BYTES;
+use crate::execution::errors::EvmError;
+
+const CALL_TRACER: &str = "callTracer";
+
+/// The tracing options of `debug_traceCall`, as its third parameter.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceOptions {
+    /// `callTracer`, or none for the struct logger.
+    pub tracer: Option<String>,
+    #[serde(default)]
+    pub tracer_config: CallTracerConfig,
+    /// Leaves the stack out of the struct logs.
+    #[serde(default)]
+    pub disable_stack: bool,
+    /// Most struct logs recorded, further steps are left out.
+    pub limit: Option<usize>,
+    pub state_overrides: Option<StateOverride>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallTracerConfig {
+    /// Only reports the top call frame.
+    #[serde(default)]
+    pub only_top_call: bool,
+}
+
+/// The trace of a call, in the format of the tracer that produced it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TraceResult {
+    Call(CallFrame),
+    StructLogs(StructLogTrace),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CallKind {
+    Call,
+    StaticCall,
+    DelegateCall,
+    CallCode,
+    Create,
+    Create2,
+    SelfDestruct,
+}
+
+/// A call frame of the `callTracer`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallFrame {
+    #[serde(rename = "type")]
+    pub kind: CallKind,
+    pub from: Address,
+    /// The called address, or the created one, which failed creations do not have.
+    pub to: Option<Address>,
+    /// The value sent, which static and delegate calls do not have.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    pub gas: U64,
+    pub gas_used: U64,
+    pub input: Bytes,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<Bytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revert_reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<CallFrame>,
+}
+
+/// The trace of the struct logger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLogTrace {
+    pub gas: u64,
+    pub failed: bool,
+    /// The output of the call, hex encoded without a prefix as geth does.
+    pub return_value: String,
+    pub struct_logs: Vec<StructLog>,
+}
+
+/// A step of the struct logger, with the gas left before it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructLog {
+    pub pc: u64,
+    pub op: String,
+    pub gas: u64,
+    pub gas_cost: u64,
+    pub depth: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stack: Option<Vec<U256>>,
+}
+
+impl StructLog {
+    /// About the length of the json of the log: the fields around its opcode name, and a
+    /// quoted, prefixed hex word for each stack item.
+    fn encoded_size(&self) -> usize {
+        96 + self.op.len() + self.stack.as_ref().map_or(0, |stack| stack.len() * 69)
+    }
+}
+
+/// The tracer asked for by the [`TraceOptions`], inspecting an execution.
+#[derive(Debug)]
+pub enum Tracer {
+    Call(CallTracer),
+    StructLog(StructLogger),
+}
+
+impl Tracer {
+    pub fn new(options: &TraceOptions) -> Result<Self, EvmError> {
+        match options.tracer.as_deref() {
+            Some(CALL_TRACER) => Ok(Self::Call(CallTracer {
+                only_top_call: options.tracer_config.only_top_call,
+                ..Default::default()
+            })),
+            None => Ok(Self::StructLog(StructLogger {
+                disable_stack: options.disable_stack,
+                limit: options.limit,
+                ..Default::default()
+            })),
+            Some(tracer) => Err(EvmError::UnsupportedTracer(tracer.to_string())),
+        }
+    }
+
+    /// The trace of the execution that ended with `result`, out of `gas_limit`.
+    pub fn finish(self, result: &ExecutionResult, gas_limit: u64) -> Result<TraceResult, EvmError> {
+        match self {
+            Self::Call(tracer) => {
+                let mut root = tracer
+                    .root
+                    .ok_or_else(|| EvmError::Generic("no call frame traced".to_string()))?;
+                // the transaction gas, including the intrinsic gas the frame does not see
+                root.gas = U64::from(gas_limit);
+                root.gas_used = U64::from(result.gas_used());
+                Ok(TraceResult::Call(root))
+            }
+            Self::StructLog(logger) => {
+                if logger.overflowed {
+                    return Err(EvmError::TraceTooLarge(MAX_STRUCT_LOG_BYTES));
+                }
+                Ok(TraceResult::StructLogs(StructLogTrace {
+                    gas: result.gas_used(),
+                    failed: !result.is_success(),
+                    return_value: hex::encode(result.output().cloned().unwrap_or_default()),
+                    struct_logs: logger.logs,
+                }))
+            }
+        }
+    }
+}
+
+/// An inspector over an execution that is run again from the start when it reads state the
+/// evm is missing.
+pub trait Restart {
+    /// Drops what was recorded, for executing again from the start.
+    fn reset(&mut self);
+}
+
+impl Restart for Tracer {
+    fn reset(&mut self) {
+        match self {
+            Self::Call(tracer) => {
+                tracer.frames.clear();
+                tracer.root = None;
+            }
+            Self::StructLog(logger) => {
+                logger.logs.clear();
+                logger.size = 0;
+                logger.depth = 0;
+                logger.overflowed = false;
+            }
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for Tracer {
+    fn step(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        if let Self::StructLog(logger) = self {
+            logger.step(interp, context);
+        }
+    }
+
+    fn step_end(&mut self, interp: &mut Interpreter, context: &mut EvmContext<DB>) {
+        if let Self::StructLog(logger) = self {
+            logger.step_end(interp, context);
+        }
+    }
+
+    fn call(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        match self {
+            Self::Call(tracer) => tracer.call(context, inputs),
+            Self::StructLog(logger) => logger.call(context, inputs),
+        }
+    }
+
+    fn call_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        match self {
+            Self::Call(tracer) => tracer.call_end(context, inputs, outcome),
+            Self::StructLog(logger) => logger.call_end(context, inputs, outcome),
+        }
+    }
+
+    fn create(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        match self {
+            Self::Call(tracer) => tracer.create(context, inputs),
+            Self::StructLog(logger) => logger.create(context, inputs),
+        }
+    }
+
+    fn create_end(
+        &mut self,
+        context: &mut EvmContext<DB>,
+        inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        match self {
+            Self::Call(tracer) => tracer.create_end(context, inputs, outcome),
+            Self::StructLog(logger) => logger.create_end(context, inputs, outcome),
+        }
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if let Self::Call(tracer) = self {
+            Inspector::<DB>::selfdestruct(tracer, contract, target, value);
+        }
+    }
+}
+
+/// Records whether an execution reaches the coinbase, reading its account or calling into
+/// it, rather than only paying it the fees.
+#[derive(Debug)]
+pub struct CoinbaseTracer {
+    coinbase: Address,
+    touched: bool,
+}
+
+impl CoinbaseTracer {
+    pub fn new(coinbase: Address) -> Self {
+        Self {
+            coinbase,
+            touched: false,
+        }
+    }
+
+    pub fn touched(&self) -> bool {
+        self.touched
+    }
+}
+
+impl Restart for CoinbaseTracer {
+    fn reset(&mut self) {
+        self.touched = false;
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CoinbaseTracer {
+    fn step(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        let reads_account = matches!(
+            interp.current_opcode(),
+            opcode::BALANCE | opcode::EXTCODESIZE | opcode::EXTCODECOPY | opcode::EXTCODEHASH
+        );
+        if reads_account
+            && interp
+                .stack
+                .peek(0)
+                .is_ok_and(|word| Address::from_word(word.into()) == self.coinbase)
+        {
+            self.touched = true;
+        }
+    }
+
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        if inputs.target_address == self.coinbase || inputs.bytecode_address == self.coinbase {
+            self.touched = true;
+        }
+        None
+    }
+
+    fn selfdestruct(&mut self, _contract: Address, target: Address, _value: U256) {
+        if target == self.coinbase {
+            self.touched = true;
+        }
+    }
+}
+
+/// Builds the tree of call frames, keeping the frames entered but not yet returned from.
+#[derive(Debug, Default)]
+pub struct CallTracer {
+    only_top_call: bool,
+    frames: Vec<CallFrame>,
+    root: Option<CallFrame>,
+}
+
+impl CallTracer {
+    fn enter(&mut self, frame: CallFrame) {
+        self.frames.push(frame);
+    }
+
+    /// Closes the innermost open frame with the result of its execution.
+    fn exit(&mut self, result: InstructionResult, output: Bytes, gas_used: u64) {
+        let Some(mut frame) = self.frames.pop() else {
+            return;
+        };
+        frame.gas_used = U64::from(gas_used);
+        if result.is_revert() {
+            frame.error = Some("execution reverted".to_string());
+            frame.revert_reason = decode_revert_reason(&output);
+        } else if !result.is_ok() {
+            frame.error = Some(halt_reason(result));
+        }
+        frame.output = (!output.is_empty()).then_some(output);
+
+        match self.frames.last_mut() {
+            Some(parent) if !self.only_top_call => parent.calls.push(frame),
+            Some(_) => {}
+            None => self.root = Some(frame),
+        }
+    }
+}
+
+impl<DB: Database> Inspector<DB> for CallTracer {
+    fn call(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        let kind = match inputs.scheme {
+            CallScheme::Call | CallScheme::ExtCall => CallKind::Call,
+            CallScheme::StaticCall | CallScheme::ExtStaticCall => CallKind::StaticCall,
+            CallScheme::DelegateCall | CallScheme::ExtDelegateCall => CallKind::DelegateCall,
+            CallScheme::CallCode => CallKind::CallCode,
+        };
+        // delegated code runs in the context of the contract delegating to it, whose address
+        // revm passes as the target
+        let from = match kind {
+            CallKind::DelegateCall | CallKind::CallCode => inputs.target_address,
+            _ => inputs.caller,
+        };
+        let value =
+            matches!(kind, CallKind::Call | CallKind::CallCode).then(|| inputs.call_value());
+
+        self.enter(CallFrame {
+            kind,
+            from,
+            to: Some(inputs.bytecode_address),
+            value,
+            gas: U64::from(inputs.gas_limit),
+            gas_used: U64::ZERO,
+            input: inputs.input.clone(),
+            output: None,
+            error: None,
+            revert_reason: None,
+            calls: Vec::new(),
+        });
+        None
+    }
+
+    fn call_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        let result = &outcome.result;
+        self.exit(result.result, result.output.clone(), result.gas.spent());
+        outcome
+    }
+
+    fn create(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        let kind = match inputs.scheme {
+            CreateScheme::Create => CallKind::Create,
+            CreateScheme::Create2 { .. } => CallKind::Create2,
+        };
+        self.enter(CallFrame {
+            kind,
+            from: inputs.caller,
+            to: None,
+            value: Some(inputs.value),
+            gas: U64::from(inputs.gas_limit),
+            gas_used: U64::ZERO,
+            input: inputs.init_code.clone(),
+            output: None,
+            error: None,
+            revert_reason: None,
+            calls: Vec::new(),
+        });
+        None
+    }
+
+    fn create_end(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.to = outcome.address;
+        }
+        let result = &outcome.result;
+        self.exit(result.result, result.output.clone(), result.gas.spent());
+        outcome
+    }
+
+    fn selfdestruct(&mut self, contract: Address, target: Address, value: U256) {
+        if self.only_top_call {
+            return;
+        }
+        if let Some(parent) = self.frames.last_mut() {
+            parent.calls.push(CallFrame {
+                kind: CallKind::SelfDestruct,
+                from: contract,
+                to: Some(target),
+                value: Some(value),
+                gas: U64::ZERO,
+                gas_used: U64::ZERO,
+                input: Bytes::new(),
+                output: None,
+                error: None,
+                revert_reason: None,
+                calls: Vec::new(),
+            });
+        }
+    }
+}
+
+/// Records every step of the execution, up to the limit of steps asked for or
+/// [`MAX_STRUCT_LOG_BYTES`] of logs.
+#[derive(Debug, Default)]
+pub struct StructLogger {
+    disable_stack: bool,
+    limit: Option<usize>,
+    logs: Vec<StructLog>,
+    /// The encoded size of the logs recorded.
+    size: usize,
+    depth: u64,
+    /// The logs of the execution took more than [`MAX_STRUCT_LOG_BYTES`].
+    overflowed: bool,
+}
+
+impl StructLogger {
+    fn step<DB: Database>(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        if self.limit.is_some_and(|limit| self.logs.len() >= limit) {
+            return;
+        }
+        if self.overflowed {
+            return;
+        }
+
+        let opcode = interp.current_opcode();
+        let log = StructLog {
+            pc: interp.program_counter() as u64,
+            op: OpCode::new(opcode).map_or_else(
+                || format!("opcode {opcode:#04x} not defined"),
+                |op| op.as_str().to_string(),
+            ),
+            gas: interp.gas.remaining(),
+            gas_cost: 0,
+            depth: self.depth,
+            stack: (!self.disable_stack).then(|| interp.stack.data().clone()),
+        };
+        self.size += log.encoded_size();
+        if self.size > MAX_STRUCT_LOG_BYTES {
+            self.overflowed = true;
+            return;
+        }
+        self.logs.push(log);
+    }
+
+    fn step_end<DB: Database>(&mut self, interp: &mut Interpreter, _context: &mut EvmContext<DB>) {
+        if let Some(log) = self.logs.last_mut().filter(|log| log.depth == self.depth) {
+            log.gas_cost = log.gas.saturating_sub(interp.gas.remaining());
+        }
+    }
+
+    fn call<DB: Database>(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CallInputs,
+    ) -> Option<CallOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn call_end<DB: Database>(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CallInputs,
+        outcome: CallOutcome,
+    ) -> CallOutcome {
+        self.depth -= 1;
+        outcome
+    }
+
+    fn create<DB: Database>(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &mut CreateInputs,
+    ) -> Option<CreateOutcome> {
+        self.depth += 1;
+        None
+    }
+
+    fn create_end<DB: Database>(
+        &mut self,
+        _context: &mut EvmContext<DB>,
+        _inputs: &CreateInputs,
+        outcome: CreateOutcome,
+    ) -> CreateOutcome {
+        self.depth -= 1;
+        outcome
+    }
+}
+
+/// The error geth reports for a frame halted by `result`.
+fn halt_reason(result: InstructionResult) -> String {
+    match result {
+        InstructionResult::OutOfGas
+        | InstructionResult::MemoryOOG
+        | InstructionResult::MemoryLimitOOG
+        | InstructionResult::PrecompileOOG
+        | InstructionResult::InvalidOperandOOG => "out of gas".to_string(),
+        InstructionResult::OpcodeNotFound | InstructionResult::InvalidFEOpcode => {
+            "invalid opcode".to_string()
+        }
+        InstructionResult::StackUnderflow => "stack underflow".to_string(),
+        InstructionResult::StackOverflow => "stack limit reached".to_string(),
+        InstructionResult::InvalidJump => "invalid jump destination".to_string(),
+        InstructionResult::StateChangeDuringStaticCall => "write protection".to_string(),
+        InstructionResult::OutOfFunds => "insufficient balance for transfer".to_string(),
+        InstructionResult::CallTooDeep => "max call depth exceeded".to_string(),
+        result => format!("{result:?}"),
+    }
+}
@@ -1,7 +1,10 @@
 use std::collections::HashMap;
 
+use alloy::consensus::BlockHeader;
 use alloy::primitives::{B256, U256};
 
+use crate::types::{AnchorTrust, BlockTag};
+
 #[derive(Default, Debug, Clone)]
 pub struct Account {
     pub balance: U256,
@@ -11,3 +14,49 @@ pub struct Account {
     pub storage_hash: B256,
     pub slots: HashMap<B256, U256>,
 }
+
+/// The block whose state root reads are verified against.
+#[derive(Debug, Clone)]
+pub enum StateAnchor<H> {
+    /// A block held in state, verified through consensus.
+    Tag(BlockTag),
+    /// A header supplied by the caller, trusted once it hashes to its own `hash` field.
+    Header(H),
+    /// A header already checked, and what it is trusted through, so it is not hashed again
+    /// on every read.
+    Verified(H, AnchorTrust),
+}
+
+impl<H: BlockHeader> StateAnchor<H> {
+    /// The header anchored to and what it is trusted through, once it has been checked.
+    pub fn verified(&self) -> Option<(&H, AnchorTrust)> {
+        match self {
+            StateAnchor::Verified(header, trust) => Some((header, *trust)),
+            _ => None,
+        }
+    }
+
+    /// The tag to ask the execution provider for the anchored block with.
+    pub fn block_tag(&self) -> BlockTag {
+        match self {
+            StateAnchor::Tag(tag) => *tag,
+            StateAnchor::Header(header) | StateAnchor::Verified(header, _) => {
+                BlockTag::Number(header.number())
+            }
+        }
+    }
+}
+
+/// A reorg that orphaned the block [`BlockTag::Confirmed`] resolved to, breaking the
+/// assumption that blocks that deep under the head stay canonical.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeepReorg {
+    /// Blocks of the old branch that were replaced. Blocks the new branch could not be
+    /// backfilled over are not counted, so this is a lower bound.
+    pub depth: u64,
+    pub confirmation_depth: u64,
+    /// The block `helios_confirmed` resolved to before the reorg.
+    pub orphaned_confirmed: B256,
+    pub old_head: B256,
+    pub new_head: B256,
+}
@@ -0,0 +1,885 @@
+//! Accounting of the requests sent to the execution provider, for providers that bill by
+//! request.
+//!
+//! Every request is attributed to a [RequestCategory] and the method it calls, and priced
+//! with a cost table whose defaults approximate the compute unit pricing of common
+//! providers. Totals are kept per UTC day, so `helios_getUsage` can report daily and monthly
+//! rollups along with a projection of where the month ends up. Soft budgets log a warning
+//! when exceeded and can hold back background requests.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::time::{SystemTime, UNIX_EPOCH};
+
+use super::constants::{USAGE_FLUSH_INTERVAL, USAGE_RATE_WINDOW_DAYS};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Cost of methods missing from both the configured and the default cost table.
+pub const DEFAULT_REQUEST_COST: u64 = 20;
+
+/// Default cost of each method, roughly in the compute units common providers charge.
+pub const DEFAULT_COSTS: &[(&str, u64)] = &[
+    ("eth_chainId", 0),
+    ("web3_clientVersion", 0),
+    ("eth_createAccessList", 10),
+    ("eth_feeHistory", 10),
+    ("eth_uninstallFilter", 10),
+    ("eth_getTransactionReceipt", 15),
+    ("eth_getBlockByHash", 16),
+    ("eth_getBlockByNumber", 16),
+    ("eth_getTransactionByHash", 17),
+    ("eth_newFilter", 20),
+    ("eth_newBlockFilter", 20),
+    ("eth_newPendingTransactionFilter", 20),
+    ("eth_getFilterChanges", 20),
+    ("eth_getProof", 21),
+    ("eth_getCode", 26),
+    ("eth_getLogs", 75),
+    ("eth_getFilterLogs", 75),
+    ("eth_sendRawTransaction", 250),
+    ("eth_getBlockReceipts", 500),
+];
+
+tokio::task_local! {
+    static CATEGORY: RequestCategory;
+}
+
+/// Why a request was sent to the provider.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RequestCategory {
+    /// Following the chain as the consensus client delivers new blocks.
+    Sync,
+    /// Answering a caller of the client.
+    Interactive,
+    /// Work nothing is waiting on, such as filling in the history behind the head.
+    Background,
+    /// Requests returning many items at once, such as logs over a block range.
+    Bulk,
+}
+
+impl RequestCategory {
+    /// The category of a request for `method` sent outside of any [attribute] scope.
+    pub fn unscoped(method: &str) -> Self {
+        match method {
+            "eth_getLogs" | "eth_getFilterLogs" | "eth_getBlockReceipts" => Self::Bulk,
+            _ => Self::Interactive,
+        }
+    }
+
+    /// The category of a request for `method` sent from the current task.
+    pub fn current(method: &str) -> Self {
+        CATEGORY
+            .try_with(|category| *category)
+            .unwrap_or_else(|_| Self::unscoped(method))
+    }
+}
+
+/// Runs `fut` with the provider requests it sends attributed to `category`.
+pub async fn attribute<F: Future>(category: RequestCategory, fut: F) -> F::Output {
+    CATEGORY.scope(category, fut).await
+}
+
+/// Cost table and budgets of the usage accounting, read from the `usage` config table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageConfig {
+    /// Cost of methods without an entry in `costs` or the defaults.
+    pub default_cost: u64,
+    /// Per-method costs, taking precedence over [DEFAULT_COSTS].
+    pub costs: BTreeMap<String, u64>,
+    pub budgets: UsageBudgets,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            default_cost: DEFAULT_REQUEST_COST,
+            costs: BTreeMap::new(),
+            budgets: UsageBudgets::default(),
+        }
+    }
+}
+
+impl UsageConfig {
+    pub fn cost(&self, method: &str) -> u64 {
+        self.costs.get(method).copied().unwrap_or_else(|| {
+            DEFAULT_COSTS
+                .iter()
+                .find(|(name, _)| *name == method)
+                .map_or(self.default_cost, |(_, cost)| *cost)
+        })
+    }
+}
+
+/// Soft limits on the accounted cost. Exceeding one only logs a warning, unless
+/// `throttle_background` is set, in which case background requests are refused as well
+/// until the period is over.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageBudgets {
+    /// Cost per UTC day across all methods.
+    pub daily: Option<u64>,
+    /// Cost per calendar month across all methods.
+    pub monthly: Option<u64>,
+    /// Cost per UTC day of individual methods.
+    pub methods: BTreeMap<String, u64>,
+    pub throttle_background: bool,
+}
+
+/// A method's requests within one category.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MethodUsage {
+    pub category: RequestCategory,
+    pub method: String,
+    pub requests: u64,
+    pub cost: u64,
+}
+
+/// The requests of one day, as persisted between restarts.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DailyUsage {
+    /// Days since the unix epoch.
+    pub day: u64,
+    /// Unix time in seconds of the first request of the day.
+    pub first_request_at: u64,
+    pub methods: Vec<MethodUsage>,
+}
+
+/// Where accounted usage is kept across restarts.
+pub trait UsageStore: Send + Sync + 'static {
+    fn load(&self) -> Result<Vec<DailyUsage>>;
+    fn save(&self, days: &[DailyUsage]) -> Result<()>;
+}
+
+/// Usage of the execution provider, as reported by `helios_getUsage`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    /// The current UTC day, as `YYYY-MM-DD`.
+    pub date: String,
+    pub today: UsageTotals,
+    pub month: UsageTotals,
+    /// Totals of the days of the current month with requests, oldest first.
+    pub days: Vec<DayTotals>,
+    /// The cost the month ends at if the rate of the last week carries on.
+    pub projected_monthly_cost: u64,
+    /// Budgets exceeded in the current period: `daily`, `monthly` or a method name.
+    pub exceeded_budgets: Vec<String>,
+    /// Categories whose requests are refused until the exceeded budgets reset.
+    pub throttled: Vec<RequestCategory>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageTotals {
+    pub requests: u64,
+    pub cost: u64,
+    pub categories: Vec<CategoryUsage>,
+    pub methods: Vec<MethodUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CategoryUsage {
+    pub category: RequestCategory,
+    pub requests: u64,
+    pub cost: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DayTotals {
+    pub date: String,
+    pub requests: u64,
+    pub cost: u64,
+}
+
+/// Accounts the requests of an execution rpc. Clones share their totals.
+#[derive(Clone, Default)]
+pub struct UsageMeter {
+    config: Arc<UsageConfig>,
+    book: Arc<Mutex<Book>>,
+    store: Option<Arc<dyn UsageStore>>,
+}
+
+#[derive(Default)]
+struct Book {
+    days: BTreeMap<u64, Day>,
+    /// Days changed since they were last saved.
+    dirty: BTreeSet<u64>,
+    last_flush: u64,
+    /// Budgets already warned about, by the first day of the period they were exceeded in.
+    warned: BTreeSet<(u64, String)>,
+}
+
+struct Day {
+    first_request_at: u64,
+    requests: u64,
+    cost: u64,
+    methods: BTreeMap<RequestCategory, BTreeMap<String, Count>>,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct Count {
+    requests: u64,
+    cost: u64,
+}
+
+struct Exceeded {
+    budget: String,
+    period: u64,
+    spent: u64,
+    limit: u64,
+}
+
+impl UsageMeter {
+    pub fn new(config: UsageConfig) -> Self {
+        Self {
+            config: Arc::new(config),
+            ..Default::default()
+        }
+    }
+
+    /// Persists totals in `store`, resuming from what it already holds.
+    pub fn with_store(mut self, store: impl UsageStore) -> Self {
+        match store.load() {
+            Ok(days) => {
+                let mut book = self.book.lock().unwrap();
+                for day in days {
+                    book.days.insert(day.day, Day::from(day));
+                }
+            }
+            Err(err) => {
+                warn!(target: "helios::usage", error = %err, "could not load provider usage")
+            }
+        }
+
+        self.store = Some(Arc::new(store));
+        self
+    }
+
+    pub fn config(&self) -> &UsageConfig {
+        &self.config
+    }
+
+    /// Accounts a request for `method` sent from the current task.
+    pub fn record(&self, method: &str) {
+        self.record_at(now(), RequestCategory::current(method), method);
+    }
+
+    /// Accounts a request sent at `now`, in seconds since the unix epoch.
+    pub fn record_at(&self, now: u64, category: RequestCategory, method: &str) {
+        let cost = self.config.cost(method);
+        let today = now / SECONDS_PER_DAY;
+
+        let flush = {
+            let mut book = self.book.lock().unwrap();
+            book.days
+                .entry(today)
+                .or_insert_with(|| Day::new(now))
+                .add(category, method, cost);
+            book.dirty.insert(today);
+
+            for exceeded in self.exceeded(&book, today) {
+                if book
+                    .warned
+                    .insert((exceeded.period, exceeded.budget.clone()))
+                {
+                    warn!(
+                        target: "helios::usage",
+                        budget = %exceeded.budget,
+                        spent = exceeded.spent,
+                        limit = exceeded.limit,
+                        throttling = self.config.budgets.throttle_background,
+                        "provider usage budget exceeded"
+                    );
+                }
+            }
+
+            let due = now.saturating_sub(book.last_flush) >= USAGE_FLUSH_INTERVAL.as_secs();
+            if due {
+                book.last_flush = now;
+            }
+            due
+        };
+
+        if flush {
+            if let Err(err) = self.flush() {
+                warn!(target: "helios::usage", error = %err, "could not save provider usage");
+            }
+        }
+    }
+
+    /// Saves the days changed since the last flush. Does nothing without a store.
+    pub fn flush(&self) -> Result<()> {
+        let Some(store) = &self.store else {
+            return Ok(());
+        };
+
+        let days = {
+            let mut book = self.book.lock().unwrap();
+            let dirty = std::mem::take(&mut book.dirty);
+            dirty
+                .into_iter()
+                .filter_map(|day| book.days.get(&day).map(|usage| usage.to_daily(day)))
+                .collect::<Vec<_>>()
+        };
+
+        if days.is_empty() {
+            return Ok(());
+        }
+        store.save(&days)
+    }
+
+    /// Whether requests in `category` are currently refused.
+    pub fn throttles(&self, category: RequestCategory) -> bool {
+        self.throttles_at(now(), category)
+    }
+
+    pub fn throttles_at(&self, now: u64, category: RequestCategory) -> bool {
+        if category != RequestCategory::Background || !self.config.budgets.throttle_background {
+            return false;
+        }
+
+        let book = self.book.lock().unwrap();
+        !self.exceeded(&book, now / SECONDS_PER_DAY).is_empty()
+    }
+
+    pub fn report(&self) -> UsageReport {
+        self.report_at(now())
+    }
+
+    pub fn report_at(&self, now: u64) -> UsageReport {
+        let today = now / SECONDS_PER_DAY;
+        let (month_start, month_end) = month_of(today);
+        let book = self.book.lock().unwrap();
+
+        let today_totals = totals(book.days.get(&today).into_iter());
+        let month_days = book.days.range(month_start..=today);
+        let month = totals(month_days.clone().map(|(_, day)| day));
+        let days = month_days
+            .map(|(number, day)| DayTotals {
+                date: date(*number),
+                requests: day.requests,
+                cost: day.cost,
+            })
+            .collect();
+
+        // the rate of the last week, measured from the first request within it
+        let window = book
+            .days
+            .range(today.saturating_sub(USAGE_RATE_WINDOW_DAYS - 1)..=today);
+        let window_cost = window.clone().map(|(_, day)| day.cost).sum::<u64>();
+        let elapsed = window
+            .map(|(_, day)| now.saturating_sub(day.first_request_at))
+            .max()
+            .unwrap_or_default();
+        let remaining = (month_end * SECONDS_PER_DAY).saturating_sub(now);
+        let projected = match elapsed {
+            0 => month.cost,
+            elapsed => {
+                let rest = u128::from(window_cost) * u128::from(remaining) / u128::from(elapsed);
+                month
+                    .cost
+                    .saturating_add(rest.try_into().unwrap_or(u64::MAX))
+            }
+        };
+
+        let exceeded_budgets = self
+            .exceeded(&book, today)
+            .into_iter()
+            .map(|exceeded| exceeded.budget)
+            .collect::<Vec<_>>();
+        let throttled = if self.config.budgets.throttle_background && !exceeded_budgets.is_empty() {
+            vec![RequestCategory::Background]
+        } else {
+            Vec::new()
+        };
+
+        UsageReport {
+            date: date(today),
+            today: today_totals,
+            month,
+            days,
+            projected_monthly_cost: projected,
+            exceeded_budgets,
+            throttled,
+        }
+    }
+
+    fn exceeded(&self, book: &Book, today: u64) -> Vec<Exceeded> {
+        let budgets = &self.config.budgets;
+        let mut exceeded = Vec::new();
+        let day = book.days.get(&today);
+
+        if let Some(limit) = budgets.daily {
+            let spent = day.map_or(0, |day| day.cost);
+            if spent > limit {
+                exceeded.push(Exceeded {
+                    budget: "daily".to_string(),
+                    period: today,
+                    spent,
+                    limit,
+                });
+            }
+        }
+
+        if let Some(limit) = budgets.monthly {
+            let (month_start, _) = month_of(today);
+            let spent = book
+                .days
+                .range(month_start..=today)
+                .map(|(_, day)| day.cost)
+                .sum::<u64>();
+            if spent > limit {
+                exceeded.push(Exceeded {
+                    budget: "monthly".to_string(),
+                    period: month_start,
+                    spent,
+                    limit,
+                });
+            }
+        }
+
+        for (method, limit) in &budgets.methods {
+            let spent = day.map_or(0, |day| day.method_cost(method));
+            if spent > *limit {
+                exceeded.push(Exceeded {
+                    budget: method.clone(),
+                    period: today,
+                    spent,
+                    limit: *limit,
+                });
+            }
+        }
+
+        exceeded
+    }
+}
+
+impl std::fmt::Debug for UsageMeter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UsageMeter")
+            .field("config", &self.config)
+            .field("persisted", &self.store.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Day {
+    fn new(first_request_at: u64) -> Self {
+        Self {
+            first_request_at,
+            requests: 0,
+            cost: 0,
+            methods: BTreeMap::new(),
+        }
+    }
+
+    fn add(&mut self, category: RequestCategory, method: &str, cost: u64) {
+        let count = self
+            .methods
+            .entry(category)
+            .or_default()
+            .entry(method.to_string())
+            .or_default();
+        count.requests += 1;
+        count.cost += cost;
+        self.requests += 1;
+        self.cost += cost;
+    }
+
+    fn method_cost(&self, method: &str) -> u64 {
+        self.methods
+            .values()
+            .filter_map(|methods| methods.get(method))
+            .map(|count| count.cost)
+            .sum()
+    }
+
+    fn to_daily(&self, day: u64) -> DailyUsage {
+        let methods = self
+            .methods
+            .iter()
+            .flat_map(|(category, methods)| {
+                methods.iter().map(|(method, count)| MethodUsage {
+                    category: *category,
+                    method: method.clone(),
+                    requests: count.requests,
+                    cost: count.cost,
+                })
+            })
+            .collect();
+
+        DailyUsage {
+            day,
+            first_request_at: self.first_request_at,
+            methods,
+        }
+    }
+}
+
+impl From<DailyUsage> for Day {
+    fn from(usage: DailyUsage) -> Self {
+        let mut day = Day::new(usage.first_request_at);
+        for method in usage.methods {
+            let count = day
+                .methods
+                .entry(method.category)
+                .or_default()
+                .entry(method.method)
+                .or_default();
+            count.requests += method.requests;
+            count.cost += method.cost;
+            day.requests += method.requests;
+            day.cost += method.cost;
+        }
+        day
+    }
+}
+
+fn totals<'a>(days: impl Iterator<Item = &'a Day>) -> UsageTotals {
+    let mut categories = BTreeMap::<RequestCategory, Count>::new();
+    let mut methods = BTreeMap::<(RequestCategory, &str), Count>::new();
+
+    for day in days {
+        for (category, counts) in &day.methods {
+            for (method, count) in counts {
+                for total in [
+                    categories.entry(*category).or_default(),
+                    methods.entry((*category, method.as_str())).or_default(),
+                ] {
+                    total.requests += count.requests;
+                    total.cost += count.cost;
+                }
+            }
+        }
+    }
+
+    UsageTotals {
+        requests: categories.values().map(|count| count.requests).sum(),
+        cost: categories.values().map(|count| count.cost).sum(),
+        categories: categories
+            .into_iter()
+            .map(|(category, count)| CategoryUsage {
+                category,
+                requests: count.requests,
+                cost: count.cost,
+            })
+            .collect(),
+        methods: methods
+            .into_iter()
+            .map(|((category, method), count)| MethodUsage {
+                category,
+                method: method.to_string(),
+                requests: count.requests,
+                cost: count.cost,
+            })
+            .collect(),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// The year, month and day of the month of a day since the unix epoch.
+fn civil(day: u64) -> (u64, u64, u64) {
+    // Howard Hinnant's days to civil conversion, shifted to years starting in March
+    let z = day + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + u64::from(m <= 2);
+    (y, m, d)
+}
+
+/// The first day of the month `day` falls in and the first day of the month after.
+fn month_of(day: u64) -> (u64, u64) {
+    let (year, month, day_of_month) = civil(day);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let length = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    let start = day + 1 - day_of_month;
+    (start, start + length)
+}
+
+fn date(day: u64) -> String {
+    let (year, month, day) = civil(day);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-02-27T00:00:00Z, late in a leap february
+    const FEB_27: u64 = 1_708_992_000;
+    const HOUR: u64 = 3_600;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        days: Mutex<BTreeMap<u64, DailyUsage>>,
+    }
+
+    impl MemoryStore {
+        fn with_days(days: Vec<DailyUsage>) -> Self {
+            let days = days.into_iter().map(|day| (day.day, day)).collect();
+            Self {
+                days: Mutex::new(days),
+            }
+        }
+    }
+
+    impl UsageStore for Arc<MemoryStore> {
+        fn load(&self) -> Result<Vec<DailyUsage>> {
+            Ok(self.days.lock().unwrap().values().cloned().collect())
+        }
+
+        fn save(&self, days: &[DailyUsage]) -> Result<()> {
+            let mut stored = self.days.lock().unwrap();
+            for day in days {
+                stored.insert(day.day, day.clone());
+            }
+            Ok(())
+        }
+    }
+
+    /// Six hours of following the chain and serving a wallet, one round per hour.
+    fn run_workload(meter: &UsageMeter, start: u64) {
+        for hour in 0..6 {
+            let now = start + hour * HOUR;
+            for _ in 0..300 {
+                meter.record_at(now, RequestCategory::Sync, "eth_getBlockByNumber");
+            }
+            for _ in 0..10 {
+                meter.record_at(now, RequestCategory::Interactive, "eth_getProof");
+            }
+            meter.record_at(now, RequestCategory::Bulk, "eth_getLogs");
+            meter.record_at(now, RequestCategory::Interactive, "eth_chainId");
+        }
+    }
+
+    fn category(totals: &UsageTotals, category: RequestCategory) -> (u64, u64) {
+        totals
+            .categories
+            .iter()
+            .find(|usage| usage.category == category)
+            .map_or((0, 0), |usage| (usage.requests, usage.cost))
+    }
+
+    #[test]
+    fn test_workload_totals() {
+        let meter = UsageMeter::default();
+        run_workload(&meter, FEB_27);
+
+        let report = meter.report_at(FEB_27 + 6 * HOUR);
+        assert_eq!(report.date, "2024-02-27");
+        assert_eq!(
+            category(&report.today, RequestCategory::Sync),
+            (1_800, 28_800)
+        );
+        assert_eq!(
+            category(&report.today, RequestCategory::Interactive),
+            (66, 1_260)
+        );
+        assert_eq!(category(&report.today, RequestCategory::Bulk), (6, 450));
+        assert_eq!(category(&report.today, RequestCategory::Background), (0, 0));
+        assert_eq!(report.today.requests, 1_872);
+        assert_eq!(report.today.cost, 30_510);
+        assert_eq!(report.month, report.today);
+
+        let chain_id = report
+            .today
+            .methods
+            .iter()
+            .find(|usage| usage.method == "eth_chainId")
+            .unwrap();
+        assert_eq!((chain_id.requests, chain_id.cost), (6, 0));
+    }
+
+    #[test]
+    fn test_daily_and_monthly_rollups() {
+        let meter = UsageMeter::default();
+        // feb 27 and 28, and the first of march after a quiet leap day
+        for day in [0, 1, 3] {
+            run_workload(&meter, FEB_27 + day * SECONDS_PER_DAY);
+        }
+
+        let feb_28 = meter.report_at(FEB_27 + SECONDS_PER_DAY + 12 * HOUR);
+        assert_eq!(feb_28.month.cost, 2 * 30_510);
+        assert_eq!(
+            feb_28
+                .days
+                .iter()
+                .map(|day| day.date.as_str())
+                .collect::<Vec<_>>(),
+            ["2024-02-27", "2024-02-28"]
+        );
+
+        // march starts a new month
+        let march_1 = meter.report_at(FEB_27 + 3 * SECONDS_PER_DAY + 12 * HOUR);
+        assert_eq!(march_1.date, "2024-03-01");
+        assert_eq!(march_1.today.cost, 30_510);
+        assert_eq!(march_1.month, march_1.today);
+        assert_eq!(march_1.days.len(), 1);
+    }
+
+    #[test]
+    fn test_projection_follows_recent_rate() {
+        let meter = UsageMeter::default();
+        run_workload(&meter, FEB_27);
+
+        // 30_510 in the six hours since the first request, with 66 hours of february left
+        let now = FEB_27 + 6 * HOUR;
+        assert_eq!(meter.report_at(now).projected_monthly_cost, 30_510 * 12);
+
+        // a quiet afternoon halves the rate
+        let now = FEB_27 + 12 * HOUR;
+        assert_eq!(
+            meter.report_at(now).projected_monthly_cost,
+            30_510 + 30_510 * 60 / 12
+        );
+
+        assert_eq!(
+            UsageMeter::default().report_at(now).projected_monthly_cost,
+            0
+        );
+    }
+
+    #[test]
+    fn test_budgets_warn_and_throttle_background() {
+        let config = UsageConfig {
+            budgets: UsageBudgets {
+                daily: Some(30_000),
+                methods: BTreeMap::from([("eth_getLogs".to_string(), 500)]),
+                throttle_background: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let meter = UsageMeter::new(config);
+
+        run_workload(&meter, FEB_27);
+        let now = FEB_27 + 6 * HOUR;
+        let report = meter.report_at(now);
+        assert_eq!(report.exceeded_budgets, ["daily"]);
+        assert_eq!(report.throttled, [RequestCategory::Background]);
+        assert!(meter.throttles_at(now, RequestCategory::Background));
+        assert!(!meter.throttles_at(now, RequestCategory::Sync));
+        assert!(!meter.throttles_at(now, RequestCategory::Interactive));
+
+        meter.record_at(now, RequestCategory::Bulk, "eth_getLogs");
+        let report = meter.report_at(now);
+        assert_eq!(report.exceeded_budgets, ["daily", "eth_getLogs"]);
+
+        // each budget is warned about once per period
+        let warned = meter.book.lock().unwrap().warned.clone();
+        let today = FEB_27 / SECONDS_PER_DAY;
+        assert_eq!(
+            warned,
+            BTreeSet::from([
+                (today, "daily".to_string()),
+                (today, "eth_getLogs".to_string())
+            ])
+        );
+
+        // budgets reset with the day
+        let tomorrow = FEB_27 + SECONDS_PER_DAY;
+        assert!(!meter.throttles_at(tomorrow, RequestCategory::Background));
+
+        // without throttling, exceeded budgets only warn
+        let mut config = meter.config().clone();
+        config.budgets.throttle_background = false;
+        let meter = UsageMeter::new(config);
+        run_workload(&meter, FEB_27);
+        assert!(!meter.throttles_at(now, RequestCategory::Background));
+        assert!(meter.report_at(now).throttled.is_empty());
+    }
+
+    #[test]
+    fn test_totals_persist_across_restarts() {
+        let store = Arc::new(MemoryStore::default());
+        let meter = UsageMeter::default().with_store(store.clone());
+        run_workload(&meter, FEB_27);
+        meter.flush().unwrap();
+
+        let resumed = UsageMeter::default().with_store(store.clone());
+        let now = FEB_27 + 6 * HOUR;
+        assert_eq!(resumed.report_at(now), meter.report_at(now));
+
+        resumed.record_at(now, RequestCategory::Sync, "eth_getBlockByNumber");
+        resumed.flush().unwrap();
+        let stored = MemoryStore::with_days(store.load().unwrap());
+        let resumed = UsageMeter::default().with_store(Arc::new(stored));
+        assert_eq!(resumed.report_at(now).today.cost, 30_510 + 16);
+    }
+
+    #[test]
+    fn test_costs() {
+        let config = UsageConfig {
+            default_cost: 5,
+            costs: BTreeMap::from([("eth_getLogs".to_string(), 255)]),
+            ..Default::default()
+        };
+        assert_eq!(config.cost("eth_getLogs"), 255);
+        assert_eq!(config.cost("eth_getBlockReceipts"), 500);
+        assert_eq!(config.cost("eth_getBalance"), 5);
+        assert_eq!(UsageConfig::default().cost("eth_getBalance"), 20);
+    }
+
+    #[tokio::test]
+    async fn test_attribution() {
+        assert_eq!(
+            RequestCategory::current("eth_getProof"),
+            RequestCategory::Interactive
+        );
+        assert_eq!(
+            RequestCategory::current("eth_getLogs"),
+            RequestCategory::Bulk
+        );
+
+        let category = attribute(RequestCategory::Sync, async {
+            RequestCategory::current("eth_getLogs")
+        })
+        .await;
+        assert_eq!(category, RequestCategory::Sync);
+    }
+
+    #[test]
+    fn test_calendar() {
+        assert_eq!(date(0), "1970-01-01");
+        assert_eq!(date(FEB_27 / SECONDS_PER_DAY), "2024-02-27");
+        assert_eq!(date(FEB_27 / SECONDS_PER_DAY + 2), "2024-02-29");
+        assert_eq!(date(FEB_27 / SECONDS_PER_DAY + 3), "2024-03-01");
+
+        let (start, end) = month_of(FEB_27 / SECONDS_PER_DAY);
+        assert_eq!(
+            (date(start), date(end)),
+            ("2024-02-01".into(), "2024-03-01".into())
+        );
+        let (start, end) = month_of(0);
+        assert_eq!(end - start, 31);
+    }
+}
@@ -3,4 +3,14 @@ use serde::{Deserialize, Serialize};
 #[derive(Clone, Copy, Serialize, Deserialize, Default, Debug)]
 pub struct ForkSchedule {
     pub prague_timestamp: u64,
+    /// First proof of stake block. Earlier blocks predate the beacon chain and cannot be
+    /// verified by the light client.
+    #[serde(default)]
+    pub merge_block: u64,
+}
+
+impl ForkSchedule {
+    pub fn is_pre_merge(&self, number: u64) -> bool {
+        number < self.merge_block
+    }
 }
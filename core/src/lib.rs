@@ -1,8 +1,14 @@
 pub mod client;
+pub mod config;
 pub mod consensus;
+pub mod database;
 pub mod errors;
 pub mod execution;
 pub mod fork_schedule;
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+pub mod metrics;
 pub mod network_spec;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod time;
 pub mod types;
@@ -0,0 +1,356 @@
+//! Prometheus metrics of provider requests, proof verification and sync progress.
+//!
+//! Each client records into a registry of its own, kept by its execution rpc and shared by
+//! the clones of it, so several clients in one process are measured apart. Providers
+//! connected through [`HttpRpc::connect`] are measured by [`MetricsLayer`] and the retry and
+//! rate limit layers below it, the execution client and the state record through
+//! [`ExecutionRpc::metrics`], and [`serve`] exposes everything recorded for scraping.
+//!
+//! [`HttpRpc::connect`]: crate::execution::rpc::http_rpc::HttpRpc::connect
+//! [`ExecutionRpc::metrics`]: crate::execution::rpc::ExecutionRpc::metrics
+
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket, SerializedRequest};
+use alloy::transports::{Transport, TransportError, TransportFut};
+use eyre::Result;
+use futures::future::{BoxFuture, FutureExt};
+use hyper::header::CONTENT_TYPE;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server, StatusCode};
+use prometheus::{
+    Encoder, Gauge, HistogramOpts, HistogramVec, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use tower::{Layer, Service};
+use tracing::{info, warn};
+
+use crate::client::supervisor::Supervisor;
+use crate::types::HeadFeed;
+
+/// Name of the metrics server under the supervisor of the node.
+const METRICS_TASK: &str = "metrics";
+
+/// The metrics one client recorded so far.
+pub struct Metrics {
+    registry: Registry,
+    rpc_calls: IntCounterVec,
+    rpc_errors: IntCounterVec,
+    rpc_retries: IntCounterVec,
+    rpc_latency: HistogramVec,
+    rate_budget: Gauge,
+    rate_limit_queue: IntGauge,
+    rate_budget_exceeded: IntCounterVec,
+    latest_block: IntGauge,
+    finalized_block: IntGauge,
+    head_feed_stalled: IntGauge,
+    since_update: Gauge,
+    last_update: Mutex<Option<Instant>>,
+    verification_failures: IntCounterVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+impl Metrics {
+    /// An empty registry of the metrics of one client.
+    pub fn new() -> Self {
+        Self::register().expect("metrics are valid")
+    }
+
+    fn register() -> prometheus::Result<Self> {
+        let registry = Registry::new();
+        let counter = |name: &str, help: &str, label: &str| {
+            let counter = IntCounterVec::new(Opts::new(name, help), &[label])?;
+            registry.register(Box::new(counter.clone()))?;
+            Ok::<_, prometheus::Error>(counter)
+        };
+        let gauge = |name: &str, help: &str| {
+            let gauge = IntGauge::new(name, help)?;
+            registry.register(Box::new(gauge.clone()))?;
+            Ok::<_, prometheus::Error>(gauge)
+        };
+
+        let rpc_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "helios_rpc_request_duration_seconds",
+                "Latency of execution provider requests, retries included",
+            ),
+            &["method"],
+        )?;
+        registry.register(Box::new(rpc_latency.clone()))?;
+        let since_update = Gauge::new(
+            "helios_seconds_since_consensus_update",
+            "Seconds since consensus last delivered a verified block",
+        )?;
+        registry.register(Box::new(since_update.clone()))?;
+        let rate_budget = Gauge::new(
+            "helios_rpc_rate_budget_requests",
+            "Requests the execution provider budget allows right away, negative while requests wait",
+        )?;
+        registry.register(Box::new(rate_budget.clone()))?;
+
+        Ok(Self {
+            rpc_calls: counter(
+                "helios_rpc_calls_total",
+                "Execution provider requests",
+                "method",
+            )?,
+            rpc_errors: counter(
+                "helios_rpc_errors_total",
+                "Execution provider requests that failed",
+                "method",
+            )?,
+            rpc_retries: counter(
+                "helios_rpc_retries_total",
+                "Retries of rate limited or timed out execution provider requests",
+                "method",
+            )?,
+            rate_limit_queue: gauge(
+                "helios_rpc_rate_limit_queued",
+                "Execution provider requests waiting for the request budget",
+            )?,
+            rate_budget_exceeded: counter(
+                "helios_rpc_rate_budget_exceeded_total",
+                "Execution provider requests failed for waiting too long on the request budget",
+                "method",
+            )?,
+            latest_block: gauge(
+                "helios_latest_block_number",
+                "Number of the latest verified block",
+            )?,
+            finalized_block: gauge(
+                "helios_finalized_block_number",
+                "Number of the latest finalized block",
+            )?,
+            head_feed_stalled: gauge(
+                "helios_head_feed_stalled",
+                "1 while no verified head arrived for longer than the consensus client allows",
+            )?,
+            verification_failures: counter(
+                "helios_proof_verification_failures_total",
+                "Provider responses that failed to verify, by what was verified",
+                "kind",
+            )?,
+            rpc_latency,
+            rate_budget,
+            since_update,
+            last_update: Mutex::default(),
+            registry,
+        })
+    }
+
+    /// Records a retry of each request of `req`.
+    pub fn record_retry(&self, req: &RequestPacket) {
+        for req in requests(req) {
+            self.rpc_retries.with_label_values(&[req.method()]).inc();
+        }
+    }
+
+    /// Records the requests left in the budget after taking those of a request.
+    pub fn record_rate_budget(&self, available: f64) {
+        self.rate_budget.set(available);
+    }
+
+    pub fn record_rate_limit_queue(&self, queued: i64) {
+        self.rate_limit_queue.set(queued);
+    }
+
+    /// Records a verified block at the head, numbered `number`.
+    pub fn record_latest_block(&self, number: u64) {
+        self.latest_block.set(number as i64);
+        *self.last_update.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub fn record_finalized_block(&self, number: u64) {
+        self.finalized_block.set(number as i64);
+    }
+
+    pub fn record_head_feed(&self, head_feed: HeadFeed) {
+        self.head_feed_stalled
+            .set((head_feed != HeadFeed::Live) as i64);
+    }
+
+    /// Records each request of `req` as failed for exceeding the request budget.
+    pub fn record_rate_budget_exceeded(&self, req: &RequestPacket) {
+        for req in requests(req) {
+            self.rate_budget_exceeded
+                .with_label_values(&[req.method()])
+                .inc();
+        }
+    }
+
+    /// Records a provider response that failed to verify, of `kind`: `account` or `storage`
+    /// proofs, `code`, `receipts`, `logs` or a `block`.
+    pub fn record_verification_failure(&self, kind: &str) {
+        self.verification_failures.with_label_values(&[kind]).inc();
+    }
+
+    /// The metrics in the prometheus text format.
+    pub fn encode(&self) -> String {
+        if let Some(last_update) = *self.last_update.lock().unwrap() {
+            self.since_update.set(last_update.elapsed().as_secs_f64());
+        }
+
+        let mut buf = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buf)
+            .expect("metrics encode as text");
+        String::from_utf8(buf).expect("text format is utf-8")
+    }
+
+    fn record_response(
+        &self,
+        req: &RequestPacket,
+        res: &Result<ResponsePacket, TransportError>,
+        start: Instant,
+    ) {
+        let elapsed = start.elapsed().as_secs_f64();
+        let failed = |req: &SerializedRequest| match res {
+            Err(_) => true,
+            Ok(ResponsePacket::Single(response)) => response.payload.is_error(),
+            Ok(ResponsePacket::Batch(responses)) => responses
+                .iter()
+                .any(|response| response.id == *req.id() && response.payload.is_error()),
+        };
+
+        for req in requests(req) {
+            let method = req.method();
+            self.rpc_calls.with_label_values(&[method]).inc();
+            self.rpc_latency
+                .with_label_values(&[method])
+                .observe(elapsed);
+            if failed(req) {
+                self.rpc_errors.with_label_values(&[method]).inc();
+            }
+        }
+    }
+}
+
+fn requests(req: &RequestPacket) -> &[SerializedRequest] {
+    match req {
+        RequestPacket::Single(req) => std::slice::from_ref(req),
+        RequestPacket::Batch(reqs) => reqs,
+    }
+}
+
+/// A transport layer counting and timing the requests sent through it in `metrics`.
+#[derive(Debug, Clone)]
+pub struct MetricsLayer {
+    metrics: Arc<Metrics>,
+}
+
+impl MetricsLayer {
+    pub fn new(metrics: Arc<Metrics>) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// Records the requests of the wrapped transport.
+#[derive(Debug, Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    metrics: Arc<Metrics>,
+}
+
+impl<S: Transport + Clone> Service<RequestPacket> for MetricsService<S> {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let mut inner = self.inner.clone();
+        let metrics = self.metrics.clone();
+        Box::pin(async move {
+            let start = Instant::now();
+            let res = inner.call(req.clone()).await;
+            metrics.record_response(&req, &res, start);
+            res
+        })
+    }
+}
+
+/// Serves `metrics` at `/metrics` on `addr` under `supervisor`, which binds the same address
+/// again should the server stop, returning the address bound, which tells the port when
+/// `addr` asks for any. The server runs until [`stop`].
+pub fn serve(
+    addr: SocketAddr,
+    supervisor: &Supervisor,
+    metrics: Arc<Metrics>,
+) -> Result<SocketAddr> {
+    let (addr, server) = bind(addr, metrics.clone())?;
+    let first = Mutex::new(Some(server));
+
+    supervisor.supervise_service(METRICS_TASK, move || {
+        let server = match first.lock().unwrap().take() {
+            Some(server) => Ok(server),
+            None => bind(addr, metrics.clone()).map(|(_, server)| server),
+        };
+        async move {
+            server?.await?;
+            warn!(target: "helios::metrics", "metrics server stopped");
+            Ok(())
+        }
+    });
+    info!(target: "helios::metrics", %addr, "serving metrics");
+
+    Ok(addr)
+}
+
+/// Stops the metrics server [`serve`] started under `supervisor`, closing its listener.
+pub fn stop(supervisor: &Supervisor) {
+    supervisor.stop(METRICS_TASK);
+}
+
+fn bind(
+    addr: SocketAddr,
+    metrics: Arc<Metrics>,
+) -> Result<(SocketAddr, BoxFuture<'static, hyper::Result<()>>)> {
+    let make_service = make_service_fn(move |_| {
+        let metrics = metrics.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| respond(req, metrics.clone()))) }
+    });
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    Ok((server.local_addr(), server.boxed()))
+}
+
+async fn respond(req: Request<Body>, metrics: Arc<Metrics>) -> Result<Response<Body>, Infallible> {
+    let response = if req.uri().path() == "/metrics" {
+        Response::builder()
+            .header(CONTENT_TYPE, prometheus::TEXT_FORMAT)
+            .body(Body::from(metrics.encode()))
+    } else {
+        Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+    };
+    Ok(response.expect("response is valid"))
+}
@@ -1,13 +1,177 @@
-use alloy::{network::Network, rpc::types::Log};
+use alloy::{
+    consensus::{
+        proofs::{calculate_transaction_root, calculate_withdrawals_root},
+        BlockHeader, Transaction,
+    },
+    eips::{
+        calc_next_block_base_fee, eip1559::BaseFeeParams, eip2718::Encodable2718,
+        eip4895::Withdrawal,
+    },
+    network::{primitives::HeaderResponse, BlockResponse, Network, TransactionResponse},
+    primitives::{keccak256, Address, B256},
+    rlp::{self, Encodable},
+    rpc::types::Log,
+};
 use revm::primitives::{BlockEnv, TxEnv};
 
+use crate::execution::blob::BlobParams;
+use crate::execution::errors::ExecutionError;
+use crate::execution::preflight::RawTransaction;
 use crate::fork_schedule::ForkSchedule;
 
 pub trait NetworkSpec: Network {
     fn encode_receipt(receipt: &Self::ReceiptResponse) -> Vec<u8>;
-    fn is_hash_valid(block: &Self::BlockResponse) -> bool;
+    /// Appends the encoding of `receipt` to `out`, letting a receipts root reuse one buffer
+    /// for the whole block.
+    fn encode_receipt_into(receipt: &Self::ReceiptResponse, out: &mut Vec<u8>) {
+        out.extend_from_slice(&Self::encode_receipt(receipt));
+    }
+    /// The rlp encoding of a header, which its hash is computed over.
+    fn encode_header(header: &Self::HeaderResponse) -> Vec<u8>;
+    /// The rlp encoding of a block as sent over the network, or `None` when the response does
+    /// not carry everything it commits to, such as full transactions or uncle headers.
+    fn encode_block(block: &Self::BlockResponse) -> Option<Vec<u8>>;
+    /// Checks the transactions and withdrawals of a block against the roots of its header,
+    /// as far as the response allows. Without full transactions only an empty list can be
+    /// checked.
+    fn check_body(block: &Self::BlockResponse) -> Result<(), ExecutionError>;
+    fn is_hash_valid(block: &Self::BlockResponse) -> bool {
+        Self::is_header_hash_valid(block.header()) && Self::check_body(block).is_ok()
+    }
+    fn is_header_hash_valid(header: &Self::HeaderResponse) -> bool;
     fn receipt_contains(list: &[Self::ReceiptResponse], elem: &Self::ReceiptResponse) -> bool;
+    /// The address of the contract the creation `tx` deploys, or `None` where the transaction
+    /// alone does not determine it.
+    fn created_address(tx: &Self::TransactionResponse) -> Option<Address> {
+        Some(tx.from().create(tx.nonce()))
+    }
     fn receipt_logs(receipt: &Self::ReceiptResponse) -> Vec<Log>;
+    /// Decodes a signed transaction in its network encoding, as sent with
+    /// `eth_sendRawTransaction`, recovering its sender.
+    fn decode_transaction(bytes: &[u8]) -> Result<RawTransaction, ExecutionError>;
     fn tx_env(request: &Self::TransactionRequest) -> TxEnv;
-    fn block_env(block: &Self::BlockResponse, fork_schedule: &ForkSchedule) -> BlockEnv;
+    fn block_env(header: &Self::HeaderResponse, fork_schedule: &ForkSchedule) -> BlockEnv;
+    /// The base fee of the block after `header`, or `None` where the header alone does not
+    /// determine it.
+    fn next_base_fee(header: &Self::HeaderResponse) -> Option<u64> {
+        Some(calc_next_block_base_fee(
+            header.gas_used(),
+            header.gas_limit(),
+            header.base_fee_per_gas()?,
+            BaseFeeParams::ethereum(),
+        ))
+    }
+    /// The blob schedule pricing the blob gas of the block of `header`, or `None` where
+    /// blob transactions are not part of the chain, as before cancun.
+    fn blob_params(
+        header: &Self::HeaderResponse,
+        fork_schedule: &ForkSchedule,
+    ) -> Option<BlobParams> {
+        header.excess_blob_gas()?;
+        Some(BlobParams::active_at(header.timestamp(), fork_schedule))
+    }
+}
+
+/// Checks the full transactions of the block `block_hash` against its `transactions_root`,
+/// with each transaction hashing to the hash it is served with and claiming the place it
+/// has in the block. `envelope` gives the network encoding of a transaction.
+pub fn check_transactions<T, E>(
+    block_hash: B256,
+    transactions_root: B256,
+    transactions: &[T],
+    envelope: impl Fn(&T) -> E,
+) -> Result<(), ExecutionError>
+where
+    T: TransactionResponse,
+    E: Encodable2718,
+{
+    let envelopes = transactions.iter().map(envelope).collect::<Vec<_>>();
+    if calculate_transaction_root(&envelopes) != transactions_root {
+        return Err(ExecutionError::BlockBodyMismatch(
+            block_hash,
+            "transactions root",
+        ));
+    }
+
+    // the root commits to the encodings only, the hashes and positions beside them are
+    // the provider's word
+    for (index, (tx, envelope)) in transactions.iter().zip(&envelopes).enumerate() {
+        let hash = tx.tx_hash();
+        if keccak256(envelope.encoded_2718()) != hash {
+            return Err(ExecutionError::TransactionHashMismatch(
+                block_hash, index, hash,
+            ));
+        }
+        let misplaced = tx.block_hash().is_some_and(|hash| hash != block_hash)
+            || tx.transaction_index().is_some_and(|i| i != index as u64);
+        if misplaced {
+            return Err(ExecutionError::MisplacedTransaction(
+                block_hash, hash, index,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the `withdrawals` of the block `block_hash` against its `withdrawals_root`. Before
+/// shanghai the header has no root, so a list is never committed to, not even an empty one,
+/// while a response leaving the list out of a later block is not wrong either.
+pub fn check_withdrawals(
+    block_hash: B256,
+    withdrawals_root: Option<B256>,
+    withdrawals: Option<&[Withdrawal]>,
+) -> Result<(), ExecutionError> {
+    let Some(withdrawals) = withdrawals else {
+        return Ok(());
+    };
+    if Some(calculate_withdrawals_root(withdrawals)) != withdrawals_root {
+        return Err(ExecutionError::BlockBodyMismatch(
+            block_hash,
+            "withdrawals root",
+        ));
+    }
+    Ok(())
+}
+
+/// Encodes a block without uncles from its header, its network encoded transactions and its
+/// withdrawals, which are left out entirely before shanghai.
+pub fn encode_block_parts<H, T, W>(
+    header: &H,
+    transactions: &[T],
+    withdrawals: Option<&[W]>,
+) -> Vec<u8>
+where
+    H: Encodable,
+    T: Encodable,
+    W: Encodable,
+{
+    let list = |payload_length| rlp::Header {
+        list: true,
+        payload_length,
+    };
+    let transactions_header = list(transactions.iter().map(Encodable::length).sum());
+    let ommers_header = list(0);
+    let withdrawals_header =
+        withdrawals.map(|withdrawals| list(withdrawals.iter().map(Encodable::length).sum()));
+
+    let payload_length = header.length()
+        + transactions_header.length_with_payload()
+        + ommers_header.length_with_payload()
+        + withdrawals_header.map_or(0, |header| header.length_with_payload());
+
+    let mut out = Vec::with_capacity(list(payload_length).length_with_payload());
+    list(payload_length).encode(&mut out);
+    header.encode(&mut out);
+    transactions_header.encode(&mut out);
+    for tx in transactions {
+        tx.encode(&mut out);
+    }
+    ommers_header.encode(&mut out);
+    if let (Some(header), Some(withdrawals)) = (withdrawals_header, withdrawals) {
+        header.encode(&mut out);
+        for withdrawal in withdrawals {
+            withdrawal.encode(&mut out);
+        }
+    }
+    out
 }
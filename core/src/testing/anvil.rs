@@ -0,0 +1,91 @@
+use std::env;
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use eyre::{eyre, Result};
+
+/// How long a freshly spawned node gets to start listening.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A local anvil node, killed when dropped.
+///
+/// Blocks are mined as soon as a transaction arrives, and the unlocked development accounts
+/// accept `eth_sendTransaction`. The hardfork is pinned to cancun so the responses match the
+/// fork schedule tests run the client with.
+#[derive(Debug)]
+pub struct Anvil {
+    child: Child,
+    endpoint: String,
+}
+
+impl Anvil {
+    /// Locates the anvil binary: `ANVIL_BIN` if set, otherwise `anvil` on the `PATH`, otherwise
+    /// the default foundryup install location.
+    pub fn binary() -> Option<PathBuf> {
+        if let Some(path) = env::var_os("ANVIL_BIN") {
+            return Some(PathBuf::from(path));
+        }
+
+        let on_path = env::var_os("PATH").and_then(|paths| {
+            env::split_paths(&paths)
+                .map(|dir| dir.join("anvil"))
+                .find(|path| path.is_file())
+        });
+
+        on_path.or_else(|| {
+            let home = env::var_os("HOME")?;
+            let path = PathBuf::from(home).join(".foundry/bin/anvil");
+            path.is_file().then_some(path)
+        })
+    }
+
+    /// Starts a node and waits until it accepts connections. Returns `None` when no anvil
+    /// binary is found, so callers can skip instead of failing.
+    pub fn spawn() -> Result<Option<Self>> {
+        let Some(binary) = Self::binary() else {
+            return Ok(None);
+        };
+
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?
+            .local_addr()?
+            .port();
+        let child = Command::new(&binary)
+            .args(["--port", &port.to_string(), "--hardfork", "cancun"])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|err| eyre!("failed to start {}: {err}", binary.display()))?;
+
+        let mut anvil = Self {
+            child,
+            endpoint: format!("http://127.0.0.1:{port}"),
+        };
+
+        let started = Instant::now();
+        while TcpStream::connect((Ipv4Addr::LOCALHOST, port)).is_err() {
+            if let Some(status) = anvil.child.try_wait()? {
+                return Err(eyre!("anvil exited during startup: {status}"));
+            }
+            if started.elapsed() > STARTUP_TIMEOUT {
+                return Err(eyre!("anvil did not listen on port {port} in time"));
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        Ok(Some(anvil))
+    }
+
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+}
+
+impl Drop for Anvil {
+    fn drop(&mut self) {
+        _ = self.child.kill();
+        _ = self.child.wait();
+    }
+}
@@ -0,0 +1,998 @@
+//! Seeded generator for small, internally consistent execution chains.
+//!
+//! Every block links to its parent, commits to its transactions, receipts and withdrawals
+//! with correctly computed roots, and shares a single state root whose account and storage
+//! proofs can be served by [`MockChain::proof`]. The same seed always produces the same
+//! chain, so tests can assert on exact hashes across runs.
+//!
+//! Light client updates of these blocks are signed by the seeded sync committees of
+//! `helios_consensus_core::testing`, behind that crate's `testing` feature.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use alloy::consensus::proofs::{calculate_transaction_root, calculate_withdrawals_root};
+use alloy::consensus::{
+    Eip658Value, Header as ConsensusHeader, Receipt, ReceiptWithBloom, SignableTransaction,
+    TxEip1559, TxEnvelope, TxLegacy, TxType,
+};
+use alloy::eips::eip4895::{Withdrawal, Withdrawals};
+use alloy::primitives::{
+    b256, keccak256, Address, Bloom, Bytes, Log as PrimitiveLog, LogData, PrimitiveSignature,
+    TxKind, B256, B64, U256,
+};
+use alloy::rlp;
+use alloy::rpc::types::{
+    Block, BlockTransactions, EIP1186AccountProofResponse, EIP1186StorageProof, Header, Log,
+    Transaction, TransactionReceipt,
+};
+use alloy_trie::proof::ProofRetainer;
+use alloy_trie::root::ordered_trie_root_with_encoder;
+use alloy_trie::{HashBuilder, Nibbles, TrieAccount, EMPTY_ROOT_HASH};
+
+use crate::execution::blob::BlobParams;
+use crate::fork_schedule::ForkSchedule;
+
+const GENESIS_TIMESTAMP: u64 = 1_700_000_000;
+const SECONDS_PER_BLOCK: u64 = 12;
+const BASE_FEE: u64 = 1_000_000_000;
+const GAS_LIMIT: u64 = 30_000_000;
+const TRANSFER_GAS: u64 = 21_000;
+const TOKEN_TRANSFER_GAS: u64 = 51_000;
+// sha256 of an empty list of execution layer requests
+const EMPTY_REQUESTS_HASH: B256 =
+    b256!("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+
+/// Execution layer forks that change the shape of generated headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fork {
+    Paris,
+    Shanghai,
+    Cancun,
+    Prague,
+}
+
+/// Configures and generates a [`MockChain`].
+#[derive(Debug, Clone)]
+pub struct ChainBuilder {
+    seed: u64,
+    chain_id: u64,
+    start: u64,
+    length: u64,
+    txs_per_block: usize,
+    accounts: usize,
+    funded: BTreeMap<Address, (u64, U256)>,
+    empty_blocks: BTreeSet<u64>,
+    forks: BTreeMap<u64, Fork>,
+    genesis_timestamp: u64,
+    typed_txs: bool,
+    blob_gas_used: u64,
+    excess_blob_gas: u64,
+    withdrawals_per_block: usize,
+}
+
+impl ChainBuilder {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            chain_id: 1,
+            start: 1,
+            length: 8,
+            txs_per_block: 4,
+            accounts: 4,
+            funded: BTreeMap::new(),
+            empty_blocks: BTreeSet::new(),
+            forks: BTreeMap::from([(0, Fork::Cancun)]),
+            genesis_timestamp: GENESIS_TIMESTAMP,
+            typed_txs: false,
+            blob_gas_used: 0,
+            excess_blob_gas: 0,
+            withdrawals_per_block: 0,
+        }
+    }
+
+    pub fn chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// Number of the first generated block.
+    pub fn start_at(mut self, number: u64) -> Self {
+        self.start = number;
+        self
+    }
+
+    pub fn length(mut self, length: u64) -> Self {
+        self.length = length;
+        self
+    }
+
+    pub fn txs_per_block(mut self, txs_per_block: usize) -> Self {
+        self.txs_per_block = txs_per_block;
+        self
+    }
+
+    /// Number of externally owned accounts sending transactions. A token contract with
+    /// a few storage slots is always added on top of these.
+    pub fn accounts(mut self, accounts: usize) -> Self {
+        self.accounts = accounts.max(1);
+        self
+    }
+
+    /// Adds an externally owned account at `address`, such as that of a test signer, with the
+    /// given nonce and balance.
+    pub fn account(mut self, address: Address, nonce: u64, balance: U256) -> Self {
+        self.funded.insert(address, (nonce, balance));
+        self
+    }
+
+    /// Generates block `number` without any transactions.
+    pub fn empty_block(mut self, number: u64) -> Self {
+        self.empty_blocks.insert(number);
+        self
+    }
+
+    /// Activates `fork` from block `number` onwards.
+    pub fn fork_at(mut self, number: u64, fork: Fork) -> Self {
+        self.forks.insert(number, fork);
+        self
+    }
+
+    /// Unix time block zero would have been produced at, for tests that need the chain to
+    /// look recent. Blocks follow it every 12 seconds.
+    pub fn genesis_timestamp(mut self, timestamp: u64) -> Self {
+        self.genesis_timestamp = timestamp;
+        self
+    }
+
+    /// Makes the plain value transfers EIP-1559 transactions, so blocks mix legacy and typed
+    /// receipts.
+    pub fn typed_txs(mut self) -> Self {
+        self.typed_txs = true;
+        self
+    }
+
+    /// Blob gas used by every block from cancun on, as if it carried blob transactions, and
+    /// the excess blob gas of the first such block. The excess of later blocks follows from
+    /// their parent.
+    pub fn blob_gas(mut self, used: u64, initial_excess: u64) -> Self {
+        self.blob_gas_used = used;
+        self.excess_blob_gas = initial_excess;
+        self
+    }
+
+    /// Number of withdrawals in every block from shanghai on, which otherwise carry an
+    /// empty list.
+    pub fn withdrawals_per_block(mut self, withdrawals_per_block: usize) -> Self {
+        self.withdrawals_per_block = withdrawals_per_block;
+        self
+    }
+
+    pub fn build(self) -> MockChain {
+        let mut rng = SeededRng::new(self.seed);
+        let state = MockState::generate(&mut rng, self.accounts, &self.funded);
+
+        let mut chain = MockChain {
+            config: self,
+            blocks: Vec::new(),
+            receipts: HashMap::new(),
+            state,
+            rng,
+        };
+        chain.extend(chain.config.length);
+        chain
+    }
+
+    fn timestamp(&self, number: u64) -> u64 {
+        self.genesis_timestamp + number * SECONDS_PER_BLOCK
+    }
+
+    fn fork(&self, number: u64) -> Fork {
+        self.forks
+            .range(..=number)
+            .next_back()
+            .map(|(_, fork)| *fork)
+            .unwrap_or(Fork::Paris)
+    }
+}
+
+/// A deterministic chain generated by [`ChainBuilder`].
+#[derive(Debug, Clone)]
+pub struct MockChain {
+    config: ChainBuilder,
+    blocks: Vec<Block<Transaction>>,
+    receipts: HashMap<u64, Vec<TransactionReceipt>>,
+    state: MockState,
+    rng: SeededRng,
+}
+
+impl MockChain {
+    pub fn chain_id(&self) -> u64 {
+        self.config.chain_id
+    }
+
+    pub fn blocks(&self) -> &[Block<Transaction>] {
+        &self.blocks
+    }
+
+    pub fn block(&self, number: u64) -> Option<&Block<Transaction>> {
+        self.blocks
+            .iter()
+            .find(|block| block.header.number == number)
+    }
+
+    pub fn block_by_hash(&self, hash: B256) -> Option<&Block<Transaction>> {
+        self.blocks.iter().find(|block| block.header.hash == hash)
+    }
+
+    pub fn head(&self) -> &Block<Transaction> {
+        self.blocks.last().expect("chain has at least one block")
+    }
+
+    pub fn receipts(&self, number: u64) -> Option<&[TransactionReceipt]> {
+        self.receipts.get(&number).map(Vec::as_slice)
+    }
+
+    pub fn transaction(&self, hash: B256) -> Option<&Transaction> {
+        self.blocks
+            .iter()
+            .filter_map(|block| block.transactions.as_transactions())
+            .flatten()
+            .find(|tx| *tx.inner.tx_hash() == hash)
+    }
+
+    pub fn receipt(&self, hash: B256) -> Option<&TransactionReceipt> {
+        self.receipts
+            .values()
+            .flatten()
+            .find(|receipt| receipt.transaction_hash == hash)
+    }
+
+    /// Mutable access to a receipt, for serving a provider response that disagrees with the
+    /// generated chain.
+    pub fn receipt_mut(&mut self, hash: B256) -> Option<&mut TransactionReceipt> {
+        self.receipts
+            .values_mut()
+            .flatten()
+            .find(|receipt| receipt.transaction_hash == hash)
+    }
+
+    /// All logs emitted on the chain, ordered by block.
+    pub fn logs(&self) -> Vec<Log> {
+        self.blocks
+            .iter()
+            .filter_map(|block| self.receipts.get(&block.header.number))
+            .flatten()
+            .flat_map(|receipt| receipt.inner.logs().to_vec())
+            .collect()
+    }
+
+    /// Addresses of every account in the state, including the token contract.
+    pub fn accounts(&self) -> Vec<Address> {
+        self.state.accounts.keys().copied().collect()
+    }
+
+    /// Address of the contract emitting the `Transfer` logs.
+    pub fn token(&self) -> Address {
+        self.state.token
+    }
+
+    /// Storage slots set on `address`.
+    pub fn storage(&self, address: Address) -> Vec<(B256, U256)> {
+        self.state
+            .accounts
+            .get(&address)
+            .map(|account| {
+                account
+                    .storage
+                    .iter()
+                    .map(|(slot, value)| (*slot, *value))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn code(&self, address: Address) -> Bytes {
+        self.state
+            .accounts
+            .get(&address)
+            .map(|account| account.code.clone())
+            .unwrap_or_default()
+    }
+
+    /// State root shared by every block of the chain.
+    pub fn state_root(&self) -> B256 {
+        self.state.root
+    }
+
+    /// Builds an `eth_getProof` response for `address` and `slots` against the state root.
+    pub fn proof(&self, address: Address, slots: &[B256]) -> EIP1186AccountProofResponse {
+        self.state.proof(address, slots)
+    }
+
+    /// Fork schedule matching the timestamps at which the configured forks activate.
+    pub fn fork_schedule(&self) -> ForkSchedule {
+        let prague_timestamp = self
+            .config
+            .forks
+            .iter()
+            .find(|(_, fork)| **fork >= Fork::Prague)
+            .map(|(number, _)| self.config.timestamp(*number))
+            .unwrap_or(u64::MAX);
+
+        // generated chains start after the merge
+        ForkSchedule {
+            prague_timestamp,
+            merge_block: 0,
+        }
+    }
+
+    /// Appends `count` blocks on top of the current head.
+    pub fn extend(&mut self, count: u64) {
+        for _ in 0..count {
+            let mut rng = self.rng.clone();
+            self.push_block(&mut rng, Bytes::new());
+            self.rng = rng;
+        }
+    }
+
+    /// Returns a competing chain that replaces the last `depth` blocks with a new branch of
+    /// the same length. Different `seed`s produce different branches.
+    pub fn reorg(&self, depth: u64, seed: u64) -> MockChain {
+        let keep = self.blocks.len().saturating_sub(depth as usize);
+        let mut chain = MockChain {
+            config: self.config.clone(),
+            blocks: self.blocks[..keep].to_vec(),
+            receipts: HashMap::new(),
+            state: self.state.clone(),
+            rng: SeededRng::new(seed),
+        };
+        let mut rng = SeededRng::new(seed);
+        for block in &chain.blocks {
+            let number = block.header.number;
+            if let Some(receipts) = self.receipts.get(&number) {
+                chain.receipts.insert(number, receipts.clone());
+            }
+        }
+
+        // the branch marker keeps hashes distinct even if the generated bodies collide
+        let marker = Bytes::from(seed.to_be_bytes().to_vec());
+        for _ in 0..depth {
+            chain.push_block(&mut rng, marker.clone());
+        }
+        chain.rng = rng;
+
+        chain
+    }
+
+    /// Returns a competing chain that replaces the last `depth` blocks with blocks holding the
+    /// same transactions under other hashes, as when a reorg includes them again. Different
+    /// `seed`s produce different hashes.
+    pub fn reorg_keeping_txs(&self, depth: u64, seed: u64) -> MockChain {
+        let mut chain = self.clone();
+        let keep = chain.blocks.len().saturating_sub(depth as usize);
+        let marker = Bytes::from(seed.to_be_bytes().to_vec());
+
+        for index in keep..chain.blocks.len() {
+            let parent_hash = index
+                .checked_sub(1)
+                .map(|parent| chain.blocks[parent].header.hash)
+                .unwrap_or_default();
+            let block = &mut chain.blocks[index];
+            let mut inner = block.header.inner.clone();
+            inner.parent_hash = parent_hash;
+            inner.extra_data = marker.clone();
+            let hash = inner.hash_slow();
+            block.header.hash = hash;
+            block.header.inner = inner;
+
+            if let BlockTransactions::Full(txs) = &mut block.transactions {
+                for tx in txs {
+                    tx.block_hash = Some(hash);
+                }
+            }
+            // receipts and their logs name the block they are in
+            let number = block.header.number;
+            for receipt in chain.receipts.get_mut(&number).into_iter().flatten() {
+                let mut json = serde_json::to_value(&*receipt).expect("receipt serializes");
+                json["blockHash"] = serde_json::json!(hash);
+                for log in json["logs"].as_array_mut().into_iter().flatten() {
+                    log["blockHash"] = serde_json::json!(hash);
+                }
+                *receipt = serde_json::from_value(json).expect("rehashed receipt is valid");
+            }
+        }
+
+        chain
+    }
+
+    fn push_block(&mut self, rng: &mut SeededRng, extra_data: Bytes) {
+        let parent = self.blocks.last().map(|block| &block.header);
+        let number = parent
+            .map(|parent| parent.number + 1)
+            .unwrap_or(self.config.start);
+        let parent_hash = parent.map(|parent| parent.hash).unwrap_or_default();
+        let timestamp = self.config.timestamp(number);
+        let fork = self.config.fork(number);
+
+        let tx_count = if self.config.empty_blocks.contains(&number) {
+            0
+        } else {
+            self.config.txs_per_block
+        };
+        let mut txs = (0..tx_count)
+            .map(|index| self.generate_tx(rng, number, index, tx_count))
+            .collect::<Vec<_>>();
+        let mut cumulative_gas_used = 0;
+        for tx in &mut txs {
+            cumulative_gas_used += tx.gas_used;
+            tx.cumulative_gas_used = cumulative_gas_used;
+        }
+
+        let envelopes = txs.iter().map(|tx| tx.envelope.clone()).collect::<Vec<_>>();
+        let receipts_encoded = txs
+            .iter()
+            .map(GeneratedTx::encode_receipt)
+            .collect::<Vec<_>>();
+        let mut logs_bloom = Bloom::ZERO;
+        for log in txs.iter().flat_map(|tx| &tx.logs) {
+            logs_bloom.accrue_log(log);
+        }
+        let withdrawals = (fork >= Fork::Shanghai).then(|| self.generate_withdrawals(rng, number));
+
+        let inner = ConsensusHeader {
+            parent_hash,
+            ommers_hash: keccak256([rlp::EMPTY_LIST_CODE]),
+            beneficiary: rng.address(),
+            state_root: self.state.root,
+            transactions_root: calculate_transaction_root(&envelopes),
+            receipts_root: ordered_trie_root(&receipts_encoded),
+            withdrawals_root: withdrawals.as_deref().map(calculate_withdrawals_root),
+            logs_bloom,
+            difficulty: U256::ZERO,
+            number,
+            gas_limit: GAS_LIMIT,
+            gas_used: cumulative_gas_used,
+            timestamp,
+            mix_hash: rng.b256(),
+            nonce: B64::ZERO,
+            base_fee_per_gas: Some(BASE_FEE),
+            blob_gas_used: (fork >= Fork::Cancun).then_some(self.config.blob_gas_used),
+            excess_blob_gas: (fork >= Fork::Cancun).then(|| self.excess_blob_gas(fork)),
+            parent_beacon_block_root: (fork >= Fork::Cancun).then(|| rng.b256()),
+            requests_hash: (fork >= Fork::Prague).then_some(EMPTY_REQUESTS_HASH),
+            extra_data,
+        };
+        let hash = inner.hash_slow();
+
+        let mut log_index = 0;
+        let receipts = txs
+            .iter()
+            .enumerate()
+            .map(|(index, tx)| {
+                let receipt = tx.rpc_receipt(hash, number, timestamp, index as u64, log_index);
+                log_index += tx.logs.len() as u64;
+                receipt
+            })
+            .collect::<Vec<_>>();
+
+        let transactions = txs
+            .into_iter()
+            .enumerate()
+            .map(|(index, tx)| Transaction {
+                block_hash: Some(hash),
+                block_number: Some(number),
+                transaction_index: Some(index as u64),
+                from: tx.from,
+                effective_gas_price: Some(tx.gas_price),
+                inner: tx.envelope,
+            })
+            .collect::<Vec<_>>();
+
+        let header = Header {
+            hash,
+            inner,
+            total_difficulty: Some(U256::ZERO),
+            size: None,
+        };
+        let block = Block::new(header, BlockTransactions::Full(transactions))
+            .with_withdrawals(withdrawals.map(Withdrawals::new));
+
+        self.receipts.insert(number, receipts);
+        self.blocks.push(block);
+    }
+
+    fn generate_withdrawals(&self, rng: &mut SeededRng, number: u64) -> Vec<Withdrawal> {
+        let count = self.config.withdrawals_per_block;
+        // indices run on from those of earlier blocks, as they do on chain
+        let first = number.saturating_sub(self.config.start) * count as u64;
+        (0..count as u64)
+            .map(|offset| Withdrawal {
+                index: first + offset,
+                validator_index: rng.below(1 << 20),
+                address: rng.address(),
+                amount: 1 + rng.below(32_000_000_000),
+            })
+            .collect()
+    }
+
+    fn excess_blob_gas(&self, fork: Fork) -> u64 {
+        let params = if fork >= Fork::Prague {
+            BlobParams::PRAGUE
+        } else {
+            BlobParams::CANCUN
+        };
+        let parent = self.blocks.last().map(|block| &block.header);
+        match parent.and_then(|parent| parent.excess_blob_gas.zip(parent.blob_gas_used)) {
+            Some((excess, used)) => params.next_excess_blob_gas(excess, used),
+            None => self.config.excess_blob_gas,
+        }
+    }
+
+    fn generate_tx(
+        &self,
+        rng: &mut SeededRng,
+        number: u64,
+        index: usize,
+        tx_count: usize,
+    ) -> GeneratedTx {
+        let senders = self.state.senders();
+        let from = senders[rng.below(senders.len() as u64) as usize];
+        let recipient = senders[rng.below(senders.len() as u64) as usize];
+        let gas_price = u128::from(BASE_FEE) * 2;
+
+        // every other transaction is a token transfer emitting a single log
+        let (to, value, gas_used, logs) = if index % 2 == 0 {
+            let amount = U256::from(rng.below(1_000_000) + 1);
+            let log = PrimitiveLog {
+                address: self.state.token,
+                data: LogData::new_unchecked(
+                    vec![
+                        keccak256("Transfer(address,address,uint256)"),
+                        from.into_word(),
+                        recipient.into_word(),
+                    ],
+                    amount.to_be_bytes_vec().into(),
+                ),
+            };
+            (self.state.token, U256::ZERO, TOKEN_TRANSFER_GAS, vec![log])
+        } else {
+            let value = U256::from(rng.below(1_000_000_000_000_000_000) + 1);
+            (recipient, value, TRANSFER_GAS, vec![])
+        };
+
+        let nonce = number * tx_count as u64 + index as u64;
+        // not a recoverable signature, the sender is carried on the rpc transaction instead
+        let signature = PrimitiveSignature::new(
+            U256::from(rng.next_u64() | 1),
+            U256::from(rng.next_u64() | 1),
+            false,
+        );
+        let envelope = if self.config.typed_txs && index % 2 == 1 {
+            let tx = TxEip1559 {
+                chain_id: self.config.chain_id,
+                nonce,
+                gas_limit: gas_used,
+                max_fee_per_gas: gas_price,
+                max_priority_fee_per_gas: gas_price - u128::from(BASE_FEE),
+                to: TxKind::Call(to),
+                value,
+                access_list: Default::default(),
+                input: Bytes::new(),
+            };
+            TxEnvelope::Eip1559(tx.into_signed(signature))
+        } else {
+            let tx = TxLegacy {
+                chain_id: Some(self.config.chain_id),
+                nonce,
+                gas_price,
+                gas_limit: gas_used,
+                to: TxKind::Call(to),
+                value,
+                input: Bytes::new(),
+            };
+            TxEnvelope::Legacy(tx.into_signed(signature))
+        };
+
+        GeneratedTx {
+            envelope,
+            from,
+            to,
+            gas_price,
+            gas_used,
+            cumulative_gas_used: 0,
+            logs,
+        }
+    }
+}
+
+struct GeneratedTx {
+    envelope: TxEnvelope,
+    from: Address,
+    to: Address,
+    gas_price: u128,
+    gas_used: u64,
+    cumulative_gas_used: u64,
+    logs: Vec<PrimitiveLog>,
+}
+
+impl GeneratedTx {
+    fn bloom(&self) -> Bloom {
+        let mut bloom = Bloom::ZERO;
+        for log in &self.logs {
+            bloom.accrue_log(log);
+        }
+        bloom
+    }
+
+    /// Encodes the receipt the same way its receipts root entry is built.
+    fn encode_receipt(&self) -> Vec<u8> {
+        let receipt = Receipt {
+            status: Eip658Value::Eip658(true),
+            cumulative_gas_used: self.cumulative_gas_used,
+            logs: self.logs.clone(),
+        };
+
+        let mut encoded = match self.envelope.tx_type() {
+            TxType::Legacy => Vec::new(),
+            tx_type => vec![tx_type as u8],
+        };
+        encoded.extend(rlp::encode(ReceiptWithBloom::new(receipt, self.bloom())));
+        encoded
+    }
+
+    fn rpc_receipt(
+        &self,
+        block_hash: B256,
+        block_number: u64,
+        block_timestamp: u64,
+        transaction_index: u64,
+        first_log_index: u64,
+    ) -> TransactionReceipt {
+        let transaction_hash = *self.envelope.tx_hash();
+        let logs = self
+            .logs
+            .iter()
+            .enumerate()
+            .map(|(i, log)| Log {
+                inner: log.clone(),
+                block_hash: Some(block_hash),
+                block_number: Some(block_number),
+                block_timestamp: Some(block_timestamp),
+                transaction_hash: Some(transaction_hash),
+                transaction_index: Some(transaction_index),
+                log_index: Some(first_log_index + i as u64),
+                removed: false,
+            })
+            .collect::<Vec<_>>();
+
+        // go through the json representation, like receipts returned by a provider
+        let receipt = serde_json::json!({
+            "type": quantity(self.envelope.tx_type() as u64),
+            "status": "0x1",
+            "cumulativeGasUsed": quantity(self.cumulative_gas_used),
+            "logs": logs,
+            "logsBloom": self.bloom(),
+            "transactionHash": transaction_hash,
+            "transactionIndex": quantity(transaction_index),
+            "blockHash": block_hash,
+            "blockNumber": quantity(block_number),
+            "gasUsed": quantity(self.gas_used),
+            "effectiveGasPrice": format!("{:#x}", self.gas_price),
+            "from": self.from,
+            "to": self.to,
+            "contractAddress": null,
+        });
+
+        serde_json::from_value(receipt).expect("generated receipt is valid")
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockAccount {
+    nonce: u64,
+    balance: U256,
+    code: Bytes,
+    storage: BTreeMap<B256, U256>,
+}
+
+impl MockAccount {
+    fn storage_root(&self, slots: &[B256]) -> (B256, Vec<Vec<Bytes>>) {
+        let leaves = self
+            .storage
+            .iter()
+            .map(|(slot, value)| (keccak256(slot), rlp::encode(value)))
+            .collect::<BTreeMap<_, _>>();
+        let targets = slots.iter().map(keccak256).collect::<Vec<_>>();
+
+        build_trie(&leaves, &targets)
+    }
+
+    fn trie_account(&self) -> TrieAccount {
+        TrieAccount {
+            nonce: self.nonce,
+            balance: self.balance,
+            storage_root: self.storage_root(&[]).0,
+            code_hash: keccak256(&self.code),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct MockState {
+    accounts: BTreeMap<Address, MockAccount>,
+    token: Address,
+    root: B256,
+}
+
+impl MockState {
+    fn generate(
+        rng: &mut SeededRng,
+        count: usize,
+        funded: &BTreeMap<Address, (u64, U256)>,
+    ) -> Self {
+        let mut accounts = BTreeMap::new();
+        for _ in 0..count {
+            accounts.insert(
+                rng.address(),
+                MockAccount {
+                    nonce: rng.below(100),
+                    balance: U256::from(rng.next_u64()) * U256::from(1_000_000_000u64),
+                    code: Bytes::new(),
+                    storage: BTreeMap::new(),
+                },
+            );
+        }
+        for (address, (nonce, balance)) in funded {
+            accounts.insert(
+                *address,
+                MockAccount {
+                    nonce: *nonce,
+                    balance: *balance,
+                    code: Bytes::new(),
+                    storage: BTreeMap::new(),
+                },
+            );
+        }
+
+        let token = rng.address();
+        let storage = (0..4u64)
+            .map(|slot| (B256::from(U256::from(slot)), U256::from(rng.next_u64() | 1)))
+            .collect();
+        accounts.insert(
+            token,
+            MockAccount {
+                nonce: 1,
+                balance: U256::ZERO,
+                // PUSH1 0 PUSH1 0 REVERT
+                code: Bytes::from_static(&[0x60, 0x00, 0x60, 0x00, 0xfd]),
+                storage,
+            },
+        );
+
+        let mut state = Self {
+            accounts,
+            token,
+            root: B256::ZERO,
+        };
+        state.root = state.account_trie(&[]).0;
+        state
+    }
+
+    fn senders(&self) -> Vec<Address> {
+        self.accounts
+            .keys()
+            .filter(|address| **address != self.token)
+            .copied()
+            .collect()
+    }
+
+    fn account_trie(&self, targets: &[Address]) -> (B256, Vec<Vec<Bytes>>) {
+        let leaves = self
+            .accounts
+            .iter()
+            .map(|(address, account)| (keccak256(address), rlp::encode(account.trie_account())))
+            .collect::<BTreeMap<_, _>>();
+        let targets = targets.iter().map(keccak256).collect::<Vec<_>>();
+
+        build_trie(&leaves, &targets)
+    }
+
+    fn proof(&self, address: Address, slots: &[B256]) -> EIP1186AccountProofResponse {
+        let (_, mut account_proofs) = self.account_trie(&[address]);
+        let account_proof = account_proofs.pop().unwrap_or_default();
+
+        let Some(account) = self.accounts.get(&address) else {
+            return EIP1186AccountProofResponse {
+                address,
+                code_hash: keccak256([]),
+                storage_hash: EMPTY_ROOT_HASH,
+                account_proof,
+                storage_proof: slots
+                    .iter()
+                    .map(|slot| EIP1186StorageProof {
+                        key: (*slot).into(),
+                        value: U256::ZERO,
+                        proof: vec![],
+                    })
+                    .collect(),
+                ..Default::default()
+            };
+        };
+
+        let (storage_hash, storage_proofs) = account.storage_root(slots);
+        let storage_proof = slots
+            .iter()
+            .zip(storage_proofs)
+            .map(|(slot, proof)| EIP1186StorageProof {
+                key: (*slot).into(),
+                value: account.storage.get(slot).copied().unwrap_or_default(),
+                proof,
+            })
+            .collect();
+
+        EIP1186AccountProofResponse {
+            address,
+            balance: account.balance,
+            code_hash: keccak256(&account.code),
+            nonce: account.nonce,
+            storage_hash,
+            account_proof,
+            storage_proof,
+        }
+    }
+}
+
+/// Builds a trie from pre-hashed, sorted leaves, returning its root and the proof of each
+/// target key in order.
+fn build_trie(leaves: &BTreeMap<B256, Vec<u8>>, targets: &[B256]) -> (B256, Vec<Vec<Bytes>>) {
+    let retainer = ProofRetainer::new(targets.iter().map(Nibbles::unpack).collect());
+    let mut builder = HashBuilder::default().with_proof_retainer(retainer);
+    for (key, value) in leaves {
+        builder.add_leaf(Nibbles::unpack(key), value);
+    }
+
+    let root = builder.root();
+    let nodes = builder.take_proof_nodes();
+    let proofs = targets
+        .iter()
+        .map(|target| {
+            nodes
+                .matching_nodes_sorted(&Nibbles::unpack(target))
+                .into_iter()
+                .map(|(_, node)| node)
+                .collect()
+        })
+        .collect();
+
+    (root, proofs)
+}
+
+fn ordered_trie_root(items: &[Vec<u8>]) -> B256 {
+    ordered_trie_root_with_encoder(items, |item: &Vec<u8>, buffer: &mut Vec<u8>| {
+        buffer.extend_from_slice(item)
+    })
+}
+
+fn quantity(value: u64) -> String {
+    format!("{value:#x}")
+}
+
+/// splitmix64, which is plenty for fixtures and keeps the output stable across platforms.
+#[derive(Debug, Clone)]
+struct SeededRng(u64);
+
+impl SeededRng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound.max(1)
+    }
+
+    fn b256(&mut self) -> B256 {
+        let mut bytes = [0u8; 32];
+        for chunk in bytes.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next_u64().to_be_bytes());
+        }
+        B256::from(bytes)
+    }
+
+    fn address(&mut self) -> Address {
+        Address::from_word(self.b256())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::execution::proof::{verify_account_proof, verify_storage_proof};
+
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_chain() {
+        let a = ChainBuilder::new(7).build();
+        let b = ChainBuilder::new(7).build();
+        let c = ChainBuilder::new(8).build();
+
+        assert_eq!(a.head().header.hash, b.head().header.hash);
+        assert_ne!(a.head().header.hash, c.head().header.hash);
+    }
+
+    #[test]
+    fn test_parent_links() {
+        let chain = ChainBuilder::new(1).start_at(100).length(5).build();
+
+        for pair in chain.blocks().windows(2) {
+            assert_eq!(pair[1].header.parent_hash, pair[0].header.hash);
+            assert_eq!(pair[1].header.number, pair[0].header.number + 1);
+            assert_eq!(pair[0].header.inner.hash_slow(), pair[0].header.hash);
+        }
+    }
+
+    #[test]
+    fn test_proofs_verify() {
+        let chain = ChainBuilder::new(3).build();
+        let token = chain.token();
+        let slots = chain
+            .storage(token)
+            .into_iter()
+            .map(|(slot, _)| slot)
+            .chain([B256::repeat_byte(0xff)])
+            .collect::<Vec<_>>();
+
+        let proof = chain.proof(token, &slots);
+        verify_account_proof(&proof, chain.state_root()).unwrap();
+        let values = verify_storage_proof(&proof).unwrap();
+        assert_eq!(values[&B256::repeat_byte(0xff)], U256::ZERO);
+
+        for address in chain.accounts() {
+            verify_account_proof(&chain.proof(address, &[]), chain.state_root()).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_reorg_keeps_common_prefix() {
+        let chain = ChainBuilder::new(5).length(6).build();
+        let fork = chain.reorg(2, 99);
+
+        assert_eq!(fork.blocks().len(), chain.blocks().len());
+        assert_eq!(fork.blocks()[3].header.hash, chain.blocks()[3].header.hash);
+        assert_ne!(fork.head().header.hash, chain.head().header.hash);
+        assert_eq!(
+            fork.blocks()[4].header.parent_hash,
+            chain.blocks()[3].header.hash
+        );
+    }
+
+    #[test]
+    fn test_fork_transitions() {
+        let chain = ChainBuilder::new(2)
+            .start_at(0)
+            .length(4)
+            .fork_at(0, Fork::Paris)
+            .fork_at(1, Fork::Shanghai)
+            .fork_at(3, Fork::Prague)
+            .empty_block(2)
+            .build();
+
+        let blocks = chain.blocks();
+        assert!(blocks[0].header.withdrawals_root.is_none());
+        assert_eq!(blocks[1].header.withdrawals_root, Some(EMPTY_ROOT_HASH));
+        assert!(blocks[1].header.parent_beacon_block_root.is_none());
+        assert!(blocks[3].header.requests_hash.is_some());
+        assert!(blocks[2].transactions.is_empty());
+        assert_eq!(
+            chain.fork_schedule().prague_timestamp,
+            chain.config.timestamp(3)
+        );
+    }
+}
@@ -0,0 +1,15 @@
+//! Contract code installed through state overrides by the evm tests.
+
+use alloy::primitives::{hex, Bytes};
+
+/// Code that reverts with `data` whatever it is called with.
+pub fn revert_code(data: &[u8]) -> Bytes {
+    let len = u16::try_from(data.len()).unwrap().to_be_bytes();
+    // the revert data is copied from the code after the 14 byte prefix
+    let mut code = vec![0x61, len[0], len[1]];
+    code.extend(hex!("600e600039"));
+    code.extend([0x61, len[0], len[1]]);
+    code.extend(hex!("6000fd"));
+    code.extend(data);
+    code.into()
+}
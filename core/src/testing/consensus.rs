@@ -0,0 +1,210 @@
+use alloy::consensus::BlockHeader;
+use alloy::network::{primitives::HeaderResponse, BlockResponse, TransactionResponse};
+use alloy::rpc::types::{Block, Transaction};
+use eyre::{eyre, Result};
+use serde::Serialize;
+use tokio::sync::{mpsc, watch};
+
+use crate::consensus::Consensus;
+use crate::execution::backfill::BackfillConfig;
+use crate::execution::config::ExecutionConfig;
+use crate::execution::rpc::ExecutionRpc;
+use crate::execution::state::State;
+use crate::execution::ExecutionClient;
+use crate::network_spec::NetworkSpec;
+use crate::types::{BlockTag, NetworkInfo};
+
+use super::chain::MockChain;
+use super::rpc::ChainRpc;
+
+/// Stands in for the consensus client in tests against a real execution node, handing the
+/// node's own blocks to the state as if they were verified.
+///
+/// Nothing is fetched until [`ManualConsensus::advance`] is called, so tests decide exactly
+/// which blocks the client knows about.
+pub struct ManualConsensus<N: NetworkSpec, R: ExecutionRpc<N>> {
+    rpc: R,
+    state: State<N, R>,
+}
+
+impl<N: NetworkSpec, R: ExecutionRpc<N>> ManualConsensus<N, R> {
+    pub fn new(rpc: R, history_length: usize) -> Self {
+        let state = State::unsynced(history_length, rpc.clone());
+        Self { rpc, state }
+    }
+
+    pub fn state(&self) -> State<N, R> {
+        self.state.clone()
+    }
+
+    /// Pushes the node's latest block as the new head, backfilling any blocks since the last
+    /// head, and returns it.
+    pub async fn advance(&self) -> Result<N::BlockResponse> {
+        let block = self.fetch(BlockTag::Latest).await?;
+        self.state.push_block(block.clone()).await;
+        Ok(block)
+    }
+
+    /// Marks the node's block at `tag` as finalized.
+    pub async fn finalize(&self, tag: BlockTag) -> Result<N::BlockResponse> {
+        let block = self.fetch(tag).await?;
+        self.state.push_finalized_block(block.clone()).await;
+        Ok(block)
+    }
+
+    async fn fetch(&self, tag: BlockTag) -> Result<N::BlockResponse> {
+        let block = self
+            .rpc
+            .get_block_by_number(tag, true)
+            .await?
+            .ok_or_else(|| eyre!("node has no block at {tag}"))?;
+
+        if !N::is_hash_valid(&block) {
+            let number = block.header().number();
+            return Err(eyre!("node returned block {number} with an invalid hash"));
+        }
+        Ok(block)
+    }
+}
+
+/// A [`Consensus`] client for driving a whole node in tests, verifying whatever blocks its
+/// [`ConsensusFeed`] is handed.
+pub struct MockConsensus<B> {
+    chain_id: u64,
+    block_recv: Option<mpsc::Receiver<B>>,
+    finalized_block_recv: Option<watch::Receiver<Option<B>>>,
+    head_recv: watch::Receiver<Option<B>>,
+}
+
+/// Hands blocks to a [`MockConsensus`] as if they were verified.
+#[derive(Clone)]
+pub struct ConsensusFeed<B> {
+    block_send: mpsc::Sender<B>,
+    finalized_block_send: watch::Sender<Option<B>>,
+    head_send: watch::Sender<Option<B>>,
+}
+
+impl<B: Clone> MockConsensus<B> {
+    pub fn new(chain_id: u64) -> (Self, ConsensusFeed<B>) {
+        let (block_send, block_recv) = mpsc::channel(256);
+        let (finalized_block_send, finalized_block_recv) = watch::channel(None);
+        let (head_send, head_recv) = watch::channel(None);
+
+        let consensus = Self {
+            chain_id,
+            block_recv: Some(block_recv),
+            finalized_block_recv: Some(finalized_block_recv),
+            head_recv,
+        };
+        let feed = ConsensusFeed {
+            block_send,
+            finalized_block_send,
+            head_send,
+        };
+        (consensus, feed)
+    }
+}
+
+/// The execution settings to build a node on a [`MockConsensus`] with. The feed hands over
+/// every block a test needs, so there is nothing to backfill.
+pub fn execution_config() -> ExecutionConfig {
+    ExecutionConfig {
+        backfill: BackfillConfig::disabled(),
+        ..Default::default()
+    }
+}
+
+/// An execution client over a [`ChainRpc`] serving `chain`, whose state holds every block of
+/// the chain as if consensus had verified them, for tests driving the execution client
+/// without a node. Returns the state and the rpc as well, which share the client's.
+pub async fn verified_client<N>(
+    chain: &MockChain,
+) -> (ExecutionClient<N, ChainRpc>, State<N, ChainRpc>, ChainRpc)
+where
+    N: NetworkSpec<BlockResponse = Block<Transaction>>,
+{
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let (client, state) = verified_client_with(chain, rpc.clone()).await;
+    (client, state, rpc)
+}
+
+/// Like [`verified_client`], over `rpc` instead, such as a scripted one.
+pub async fn verified_client_with<N, R>(
+    chain: &MockChain,
+    rpc: R,
+) -> (ExecutionClient<N, R>, State<N, R>)
+where
+    N: NetworkSpec<BlockResponse = Block<Transaction>>,
+    R: ExecutionRpc<N>,
+{
+    let state = State::unsynced(64, rpc.clone());
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+    let client = ExecutionClient::with_rpc(rpc, state.clone(), chain.fork_schedule());
+    (client, state)
+}
+
+impl<B: Clone> ConsensusFeed<B> {
+    /// Publishes `block` as the verified head and then sends it to the state, in the same
+    /// order as the real consensus clients.
+    pub async fn advance(&self, block: B) -> Result<()> {
+        self.head_send.send_replace(Some(block.clone()));
+        self.block_send.send(block).await?;
+        Ok(())
+    }
+
+    pub fn finalize(&self, block: B) {
+        self.finalized_block_send.send_replace(Some(block));
+    }
+}
+
+impl<B> Consensus<B> for MockConsensus<B>
+where
+    B: BlockResponse<Transaction: TransactionResponse, Header: HeaderResponse>
+        + Serialize
+        + Clone
+        + Send
+        + Sync
+        + 'static,
+{
+    fn block_recv(&mut self) -> Option<mpsc::Receiver<B>> {
+        self.block_recv.take()
+    }
+
+    fn finalized_block_recv(&mut self) -> Option<watch::Receiver<Option<B>>> {
+        self.finalized_block_recv.take()
+    }
+
+    fn head(&self) -> Option<B> {
+        self.head_recv.borrow().clone()
+    }
+
+    fn expected_highest_block(&self) -> u64 {
+        u64::MAX
+    }
+
+    fn chain_id(&self) -> u64 {
+        self.chain_id
+    }
+
+    fn network_info(&self) -> NetworkInfo {
+        NetworkInfo {
+            chain_id: self.chain_id,
+            network: None,
+            genesis_time: None,
+            genesis_validators_root: None,
+            forks: Vec::new(),
+            current_fork: None,
+            weak_subjectivity_period: None,
+            checkpoint: None,
+            consensus_rpc: None,
+            execution_rpc: None,
+            l1: None,
+        }
+    }
+
+    fn shutdown(&self) -> Result<()> {
+        Ok(())
+    }
+}
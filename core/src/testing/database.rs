@@ -0,0 +1,126 @@
+//! The behaviour every [Database] backend is held to, run by the tests of each backend.
+
+use alloy::primitives::B256;
+
+use crate::database::{Checkpoint, Database};
+
+const VALUES: &str = "conformance_values";
+const OTHER: &str = "conformance_other";
+const PINS: &str = "conformance_pins";
+
+/// Runs the conformance checks against `db`, which must not hold any of the namespaces
+/// they write to or a checkpoint.
+pub fn check_conformance(db: &dyn Database) {
+    check_values(db);
+    check_pins(db);
+    check_namespaces(db);
+    check_checkpoints(db);
+}
+
+fn sorted_keys(db: &dyn Database, namespace: &str) -> Vec<Vec<u8>> {
+    let mut keys = db
+        .entries(namespace)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.key)
+        .collect::<Vec<_>>();
+    keys.sort();
+    keys
+}
+
+fn check_values(db: &dyn Database) {
+    assert_eq!(db.get(VALUES, &[1]).unwrap(), None);
+    assert!(db.entries(VALUES).unwrap().is_empty());
+
+    db.put(VALUES, &[1], &[0xaa; 3]).unwrap();
+    db.put(VALUES, &[2, 0], &[]).unwrap();
+    assert_eq!(db.get(VALUES, &[1]).unwrap(), Some(vec![0xaa; 3]));
+    // empty values are stored, not mistaken for missing ones
+    assert_eq!(db.get(VALUES, &[2, 0]).unwrap(), Some(vec![]));
+
+    db.put(VALUES, &[1], &[0xbb; 5]).unwrap();
+    assert_eq!(db.get(VALUES, &[1]).unwrap(), Some(vec![0xbb; 5]));
+
+    let mut entries = db.entries(VALUES).unwrap();
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    assert_eq!(entries.len(), 2);
+    assert_eq!((entries[0].key.as_slice(), entries[0].size), (&[1][..], 5));
+    assert_eq!(
+        (entries[1].key.as_slice(), entries[1].size),
+        (&[2, 0][..], 0)
+    );
+    assert!(entries
+        .iter()
+        .all(|entry| !entry.pinned && entry.written_at > 0));
+
+    db.delete(VALUES, &[vec![1], vec![9]]).unwrap();
+    assert_eq!(db.get(VALUES, &[1]).unwrap(), None);
+    assert_eq!(sorted_keys(db, VALUES), [vec![2, 0]]);
+}
+
+fn check_pins(db: &dyn Database) {
+    db.put(PINS, &[1], &[1]).unwrap();
+    db.put(PINS, &[2], &[2]).unwrap();
+    db.set_pinned(PINS, &[1], true).unwrap();
+    // a key can be pinned before it is written
+    db.set_pinned(PINS, &[3], true).unwrap();
+    db.put(PINS, &[3], &[3]).unwrap();
+
+    let pinned = |db: &dyn Database| {
+        let mut keys = db
+            .entries(PINS)
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry.pinned)
+            .map(|entry| entry.key)
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys
+    };
+    assert_eq!(pinned(db), [vec![1], vec![3]]);
+
+    db.set_pinned(PINS, &[1], false).unwrap();
+    db.set_pinned(PINS, &[2], false).unwrap();
+    assert_eq!(pinned(db), [vec![3]]);
+
+    // deleting an entry deletes its pin, so writing it again leaves it unpinned
+    db.delete(PINS, &[vec![3]]).unwrap();
+    db.put(PINS, &[3], &[3]).unwrap();
+    assert!(pinned(db).is_empty());
+    assert_eq!(sorted_keys(db, PINS), [vec![1], vec![2], vec![3]]);
+}
+
+fn check_namespaces(db: &dyn Database) {
+    db.put(VALUES, &[7], &[1]).unwrap();
+    db.put(OTHER, &[7], &[2]).unwrap();
+    assert_eq!(db.get(VALUES, &[7]).unwrap(), Some(vec![1]));
+    assert_eq!(db.get(OTHER, &[7]).unwrap(), Some(vec![2]));
+
+    db.delete(OTHER, &[vec![7]]).unwrap();
+    assert_eq!(db.get(OTHER, &[7]).unwrap(), None);
+    assert_eq!(db.get(VALUES, &[7]).unwrap(), Some(vec![1]));
+
+    for invalid in ["", "../checkpoint", "a/b", "a b"] {
+        assert!(db.put(invalid, &[0], &[0]).is_err(), "{invalid:?}");
+        assert!(db.get(invalid, &[0]).is_err(), "{invalid:?}");
+        assert!(db.entries(invalid).is_err(), "{invalid:?}");
+    }
+}
+
+fn check_checkpoints(db: &dyn Database) {
+    assert!(db.load_checkpoint().is_err());
+    assert_eq!(db.load_checkpoint_with_slot().unwrap(), None);
+
+    let checkpoint = Checkpoint {
+        root: B256::repeat_byte(0x5a),
+        slot: 7069376,
+    };
+    db.save_checkpoint_with_slot(checkpoint).unwrap();
+    assert_eq!(db.load_checkpoint_with_slot().unwrap(), Some(checkpoint));
+    assert_eq!(db.load_checkpoint().unwrap(), checkpoint.root);
+
+    // a checkpoint saved without its slot has no known slot
+    db.save_checkpoint(B256::repeat_byte(0x5b)).unwrap();
+    assert_eq!(db.load_checkpoint_with_slot().unwrap(), None);
+    assert_eq!(db.load_checkpoint().unwrap(), B256::repeat_byte(0x5b));
+}
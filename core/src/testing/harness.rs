@@ -0,0 +1,72 @@
+use alloy::rpc::types::{Block, Transaction};
+use tokio::sync::{
+    mpsc::{channel, Sender},
+    watch,
+};
+
+use crate::execution::state::State;
+use crate::execution::ExecutionClient;
+use crate::network_spec::NetworkSpec;
+
+use super::chain::MockChain;
+use super::rpc::ChainRpc;
+
+/// An execution client over a [`ChainRpc`] serving a [`MockChain`], whose state follows
+/// blocks sent over the same channels the consensus client uses, for tests driving the
+/// execution client without a node.
+pub struct Harness<N: NetworkSpec> {
+    pub client: ExecutionClient<N, ChainRpc>,
+    pub state: State<N, ChainRpc>,
+    pub rpc: ChainRpc,
+    pub finalized_block_send: watch::Sender<Option<N::BlockResponse>>,
+    // dropping the sender stops the state task, as when the consensus client shuts down
+    pub block_send: Sender<N::BlockResponse>,
+}
+
+impl<N> Harness<N>
+where
+    N: NetworkSpec<BlockResponse = Block<Transaction>>,
+{
+    /// A harness over `chain` holding no blocks yet.
+    pub fn new(chain: &MockChain) -> Self {
+        Self::with_history_length(chain, 64)
+    }
+
+    /// Like [`Harness::new`], with a state holding at most `history_length` blocks.
+    pub fn with_history_length(chain: &MockChain, history_length: usize) -> Self {
+        let (block_send, block_recv) = channel(256);
+        let (finalized_block_send, finalized_block_recv) = watch::channel(None);
+
+        let rpc = ChainRpc::from_chain(chain.clone());
+        let state = State::with_rpc(
+            block_recv,
+            finalized_block_recv,
+            history_length,
+            rpc.clone(),
+        );
+        let client = ExecutionClient::with_rpc(rpc.clone(), state.clone(), chain.fork_schedule());
+
+        Self {
+            client,
+            state,
+            rpc,
+            finalized_block_send,
+            block_send,
+        }
+    }
+
+    /// A harness over `chain` whose state holds every block of the chain as if consensus had
+    /// verified them.
+    pub async fn with_blocks(chain: &MockChain) -> Self {
+        let harness = Self::new(chain);
+        harness.push_chain(chain).await;
+        harness
+    }
+
+    /// Pushes every block of `chain` to the state, oldest first.
+    pub async fn push_chain(&self, chain: &MockChain) {
+        for block in chain.blocks() {
+            self.state.push_block(block.clone()).await;
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! Deterministic fixtures shared by the integration tests of the workspace crates, and
+//! helpers to run the end to end tests against a local node.
+//!
+//! Only compiled with the `testing` feature, which the other crates enable from their
+//! `dev-dependencies`.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub mod anvil;
+pub mod chain;
+pub mod code;
+pub mod consensus;
+pub mod database;
+pub mod harness;
+pub mod rpc;
@@ -0,0 +1,613 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use alloy::eips::{calc_next_block_base_fee, eip1559::BaseFeeParams, BlockNumberOrTag};
+use alloy::primitives::{Address, B256, U256};
+use alloy::providers::ProviderBuilder;
+use alloy::rpc::client::RpcClient;
+use alloy::rpc::json_rpc::{
+    RequestPacket, Response, ResponsePacket, ResponsePayload, SerializedRequest,
+};
+use alloy::rpc::types::{
+    AccessList, BlockId, EIP1186AccountProofResponse, FeeHistory, Filter, FilterChanges, Header,
+    Log,
+};
+use alloy::transports::{TransportError, TransportFut};
+use async_trait::async_trait;
+use eyre::{eyre, Result};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::value::RawValue;
+use serde_json::{json, Value};
+use tower::Service;
+
+use super::chain::{ChainBuilder, MockChain};
+use crate::execution::blob::BlobParams;
+use crate::execution::log_filter::log_matches_filter;
+use crate::execution::rpc::http_rpc::HttpRpc;
+use crate::execution::rpc::ExecutionRpc;
+#[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+use crate::metrics::Metrics;
+use crate::network_spec::NetworkSpec;
+use crate::time::sleep;
+use crate::types::BlockTag;
+
+/// An [`ExecutionRpc`] serving a [`MockChain`].
+///
+/// Responses are converted through their json representation, so the same chain can back
+/// any network whose rpc types deserialize from Ethereum responses. Clones share the chain,
+/// which can be swapped with [`ChainRpc::set_chain`] to simulate a reorg on the provider.
+/// After [`ChainRpc::disconnect`] every provider method panics, for asserting that a code
+/// path is answered locally.
+///
+/// Log and block filters are tracked like a node would, returning what was added to the
+/// chain since they were last polled.
+#[derive(Clone)]
+pub struct ChainRpc {
+    chain: Arc<RwLock<MockChain>>,
+    disconnected: Arc<AtomicBool>,
+    filters: Arc<Mutex<HashMap<U256, ProviderFilter>>>,
+    map_logs: Arc<Mutex<Option<LogsMap>>>,
+    map_proofs: Arc<Mutex<Option<ProofsMap>>>,
+    map_receipts: Arc<Mutex<Option<ReceiptsMap>>>,
+    map_fee_history: Arc<Mutex<Option<FeeHistoryMap>>>,
+    map_blocks: Arc<Mutex<Option<BlocksMap>>>,
+    access_list: Arc<Mutex<Option<AccessList>>>,
+    block_delay: Arc<Mutex<Duration>>,
+    block_failures: Arc<Mutex<HashMap<u64, usize>>>,
+    code_batch_failures: Arc<Mutex<usize>>,
+    calls: Arc<Mutex<HashMap<String, usize>>>,
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    metrics: Arc<Metrics>,
+}
+
+type LogsMap = Box<dyn Fn(&Filter, Vec<Log>) -> Vec<Log> + Send + Sync>;
+type ProofsMap =
+    Box<dyn Fn(EIP1186AccountProofResponse) -> EIP1186AccountProofResponse + Send + Sync>;
+type ReceiptsMap = Box<dyn Fn(&mut serde_json::Value) + Send + Sync>;
+type FeeHistoryMap = Box<dyn Fn(FeeHistory) -> FeeHistory + Send + Sync>;
+type BlocksMap = Box<dyn Fn(&mut serde_json::Value) + Send + Sync>;
+
+/// A filter installed on the provider, and the first block it has not returned yet.
+enum ProviderFilter {
+    Logs(Filter, u64),
+    Blocks(u64),
+}
+
+impl ChainRpc {
+    pub fn from_chain(chain: MockChain) -> Self {
+        Self {
+            chain: Arc::new(RwLock::new(chain)),
+            disconnected: Arc::default(),
+            filters: Arc::default(),
+            map_logs: Arc::default(),
+            map_proofs: Arc::default(),
+            map_receipts: Arc::default(),
+            map_fee_history: Arc::default(),
+            map_blocks: Arc::default(),
+            access_list: Arc::default(),
+            block_delay: Arc::default(),
+            block_failures: Arc::default(),
+            code_batch_failures: Arc::default(),
+            calls: Arc::default(),
+            #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+            metrics: Arc::default(),
+        }
+    }
+
+    pub fn chain(&self) -> MockChain {
+        self.chain.read().unwrap().clone()
+    }
+
+    pub fn set_chain(&self, chain: MockChain) {
+        *self.chain.write().unwrap() = chain;
+    }
+
+    /// Records what the client verifies into `metrics` instead of a fresh registry. Clones
+    /// made afterwards share it.
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Makes any further provider call panic, including from clones.
+    pub fn disconnect(&self) {
+        self.disconnected.store(true, Ordering::SeqCst);
+    }
+
+    /// Passes every `eth_getLogs` answer through `f`, as a provider mishandling filters
+    /// would, including from clones.
+    pub fn map_logs(&self, f: impl Fn(&Filter, Vec<Log>) -> Vec<Log> + Send + Sync + 'static) {
+        *self.map_logs.lock().unwrap() = Some(Box::new(f));
+    }
+
+    /// Passes every `eth_getProof` answer through `f`, as a provider serving a tampered
+    /// state would, including from clones.
+    pub fn map_proofs(
+        &self,
+        f: impl Fn(EIP1186AccountProofResponse) -> EIP1186AccountProofResponse + Send + Sync + 'static,
+    ) {
+        *self.map_proofs.lock().unwrap() = Some(Box::new(f));
+    }
+
+    /// Edits the json of every `eth_getTransactionReceipt` answer with `f`, leaving the
+    /// receipts of `eth_getBlockReceipts` as they are, including from clones.
+    pub fn map_receipts(&self, f: impl Fn(&mut serde_json::Value) + Send + Sync + 'static) {
+        *self.map_receipts.lock().unwrap() = Some(Box::new(f));
+    }
+
+    /// Passes every `eth_feeHistory` answer through `f`, as a provider misreporting fees
+    /// would, including from clones.
+    pub fn map_fee_history(&self, f: impl Fn(FeeHistory) -> FeeHistory + Send + Sync + 'static) {
+        *self.map_fee_history.lock().unwrap() = Some(Box::new(f));
+    }
+
+    /// Edits the json of every block returned by `eth_getBlockByHash` and
+    /// `eth_getBlockByNumber` with `f`, as a provider forging headers would, including from
+    /// clones.
+    pub fn map_blocks(&self, f: impl Fn(&mut serde_json::Value) + Send + Sync + 'static) {
+        *self.map_blocks.lock().unwrap() = Some(Box::new(f));
+    }
+
+    /// Answers every `eth_createAccessList` with `list`, including from clones. Until then
+    /// access lists are unavailable, leaving the evm to fetch state as it reaches it.
+    pub fn serve_access_list(&self, list: AccessList) {
+        *self.access_list.lock().unwrap() = Some(list);
+    }
+
+    /// Answers every `eth_getBlockByHash` and `eth_getBlockByNumber` only after `delay`, as a
+    /// distant provider would, including from clones.
+    pub fn delay_blocks(&self, delay: Duration) {
+        *self.block_delay.lock().unwrap() = delay;
+    }
+
+    /// Fails the next `times` requests for block `number` by number, as a flaky provider
+    /// would, including from clones.
+    pub fn fail_block(&self, number: u64, times: usize) {
+        self.block_failures.lock().unwrap().insert(number, times);
+    }
+
+    /// Fails the next `times` batches of code requests as a whole, as a provider rejecting
+    /// batches would, including from clones. Single code requests are still answered.
+    pub fn fail_code_batches(&self, times: usize) {
+        *self.code_batch_failures.lock().unwrap() = times;
+    }
+
+    /// An [`HttpRpc`] answering from this chain, for driving a whole node against it. Only
+    /// the state methods, `eth_getProof` and `eth_getCode`, are served, others fail as
+    /// unsupported. Calls count and panic after [`ChainRpc::disconnect`] as on `self`.
+    pub fn http<N: NetworkSpec>(&self) -> HttpRpc<N> {
+        let client = RpcClient::new(ChainTransport { rpc: self.clone() }, true);
+        let provider = ProviderBuilder::new().network::<N>().on_client(client);
+        HttpRpc::from_provider("mock://chain", provider)
+    }
+
+    /// How often `method` was called on the provider, including from clones.
+    pub fn calls(&self, method: &str) -> usize {
+        let calls = self.calls.lock().unwrap();
+        calls.get(method).copied().unwrap_or_default()
+    }
+
+    fn convert_block<T: DeserializeOwned>(&self, block: &impl Serialize) -> Result<T> {
+        let mut json = serde_json::to_value(block)?;
+        if let Some(map) = &*self.map_blocks.lock().unwrap() {
+            map(&mut json);
+        }
+        Ok(serde_json::from_value(json)?)
+    }
+
+    fn proof(&self, address: Address, slots: &[B256]) -> EIP1186AccountProofResponse {
+        let proof = self.provider("get_proof").proof(address, slots);
+        match &*self.map_proofs.lock().unwrap() {
+            Some(map) => map(proof),
+            None => proof,
+        }
+    }
+
+    fn check_connected(&self, method: &str) {
+        assert!(
+            !self.disconnected.load(Ordering::SeqCst),
+            "{method} called on a disconnected provider"
+        );
+        *self
+            .calls
+            .lock()
+            .unwrap()
+            .entry(method.to_string())
+            .or_default() += 1;
+    }
+
+    async fn delay_block(&self) {
+        let delay = *self.block_delay.lock().unwrap();
+        if !delay.is_zero() {
+            sleep(delay).await;
+        }
+    }
+
+    /// Whether a request for block `number` is to fail, counting it against the failures
+    /// left.
+    fn take_block_failure(&self, number: u64) -> bool {
+        let mut failures = self.block_failures.lock().unwrap();
+        match failures.get_mut(&number) {
+            Some(left) if *left > 0 => {
+                *left -= 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn provider(&self, method: &str) -> RwLockReadGuard<'_, MockChain> {
+        self.check_connected(method);
+        self.chain.read().unwrap()
+    }
+
+    fn install_filter(&self, method: &str, filter: impl FnOnce(u64) -> ProviderFilter) -> U256 {
+        let next_block = self.provider(method).head().header.number + 1;
+        let mut filters = self.filters.lock().unwrap();
+        let id = filters.keys().max().copied().unwrap_or_default() + U256::from(1);
+        filters.insert(id, filter(next_block));
+        id
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl<N: NetworkSpec> ExecutionRpc<N> for ChainRpc {
+    /// Generates a default chain, using `rpc` as the seed if it is a number.
+    fn new(rpc: &str) -> Result<Self> {
+        let seed = rpc.parse().unwrap_or_default();
+        Ok(Self::from_chain(ChainBuilder::new(seed).build()))
+    }
+
+    #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+    fn metrics(&self) -> Option<&Arc<Metrics>> {
+        Some(&self.metrics)
+    }
+
+    async fn get_proof(
+        &self,
+        address: Address,
+        slots: &[B256],
+        _block: BlockId,
+    ) -> Result<EIP1186AccountProofResponse> {
+        Ok(self.proof(address, slots))
+    }
+
+    async fn create_access_list(
+        &self,
+        _tx: &N::TransactionRequest,
+        _block: BlockTag,
+    ) -> Result<AccessList> {
+        self.check_connected("create_access_list");
+        self.access_list
+            .lock()
+            .unwrap()
+            .clone()
+            .ok_or_else(|| eyre!("not implemented"))
+    }
+
+    async fn get_code(&self, address: Address, _block: u64) -> Result<Vec<u8>> {
+        Ok(self.provider("get_code").code(address).to_vec())
+    }
+
+    async fn get_codes(&self, addresses: &[Address], _block: u64) -> Result<Vec<Result<Vec<u8>>>> {
+        let chain = self.provider("get_codes");
+        let mut failures = self.code_batch_failures.lock().unwrap();
+        if *failures > 0 {
+            *failures -= 1;
+            return Err(eyre!("batch rejected"));
+        }
+
+        let codes = addresses
+            .iter()
+            .map(|address| Ok(chain.code(*address).to_vec()));
+        Ok(codes.collect())
+    }
+
+    async fn send_raw_transaction(&self, _bytes: &[u8]) -> Result<B256> {
+        self.check_connected("send_raw_transaction");
+        Err(eyre!("not implemented"))
+    }
+
+    async fn get_transaction_receipt(&self, tx_hash: B256) -> Result<Option<N::ReceiptResponse>> {
+        let chain = self.provider("get_transaction_receipt");
+        let Some(receipt) = chain.receipt(tx_hash) else {
+            return Ok(None);
+        };
+
+        let mut json = serde_json::to_value(receipt)?;
+        if let Some(map) = &*self.map_receipts.lock().unwrap() {
+            map(&mut json);
+        }
+        Ok(Some(serde_json::from_value(json)?))
+    }
+
+    async fn get_block_receipts(&self, block: BlockId) -> Result<Option<Vec<N::ReceiptResponse>>> {
+        let chain = self.provider("get_block_receipts");
+        let number = match block {
+            BlockId::Hash(hash) => chain
+                .block_by_hash(hash.block_hash)
+                .map(|block| block.header.number),
+            BlockId::Number(BlockNumberOrTag::Number(number)) => Some(number),
+            BlockId::Number(_) => resolve(&chain, BlockTag::Latest),
+        };
+        number
+            .and_then(|number| chain.receipts(number))
+            .map(|receipts| receipts.iter().map(convert).collect())
+            .transpose()
+    }
+
+    async fn get_transaction(&self, tx_hash: B256) -> Result<Option<N::TransactionResponse>> {
+        let chain = self.provider("get_transaction");
+        chain.transaction(tx_hash).map(convert).transpose()
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>> {
+        let chain = self.provider("get_logs");
+        let logs = chain
+            .logs()
+            .into_iter()
+            .filter(|log| log_matches_filter(log, filter))
+            .collect();
+
+        match &*self.map_logs.lock().unwrap() {
+            Some(map) => Ok(map(filter, logs)),
+            None => Ok(logs),
+        }
+    }
+
+    async fn get_filter_changes(&self, filter_id: U256) -> Result<FilterChanges> {
+        let chain = self.provider("get_filter_changes");
+        let mut filters = self.filters.lock().unwrap();
+        let filter = filters
+            .get_mut(&filter_id)
+            .ok_or_else(|| eyre!("filter not found: {filter_id}"))?;
+        let next_block = chain.head().header.number + 1;
+
+        Ok(match filter {
+            ProviderFilter::Logs(filter, from) => {
+                let to = filter
+                    .get_to_block()
+                    .map_or(next_block - 1, |to| to.min(next_block - 1));
+                let range = filter.clone().from_block(*from).to_block(to);
+                *from = next_block;
+                let logs = chain
+                    .logs()
+                    .into_iter()
+                    .filter(|log| log_matches_filter(log, &range))
+                    .collect();
+                FilterChanges::Logs(logs)
+            }
+            ProviderFilter::Blocks(from) => {
+                let hashes = (*from..next_block)
+                    .filter_map(|number| chain.block(number))
+                    .map(|block| block.header.hash)
+                    .collect();
+                *from = next_block;
+                FilterChanges::Hashes(hashes)
+            }
+        })
+    }
+
+    async fn get_filter_logs(&self, filter_id: U256) -> Result<Vec<Log>> {
+        let chain = self.provider("get_filter_logs");
+        let filters = self.filters.lock().unwrap();
+        let Some(ProviderFilter::Logs(filter, _)) = filters.get(&filter_id) else {
+            return Err(eyre!("filter not found: {filter_id}"));
+        };
+
+        Ok(chain
+            .logs()
+            .into_iter()
+            .filter(|log| log_matches_filter(log, filter))
+            .collect())
+    }
+
+    async fn uninstall_filter(&self, filter_id: U256) -> Result<bool> {
+        self.check_connected("uninstall_filter");
+        Ok(self.filters.lock().unwrap().remove(&filter_id).is_some())
+    }
+
+    async fn new_filter(&self, filter: &Filter) -> Result<U256> {
+        let filter = filter.clone();
+        Ok(self.install_filter("new_filter", |next_block| {
+            let from = filter.get_from_block().unwrap_or(next_block);
+            ProviderFilter::Logs(filter, from)
+        }))
+    }
+
+    async fn new_block_filter(&self) -> Result<U256> {
+        Ok(self.install_filter("new_block_filter", ProviderFilter::Blocks))
+    }
+
+    async fn new_pending_transaction_filter(&self) -> Result<U256> {
+        self.check_connected("new_pending_transaction_filter");
+        Err(eyre!("not implemented"))
+    }
+
+    async fn chain_id(&self) -> Result<u64> {
+        Ok(self.provider("chain_id").chain_id())
+    }
+
+    async fn get_block(&self, hash: B256, _full_tx: bool) -> Result<N::BlockResponse> {
+        self.delay_block().await;
+        let chain = self.provider("get_block");
+        let block = chain
+            .block_by_hash(hash)
+            .ok_or_else(|| eyre!("block not found: {hash}"))?;
+        self.convert_block(block)
+    }
+
+    async fn get_block_by_number(
+        &self,
+        block: BlockTag,
+        _full_tx: bool,
+    ) -> Result<Option<N::BlockResponse>> {
+        self.delay_block().await;
+        let chain = self.provider("get_block_by_number");
+        if let BlockTag::Number(number) = block {
+            if self.take_block_failure(number) {
+                return Err(eyre!("request for block {number} failed"));
+            }
+        }
+        resolve(&chain, block)
+            .and_then(|number| chain.block(number))
+            .map(|block| self.convert_block(block))
+            .transpose()
+    }
+
+    /// Reports the fees of the chain like a node would, with every reward percentile zero.
+    async fn get_fee_history(
+        &self,
+        block_count: u64,
+        last_block: u64,
+        reward_percentiles: &[f64],
+    ) -> Result<FeeHistory> {
+        let history = {
+            let chain = self.provider("get_fee_history");
+            let last_block = last_block.min(chain.head().header.number);
+            let first = chain.blocks()[0].header.number;
+            let oldest = (last_block + 1).saturating_sub(block_count).max(first);
+            let headers = (oldest..=last_block)
+                .map(|number| &chain.block(number).unwrap().header)
+                .collect::<Vec<_>>();
+
+            let mut base_fee_per_gas = headers
+                .iter()
+                .map(|header| header.base_fee_per_gas.unwrap_or_default() as u128)
+                .collect::<Vec<_>>();
+            let next_base_fee = match chain.block(last_block + 1) {
+                Some(next) => next.header.base_fee_per_gas.unwrap_or_default(),
+                None => headers.last().map_or(0, |header| {
+                    calc_next_block_base_fee(
+                        header.gas_used,
+                        header.gas_limit,
+                        header.base_fee_per_gas.unwrap_or_default(),
+                        BaseFeeParams::ethereum(),
+                    )
+                }),
+            };
+            base_fee_per_gas.push(next_base_fee as u128);
+
+            // blocks before cancun report zero blob fees, as in geth
+            let schedule = chain.fork_schedule();
+            let blob_fees = |header: &Header| match (header.excess_blob_gas, header.blob_gas_used) {
+                (Some(excess), Some(used)) => {
+                    let params = BlobParams::active_at(header.timestamp, &schedule);
+                    (params.base_fee(excess), params.used_ratio(used))
+                }
+                _ => (0, 0.0),
+            };
+            let (mut base_fee_per_blob_gas, blob_gas_used_ratio): (Vec<_>, Vec<_>) =
+                headers.iter().map(|header| blob_fees(header)).unzip();
+            let next_blob_base_fee = match (chain.block(last_block + 1), headers.last()) {
+                (Some(next), _) => blob_fees(&next.header).0,
+                (None, Some(header)) => {
+                    let params = BlobParams::active_at(header.timestamp, &schedule);
+                    match (header.excess_blob_gas, header.blob_gas_used) {
+                        (Some(excess), Some(used)) => {
+                            params.base_fee(params.next_excess_blob_gas(excess, used))
+                        }
+                        _ => 0,
+                    }
+                }
+                (None, None) => 0,
+            };
+            base_fee_per_blob_gas.push(next_blob_base_fee);
+
+            FeeHistory {
+                oldest_block: oldest,
+                gas_used_ratio: headers
+                    .iter()
+                    .map(|header| header.gas_used as f64 / header.gas_limit as f64)
+                    .collect(),
+                reward: (!reward_percentiles.is_empty())
+                    .then(|| vec![vec![0; reward_percentiles.len()]; headers.len()]),
+                base_fee_per_gas,
+                base_fee_per_blob_gas,
+                blob_gas_used_ratio,
+            }
+        };
+
+        Ok(match &*self.map_fee_history.lock().unwrap() {
+            Some(map) => map(history),
+            None => history,
+        })
+    }
+}
+
+/// Answers the requests of the [`HttpRpc`] of [`ChainRpc::http`].
+#[derive(Clone)]
+struct ChainTransport {
+    rpc: ChainRpc,
+}
+
+impl ChainTransport {
+    fn answer(&self, req: &SerializedRequest) -> Response {
+        let params = req
+            .params()
+            .and_then(|params| serde_json::from_str(params.get()).ok())
+            .unwrap_or(Value::Null);
+        let result = match req.method() {
+            "eth_getProof" => serde_json::from_value::<(Address, Vec<B256>, BlockId)>(params)
+                .ok()
+                .map(|(address, slots, _)| json!(self.rpc.proof(address, &slots))),
+            "eth_getCode" => serde_json::from_value::<(Address, BlockId)>(params)
+                .ok()
+                .map(|(address, _)| json!(self.rpc.provider("get_code").code(address))),
+            _ => None,
+        };
+
+        let payload = match result {
+            Some(result) => {
+                ResponsePayload::Success(RawValue::from_string(result.to_string()).unwrap())
+            }
+            None => {
+                let error = json!({
+                    "code": -32601,
+                    "message": format!("{} is not served by the mock chain", req.method()),
+                });
+                ResponsePayload::Failure(serde_json::from_value(error).unwrap())
+            }
+        };
+        Response {
+            id: req.id().clone(),
+            payload,
+        }
+    }
+}
+
+impl Service<RequestPacket> for ChainTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let res = match req {
+            RequestPacket::Single(req) => ResponsePacket::Single(self.answer(&req)),
+            RequestPacket::Batch(reqs) => {
+                ResponsePacket::Batch(reqs.iter().map(|req| self.answer(req)).collect())
+            }
+        };
+        Box::pin(async move { Ok(res) })
+    }
+}
+
+fn resolve(chain: &MockChain, block: BlockTag) -> Option<u64> {
+    match block {
+        BlockTag::Number(number) => Some(number),
+        _ => chain.blocks().last().map(|block| block.header.number),
+    }
+}
+
+fn convert<T: Serialize, U: DeserializeOwned>(value: &T) -> Result<U> {
+    Ok(serde_json::from_value(serde_json::to_value(value)?)?)
+}
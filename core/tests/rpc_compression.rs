@@ -0,0 +1,171 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use alloy::primitives::{Address, Bytes, LogData, B256};
+use alloy::rpc::types::Log;
+use jsonrpsee::server::{RpcModule, ServerBuilder, ServerHandle};
+use jsonrpsee::types::ErrorObjectOwned;
+use reqwest::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_TYPE};
+use serde_json::{json, Value};
+use tower::ServiceBuilder;
+
+use helios_core::client::compression::{self, CompressionConfig};
+use helios_core::config::ByteSize;
+
+/// Logs with enough repetition across them to compress well, as real logs do.
+fn logs(count: u64) -> Vec<Log> {
+    (0..count)
+        .map(|i| Log {
+            inner: alloy::primitives::Log {
+                address: Address::repeat_byte(0xa1),
+                data: LogData::new_unchecked(
+                    vec![B256::repeat_byte(0xdd), B256::with_last_byte(i as u8)],
+                    Bytes::from(vec![0u8; 64]),
+                ),
+            },
+            block_hash: Some(B256::repeat_byte(0x01)),
+            block_number: Some(100 + i / 10),
+            transaction_hash: Some(B256::with_last_byte(i as u8)),
+            transaction_index: Some(i % 10),
+            log_index: Some(i),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Serves `eth_getLogs` with `logs`, recording the `Accept-Encoding` of each request.
+async fn serve(
+    logs: Vec<Log>,
+    config: CompressionConfig,
+) -> (SocketAddr, ServerHandle, Arc<Mutex<Vec<String>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorder = seen.clone();
+    let middleware = ServiceBuilder::new()
+        .map_request(move |req: http::Request<hyper::Body>| {
+            let encoding = req
+                .headers()
+                .get(ACCEPT_ENCODING.as_str())
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            recorder.lock().unwrap().push(encoding);
+            req
+        })
+        .layer(compression::layer(&config));
+
+    let server = ServerBuilder::default()
+        .set_middleware(middleware)
+        .build("127.0.0.1:0")
+        .await
+        .unwrap();
+    let addr = server.local_addr().unwrap();
+
+    let mut module = RpcModule::new(());
+    module
+        .register_method("eth_getLogs", move |_, _| -> Result<_, ErrorObjectOwned> {
+            Ok(logs.clone())
+        })
+        .unwrap();
+
+    (addr, server.start(module), seen)
+}
+
+fn get_logs(client: &reqwest::Client, addr: SocketAddr) -> reqwest::RequestBuilder {
+    client
+        .post(format!("http://{addr}"))
+        .header(CONTENT_TYPE, "application/json")
+        .json(&json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_getLogs", "params": [{}] }))
+}
+
+/// Posts `eth_getLogs` with the provider client, returning the decoded logs.
+async fn fetch_logs(addr: SocketAddr, compression: bool) -> Vec<Log> {
+    let client = compression::http_client(compression).unwrap();
+    let body: Value = get_logs(&client, addr)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    serde_json::from_value(body["result"].clone()).unwrap()
+}
+
+/// Posts `eth_getLogs` accepting `encoding`, returning the undecoded response.
+async fn post_raw(addr: SocketAddr, encoding: &str) -> reqwest::Response {
+    let client = compression::http_client(false).unwrap();
+    get_logs(&client, addr)
+        .header(ACCEPT_ENCODING, encoding)
+        .send()
+        .await
+        .unwrap()
+}
+
+fn uncompressed_len(logs: &[Log]) -> usize {
+    serde_json::to_vec(&json!({ "jsonrpc": "2.0", "result": logs, "id": 1 }))
+        .unwrap()
+        .len()
+}
+
+#[tokio::test]
+async fn test_large_logs_response_round_trips_compressed() {
+    let expected = logs(2_000);
+    let config = CompressionConfig {
+        enabled: true,
+        ..Default::default()
+    };
+    let (addr, _handle, seen) = serve(expected.clone(), config).await;
+
+    assert_eq!(fetch_logs(addr, true).await, expected);
+
+    let advertised = seen.lock().unwrap().pop().unwrap();
+    assert!(advertised.contains("gzip"), "{advertised}");
+    assert!(advertised.contains("zstd"), "{advertised}");
+
+    for encoding in ["gzip", "zstd"] {
+        let response = post_raw(addr, encoding).await;
+        assert_eq!(response.headers()[CONTENT_ENCODING], encoding);
+        let body = response.bytes().await.unwrap();
+        assert!(body.len() * 10 < uncompressed_len(&expected), "{encoding}");
+    }
+
+    // neither is offered to callers asking for other encodings only
+    let response = post_raw(addr, "br, deflate").await;
+    assert!(response.headers().get(CONTENT_ENCODING).is_none());
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["result"], serde_json::to_value(&expected).unwrap());
+}
+
+#[tokio::test]
+async fn test_small_responses_sent_uncompressed() {
+    let config = CompressionConfig {
+        enabled: true,
+        min_size: ByteSize::from_bytes(4 << 10),
+    };
+    let expected = logs(2);
+    let (addr, _handle, _) = serve(expected.clone(), config).await;
+
+    let response = post_raw(addr, "gzip, zstd").await;
+    assert!(response.headers().get(CONTENT_ENCODING).is_none());
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["result"], serde_json::to_value(&expected).unwrap());
+}
+
+#[tokio::test]
+async fn test_compression_disabled_by_default() {
+    let config = CompressionConfig::default();
+    assert!(!config.enabled);
+    let expected = logs(2_000);
+    let (addr, _handle, seen) = serve(expected.clone(), config).await;
+
+    let response = post_raw(addr, "gzip, zstd").await;
+    assert!(response.headers().get(CONTENT_ENCODING).is_none());
+    assert_eq!(
+        response.bytes().await.unwrap().len(),
+        uncompressed_len(&expected)
+    );
+
+    // a provider client with compression off does not ask for it
+    assert_eq!(fetch_logs(addr, false).await, expected);
+    let advertised = seen.lock().unwrap().pop().unwrap();
+    assert!(!advertised.contains("gzip") && !advertised.contains("zstd"));
+}
@@ -0,0 +1,148 @@
+use std::net::SocketAddr;
+use std::path::Path;
+use std::sync::Arc;
+
+use rustls::crypto::ring::default_provider;
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, RootCertStore};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::TlsConnector;
+
+use helios_core::client::tls::{TlsConfig, TlsError, TlsTerminator};
+
+struct Material {
+    cert: rcgen::Certificate,
+    key: rcgen::KeyPair,
+}
+
+fn self_signed() -> Material {
+    let rcgen::CertifiedKey { cert, key_pair } =
+        rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    Material {
+        cert,
+        key: key_pair,
+    }
+}
+
+fn write_config(dir: &Path, cert: &rcgen::Certificate, key: &rcgen::KeyPair) -> TlsConfig {
+    let cert_path = dir.join("cert.pem");
+    let key_path = dir.join("key.pem");
+    std::fs::write(&cert_path, cert.pem()).unwrap();
+    std::fs::write(&key_path, key.serialize_pem()).unwrap();
+
+    TlsConfig {
+        cert_path,
+        key_path,
+    }
+}
+
+/// Plaintext upstream answering every read with the same bytes prefixed by `pong `.
+async fn echo_upstream() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            tokio::spawn(async move {
+                let mut buf = [0u8; 1024];
+                while let Ok(n) = stream.read(&mut buf).await {
+                    if n == 0 {
+                        break;
+                    }
+                    let reply = [b"pong ".as_slice(), &buf[..n]].concat();
+                    if stream.write_all(&reply).await.is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+    });
+
+    addr
+}
+
+async fn round_trip(addr: SocketAddr, cert: &rcgen::Certificate, message: &[u8]) -> Vec<u8> {
+    let mut roots = RootCertStore::empty();
+    roots
+        .add(CertificateDer::from(cert.der().to_vec()))
+        .unwrap();
+    let config = ClientConfig::builder_with_provider(Arc::new(default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    let connector = TlsConnector::from(Arc::new(config));
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let server_name = ServerName::try_from("localhost").unwrap();
+    let mut stream = connector.connect(server_name, stream).await.unwrap();
+
+    stream.write_all(message).await.unwrap();
+    let mut buf = vec![0u8; message.len() + 5];
+    stream.read_exact(&mut buf).await.unwrap();
+    buf
+}
+
+#[tokio::test]
+async fn test_tls_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let material = self_signed();
+    let config = write_config(dir.path(), &material.cert, &material.key);
+
+    let upstream = echo_upstream().await;
+    let terminator = TlsTerminator::new(config).unwrap();
+    let addr = terminator
+        .start("127.0.0.1:0".parse().unwrap(), upstream)
+        .await
+        .unwrap();
+
+    let request = br#"{"jsonrpc":"2.0","id":1,"method":"eth_chainId"}"#;
+    let response = round_trip(addr, &material.cert, request).await;
+    assert_eq!(response, [b"pong ".as_slice(), request].concat());
+}
+
+#[tokio::test]
+async fn test_reload_serves_new_certificate() {
+    let dir = tempfile::tempdir().unwrap();
+    let old = self_signed();
+    let config = write_config(dir.path(), &old.cert, &old.key);
+
+    let terminator = TlsTerminator::new(config).unwrap();
+    let new = self_signed();
+    write_config(dir.path(), &new.cert, &new.key);
+    terminator.reload().unwrap();
+
+    let upstream = echo_upstream().await;
+    let addr = terminator
+        .start("127.0.0.1:0".parse().unwrap(), upstream)
+        .await
+        .unwrap();
+
+    let response = round_trip(addr, &new.cert, b"ping").await;
+    assert_eq!(response, b"pong ping");
+}
+
+#[test]
+fn test_mismatched_key_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let a = self_signed();
+    let b = self_signed();
+    let config = write_config(dir.path(), &a.cert, &b.key);
+
+    let err = TlsTerminator::new(config).err().unwrap();
+    assert!(matches!(err, TlsError::KeyMismatch { .. }));
+}
+
+#[test]
+fn test_missing_files_rejected() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = TlsConfig {
+        cert_path: dir.path().join("missing.pem"),
+        key_path: dir.path().join("missing-key.pem"),
+    };
+
+    let err = TlsTerminator::new(config).err().unwrap();
+    assert!(matches!(err, TlsError::Read(..)));
+    assert!(err.to_string().contains("missing.pem"));
+}
@@ -2,9 +2,9 @@ use std::cmp;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use alloy::primitives::B256;
+use alloy::primitives::{FixedBytes, B256};
 use eyre::Result;
-use ssz_types::BitVector;
+use ssz_types::{BitVector, FixedVector};
 use tracing::{info, warn};
 use tree_hash::TreeHash;
 #[cfg(target_arch = "wasm32")]
@@ -19,7 +19,7 @@ use crate::proof::{
 use crate::types::bls::{PublicKey, Signature};
 use crate::types::{
     BeaconBlockHeader, Bootstrap, ExecutionPayloadHeader, FinalityUpdate, Forks, GenericUpdate,
-    LightClientHeader, LightClientStore, OptimisticUpdate, Update,
+    LightClientHeader, LightClientStore, OptimisticUpdate, Slot, Update,
 };
 use crate::utils::{
     calculate_fork_version, compute_committee_sign_root, compute_fork_data_root,
@@ -39,7 +39,9 @@ pub fn verify_bootstrap<S: ConsensusSpec>(
         bootstrap.header().beacon(),
         bootstrap.current_sync_committee(),
         bootstrap.current_sync_committee_branch(),
-        bootstrap.header().beacon().slot / S::slots_per_epoch(),
+        Slot::new(bootstrap.header().beacon().slot)
+            .epoch::<S>()
+            .as_u64(),
         forks,
     );
 
@@ -155,7 +157,7 @@ pub fn apply_generic_update<S: ConsensusSpec>(
         store.optimistic_header = update.attested_header.clone();
     }
 
-    let update_attested_period = calc_sync_period::<S>(update.attested_header.beacon().slot);
+    let update_attested_period = Slot::new(update.attested_header.beacon().slot).period::<S>();
 
     let update_finalized_slot = update
         .finalized_header
@@ -163,7 +165,7 @@ pub fn apply_generic_update<S: ConsensusSpec>(
         .map(|h| h.beacon().slot)
         .unwrap_or(0);
 
-    let update_finalized_period = calc_sync_period::<S>(update_finalized_slot);
+    let update_finalized_period = Slot::new(update_finalized_slot).period::<S>();
 
     let update_has_finalized_next_committee = store.next_sync_committee.is_none()
         && has_sync_update(update)
@@ -195,13 +197,13 @@ fn apply_update_no_quorum_check<S: ConsensusSpec>(
     store: &mut LightClientStore<S>,
     update: &GenericUpdate<S>,
 ) -> Option<B256> {
-    let store_period = calc_sync_period::<S>(store.finalized_header.beacon().slot);
+    let store_period = Slot::new(store.finalized_header.beacon().slot).period::<S>();
     let update_finalized_slot = update
         .finalized_header
         .as_ref()
         .map(|h| h.beacon().slot)
         .unwrap_or(0);
-    let update_finalized_period = calc_sync_period::<S>(update_finalized_slot);
+    let update_finalized_period = Slot::new(update_finalized_slot).period::<S>();
 
     if store.next_sync_committee.is_none() {
         if update_finalized_period != store_period {
@@ -227,7 +229,7 @@ fn apply_update_no_quorum_check<S: ConsensusSpec>(
             store.optimistic_header = store.finalized_header.clone();
         }
 
-        if store.finalized_header.beacon().slot % S::slots_per_epoch() == 0 {
+        if Slot::new(store.finalized_header.beacon().slot).is_epoch_start::<S>() {
             let checkpoint = store.finalized_header.beacon().tree_hash_root();
             return Some(checkpoint);
         }
@@ -268,8 +270,8 @@ pub fn verify_generic_update<S: ConsensusSpec>(
         return Err(ConsensusError::InvalidTimestamp.into());
     }
 
-    let store_period = calc_sync_period::<S>(store.finalized_header.beacon().slot);
-    let update_sig_period = calc_sync_period::<S>(update.signature_slot);
+    let store_period = Slot::new(store.finalized_header.beacon().slot).period::<S>();
+    let update_sig_period = Slot::new(update.signature_slot).period::<S>();
     let valid_period = if store.next_sync_committee.is_some() {
         update_sig_period == store_period || update_sig_period == store_period + 1
     } else {
@@ -279,7 +281,7 @@ pub fn verify_generic_update<S: ConsensusSpec>(
         return Err(ConsensusError::InvalidPeriod.into());
     }
 
-    let update_attested_period = calc_sync_period::<S>(update.attested_header.beacon().slot);
+    let update_attested_period = Slot::new(update.attested_header.beacon().slot).period::<S>();
     let update_has_next_committee = store.next_sync_committee.is_none()
         && update.next_sync_committee.is_some()
         && update_attested_period == store_period;
@@ -290,7 +292,9 @@ pub fn verify_generic_update<S: ConsensusSpec>(
         return Err(ConsensusError::NotRelevant.into());
     }
 
-    let update_attested_epoch = update.attested_header.beacon().slot / S::slots_per_epoch();
+    let update_attested_epoch = Slot::new(update.attested_header.beacon().slot)
+        .epoch::<S>()
+        .as_u64();
 
     if let Some(finalized_header) = &update.finalized_header {
         if let Some(finality_branch) = &update.finality_branch {
@@ -389,9 +393,16 @@ pub fn expected_current_slot(now: SystemTime, genesis_time: u64) -> u64 {
     since_genesis / 12
 }
 
+/// First four bytes of the fork data root, which tell networks and their forks apart.
+pub fn compute_fork_digest(fork_version: FixedBytes<4>, genesis_root: B256) -> FixedBytes<4> {
+    let current_version = FixedVector::from(fork_version.to_vec());
+    let fork_data_root = compute_fork_data_root(current_version, genesis_root);
+    FixedBytes::from_slice(&fork_data_root[..4])
+}
+
+/// The sync committee period of a raw `slot`, see [`Slot::period`].
 pub fn calc_sync_period<S: ConsensusSpec>(slot: u64) -> u64 {
-    let epoch = slot / S::slots_per_epoch();
-    epoch / S::epochs_per_sync_committee_period()
+    Slot::new(slot).period::<S>().as_u64()
 }
 
 pub fn get_bits<S: ConsensusSpec>(bitfield: &BitVector<S::SyncCommitteeSize>) -> u64 {
@@ -418,11 +429,11 @@ fn is_better_update<S: ConsensusSpec>(
 
     // compare presence of relevant sync committee
     let new_has_relevant_sync_committee = new_update.next_sync_committee_branch.is_some()
-        && calc_sync_period::<S>(new_update.attested_header.beacon().slot)
-            == calc_sync_period::<S>(new_update.signature_slot);
+        && Slot::new(new_update.attested_header.beacon().slot).period::<S>()
+            == Slot::new(new_update.signature_slot).period::<S>();
     let old_has_relevant_sync_committee = old_update.next_sync_committee_branch.is_some()
-        && calc_sync_period::<S>(old_update.attested_header.beacon().slot)
-            == calc_sync_period::<S>(old_update.signature_slot);
+        && Slot::new(old_update.attested_header.beacon().slot).period::<S>()
+            == Slot::new(old_update.signature_slot).period::<S>();
     if new_has_relevant_sync_committee != old_has_relevant_sync_committee {
         return new_has_relevant_sync_committee;
     }
@@ -436,24 +447,14 @@ fn is_better_update<S: ConsensusSpec>(
 
     // compare sync committee finality
     if new_has_finality {
-        let new_has_sync_committee_finality =
-            calc_sync_period::<S>(
-                new_update
-                    .finalized_header
-                    .clone()
-                    .unwrap_or_default()
-                    .beacon()
-                    .slot,
-            ) == calc_sync_period::<S>(new_update.attested_header.beacon().slot);
-        let old_has_sync_committee_finality =
-            calc_sync_period::<S>(
-                old_update
-                    .finalized_header
-                    .clone()
-                    .unwrap_or_default()
-                    .beacon()
-                    .slot,
-            ) == calc_sync_period::<S>(old_update.attested_header.beacon().slot);
+        let finalized_period = |update: &GenericUpdate<S>| {
+            let finalized = update.finalized_header.clone().unwrap_or_default();
+            Slot::new(finalized.beacon().slot).period::<S>()
+        };
+        let new_has_sync_committee_finality = finalized_period(new_update)
+            == Slot::new(new_update.attested_header.beacon().slot).period::<S>();
+        let old_has_sync_committee_finality = finalized_period(old_update)
+            == Slot::new(old_update.attested_header.beacon().slot).period::<S>();
         if new_has_sync_committee_finality != old_has_sync_committee_finality {
             return new_has_sync_committee_finality;
         }
@@ -500,7 +501,7 @@ fn safety_threshold<S: ConsensusSpec>(store: &LightClientStore<S>) -> u64 {
 }
 
 fn is_valid_header<S: ConsensusSpec>(header: &LightClientHeader, forks: &Forks) -> bool {
-    let epoch = header.beacon().slot / S::slots_per_epoch();
+    let epoch = Slot::new(header.beacon().slot).epoch::<S>().as_u64();
 
     // This deviates from the spec in that it dos not check that the blob fields are unset prior to
     // deneb. This is fine since an honest sync committee will never sign an invalid block, which
@@ -1,5 +1,7 @@
 pub mod consensus_spec;
 pub mod errors;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod types;
 
 mod consensus_core;
@@ -7,3 +9,4 @@ mod proof;
 mod utils;
 
 pub use crate::consensus_core::*;
+pub use crate::proof::execution_block_hash_proof;
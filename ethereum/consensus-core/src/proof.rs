@@ -4,9 +4,14 @@ use tree_hash::TreeHash;
 
 use crate::{
     consensus_spec::ConsensusSpec,
-    types::{BeaconBlockHeader, ExecutionPayloadHeader, Forks, SyncCommittee},
+    types::{BeaconBlockHeader, ExecutionPayloadHeader, Forks, LightClientHeader, SyncCommittee},
 };
 
+/// Generalized index of the execution payload in a beacon block body.
+const EXECUTION_PAYLOAD_INDEX: u64 = 25;
+/// Index of the block hash among the fields of an execution payload header.
+const BLOCK_HASH_FIELD: usize = 12;
+
 pub fn is_finality_proof_valid(
     attested_header: &BeaconBlockHeader,
     finality_header: &BeaconBlockHeader,
@@ -81,6 +86,79 @@ pub fn is_execution_payload_proof_valid(
     is_proof_valid(attested_header.body_root, execution, execution_branch, 4, 9)
 }
 
+/// The branch proving the execution block hash of `header` against the body root of its
+/// beacon block, with the generalized index it is proven at. Headers from before Capella
+/// carry no execution payload header to prove it with.
+pub fn execution_block_hash_proof(header: &LightClientHeader) -> Option<(u64, Vec<B256>)> {
+    let execution = header.execution().ok()?;
+    let body_branch = header.execution_branch().ok()?;
+
+    let mut fields = vec![
+        execution.parent_hash().tree_hash_root(),
+        execution.fee_recipient().tree_hash_root(),
+        execution.state_root().tree_hash_root(),
+        execution.receipts_root().tree_hash_root(),
+        execution.logs_bloom().tree_hash_root(),
+        execution.prev_randao().tree_hash_root(),
+        execution.block_number().tree_hash_root(),
+        execution.gas_limit().tree_hash_root(),
+        execution.gas_used().tree_hash_root(),
+        execution.timestamp().tree_hash_root(),
+        execution.extra_data().tree_hash_root(),
+        execution.base_fee_per_gas().tree_hash_root(),
+        execution.block_hash().tree_hash_root(),
+        execution.transactions_root().tree_hash_root(),
+    ];
+    fields.extend(
+        execution
+            .withdrawals_root()
+            .ok()
+            .map(|root| root.tree_hash_root()),
+    );
+    fields.extend(
+        execution
+            .blob_gas_used()
+            .ok()
+            .map(|gas| gas.tree_hash_root()),
+    );
+    fields.extend(
+        execution
+            .excess_blob_gas()
+            .ok()
+            .map(|gas| gas.tree_hash_root()),
+    );
+
+    let depth = fields.len().next_power_of_two().trailing_zeros();
+    let mut branch = merkle_branch(fields, BLOCK_HASH_FIELD);
+    branch.extend(body_branch.iter().copied());
+
+    let index = (EXECUTION_PAYLOAD_INDEX << depth) + BLOCK_HASH_FIELD as u64;
+    Some((index, branch))
+}
+
+/// The siblings of leaf `index` from the bottom of the tree up, padding `nodes` with zero
+/// chunks to a power of two as SSZ merkleization does.
+pub(crate) fn merkle_branch(mut nodes: Vec<B256>, mut index: usize) -> Vec<B256> {
+    nodes.resize(nodes.len().next_power_of_two(), B256::ZERO);
+
+    let mut branch = Vec::new();
+    let mut hasher = Sha256::new();
+    while nodes.len() > 1 {
+        branch.push(nodes[index ^ 1]);
+        nodes = nodes
+            .chunks(2)
+            .map(|pair| {
+                hasher.update(pair[0]);
+                hasher.update(pair[1]);
+                B256::from_slice(&hasher.finalize_reset())
+            })
+            .collect();
+        index /= 2;
+    }
+
+    branch
+}
+
 fn is_proof_valid<T: TreeHash>(
     root: B256,
     leaf_object: &T,
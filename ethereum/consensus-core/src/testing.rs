@@ -0,0 +1,340 @@
+//! Seeded sync committees signing light client updates over given execution headers.
+//!
+//! A [`MockBeaconChain`] wraps execution headers, such as those of a generated execution
+//! chain, into beacon headers whose body and state roots commit to them, and signs updates
+//! of them with a sync committee derived from its seed. The branches, roots and signatures
+//! are real, so bootstraps and updates pass the same verification as those of a beacon
+//! node. Every fork is active from genesis, so everything takes its Electra form, and the
+//! same seed always produces the same committees and signatures.
+
+use std::marker::PhantomData;
+
+use alloy::primitives::{FixedBytes, B256, U256};
+use alloy::rpc::types::Header;
+use bls12_381::{G1Affine, G1Projective, G2Affine, Scalar};
+use sha2::{Digest, Sha256};
+use ssz_types::{BitVector, FixedVector, VariableList};
+use tree_hash::TreeHash;
+
+use crate::consensus_spec::ConsensusSpec;
+use crate::proof::merkle_branch;
+use crate::types::bls::{hash_to_curve, PublicKey, Signature};
+use crate::types::bytes::{ByteList, ByteVector};
+use crate::types::{
+    BeaconBlockHeader, Bootstrap, BootstrapElectra, ExecutionPayloadHeader,
+    ExecutionPayloadHeaderElectra, FinalityUpdate, FinalityUpdateElectra, Fork, Forks,
+    LightClientHeader, LightClientHeaderElectra, OptimisticUpdate, Slot, SyncAggregate,
+    SyncCommittee, Update, UpdateElectra,
+};
+use crate::utils::{calculate_fork_version, compute_committee_sign_root, compute_fork_data_root};
+
+/// Leaf of the execution payload among the chunks of a beacon block body.
+const EXECUTION_PAYLOAD_LEAF: usize = 9;
+/// Chunks of a beacon block body.
+const BODY_LEAVES: usize = 16;
+/// Chunks of an Electra beacon state, the depth its sync committees are proven at.
+const STATE_LEAVES: usize = 64;
+/// Leaf of the slot among the chunks of a beacon state.
+const SLOT_LEAF: usize = 2;
+/// Chunk of a beacon state holding the finalized checkpoint, whose root is the second of
+/// the two leaves below it.
+const FINALIZED_CHECKPOINT_LEAF: usize = 20;
+const CURRENT_SYNC_COMMITTEE_LEAF: usize = 22;
+const NEXT_SYNC_COMMITTEE_LEAF: usize = 23;
+
+/// Signs light client updates with a committee for each sync committee period.
+#[derive(Debug, Clone)]
+pub struct MockBeaconChain<S: ConsensusSpec> {
+    seed: u64,
+    forks: Forks,
+    genesis_root: B256,
+    phantom: PhantomData<S>,
+}
+
+/// A beacon header carrying an execution header, with the state it commits to and the header
+/// that state finalizes.
+#[derive(Debug, Clone)]
+pub struct MockBeaconBlock {
+    pub header: LightClientHeader,
+    finalized: Option<Box<LightClientHeader>>,
+    state: Vec<B256>,
+}
+
+impl MockBeaconBlock {
+    /// The root the block is bootstrapped from, as a checkpoint names it.
+    pub fn checkpoint(&self) -> B256 {
+        self.header.beacon().tree_hash_root()
+    }
+
+    /// The hash of the execution block the beacon block carries.
+    pub fn execution_block_hash(&self) -> B256 {
+        *self.header.execution().unwrap().block_hash()
+    }
+
+    fn state_branch(&self, leaf: usize) -> Vec<B256> {
+        merkle_branch(self.state.clone(), leaf)
+    }
+
+    fn finality_branch(&self) -> Vec<B256> {
+        let mut branch = vec![B256::ZERO];
+        branch.extend(self.state_branch(FINALIZED_CHECKPOINT_LEAF));
+        branch
+    }
+}
+
+impl<S: ConsensusSpec> MockBeaconChain<S> {
+    pub fn new(seed: u64) -> Self {
+        let fork = |version: u8| Fork {
+            epoch: 0,
+            fork_version: FixedBytes::from([version, 0, 0, 1]),
+        };
+
+        Self {
+            seed,
+            forks: Forks {
+                genesis: fork(0),
+                altair: fork(1),
+                bellatrix: fork(2),
+                capella: fork(3),
+                deneb: fork(4),
+                electra: fork(5),
+            },
+            genesis_root: B256::from_slice(&Sha256::digest(seed.to_le_bytes())),
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn forks(&self) -> &Forks {
+        &self.forks
+    }
+
+    pub fn genesis_root(&self) -> B256 {
+        self.genesis_root
+    }
+
+    /// The committee signing during sync committee period `period`.
+    pub fn committee(&self, period: u64) -> SyncCommittee<S> {
+        let keys = self
+            .secret_keys(period)
+            .iter()
+            .map(|key| G1Projective::generator() * key)
+            .collect::<Vec<_>>();
+        let aggregate = keys
+            .iter()
+            .fold(G1Projective::identity(), |sum, key| sum + key);
+
+        SyncCommittee {
+            pubkeys: FixedVector::from(keys.iter().map(public_key).collect::<Vec<_>>()),
+            aggregate_pubkey: public_key(&aggregate),
+        }
+    }
+
+    /// A beacon block at `slot` carrying `execution`, whose state finalizes `finalized`.
+    pub fn block(
+        &self,
+        slot: u64,
+        execution: &Header,
+        finalized: Option<&MockBeaconBlock>,
+    ) -> MockBeaconBlock {
+        let execution = payload_header(execution);
+        let mut body = vec![B256::ZERO; BODY_LEAVES];
+        body[EXECUTION_PAYLOAD_LEAF] = execution.tree_hash_root();
+
+        let period = Slot::new(slot).period::<S>().as_u64();
+        let finalized_root = finalized
+            .map(|block| block.checkpoint())
+            .unwrap_or_default();
+        let mut state = vec![B256::ZERO; STATE_LEAVES];
+        state[SLOT_LEAF] = slot.tree_hash_root();
+        state[FINALIZED_CHECKPOINT_LEAF] = hash_pair(B256::ZERO, finalized_root);
+        state[CURRENT_SYNC_COMMITTEE_LEAF] = self.committee(period).tree_hash_root();
+        state[NEXT_SYNC_COMMITTEE_LEAF] = self.committee(period + 1).tree_hash_root();
+
+        let header = LightClientHeader::Electra(LightClientHeaderElectra {
+            beacon: BeaconBlockHeader {
+                slot,
+                proposer_index: 0,
+                parent_root: B256::ZERO,
+                state_root: merkle_root(state.clone()),
+                body_root: merkle_root(body.clone()),
+            },
+            execution_branch: FixedVector::from(merkle_branch(body, EXECUTION_PAYLOAD_LEAF)),
+            execution,
+        });
+
+        MockBeaconBlock {
+            header,
+            finalized: finalized.map(|block| Box::new(block.header.clone())),
+            state,
+        }
+    }
+
+    pub fn bootstrap(&self, block: &MockBeaconBlock) -> Bootstrap<S> {
+        let period = Slot::new(block.header.beacon().slot).period::<S>().as_u64();
+
+        Bootstrap::Electra(BootstrapElectra {
+            header: block.header.clone(),
+            current_sync_committee: self.committee(period),
+            current_sync_committee_branch: FixedVector::from(
+                block.state_branch(CURRENT_SYNC_COMMITTEE_LEAF),
+            ),
+        })
+    }
+
+    /// An update of `attested` signed at `signature_slot`, carrying the next sync committee
+    /// and, if the attested state finalizes a block, its header.
+    pub fn update(&self, attested: &MockBeaconBlock, signature_slot: u64) -> Update<S> {
+        let period = Slot::new(attested.header.beacon().slot)
+            .period::<S>()
+            .as_u64();
+        let (finalized_header, finality_branch) = match &attested.finalized {
+            Some(header) => (*header.clone(), attested.finality_branch()),
+            None => (LightClientHeader::default(), Vec::new()),
+        };
+
+        Update::Electra(UpdateElectra {
+            attested_header: attested.header.clone(),
+            next_sync_committee: self.committee(period + 1),
+            next_sync_committee_branch: FixedVector::from(
+                attested.state_branch(NEXT_SYNC_COMMITTEE_LEAF),
+            ),
+            finalized_header,
+            finality_branch: FixedVector::from(finality_branch),
+            sync_aggregate: self.sync_aggregate(&attested.header, signature_slot),
+            signature_slot,
+        })
+    }
+
+    /// A finality update of `attested` signed at `signature_slot`.
+    ///
+    /// # Panics
+    ///
+    /// If the state of `attested` finalizes no block.
+    pub fn finality_update(
+        &self,
+        attested: &MockBeaconBlock,
+        signature_slot: u64,
+    ) -> FinalityUpdate<S> {
+        let finalized = attested
+            .finalized
+            .as_deref()
+            .expect("the attested block finalizes no block");
+
+        FinalityUpdate::Electra(FinalityUpdateElectra {
+            attested_header: attested.header.clone(),
+            finalized_header: finalized.clone(),
+            finality_branch: FixedVector::from(attested.finality_branch()),
+            sync_aggregate: self.sync_aggregate(&attested.header, signature_slot),
+            signature_slot,
+        })
+    }
+
+    pub fn optimistic_update(
+        &self,
+        attested: &MockBeaconBlock,
+        signature_slot: u64,
+    ) -> OptimisticUpdate<S> {
+        OptimisticUpdate {
+            attested_header: attested.header.clone(),
+            sync_aggregate: self.sync_aggregate(&attested.header, signature_slot),
+            signature_slot,
+        }
+    }
+
+    /// The signature of the whole committee of the period of `signature_slot` over
+    /// `attested`.
+    fn sync_aggregate(
+        &self,
+        attested: &LightClientHeader,
+        signature_slot: u64,
+    ) -> SyncAggregate<S> {
+        let fork_version =
+            calculate_fork_version::<S>(&self.forks, signature_slot.saturating_sub(1));
+        let fork_data_root = compute_fork_data_root(fork_version, self.genesis_root);
+        let signing_root =
+            compute_committee_sign_root(attested.beacon().tree_hash_root(), fork_data_root);
+
+        let period = Slot::new(signature_slot).period::<S>().as_u64();
+        let key: Scalar = self.secret_keys(period).iter().sum();
+        let signature = G2Affine::from(hash_to_curve(signing_root.as_slice()) * key);
+
+        let mut bits = BitVector::new();
+        for i in 0..bits.len() {
+            bits.set(i, true).unwrap();
+        }
+
+        SyncAggregate {
+            sync_committee_bits: bits,
+            sync_committee_signature: Signature::from(FixedBytes::from(signature.to_compressed())),
+        }
+    }
+
+    fn secret_keys(&self, period: u64) -> Vec<Scalar> {
+        (0..S::sync_committee_size())
+            .map(|index| {
+                let mut wide = [0u8; 64];
+                for (half, chunk) in wide.chunks_mut(32).enumerate() {
+                    let digest = Sha256::new()
+                        .chain_update(self.seed.to_le_bytes())
+                        .chain_update(period.to_le_bytes())
+                        .chain_update(index.to_le_bytes())
+                        .chain_update([half as u8])
+                        .finalize();
+                    chunk.copy_from_slice(&digest);
+                }
+                Scalar::from_bytes_wide(&wide)
+            })
+            .collect()
+    }
+}
+
+/// The execution payload header of `header`. The mock chain keeps no SSZ transaction or
+/// withdrawal lists, so the roots are those of the execution header.
+fn payload_header(header: &Header) -> ExecutionPayloadHeader {
+    ExecutionPayloadHeader::Electra(ExecutionPayloadHeaderElectra {
+        parent_hash: header.parent_hash,
+        fee_recipient: header.beneficiary,
+        state_root: header.state_root,
+        receipts_root: header.receipts_root,
+        logs_bloom: ByteVector {
+            inner: FixedVector::from(header.logs_bloom.to_vec()),
+        },
+        prev_randao: header.mix_hash,
+        block_number: header.number,
+        gas_limit: header.gas_limit,
+        gas_used: header.gas_used,
+        timestamp: header.timestamp,
+        extra_data: ByteList {
+            inner: VariableList::from(header.extra_data.to_vec()),
+        },
+        base_fee_per_gas: U256::from(header.base_fee_per_gas.unwrap_or_default()),
+        block_hash: header.hash,
+        transactions_root: header.transactions_root,
+        withdrawals_root: header.withdrawals_root.unwrap_or_default(),
+        blob_gas_used: header.blob_gas_used.unwrap_or_default(),
+        excess_blob_gas: header.excess_blob_gas.unwrap_or_default(),
+    })
+}
+
+fn public_key(point: &G1Projective) -> PublicKey {
+    PublicKey::from(FixedBytes::from(G1Affine::from(point).to_compressed()))
+}
+
+fn hash_pair(left: B256, right: B256) -> B256 {
+    B256::from_slice(
+        &Sha256::new()
+            .chain_update(left)
+            .chain_update(right)
+            .finalize(),
+    )
+}
+
+fn merkle_root(mut nodes: Vec<B256>) -> B256 {
+    while nodes.len() > 1 {
+        nodes = nodes
+            .chunks(2)
+            .map(|pair| hash_pair(pair[0], pair[1]))
+            .collect();
+    }
+    nodes[0]
+}
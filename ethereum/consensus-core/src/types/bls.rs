@@ -1,3 +1,4 @@
+use alloy::primitives::FixedBytes;
 use bls12_381::{
     hash_to_curve::{ExpandMsgXmd, HashToCurve},
     multi_miller_loop, G1Affine, G1Projective, G2Affine, G2Prepared, G2Projective, Gt, Scalar,
@@ -5,6 +6,7 @@ use bls12_381::{
 use eyre::{eyre, Result};
 use serde::{Deserialize, Serialize};
 use ssz_derive::{Decode, Encode};
+use ssz_types::FixedVector;
 use tree_hash_derive::TreeHash;
 
 use super::bytes::ByteVector;
@@ -24,6 +26,11 @@ pub struct Signature {
 }
 
 impl PublicKey {
+    /// The compressed encoding of the key.
+    pub fn to_bytes(&self) -> FixedBytes<48> {
+        FixedBytes::from_slice(&self.inner.inner)
+    }
+
     fn point(&self) -> Result<G1Affine> {
         let bytes = self.inner.inner.to_vec();
         let bytes = bytes.as_slice().try_into()?;
@@ -36,7 +43,32 @@ impl PublicKey {
     }
 }
 
+impl From<FixedBytes<48>> for PublicKey {
+    fn from(bytes: FixedBytes<48>) -> Self {
+        Self {
+            inner: ByteVector {
+                inner: FixedVector::from(bytes.to_vec()),
+            },
+        }
+    }
+}
+
+impl From<FixedBytes<96>> for Signature {
+    fn from(bytes: FixedBytes<96>) -> Self {
+        Self {
+            inner: ByteVector {
+                inner: FixedVector::from(bytes.to_vec()),
+            },
+        }
+    }
+}
+
 impl Signature {
+    /// The compressed encoding of the signature.
+    pub fn to_bytes(&self) -> FixedBytes<96> {
+        FixedBytes::from_slice(&self.inner.inner)
+    }
+
     /// FastAggregateVerify
     ///
     /// Verifies an AggregateSignature against a list of PublicKeys.
@@ -127,7 +159,7 @@ fn ate2_evaluation(p1: &G2Affine, q1: &G1Affine, r1: &G2Affine, s1: &G1Affine) -
 }
 
 /// Hash a message to the curve
-fn hash_to_curve(msg: &[u8]) -> G2Projective {
+pub(crate) fn hash_to_curve(msg: &[u8]) -> G2Projective {
     const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
     <G2Projective as HashToCurve<ExpandMsgXmd<sha2::Sha256>>>::hash_to_curve(msg, DST)
 }
@@ -19,6 +19,9 @@ use self::{
 pub mod bls;
 pub mod bytes;
 mod serde_utils;
+pub mod slots;
+
+pub use self::slots::{Epoch, Period, Slot};
 
 pub type LogsBloom = ByteVector<typenum::U256>;
 pub type KZGCommitment = ByteVector<typenum::U48>;
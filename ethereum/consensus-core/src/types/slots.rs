@@ -0,0 +1,231 @@
+//! Slots, epochs and sync committee periods as distinct types.
+//!
+//! All three are plain `u64`s on the wire and used to be passed around as such, which made it
+//! easy to hand a slot to something expecting a period. The conversions between them depend on
+//! the network, so they take the [`ConsensusSpec`] to use.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub};
+
+use serde::{Deserialize, Serialize};
+
+use crate::consensus_spec::ConsensusSpec;
+
+macro_rules! newtype {
+    ($(#[$attr:meta])* $name:ident) => {
+        $(#[$attr])*
+        #[derive(
+            Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+        )]
+        #[serde(transparent)]
+        pub struct $name(u64);
+
+        impl $name {
+            pub const fn new(value: u64) -> Self {
+                Self(value)
+            }
+
+            pub const fn as_u64(self) -> u64 {
+                self.0
+            }
+
+            pub fn saturating_sub(self, rhs: u64) -> Self {
+                Self(self.0.saturating_sub(rhs))
+            }
+
+            pub fn checked_sub(self, rhs: u64) -> Option<Self> {
+                self.0.checked_sub(rhs).map(Self)
+            }
+
+            /// How many are between `earlier` and `self`, or zero if `earlier` is later.
+            pub fn since(self, earlier: Self) -> u64 {
+                self.0.saturating_sub(earlier.0)
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for u64 {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        impl Add<u64> for $name {
+            type Output = Self;
+
+            fn add(self, rhs: u64) -> Self {
+                Self(self.0 + rhs)
+            }
+        }
+
+        impl AddAssign<u64> for $name {
+            fn add_assign(&mut self, rhs: u64) {
+                self.0 += rhs;
+            }
+        }
+
+        /// Saturates at zero, as [`saturating_sub`](Self::saturating_sub) does.
+        impl Sub<u64> for $name {
+            type Output = Self;
+
+            fn sub(self, rhs: u64) -> Self {
+                self.saturating_sub(rhs)
+            }
+        }
+
+        /// How many are between `rhs` and `self`, zero if `rhs` is later, as
+        /// [`since`](Self::since) counts.
+        impl Sub for $name {
+            type Output = u64;
+
+            fn sub(self, rhs: Self) -> u64 {
+                self.since(rhs)
+            }
+        }
+    };
+}
+
+newtype!(
+    /// A beacon chain slot.
+    Slot
+);
+
+newtype!(
+    /// A beacon chain epoch.
+    Epoch
+);
+
+newtype!(
+    /// A sync committee period.
+    Period
+);
+
+impl Slot {
+    pub fn epoch<S: ConsensusSpec>(self) -> Epoch {
+        Epoch(self.0 / S::slots_per_epoch())
+    }
+
+    pub fn period<S: ConsensusSpec>(self) -> Period {
+        self.epoch::<S>().period::<S>()
+    }
+
+    /// Whether this is the first slot of its epoch.
+    pub fn is_epoch_start<S: ConsensusSpec>(self) -> bool {
+        self.0 % S::slots_per_epoch() == 0
+    }
+}
+
+impl Epoch {
+    pub fn start_slot<S: ConsensusSpec>(self) -> Slot {
+        Slot(self.0 * S::slots_per_epoch())
+    }
+
+    pub fn period<S: ConsensusSpec>(self) -> Period {
+        Period(self.0 / S::epochs_per_sync_committee_period())
+    }
+}
+
+impl Period {
+    pub fn start_epoch<S: ConsensusSpec>(self) -> Epoch {
+        Epoch(self.0 * S::epochs_per_sync_committee_period())
+    }
+
+    pub fn start_slot<S: ConsensusSpec>(self) -> Slot {
+        self.start_epoch::<S>().start_slot::<S>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::consensus_spec::{MainnetConsensusSpec as Mainnet, MinimalConsensusSpec as Minimal};
+
+    #[test]
+    fn test_epoch_boundaries() {
+        assert_eq!(Slot::new(31).epoch::<Mainnet>(), Epoch::new(0));
+        assert_eq!(Slot::new(32).epoch::<Mainnet>(), Epoch::new(1));
+        assert!(Slot::new(32).is_epoch_start::<Mainnet>());
+        assert!(!Slot::new(33).is_epoch_start::<Mainnet>());
+
+        assert_eq!(Slot::new(7).epoch::<Minimal>(), Epoch::new(0));
+        assert_eq!(Slot::new(8).epoch::<Minimal>(), Epoch::new(1));
+        assert_eq!(Epoch::new(1).start_slot::<Minimal>(), Slot::new(8));
+    }
+
+    #[test]
+    fn test_period_boundaries() {
+        assert_eq!(Slot::new(8191).period::<Mainnet>(), Period::new(0));
+        assert_eq!(Slot::new(8192).period::<Mainnet>(), Period::new(1));
+        assert_eq!(Epoch::new(255).period::<Mainnet>(), Period::new(0));
+        assert_eq!(Epoch::new(256).period::<Mainnet>(), Period::new(1));
+        assert_eq!(Period::new(1).start_slot::<Mainnet>(), Slot::new(8192));
+        assert_eq!(Period::new(1).start_epoch::<Mainnet>(), Epoch::new(256));
+
+        assert_eq!(Slot::new(63).period::<Minimal>(), Period::new(0));
+        assert_eq!(Slot::new(64).period::<Minimal>(), Period::new(1));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let slot = Slot::new(10);
+        assert_eq!(slot + 5, Slot::new(15));
+        assert_eq!(slot - Slot::new(4), 6);
+        assert_eq!(slot - Slot::new(12), 0);
+        assert_eq!(slot - 11, Slot::new(0));
+        assert_eq!(slot.saturating_sub(20), Slot::new(0));
+        assert_eq!(slot.checked_sub(11), None);
+        assert_eq!(slot.since(Slot::new(12)), 0);
+
+        let mut period = Period::new(3);
+        period += 1;
+        assert_eq!(period, Period::new(4));
+    }
+
+    #[test]
+    fn test_serialized_as_plain_integers() {
+        assert_eq!(serde_json::to_string(&Slot::new(8192)).unwrap(), "8192");
+        assert_eq!(
+            serde_json::from_str::<Period>("42").unwrap(),
+            Period::new(42)
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_u64_round_trip(value: u64) {
+            prop_assert_eq!(u64::from(Slot::from(value)), value);
+            prop_assert_eq!(Epoch::new(value).as_u64(), value);
+            let json = serde_json::to_string(&Period::new(value)).unwrap();
+            prop_assert_eq!(serde_json::from_str::<Period>(&json).unwrap(), Period::new(value));
+        }
+
+        #[test]
+        fn test_start_slot_round_trip(epoch in 0..u64::MAX / 32, period in 0..u64::MAX / 8192) {
+            prop_assert_eq!(Epoch::new(epoch).start_slot::<Mainnet>().epoch::<Mainnet>(), Epoch::new(epoch));
+            prop_assert_eq!(Period::new(period).start_slot::<Mainnet>().period::<Mainnet>(), Period::new(period));
+        }
+
+        #[test]
+        fn test_slot_within_its_epoch_and_period(slot: u64) {
+            let slot = Slot::new(slot);
+            let epoch = slot.epoch::<Mainnet>();
+            prop_assert!(epoch.start_slot::<Mainnet>() <= slot);
+            prop_assert!(slot.since(epoch.start_slot::<Mainnet>()) < Mainnet::slots_per_epoch());
+            prop_assert_eq!(epoch.period::<Mainnet>(), slot.period::<Mainnet>());
+            prop_assert!(slot.period::<Mainnet>().start_slot::<Mainnet>() <= slot);
+        }
+    }
+}
@@ -6,7 +6,7 @@ use tree_hash_derive::TreeHash;
 
 use crate::{
     consensus_spec::ConsensusSpec,
-    types::{bls::PublicKey, Forks, SyncCommittee},
+    types::{bls::PublicKey, Forks, Slot, SyncCommittee},
 };
 
 pub fn compute_committee_sign_root(header: B256, fork_data_root: B256) -> B256 {
@@ -19,7 +19,7 @@ pub fn calculate_fork_version<S: ConsensusSpec>(
     forks: &Forks,
     slot: u64,
 ) -> FixedVector<u8, typenum::U4> {
-    let epoch = slot / S::slots_per_epoch();
+    let epoch = Slot::new(slot).epoch::<S>().as_u64();
 
     let version = if epoch >= forks.electra.epoch {
         forks.electra.fork_version
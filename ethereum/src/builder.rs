@@ -1,3 +1,4 @@
+use std::any::{type_name, TypeId};
 #[cfg(not(target_arch = "wasm32"))]
 use std::net::{IpAddr, SocketAddr};
 #[cfg(not(target_arch = "wasm32"))]
@@ -5,16 +6,39 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use alloy::primitives::B256;
-use eyre::{eyre, Result};
+use alloy::providers::{Provider, RootProvider};
+use alloy::transports::{BoxTransport, Transport};
+use url::Url;
 
 use helios_consensus_core::consensus_spec::MainnetConsensusSpec;
-use helios_core::client::Client;
+#[cfg(not(target_arch = "wasm32"))]
+use helios_core::client::ipc::{IpcConfig, DEFAULT_IPC_PERMISSIONS};
+#[cfg(not(target_arch = "wasm32"))]
+use helios_core::client::tls::TlsConfig;
+use helios_core::client::trust::RpcPolicies;
+use helios_core::config::ByteSize;
+use helios_core::execution::code_cache::CodeCacheConfig;
+use helios_core::execution::config::ExecutionConfig;
+use helios_core::execution::rpc::headers::HeaderConfig;
+use helios_core::execution::rpc::http_rpc::HttpRpc as ExecutionHttpRpc;
+use helios_core::execution::rpc::rate_limit::RateLimitConfig;
+use helios_core::execution::rpc::retry::RetryConfig;
+use helios_core::execution::rpc::timeout::HttpClientConfig;
+use helios_core::execution::rpc::ExecutionRpc;
+use helios_core::execution::usage::UsageMeter;
+#[cfg(all(not(feature = "metrics"), not(target_arch = "wasm32")))]
+use tracing::warn;
 
+use crate::config::base::BaseConfig;
+use crate::config::checkpoints::check_checkpoint;
+use crate::config::errors::ConfigError;
 use crate::config::networks::Network;
 use crate::config::Config;
 use crate::consensus::ConsensusClient;
-use crate::database::Database;
-use crate::rpc::http_rpc::HttpRpc;
+use crate::database::usage::DbUsageStore;
+use crate::database::{Database, OpenDatabase, SharedDB};
+use crate::rpc::http_rpc::HttpRpc as ConsensusHttpRpc;
+use crate::rpc::ConsensusRpc;
 use crate::spec::Ethereum;
 use crate::EthereumClient;
 
@@ -23,17 +47,35 @@ pub struct EthereumClientBuilder {
     network: Option<Network>,
     consensus_rpc: Option<String>,
     execution_rpc: Option<String>,
+    execution_provider: Option<RootProvider<BoxTransport, Ethereum>>,
     checkpoint: Option<B256>,
     #[cfg(not(target_arch = "wasm32"))]
     rpc_bind_ip: Option<IpAddr>,
     #[cfg(not(target_arch = "wasm32"))]
     rpc_port: Option<u16>,
     #[cfg(not(target_arch = "wasm32"))]
+    rpc_tls: Option<TlsConfig>,
+    #[cfg(not(target_arch = "wasm32"))]
+    ipc_path: Option<PathBuf>,
+    #[cfg(not(target_arch = "wasm32"))]
+    metrics_address: Option<SocketAddr>,
+    #[cfg(not(target_arch = "wasm32"))]
     data_dir: Option<PathBuf>,
     config: Option<Config>,
     fallback: Option<String>,
     load_external_fallback: bool,
+    fallback_quorum: Option<usize>,
     strict_checkpoint_age: bool,
+    premerge_passthrough: bool,
+    confirmation_depth: Option<u64>,
+    rpc_policies: Option<RpcPolicies>,
+    execution_retry: Option<RetryConfig>,
+    execution_rate_limit: Option<RateLimitConfig>,
+    execution_http: Option<HttpClientConfig>,
+    execution_headers: Option<HeaderConfig>,
+    code_cache: Option<CodeCacheConfig>,
+    database: Option<Arc<dyn Database>>,
+    offline: bool,
 }
 
 impl EthereumClientBuilder {
@@ -56,6 +98,16 @@ impl EthereumClientBuilder {
         self
     }
 
+    /// Sends all execution requests through the given provider instead of building a new one
+    /// from the execution rpc url, so that it can be shared between several clients.
+    pub fn execution_provider<T: Transport + Clone>(
+        mut self,
+        provider: RootProvider<T, Ethereum>,
+    ) -> Self {
+        self.execution_provider = Some(provider.boxed());
+        self
+    }
+
     pub fn checkpoint(mut self, checkpoint: B256) -> Self {
         self.checkpoint = Some(checkpoint);
         self
@@ -73,6 +125,31 @@ impl EthereumClientBuilder {
         self
     }
 
+    /// Serves the rpc over TLS using a PEM encoded certificate chain and private key.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn rpc_tls(mut self, cert_path: PathBuf, key_path: PathBuf) -> Self {
+        self.rpc_tls = Some(TlsConfig {
+            cert_path,
+            key_path,
+        });
+        self
+    }
+
+    /// Also serves the rpc over a Unix domain socket, or a named pipe on Windows, at `path`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn ipc_path(mut self, path: PathBuf) -> Self {
+        self.ipc_path = Some(path);
+        self
+    }
+
+    /// Serves prometheus metrics on `addr`, in place of the `metrics_address` of the config.
+    /// Only has an effect when built with the `metrics` feature.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn metrics_address(mut self, addr: SocketAddr) -> Self {
+        self.metrics_address = Some(addr);
+        self
+    }
+
     #[cfg(not(target_arch = "wasm32"))]
     pub fn data_dir(mut self, data_dir: PathBuf) -> Self {
         self.data_dir = Some(data_dir);
@@ -94,37 +171,227 @@ impl EthereumClientBuilder {
         self
     }
 
+    /// Requires `quorum` external fallback services to agree on a checkpoint before it is
+    /// bootstrapped from.
+    pub fn fallback_quorum(mut self, quorum: usize) -> Self {
+        self.fallback_quorum = Some(quorum);
+        self
+    }
+
     pub fn strict_checkpoint_age(mut self) -> Self {
         self.strict_checkpoint_age = true;
         self
     }
 
-    pub fn build<DB: Database>(self) -> Result<EthereumClient<DB>> {
-        let base_config = if let Some(network) = self.network {
-            network.to_base_config()
-        } else {
-            let config = self
-                .config
-                .as_ref()
-                .ok_or(eyre!("missing network config"))?;
-            config.to_base_config()
-        };
+    /// Serves blocks and logs from before the merge unverified from the execution provider.
+    pub fn premerge_passthrough(mut self) -> Self {
+        self.premerge_passthrough = true;
+        self
+    }
 
-        let consensus_rpc = self.consensus_rpc.unwrap_or_else(|| {
-            self.config
-                .as_ref()
-                .expect("missing consensus rpc")
-                .consensus_rpc
-                .clone()
-        });
+    /// Resolves the `helios_confirmed` tag to `depth` blocks under the latest.
+    pub fn confirmation_depth(mut self, depth: u64) -> Self {
+        self.confirmation_depth = Some(depth);
+        self
+    }
 
-        let execution_rpc = self.execution_rpc.unwrap_or_else(|| {
-            self.config
-                .as_ref()
-                .expect("missing execution rpc")
-                .execution_rpc
-                .clone()
+    /// Sets the verification policy of each rpc method, replacing the `rpc` table of the
+    /// config.
+    pub fn rpc_policies(mut self, policies: RpcPolicies) -> Self {
+        self.rpc_policies = Some(policies);
+        self
+    }
+
+    /// Sets how rate limited execution provider requests are retried, replacing the
+    /// `execution_retry` table of the config. Has no effect on a provider given with
+    /// [`Self::execution_provider`], which brings its own layers.
+    pub fn execution_retry(mut self, retry: RetryConfig) -> Self {
+        self.execution_retry = Some(retry);
+        self
+    }
+
+    /// Holds execution provider requests to the budget of `rate_limit`, replacing the
+    /// `execution_rate_limit` table of the config. Requests of a provider given with
+    /// [`Self::execution_provider`] are not held back.
+    pub fn execution_rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.execution_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Sets the timeouts and connection pooling of execution provider requests, replacing
+    /// the `execution_http` table of the config. Like [`Self::execution_retry`] it has no
+    /// effect on a provider given with [`Self::execution_provider`].
+    pub fn execution_http(mut self, http: HttpClientConfig) -> Self {
+        self.execution_http = Some(http);
+        self
+    }
+
+    /// Sets the headers and credentials sent with execution provider requests, replacing
+    /// the `execution_headers` table of the config. A provider given with
+    /// [`Self::execution_provider`] sends its own.
+    pub fn execution_headers(mut self, headers: HeaderConfig) -> Self {
+        self.execution_headers = Some(headers);
+        self
+    }
+
+    /// Caches fetched contract code in `dir`, keeping up to `max_size` of it, in place of the
+    /// `code_cache` table of the config.
+    pub fn code_cache(mut self, dir: PathBuf, max_size: ByteSize) -> Self {
+        self.code_cache = Some(CodeCacheConfig {
+            dir: Some(dir),
+            max_size,
         });
+        self
+    }
+
+    /// Keeps checkpoints, sync committees, provider usage, verified headers and, without a
+    /// directory of its own, cached contract code in `database`. The client must then be
+    /// built as `build::<SharedDB>()`, building it as any other fails with
+    /// [`ConfigError::DatabaseType`].
+    pub fn with_database(mut self, database: impl Database) -> Self {
+        self.database = Some(Arc::new(database));
+        self
+    }
+
+    /// Skips the checks [`Self::build_checked`] makes against the rpcs, for constructing a
+    /// client without network access.
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    /// Builds the client like [`Self::build`], after running [`Self::preflight`] unless
+    /// [`Self::offline`] is set, so that rpcs serving another chain are reported before the
+    /// client starts syncing from them.
+    pub async fn build_checked<DB: OpenDatabase>(self) -> Result<EthereumClient<DB>, ConfigError> {
+        if !self.offline {
+            self.preflight().await?;
+        }
+        self.build()
+    }
+
+    /// Checks that the execution rpc serves the chain id of the selected network, and the
+    /// consensus rpc its genesis validators root.
+    pub async fn preflight(&self) -> Result<(), ConfigError> {
+        let base_config = self.base_config()?;
+        let (consensus_rpc, execution_rpc) = self.rpc_urls(&base_config)?;
+        let expected = base_config.chain;
+
+        let chain_id = match &self.execution_provider {
+            Some(provider) => provider.get_chain_id().await.map_err(eyre::Report::from),
+            None => {
+                let compression = self
+                    .config
+                    .as_ref()
+                    .map(|config| config.compression)
+                    .unwrap_or_default();
+                let rpc = ExecutionHttpRpc::<Ethereum>::connect(
+                    &execution_rpc,
+                    compression.enabled,
+                    RetryConfig::default(),
+                    &RateLimitConfig::default(),
+                    self.execution_http_config(),
+                    &self.execution_headers_config(),
+                )
+                .map_err(ConfigError::Setup)?;
+                rpc.chain_id().await
+            }
+        }
+        .map_err(|err| ConfigError::Unreachable("execution rpc", err))?;
+        if chain_id != expected.chain_id {
+            return Err(ConfigError::ChainIdMismatch {
+                expected: expected.chain_id,
+                got: chain_id,
+            });
+        }
+
+        let consensus =
+            <ConsensusHttpRpc as ConsensusRpc<MainnetConsensusSpec>>::new(&consensus_rpc);
+        let genesis_root = ConsensusRpc::<MainnetConsensusSpec>::get_genesis_root(&consensus)
+            .await
+            .map_err(|err| ConfigError::Unreachable("consensus rpc", err))?;
+        if genesis_root != expected.genesis_root {
+            return Err(ConfigError::GenesisRootMismatch {
+                expected: expected.genesis_root,
+                got: genesis_root,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn base_config(&self) -> Result<BaseConfig, ConfigError> {
+        match (self.network, &self.config) {
+            (Some(network), _) => Ok(network.to_base_config()),
+            (None, Some(config)) => Ok(config.to_base_config()),
+            (None, None) => Err(ConfigError::MissingField("network")),
+        }
+    }
+
+    /// The consensus and execution rpc urls, falling back to the config and then to the
+    /// consensus rpc of the network preset.
+    fn rpc_urls(&self, base_config: &BaseConfig) -> Result<(String, String), ConfigError> {
+        let consensus_rpc = self
+            .consensus_rpc
+            .clone()
+            .or_else(|| {
+                self.config
+                    .as_ref()
+                    .map(|config| config.consensus_rpc.clone())
+            })
+            .filter(|rpc| !rpc.is_empty())
+            .or_else(|| base_config.consensus_rpc.clone())
+            .ok_or(ConfigError::MissingField("consensus_rpc"))?;
+        check_url("consensus_rpc", &consensus_rpc)?;
+
+        let execution_rpc = self
+            .execution_rpc
+            .clone()
+            .or_else(|| {
+                self.config
+                    .as_ref()
+                    .map(|config| config.execution_rpc.clone())
+            })
+            .filter(|rpc| !rpc.is_empty())
+            .ok_or(ConfigError::MissingField("execution_rpc"))?;
+        // a provider brings its own transport, the url only names it
+        if self.execution_provider.is_none() {
+            check_url("execution_rpc", &execution_rpc)?;
+        }
+
+        Ok((consensus_rpc, execution_rpc))
+    }
+
+    fn execution_http_config(&self) -> HttpClientConfig {
+        self.execution_http
+            .or(self.config.as_ref().map(|config| config.execution_http))
+            .unwrap_or_default()
+    }
+
+    fn execution_headers_config(&self) -> HeaderConfig {
+        self.execution_headers
+            .clone()
+            .or_else(|| {
+                self.config
+                    .as_ref()
+                    .map(|config| config.execution_headers.clone())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Builds the client once the configuration is found to be complete and well formed,
+    /// without making any request. The rpcs are left unchecked, see [`Self::build_checked`]
+    /// for checking them first.
+    pub fn build<DB: OpenDatabase>(self) -> Result<EthereumClient<DB>, ConfigError> {
+        // a database of another type would keep the checkpoints apart from the supplied one
+        if self.database.is_some() && TypeId::of::<DB>() != TypeId::of::<SharedDB>() {
+            return Err(ConfigError::DatabaseType(type_name::<DB>()));
+        }
+
+        let base_config = self.base_config()?;
+        let (consensus_rpc, execution_rpc) = self.rpc_urls(&base_config)?;
+        let execution_http = self.execution_http_config();
+        let execution_headers = self.execution_headers_config();
 
         let checkpoint = if let Some(checkpoint) = self.checkpoint {
             Some(checkpoint)
@@ -133,6 +400,7 @@ impl EthereumClientBuilder {
         } else {
             None
         };
+        let checkpoint = checkpoint.map(check_checkpoint).transpose()?;
 
         let default_checkpoint = if let Some(config) = &self.config {
             config.default_checkpoint
@@ -158,6 +426,46 @@ impl EthereumClientBuilder {
             None
         };
 
+        #[cfg(not(target_arch = "wasm32"))]
+        let rpc_tls = if self.rpc_tls.is_some() {
+            self.rpc_tls
+        } else if let Some(config) = &self.config {
+            match (&config.rpc_tls_cert, &config.rpc_tls_key) {
+                (Some(cert_path), Some(key_path)) => Some(TlsConfig {
+                    cert_path: cert_path.clone(),
+                    key_path: key_path.clone(),
+                }),
+                (None, None) => None,
+                _ => return Err(ConfigError::IncompleteTls),
+            }
+        } else {
+            None
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let ipc = self
+            .ipc_path
+            .or_else(|| {
+                self.config
+                    .as_ref()
+                    .and_then(|config| config.ipc_path.clone())
+            })
+            .map(|path| IpcConfig {
+                path,
+                permissions: self
+                    .config
+                    .as_ref()
+                    .and_then(|config| config.ipc_permissions)
+                    .unwrap_or(DEFAULT_IPC_PERMISSIONS),
+            });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let ipc_only = self.config.as_ref().is_some_and(|config| config.ipc_only);
+        #[cfg(not(target_arch = "wasm32"))]
+        if ipc_only && ipc.is_none() {
+            return Err(ConfigError::IpcWithoutPath);
+        }
+
         #[cfg(not(target_arch = "wasm32"))]
         let data_dir = if self.data_dir.is_some() {
             self.data_dir
@@ -174,6 +482,9 @@ impl EthereumClientBuilder {
         } else {
             None
         };
+        if let Some(fallback) = &fallback {
+            check_url("fallback", fallback)?;
+        }
 
         let load_external_fallback = if let Some(config) = &self.config {
             self.load_external_fallback || config.load_external_fallback
@@ -181,12 +492,72 @@ impl EthereumClientBuilder {
             self.load_external_fallback
         };
 
+        let fallback_quorum = self
+            .fallback_quorum
+            .or(self.config.as_ref().map(|config| config.fallback_quorum))
+            .unwrap_or(base_config.fallback_quorum);
+
         let strict_checkpoint_age = if let Some(config) = &self.config {
             self.strict_checkpoint_age || config.strict_checkpoint_age
         } else {
             self.strict_checkpoint_age
         };
 
+        let premerge_passthrough = if let Some(config) = &self.config {
+            self.premerge_passthrough || config.premerge_passthrough
+        } else {
+            self.premerge_passthrough
+        };
+
+        let execution_retry = self
+            .execution_retry
+            .or(self.config.as_ref().map(|config| config.execution_retry))
+            .unwrap_or_default();
+
+        let execution_rate_limit = self
+            .execution_rate_limit
+            .or_else(|| {
+                self.config
+                    .as_ref()
+                    .map(|config| config.execution_rate_limit.clone())
+            })
+            .unwrap_or_default();
+
+        let confirmation_depth = self
+            .confirmation_depth
+            .or(self.config.as_ref().map(|config| config.confirmation_depth))
+            .unwrap_or(base_config.confirmation_depth);
+
+        let sync_committee_retention = self
+            .config
+            .as_ref()
+            .map(|config| config.sync_committee_retention)
+            .unwrap_or(base_config.sync_committee_retention);
+
+        let ens_registry = self
+            .config
+            .as_ref()
+            .map(|config| config.ens_registry)
+            .unwrap_or(base_config.ens_registry);
+
+        let ccip_read = self
+            .config
+            .as_ref()
+            .map(|config| config.ccip_read)
+            .unwrap_or(base_config.ccip_read);
+
+        let ccip_read_timeout = self
+            .config
+            .as_ref()
+            .map(|config| config.ccip_read_timeout)
+            .unwrap_or(base_config.ccip_read_timeout);
+
+        let rpc_policies = match (self.rpc_policies, &self.config) {
+            (Some(policies), _) => policies,
+            (None, Some(config)) => config.rpc.clone(),
+            (None, None) => RpcPolicies::default(),
+        };
+
         let config = Config {
             consensus_rpc,
             execution_rpc,
@@ -201,6 +572,33 @@ impl EthereumClientBuilder {
             #[cfg(target_arch = "wasm32")]
             rpc_port: None,
             #[cfg(not(target_arch = "wasm32"))]
+            rpc_tls_cert: rpc_tls.as_ref().map(|tls| tls.cert_path.clone()),
+            #[cfg(not(target_arch = "wasm32"))]
+            rpc_tls_key: rpc_tls.as_ref().map(|tls| tls.key_path.clone()),
+            #[cfg(target_arch = "wasm32")]
+            rpc_tls_cert: None,
+            #[cfg(target_arch = "wasm32")]
+            rpc_tls_key: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            ipc_path: ipc.as_ref().map(|ipc| ipc.path.clone()),
+            #[cfg(not(target_arch = "wasm32"))]
+            ipc_permissions: ipc.as_ref().map(|ipc| ipc.permissions),
+            #[cfg(not(target_arch = "wasm32"))]
+            ipc_only,
+            #[cfg(target_arch = "wasm32")]
+            ipc_path: None,
+            #[cfg(target_arch = "wasm32")]
+            ipc_permissions: None,
+            #[cfg(target_arch = "wasm32")]
+            ipc_only: false,
+            #[cfg(not(target_arch = "wasm32"))]
+            metrics_address: self.metrics_address.or(self
+                .config
+                .as_ref()
+                .and_then(|config| config.metrics_address)),
+            #[cfg(target_arch = "wasm32")]
+            metrics_address: None,
+            #[cfg(not(target_arch = "wasm32"))]
             data_dir,
             #[cfg(target_arch = "wasm32")]
             data_dir: None,
@@ -208,28 +606,175 @@ impl EthereumClientBuilder {
             forks: base_config.forks,
             execution_forks: base_config.execution_forks,
             max_checkpoint_age: base_config.max_checkpoint_age,
+            clock_skew_tolerance: base_config.clock_skew_tolerance,
             fallback,
             load_external_fallback,
+            fallback_quorum,
             strict_checkpoint_age,
+            premerge_passthrough,
+            confirmation_depth,
+            sync_committee_retention,
+            ens_registry,
+            ccip_read,
+            ccip_read_timeout,
+            rpc: rpc_policies,
+            usage: self
+                .config
+                .as_ref()
+                .map(|config| config.usage.clone())
+                .unwrap_or_default(),
+            orphan_cache: self
+                .config
+                .as_ref()
+                .map(|config| config.orphan_cache)
+                .unwrap_or_default(),
+            backfill: self
+                .config
+                .as_ref()
+                .map(|config| config.backfill)
+                .unwrap_or_default(),
+            logs: self
+                .config
+                .as_ref()
+                .map(|config| config.logs)
+                .unwrap_or_default(),
+            fee_history: self
+                .config
+                .as_ref()
+                .map(|config| config.fee_history)
+                .unwrap_or_default(),
+            gas_price: self
+                .config
+                .as_ref()
+                .map(|config| config.gas_price)
+                .unwrap_or_default(),
+            historical_state: self
+                .config
+                .as_ref()
+                .map(|config| config.historical_state)
+                .unwrap_or_default(),
+            evm: self
+                .config
+                .as_ref()
+                .map(|config| config.evm)
+                .unwrap_or_default(),
+            payload_limits: self
+                .config
+                .as_ref()
+                .map(|config| config.payload_limits)
+                .unwrap_or_default(),
+            code_cache: self
+                .code_cache
+                .or_else(|| self.config.as_ref().map(|config| config.code_cache.clone()))
+                .unwrap_or_default(),
+            compression: self
+                .config
+                .as_ref()
+                .map(|config| config.compression)
+                .unwrap_or_default(),
+            execution_retry,
+            execution_rate_limit,
+            execution_http,
+            execution_headers,
+            broadcast_rpcs: self
+                .config
+                .as_ref()
+                .map(|config| config.broadcast_rpcs.clone())
+                .unwrap_or_default(),
+            skip_transaction_checks: self
+                .config
+                .as_ref()
+                .is_some_and(|config| config.skip_transaction_checks),
             database_type: None,
+            database: self.database,
         };
 
         #[cfg(not(target_arch = "wasm32"))]
-        let socket = if let (Some(rpc_bind_ip), Some(rpc_port)) = (rpc_bind_ip, rpc_port) {
-            Some(SocketAddr::new(rpc_bind_ip, rpc_port))
-        } else {
-            None
+        let socket = match (rpc_bind_ip, rpc_port) {
+            (Some(rpc_bind_ip), Some(rpc_port)) if !ipc_only => {
+                Some(SocketAddr::new(rpc_bind_ip, rpc_port))
+            }
+            _ => None,
         };
 
         let config = Arc::new(config);
-        let consensus = ConsensusClient::new(&config.consensus_rpc, config.clone())?;
+        let consensus = ConsensusClient::new(&config.consensus_rpc, config.clone())
+            .map_err(ConfigError::Setup)?;
 
-        Client::<Ethereum, ConsensusClient<MainnetConsensusSpec, HttpRpc, DB>>::new(
-            &config.execution_rpc.clone(),
+        let execution_rpc = match self.execution_provider {
+            Some(provider) => ExecutionHttpRpc::from_provider(&config.execution_rpc, provider),
+            None => ExecutionHttpRpc::connect(
+                &config.execution_rpc,
+                config.compression.enabled,
+                config.execution_retry,
+                &config.execution_rate_limit,
+                config.execution_http,
+                &config.execution_headers,
+            )
+            .map_err(ConfigError::Setup)?,
+        };
+        let db = DB::new(&config).map_err(ConfigError::Setup)?;
+        let execution_config = ExecutionConfig {
+            header_store: Some(Arc::new(db.clone())),
+            ..config.execution_config()
+        };
+        let usage = UsageMeter::new(config.usage.clone()).with_store(DbUsageStore::new(db));
+
+        let client = EthereumClient::<DB>::with_execution_rpc(
+            execution_rpc.with_usage(usage),
             consensus,
             config.execution_forks,
+            execution_config,
             #[cfg(not(target_arch = "wasm32"))]
             socket,
-        )
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let client = match ipc {
+            Some(ipc) => client.with_rpc_ipc(ipc),
+            None => client,
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let client = match rpc_tls {
+            Some(tls) => client.with_rpc_tls(tls),
+            None => client,
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let client = client
+            .with_rpc_policies(config.rpc.clone())
+            .with_rpc_compression(config.compression);
+
+        #[cfg(all(feature = "metrics", not(target_arch = "wasm32")))]
+        let client = match config.metrics_address {
+            Some(addr) => client.with_metrics(addr),
+            None => client,
+        };
+        #[cfg(all(not(feature = "metrics"), not(target_arch = "wasm32")))]
+        if config.metrics_address.is_some() {
+            warn!(
+                target: "helios::client",
+                "metrics_address is set, but helios was built without the metrics feature"
+            );
+        }
+
+        Ok(client)
+    }
+}
+
+/// Rejects urls that do not parse, or name a scheme other than http, as `localhost:8545`
+/// would.
+fn check_url(field: &'static str, url: &str) -> Result<(), ConfigError> {
+    let invalid = |reason: String| ConfigError::InvalidUrl {
+        field,
+        url: url.to_string(),
+        reason,
+    };
+
+    let parsed = Url::parse(url).map_err(|err| invalid(err.to_string()))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(()),
+        scheme => Err(invalid(format!("{scheme} is not an http scheme"))),
     }
 }
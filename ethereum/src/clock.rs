@@ -0,0 +1,223 @@
+//! Time as the consensus client sees it: the local clock, and for scheduling polls the local
+//! clock corrected for the drift measured against the timestamps of verified blocks.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tracing::warn;
+
+use helios_core::time::{SystemTime, UNIX_EPOCH};
+
+use crate::constants::{
+    DRIFT_MIN_SAMPLES, DRIFT_SMOOTHING, DRIFT_WARNING_THRESHOLD, EXPECTED_HEAD_AGE,
+};
+
+/// A source of wall clock time.
+pub trait Clock: Send + Sync + 'static {
+    /// Time since the unix epoch.
+    fn now(&self) -> Duration;
+}
+
+/// The local system clock.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+    }
+}
+
+/// A clock that only moves when told to, for tests.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: Mutex<Duration>,
+}
+
+impl ManualClock {
+    pub fn new(now: Duration) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
+
+impl<C: Clock> Clock for Arc<C> {
+    fn now(&self) -> Duration {
+        C::now(self)
+    }
+}
+
+/// Tracks how far the local clock is off by comparing when verified heads arrive to when
+/// they were produced.
+///
+/// Each head gives a sample of its arrival time minus its slot timestamp and the age a head
+/// normally has on arrival. Samples are weighted exponentially, so a late head or a missed
+/// slot barely moves the estimate while a clock that is persistently off shows up after a few
+/// slots. The estimate is only applied once enough heads have been seen, and by at most
+/// [`DRIFT_WARNING_THRESHOLD`] either way.
+///
+/// Heads arrive from the consensus rpc, which also decides how late they look, so the
+/// estimate only times polls. Ages, timestamps and checkpoints are checked against
+/// [`ConsensusClock::now`], the uncorrected local clock.
+pub struct ConsensusClock {
+    clock: Box<dyn Clock>,
+    drift: Mutex<DriftEstimate>,
+    warned: AtomicBool,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct DriftEstimate {
+    /// Milliseconds the local clock runs ahead of the network, negative when behind.
+    millis: f64,
+    samples: u32,
+}
+
+impl ConsensusClock {
+    pub fn new(clock: impl Clock) -> Self {
+        Self {
+            clock: Box::new(clock),
+            drift: Mutex::default(),
+            warned: AtomicBool::new(false),
+        }
+    }
+
+    pub fn system() -> Self {
+        Self::new(SystemClock)
+    }
+
+    /// Time since the unix epoch by the local clock.
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    /// Time since the unix epoch corrected by the estimated drift, for scheduling only.
+    pub fn scheduling_now(&self) -> Duration {
+        let limit = DRIFT_WARNING_THRESHOLD.as_millis() as i64;
+        let correction = self.drift().unwrap_or_default().clamp(-limit, limit);
+        let local = self.clock.now().as_millis() as i64;
+        Duration::from_millis(local.saturating_sub(correction).max(0) as u64)
+    }
+
+    /// The estimated drift in milliseconds, positive when the local clock is ahead, or `None`
+    /// until enough heads have been observed. Reported as measured, whereas scheduling
+    /// corrects for at most [`DRIFT_WARNING_THRESHOLD`] of it.
+    pub fn drift(&self) -> Option<i64> {
+        let estimate = *self.drift.lock().unwrap();
+        (estimate.samples >= DRIFT_MIN_SAMPLES).then_some(estimate.millis.round() as i64)
+    }
+
+    /// Records the arrival of a newly verified head produced at `timestamp`, in seconds.
+    pub fn observe_head(&self, timestamp: u64) {
+        let produced = Duration::from_secs(timestamp) + EXPECTED_HEAD_AGE;
+        let sample = self.clock.now().as_millis() as f64 - produced.as_millis() as f64;
+
+        {
+            let mut estimate = self.drift.lock().unwrap();
+            estimate.millis = if estimate.samples == 0 {
+                sample
+            } else {
+                DRIFT_SMOOTHING * sample + (1.0 - DRIFT_SMOOTHING) * estimate.millis
+            };
+            estimate.samples = estimate.samples.saturating_add(1);
+        }
+
+        let Some(drift) = self.drift() else {
+            return;
+        };
+        let exceeded = drift.unsigned_abs() > DRIFT_WARNING_THRESHOLD.as_millis() as u64;
+        if exceeded && !self.warned.swap(true, Ordering::Relaxed) {
+            let direction = if drift > 0 { "ahead" } else { "behind" };
+            warn!(
+                target: "helios::consensus",
+                drift_ms = drift,
+                "local clock appears to be {:.1}s {direction} of the network, consider syncing it with ntp",
+                drift.unsigned_abs() as f64 / 1000.0,
+            );
+        }
+    }
+}
+
+impl std::fmt::Debug for ConsensusClock {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConsensusClock")
+            .field("drift", &*self.drift.lock().unwrap())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENESIS: u64 = 1_606_824_023;
+
+    fn observe_heads(clock: &ConsensusClock, manual: &ManualClock, skew: i64, count: u64) {
+        for slot in 0..count {
+            let timestamp = GENESIS + slot * 12;
+            let arrival = (timestamp + EXPECTED_HEAD_AGE.as_secs()) as i64 + skew;
+            manual.set(Duration::from_secs(arrival as u64));
+            clock.observe_head(timestamp);
+        }
+    }
+
+    #[test]
+    fn test_drift_detected_either_way() {
+        for skew in [10, -10] {
+            let manual = Arc::new(ManualClock::default());
+            let clock = ConsensusClock::new(manual.clone());
+
+            observe_heads(&clock, &manual, skew, u64::from(DRIFT_MIN_SAMPLES) - 1);
+            assert_eq!(clock.drift(), None);
+            assert_eq!(clock.now(), manual.now());
+
+            observe_heads(&clock, &manual, skew, 1);
+            assert_eq!(clock.drift(), Some(skew * 1000));
+            // the correction stops at the warning threshold, and never applies to `now`
+            let limit = DRIFT_WARNING_THRESHOLD.as_secs() as i64;
+            let corrected = manual.now().as_secs() as i64 - skew.clamp(-limit, limit);
+            assert_eq!(
+                clock.scheduling_now(),
+                Duration::from_secs(corrected as u64)
+            );
+            assert_eq!(clock.now(), manual.now());
+            assert!(clock.warned.load(Ordering::Relaxed));
+        }
+    }
+
+    #[test]
+    fn test_single_late_head_barely_moves_estimate() {
+        let manual = Arc::new(ManualClock::default());
+        let clock = ConsensusClock::new(manual.clone());
+        observe_heads(&clock, &manual, 0, 16);
+        assert_eq!(clock.drift(), Some(0));
+
+        // a missed slot makes the next head a slot older than usual
+        let timestamp = GENESIS + 16 * 12;
+        manual.set(Duration::from_secs(
+            timestamp + EXPECTED_HEAD_AGE.as_secs() + 12,
+        ));
+        clock.observe_head(timestamp);
+
+        let drift = clock.drift().unwrap();
+        assert!(drift > 0 && drift < DRIFT_WARNING_THRESHOLD.as_millis() as i64);
+        assert!(!clock.warned.load(Ordering::Relaxed));
+    }
+}
@@ -2,13 +2,18 @@ use std::default::Default;
 use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
 
-use alloy::primitives::B256;
+use alloy::primitives::{Address, B256};
+use helios_core::config::ConfigDuration;
+use helios_core::execution::constants::{DEFAULT_CCIP_READ_TIMEOUT, DEFAULT_CONFIRMATION_DEPTH};
 use helios_core::fork_schedule::ForkSchedule;
 use serde::Serialize;
 
 use helios_consensus_core::types::Forks;
 
 use crate::config::types::ChainConfig;
+use crate::constants::{
+    DEFAULT_CLOCK_SKEW_TOLERANCE, DEFAULT_FALLBACK_QUORUM, DEFAULT_SYNC_COMMITTEE_RETENTION,
+};
 
 /// The base configuration for a network.
 #[derive(Serialize)]
@@ -20,10 +25,18 @@ pub struct BaseConfig {
     pub chain: ChainConfig,
     pub forks: Forks,
     pub execution_forks: ForkSchedule,
-    pub max_checkpoint_age: u64,
+    pub max_checkpoint_age: ConfigDuration,
+    pub clock_skew_tolerance: ConfigDuration,
     pub data_dir: Option<PathBuf>,
     pub load_external_fallback: bool,
+    pub fallback_quorum: usize,
     pub strict_checkpoint_age: bool,
+    pub premerge_passthrough: bool,
+    pub confirmation_depth: u64,
+    pub sync_committee_retention: u64,
+    pub ens_registry: Option<Address>,
+    pub ccip_read: bool,
+    pub ccip_read_timeout: ConfigDuration,
 }
 
 impl Default for BaseConfig {
@@ -35,11 +48,19 @@ impl Default for BaseConfig {
             default_checkpoint: B256::ZERO,
             chain: Default::default(),
             forks: Default::default(),
-            max_checkpoint_age: 0,
+            max_checkpoint_age: ConfigDuration::default(),
+            clock_skew_tolerance: DEFAULT_CLOCK_SKEW_TOLERANCE.into(),
             data_dir: None,
             execution_forks: ForkSchedule::default(),
             load_external_fallback: false,
+            fallback_quorum: DEFAULT_FALLBACK_QUORUM,
             strict_checkpoint_age: false,
+            premerge_passthrough: false,
+            confirmation_depth: DEFAULT_CONFIRMATION_DEPTH,
+            sync_committee_retention: DEFAULT_SYNC_COMMITTEE_RETENTION,
+            ens_registry: None,
+            ccip_read: false,
+            ccip_read_timeout: DEFAULT_CCIP_READ_TIMEOUT.into(),
         }
     }
 }
@@ -1,7 +1,11 @@
+use std::cmp::Reverse;
+use std::str::FromStr;
 use std::{collections::HashMap, time::Duration};
 
 use alloy::primitives::B256;
+use chrono::{DateTime, Utc};
 use eyre::Result;
+use helios_core::time::Instant;
 use reqwest::ClientBuilder;
 use retri::{retry, BackoffSettings};
 use serde::{
@@ -9,6 +13,7 @@ use serde::{
     Deserialize, Serialize,
 };
 
+use crate::config::errors::ConfigError;
 use crate::config::networks;
 
 /// The location where the list of checkpoint services are stored.
@@ -81,6 +86,90 @@ pub struct CheckpointFallback {
     pub networks: Vec<networks::Network>,
 }
 
+/// What a single checkpoint sync service answered when asked for its latest checkpoint.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointReport {
+    pub name: String,
+    pub endpoint: String,
+    pub root: Option<B256>,
+    pub slot: Option<u64>,
+    pub epoch: Option<u64>,
+    /// Seconds since the start of the checkpoint slot.
+    pub age: Option<u64>,
+    /// Milliseconds the service took to answer.
+    pub latency: u64,
+    pub error: Option<String>,
+}
+
+/// The answers of a set of checkpoint sync services, and the checkpoint most of them agree on
+/// at the latest epoch any of them returned.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointSurvey {
+    pub reports: Vec<CheckpointReport>,
+    pub majority: Option<B256>,
+    /// Whether more than half of the services that answered returned the majority checkpoint.
+    pub quorum: bool,
+}
+
+impl CheckpointSurvey {
+    fn new(reports: Vec<CheckpointReport>) -> Self {
+        let answered = reports
+            .iter()
+            .filter(|report| report.root.is_some())
+            .collect::<Vec<_>>();
+        let max_epoch = answered.iter().filter_map(|report| report.epoch).max();
+
+        let mut counts: HashMap<B256, usize> = HashMap::new();
+        for report in &answered {
+            if report.epoch == max_epoch {
+                *counts.entry(report.root.unwrap()).or_default() += 1;
+            }
+        }
+        // ties go to the lowest root, so the same answers always give the same majority
+        let majority = counts
+            .into_iter()
+            .max_by_key(|(root, count)| (*count, Reverse(*root)));
+        let quorum = majority.is_some_and(|(_, count)| count * 2 > answered.len());
+
+        Self {
+            majority: majority.map(|(root, _)| root),
+            quorum,
+            reports,
+        }
+    }
+
+    /// The majority checkpoint, provided at least `quorum` services returned it and they are
+    /// more than half of the services that answered.
+    pub fn agreed(&self, quorum: usize) -> Result<B256> {
+        let root = self
+            .majority
+            .ok_or_else(|| eyre::eyre!("no checkpoint service returned a checkpoint"))?;
+        let agreeing = self
+            .reports
+            .iter()
+            .filter(|report| report.root == Some(root))
+            .count();
+
+        if agreeing < quorum || !self.quorum {
+            let answered = self.reports.iter().filter(|r| r.root.is_some()).count();
+            eyre::bail!(
+                "only {agreeing} of {answered} checkpoint services agree on {root}, a majority of at least {quorum} is required"
+            );
+        }
+
+        Ok(root)
+    }
+
+    /// Services that answered with a checkpoint other than the majority.
+    pub fn outliers(&self) -> impl Iterator<Item = &CheckpointReport> {
+        self.reports
+            .iter()
+            .filter(|report| report.root.is_some() && report.root != self.majority)
+    }
+}
+
 async fn get(req: &str) -> Result<reqwest::Response> {
     retry(
         || async {
@@ -109,6 +198,7 @@ impl CheckpointFallback {
                 networks::Network::Mainnet,
                 networks::Network::Sepolia,
                 networks::Network::Holesky,
+                networks::Network::Hoodi,
             ],
         }
     }
@@ -151,77 +241,63 @@ impl CheckpointFallback {
         Self::fetch_latest_checkpoint_from_services(&services[..]).await
     }
 
-    async fn query_service(endpoint: &str) -> Option<RawSlotResponse> {
+    async fn query_service(endpoint: &str) -> Result<Slot> {
         let constructed_url = Self::construct_url(endpoint);
-        let res = get(&constructed_url).await.ok()?;
-        let raw: RawSlotResponse = res.json().await.ok()?;
-        Some(raw)
+        let res = get(&constructed_url).await?;
+        let raw: RawSlotResponse = res.json().await?;
+        raw.data
+            .slots
+            .into_iter()
+            .find(|s| s.block_root.is_some())
+            .ok_or_else(|| eyre::eyre!("no valid slots"))
+    }
+
+    /// Asks each of `services` for its latest checkpoint concurrently, without giving up on
+    /// the ones that fail.
+    pub async fn survey(services: &[CheckpointFallbackService]) -> CheckpointSurvey {
+        let tasks = services.iter().map(|service| async move {
+            let started = Instant::now();
+            let slot = Self::query_service(&service.endpoint).await;
+            let latency = started.elapsed().as_millis() as u64;
+
+            let mut report = CheckpointReport {
+                name: service.name.clone(),
+                endpoint: service.endpoint.clone(),
+                root: None,
+                slot: None,
+                epoch: None,
+                age: None,
+                latency,
+                error: None,
+            };
+            match slot {
+                Ok(slot) => {
+                    report.root = slot.block_root;
+                    report.slot = Some(slot.slot);
+                    report.epoch = Some(slot.epoch);
+                    report.age = DateTime::parse_from_rfc3339(&slot.time.start_time)
+                        .ok()
+                        .and_then(|start| {
+                            let age = Utc::now().signed_duration_since(start);
+                            u64::try_from(age.num_seconds()).ok()
+                        });
+                }
+                Err(err) => report.error = Some(err.to_string()),
+            }
+            report
+        });
+
+        CheckpointSurvey::new(futures::future::join_all(tasks).await)
     }
 
     /// Fetch the latest checkpoint from a list of checkpoint fallback services.
     pub async fn fetch_latest_checkpoint_from_services(
         services: &[CheckpointFallbackService],
     ) -> eyre::Result<B256> {
-        // Iterate over all mainnet checkpoint sync services and get the latest checkpoint slot for each.
-        let tasks: Vec<_> = services
-            .iter()
-            .map(|service| async move {
-                let service = service.clone();
-                match Self::query_service(&service.endpoint).await {
-                    Some(raw) => {
-                        if raw.data.slots.is_empty() {
-                            return Err(eyre::eyre!("no slots"));
-                        }
-
-                        let slot = raw
-                            .data
-                            .slots
-                            .iter()
-                            .find(|s| s.block_root.is_some())
-                            .ok_or(eyre::eyre!("no valid slots"))?;
-
-                        Ok(slot.clone())
-                    }
-                    None => Err(eyre::eyre!("failed to query service")),
-                }
-            })
-            .collect();
-
-        let slots = futures::future::join_all(tasks)
+        Self::survey(services)
             .await
-            .iter()
-            .filter_map(|slot| match &slot {
-                Ok(s) => Some(s.clone()),
-                _ => None,
-            })
-            .filter(|s| s.block_root.is_some())
-            .collect::<Vec<_>>();
-
-        // Get the max epoch
-        let max_epoch_slot = slots.iter().max_by_key(|x| x.epoch).ok_or(eyre::eyre!(
-            "Failed to find max epoch from checkpoint slots"
-        ))?;
-        let max_epoch = max_epoch_slot.epoch;
-
-        // Filter out all the slots that are not the max epoch.
-        let slots = slots
-            .into_iter()
-            .filter(|x| x.epoch == max_epoch)
-            .collect::<Vec<_>>();
-
-        // Return the most commonly verified checkpoint.
-        let checkpoints = slots
-            .iter()
-            .filter_map(|x| x.block_root)
-            .collect::<Vec<_>>();
-        let mut m: HashMap<B256, usize> = HashMap::new();
-        for c in checkpoints {
-            *m.entry(c).or_default() += 1;
-        }
-        let most_common = m.into_iter().max_by_key(|(_, v)| *v).map(|(k, _)| k);
-
-        // Return the most commonly verified checkpoint for the latest epoch.
-        most_common.ok_or_else(|| eyre::eyre!("No checkpoint found"))
+            .majority
+            .ok_or_else(|| eyre::eyre!("No checkpoint found"))
     }
 
     /// Associated function to fetch the latest checkpoint from a specific checkpoint sync fallback
@@ -300,6 +376,31 @@ impl CheckpointFallback {
     }
 }
 
+/// Reads a checkpoint block root given as 64 hex digits, with or without a `0x` prefix.
+pub fn parse_checkpoint(s: &str) -> Result<B256, ConfigError> {
+    let digits = s.strip_prefix("0x").unwrap_or(s);
+    if digits.len() != 64 {
+        return Err(ConfigError::InvalidCheckpoint(format!(
+            "{s:?} has {} hex digits, a block root has 64",
+            digits.len()
+        )));
+    }
+
+    let checkpoint = B256::from_str(digits)
+        .map_err(|_| ConfigError::InvalidCheckpoint(format!("{s:?} is not hex")))?;
+    check_checkpoint(checkpoint)
+}
+
+/// Rejects the zero root, which no block has and which an unset checkpoint often ends up as.
+pub fn check_checkpoint(checkpoint: B256) -> Result<B256, ConfigError> {
+    if checkpoint.is_zero() {
+        return Err(ConfigError::InvalidCheckpoint(
+            "the zero root is not a block".to_string(),
+        ));
+    }
+    Ok(checkpoint)
+}
+
 fn deserialize_number<'de, D>(deserializer: D) -> Result<u64, D::Error>
 where
     D: de::Deserializer<'de>,
@@ -317,3 +418,41 @@ where
         StringOrNumber::Number(v) => Ok(v),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(name: &str, root: u8, epoch: u64) -> CheckpointReport {
+        CheckpointReport {
+            name: name.to_string(),
+            endpoint: format!("https://{name}"),
+            root: Some(B256::repeat_byte(root)),
+            slot: Some(epoch * 32),
+            epoch: Some(epoch),
+            age: None,
+            latency: 0,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_majority_tie_is_stable() {
+        let reports = vec![
+            report("a", 2, 10),
+            report("b", 1, 10),
+            report("c", 2, 10),
+            report("d", 1, 10),
+            report("e", 3, 9),
+        ];
+
+        for _ in 0..16 {
+            let survey = CheckpointSurvey::new(reports.clone());
+            assert_eq!(survey.majority, Some(B256::repeat_byte(1)));
+            assert!(!survey.quorum);
+
+            let reversed = reports.iter().rev().cloned().collect();
+            assert_eq!(CheckpointSurvey::new(reversed).majority, survey.majority);
+        }
+    }
+}
@@ -1,9 +1,14 @@
-use std::net::IpAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::{collections::HashMap, path::PathBuf};
 use url::Url;
 
 use alloy::primitives::B256;
-use figment::{providers::Serialized, value::Value};
+use figment::{
+    providers::Serialized,
+    value::{Dict, Value},
+};
+use helios_core::config::ConfigDuration;
+use helios_core::execution::rpc::headers::HeaderConfig;
 use serde::{Deserialize, Serialize};
 
 /// Cli Config
@@ -14,10 +19,27 @@ pub struct CliConfig {
     pub checkpoint: Option<B256>,
     pub rpc_bind_ip: Option<IpAddr>,
     pub rpc_port: Option<u16>,
+    pub rpc_tls_cert: Option<PathBuf>,
+    pub rpc_tls_key: Option<PathBuf>,
+    pub ipc_path: Option<PathBuf>,
+    pub ipc_permissions: Option<u32>,
+    pub ipc_only: Option<bool>,
+    pub metrics_address: Option<SocketAddr>,
     pub data_dir: Option<PathBuf>,
     pub fallback: Option<String>,
     pub load_external_fallback: Option<bool>,
+    pub fallback_quorum: Option<usize>,
     pub strict_checkpoint_age: Option<bool>,
+    pub premerge_passthrough: Option<bool>,
+    pub confirmation_depth: Option<u64>,
+    pub execution_max_retries: Option<u32>,
+    pub execution_initial_backoff: Option<ConfigDuration>,
+    pub execution_max_backoff: Option<ConfigDuration>,
+    pub execution_compute_units: Option<u64>,
+    /// Only the `headers` of it are taken, added to those of the config.
+    pub execution_headers: HeaderConfig,
+    /// Replaces the configured broadcast rpcs unless empty.
+    pub broadcast_rpcs: Vec<Url>,
 }
 
 impl CliConfig {
@@ -44,6 +66,30 @@ impl CliConfig {
             user_dict.insert("rpc_port", Value::from(port));
         }
 
+        if let Some(cert) = self.rpc_tls_cert.as_ref() {
+            user_dict.insert("rpc_tls_cert", Value::from(cert.to_str().unwrap()));
+        }
+
+        if let Some(key) = self.rpc_tls_key.as_ref() {
+            user_dict.insert("rpc_tls_key", Value::from(key.to_str().unwrap()));
+        }
+
+        if let Some(path) = self.ipc_path.as_ref() {
+            user_dict.insert("ipc_path", Value::from(path.to_str().unwrap()));
+        }
+
+        if let Some(permissions) = self.ipc_permissions {
+            user_dict.insert("ipc_permissions", Value::from(permissions));
+        }
+
+        if let Some(ipc_only) = self.ipc_only {
+            user_dict.insert("ipc_only", Value::from(ipc_only));
+        }
+
+        if let Some(addr) = self.metrics_address {
+            user_dict.insert("metrics_address", Value::from(addr.to_string()));
+        }
+
         if let Some(data_dir) = self.data_dir.as_ref() {
             user_dict.insert("data_dir", Value::from(data_dir.to_str().unwrap()));
         }
@@ -56,10 +102,65 @@ impl CliConfig {
             user_dict.insert("load_external_fallback", Value::from(l));
         }
 
+        if let Some(quorum) = self.fallback_quorum {
+            user_dict.insert("fallback_quorum", Value::from(quorum));
+        }
+
         if let Some(s) = self.strict_checkpoint_age {
             user_dict.insert("strict_checkpoint_age", Value::from(s));
         }
 
+        if let Some(p) = self.premerge_passthrough {
+            user_dict.insert("premerge_passthrough", Value::from(p));
+        }
+
+        if let Some(depth) = self.confirmation_depth {
+            user_dict.insert("confirmation_depth", Value::from(depth));
+        }
+
+        // merged into the `execution_retry` table, keeping the fields not given
+        let mut retry = Dict::new();
+        if let Some(retries) = self.execution_max_retries {
+            retry.insert("max_retries".to_string(), Value::from(retries));
+        }
+        if let Some(backoff) = self.execution_initial_backoff {
+            retry.insert(
+                "initial_backoff".to_string(),
+                Value::from(backoff.to_string()),
+            );
+        }
+        if let Some(backoff) = self.execution_max_backoff {
+            retry.insert("max_backoff".to_string(), Value::from(backoff.to_string()));
+        }
+        if let Some(units) = self.execution_compute_units {
+            retry.insert("compute_units_per_second".to_string(), Value::from(units));
+        }
+        if !retry.is_empty() {
+            user_dict.insert("execution_retry", Value::from(retry));
+        }
+
+        // merged into the `execution_headers` table, overriding configured headers by name
+        if !self.execution_headers.headers.is_empty() {
+            let headers = self
+                .execution_headers
+                .headers
+                .iter()
+                .map(|(name, value)| (name.clone(), Value::from(value.clone())))
+                .collect::<Dict>();
+            let mut table = Dict::new();
+            table.insert("headers".to_string(), Value::from(headers));
+            user_dict.insert("execution_headers", Value::from(table));
+        }
+
+        if !self.broadcast_rpcs.is_empty() {
+            let rpcs = self
+                .broadcast_rpcs
+                .iter()
+                .map(|rpc| Value::from(rpc.to_string()))
+                .collect::<Vec<_>>();
+            user_dict.insert("broadcast_rpcs", Value::from(rpcs));
+        }
+
         Serialized::from(user_dict, network)
     }
 }
@@ -0,0 +1,35 @@
+use alloy::primitives::B256;
+use eyre::Report;
+use thiserror::Error;
+
+/// Why a client could not be built from its configuration. Each renders as a single line,
+/// fit to be shown on its own.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("missing {0}, it is neither set nor in the config")]
+    MissingField(&'static str),
+    #[error("invalid {field} {url:?}: {reason}")]
+    InvalidUrl {
+        field: &'static str,
+        url: String,
+        reason: String,
+    },
+    #[error("invalid checkpoint: {0}")]
+    InvalidCheckpoint(String),
+    #[error("no network preset has chain id {0}")]
+    UnknownChainId(u64),
+    #[error("the execution rpc serves chain id {got}, expected {expected}")]
+    ChainIdMismatch { expected: u64, got: u64 },
+    #[error("the consensus rpc serves genesis validators root {got}, expected {expected}")]
+    GenesisRootMismatch { expected: B256, got: B256 },
+    #[error("could not reach the {0}: {1}")]
+    Unreachable(&'static str, Report),
+    #[error("rpc_tls_cert and rpc_tls_key must be set together")]
+    IncompleteTls,
+    #[error("ipc_only is set without an ipc_path")]
+    IpcWithoutPath,
+    #[error("a database was supplied with with_database, so the client must be built as SharedDB, not {0}")]
+    DatabaseType(&'static str),
+    #[error("{0}")]
+    Setup(Report),
+}
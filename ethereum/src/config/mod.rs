@@ -1,12 +1,31 @@
-use std::net::{IpAddr, Ipv4Addr};
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::{path::PathBuf, process::exit};
 
-use alloy::primitives::B256;
+use alloy::primitives::{Address, B256};
 use figment::{
     providers::{Format, Serialized, Toml},
     Figment,
 };
+use helios_core::client::compression::CompressionConfig;
+use helios_core::client::trust::RpcPolicies;
+use helios_core::config::ConfigDuration;
+use helios_core::database::Database;
+use helios_core::execution::backfill::BackfillConfig;
+use helios_core::execution::code_cache::CodeCacheConfig;
+use helios_core::execution::config::ExecutionConfig;
+use helios_core::execution::evm::EvmConfig;
+use helios_core::execution::fee_history::{FeeHistoryConfig, GasPriceConfig};
+use helios_core::execution::historical::HistoricalStateConfig;
+use helios_core::execution::limits::PayloadLimits;
+use helios_core::execution::log_filter::LogVerificationConfig;
+use helios_core::execution::orphans::OrphanCacheConfig;
+use helios_core::execution::rpc::headers::HeaderConfig;
+use helios_core::execution::rpc::rate_limit::RateLimitConfig;
+use helios_core::execution::rpc::retry::RetryConfig;
+use helios_core::execution::rpc::timeout::HttpClientConfig;
+use helios_core::execution::usage::UsageConfig;
 use helios_core::fork_schedule::ForkSchedule;
 use serde::Deserialize;
 
@@ -19,9 +38,10 @@ use self::types::ChainConfig;
 
 pub mod checkpoints;
 pub mod cli;
+pub mod errors;
 pub mod networks;
 
-mod base;
+pub(crate) mod base;
 mod types;
 
 #[derive(Deserialize, Debug, Default)]
@@ -30,17 +50,119 @@ pub struct Config {
     pub execution_rpc: String,
     pub rpc_bind_ip: Option<IpAddr>,
     pub rpc_port: Option<u16>,
+    /// PEM certificate chain, serves the rpc over TLS together with `rpc_tls_key`.
+    pub rpc_tls_cert: Option<PathBuf>,
+    pub rpc_tls_key: Option<PathBuf>,
+    /// Unix domain socket, or named pipe on Windows, the rpc is also served on.
+    #[serde(default)]
+    pub ipc_path: Option<PathBuf>,
+    /// Unix mode of the socket file at `ipc_path`, 0600 unless set.
+    #[serde(default)]
+    pub ipc_permissions: Option<u32>,
+    /// Serves the rpc over `ipc_path` alone, without the http listener.
+    #[serde(default)]
+    pub ipc_only: bool,
+    /// Where prometheus metrics are served, when built with the `metrics` feature.
+    pub metrics_address: Option<SocketAddr>,
     pub default_checkpoint: B256,
     pub checkpoint: Option<B256>,
     pub data_dir: Option<PathBuf>,
     pub chain: ChainConfig,
     pub forks: Forks,
     pub execution_forks: ForkSchedule,
-    pub max_checkpoint_age: u64,
+    pub max_checkpoint_age: ConfigDuration,
+    /// How far ahead of the local clock an update may be signed before it is rejected as
+    /// coming from the future.
+    pub clock_skew_tolerance: ConfigDuration,
     pub fallback: Option<String>,
     pub load_external_fallback: bool,
+    /// Services of the community maintained list that must agree on the checkpoint loaded
+    /// with `load_external_fallback`.
+    pub fallback_quorum: usize,
     pub strict_checkpoint_age: bool,
+    /// Serve blocks and logs from before the merge unverified from the execution provider,
+    /// instead of rejecting them.
+    pub premerge_passthrough: bool,
+    /// Blocks under the latest that the `helios_confirmed` tag resolves to.
+    pub confirmation_depth: u64,
+    /// Verified sync committee periods kept in the database.
+    pub sync_committee_retention: u64,
+    /// The ENS registry names are resolved against, set for the networks ENS is deployed on.
+    pub ens_registry: Option<Address>,
+    /// Follows the `OffchainLookup` reverts of calls to the gateways they name, each given
+    /// `ccip_read_timeout` to answer.
+    pub ccip_read: bool,
+    pub ccip_read_timeout: ConfigDuration,
+    /// Verification policies of the rpc methods, from the `rpc` table.
+    #[serde(default)]
+    pub rpc: RpcPolicies,
+    /// Cost table and budgets of the provider usage accounting, from the `usage` table.
+    #[serde(default)]
+    pub usage: UsageConfig,
+    /// Orphaned blocks kept for lookups by hash, from the `orphan_cache` table.
+    #[serde(default)]
+    pub orphan_cache: OrphanCacheConfig,
+    /// Concurrency and retries of the startup backfill of recent blocks, from the `backfill`
+    /// table.
+    #[serde(default)]
+    pub backfill: BackfillConfig,
+    /// Limits and strictness of the `eth_getLogs` verification, from the `logs` table.
+    #[serde(default)]
+    pub logs: LogVerificationConfig,
+    /// Whether fee history ranges before the verified blocks are clamped or served partially
+    /// verified, from the `fee_history` table.
+    #[serde(default)]
+    pub fee_history: FeeHistoryConfig,
+    /// How many recent blocks, and which percentile of their rewards, suggested gas prices
+    /// are taken from, from the `gas_price` table.
+    #[serde(default)]
+    pub gas_price: GasPriceConfig,
+    /// Whether and how far back state of blocks before the verified ones is served, from the
+    /// `historical_state` table.
+    #[serde(default)]
+    pub historical_state: HistoricalStateConfig,
+    /// How often calls may fetch state their access list left out, from the `evm` table.
+    #[serde(default)]
+    pub evm: EvmConfig,
+    /// Upper bounds on the size of provider payloads, from the `payload_limits` table.
+    #[serde(default)]
+    pub payload_limits: PayloadLimits,
+    /// Directory and size limit of the contract code cache, from the `code_cache` table.
+    #[serde(default)]
+    pub code_cache: CodeCacheConfig,
+    /// Compression of rpc server responses and provider requests, from the `compression`
+    /// table.
+    #[serde(default)]
+    pub compression: CompressionConfig,
+    /// Retries of rate limited execution provider requests, from the `execution_retry` table.
+    #[serde(default)]
+    pub execution_retry: RetryConfig,
+    /// The budget execution provider requests are held to, from the `execution_rate_limit`
+    /// table.
+    #[serde(default)]
+    pub execution_rate_limit: RateLimitConfig,
+    /// Timeouts and connection pooling of execution provider requests, from the
+    /// `execution_http` table.
+    #[serde(default)]
+    pub execution_http: HttpClientConfig,
+    /// Headers and credentials sent with execution provider requests, from the
+    /// `execution_headers` table.
+    #[serde(default)]
+    pub execution_headers: HeaderConfig,
+    /// More execution rpcs raw transactions are also submitted to, in case the provider
+    /// drops them.
+    #[serde(default)]
+    pub broadcast_rpcs: Vec<String>,
+    /// Sends raw transactions as they are, without first checking their chain id, nonce and
+    /// cost against the verified state of the sender.
+    #[serde(default)]
+    pub skip_transaction_checks: bool,
     pub database_type: Option<String>,
+    /// A database supplied with
+    /// [`with_database`](crate::builder::EthereumClientBuilder::with_database), which
+    /// [`SharedDB`](crate::database::SharedDB) clients keep everything in.
+    #[serde(skip)]
+    pub database: Option<Arc<dyn Database>>,
 }
 
 impl Config {
@@ -71,6 +193,11 @@ impl Config {
                         println!("\talternatively, you can add the field to your helios.toml file");
                         println!("\nfor more information, check the github README");
                     }
+                    figment::error::Kind::Message(msg) if !err.path.is_empty() => {
+                        let field = err.path.join(".").replace('_', "-");
+                        println!("\x1b[91merror\x1b[0m: invalid configuration field: {field}");
+                        println!("\n\t{msg}");
+                    }
                     _ => println!("cannot parse configuration: {err}"),
                 }
                 exit(1);
@@ -88,9 +215,41 @@ impl Config {
             forks: self.forks.clone(),
             execution_forks: self.execution_forks,
             max_checkpoint_age: self.max_checkpoint_age,
+            clock_skew_tolerance: self.clock_skew_tolerance,
             data_dir: self.data_dir.clone(),
             load_external_fallback: self.load_external_fallback,
+            fallback_quorum: self.fallback_quorum,
             strict_checkpoint_age: self.strict_checkpoint_age,
+            premerge_passthrough: self.premerge_passthrough,
+            confirmation_depth: self.confirmation_depth,
+            sync_committee_retention: self.sync_committee_retention,
+            ens_registry: self.ens_registry,
+            ccip_read: self.ccip_read,
+            ccip_read_timeout: self.ccip_read_timeout,
+        }
+    }
+
+    /// The settings of the execution client, which the node is built with.
+    pub fn execution_config(&self) -> ExecutionConfig {
+        ExecutionConfig {
+            premerge_passthrough: self.premerge_passthrough,
+            confirmation_depth: self.confirmation_depth,
+            backfill: self.backfill,
+            orphan_cache: self.orphan_cache,
+            logs: self.logs,
+            fee_history: self.fee_history,
+            gas_price: self.gas_price,
+            historical_state: self.historical_state,
+            evm: self.evm,
+            payload_limits: self.payload_limits,
+            broadcast_rpcs: self.broadcast_rpcs.clone(),
+            broadcast_rate_limit: self.execution_rate_limit.clone(),
+            skip_transaction_checks: self.skip_transaction_checks,
+            ccip_read: self.ccip_read,
+            ccip_read_timeout: self.ccip_read_timeout.as_duration(),
+            code_cache: self.code_cache.clone(),
+            database: self.database.clone(),
+            header_store: None,
         }
     }
 }
@@ -100,6 +259,12 @@ impl From<BaseConfig> for Config {
         Config {
             rpc_bind_ip: Some(base.rpc_bind_ip),
             rpc_port: Some(base.rpc_port),
+            rpc_tls_cert: None,
+            rpc_tls_key: None,
+            ipc_path: None,
+            ipc_permissions: None,
+            ipc_only: false,
+            metrics_address: None,
             consensus_rpc: base.consensus_rpc.unwrap_or_default(),
             execution_rpc: String::new(),
             checkpoint: None,
@@ -108,11 +273,38 @@ impl From<BaseConfig> for Config {
             forks: base.forks,
             execution_forks: base.execution_forks,
             max_checkpoint_age: base.max_checkpoint_age,
+            clock_skew_tolerance: base.clock_skew_tolerance,
             data_dir: base.data_dir,
             fallback: None,
             load_external_fallback: base.load_external_fallback,
+            fallback_quorum: base.fallback_quorum,
             strict_checkpoint_age: base.strict_checkpoint_age,
+            premerge_passthrough: base.premerge_passthrough,
+            confirmation_depth: base.confirmation_depth,
+            sync_committee_retention: base.sync_committee_retention,
+            ens_registry: base.ens_registry,
+            ccip_read: base.ccip_read,
+            ccip_read_timeout: base.ccip_read_timeout,
+            rpc: RpcPolicies::default(),
+            usage: UsageConfig::default(),
+            orphan_cache: OrphanCacheConfig::default(),
+            backfill: BackfillConfig::default(),
+            logs: LogVerificationConfig::default(),
+            fee_history: FeeHistoryConfig::default(),
+            gas_price: GasPriceConfig::default(),
+            historical_state: HistoricalStateConfig::default(),
+            evm: EvmConfig::default(),
+            payload_limits: PayloadLimits::default(),
+            code_cache: CodeCacheConfig::default(),
+            compression: CompressionConfig::default(),
+            execution_retry: RetryConfig::default(),
+            execution_rate_limit: RateLimitConfig::default(),
+            execution_http: HttpClientConfig::default(),
+            execution_headers: HeaderConfig::default(),
+            broadcast_rpcs: Vec::new(),
+            skip_transaction_checks: false,
             database_type: None,
+            database: None,
         }
     }
 }
@@ -3,19 +3,24 @@ use std::fmt::Display;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use alloy::primitives::{b256, fixed_bytes};
+use alloy::primitives::{address, b256, fixed_bytes, Address, B256};
 #[cfg(not(target_arch = "wasm32"))]
 use dirs::home_dir;
 use eyre::Result;
+use helios_core::config::ConfigDuration;
 use helios_core::fork_schedule::ForkSchedule;
 use serde::{Deserialize, Serialize};
-use strum::EnumIter;
+use strum::{EnumIter, IntoEnumIterator};
 
 use helios_consensus_core::types::{Fork, Forks};
 
 use crate::config::base::BaseConfig;
+use crate::config::errors::ConfigError;
 use crate::config::types::ChainConfig;
 
+/// The ENS registry, deployed at the same address on mainnet and the testnets ENS supports.
+const ENS_REGISTRY: Address = address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
 #[derive(
     Debug, Clone, Copy, Serialize, Deserialize, EnumIter, Hash, Eq, PartialEq, PartialOrd, Ord,
 )]
@@ -23,6 +28,7 @@ pub enum Network {
     Mainnet,
     Sepolia,
     Holesky,
+    Hoodi,
     PectraDevnet,
 }
 
@@ -30,13 +36,14 @@ impl FromStr for Network {
     type Err = eyre::Report;
 
     fn from_str(s: &str) -> Result<Self> {
-        match s {
-            "mainnet" => Ok(Self::Mainnet),
-            "sepolia" => Ok(Self::Sepolia),
-            "holesky" => Ok(Self::Holesky),
-            "pectra-devnet" => Ok(Self::PectraDevnet),
-            _ => Err(eyre::eyre!("network not recognized")),
-        }
+        Self::iter()
+            .find(|network| network.to_string() == s)
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "network {s:?} not recognized, expected one of: {}",
+                    Self::names().join(", ")
+                )
+            })
     }
 }
 
@@ -46,6 +53,7 @@ impl Display for Network {
             Self::Mainnet => "mainnet",
             Self::Sepolia => "sepolia",
             Self::Holesky => "holesky",
+            Self::Hoodi => "hoodi",
             Self::PectraDevnet => "pectra-devnet",
         };
 
@@ -59,17 +67,22 @@ impl Network {
             Self::Mainnet => mainnet(),
             Self::Sepolia => sepolia(),
             Self::Holesky => holesky(),
+            Self::Hoodi => hoodi(),
             Self::PectraDevnet => pectra_devnet(),
         }
     }
 
-    pub fn from_chain_id(id: u64) -> Result<Self> {
-        match id {
-            1 => Ok(Network::Mainnet),
-            11155111 => Ok(Network::Sepolia),
-            17000 => Ok(Network::Holesky),
-            _ => Err(eyre::eyre!("chain id not known")),
-        }
+    /// Names of the built in presets, as accepted by [Network::from_str].
+    pub fn names() -> Vec<String> {
+        Self::iter().map(|network| network.to_string()).collect()
+    }
+
+    /// The preset of the chain with id `id`, for embedders that only know the chain by its
+    /// number.
+    pub fn from_chain_id(id: u64) -> Result<Self, ConfigError> {
+        Self::iter()
+            .find(|network| network.to_base_config().chain.chain_id == id)
+            .ok_or(ConfigError::UnknownChainId(id))
     }
 }
 
@@ -113,10 +126,12 @@ pub fn mainnet() -> BaseConfig {
         },
         execution_forks: ForkSchedule {
             prague_timestamp: u64::MAX,
+            merge_block: 15537394,
         },
-        max_checkpoint_age: 1_209_600, // 14 days
+        max_checkpoint_age: ConfigDuration::from_secs(1_209_600), // 14 days
         #[cfg(not(target_arch = "wasm32"))]
         data_dir: Some(data_dir(Network::Mainnet)),
+        ens_registry: Some(ENS_REGISTRY),
         ..std::default::Default::default()
     }
 }
@@ -161,10 +176,12 @@ pub fn sepolia() -> BaseConfig {
         },
         execution_forks: ForkSchedule {
             prague_timestamp: 1741159776,
+            merge_block: 1450409,
         },
-        max_checkpoint_age: 1_209_600, // 14 days
+        max_checkpoint_age: ConfigDuration::from_secs(1_209_600), // 14 days
         #[cfg(not(target_arch = "wasm32"))]
         data_dir: Some(data_dir(Network::Sepolia)),
+        ens_registry: Some(ENS_REGISTRY),
         ..std::default::Default::default()
     }
 }
@@ -209,10 +226,61 @@ pub fn holesky() -> BaseConfig {
         },
         execution_forks: ForkSchedule {
             prague_timestamp: 1740434112,
+            merge_block: 0,
         },
-        max_checkpoint_age: 1_209_600, // 14 days
+        max_checkpoint_age: ConfigDuration::from_secs(1_209_600), // 14 days
         #[cfg(not(target_arch = "wasm32"))]
         data_dir: Some(data_dir(Network::Holesky)),
+        ens_registry: Some(ENS_REGISTRY),
+        ..std::default::Default::default()
+    }
+}
+
+pub fn hoodi() -> BaseConfig {
+    BaseConfig {
+        // no checkpoint is pinned yet, so bootstrap from the fallback services
+        default_checkpoint: B256::ZERO,
+        load_external_fallback: true,
+        rpc_port: 8545,
+        consensus_rpc: None,
+        chain: ChainConfig {
+            chain_id: 560048,
+            genesis_time: 1742213400,
+            genesis_root: b256!("212f13fc4df078b6cb7db228f1c8307566dcecf900867401a92023d7ba99cb5f"),
+        },
+        forks: Forks {
+            genesis: Fork {
+                epoch: 0,
+                fork_version: fixed_bytes!("10000910"),
+            },
+            altair: Fork {
+                epoch: 0,
+                fork_version: fixed_bytes!("20000910"),
+            },
+            bellatrix: Fork {
+                epoch: 0,
+                fork_version: fixed_bytes!("30000910"),
+            },
+            capella: Fork {
+                epoch: 0,
+                fork_version: fixed_bytes!("40000910"),
+            },
+            deneb: Fork {
+                epoch: 0,
+                fork_version: fixed_bytes!("50000910"),
+            },
+            electra: Fork {
+                epoch: 2048,
+                fork_version: fixed_bytes!("60000910"),
+            },
+        },
+        execution_forks: ForkSchedule {
+            prague_timestamp: 1742999832,
+            merge_block: 0,
+        },
+        max_checkpoint_age: ConfigDuration::from_secs(1_209_600), // 14 days
+        #[cfg(not(target_arch = "wasm32"))]
+        data_dir: Some(data_dir(Network::Hoodi)),
         ..std::default::Default::default()
     }
 }
@@ -257,8 +325,9 @@ pub fn pectra_devnet() -> BaseConfig {
         },
         execution_forks: ForkSchedule {
             prague_timestamp: 1738607700,
+            merge_block: 0,
         },
-        max_checkpoint_age: 1_209_600, // 14 days
+        max_checkpoint_age: ConfigDuration::from_secs(1_209_600), // 14 days
         #[cfg(not(target_arch = "wasm32"))]
         data_dir: Some(data_dir(Network::PectraDevnet)),
         ..std::default::Default::default()
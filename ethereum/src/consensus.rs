@@ -1,11 +1,11 @@
 use std::marker::PhantomData;
 use std::process;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use alloy::consensus::proofs::{calculate_transaction_root, calculate_withdrawals_root};
 use alloy::consensus::{Header as ConsensusHeader, Transaction as TxTrait, TxEnvelope};
 use alloy::eips::eip4895::{Withdrawal, Withdrawals};
-use alloy::primitives::{b256, fixed_bytes, Bloom, BloomInput, B256, U256};
+use alloy::primitives::{b256, fixed_bytes, Address, Bloom, BloomInput, B256, U256};
 use alloy::rlp::Decodable;
 use alloy::rpc::types::{Block, BlockTransactions, Header, Transaction};
 use chrono::Duration;
@@ -21,30 +21,55 @@ use tokio::sync::mpsc::Sender;
 use tokio::sync::watch;
 
 use helios_consensus_core::{
-    apply_bootstrap, apply_finality_update, apply_update, calc_sync_period,
+    apply_bootstrap, apply_finality_update, apply_update,
     consensus_spec::ConsensusSpec,
     errors::ConsensusError,
-    expected_current_slot, get_bits,
-    types::{ExecutionPayload, FinalityUpdate, LightClientStore, Update},
+    execution_block_hash_proof, get_bits,
+    types::{
+        BeaconBlockHeader, ExecutionPayload, FinalityUpdate, GenericUpdate, LightClientHeader,
+        LightClientStore, Period, Slot, SyncAggregate, SyncCommittee, Update,
+    },
     verify_bootstrap, verify_finality_update, verify_update,
 };
+#[cfg(not(target_arch = "wasm32"))]
+use helios_core::client::supervisor::Supervisor;
 use helios_core::consensus::Consensus;
-use helios_core::time::{interval_at, Instant, SystemTime, UNIX_EPOCH};
+use helios_core::time::{interval_at, Instant};
+use helios_core::types::{
+    redact_url, BeaconAttestation, BeaconBlockMapping, BeaconHeader, CheckpointInfo,
+    CheckpointSource, DbStats, FinalityProof, FinalityTiming, ForkInfo, NetworkInfo,
+    SyncAggregateInfo, SyncCommitteeInfo, SyncCommitteeProof, SyncCommitteeSource,
+};
 
-use crate::config::checkpoints::CheckpointFallback;
+use crate::clock::ConsensusClock;
+use crate::config::checkpoints::{CheckpointFallback, CheckpointFallbackService};
 use crate::config::networks::Network;
 use crate::config::Config;
-use crate::constants::MAX_REQUEST_LIGHT_CLIENT_UPDATES;
-use crate::database::Database;
+use crate::constants::{
+    BEACON_MAPPING_BACKFILL_SLOTS, CURRENT_SYNC_COMMITTEE_INDEX, FINALIZED_ROOT_INDEX, GC_INTERVAL,
+    MAX_REQUEST_LIGHT_CLIENT_UPDATES, NEXT_SYNC_COMMITTEE_INDEX,
+};
+use crate::database::gc::{configured_namespaces, GarbageCollector, CHECKPOINTS};
+use crate::database::sync_committees::{DbSyncCommitteeStore, SyncCommitteeStore};
+use crate::database::{Checkpoint, Database, OpenDatabase};
+use crate::finality::{FinalityTracker, FinalityWindow};
 use crate::rpc::ConsensusRpc;
 
-pub struct ConsensusClient<S: ConsensusSpec, R: ConsensusRpc<S>, DB: Database> {
+pub struct ConsensusClient<S: ConsensusSpec, R: ConsensusRpc<S>, DB: OpenDatabase> {
     pub block_recv: Option<Receiver<Block<Transaction>>>,
     pub finalized_block_recv: Option<watch::Receiver<Option<Block<Transaction>>>>,
-    pub checkpoint_recv: watch::Receiver<Option<B256>>,
+    pub beacon_mapping_recv: Option<Receiver<BeaconBlockMapping>>,
+    pub checkpoint_recv: watch::Receiver<Option<Checkpoint>>,
+    head_recv: watch::Receiver<Option<Block<Transaction>>>,
     shutdown_send: watch::Sender<bool>,
+    db: Arc<DB>,
     genesis_time: u64,
     config: Arc<Config>,
+    bootstrap_checkpoint: Arc<Mutex<(B256, CheckpointSource)>>,
+    gc: Arc<GarbageCollector<DB>>,
+    sync_committees: Arc<DbSyncCommitteeStore<S, DB>>,
+    clock: Arc<ConsensusClock>,
+    finality: Arc<FinalityTracker>,
     phantom: PhantomData<(S, R, DB)>,
 }
 
@@ -52,15 +77,26 @@ pub struct ConsensusClient<S: ConsensusSpec, R: ConsensusRpc<S>, DB: Database> {
 pub struct Inner<S: ConsensusSpec, R: ConsensusRpc<S>> {
     pub rpc: R,
     pub store: LightClientStore<S>,
-    last_checkpoint: Option<B256>,
+    last_checkpoint: Option<Checkpoint>,
+    /// The signature the optimistic header was accepted on.
+    head_signature: Option<SyncAggregateInfo>,
+    /// The finality branch the finalized header was accepted on, with the signature over its
+    /// attested header. Neither is known for a header bootstrapped from a checkpoint.
+    finality_proof: Option<(FinalityProof, SyncAggregateInfo)>,
     block_send: Sender<Block<Transaction>>,
     finalized_block_send: watch::Sender<Option<Block<Transaction>>>,
-    checkpoint_send: watch::Sender<Option<B256>>,
+    beacon_mapping_send: Sender<BeaconBlockMapping>,
+    checkpoint_send: watch::Sender<Option<Checkpoint>>,
+    head_send: watch::Sender<Option<Block<Transaction>>>,
     pub config: Arc<Config>,
+    clock: Arc<ConsensusClock>,
+    finality: Arc<FinalityTracker>,
+    sync_committees: Option<Arc<dyn SyncCommitteeStore>>,
+    fallback_services: Option<Vec<CheckpointFallbackService>>,
     phantom: PhantomData<S>,
 }
 
-impl<S: ConsensusSpec, R: ConsensusRpc<S>, DB: Database> Consensus<Block>
+impl<S: ConsensusSpec, R: ConsensusRpc<S>, DB: OpenDatabase> Consensus<Block>
     for ConsensusClient<S, R, DB>
 {
     fn block_recv(&mut self) -> Option<Receiver<Block<Transaction>>> {
@@ -71,34 +107,185 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>, DB: Database> Consensus<Block>
         self.finalized_block_recv.take()
     }
 
+    fn beacon_mapping_recv(&mut self) -> Option<Receiver<BeaconBlockMapping>> {
+        self.beacon_mapping_recv.take()
+    }
+
+    fn head(&self) -> Option<Block<Transaction>> {
+        self.head_recv.borrow().clone()
+    }
+
     fn expected_highest_block(&self) -> u64 {
         u64::MAX
     }
 
+    fn ens_registry(&self) -> Option<Address> {
+        self.config.ens_registry
+    }
+
     fn chain_id(&self) -> u64 {
         self.config.chain.chain_id
     }
 
+    fn network_info(&self) -> NetworkInfo {
+        let forks = fork_infos::<S>(&self.config);
+        let current_epoch = self.expected_current_slot().epoch::<S>().as_u64();
+        let current_fork = forks
+            .iter()
+            .rev()
+            .find(|fork| fork.epoch.is_some_and(|epoch| epoch <= current_epoch))
+            .map(|fork| fork.name.clone());
+
+        let (initial, source) = *self.bootstrap_checkpoint.lock().unwrap();
+        let checkpoint = CheckpointInfo {
+            initial,
+            source,
+            latest: self
+                .checkpoint_recv
+                .borrow()
+                .map(|checkpoint| checkpoint.root),
+        };
+
+        NetworkInfo {
+            chain_id: self.config.chain.chain_id,
+            network: Network::from_chain_id(self.config.chain.chain_id)
+                .ok()
+                .map(|network| network.to_string()),
+            genesis_time: Some(self.config.chain.genesis_time),
+            genesis_validators_root: Some(self.config.chain.genesis_root),
+            forks,
+            current_fork,
+            weak_subjectivity_period: Some(self.config.max_checkpoint_age.as_secs()),
+            checkpoint: Some(checkpoint),
+            consensus_rpc: Some(redact_url(&self.config.consensus_rpc)),
+            execution_rpc: Some(redact_url(&self.config.execution_rpc)),
+            l1: None,
+        }
+    }
+
+    fn db_stats(&self) -> Result<Option<DbStats>> {
+        self.gc.stats().map(Some)
+    }
+
+    fn sync_committee(&self, period: u64) -> Result<Option<SyncCommitteeInfo>> {
+        self.sync_committees.load(period)
+    }
+
+    fn sync_committee_periods(&self) -> Result<Vec<u64>> {
+        self.sync_committees.periods()
+    }
+
+    fn clock_drift(&self) -> Option<i64> {
+        self.clock.drift()
+    }
+
+    fn build_features(&self) -> Vec<String> {
+        let mut features = Vec::new();
+        if cfg!(feature = "metrics") {
+            features.push("helios-ethereum/metrics".to_string());
+        }
+        features
+    }
+
+    fn finality_timing(&self, timestamp: u64) -> Option<FinalityTiming> {
+        // the slot the block was produced in, and the current one by the uncorrected clock
+        let slot_time = S::seconds_per_slot();
+        let slot = Slot::new(timestamp.saturating_sub(self.genesis_time) / slot_time);
+        let timing = match self
+            .finality
+            .estimate::<S>(slot, self.expected_current_slot())
+        {
+            FinalityWindow::Finalized => FinalityTiming::finalized(),
+            FinalityWindow::Stalled => FinalityTiming {
+                stalled: true,
+                ..Default::default()
+            },
+            FinalityWindow::Pending {
+                earliest,
+                expected,
+                latest,
+            } => {
+                let at = |slot: Slot| Some(slot.as_u64() * slot_time + self.genesis_time);
+                FinalityTiming {
+                    expected_at: at(expected),
+                    earliest: at(earliest),
+                    latest: at(latest),
+                    ..Default::default()
+                }
+            }
+        };
+
+        Some(timing)
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn supervise(&self, supervisor: &Supervisor) {
+        let gc = self.gc.clone();
+        let shutdown_recv = self.shutdown_send.subscribe();
+        supervisor.supervise_service("gc", move || {
+            let collect = gc.clone().run(GC_INTERVAL, shutdown_recv.clone());
+            async move {
+                collect.await;
+                Ok(())
+            }
+        });
+    }
+
     fn shutdown(&self) -> Result<()> {
         self.shutdown_send.send(true)?;
+
+        // the checkpoint saver may not have caught up with the latest checkpoint yet
+        if let Some(checkpoint) = *self.checkpoint_recv.borrow() {
+            self.db.save_checkpoint_with_slot(checkpoint)?;
+        }
+
+        Ok(())
+    }
+
+    fn restart(&self) -> Result<()> {
+        self.shutdown_send.send(false)?;
         Ok(())
     }
 }
 
-impl<S: ConsensusSpec, R: ConsensusRpc<S>, DB: Database> ConsensusClient<S, R, DB> {
+impl<S: ConsensusSpec, R: ConsensusRpc<S>, DB: OpenDatabase> ConsensusClient<S, R, DB> {
     pub fn new(rpc: &str, config: Arc<Config>) -> Result<ConsensusClient<S, R, DB>> {
+        Self::with_clock(rpc, config, ConsensusClock::system())
+    }
+
+    /// Creates a client that keeps time with `clock` instead of the system clock.
+    pub fn with_clock(
+        rpc: &str,
+        config: Arc<Config>,
+        clock: ConsensusClock,
+    ) -> Result<ConsensusClient<S, R, DB>> {
+        let clock = Arc::new(clock);
+        let clock_ref = clock.clone();
+        let finality = Arc::new(FinalityTracker::new());
+        let finality_ref = finality.clone();
         let (block_send, block_recv) = channel(256);
         let (finalized_block_send, finalized_block_recv) = watch::channel(None);
+        let (beacon_mapping_send, beacon_mapping_recv) = channel(256);
         let (checkpoint_send, checkpoint_recv) = watch::channel(None);
+        let (head_send, head_recv) = watch::channel(None);
         let (shutdown_send, shutdown_recv) = watch::channel(false);
 
         let config_clone = config.clone();
         let rpc = rpc.to_string();
         let genesis_time = config.chain.genesis_time;
         let db = Arc::new(DB::new(&config)?);
-        let initial_checkpoint = config
-            .checkpoint
-            .unwrap_or_else(|| db.load_checkpoint().unwrap_or(config.default_checkpoint));
+        let (initial_checkpoint, checkpoint_source) = match config.checkpoint {
+            Some(checkpoint) => (checkpoint, CheckpointSource::User),
+            None => match db.load_checkpoint() {
+                Ok(checkpoint) => (checkpoint, CheckpointSource::Persisted),
+                Err(_) => (config.default_checkpoint, CheckpointSource::Configured),
+            },
+        };
+        let bootstrap_checkpoint = Arc::new(Mutex::new((initial_checkpoint, checkpoint_source)));
+        let bootstrap_checkpoint_ref = bootstrap_checkpoint.clone();
+        let sync_committees = Arc::new(DbSyncCommitteeStore::<S, DB>::new((*db).clone()));
+        let sync_committees_ref = sync_committees.clone();
+        let db_ref = db.clone();
 
         #[cfg(not(target_arch = "wasm32"))]
         let run = tokio::spawn;
@@ -113,42 +300,63 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>, DB: Database> ConsensusClient<S, R, D
                 &rpc,
                 block_send,
                 finalized_block_send,
+                beacon_mapping_send,
                 checkpoint_send,
                 config.clone(),
-            );
-
-            let res = inner.sync(initial_checkpoint).await;
-            if let Err(err) = res {
-                if config.load_external_fallback {
-                    let res = sync_all_fallbacks(&mut inner, config.chain.chain_id).await;
-                    if let Err(err) = res {
-                        error!(target: "helios::consensus", err = %err, "sync failed");
-                        process::exit(1);
-                    }
-                } else if let Some(fallback) = &config.fallback {
-                    let res = sync_fallback(&mut inner, fallback).await;
-                    if let Err(err) = res {
-                        error!(target: "helios::consensus", err = %err, "sync failed");
-                        process::exit(1);
-                    }
-                } else {
+            )
+            .with_clock(clock_ref)
+            .with_finality(finality_ref)
+            .with_head_send(head_send)
+            .with_sync_committees(sync_committees_ref);
+
+            let stored = db_ref.load_checkpoint_with_slot().unwrap_or_else(|err| {
+                warn!(target: "helios::consensus", err = %err, "failed to load stored checkpoint");
+                None
+            });
+            let (checkpoint, source, slot) = inner
+                .select_checkpoint((initial_checkpoint, checkpoint_source), stored)
+                .await;
+            *bootstrap_checkpoint_ref.lock().unwrap() = (checkpoint, source);
+
+            match inner.sync_or_fallback(checkpoint, slot).await {
+                Ok(Some(fallback)) => {
+                    *bootstrap_checkpoint_ref.lock().unwrap() =
+                        (fallback, CheckpointSource::Fallback);
+                }
+                Ok(None) => {}
+                Err(err) => {
                     error!(target: "helios::consensus", err = %err, "sync failed");
                     process::exit(1);
                 }
             }
 
             _ = inner.send_blocks().await;
+            inner.send_backfilled_mappings().await;
 
-            let start = Instant::now() + inner.duration_until_next_update().to_std().unwrap();
-            let mut interval = interval_at(start, std::time::Duration::from_secs(12));
+            let next_update = |inner: &Inner<S, R>| {
+                Instant::now()
+                    + inner
+                        .duration_until_next_update()
+                        .to_std()
+                        .unwrap_or_default()
+            };
+            let mut interval = interval_at(next_update(&inner), std::time::Duration::from_secs(12));
 
             loop {
                 tokio::select! {
-                    _ = shutdown_rx.changed() => {
-                        if *shutdown_rx.borrow() {
-                            info!(target: "helios::consensus", "shutting down consensus client");
+                    changed = shutdown_rx.changed() => {
+                        if changed.is_err() {
                             break;
                         }
+                        if *shutdown_rx.borrow_and_update() {
+                            info!(target: "helios::consensus", "shutting down consensus client");
+                            // paused until restarted, or for good once the client is dropped
+                            if shutdown_rx.wait_for(|shutdown| !shutdown).await.is_err() {
+                                break;
+                            }
+                            info!(target: "helios::consensus", "restarting consensus client");
+                            interval = interval_at(next_update(&inner), std::time::Duration::from_secs(12));
+                        }
                     }
                     _ = interval.tick() => {
                         let res = inner.advance().await;
@@ -162,41 +370,52 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>, DB: Database> ConsensusClient<S, R, D
                             warn!(target: "helios::consensus", "send error: {}", err);
                             continue;
                         }
+
+                        // follow the drift corrected clock as the estimate settles
+                        interval = interval_at(next_update(&inner), std::time::Duration::from_secs(12));
                     }
                 }
             }
         });
 
-        save_new_checkpoints(
-            checkpoint_recv.clone(),
+        let gc = Arc::new(GarbageCollector::new(
             db.clone(),
-            initial_checkpoint,
-            shutdown_recv,
-        );
+            &configured_namespaces(&config_clone),
+        ));
+        // on native targets the node supervises collection, see `Consensus::supervise`
+        #[cfg(target_arch = "wasm32")]
+        gc.clone().spawn(GC_INTERVAL, shutdown_recv.clone());
+
+        save_new_checkpoints(checkpoint_recv.clone(), db.clone(), initial_checkpoint);
 
         Ok(ConsensusClient {
             block_recv: Some(block_recv),
             finalized_block_recv: Some(finalized_block_recv),
+            beacon_mapping_recv: Some(beacon_mapping_recv),
             checkpoint_recv,
+            head_recv,
             shutdown_send,
+            db,
             genesis_time,
             config: config_clone,
+            bootstrap_checkpoint,
+            gc,
+            sync_committees,
+            clock,
+            finality,
             phantom: PhantomData,
         })
     }
 
-    pub fn expected_current_slot(&self) -> u64 {
-        let now = SystemTime::now();
-
-        expected_current_slot(now, self.genesis_time)
+    pub fn expected_current_slot(&self) -> Slot {
+        slot_at(self.clock.now(), self.genesis_time)
     }
 }
 
 fn save_new_checkpoints<DB: Database>(
-    mut checkpoint_recv: watch::Receiver<Option<B256>>,
+    mut checkpoint_recv: watch::Receiver<Option<Checkpoint>>,
     db: Arc<DB>,
     initial_checkpoint: B256,
-    mut shutdown_recv: watch::Receiver<bool>,
 ) {
     #[cfg(not(target_arch = "wasm32"))]
     let run = tokio::spawn;
@@ -206,27 +425,22 @@ fn save_new_checkpoints<DB: Database>(
 
     run(async move {
         let mut last_saved_checkpoint = initial_checkpoint;
-        loop {
-            tokio::select! {
-                _ = shutdown_recv.changed() => {
-                    if *shutdown_recv.borrow() {
-                        break;
-                    }
-                }
-                checkpoint_result = checkpoint_recv.changed() => {
-                    if checkpoint_result.is_err() {
-                        break;
-                    }
-                    let new_checkpoint = *checkpoint_recv.borrow_and_update();
-                    if let Some(new_checkpoint) = new_checkpoint.as_ref() {
-                        if *new_checkpoint != last_saved_checkpoint {
-                            if db.save_checkpoint(*new_checkpoint).is_err() {
-                                warn!(target: "helios::consensus", "failed to save checkpoint");
-                            } else {
-                                info!(target: "helios::consensus", "saved checkpoint to DB: 0x{}", hex::encode(*new_checkpoint));
-                                last_saved_checkpoint = *new_checkpoint;
-                            }
+        // runs until the sync task drops the sender, so checkpoints verified while shutting
+        // down are still saved
+        while checkpoint_recv.changed().await.is_ok() {
+            let new_checkpoint = *checkpoint_recv.borrow_and_update();
+            if let Some(new_checkpoint) = new_checkpoint {
+                if new_checkpoint.root != last_saved_checkpoint {
+                    if db.save_checkpoint_with_slot(new_checkpoint).is_err() {
+                        warn!(target: "helios::consensus", "failed to save checkpoint");
+                    } else {
+                        info!(target: "helios::consensus", slot = new_checkpoint.slot, "saved checkpoint to DB: 0x{}", hex::encode(new_checkpoint.root));
+                        if let Err(err) =
+                            record_checkpoint(&*db, new_checkpoint.root, last_saved_checkpoint)
+                        {
+                            warn!(target: "helios::consensus", err = %err, "failed to record checkpoint history");
                         }
+                        last_saved_checkpoint = new_checkpoint.root;
                     }
                 }
             }
@@ -234,26 +448,61 @@ fn save_new_checkpoints<DB: Database>(
     });
 }
 
+/// Adds `checkpoint` to the checkpoint history, moving the pin over from `previous`.
+fn record_checkpoint<DB: Database>(db: &DB, checkpoint: B256, previous: B256) -> Result<()> {
+    db.put(CHECKPOINTS.name, checkpoint.as_slice(), &[])?;
+    db.set_pinned(CHECKPOINTS.name, checkpoint.as_slice(), true)?;
+    db.set_pinned(CHECKPOINTS.name, previous.as_slice(), false)
+}
+
 async fn sync_fallback<S: ConsensusSpec, R: ConsensusRpc<S>>(
     inner: &mut Inner<S, R>,
     fallback: &str,
-) -> Result<()> {
+) -> Result<B256> {
     let checkpoint = CheckpointFallback::fetch_checkpoint_from_api(fallback).await?;
-    inner.sync(checkpoint).await
+    inner.sync(checkpoint).await?;
+    Ok(checkpoint)
 }
 
-async fn sync_all_fallbacks<S: ConsensusSpec, R: ConsensusRpc<S>>(
-    inner: &mut Inner<S, R>,
-    chain_id: u64,
-) -> Result<()> {
-    let network = Network::from_chain_id(chain_id)?;
-    let checkpoint = CheckpointFallback::new()
-        .build()
-        .await?
-        .fetch_latest_checkpoint(&network)
-        .await?;
-
-    inner.sync(checkpoint).await
+fn fork_infos<S: ConsensusSpec>(config: &Config) -> Vec<ForkInfo> {
+    let forks = &config.forks;
+    let epoch_timestamp = |epoch: u64| {
+        epoch
+            .checked_mul(S::slots_per_epoch() * S::seconds_per_slot())
+            .and_then(|secs| secs.checked_add(config.chain.genesis_time))
+    };
+
+    let mut infos = [
+        ("genesis", &forks.genesis),
+        ("altair", &forks.altair),
+        ("bellatrix", &forks.bellatrix),
+        ("capella", &forks.capella),
+        ("deneb", &forks.deneb),
+        ("electra", &forks.electra),
+    ]
+    .into_iter()
+    .map(|(name, fork)| {
+        // forks that are not yet scheduled use an epoch of u64::MAX
+        let epoch = (fork.epoch != u64::MAX).then_some(fork.epoch);
+        ForkInfo {
+            name: name.to_string(),
+            epoch,
+            timestamp: epoch.and_then(epoch_timestamp),
+            fork_version: Some(fork.fork_version),
+        }
+    })
+    .collect::<Vec<_>>();
+
+    if config.execution_forks.prague_timestamp != u64::MAX {
+        infos.push(ForkInfo {
+            name: "prague".to_string(),
+            epoch: None,
+            timestamp: Some(config.execution_forks.prague_timestamp),
+            fork_version: None,
+        });
+    }
+
+    infos
 }
 
 impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
@@ -261,23 +510,63 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
         rpc: &str,
         block_send: Sender<Block<Transaction>>,
         finalized_block_send: watch::Sender<Option<Block<Transaction>>>,
-        checkpoint_send: watch::Sender<Option<B256>>,
+        beacon_mapping_send: Sender<BeaconBlockMapping>,
+        checkpoint_send: watch::Sender<Option<Checkpoint>>,
         config: Arc<Config>,
     ) -> Inner<S, R> {
-        let rpc = R::new(rpc);
+        let rpc = R::new(rpc).with_compression(config.compression.enabled);
 
         Inner {
             rpc,
             store: LightClientStore::default(),
             last_checkpoint: None,
+            head_signature: None,
+            finality_proof: None,
             block_send,
             finalized_block_send,
+            beacon_mapping_send,
             checkpoint_send,
+            head_send: watch::channel(None).0,
             config,
+            clock: Arc::new(ConsensusClock::system()),
+            finality: Arc::new(FinalityTracker::new()),
+            sync_committees: None,
+            fallback_services: None,
             phantom: PhantomData,
         }
     }
 
+    /// Keeps time with `clock`, which may be shared with the client owning this.
+    pub fn with_clock(mut self, clock: Arc<ConsensusClock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Records finality into `tracker`, which may be shared with the client owning this.
+    pub fn with_finality(mut self, tracker: Arc<FinalityTracker>) -> Self {
+        self.finality = tracker;
+        self
+    }
+
+    /// Publishes each verified head to `send`, ahead of the block channel the state reads.
+    pub fn with_head_send(mut self, send: watch::Sender<Option<Block<Transaction>>>) -> Self {
+        self.head_send = send;
+        self
+    }
+
+    /// Persists every sync committee verified from here on to `store`.
+    pub fn with_sync_committees(mut self, store: Arc<dyn SyncCommitteeStore>) -> Self {
+        self.sync_committees = Some(store);
+        self
+    }
+
+    /// Asks `services` for checkpoints with `load_external_fallback`, instead of the healthy
+    /// services of the community maintained list.
+    pub fn with_fallback_services(mut self, services: Vec<CheckpointFallbackService>) -> Self {
+        self.fallback_services = Some(services);
+        self
+    }
+
     pub async fn check_rpc(&self) -> Result<()> {
         let chain_id = self.rpc.chain_id().await?;
 
@@ -313,16 +602,16 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
 
     pub async fn get_payloads(
         &self,
-        start_slot: u64,
-        end_slot: u64,
+        start_slot: Slot,
+        end_slot: Slot,
     ) -> Result<Vec<ExecutionPayload<S>>> {
-        let payloads_fut = (start_slot..end_slot)
+        let payloads_fut = (start_slot.as_u64()..end_slot.as_u64())
             .rev()
             .map(|slot| self.rpc.get_block(slot));
 
         let mut prev_parent_hash: B256 = *self
             .rpc
-            .get_block(end_slot)
+            .get_block(end_slot.as_u64())
             .await?
             .body
             .execution_payload()
@@ -354,6 +643,8 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
     pub async fn sync(&mut self, checkpoint: B256) -> Result<()> {
         self.store = LightClientStore::default();
         self.last_checkpoint = None;
+        self.head_signature = None;
+        self.finality_proof = None;
 
         self.bootstrap(checkpoint).await?;
 
@@ -376,11 +667,113 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
         Ok(())
     }
 
+    /// Chooses between the `configured` checkpoint and the one `stored` by an earlier run,
+    /// preferring the stored one when its slot is later. The configured checkpoint is given
+    /// up when its bootstrap cannot be fetched. Returns the slot of the chosen checkpoint
+    /// when it is known.
+    pub async fn select_checkpoint(
+        &self,
+        configured: (B256, CheckpointSource),
+        stored: Option<Checkpoint>,
+    ) -> (B256, CheckpointSource, Option<u64>) {
+        let Some(stored) = stored else {
+            return (configured.0, configured.1, None);
+        };
+        if stored.root == configured.0 {
+            return (configured.0, configured.1, Some(stored.slot));
+        }
+
+        match self.rpc.get_bootstrap(configured.0).await {
+            Ok(bootstrap) if bootstrap.header().beacon().slot >= stored.slot => {
+                let slot = bootstrap.header().beacon().slot;
+                (configured.0, configured.1, Some(slot))
+            }
+            _ => {
+                info!(
+                    target: "helios::consensus",
+                    slot = stored.slot,
+                    "using stored checkpoint: 0x{}",
+                    hex::encode(stored.root)
+                );
+                (stored.root, CheckpointSource::Persisted, Some(stored.slot))
+            }
+        }
+    }
+
+    /// Syncs from `checkpoint`, at `slot` when known, turning to the fallbacks when it fails
+    /// to sync or is older than `max_checkpoint_age`. Returns the checkpoint synced from when
+    /// it came from a fallback.
+    pub async fn sync_or_fallback(
+        &mut self,
+        checkpoint: B256,
+        slot: Option<u64>,
+    ) -> Result<Option<B256>> {
+        let has_fallback = self.config.load_external_fallback || self.config.fallback.is_some();
+        let stale = slot.filter(|slot| !self.is_valid_checkpoint(Slot::new(*slot)));
+
+        let err = match stale {
+            Some(slot) if has_fallback => {
+                warn!(
+                    target: "helios::consensus",
+                    slot,
+                    "checkpoint too old, fetching a recent one from the fallbacks"
+                );
+                ConsensusError::CheckpointTooOld.into()
+            }
+            _ => match self.sync(checkpoint).await {
+                Ok(()) => return Ok(None),
+                Err(err) => err,
+            },
+        };
+
+        if self.config.load_external_fallback {
+            self.sync_from_fallback().await.map(Some)
+        } else if let Some(fallback) = self.config.fallback.clone() {
+            sync_fallback(self, &fallback).await.map(Some)
+        } else {
+            Err(err)
+        }
+    }
+
+    /// Syncs from the checkpoint at least `fallback_quorum` of the external fallback services
+    /// agree on. Nothing but their agreement vouches for it.
+    pub async fn sync_from_fallback(&mut self) -> Result<B256> {
+        let services = match &self.fallback_services {
+            Some(services) => services.clone(),
+            None => {
+                let network = Network::from_chain_id(self.config.chain.chain_id)?;
+                CheckpointFallback::new()
+                    .build()
+                    .await?
+                    .get_healthy_fallback_services(&network)
+            }
+        };
+
+        let survey = CheckpointFallback::survey(&services).await;
+        let checkpoint = survey.agreed(self.config.fallback_quorum)?;
+        for outlier in survey.outliers() {
+            warn!(
+                target: "helios::consensus",
+                service = %outlier.name,
+                "fallback service disagrees with the majority checkpoint"
+            );
+        }
+        warn!(
+            target: "helios::consensus",
+            services = services.len(),
+            quorum = self.config.fallback_quorum,
+            "bootstrapping from a checkpoint of the external fallback services, it is only as trustworthy as they are: 0x{}",
+            hex::encode(checkpoint)
+        );
+
+        self.sync(checkpoint).await?;
+        Ok(checkpoint)
+    }
+
     pub async fn get_updates(&self) -> Result<Vec<Update<S>>> {
-        let expected_current_slot = self.expected_current_slot();
-        let expected_current_period = calc_sync_period::<S>(expected_current_slot);
+        let expected_current_period = self.expected_current_slot().period::<S>();
         let mut next_update_fetch_period =
-            calc_sync_period::<S>(self.store.finalized_header.beacon().slot);
+            Slot::new(self.store.finalized_header.beacon().slot).period::<S>();
 
         let mut updates: Vec<Update<S>> = vec![];
         if expected_current_period - next_update_fetch_period >= 128 {
@@ -391,7 +784,10 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
                 );
                 let update = self
                     .rpc
-                    .get_updates(next_update_fetch_period, batch_size.try_into().unwrap())
+                    .get_updates(
+                        next_update_fetch_period.as_u64(),
+                        batch_size.try_into().unwrap(),
+                    )
                     .await?;
                 updates.extend(update);
 
@@ -401,7 +797,10 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
 
         let update: Vec<Update<S>> = self
             .rpc
-            .get_updates(next_update_fetch_period, MAX_REQUEST_LIGHT_CLIENT_UPDATES)
+            .get_updates(
+                next_update_fetch_period.as_u64(),
+                MAX_REQUEST_LIGHT_CLIENT_UPDATES,
+            )
             .await?;
         updates.extend(update);
 
@@ -415,8 +814,8 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
 
         if self.store.next_sync_committee.is_none() {
             debug!(target: "helios::consensus", "checking for sync committee update");
-            let current_period = calc_sync_period::<S>(self.store.finalized_header.beacon().slot);
-            let mut updates = self.rpc.get_updates(current_period, 1).await?;
+            let current_period = Slot::new(self.store.finalized_header.beacon().slot).period::<S>();
+            let mut updates = self.rpc.get_updates(current_period.as_u64(), 1).await?;
 
             if updates.len() == 1 {
                 let update = updates.get_mut(0).unwrap();
@@ -438,7 +837,22 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
         let finalized_slot = self.store.finalized_header.beacon().slot;
         let finalized_payload = self.get_execution_payload(&Some(finalized_slot)).await?;
 
-        self.block_send.send(payload_to_block(payload)).await?;
+        // nobody may be reading the mappings, so never wait on a full channel for them
+        for (header, payload, finalized) in [
+            (&self.store.optimistic_header, &payload, false),
+            (&self.store.finalized_header, &finalized_payload, true),
+        ] {
+            let attestation = self.attestation(header, finalized);
+            _ = self.beacon_mapping_send.try_send(beacon_mapping::<S>(
+                header.beacon(),
+                payload,
+                attestation,
+            ));
+        }
+
+        let block = payload_to_block(payload);
+        self.head_send.send_replace(Some(block.clone()));
+        self.block_send.send(block).await?;
         self.finalized_block_send
             .send(Some(payload_to_block(finalized_payload)))?;
         self.checkpoint_send.send(self.last_checkpoint)?;
@@ -446,22 +860,53 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
         Ok(())
     }
 
+    /// Sends the mappings of the blocks behind the optimistic header, which the state
+    /// backfills from the execution provider instead of receiving them from here. A beacon
+    /// block is only taken once it hashes to the parent root of the one after it, so blocks
+    /// the server answers with from another branch are skipped.
+    pub async fn send_backfilled_mappings(&self) {
+        let head = self.store.optimistic_header.beacon();
+        let start = head.slot.saturating_sub(BEACON_MAPPING_BACKFILL_SLOTS);
+        let blocks = join_all(
+            (start..head.slot)
+                .rev()
+                .map(|slot| self.rpc.get_block(slot)),
+        )
+        .await;
+
+        // empty slots are answered with an error
+        let mut parent_root = head.parent_root;
+        for block in blocks.into_iter().flatten() {
+            if block.tree_hash_root() != parent_root {
+                continue;
+            }
+            let header = BeaconBlockHeader {
+                slot: block.slot,
+                proposer_index: block.proposer_index,
+                parent_root: block.parent_root,
+                state_root: block.state_root,
+                body_root: block.body.tree_hash_root(),
+            };
+            parent_root = block.parent_root;
+            _ = self.beacon_mapping_send.try_send(beacon_mapping::<S>(
+                &header,
+                block.body.execution_payload(),
+                None,
+            ));
+        }
+    }
+
     /// Gets the duration until the next update
-    /// Updates are scheduled for 4 seconds into each slot
+    /// Updates are scheduled for 4 seconds into each slot, by the drift corrected clock
     pub fn duration_until_next_update(&self) -> Duration {
-        let current_slot = self.expected_current_slot();
-        let next_slot = current_slot + 1;
+        let now = self.clock.scheduling_now();
+        let next_slot = slot_at(now, self.config.chain.genesis_time) + 1;
         let next_slot_timestamp = self.slot_timestamp(next_slot);
 
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_else(|_| panic!("unreachable"))
-            .as_secs();
-
-        let time_to_next_slot = next_slot_timestamp - now;
+        let time_to_next_slot = next_slot_timestamp.saturating_sub(now.as_secs());
         let next_update = time_to_next_slot + 4;
 
-        Duration::try_seconds(next_update as i64).unwrap()
+        Duration::try_seconds(next_update as i64).unwrap_or(Duration::MAX)
     }
 
     pub async fn bootstrap(&mut self, checkpoint: B256) -> Result<()> {
@@ -471,7 +916,7 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
             .await
             .map_err(|err| eyre!("could not fetch bootstrap: {}", err))?;
 
-        let is_valid = self.is_valid_checkpoint(bootstrap.header().beacon().slot);
+        let is_valid = self.is_valid_checkpoint(Slot::new(bootstrap.header().beacon().slot));
 
         if !is_valid {
             if self.config.strict_checkpoint_age {
@@ -484,13 +929,23 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
         verify_bootstrap(&bootstrap, checkpoint, &self.config.forks)?;
         apply_bootstrap(&mut self.store, &bootstrap);
 
+        let header = bootstrap.header().beacon();
+        self.record_sync_committee(sync_committee_info::<S>(
+            Slot::new(header.slot).period::<S>(),
+            bootstrap.current_sync_committee(),
+            SyncCommitteeSource::Bootstrap,
+            header,
+            bootstrap.current_sync_committee_branch(),
+            CURRENT_SYNC_COMMITTEE_INDEX,
+        ));
+
         Ok(())
     }
 
     pub fn verify_update(&self, update: &Update<S>) -> Result<()> {
         verify_update::<S>(
             update,
-            self.expected_current_slot(),
+            self.latest_acceptable_slot(),
             &self.store,
             self.config.chain.genesis_root,
             &self.config.forks,
@@ -500,7 +955,7 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
     fn verify_finality_update(&self, update: &FinalityUpdate<S>) -> Result<()> {
         verify_finality_update::<S>(
             update,
-            self.expected_current_slot(),
+            self.latest_acceptable_slot(),
             &self.store,
             self.config.chain.genesis_root,
             &self.config.forks,
@@ -508,25 +963,133 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
     }
 
     pub fn apply_update(&mut self, update: &Update<S>) {
+        let prev_period = self.finalized_period();
+        let had_next_committee = self.store.next_sync_committee.is_some();
+        let prev_slots = self.header_slots();
+
         let new_checkpoint = apply_update::<S>(&mut self.store, update);
-        if new_checkpoint.is_some() {
-            self.last_checkpoint = new_checkpoint;
+        self.keep_checkpoint(new_checkpoint);
+        self.keep_attestations(&GenericUpdate::from(update), prev_slots);
+
+        // the next committee is taken from an update when first learned and on rotation
+        let period = self.finalized_period();
+        let learned_next_committee = !had_next_committee || period != prev_period;
+        if learned_next_committee && self.store.next_sync_committee.is_some() {
+            let header = update.attested_header().beacon();
+            self.record_sync_committee(sync_committee_info::<S>(
+                Slot::new(header.slot).period::<S>() + 1,
+                update.next_sync_committee(),
+                SyncCommitteeSource::Update,
+                header,
+                update.next_sync_committee_branch(),
+                NEXT_SYNC_COMMITTEE_INDEX,
+            ));
+        }
+    }
+
+    /// Keeps a checkpoint returned when an update is applied, which is the root of the newly
+    /// finalized header.
+    fn keep_checkpoint(&mut self, root: Option<B256>) {
+        if let Some(root) = root {
+            let slot = self.store.finalized_header.beacon().slot;
+            self.last_checkpoint = Some(Checkpoint { root, slot });
+        }
+    }
+
+    /// The slots of the optimistic and finalized headers.
+    fn header_slots(&self) -> (u64, u64) {
+        (
+            self.store.optimistic_header.beacon().slot,
+            self.store.finalized_header.beacon().slot,
+        )
+    }
+
+    /// Keeps what `update` proved of the headers it replaced since `prev_slots`.
+    fn keep_attestations(&mut self, update: &GenericUpdate<S>, prev_slots: (u64, u64)) {
+        let (prev_optimistic_slot, prev_finalized_slot) = prev_slots;
+        let attested = update.attested_header.beacon();
+        let signature = || sync_aggregate_info::<S>(&update.sync_aggregate, update.signature_slot);
+
+        let optimistic = self.store.optimistic_header.beacon();
+        if optimistic.slot != prev_optimistic_slot {
+            // the optimistic header also moves up to a newly finalized one it trails
+            self.head_signature =
+                (optimistic.tree_hash_root() == attested.tree_hash_root()).then(signature);
+        }
+
+        let finalized = self.store.finalized_header.beacon().slot;
+        if finalized != prev_finalized_slot {
+            self.finality_proof = update.finality_branch.as_ref().map(|branch| {
+                let proof = FinalityProof {
+                    attested_header: beacon_header(attested),
+                    generalized_index: (1 << branch.len()) + FINALIZED_ROOT_INDEX,
+                    branch: branch.clone(),
+                };
+                (proof, signature())
+            });
+        }
+    }
+
+    /// What the light client trusted `header` on, as either its optimistic or its finalized
+    /// header. Headers from before Capella cannot be linked to their execution block.
+    fn attestation(
+        &self,
+        header: &LightClientHeader,
+        finalized: bool,
+    ) -> Option<BeaconAttestation> {
+        let (generalized_index, execution_branch) = execution_block_hash_proof(header)?;
+        let (finality, sync_aggregate) = match (finalized, &self.finality_proof) {
+            (true, Some((proof, signature))) => (Some(proof.clone()), Some(signature.clone())),
+            (true, None) => (None, None),
+            (false, _) => (None, self.head_signature.clone()),
+        };
+
+        Some(BeaconAttestation {
+            generalized_index,
+            execution_branch,
+            finality,
+            sync_aggregate,
+        })
+    }
+
+    fn finalized_period(&self) -> Period {
+        Slot::new(self.store.finalized_header.beacon().slot).period::<S>()
+    }
+
+    /// Persists a verified committee, keeping the current and next committees pinned.
+    fn record_sync_committee(&self, committee: SyncCommitteeInfo) {
+        let Some(store) = &self.sync_committees else {
+            return;
+        };
+
+        let current = self.finalized_period();
+        if let Err(err) = store.save(&committee, [current.as_u64(), (current + 1).as_u64()]) {
+            warn!(
+                target: "helios::consensus",
+                err = %err,
+                period = committee.period,
+                "failed to persist sync committee"
+            );
         }
     }
 
     fn apply_finality_update(&mut self, update: &FinalityUpdate<S>) {
-        let prev_finalized_slot = self.store.finalized_header.beacon().slot;
-        let prev_optimistic_slot = self.store.optimistic_header.beacon().slot;
+        let (prev_optimistic_slot, prev_finalized_slot) = self.header_slots();
         let new_checkpoint = apply_finality_update::<S>(&mut self.store, update);
-        let new_finalized_slot = self.store.finalized_header.beacon().slot;
-        let new_optimistic_slot = self.store.optimistic_header.beacon().slot;
-        if new_checkpoint.is_some() {
-            self.last_checkpoint = new_checkpoint;
-        }
+        let (new_optimistic_slot, new_finalized_slot) = self.header_slots();
+        self.keep_checkpoint(new_checkpoint);
+        self.keep_attestations(
+            &GenericUpdate::from(update),
+            (prev_optimistic_slot, prev_finalized_slot),
+        );
+        self.finality
+            .observe::<S>(Slot::new(new_finalized_slot), self.expected_current_slot());
         if new_finalized_slot != prev_finalized_slot {
             self.log_finality_update(update);
         }
         if new_optimistic_slot != prev_optimistic_slot {
+            self.clock
+                .observe_head(self.slot_timestamp(Slot::new(new_optimistic_slot)));
             self.log_optimistic_update(update)
         }
     }
@@ -536,7 +1099,7 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
         let participation =
             get_bits::<S>(&update.sync_aggregate().sync_committee_bits) as f32 / size * 100f32;
         let decimals = if participation == 100.0 { 1 } else { 2 };
-        let age = self.age(self.store.finalized_header.beacon().slot);
+        let age = self.age(Slot::new(self.store.finalized_header.beacon().slot));
 
         info!(
             target: "helios::consensus",
@@ -555,7 +1118,7 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
         let participation =
             get_bits::<S>(&update.sync_aggregate().sync_committee_bits) as f32 / size * 100f32;
         let decimals = if participation == 100.0 { 1 } else { 2 };
-        let age = self.age(self.store.optimistic_header.beacon().slot);
+        let age = self.age(Slot::new(self.store.optimistic_header.beacon().slot));
 
         info!(
             target: "helios::consensus",
@@ -569,37 +1132,108 @@ impl<S: ConsensusSpec, R: ConsensusRpc<S>> Inner<S, R> {
         );
     }
 
-    fn age(&self, slot: u64) -> Duration {
+    fn age(&self, slot: Slot) -> Duration {
         let expected_time = self.slot_timestamp(slot);
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_else(|_| panic!("unreachable"));
+        let now = self.clock.now();
 
-        let delay = now - std::time::Duration::from_secs(expected_time);
+        // a head accepted within the skew tolerance can be slightly in the future
+        let delay = now.saturating_sub(std::time::Duration::from_secs(expected_time));
         chrono::Duration::from_std(delay).unwrap()
     }
 
-    pub fn expected_current_slot(&self) -> u64 {
-        let now = SystemTime::now();
+    pub fn expected_current_slot(&self) -> Slot {
+        slot_at(self.clock.now(), self.config.chain.genesis_time)
+    }
 
-        expected_current_slot(now, self.config.chain.genesis_time)
+    /// The latest slot an update may be signed in before it counts as coming from the
+    /// future, allowing for the configured clock skew.
+    fn latest_acceptable_slot(&self) -> u64 {
+        let now = self.clock.now() + self.config.clock_skew_tolerance.as_duration();
+        slot_at(now, self.config.chain.genesis_time).as_u64()
     }
 
-    fn slot_timestamp(&self, slot: u64) -> u64 {
-        slot * 12 + self.config.chain.genesis_time
+    fn slot_timestamp(&self, slot: Slot) -> u64 {
+        slot.as_u64() * 12 + self.config.chain.genesis_time
     }
 
     // Determines blockhash_slot age and returns true if it is less than 14 days old
-    fn is_valid_checkpoint(&self, blockhash_slot: u64) -> bool {
-        let current_slot = self.expected_current_slot();
-        let current_slot_timestamp = self.slot_timestamp(current_slot);
-        let blockhash_slot_timestamp = self.slot_timestamp(blockhash_slot);
+    fn is_valid_checkpoint(&self, blockhash_slot: Slot) -> bool {
+        let slot_age = self.expected_current_slot().since(blockhash_slot) * 12;
+        slot_age < self.config.max_checkpoint_age.as_secs()
+    }
+}
+
+fn slot_at(now: std::time::Duration, genesis_time: u64) -> Slot {
+    Slot::new(now.as_secs().saturating_sub(genesis_time) / 12)
+}
+
+/// Describes `committee` with the branch proving it against the state root of `header`, at
+/// `index` among the leaves of that depth.
+fn sync_committee_info<S: ConsensusSpec>(
+    period: Period,
+    committee: &SyncCommittee<S>,
+    source: SyncCommitteeSource,
+    header: &BeaconBlockHeader,
+    branch: &[B256],
+    index: u64,
+) -> SyncCommitteeInfo {
+    SyncCommitteeInfo {
+        period: period.as_u64(),
+        pubkeys: committee
+            .pubkeys
+            .iter()
+            .map(|pubkey| pubkey.to_bytes())
+            .collect(),
+        aggregate_pubkey: committee.aggregate_pubkey.to_bytes(),
+        root: committee.tree_hash_root(),
+        proof: SyncCommitteeProof {
+            source,
+            slot: header.slot,
+            beacon_block_root: header.tree_hash_root(),
+            state_root: header.state_root,
+            generalized_index: (1 << branch.len()) + index,
+            branch: branch.to_vec(),
+        },
+    }
+}
+
+fn beacon_mapping<S: ConsensusSpec>(
+    header: &BeaconBlockHeader,
+    payload: &ExecutionPayload<S>,
+    attestation: Option<BeaconAttestation>,
+) -> BeaconBlockMapping {
+    BeaconBlockMapping {
+        slot: header.slot,
+        epoch: Slot::new(header.slot).epoch::<S>().as_u64(),
+        proposer_index: header.proposer_index,
+        parent_root: header.parent_root,
+        state_root: header.state_root,
+        body_root: header.body_root,
+        beacon_block_root: header.tree_hash_root(),
+        execution_block_number: *payload.block_number(),
+        execution_block_hash: *payload.block_hash(),
+        attestation,
+    }
+}
 
-        let slot_age = current_slot_timestamp
-            .checked_sub(blockhash_slot_timestamp)
-            .unwrap_or_default();
+fn beacon_header(header: &BeaconBlockHeader) -> BeaconHeader {
+    BeaconHeader {
+        slot: header.slot,
+        proposer_index: header.proposer_index,
+        parent_root: header.parent_root,
+        state_root: header.state_root,
+        body_root: header.body_root,
+    }
+}
 
-        slot_age < self.config.max_checkpoint_age
+fn sync_aggregate_info<S: ConsensusSpec>(
+    aggregate: &SyncAggregate<S>,
+    signature_slot: u64,
+) -> SyncAggregateInfo {
+    SyncAggregateInfo {
+        sync_committee_bits: aggregate.sync_committee_bits.as_slice().to_vec().into(),
+        sync_committee_signature: aggregate.sync_committee_signature.to_bytes(),
+        signature_slot,
     }
 }
 
@@ -680,20 +1314,28 @@ fn payload_to_block<S: ConsensusSpec>(value: ExecutionPayload<S>) -> Block<Trans
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
+    use std::time::Duration;
 
-    use alloy::primitives::b256;
+    use alloy::primitives::{b256, B256};
     use tokio::sync::{mpsc::channel, watch};
+    use tree_hash::TreeHash;
 
-    use helios_consensus_core::consensus_spec::MainnetConsensusSpec;
+    use helios_consensus_core::consensus_spec::{MainnetConsensusSpec, MinimalConsensusSpec};
     use helios_consensus_core::errors::ConsensusError;
     use helios_consensus_core::types::bls::{PublicKey, Signature};
-    use helios_consensus_core::types::Update;
+    use helios_consensus_core::types::{Slot, SyncCommittee, Update};
+    use helios_consensus_core::{calc_sync_period, verify_bootstrap};
+    use helios_core::execution::proof::{beacon_header_root, is_merkle_branch_valid};
+    use helios_core::types::{ForkInfo, SyncCommitteeInfo, SyncCommitteeSource};
 
     use crate::{
+        clock::{Clock, ConsensusClock, ManualClock},
         config::{networks, Config},
-        consensus::calc_sync_period,
-        consensus::Inner,
+        consensus::{beacon_header, fork_infos, Inner},
         constants::MAX_REQUEST_LIGHT_CLIENT_UPDATES,
+        database::gc::SYNC_COMMITTEES,
+        database::sync_committees::{DbSyncCommitteeStore, SyncCommitteeStore},
+        database::{Database, FileDB, OpenDatabase},
         rpc::{mock_rpc::MockRpc, ConsensusRpc},
     };
 
@@ -711,24 +1353,38 @@ mod tests {
             ..Default::default()
         };
 
-        let checkpoint = b256!("5afc212a7924789b2bc86acad3ab3a6ffb1f6e97253ea50bee7f4f51422c9275");
+        get_client_with_config(config, sync).await
+    }
 
+    const CHECKPOINT: B256 =
+        b256!("5afc212a7924789b2bc86acad3ab3a6ffb1f6e97253ea50bee7f4f51422c9275");
+
+    fn new_client(config: Config) -> Inner<MainnetConsensusSpec, MockRpc> {
         let (block_send, _) = channel(256);
         let (finalized_block_send, _) = watch::channel(None);
+        let (beacon_mapping_send, _) = channel(256);
         let (channel_send, _) = watch::channel(None);
 
-        let mut client = Inner::new(
+        Inner::new(
             "testdata/",
             block_send,
             finalized_block_send,
+            beacon_mapping_send,
             channel_send,
             Arc::new(config),
-        );
+        )
+    }
+
+    async fn get_client_with_config(
+        config: Config,
+        sync: bool,
+    ) -> Inner<MainnetConsensusSpec, MockRpc> {
+        let mut client = new_client(config);
 
         if sync {
-            client.sync(checkpoint).await.unwrap()
+            client.sync(CHECKPOINT).await.unwrap()
         } else {
-            client.bootstrap(checkpoint).await.unwrap();
+            client.bootstrap(CHECKPOINT).await.unwrap();
         }
 
         client
@@ -830,6 +1486,42 @@ mod tests {
         client.verify_finality_update(&update).unwrap();
     }
 
+    #[tokio::test]
+    async fn test_attestations_prove_execution_block_hashes() {
+        let client = get_client(false, true).await;
+        let store = &client.store;
+
+        for (header, finalized) in [
+            (&store.optimistic_header, false),
+            (&store.finalized_header, true),
+        ] {
+            let attestation = client.attestation(header, finalized).unwrap();
+            let beacon = header.beacon();
+            let block_hash = *header.execution().unwrap().block_hash();
+            assert!(is_merkle_branch_valid(
+                block_hash,
+                &attestation.execution_branch,
+                attestation.generalized_index,
+                beacon.body_root,
+            ));
+            assert_eq!(
+                beacon_header_root(&beacon_header(beacon)),
+                beacon.tree_hash_root()
+            );
+            assert!(attestation.sync_aggregate.is_some());
+        }
+
+        // the finality update moved the finalized header past the bootstrapped one
+        let attestation = client.attestation(&store.finalized_header, true).unwrap();
+        let finality = attestation.finality.unwrap();
+        assert!(is_merkle_branch_valid(
+            store.finalized_header.beacon().tree_hash_root(),
+            &finality.branch,
+            finality.generalized_index,
+            finality.attested_header.state_root,
+        ));
+    }
+
     #[tokio::test]
     async fn test_verify_finality_invalid_finality() {
         let client = get_client(false, true).await;
@@ -875,4 +1567,207 @@ mod tests {
     async fn test_verify_checkpoint_age_invalid() {
         get_client(true, false).await;
     }
+
+    /// A client whose clock reads `skew` seconds off from when the first update was
+    /// signed, together with that update.
+    async fn skewed_client(
+        skew: i64,
+        clock_skew_tolerance: Duration,
+    ) -> (
+        Inner<MainnetConsensusSpec, MockRpc>,
+        Arc<ManualClock>,
+        Update<MainnetConsensusSpec>,
+    ) {
+        let base_config = networks::mainnet();
+        let config = Config {
+            chain: base_config.chain,
+            forks: base_config.forks,
+            clock_skew_tolerance: clock_skew_tolerance.into(),
+            ..Default::default()
+        };
+        let client = get_client_with_config(config, false).await;
+
+        let period = calc_sync_period::<MainnetConsensusSpec>(
+            client.store.finalized_header.beacon().slot.into(),
+        );
+        let update = client
+            .rpc
+            .get_updates(period, MAX_REQUEST_LIGHT_CLIENT_UPDATES)
+            .await
+            .unwrap()
+            .remove(0);
+
+        let signed_at = client.slot_timestamp(Slot::new(*update.signature_slot())) as i64;
+        let clock = Arc::new(ManualClock::new(Duration::from_secs(
+            (signed_at + skew) as u64,
+        )));
+        let client = client.with_clock(Arc::new(ConsensusClock::new(clock.clone())));
+        (client, clock, update)
+    }
+
+    #[tokio::test]
+    async fn test_future_update_rejected_without_skew_tolerance() {
+        let (client, _, update) = skewed_client(-10, Duration::ZERO).await;
+
+        let err = client.verify_update(&update).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ConsensusError::InvalidTimestamp.to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_future_update_accepted_within_skew_tolerance() {
+        let (client, _, update) = skewed_client(-10, Duration::from_secs(10)).await;
+        client.verify_update(&update).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_drift_corrected_clock() {
+        // heads the consensus rpc makes look late do not make future updates acceptable
+        let (client, clock, update) = skewed_client(-10, Duration::ZERO).await;
+        let now = clock.now();
+        for slot in 0..16 {
+            let timestamp = client.slot_timestamp(Slot::new(slot));
+            clock.set(Duration::from_secs(timestamp + 16 - 10));
+            client.clock.observe_head(timestamp);
+        }
+        assert_eq!(client.clock.drift(), Some(-10_000));
+        clock.set(now);
+        let err = client.verify_update(&update).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            ConsensusError::InvalidTimestamp.to_string()
+        );
+
+        // a clock running 10 seconds ahead schedules polls by network time, correcting by
+        // at most the warning threshold
+        let (client, clock, _) = skewed_client(10, Duration::ZERO).await;
+        let signed_at = clock.now() - Duration::from_secs(10);
+        for slot in 0..16 {
+            let timestamp = client.slot_timestamp(Slot::new(slot));
+            clock.set(Duration::from_secs(timestamp + 16 + 10));
+            client.clock.observe_head(timestamp);
+        }
+        assert_eq!(client.clock.drift(), Some(10_000));
+
+        // eleven seconds into the slot by the local clock, six after the correction
+        clock.set(signed_at + Duration::from_secs(11));
+        let next = client.duration_until_next_update();
+        assert_eq!(next.num_seconds(), 10);
+    }
+
+    fn committee_of(info: &SyncCommitteeInfo) -> SyncCommittee<MainnetConsensusSpec> {
+        SyncCommittee {
+            pubkeys: info
+                .pubkeys
+                .iter()
+                .map(|pubkey| PublicKey::from(*pubkey))
+                .collect::<Vec<_>>()
+                .into(),
+            aggregate_pubkey: info.aggregate_pubkey.into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sync_committees_persisted() {
+        let data_dir =
+            std::env::temp_dir().join(format!("helios-sync-committees-{}", std::process::id()));
+        _ = std::fs::remove_dir_all(&data_dir);
+        let base_config = networks::mainnet();
+        let config = Config {
+            chain: base_config.chain,
+            forks: base_config.forks,
+            data_dir: Some(data_dir),
+            ..Default::default()
+        };
+        let db = FileDB::new(&config).unwrap();
+        let store = Arc::new(DbSyncCommitteeStore::<MainnetConsensusSpec, _>::new(
+            db.clone(),
+        ));
+
+        let mut client = new_client(config).with_sync_committees(store.clone());
+        client.sync(CHECKPOINT).await.unwrap();
+
+        // the updates rotate the committee a few times after the bootstrap
+        let mut bootstrap = client.rpc.get_bootstrap(CHECKPOINT).await.unwrap();
+        let first = calc_sync_period::<MainnetConsensusSpec>(bootstrap.header().beacon().slot);
+        let period =
+            calc_sync_period::<MainnetConsensusSpec>(client.store.finalized_header.beacon().slot);
+        assert!(period > first);
+        let periods = (first..=period + 1).collect::<Vec<_>>();
+        assert_eq!(store.periods().unwrap(), periods);
+
+        let mut pinned = db
+            .entries(SYNC_COMMITTEES.name)
+            .unwrap()
+            .into_iter()
+            .filter(|entry| entry.pinned)
+            .map(|entry| entry.key)
+            .collect::<Vec<_>>();
+        pinned.sort();
+        assert_eq!(
+            pinned,
+            [
+                period.to_be_bytes().to_vec(),
+                (period + 1).to_be_bytes().to_vec()
+            ]
+        );
+
+        // the bootstrap committee, with the branch the checkpoint commits to it by
+        let initial = store.load(first).unwrap().unwrap();
+        let committee = committee_of(&initial);
+        assert_eq!(initial.root, committee.tree_hash_root());
+        assert_eq!(&committee, bootstrap.current_sync_committee());
+        assert_eq!(initial.proof.source, SyncCommitteeSource::Bootstrap);
+        assert_eq!(initial.proof.beacon_block_root, CHECKPOINT);
+        assert_eq!(
+            initial.proof.branch,
+            bootstrap.current_sync_committee_branch()
+        );
+        assert_eq!(initial.proof.generalized_index, 54);
+
+        *bootstrap.current_sync_committee_mut() = committee;
+        verify_bootstrap(&bootstrap, CHECKPOINT, &client.config.forks).unwrap();
+
+        // committees learned from updates, proven against their attested headers
+        let current = store.load(period).unwrap().unwrap();
+        let committee = committee_of(&current);
+        assert_eq!(current.root, committee.tree_hash_root());
+        assert_eq!(committee, client.store.current_sync_committee);
+        assert_eq!(current.proof.source, SyncCommitteeSource::Update);
+        assert_eq!(current.proof.generalized_index, 55);
+
+        let next = store.load(period + 1).unwrap().unwrap();
+        assert_eq!(Some(committee_of(&next)), client.store.next_sync_committee);
+        assert_eq!(store.load(period + 2).unwrap(), None);
+    }
+
+    #[test]
+    fn test_fork_timestamps_follow_slot_time() {
+        let mut config = Config {
+            chain: networks::mainnet().chain,
+            forks: networks::mainnet().forks,
+            ..Default::default()
+        };
+        config.forks.deneb.epoch = 10;
+
+        let deneb = |infos: Vec<ForkInfo>| {
+            infos
+                .into_iter()
+                .find(|fork| fork.name == "deneb")
+                .and_then(|fork| fork.timestamp)
+                .unwrap()
+        };
+        let genesis = config.chain.genesis_time;
+        // 32 slots of 12 seconds an epoch on mainnet, 8 of 6 on the minimal spec
+        assert_eq!(
+            deneb(fork_infos::<MainnetConsensusSpec>(&config)),
+            genesis + 10 * 32 * 12
+        );
+        assert_eq!(
+            deneb(fork_infos::<MinimalConsensusSpec>(&config)),
+            genesis + 10 * 8 * 6
+        );
+    }
 }
@@ -1,4 +1,49 @@
+use std::time::Duration;
+
 // Consensus constants
 
 // https://github.com/ethereum/consensus-specs/blob/dev/specs/altair/light-client/p2p-interface.md#configuration
 pub const MAX_REQUEST_LIGHT_CLIENT_UPDATES: u8 = 128;
+/// Leaf indices of the sync committees among the beacon state fields, before they are
+/// turned into generalized indices by the depth of the branch.
+pub const CURRENT_SYNC_COMMITTEE_INDEX: u64 = 22;
+pub const NEXT_SYNC_COMMITTEE_INDEX: u64 = 23;
+/// Leaf index of the finalized checkpoint root among the beacon state fields.
+pub const FINALIZED_ROOT_INDEX: u64 = 41;
+/// Slots behind the first head searched for the beacon blocks of the execution blocks the
+/// state backfills, twice its window so that missed slots still leave it covered.
+pub const BEACON_MAPPING_BACKFILL_SLOTS: u64 = 128;
+/// Fallback services that must agree on a checkpoint before it is bootstrapped from.
+pub const DEFAULT_FALLBACK_QUORUM: usize = 2;
+
+// Clock constants
+
+/// How old a new head normally is when it arrives on an accurate clock. Updates are polled
+/// four seconds into a slot and attest to the block of the slot before.
+pub const EXPECTED_HEAD_AGE: Duration = Duration::from_secs(16);
+/// Weight of each new head in the clock drift estimate.
+pub const DRIFT_SMOOTHING: f64 = 0.1;
+/// Heads to observe before the drift estimate is trusted.
+pub const DRIFT_MIN_SAMPLES: u32 = 8;
+/// Drift beyond which a warning is logged.
+pub const DRIFT_WARNING_THRESHOLD: Duration = Duration::from_secs(5);
+/// Default allowance for updates that appear to come from the future.
+pub const DEFAULT_CLOCK_SKEW_TOLERANCE: Duration = Duration::from_secs(2);
+
+// Finality constants
+
+/// Finality advances whose lag is kept for estimating finality times.
+pub const FINALITY_HISTORY_LENGTH: usize = 32;
+/// Epochs the finalized slot may trail the current slot before finality counts as stalled,
+/// twice what it trails by on a healthy chain.
+pub const FINALITY_STALL_EPOCHS: u64 = 4;
+
+// Database constants
+
+pub const GC_INTERVAL: Duration = Duration::from_secs(600);
+pub const GC_BATCH_SIZE: usize = 256;
+pub const MAX_CHECKPOINT_HISTORY: u64 = 32;
+/// Days of provider usage kept, a little over a year.
+pub const MAX_USAGE_HISTORY: u64 = 400;
+/// Sync committee periods kept by default, around nine months of committees.
+pub const DEFAULT_SYNC_COMMITTEE_RETENTION: u64 = 256;
@@ -0,0 +1,331 @@
+//! Garbage collection of namespaced [Database] entries.
+//!
+//! Each namespace declares a [RetentionPolicy], which a background task enforces by deleting
+//! the oldest entries in batches. Pinned entries count towards a namespace's usage but are
+//! never deleted.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use eyre::Result;
+use tokio::sync::watch;
+use tracing::{debug, warn};
+
+use helios_core::database;
+use helios_core::execution::{code_cache, historical, state};
+use helios_core::time::{interval, SystemTime, UNIX_EPOCH};
+use helios_core::types::{DbStats, NamespaceStats, RetentionPolicy};
+
+use crate::config::Config;
+use crate::constants::{
+    DEFAULT_SYNC_COMMITTEE_RETENTION, GC_BATCH_SIZE, MAX_CHECKPOINT_HISTORY, MAX_USAGE_HISTORY,
+};
+
+pub use super::EntryInfo;
+
+use super::Database;
+
+/// Saved checkpoints, latest pinned since it is the one the client resumes from.
+pub const CHECKPOINTS: Namespace = Namespace {
+    name: "checkpoints",
+    retention: RetentionPolicy::MaxEntries(MAX_CHECKPOINT_HISTORY),
+};
+
+/// Provider usage, one entry per day.
+pub const USAGE: Namespace = Namespace {
+    name: "usage",
+    retention: RetentionPolicy::MaxEntries(MAX_USAGE_HISTORY),
+};
+
+/// Verified sync committees, one entry per period. The current and next committees are
+/// pinned, and the number of periods kept is set by `sync_committee_retention`.
+pub const SYNC_COMMITTEES: Namespace = Namespace {
+    name: "sync_committees",
+    retention: RetentionPolicy::MaxEntries(DEFAULT_SYNC_COMMITTEE_RETENTION),
+};
+
+/// The checkpoint the client resumes from. Its history lives in [CHECKPOINTS], which keeps
+/// the checkpoints as keys only.
+pub const LATEST_CHECKPOINT: Namespace = Namespace {
+    name: database::LATEST_CHECKPOINT,
+    retention: RetentionPolicy::Pinned,
+};
+
+/// Contract code, which the code cache keeps within its own size limit.
+pub const CODE: Namespace = Namespace {
+    name: code_cache::CODE,
+    retention: RetentionPolicy::Pinned,
+};
+
+/// Verified historical headers, which the execution state deletes as blocks move out of
+/// the historical depth.
+pub const VERIFIED_HEADERS: Namespace = Namespace {
+    name: historical::VERIFIED_HEADERS,
+    retention: RetentionPolicy::Pinned,
+};
+
+/// Beacon block roots of execution blocks, which the execution state deletes with the
+/// blocks they map.
+pub const BEACON_MAPPINGS: Namespace = Namespace {
+    name: state::BEACON_MAPPINGS,
+    retention: RetentionPolicy::Pinned,
+};
+
+/// Every namespace the client persists entries in. Code, headers and mappings are only
+/// written here when the embedder supplies the database.
+pub const NAMESPACES: &[Namespace] = &[
+    LATEST_CHECKPOINT,
+    CHECKPOINTS,
+    USAGE,
+    SYNC_COMMITTEES,
+    CODE,
+    VERIFIED_HEADERS,
+    BEACON_MAPPINGS,
+];
+
+/// [NAMESPACES] with the retention `config` sets for them.
+pub fn configured_namespaces(config: &Config) -> Vec<Namespace> {
+    NAMESPACES
+        .iter()
+        .map(|&namespace| {
+            if namespace == SYNC_COMMITTEES {
+                Namespace {
+                    retention: RetentionPolicy::MaxEntries(config.sync_committee_retention),
+                    ..namespace
+                }
+            } else {
+                namespace
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Namespace {
+    pub name: &'static str,
+    pub retention: RetentionPolicy,
+}
+
+pub struct GarbageCollector<DB: Database> {
+    db: Arc<DB>,
+    namespaces: Vec<Namespace>,
+    evicted: Vec<AtomicU64>,
+}
+
+impl<DB: Database> GarbageCollector<DB> {
+    pub fn new(db: Arc<DB>, namespaces: &[Namespace]) -> Self {
+        Self {
+            db,
+            namespaces: namespaces.to_vec(),
+            evicted: namespaces.iter().map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    /// Brings every namespace within its retention policy.
+    pub fn collect(&self) -> Result<()> {
+        let now = now_millis();
+
+        for (namespace, evicted) in self.namespaces.iter().zip(&self.evicted) {
+            let entries = self.db.entries(namespace.name)?;
+            let keys = select_evictions(namespace.retention, entries, now);
+
+            for batch in keys.chunks(GC_BATCH_SIZE) {
+                self.db.delete(namespace.name, batch)?;
+                evicted.fetch_add(batch.len() as u64, Ordering::Relaxed);
+            }
+
+            if !keys.is_empty() {
+                debug!(
+                    target: "helios::database",
+                    namespace = namespace.name,
+                    evicted = keys.len(),
+                    "collected namespace"
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn stats(&self) -> Result<DbStats> {
+        let namespaces = self
+            .namespaces
+            .iter()
+            .zip(&self.evicted)
+            .map(|(namespace, evicted)| {
+                let entries = self.db.entries(namespace.name)?;
+                Ok(NamespaceStats {
+                    name: namespace.name.to_string(),
+                    retention: namespace.retention,
+                    entries: entries.len() as u64,
+                    pinned_entries: entries.iter().filter(|entry| entry.pinned).count() as u64,
+                    bytes: entries.iter().map(|entry| entry.size).sum(),
+                    oldest_written_at: entries.iter().map(|entry| entry.written_at).min(),
+                    evicted: evicted.load(Ordering::Relaxed),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(DbStats { namespaces })
+    }
+
+    /// Collects every `period` in a task of its own, as [`GarbageCollector::run`] does.
+    pub fn spawn(self: Arc<Self>, period: Duration, shutdown_recv: watch::Receiver<bool>) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let run = tokio::spawn;
+
+        #[cfg(target_arch = "wasm32")]
+        let run = wasm_bindgen_futures::spawn_local;
+
+        run(self.run(period, shutdown_recv));
+    }
+
+    /// Collects every `period`, starting immediately. Collection pauses while the client is
+    /// shut down and stops for good once it is dropped.
+    pub fn run(
+        self: Arc<Self>,
+        period: Duration,
+        mut shutdown_recv: watch::Receiver<bool>,
+    ) -> impl Future<Output = ()> {
+        async move {
+            let mut interval = interval(period);
+            loop {
+                tokio::select! {
+                    changed = shutdown_recv.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        let shutdown = *shutdown_recv.borrow_and_update();
+                        if shutdown && shutdown_recv.wait_for(|shutdown| !shutdown).await.is_err() {
+                            break;
+                        }
+                    }
+                    _ = interval.tick() => {
+                        if let Err(err) = self.collect() {
+                            warn!(target: "helios::database", err = %err, "garbage collection failed");
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Picks the keys to delete to bring `entries` within `retention`, oldest first. Pinned
+/// entries are never picked, even when they alone exceed the limit.
+pub fn select_evictions(
+    retention: RetentionPolicy,
+    mut entries: Vec<EntryInfo>,
+    now: u64,
+) -> Vec<Vec<u8>> {
+    entries.sort_by(|a, b| (a.written_at, &a.key).cmp(&(b.written_at, &b.key)));
+
+    let mut count = entries.len() as u64;
+    let mut bytes = entries.iter().map(|entry| entry.size).sum::<u64>();
+    let mut evictions = Vec::new();
+
+    for entry in entries.into_iter().filter(|entry| !entry.pinned) {
+        let over = match retention {
+            RetentionPolicy::MaxEntries(max) => count > max,
+            RetentionPolicy::MaxBytes(max) => bytes > max.as_bytes(),
+            RetentionPolicy::MaxAge(max) => {
+                now.saturating_sub(entry.written_at) > max.as_duration().as_millis() as u64
+            }
+            RetentionPolicy::Pinned => false,
+        };
+
+        // entries are sorted oldest first, so no later entry is over the limit either
+        if !over {
+            break;
+        }
+
+        count -= 1;
+        bytes -= entry.size;
+        evictions.push(entry.key);
+    }
+
+    evictions
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use helios_core::config::{ByteSize, ConfigDuration};
+
+    use super::*;
+
+    fn entry(key: u8, size: u64, written_at: u64, pinned: bool) -> EntryInfo {
+        EntryInfo {
+            key: vec![key],
+            size,
+            written_at,
+            pinned,
+        }
+    }
+
+    fn keys(keys: &[u8]) -> Vec<Vec<u8>> {
+        keys.iter().map(|key| vec![*key]).collect()
+    }
+
+    #[test]
+    fn test_max_entries_evicts_oldest_unpinned() {
+        let entries = vec![
+            entry(3, 10, 300, false),
+            entry(1, 10, 100, true),
+            entry(4, 10, 400, false),
+            entry(2, 10, 200, false),
+            entry(5, 10, 500, false),
+        ];
+
+        let evicted = select_evictions(RetentionPolicy::MaxEntries(3), entries, 1_000);
+        assert_eq!(evicted, keys(&[2, 3]));
+    }
+
+    #[test]
+    fn test_max_bytes_evicts_until_within_cap() {
+        let entries = vec![
+            entry(1, 600, 100, false),
+            entry(2, 300, 200, true),
+            entry(3, 300, 300, false),
+            entry(4, 300, 400, false),
+        ];
+
+        let evicted = select_evictions(
+            RetentionPolicy::MaxBytes(ByteSize::from_bytes(1_000)),
+            entries,
+            1_000,
+        );
+        assert_eq!(evicted, keys(&[1]));
+    }
+
+    #[test]
+    fn test_max_age_evicts_expired() {
+        let entries = vec![
+            entry(1, 1, 1_000, false),
+            entry(2, 1, 2_000, true),
+            entry(3, 1, 9_000, false),
+        ];
+
+        let evicted = select_evictions(
+            RetentionPolicy::MaxAge(ConfigDuration::from_secs(5)),
+            entries,
+            10_000,
+        );
+        assert_eq!(evicted, keys(&[1]));
+    }
+
+    #[test]
+    fn test_pinned_entries_are_kept_over_cap() {
+        let entries = vec![entry(1, 10, 100, true), entry(2, 10, 200, true)];
+        assert!(select_evictions(RetentionPolicy::MaxEntries(1), entries.clone(), 0).is_empty());
+        assert!(select_evictions(RetentionPolicy::Pinned, entries, 0).is_empty());
+    }
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use std::{fs, io::ErrorKind, path::Path};
+
+use alloy::primitives::B256;
+use eyre::Result;
+
+#[cfg(not(target_arch = "wasm32"))]
+use helios_core::database::{decode_checkpoint, file::FileStore, redb::RedbStore};
+
+use crate::config::Config;
+
+pub use helios_core::database::{Checkpoint, Database, EntryInfo};
+
+pub mod gc;
+pub mod sync_committees;
+pub mod usage;
+
+/// Name of the file the embedded store keeps in the data dir.
+#[cfg(not(target_arch = "wasm32"))]
+const EMBEDDED_FILE: &str = "helios.redb";
+
+/// A [Database] the client opens from its config, once for each of its parts.
+pub trait OpenDatabase: Database + Clone {
+    fn new(config: &Config) -> Result<Self>
+    where
+        Self: Sized;
+}
+
+/// A directory per namespace and a file per entry in the data dir.
+#[cfg(not(target_arch = "wasm32"))]
+pub type FileDB = FileStore;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl OpenDatabase for FileDB {
+    fn new(config: &Config) -> Result<Self> {
+        let Some(data_dir) = &config.data_dir else {
+            eyre::bail!("data dir not in config")
+        };
+
+        let db = FileStore::open(data_dir);
+        migrate_checkpoint_file(&db, data_dir)?;
+        Ok(db)
+    }
+}
+
+/// Every namespace in a single embedded file in the data dir, for long running nodes.
+#[cfg(not(target_arch = "wasm32"))]
+pub type EmbeddedDB = RedbStore;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl OpenDatabase for EmbeddedDB {
+    fn new(config: &Config) -> Result<Self> {
+        let Some(data_dir) = &config.data_dir else {
+            eyre::bail!("data dir not in config")
+        };
+
+        let db = RedbStore::open(data_dir.join(EMBEDDED_FILE))?;
+        migrate_checkpoint_file(&db, data_dir)?;
+        Ok(db)
+    }
+}
+
+/// The database an embedder supplied with
+/// [`with_database`](crate::builder::EthereumClientBuilder::with_database).
+pub type SharedDB = Arc<dyn Database>;
+
+impl OpenDatabase for SharedDB {
+    fn new(config: &Config) -> Result<Self> {
+        config
+            .database
+            .clone()
+            .ok_or_else(|| eyre::eyre!("no database supplied to the client builder"))
+    }
+}
+
+/// Checkpoints saved by earlier versions are kept in a `checkpoint` file of the data dir,
+/// which is moved into `db` on the first start that finds it. A checkpoint already in `db`
+/// is newer, so the file is then only removed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn migrate_checkpoint_file(db: &impl Database, data_dir: &Path) -> Result<()> {
+    let path = data_dir.join("checkpoint");
+    if !path.is_file() {
+        return Ok(());
+    }
+
+    let buf = match fs::read(&path) {
+        Ok(buf) => buf,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    if db.load_checkpoint().is_err() {
+        match decode_checkpoint(&buf) {
+            Some((root, Some(slot))) => db.save_checkpoint_with_slot(Checkpoint { root, slot })?,
+            Some((root, None)) => db.save_checkpoint(root)?,
+            None => {}
+        }
+    }
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+/// Keeps nothing but the checkpoint of the config, for clients that do not persist.
+#[derive(Debug, Clone)]
+pub struct ConfigDB {
+    checkpoint: B256,
+}
+
+impl OpenDatabase for ConfigDB {
+    fn new(config: &Config) -> Result<Self> {
+        Ok(Self {
+            checkpoint: config.checkpoint.unwrap_or(config.default_checkpoint),
+        })
+    }
+}
+
+impl Database for ConfigDB {
+    fn put(&self, _namespace: &str, _key: &[u8], _value: &[u8]) -> Result<()> {
+        Ok(())
+    }
+
+    fn get(&self, _namespace: &str, _key: &[u8]) -> Result<Option<Vec<u8>>> {
+        Ok(None)
+    }
+
+    fn delete(&self, _namespace: &str, _keys: &[Vec<u8>]) -> Result<()> {
+        Ok(())
+    }
+
+    fn set_pinned(&self, _namespace: &str, _key: &[u8], _pinned: bool) -> Result<()> {
+        Ok(())
+    }
+
+    fn entries(&self, _namespace: &str) -> Result<Vec<EntryInfo>> {
+        Ok(Vec::new())
+    }
+
+    fn load_checkpoint(&self) -> Result<B256> {
+        Ok(self.checkpoint)
+    }
+
+    fn save_checkpoint(&self, _checkpoint: B256) -> Result<()> {
+        Ok(())
+    }
+
+    fn save_checkpoint_with_slot(&self, _checkpoint: Checkpoint) -> Result<()> {
+        Ok(())
+    }
+}
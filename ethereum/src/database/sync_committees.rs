@@ -0,0 +1,125 @@
+//! Persistence of verified sync committees in a [Database] namespace.
+
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use alloy::primitives::B256;
+use eyre::Result;
+use tracing::warn;
+use tree_hash::TreeHash;
+
+use helios_consensus_core::consensus_spec::ConsensusSpec;
+use helios_consensus_core::types::SyncCommittee;
+use helios_core::types::SyncCommitteeInfo;
+
+use super::gc::SYNC_COMMITTEES;
+use super::Database;
+
+/// Where the light client keeps the sync committees it verifies.
+pub trait SyncCommitteeStore: Debug + Send + Sync + 'static {
+    /// Saves `committee`, pinning the committees of the `pinned` periods and unpinning any
+    /// other.
+    fn save(&self, committee: &SyncCommitteeInfo, pinned: [u64; 2]) -> Result<()>;
+    fn load(&self, period: u64) -> Result<Option<SyncCommitteeInfo>>;
+    /// Periods with a saved committee, in ascending order.
+    fn periods(&self) -> Result<Vec<u64>>;
+}
+
+/// Keeps each committee as a JSON entry keyed by its period.
+pub struct DbSyncCommitteeStore<S: ConsensusSpec, DB: Database> {
+    db: DB,
+    phantom: PhantomData<S>,
+}
+
+impl<S: ConsensusSpec, DB: Database> DbSyncCommitteeStore<S, DB> {
+    pub fn new(db: DB) -> Self {
+        Self {
+            db,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: ConsensusSpec, DB: Database> Debug for DbSyncCommitteeStore<S, DB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DbSyncCommitteeStore").finish_non_exhaustive()
+    }
+}
+
+impl<S: ConsensusSpec, DB: Database> SyncCommitteeStore for DbSyncCommitteeStore<S, DB> {
+    fn save(&self, committee: &SyncCommitteeInfo, pinned: [u64; 2]) -> Result<()> {
+        let value = serde_json::to_vec(committee)?;
+        self.db.put(
+            SYNC_COMMITTEES.name,
+            &committee.period.to_be_bytes(),
+            &value,
+        )?;
+
+        for entry in self.db.entries(SYNC_COMMITTEES.name)? {
+            let Some(period) = period_of(&entry.key) else {
+                continue;
+            };
+            let pin = pinned.contains(&period);
+            if pin != entry.pinned {
+                self.db.set_pinned(SYNC_COMMITTEES.name, &entry.key, pin)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Committees whose members no longer hash to their root are treated as missing, so a
+    /// damaged entry is never served as verified.
+    fn load(&self, period: u64) -> Result<Option<SyncCommitteeInfo>> {
+        let Some(value) = self.db.get(SYNC_COMMITTEES.name, &period.to_be_bytes())? else {
+            return Ok(None);
+        };
+        let committee: SyncCommitteeInfo = serde_json::from_slice(&value)?;
+
+        let root = committee_root::<S>(&committee);
+        if committee.period != period || root != Some(committee.root) {
+            warn!(
+                target: "helios::database",
+                period,
+                "stored sync committee does not match its root"
+            );
+            return Ok(None);
+        }
+
+        Ok(Some(committee))
+    }
+
+    fn periods(&self) -> Result<Vec<u64>> {
+        let mut periods = self
+            .db
+            .entries(SYNC_COMMITTEES.name)?
+            .iter()
+            .filter_map(|entry| period_of(&entry.key))
+            .collect::<Vec<_>>();
+        periods.sort_unstable();
+        Ok(periods)
+    }
+}
+
+/// The SSZ hash tree root of the committee `info` describes, if it has as many members as a
+/// committee of `S`.
+pub fn committee_root<S: ConsensusSpec>(info: &SyncCommitteeInfo) -> Option<B256> {
+    if info.pubkeys.len() as u64 != S::sync_committee_size() {
+        return None;
+    }
+
+    let committee = SyncCommittee::<S> {
+        pubkeys: info
+            .pubkeys
+            .iter()
+            .map(|pubkey| (*pubkey).into())
+            .collect::<Vec<_>>()
+            .into(),
+        aggregate_pubkey: info.aggregate_pubkey.into(),
+    };
+    Some(committee.tree_hash_root())
+}
+
+fn period_of(key: &[u8]) -> Option<u64> {
+    Some(u64::from_be_bytes(key.try_into().ok()?))
+}
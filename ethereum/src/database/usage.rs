@@ -0,0 +1,39 @@
+//! Persistence of the provider usage accounting in a [Database] namespace.
+
+use eyre::Result;
+
+use helios_core::execution::usage::{DailyUsage, UsageStore};
+
+use super::gc::USAGE;
+use super::Database;
+
+/// Keeps each day of provider usage as a JSON entry keyed by its day number.
+pub struct DbUsageStore<DB: Database> {
+    db: DB,
+}
+
+impl<DB: Database> DbUsageStore<DB> {
+    pub fn new(db: DB) -> Self {
+        Self { db }
+    }
+}
+
+impl<DB: Database> UsageStore for DbUsageStore<DB> {
+    fn load(&self) -> Result<Vec<DailyUsage>> {
+        let mut days = Vec::new();
+        for entry in self.db.entries(USAGE.name)? {
+            if let Some(value) = self.db.get(USAGE.name, &entry.key)? {
+                days.push(serde_json::from_slice(&value)?);
+            }
+        }
+        Ok(days)
+    }
+
+    fn save(&self, days: &[DailyUsage]) -> Result<()> {
+        for day in days {
+            let value = serde_json::to_vec(day)?;
+            self.db.put(USAGE.name, &day.day.to_be_bytes(), &value)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,271 @@
+//! Finality as the consensus client observes it: how far the finalized checkpoint trails the
+//! head, whether finality has stalled, and when a given slot can be expected to finalize.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use tracing::{info, warn};
+
+use helios_consensus_core::consensus_spec::ConsensusSpec;
+use helios_consensus_core::types::Slot;
+
+use crate::constants::{FINALITY_HISTORY_LENGTH, FINALITY_STALL_EPOCHS};
+
+/// When a slot is expected to finalize, in slots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalityWindow {
+    Finalized,
+    /// Finality has not advanced for too long to predict when it will.
+    Stalled,
+    Pending {
+        earliest: Slot,
+        expected: Slot,
+        latest: Slot,
+    },
+}
+
+/// Follows the finalized checkpoint across finality updates.
+///
+/// Every time finality advances, the distance from the current slot back to the new
+/// finalized slot is recorded as a lag sample. On a healthy chain the lag stays around two
+/// epochs, so the samples predict when a recent slot will finalize. Once the finalized slot
+/// trails the current slot by [`FINALITY_STALL_EPOCHS`] epochs finality counts as stalled,
+/// which is logged when it starts and ends. Lags measured while stalled are left out of the
+/// history so a recovery does not widen the estimates for good.
+#[derive(Debug, Default)]
+pub struct FinalityTracker {
+    history: Mutex<FinalityHistory>,
+}
+
+#[derive(Debug, Default)]
+struct FinalityHistory {
+    finalized: Option<Slot>,
+    /// Lags of the most recent finality advances, oldest first.
+    lags: VecDeque<u64>,
+    stalled: bool,
+}
+
+impl FinalityTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the finalized slot seen at the `current` slot.
+    pub fn observe<S: ConsensusSpec>(&self, finalized: Slot, current: Slot) {
+        let lag = current.since(finalized);
+        let stalled = lag > stall_threshold::<S>();
+        let mut history = self.history.lock().unwrap();
+
+        if history.finalized.is_some_and(|prev| finalized > prev) && !stalled {
+            if history.lags.len() == FINALITY_HISTORY_LENGTH {
+                history.lags.pop_front();
+            }
+            history.lags.push_back(lag);
+        }
+        history.finalized = Some(
+            history
+                .finalized
+                .map_or(finalized, |prev| prev.max(finalized)),
+        );
+
+        if stalled && !history.stalled {
+            warn!(
+                target: "helios::consensus",
+                finalized_slot = %finalized,
+                epochs = lag / S::slots_per_epoch(),
+                "finality appears to be stalled"
+            );
+        } else if !stalled && history.stalled {
+            info!(target: "helios::consensus", finalized_slot = %finalized, "finality resumed");
+        }
+        history.stalled = stalled;
+    }
+
+    /// Whether finality stalled as of the last observation.
+    pub fn stalled(&self) -> bool {
+        self.history.lock().unwrap().stalled
+    }
+
+    /// When `slot` is expected to be finalized, seen from the `current` slot.
+    ///
+    /// A slot finalizes together with the checkpoint of the first epoch starting at or after
+    /// it, which is expected once the head is the recently observed lag past that checkpoint.
+    /// The window spans the smallest to largest lag in the history, or the nominal two to
+    /// three epochs before any finality advance has been seen. Finality only moves at epoch
+    /// boundaries, so no estimate is earlier than the next one.
+    pub fn estimate<S: ConsensusSpec>(&self, slot: Slot, current: Slot) -> FinalityWindow {
+        let history = self.history.lock().unwrap();
+        let Some(finalized) = history.finalized else {
+            return FinalityWindow::Stalled;
+        };
+        if slot <= finalized {
+            return FinalityWindow::Finalized;
+        }
+        if current.since(finalized) > stall_threshold::<S>() {
+            return FinalityWindow::Stalled;
+        }
+
+        let epoch = S::slots_per_epoch();
+        let (min, median, max) = if history.lags.is_empty() {
+            (2 * epoch, 2 * epoch, 3 * epoch)
+        } else {
+            let mut lags = Vec::from(history.lags.clone());
+            lags.sort_unstable();
+            (lags[0], lags[lags.len() / 2], lags[lags.len() - 1])
+        };
+
+        let checkpoint = if slot.is_epoch_start::<S>() {
+            slot
+        } else {
+            (slot.epoch::<S>() + 1).start_slot::<S>()
+        };
+        let next_boundary = (current.epoch::<S>() + 1).start_slot::<S>();
+        let at = |lag: u64| (checkpoint + lag).max(next_boundary);
+
+        FinalityWindow::Pending {
+            earliest: at(min),
+            expected: at(median),
+            latest: at(max),
+        }
+    }
+}
+
+fn stall_threshold<S: ConsensusSpec>() -> u64 {
+    FINALITY_STALL_EPOCHS * S::slots_per_epoch()
+}
+
+#[cfg(test)]
+mod tests {
+    use helios_consensus_core::consensus_spec::MainnetConsensusSpec as Mainnet;
+
+    use super::*;
+
+    /// A tracker that saw finality advance by an epoch each epoch, `lags[i]` slots behind.
+    fn tracker_with_lags(start: u64, lags: &[u64]) -> FinalityTracker {
+        let tracker = FinalityTracker::new();
+        tracker.observe::<Mainnet>(Slot::new(start), Slot::new(start + 64));
+        for (i, lag) in lags.iter().enumerate() {
+            let finalized = Slot::new(start + 32 * (i as u64 + 1));
+            tracker.observe::<Mainnet>(finalized, finalized + *lag);
+        }
+        tracker
+    }
+
+    #[test]
+    fn test_finalized_slot() {
+        let tracker = tracker_with_lags(3200, &[]);
+        assert_eq!(
+            tracker.estimate::<Mainnet>(Slot::new(3199), Slot::new(3264)),
+            FinalityWindow::Finalized
+        );
+        assert_eq!(
+            tracker.estimate::<Mainnet>(Slot::new(3200), Slot::new(3264)),
+            FinalityWindow::Finalized
+        );
+    }
+
+    #[test]
+    fn test_nominal_window_without_history() {
+        let tracker = tracker_with_lags(3200, &[]);
+        let window = tracker.estimate::<Mainnet>(Slot::new(3210), Slot::new(3220));
+        // finalizes with the checkpoint at 3232, two to three epochs later
+        assert_eq!(
+            window,
+            FinalityWindow::Pending {
+                earliest: Slot::new(3296),
+                expected: Slot::new(3296),
+                latest: Slot::new(3328),
+            }
+        );
+    }
+
+    #[test]
+    fn test_window_follows_observed_lags() {
+        let tracker = tracker_with_lags(3200, &[66, 65, 70, 65, 90]);
+        let finalized = Slot::new(3200 + 32 * 5);
+        assert!(!tracker.stalled());
+
+        let window = tracker.estimate::<Mainnet>(finalized + 40, finalized + 50);
+        let checkpoint = finalized + 64;
+        assert_eq!(
+            window,
+            FinalityWindow::Pending {
+                earliest: checkpoint + 65,
+                expected: checkpoint + 66,
+                latest: checkpoint + 90,
+            }
+        );
+
+        // the block of an epoch's first slot is that epoch's checkpoint
+        let FinalityWindow::Pending { expected, .. } =
+            tracker.estimate::<Mainnet>(checkpoint, finalized + 50)
+        else {
+            panic!("expected a pending window");
+        };
+        assert_eq!(expected, checkpoint + 66);
+    }
+
+    #[test]
+    fn test_estimate_not_before_next_epoch() {
+        let tracker = tracker_with_lags(3200, &[64, 64, 64]);
+        let finalized = Slot::new(3296);
+
+        // finality is running late, so the observed lag already passed
+        let current = finalized + 100;
+        let window = tracker.estimate::<Mainnet>(finalized + 1, current);
+        let next_boundary = Slot::new(3424);
+        assert_eq!(
+            window,
+            FinalityWindow::Pending {
+                earliest: next_boundary,
+                expected: next_boundary,
+                latest: next_boundary,
+            }
+        );
+    }
+
+    #[test]
+    fn test_stall() {
+        let tracker = tracker_with_lags(3200, &[64, 66, 65]);
+        let finalized = Slot::new(3296);
+
+        // the head moves on but finality does not
+        let current = finalized + FINALITY_STALL_EPOCHS * 32 + 1;
+        tracker.observe::<Mainnet>(finalized, current);
+        assert!(tracker.stalled());
+        assert_eq!(
+            tracker.estimate::<Mainnet>(finalized + 10, current),
+            FinalityWindow::Stalled
+        );
+        // already finalized slots stay finalized
+        assert_eq!(
+            tracker.estimate::<Mainnet>(finalized, current),
+            FinalityWindow::Finalized
+        );
+
+        // finality creeps forward, but still too far behind to count as a lag sample
+        tracker.observe::<Mainnet>(finalized + 32, finalized + 208);
+        assert!(tracker.stalled());
+        let resumed = Slot::new(3456);
+        tracker.observe::<Mainnet>(resumed, resumed + 64);
+        assert!(!tracker.stalled());
+
+        let FinalityWindow::Pending { latest, .. } =
+            tracker.estimate::<Mainnet>(resumed + 40, resumed + 50)
+        else {
+            panic!("expected a pending window");
+        };
+        assert_eq!(latest, resumed + 64 + 66);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let lags = vec![100; FINALITY_HISTORY_LENGTH];
+        let tracker = tracker_with_lags(3200, &[&[64][..], &lags[..]].concat());
+        assert_eq!(
+            tracker.history.lock().unwrap().lags.len(),
+            FINALITY_HISTORY_LENGTH
+        );
+        assert!(!tracker.history.lock().unwrap().lags.contains(&64));
+    }
+}
@@ -5,9 +5,11 @@ use rpc::http_rpc::HttpRpc;
 use spec::Ethereum;
 
 pub mod builder;
+pub mod clock;
 pub mod config;
 pub mod consensus;
 pub mod database;
+pub mod finality;
 pub mod rpc;
 pub mod spec;
 
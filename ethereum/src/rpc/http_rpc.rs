@@ -10,6 +10,7 @@ use helios_consensus_core::{
     consensus_spec::ConsensusSpec,
     types::{BeaconBlock, Bootstrap, FinalityUpdate, OptimisticUpdate, Update},
 };
+use helios_core::client::compression::http_client;
 use helios_core::errors::RpcError;
 
 use super::ConsensusRpc;
@@ -18,6 +19,7 @@ use crate::constants::MAX_REQUEST_LIGHT_CLIENT_UPDATES;
 #[derive(Debug)]
 pub struct HttpRpc {
     rpc: String,
+    client: reqwest::Client,
 }
 
 #[derive(Deserialize, Debug)]
@@ -33,9 +35,9 @@ struct HttpRpcError {
     message: String,
 }
 
-async fn get<R: DeserializeOwned>(req: &str) -> Result<R> {
+async fn get<R: DeserializeOwned>(client: &reqwest::Client, req: &str) -> Result<R> {
     let response = retry(
-        || async { Ok::<_, eyre::Report>(reqwest::get(req).await?) },
+        || async { Ok::<_, eyre::Report>(client.get(req).send().await?) },
         BackoffSettings::default(),
     )
     .await?;
@@ -67,6 +69,15 @@ impl<S: ConsensusSpec> ConsensusRpc<S> for HttpRpc {
     fn new(rpc: &str) -> Self {
         HttpRpc {
             rpc: rpc.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn with_compression(self, compression: bool) -> Self {
+        // the default client is kept if a configured one cannot be built
+        match http_client(compression) {
+            Ok(client) => HttpRpc { client, ..self },
+            Err(_) => self,
         }
     }
 
@@ -77,8 +88,9 @@ impl<S: ConsensusSpec> ConsensusRpc<S> for HttpRpc {
             self.rpc, root_hex
         );
 
-        let res: BootstrapResponse<S> =
-            get(&req).await.map_err(|e| RpcError::new("bootstrap", e))?;
+        let res: BootstrapResponse<S> = get(&self.client, &req)
+            .await
+            .map_err(|e| RpcError::new("bootstrap", e))?;
 
         Ok(res.data)
     }
@@ -90,14 +102,16 @@ impl<S: ConsensusSpec> ConsensusRpc<S> for HttpRpc {
             self.rpc, period, count
         );
 
-        let res: Vec<UpdateData<S>> = get(&req).await.map_err(|e| RpcError::new("updates", e))?;
+        let res: Vec<UpdateData<S>> = get(&self.client, &req)
+            .await
+            .map_err(|e| RpcError::new("updates", e))?;
 
         Ok(res.into_iter().map(|d| d.data).collect())
     }
 
     async fn get_finality_update(&self) -> Result<FinalityUpdate<S>> {
         let req = format!("{}/eth/v1/beacon/light_client/finality_update", self.rpc);
-        let res: FinalityUpdateResponse<S> = get(&req)
+        let res: FinalityUpdateResponse<S> = get(&self.client, &req)
             .await
             .map_err(|e| RpcError::new("finality_update", e))?;
 
@@ -106,7 +120,7 @@ impl<S: ConsensusSpec> ConsensusRpc<S> for HttpRpc {
 
     async fn get_optimistic_update(&self) -> Result<OptimisticUpdate<S>> {
         let req = format!("{}/eth/v1/beacon/light_client/optimistic_update", self.rpc);
-        let res: OptimisticUpdateResponse<S> = get(&req)
+        let res: OptimisticUpdateResponse<S> = get(&self.client, &req)
             .await
             .map_err(|e| RpcError::new("optimistic_update", e))?;
 
@@ -115,18 +129,30 @@ impl<S: ConsensusSpec> ConsensusRpc<S> for HttpRpc {
 
     async fn get_block(&self, slot: u64) -> Result<BeaconBlock<S>> {
         let req = format!("{}/eth/v2/beacon/blocks/{}", self.rpc, slot);
-        let res: BeaconBlockResponse<S> =
-            get(&req).await.map_err(|e| RpcError::new("blocks", e))?;
+        let res: BeaconBlockResponse<S> = get(&self.client, &req)
+            .await
+            .map_err(|e| RpcError::new("blocks", e))?;
 
         Ok(res.data.message)
     }
 
     async fn chain_id(&self) -> Result<u64> {
         let req = format!("{}/eth/v1/config/spec", self.rpc);
-        let res: SpecResponse = get(&req).await.map_err(|e| RpcError::new("spec", e))?;
+        let res: SpecResponse = get(&self.client, &req)
+            .await
+            .map_err(|e| RpcError::new("spec", e))?;
 
         Ok(res.data.chain_id)
     }
+
+    async fn get_genesis_root(&self) -> Result<B256> {
+        let req = format!("{}/eth/v1/beacon/genesis", self.rpc);
+        let res: GenesisResponse = get(&self.client, &req)
+            .await
+            .map_err(|e| RpcError::new("genesis", e))?;
+
+        Ok(res.data.genesis_validators_root)
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -165,6 +191,16 @@ struct BootstrapResponse<S: ConsensusSpec> {
     data: Bootstrap<S>,
 }
 
+#[derive(Deserialize, Debug)]
+struct GenesisResponse {
+    data: Genesis,
+}
+
+#[derive(Deserialize, Debug)]
+struct Genesis {
+    genesis_validators_root: B256,
+}
+
 #[derive(Deserialize, Debug)]
 struct SpecResponse {
     data: Spec,
@@ -69,6 +69,10 @@ impl<S: ConsensusSpec> ConsensusRpc<S> for MockRpc {
     async fn chain_id(&self) -> Result<u64> {
         eyre::bail!("not implemented")
     }
+
+    async fn get_genesis_root(&self) -> Result<B256> {
+        eyre::bail!("not implemented")
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -14,10 +14,19 @@ use helios_consensus_core::{
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 pub trait ConsensusRpc<S: ConsensusSpec>: Send + Sync + 'static {
     fn new(path: &str) -> Self;
+    /// Whether responses are requested compressed, for transports that negotiate it.
+    fn with_compression(self, _compression: bool) -> Self
+    where
+        Self: Sized,
+    {
+        self
+    }
     async fn get_bootstrap(&self, checkpoint: B256) -> Result<Bootstrap<S>>;
     async fn get_updates(&self, period: u64, count: u8) -> Result<Vec<Update<S>>>;
     async fn get_finality_update(&self) -> Result<FinalityUpdate<S>>;
     async fn get_optimistic_update(&self) -> Result<OptimisticUpdate<S>>;
     async fn get_block(&self, slot: u64) -> Result<BeaconBlock<S>>;
     async fn chain_id(&self) -> Result<u64>;
+    /// The genesis validators root of the chain the server follows.
+    async fn get_genesis_root(&self) -> Result<B256>;
 }
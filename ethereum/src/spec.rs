@@ -1,67 +1,115 @@
 use alloy::{
-    consensus::{
-        proofs::{calculate_transaction_root, calculate_withdrawals_root},
-        BlockHeader, Receipt, ReceiptWithBloom, TxReceipt, TxType, TypedTransaction,
-    },
+    consensus::{BlockHeader, TxEnvelope, TxReceipt, TxType, TypedTransaction},
+    eips::eip2718::Decodable2718,
     network::{BuildResult, Network, NetworkWallet, TransactionBuilder, TransactionBuilderError},
-    primitives::{Address, Bytes, ChainId, TxKind, U256},
-    rpc::types::{AccessList, Log, TransactionRequest},
+    primitives::{keccak256, Address, Bytes, ChainId, TxKind, U256},
+    rlp::{self, Encodable},
+    rpc::types::{AccessList, BlockTransactions, Log, TransactionRequest},
 };
+use alloy_trie::EMPTY_ROOT_HASH;
 use revm::primitives::{BlobExcessGasAndPrice, BlockEnv, TxEnv};
 
-use helios_core::{fork_schedule::ForkSchedule, network_spec::NetworkSpec};
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::preflight::RawTransaction;
+use helios_core::fork_schedule::ForkSchedule;
+use helios_core::network_spec::{
+    check_transactions, check_withdrawals, encode_block_parts, NetworkSpec,
+};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Ethereum;
 
 impl NetworkSpec for Ethereum {
     fn encode_receipt(receipt: &Self::ReceiptResponse) -> Vec<u8> {
+        let mut out = Vec::new();
+        Self::encode_receipt_into(receipt, &mut out);
+        out
+    }
+
+    // writes the fields of a `ReceiptWithBloom` straight from the rpc receipt rather than
+    // building one, which would copy every log
+    fn encode_receipt_into(receipt: &Self::ReceiptResponse, out: &mut Vec<u8>) {
         let tx_type = receipt.transaction_type();
         let receipt = receipt.inner.as_receipt_with_bloom().unwrap();
-        let logs = receipt
-            .logs()
-            .iter()
-            .map(|l| l.inner.clone())
-            .collect::<Vec<_>>();
-
-        let consensus_receipt = Receipt {
-            cumulative_gas_used: receipt.cumulative_gas_used(),
-            status: receipt.status_or_post_state(),
-            logs,
+        let status = receipt.status_or_post_state();
+        let cumulative_gas_used = receipt.cumulative_gas_used();
+        let bloom = receipt.bloom();
+        let logs = receipt.logs();
+
+        let logs_header = rlp::Header {
+            list: true,
+            payload_length: logs.iter().map(|log| log.inner.length()).sum(),
+        };
+        let header = rlp::Header {
+            list: true,
+            payload_length: status.length()
+                + cumulative_gas_used.length()
+                + bloom.length()
+                + logs_header.length_with_payload(),
         };
 
-        let rwb = ReceiptWithBloom::new(consensus_receipt, receipt.bloom());
-        let encoded = alloy::rlp::encode(rwb);
-
-        match tx_type {
-            TxType::Legacy => encoded,
-            _ => [vec![tx_type as u8], encoded].concat(),
+        if tx_type != TxType::Legacy {
+            out.push(tx_type as u8);
+        }
+        header.encode(out);
+        status.encode(out);
+        cumulative_gas_used.encode(out);
+        bloom.encode(out);
+        logs_header.encode(out);
+        for log in logs {
+            log.inner.encode(out);
         }
     }
 
-    fn is_hash_valid(block: &Self::BlockResponse) -> bool {
-        if block.header.hash_slow() != block.header.hash {
-            return false;
+    fn encode_header(header: &Self::HeaderResponse) -> Vec<u8> {
+        rlp::encode(&header.inner)
+    }
+
+    fn encode_block(block: &Self::BlockResponse) -> Option<Vec<u8>> {
+        if !block.uncles.is_empty() {
+            return None;
         }
 
+        let transactions = match &block.transactions {
+            BlockTransactions::Full(txs) => txs.iter().map(|tx| &tx.inner).collect(),
+            BlockTransactions::Hashes(hashes) if hashes.is_empty() => Vec::new(),
+            _ => return None,
+        };
+        let withdrawals = block.withdrawals.as_ref().map(|w| w.0.as_slice());
+
+        Some(encode_block_parts(
+            &block.header.inner,
+            &transactions,
+            withdrawals,
+        ))
+    }
+
+    fn check_body(block: &Self::BlockResponse) -> Result<(), ExecutionError> {
+        let hash = block.header.hash;
         if let Some(txs) = block.transactions.as_transactions() {
-            let txs_root = calculate_transaction_root(
-                &txs.iter().map(|t| t.clone().inner).collect::<Vec<_>>(),
-            );
-            if txs_root != block.header.transactions_root {
-                return false;
+            check_transactions(hash, block.header.transactions_root, txs, |tx| {
+                tx.inner.clone()
+            })?;
+        } else if block
+            .transactions
+            .as_hashes()
+            .is_some_and(|hashes| hashes.is_empty())
+        {
+            // without full transactions the root can only be checked when there are none
+            if block.header.transactions_root != EMPTY_ROOT_HASH {
+                return Err(ExecutionError::BlockBodyMismatch(hash, "transactions root"));
             }
         }
 
-        if let Some(withdrawals) = &block.withdrawals {
-            let withdrawals_root =
-                calculate_withdrawals_root(&withdrawals.iter().copied().collect::<Vec<_>>());
-            if Some(withdrawals_root) != block.header.withdrawals_root {
-                return false;
-            }
-        }
+        check_withdrawals(
+            hash,
+            block.header.withdrawals_root,
+            block.withdrawals.as_ref().map(|w| w.0.as_slice()),
+        )
+    }
 
-        true
+    fn is_header_hash_valid(header: &Self::HeaderResponse) -> bool {
+        keccak256(Self::encode_header(header)) == header.hash
     }
 
     fn receipt_contains(list: &[Self::ReceiptResponse], elem: &Self::ReceiptResponse) -> bool {
@@ -78,6 +126,19 @@ impl NetworkSpec for Ethereum {
         receipt.inner.logs().to_vec()
     }
 
+    fn decode_transaction(mut bytes: &[u8]) -> Result<RawTransaction, ExecutionError> {
+        let invalid = ExecutionError::InvalidRawTransaction;
+        let tx = TxEnvelope::decode_2718(&mut bytes).map_err(|err| invalid(err.to_string()))?;
+        if !bytes.is_empty() {
+            return Err(invalid("trailing bytes after the transaction".to_string()));
+        }
+        let sender = tx
+            .recover_signer()
+            .map_err(|err| invalid(err.to_string()))?;
+
+        Ok(RawTransaction::new(&tx, sender))
+    }
+
     fn tx_env(tx: &Self::TransactionRequest) -> TxEnv {
         TxEnv {
             caller: tx.from.unwrap_or_default(),
@@ -99,33 +160,31 @@ impl NetworkSpec for Ethereum {
             gas_priority_fee:
                 <TransactionRequest as TransactionBuilder<Self>>::max_priority_fee_per_gas(tx)
                     .map(U256::from),
-            max_fee_per_blob_gas:
-                <TransactionRequest as TransactionBuilder<Self>>::max_fee_per_gas(tx)
-                    .map(U256::from),
-            blob_hashes: tx
-                .blob_versioned_hashes
-                .as_ref()
-                .map(|v| v.to_vec())
-                .unwrap_or_default(),
+            max_fee_per_blob_gas: tx.max_fee_per_blob_gas.map(U256::from),
+            // a request may carry the sidecar alone, the hashes following from its commitments
+            blob_hashes: match (&tx.blob_versioned_hashes, &tx.sidecar) {
+                (Some(hashes), _) => hashes.clone(),
+                (None, Some(sidecar)) => sidecar.versioned_hashes().collect(),
+                (None, None) => Vec::new(),
+            },
             authorization_list: None,
         }
     }
 
-    fn block_env(block: &Self::BlockResponse, fork_schedule: &ForkSchedule) -> BlockEnv {
-        let is_prague = block.header.timestamp >= fork_schedule.prague_timestamp;
-        let blob_excess_gas_and_price = block
-            .header
+    fn block_env(header: &Self::HeaderResponse, fork_schedule: &ForkSchedule) -> BlockEnv {
+        let is_prague = header.timestamp >= fork_schedule.prague_timestamp;
+        let blob_excess_gas_and_price = header
             .excess_blob_gas()
             .map(|v| BlobExcessGasAndPrice::new(v, is_prague));
 
         BlockEnv {
-            number: U256::from(block.header.number()),
-            coinbase: block.header.beneficiary(),
-            timestamp: U256::from(block.header.timestamp()),
-            gas_limit: U256::from(block.header.gas_limit()),
-            basefee: U256::from(block.header.base_fee_per_gas().unwrap_or(0_u64)),
-            difficulty: block.header.difficulty(),
-            prevrandao: block.header.mix_hash(),
+            number: U256::from(header.number()),
+            coinbase: header.beneficiary(),
+            timestamp: U256::from(header.timestamp()),
+            gas_limit: U256::from(header.gas_limit()),
+            basefee: U256::from(header.base_fee_per_gas().unwrap_or(0_u64)),
+            difficulty: header.difficulty(),
+            prevrandao: header.mix_hash(),
             blob_excess_gas_and_price,
         }
     }
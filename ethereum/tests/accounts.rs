@@ -0,0 +1,184 @@
+use alloy::primitives::{keccak256, U256};
+
+use helios_core::execution::code_cache::{CodeCache, CodeCacheConfig, CODE};
+use helios_core::execution::types::StateAnchor;
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::harness;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+type Harness = harness::Harness<Ethereum>;
+
+#[tokio::test]
+async fn test_get_account_and_storage() {
+    let chain = ChainBuilder::new(6).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let token = chain.token();
+    for (slot, value) in chain.storage(token) {
+        let stored = client
+            .get_storage_at(token, slot.into(), BlockTag::Latest)
+            .await
+            .unwrap();
+        assert_eq!(U256::from_be_bytes(stored.0), value);
+    }
+
+    let account = client
+        .get_account(token, None, BlockTag::Latest)
+        .await
+        .unwrap();
+    assert_eq!(account.code, chain.code(token).to_vec());
+}
+
+#[tokio::test]
+async fn test_code_cached_on_disk() {
+    let chain = ChainBuilder::new(19).length(2).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let dir = std::env::temp_dir().join(format!("helios-code-cache-{}", std::process::id()));
+    let config = CodeCacheConfig {
+        dir: Some(dir.clone()),
+        ..Default::default()
+    };
+
+    let token = chain.token();
+    let code_hash = keccak256(chain.code(token));
+    let entry = dir.join(CODE).join(alloy::hex::encode(code_hash));
+    let client = |cache| harness.client.clone().with_code_cache(cache);
+
+    let account = client(CodeCache::open(&config).unwrap())
+        .get_account(token, None, BlockTag::Number(1))
+        .await
+        .unwrap();
+    assert_eq!(account.code, chain.code(token).to_vec());
+    assert_eq!(std::fs::read(&entry).unwrap(), account.code);
+
+    // a corrupted entry is refetched from the provider and replaced
+    std::fs::write(&entry, [0xfe; 16]).unwrap();
+    let account = client(CodeCache::open(&config).unwrap())
+        .get_account(token, None, BlockTag::Number(2))
+        .await
+        .unwrap();
+    assert_eq!(account.code, chain.code(token).to_vec());
+    assert_eq!(std::fs::read(&entry).unwrap(), account.code);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn test_accounts_fetched_together() {
+    let chain = ChainBuilder::new(18).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let token = chain.token();
+    let slots = chain
+        .storage(token)
+        .into_iter()
+        .map(|(slot, _)| slot)
+        .collect::<Vec<_>>();
+    let mut requests = chain
+        .accounts()
+        .into_iter()
+        .map(|address| (address, Vec::new()))
+        .collect::<Vec<_>>();
+    requests.push((token, slots));
+    requests.push((token, Vec::new()));
+
+    let anchor = StateAnchor::Tag(BlockTag::Latest);
+    let accounts = client.get_accounts_at(&requests, &anchor).await.unwrap();
+    assert_eq!(accounts.len(), requests.len());
+    for ((address, slots), account) in requests.iter().zip(accounts) {
+        let account = account.unwrap();
+        let expected = client
+            .get_account(*address, Some(slots.as_slice()), BlockTag::Latest)
+            .await
+            .unwrap();
+        assert_eq!(account.code, chain.code(*address).to_vec());
+        assert_eq!(account.balance, expected.balance);
+        assert_eq!(account.slots, expected.slots);
+    }
+
+    // accounts without slots were cached like single reads
+    harness.rpc.disconnect();
+    let cached = requests
+        .into_iter()
+        .filter(|(_, slots)| slots.is_empty())
+        .collect::<Vec<_>>();
+    let accounts = client.get_accounts_at(&cached, &anchor).await.unwrap();
+    assert!(accounts.iter().all(Result::is_ok));
+}
+
+#[tokio::test]
+async fn test_rejected_code_batch_falls_back_per_account() {
+    let chain = ChainBuilder::new(184).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+    harness.rpc.fail_code_batches(1);
+
+    let requests = chain
+        .accounts()
+        .into_iter()
+        .chain([chain.token()])
+        .map(|address| (address, Vec::new()))
+        .collect::<Vec<_>>();
+    let anchor = StateAnchor::Tag(BlockTag::Latest);
+    let accounts = harness
+        .client
+        .get_accounts_at(&requests, &anchor)
+        .await
+        .unwrap();
+
+    // the verified proofs are kept and the code fetched account by account
+    assert_eq!(harness.rpc.calls("get_proof"), requests.len());
+    assert!(harness.rpc.calls("get_code") > 0);
+    for ((address, _), account) in requests.iter().zip(accounts) {
+        assert_eq!(account.unwrap().code, chain.code(*address).to_vec());
+    }
+}
+
+#[tokio::test]
+async fn test_local_methods_skip_provider_once_primed() {
+    let chain = ChainBuilder::new(17).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let mut addresses = chain.accounts();
+    addresses.push(chain.token());
+    for address in &addresses {
+        client
+            .get_account(*address, None, BlockTag::Latest)
+            .await
+            .unwrap();
+    }
+
+    // from here on any provider call panics and fails the test
+    harness.rpc.disconnect();
+
+    // eth_getBalance, eth_getTransactionCount and eth_getCode
+    for address in &addresses {
+        let account = client
+            .get_account(*address, None, BlockTag::Latest)
+            .await
+            .unwrap();
+        assert_eq!(account.code, chain.code(*address).to_vec());
+    }
+
+    // eth_blockNumber, eth_getBlockByNumber and eth_coinbase
+    let head = client.get_block(BlockTag::Latest, false).await.unwrap();
+    assert_eq!(head.header.number, chain.head().header.number);
+
+    // eth_getBlockByHash and the transaction lookups
+    let parent = client
+        .get_block_by_hash(head.header.parent_hash, true)
+        .await
+        .unwrap();
+    for tx in parent.transactions.txns() {
+        let stored = client.get_transaction(*tx.inner.tx_hash()).await.unwrap();
+        assert_eq!(stored.block_hash, Some(parent.header.hash));
+    }
+
+    // eth_blobBaseFee and helios_getPrevRandao
+    let blob_base_fee = client.blob_base_fee(BlockTag::Latest).await.unwrap();
+    assert_eq!(blob_base_fee, U256::from(1));
+    client.get_prev_randao(BlockTag::Latest).await.unwrap();
+}
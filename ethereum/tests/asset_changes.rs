@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{address, hex, Address, Bytes, I256, U256};
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol_types::SolValue;
+
+use helios_core::execution::asset_changes::{AssetChangeReport, TokenMetadata};
+use helios_core::execution::bundle::AccountOverride;
+use helios_core::execution::errors::EvmError;
+use helios_core::execution::evm::Evm;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::consensus::verified_client;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+const SENDER: Address = address!("00000000000000000000000000000000005e2de2");
+const OTHER: Address = address!("00000000000000000000000000000000000a11ce");
+/// Accounts outside the generated chain, so their state is proven empty before the
+/// overrides install their code.
+const TOKEN_A: Address = address!("00000000000000000000000000000000000a55e1");
+const TOKEN_B: Address = address!("00000000000000000000000000000000000a55e2");
+const ROUTER: Address = address!("00000000000000000000000000000000000a55e3");
+
+/// A token answering `decimals()` with 18 and `symbol()` with "TKN", and reverting on
+/// `name()`. `transfer`, `transferFrom` and `approve` emit their event without keeping any
+/// balances and return true.
+const TOKEN_CODE: [u8; 281] = hex!(
+    "60003560e01c8063313ce5671461004157806395d89b411461004c578063a9059cbb1461"
+    "006657806323b872dd146100a1578063095ea7b3146100de57600080fd5b601260005260"
+    "206000f35b6020600052600360205262544b4e60e81b60405260606000f35b6024356000"
+    "52600435337fddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523"
+    "b3ef60206000a3600160005260206000f35b6044356000526024356004357fddf252ad1b"
+    "e2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef60206000a360016000"
+    "5260206000f35b602435600052600435337f8c5be1e5ebec7d5bd14f71427d1e84f3dd03"
+    "14c0f7b2291e5b200ac8c7c3b92560206000a3600160005260206000f3"
+);
+
+/// Swaps for the transaction origin, moving 1000 of `TOKEN_A` from it to the router and 42
+/// of `TOKEN_B` back through `transferFrom`.
+const ROUTER_CODE: [u8; 169] = hex!(
+    "7f23b872dd00000000000000000000000000000000000000000000000000000000600052"
+    "32600452306024526103e860445260006000606460006000730000000000000000000000"
+    "0000000000000a55e15af1507f23b872dd00000000000000000000000000000000000000"
+    "000000000000000000600052306004523260245261002a60445260006000606460006000"
+    "7300000000000000000000000000000000000a55e25af15000"
+);
+
+const GAS_PRICE: u128 = 7;
+
+fn chain(seed: u64) -> MockChain {
+    ChainBuilder::new(seed)
+        .length(3)
+        .account(SENDER, 0, U256::from(10u128.pow(19)))
+        .build()
+}
+
+async fn simulate(
+    chain: &MockChain,
+    tx: &TransactionRequest,
+) -> Result<AssetChangeReport, EvmError> {
+    let (client, _, _) = verified_client::<Ethereum>(chain).await;
+
+    let code = |code: &'static [u8]| AccountOverride {
+        code: Some(Bytes::from_static(code)),
+        ..Default::default()
+    };
+    let overrides = HashMap::from([
+        (TOKEN_A, code(&TOKEN_CODE)),
+        (TOKEN_B, code(&TOKEN_CODE)),
+        (ROUTER, code(&ROUTER_CODE)),
+    ]);
+
+    Evm::new(
+        Arc::new(client),
+        chain.chain_id(),
+        chain.fork_schedule(),
+        BlockTag::Latest,
+    )
+    .with_state_overrides(overrides)
+    .simulate_asset_changes(tx)
+    .await
+}
+
+fn tx(to: Address, input: Vec<u8>) -> TransactionRequest {
+    TransactionRequest::default()
+        .with_from(SENDER)
+        .with_to(to)
+        .with_input(input)
+        .with_gas_limit(200_000)
+        .with_gas_price(GAS_PRICE)
+}
+
+fn fee(report: &AssetChangeReport) -> I256 {
+    I256::try_from(report.gas_used as u128 * GAS_PRICE).unwrap()
+}
+
+fn tkn(token: Address) -> TokenMetadata {
+    TokenMetadata {
+        token,
+        name: None,
+        symbol: Some("TKN".to_string()),
+        decimals: Some(18),
+    }
+}
+
+#[tokio::test]
+async fn test_ether_transfer() {
+    let chain = chain(155);
+    let value = U256::from(10u128.pow(18));
+    let tx = tx(OTHER, Vec::new()).with_value(value);
+
+    let report = simulate(&chain, &tx).await.unwrap();
+
+    assert!(report.success);
+    assert_eq!(report.gas_used, 21_000);
+    assert_eq!(
+        report.eth_delta,
+        -I256::try_from(value).unwrap() - fee(&report)
+    );
+    assert!(report.erc20_changes.is_empty());
+    assert!(report.tokens.is_empty());
+}
+
+#[tokio::test]
+async fn test_token_transfer() {
+    let chain = chain(156);
+    let input = [
+        hex!("a9059cbb").to_vec(),
+        (OTHER, U256::from(500)).abi_encode_params(),
+    ]
+    .concat();
+
+    let report = simulate(&chain, &tx(TOKEN_A, input)).await.unwrap();
+
+    assert!(report.success, "{:?}", report.revert_reason);
+    assert_eq!(report.eth_delta, -fee(&report));
+    assert_eq!(report.erc20_changes.len(), 1);
+    assert_eq!(report.erc20_changes[0].to, OTHER);
+    assert_eq!(
+        report.erc20_changes[0].delta,
+        I256::try_from(-500i64).unwrap()
+    );
+    assert_eq!(report.tokens, vec![tkn(TOKEN_A)]);
+}
+
+#[tokio::test]
+async fn test_swap_through_router() {
+    let chain = chain(157);
+
+    let report = simulate(&chain, &tx(ROUTER, Vec::new())).await.unwrap();
+
+    // the events are emitted by the tokens the router calls into
+    assert!(report.success, "{:?}", report.revert_reason);
+    let deltas = report
+        .erc20_changes
+        .iter()
+        .map(|change| (change.token, change.delta))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        deltas,
+        vec![
+            (TOKEN_A, I256::try_from(-1_000i64).unwrap()),
+            (TOKEN_B, I256::try_from(42i64).unwrap()),
+        ]
+    );
+    assert_eq!(report.tokens, vec![tkn(TOKEN_A), tkn(TOKEN_B)]);
+}
+
+#[tokio::test]
+async fn test_approval() {
+    let chain = chain(158);
+    let input = [
+        hex!("095ea7b3").to_vec(),
+        (ROUTER, U256::MAX).abi_encode_params(),
+    ]
+    .concat();
+
+    let report = simulate(&chain, &tx(TOKEN_B, input)).await.unwrap();
+
+    assert!(report.success, "{:?}", report.revert_reason);
+    assert_eq!(report.approvals.len(), 1);
+    assert_eq!(report.approvals[0].spender, ROUTER);
+    assert_eq!(report.approvals[0].amount, Some(U256::MAX));
+    assert!(report.erc20_changes.is_empty());
+    assert_eq!(report.tokens, vec![tkn(TOKEN_B)]);
+}
+
+#[tokio::test]
+async fn test_revert_still_charges_fee() {
+    let chain = chain(159);
+    // `name()` is the one getter the token reverts on
+    let report = simulate(&chain, &tx(TOKEN_A, hex!("06fdde03").to_vec()))
+        .await
+        .unwrap();
+
+    assert!(!report.success);
+    assert!(report.revert_reason.is_some());
+    assert!(report.gas_used > 21_000);
+    assert_eq!(report.eth_delta, -fee(&report));
+    assert!(report.tokens.is_empty());
+}
+
+#[tokio::test]
+async fn test_missing_sender_rejected() {
+    let chain = chain(160);
+    let tx = TransactionRequest::default()
+        .with_to(OTHER)
+        .with_value(U256::from(1));
+
+    let err = simulate(&chain, &tx).await.unwrap_err();
+    assert!(matches!(err, EvmError::InvalidTransaction(_)), "{err}");
+}
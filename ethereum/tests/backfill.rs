@@ -0,0 +1,160 @@
+use std::time::Duration;
+
+use alloy::primitives::{B256, U64};
+use tokio::time::Instant;
+
+use helios_core::execution::backfill::BackfillConfig;
+use helios_core::execution::state::State;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::harness;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::{BackfillProgress, BlockTag};
+use helios_ethereum::spec::Ethereum;
+
+type Harness = harness::Harness<Ethereum>;
+
+const HISTORY_LENGTH: usize = 33;
+
+fn state(rpc: &ChainRpc, concurrency: usize) -> State<Ethereum, ChainRpc> {
+    State::unsynced(HISTORY_LENGTH, rpc.clone()).with_backfill(BackfillConfig {
+        concurrency,
+        ..Default::default()
+    })
+}
+
+async fn backfilled(state: &State<Ethereum, ChainRpc>) {
+    state
+        .sync_progress()
+        .subscribe()
+        .wait_for(|progress| !progress.is_backfilling())
+        .await
+        .unwrap();
+}
+
+async fn assert_held(state: &State<Ethereum, ChainRpc>, chain: &MockChain, numbers: &[u64]) {
+    for number in numbers {
+        let block = state.get_block(BlockTag::Number(*number)).await;
+        let expected = chain.block(*number).unwrap();
+        assert_eq!(
+            block.map(|block| block.header.hash),
+            Some(expected.header.hash),
+            "block {number}"
+        );
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_backfill_fetches_window_concurrently() {
+    let chain = ChainBuilder::new(129).length(40).build();
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let latency = Duration::from_millis(100);
+    rpc.delay_blocks(latency);
+    let state = state(&rpc, 8);
+
+    let start = Instant::now();
+    state.push_block(chain.head().clone()).await;
+    let target = HISTORY_LENGTH as u64 - 1;
+    assert_eq!(
+        state.sync_progress().progress().backfill,
+        Some(BackfillProgress {
+            target_blocks: U64::from(target),
+            fetched_blocks: U64::ZERO,
+            stopped_at: None,
+        })
+    );
+    backfilled(&state).await;
+    let elapsed = start.elapsed();
+
+    // 32 blocks 8 at a time take four round trips, where one at a time would take 32
+    assert!(elapsed >= latency * 4, "{elapsed:?}");
+    assert!(elapsed < latency * 8, "{elapsed:?}");
+
+    let head = chain.head().header.number;
+    assert_eq!(state.oldest_block_number().await, Some(head - target));
+    let numbers = (head - target..=head).collect::<Vec<_>>();
+    assert_held(&state, &chain, &numbers).await;
+    assert_eq!(rpc.calls("get_block_by_number"), target as usize);
+    assert_eq!(rpc.calls("get_block"), 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_backfill_retries_failed_block() {
+    let chain = ChainBuilder::new(130).length(40).build();
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let head = chain.head().header.number;
+    let target = HISTORY_LENGTH as u64 - 1;
+    rpc.fail_block(head - 10, 2);
+    let state = state(&rpc, 4);
+
+    state.push_block(chain.head().clone()).await;
+    backfilled(&state).await;
+
+    // only the failed block is fetched again, the rest of the window once
+    assert_eq!(state.oldest_block_number().await, Some(head - target));
+    let numbers = (head - target..=head).collect::<Vec<_>>();
+    assert_held(&state, &chain, &numbers).await;
+    assert_eq!(rpc.calls("get_block_by_number"), target as usize + 2);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_backfill_stops_at_unavailable_block() {
+    let mut chain = ChainBuilder::new(131).length(40).build();
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let head = chain.head().header.number;
+    // served with a forged hash, by number as well as by hash
+    let forged = format!("{:#x}", head - 5);
+    rpc.map_blocks(move |block| {
+        if block["number"] == forged {
+            block["hash"] = serde_json::json!(B256::ZERO);
+        }
+    });
+    let state = state(&rpc, 8);
+
+    state.push_block(chain.head().clone()).await;
+    backfilled(&state).await;
+
+    // the blocks fetched under it are dropped rather than leave a gap
+    assert_eq!(state.oldest_block_number().await, Some(head - 4));
+    let numbers = (head - 4..=head).collect::<Vec<_>>();
+    assert_held(&state, &chain, &numbers).await;
+    assert!(state.get_block(BlockTag::Number(head - 6)).await.is_none());
+    // the block was retried by number, and not once more by hash
+    assert_eq!(rpc.calls("get_block"), 0);
+
+    // the partial window is reported until heads fill it
+    let target = HISTORY_LENGTH as u64 - 1;
+    assert_eq!(
+        state.sync_progress().progress().backfill,
+        Some(BackfillProgress {
+            target_blocks: U64::from(target),
+            fetched_blocks: U64::from(4),
+            stopped_at: Some(U64::from(head - 5)),
+        })
+    );
+    let missing = HISTORY_LENGTH as u64 - 5;
+    chain.extend(missing);
+    for block in &chain.blocks()[40..] {
+        assert!(state.sync_progress().progress().backfill.is_some());
+        state.push_block(block.clone()).await;
+    }
+    assert_eq!(state.sync_progress().progress().backfill, None);
+}
+
+#[tokio::test]
+async fn test_state_backfills_from_provider() {
+    let chain = ChainBuilder::new(1).start_at(10).length(6).build();
+    let harness = Harness::new(&chain);
+    let (client, state) = (&harness.client, &harness.state);
+
+    // only the first and last blocks come from consensus, the gap is fetched and verified
+    state.push_block(chain.blocks()[0].clone()).await;
+    state.push_block(chain.head().clone()).await;
+
+    for block in chain.blocks() {
+        let stored = client
+            .get_block(BlockTag::Number(block.header.number), false)
+            .await
+            .unwrap();
+        assert_eq!(stored.header.hash, block.header.hash);
+    }
+}
@@ -0,0 +1,177 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy::eips::BlockId;
+use alloy::primitives::keccak256;
+use alloy::rpc::types::{Block, Transaction};
+
+use helios_core::database::redb::RedbStore;
+use helios_core::database::Database;
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::state::BEACON_MAPPINGS;
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::harness;
+use helios_core::types::BeaconBlockMapping;
+use helios_ethereum::spec::Ethereum;
+
+type Harness = harness::Harness<Ethereum>;
+
+fn beacon_mapping(block: &Block<Transaction>, slot: u64) -> BeaconBlockMapping {
+    BeaconBlockMapping {
+        slot,
+        epoch: slot / 32,
+        proposer_index: slot % 7,
+        parent_root: keccak256((slot - 1).to_be_bytes()),
+        state_root: keccak256(block.header.state_root),
+        body_root: keccak256(block.header.hash),
+        beacon_block_root: keccak256(slot.to_be_bytes()),
+        execution_block_number: block.header.number,
+        execution_block_hash: block.header.hash,
+        attestation: None,
+    }
+}
+
+#[tokio::test]
+async fn test_beacon_mapping_lookup() {
+    let chain = ChainBuilder::new(9).start_at(50).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let (client, state) = (&harness.client, &harness.state);
+
+    // leave a missed slot between the second and third block
+    let slots = [1000, 1001, 1003, 1004];
+    for (block, slot) in chain.blocks().iter().zip(slots) {
+        state.push_beacon_mapping(beacon_mapping(block, slot)).await;
+    }
+
+    for (block, slot) in chain.blocks().iter().zip(slots) {
+        let expected = beacon_mapping(block, slot);
+        let by_hash = client
+            .get_beacon_mapping_by_block(BlockId::from(block.header.hash))
+            .await
+            .unwrap();
+        let by_number = client
+            .get_beacon_mapping_by_block(BlockId::from(block.header.number))
+            .await
+            .unwrap();
+        let by_slot = client.get_beacon_mapping_by_slot(slot).await.unwrap();
+
+        assert_eq!(by_hash, expected);
+        assert_eq!(by_number, expected);
+        assert_eq!(by_slot, expected);
+    }
+
+    let err = client.get_beacon_mapping_by_slot(1002).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::NoBeaconBlockForSlot(1002))
+    ));
+
+    let err = client.get_beacon_mapping_by_slot(900).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::SlotOutsideRetainedWindow(900, 1000, 1004))
+    ));
+}
+
+#[tokio::test]
+async fn test_beacon_mapping_dropped_on_reorg() {
+    let chain = ChainBuilder::new(10).length(4).build();
+    let fork = chain.reorg(1, 42);
+    let harness = Harness::with_blocks(&chain).await;
+    let (client, state) = (&harness.client, &harness.state);
+
+    for (i, block) in chain.blocks().iter().enumerate() {
+        state
+            .push_beacon_mapping(beacon_mapping(block, 100 + i as u64))
+            .await;
+    }
+
+    let old_head = chain.head();
+    let old_slot = 100 + chain.blocks().len() as u64 - 1;
+    state.push_block(fork.head().clone()).await;
+    state
+        .push_beacon_mapping(beacon_mapping(fork.head(), old_slot + 1))
+        .await;
+
+    let err = client
+        .get_beacon_mapping_by_slot(old_slot)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::NoBeaconBlockForSlot(_))
+    ));
+
+    let err = client
+        .get_beacon_mapping_by_block(BlockId::from(old_head.header.hash))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::NoBeaconBlockForBlock(_))
+    ));
+
+    let mapping = client
+        .get_beacon_mapping_by_block(BlockId::from(old_head.header.number))
+        .await
+        .unwrap();
+    assert_eq!(mapping.slot, old_slot + 1);
+    assert_eq!(mapping.execution_block_hash, fork.head().header.hash);
+}
+
+#[tokio::test]
+async fn test_beacon_mappings_survive_restart() {
+    let chain = ChainBuilder::new(188).length(4).build();
+    let fork = chain.reorg(1, 189);
+    let dir = tempfile::tempdir().unwrap();
+    let db: Arc<dyn Database> = Arc::new(RedbStore::open(dir.path().join("db")).unwrap());
+
+    let harness = Harness::new(&chain);
+    harness.state.persist_beacon_mappings(db.clone()).unwrap();
+    for (i, block) in chain.blocks().iter().enumerate() {
+        harness.state.push_block(block.clone()).await;
+        harness
+            .state
+            .push_beacon_mapping(beacon_mapping(block, 100 + i as u64))
+            .await;
+    }
+    // the head is replaced, which removes its mapping from the database as well
+    harness.state.push_block(fork.head().clone()).await;
+
+    // the mappings are written in the background
+    let stored = |slot: u64| db.get(BEACON_MAPPINGS, &slot.to_be_bytes()).unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while stored(102).is_none() || stored(103).is_some() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("mappings never stored");
+
+    // a restarted client answers for the blocks seen before it restarted
+    let restarted = Harness::new(&chain);
+    restarted.state.persist_beacon_mappings(db).unwrap();
+    for block in fork.blocks() {
+        restarted.state.push_block(block.clone()).await;
+    }
+    for (i, block) in chain.blocks()[..3].iter().enumerate() {
+        let mapping = restarted
+            .client
+            .get_beacon_mapping_by_block(BlockId::from(block.header.hash))
+            .await
+            .unwrap();
+        assert_eq!(mapping, beacon_mapping(block, 100 + i as u64));
+    }
+    let err = restarted
+        .client
+        .get_beacon_mapping_by_slot(103)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::SlotOutsideRetainedWindow(103, 100, 102))
+        ),
+        "{err}"
+    );
+}
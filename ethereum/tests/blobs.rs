@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use alloy::eips::eip4844::DATA_GAS_PER_BLOB;
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{Address, B256, U256};
+use alloy::rpc::types::TransactionRequest;
+
+use helios_core::execution::blob::BlobParams;
+use helios_core::execution::errors::EvmError;
+use helios_core::execution::evm::Evm;
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, Fork, MockChain};
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+type Client = ExecutionClient<Ethereum, ChainRpc>;
+
+/// A chain whose blocks all use the most blob gas allowed, starting from an excess at which
+/// the blob base fee is 23 wei.
+fn blob_chain(seed: u64) -> MockChain {
+    ChainBuilder::new(seed)
+        .length(8)
+        .blob_gas(BlobParams::CANCUN.max_blob_gas, 10 * 1024 * 1024)
+        .build()
+}
+
+async fn verified_client(chain: &MockChain) -> (Arc<Client>, ChainRpc) {
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let state = State::unsynced(64, rpc.clone());
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+    let client = ExecutionClient::with_rpc(rpc.clone(), state, chain.fork_schedule());
+    (Arc::new(client), rpc)
+}
+
+fn excess_blob_gas(chain: &MockChain, number: u64) -> u64 {
+    chain.block(number).unwrap().header.excess_blob_gas.unwrap()
+}
+
+/// A blob transaction from an account of the chain to another, without a blob fee cap.
+fn blob_tx(chain: &MockChain, blobs: usize) -> TransactionRequest {
+    let token = chain.token();
+    let eoas = chain
+        .accounts()
+        .into_iter()
+        .filter(|account| *account != token)
+        .collect::<Vec<Address>>();
+    let mut hash = B256::repeat_byte(0x11);
+    // the version of kzg commitment hashes
+    hash.0[0] = 0x01;
+
+    let mut tx = TransactionRequest::default()
+        .with_from(eoas[0])
+        .with_to(eoas[1])
+        .with_gas_limit(100_000);
+    tx.blob_versioned_hashes = Some(vec![hash; blobs]);
+    tx
+}
+
+#[tokio::test]
+async fn test_blob_base_fee_of_verified_header() {
+    let chain = blob_chain(112);
+    let (client, _) = verified_client(&chain).await;
+    let head = chain.head().header.number;
+
+    let fee = client.blob_base_fee(BlockTag::Latest).await.unwrap();
+    let expected = BlobParams::CANCUN.base_fee(excess_blob_gas(&chain, head));
+    assert_eq!(fee, U256::from(expected));
+    let first = chain.blocks()[0].header.number;
+    let fee = client.blob_base_fee(BlockTag::Number(first)).await.unwrap();
+    assert_eq!(fee, U256::from(23));
+
+    // blocks before cancun carry no blob fields to price blob gas with
+    let chain = ChainBuilder::new(113)
+        .length(3)
+        .fork_at(0, Fork::Shanghai)
+        .build();
+    let (client, _) = verified_client(&chain).await;
+    let err = client.blob_base_fee(BlockTag::Latest).await.unwrap_err();
+    assert!(err.to_string().contains("does not price blob gas"), "{err}");
+}
+
+#[tokio::test]
+async fn test_fee_history_blob_fees_verified() {
+    let chain = blob_chain(114);
+    let (client, rpc) = verified_client(&chain).await;
+    let head = chain.head().header.number;
+    let params = BlobParams::CANCUN;
+
+    let fees = client
+        .get_fee_history(3, BlockTag::Latest, &[])
+        .await
+        .unwrap();
+    let mut expected = (head - 2..=head)
+        .map(|number| params.base_fee(excess_blob_gas(&chain, number)))
+        .collect::<Vec<_>>();
+    // every block is full, so the excess after the head rises by the target
+    let next_excess = excess_blob_gas(&chain, head) + params.target_blob_gas;
+    expected.push(params.base_fee(next_excess));
+    assert_eq!(fees.history.base_fee_per_blob_gas, expected);
+    assert_eq!(fees.history.blob_gas_used_ratio, vec![1.0; 3]);
+
+    // a blob base fee of an excess other than the one in the header
+    rpc.map_fee_history(|mut history| {
+        history.base_fee_per_blob_gas[1] = BlobParams::CANCUN.base_fee(0);
+        history
+    });
+    let err = client
+        .get_fee_history(3, BlockTag::Latest, &[])
+        .await
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("baseFeePerBlobGas"), "{err}");
+    assert!(err.contains(&format!("block {}", head - 1)), "{err}");
+
+    rpc.map_fee_history(|mut history| {
+        history.blob_gas_used_ratio[2] = 0.5;
+        history
+    });
+    let err = client
+        .get_fee_history(3, BlockTag::Latest, &[])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("blobGasUsedRatio"), "{err}");
+
+    // entries the provider leaves out are filled in from the headers
+    rpc.map_fee_history(|mut history| {
+        history.base_fee_per_blob_gas.clear();
+        history.blob_gas_used_ratio.clear();
+        history
+    });
+    let filled = client
+        .get_fee_history(3, BlockTag::Latest, &[])
+        .await
+        .unwrap();
+    assert_eq!(filled, fees);
+}
+
+#[tokio::test]
+async fn test_blob_transactions_simulated_or_rejected() {
+    let chain = blob_chain(115);
+    let (client, _) = verified_client(&chain).await;
+    let evm = || {
+        Evm::new(
+            client.clone(),
+            chain.chain_id(),
+            chain.fork_schedule(),
+            BlockTag::Latest,
+        )
+    };
+
+    // without a blob fee cap the blob gas is free, so the sender need not hold the fees
+    let gas = evm().estimate_gas(&blob_tx(&chain, 2)).await.unwrap();
+    assert_eq!(gas, 21_000);
+
+    // a cap is charged up front, like on chain
+    let mut tx = blob_tx(&chain, 1);
+    tx.max_fee_per_blob_gas = Some(u128::MAX / DATA_GAS_PER_BLOB as u128);
+    let err = evm().estimate_gas(&tx).await.unwrap_err();
+    assert!(matches!(err, EvmError::InvalidTransaction(_)), "{err}");
+
+    let mut create = blob_tx(&chain, 1);
+    create.to = None;
+    let err = evm().call(&create).await.unwrap_err();
+    assert!(
+        matches!(&err, EvmError::UnsupportedBlobTransaction(reason) if reason.contains("create")),
+        "{err}"
+    );
+
+    let err = evm().call(&blob_tx(&chain, 7)).await.unwrap_err();
+    assert!(
+        matches!(err, EvmError::UnsupportedBlobTransaction(_)),
+        "{err}"
+    );
+
+    let chain = ChainBuilder::new(116)
+        .length(3)
+        .fork_at(0, Fork::Shanghai)
+        .build();
+    let (client, _) = verified_client(&chain).await;
+    let mut evm = Evm::new(
+        client,
+        chain.chain_id(),
+        chain.fork_schedule(),
+        BlockTag::Latest,
+    );
+    let err = evm.call(&blob_tx(&chain, 1)).await.unwrap_err();
+    assert!(err.to_string().contains("not supported at block"), "{err}");
+}
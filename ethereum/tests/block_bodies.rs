@@ -0,0 +1,90 @@
+use alloy::rpc::types::{Block, BlockTransactions, Transaction};
+
+use helios_core::execution::errors::ExecutionError;
+use helios_core::network_spec::NetworkSpec;
+use helios_core::testing::chain::{ChainBuilder, Fork};
+use helios_ethereum::spec::Ethereum;
+
+fn check(block: &Block<Transaction>) -> Result<(), ExecutionError> {
+    assert!(Ethereum::is_header_hash_valid(&block.header));
+    Ethereum::check_body(block)
+}
+
+#[test]
+fn test_tampered_withdrawal_amount() {
+    let chain = ChainBuilder::new(123)
+        .length(2)
+        .withdrawals_per_block(4)
+        .build();
+    let block = chain.head();
+    check(block).unwrap();
+
+    let mut tampered = block.clone();
+    tampered.withdrawals.as_mut().unwrap()[2].amount += 1;
+    let err = check(&tampered).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ExecutionError::BlockBodyMismatch(hash, "withdrawals root")
+                if hash == block.header.hash
+        ),
+        "{err}"
+    );
+
+    // a withdrawal left out changes the root just the same
+    let mut tampered = block.clone();
+    tampered.withdrawals.as_mut().unwrap().pop();
+    assert!(check(&tampered).is_err());
+}
+
+#[test]
+fn test_swapped_transaction_order() {
+    let chain = ChainBuilder::new(124).length(2).txs_per_block(3).build();
+    let block = chain.head();
+    check(block).unwrap();
+
+    let mut swapped = block.clone();
+    let BlockTransactions::Full(txs) = &mut swapped.transactions else {
+        panic!("expected full transactions");
+    };
+    txs.swap(0, 2);
+    let err = check(&swapped).unwrap_err();
+    assert!(
+        matches!(
+            err,
+            ExecutionError::BlockBodyMismatch(hash, "transactions root")
+                if hash == block.header.hash
+        ),
+        "{err}"
+    );
+
+    // the encodings in their places, but each claiming the place of another
+    let mut renumbered = block.clone();
+    let BlockTransactions::Full(txs) = &mut renumbered.transactions else {
+        panic!("expected full transactions");
+    };
+    txs[0].transaction_index = Some(2);
+    let err = check(&renumbered).unwrap_err();
+    assert!(
+        matches!(err, ExecutionError::MisplacedTransaction(_, _, 0)),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_pre_shanghai_block_without_withdrawals() {
+    let chain = ChainBuilder::new(125)
+        .length(2)
+        .fork_at(0, Fork::Paris)
+        .withdrawals_per_block(4)
+        .build();
+    let block = chain.head();
+    assert!(block.header.withdrawals_root.is_none());
+
+    // served as providers do, without a withdrawals field at all
+    let mut json = serde_json::to_value(block).unwrap();
+    json.as_object_mut().unwrap().remove("withdrawals");
+    let served = serde_json::from_value::<Block<Transaction>>(json).unwrap();
+    assert!(served.withdrawals.is_none());
+    check(&served).unwrap();
+}
@@ -0,0 +1,162 @@
+use std::sync::Arc;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::U256;
+use alloy::rpc::types::TransactionRequest;
+use tokio::sync::watch;
+
+use helios_core::execution::evm::Evm;
+use helios_core::execution::state::State;
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::harness;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::{BlockTag, HeadFeed};
+use helios_ethereum::spec::Ethereum;
+
+type Harness = harness::Harness<Ethereum>;
+
+#[tokio::test]
+async fn test_confirmed_tag_trails_head() {
+    let chain = ChainBuilder::new(2).length(8).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let confirmed = client.get_block(BlockTag::Confirmed, false).await.unwrap();
+    assert_eq!(confirmed.header.hash, chain.blocks()[3].header.hash);
+
+    let state = harness.state.clone().with_confirmation_depth(0);
+    let confirmed = state.get_block(BlockTag::Confirmed).await.unwrap();
+    assert_eq!(confirmed.header.hash, chain.head().header.hash);
+
+    // the depth is set for that state alone, not the one the client holds
+    let confirmed = client.get_block(BlockTag::Confirmed, false).await.unwrap();
+    assert_eq!(confirmed.header.hash, chain.blocks()[3].header.hash);
+
+    // deeper than the chain held in state
+    let state = harness.state.clone().with_confirmation_depth(8);
+    assert!(state.get_block(BlockTag::Confirmed).await.is_none());
+}
+
+#[tokio::test]
+async fn test_safe_and_pending_tags() {
+    let chain = ChainBuilder::new(2).length(8).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+    let state = &harness.state;
+
+    let err = client
+        .get_account(chain.token(), None, BlockTag::Safe)
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("safe block not yet available"),
+        "{err}"
+    );
+
+    let pending = client.get_block(BlockTag::Pending, false).await.unwrap();
+    assert_eq!(pending.header.hash, chain.head().header.hash);
+
+    // without a safe block of its own the tag falls back to finality
+    state.push_finalized_block(chain.blocks()[2].clone()).await;
+    let safe = client.get_block(BlockTag::Safe, false).await.unwrap();
+    assert_eq!(safe.header.hash, chain.blocks()[2].header.hash);
+
+    state.push_safe_block(chain.blocks()[5].clone()).await;
+    let safe = client.get_block(BlockTag::Safe, false).await.unwrap();
+    assert_eq!(safe.header.hash, chain.blocks()[5].header.hash);
+
+    // and finality overtakes it
+    state.push_finalized_block(chain.blocks()[6].clone()).await;
+    let safe = client.get_block(BlockTag::Safe, false).await.unwrap();
+    assert_eq!(safe.header.hash, chain.blocks()[6].header.hash);
+}
+
+async fn set_head_feed(
+    state: &State<Ethereum, ChainRpc>,
+    send: &watch::Sender<HeadFeed>,
+    head_feed: HeadFeed,
+) {
+    send.send(head_feed).unwrap();
+    state
+        .sync_progress()
+        .subscribe()
+        .wait_for(|progress| progress.head_feed == head_feed)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_safe_only_head_feed_serves_safe_block() {
+    let chain = ChainBuilder::new(132).length(8).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let (client, state) = (&harness.client, &harness.state);
+    state.push_safe_block(chain.blocks()[5].clone()).await;
+
+    let (head_feed_send, head_feed_recv) = watch::channel(HeadFeed::Live);
+    state.track_head_feed(head_feed_recv);
+    set_head_feed(state, &head_feed_send, HeadFeed::SafeOnly).await;
+    for tag in [BlockTag::Latest, BlockTag::Pending, BlockTag::Confirmed] {
+        let block = client.get_block(tag, false).await.unwrap();
+        assert_eq!(block.header.hash, chain.blocks()[5].header.hash, "{tag}");
+    }
+    // and so does the latest block number ranges end at
+    let safe = chain.blocks()[5].header.number;
+    assert_eq!(state.latest_block_number().await, Some(safe));
+
+    // a feed merely stalled still serves its last head, as does one live again
+    for head_feed in [HeadFeed::Stalled, HeadFeed::Live] {
+        set_head_feed(state, &head_feed_send, head_feed).await;
+        let latest = client.get_block(BlockTag::Latest, false).await.unwrap();
+        assert_eq!(
+            latest.header.hash,
+            chain.head().header.hash,
+            "{head_feed:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_balance_and_call_at_each_tag() {
+    let chain = ChainBuilder::new(4).length(8).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let state = &harness.state;
+    let client = Arc::new(harness.client.clone());
+
+    // the generated token contract always reverts, so call between plain accounts
+    let accounts = chain
+        .accounts()
+        .into_iter()
+        .filter(|address| *address != chain.token())
+        .collect::<Vec<_>>();
+    let tx = TransactionRequest::default()
+        .with_from(accounts[0])
+        .with_to(accounts[1])
+        .with_value(U256::from(1));
+    let call = |tag| {
+        let mut evm = Evm::new(client.clone(), chain.chain_id(), chain.fork_schedule(), tag);
+        let tx = tx.clone();
+        async move { evm.call(&tx).await }
+    };
+
+    let err = call(BlockTag::Safe).await.unwrap_err();
+    assert!(
+        err.to_string().contains("safe block not yet available"),
+        "{err}"
+    );
+
+    state.push_finalized_block(chain.blocks()[3].clone()).await;
+    state.push_safe_block(chain.blocks()[5].clone()).await;
+
+    let expected = chain.proof(accounts[0], &[]).balance;
+    for tag in [
+        BlockTag::Latest,
+        BlockTag::Pending,
+        BlockTag::Safe,
+        BlockTag::Finalized,
+        BlockTag::Number(chain.blocks()[4].header.number),
+    ] {
+        let account = client.get_account(accounts[0], None, tag).await.unwrap();
+        assert_eq!(account.balance, expected, "{tag}");
+        call(tag).await.unwrap_or_else(|err| panic!("{tag}: {err}"));
+    }
+}
@@ -0,0 +1,242 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use alloy::primitives::B256;
+use serde_json::{json, Value};
+use strum::IntoEnumIterator;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use helios_ethereum::config::checkpoints::parse_checkpoint;
+use helios_ethereum::config::errors::ConfigError;
+use helios_ethereum::config::networks::{self, Network};
+use helios_ethereum::config::Config;
+use helios_ethereum::database::{ConfigDB, OpenDatabase};
+use helios_ethereum::EthereumClientBuilder;
+
+/// Serves `eth_chainId` as `chain_id` to POST requests and the beacon genesis with
+/// `genesis_root` to GET requests. Returns the url and the count of requests received.
+async fn serve(chain_id: u64, genesis_root: B256) -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(AtomicUsize::new(0));
+
+    let counter = requests.clone();
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::spawn(serve_connection(
+                stream,
+                chain_id,
+                genesis_root,
+                counter.clone(),
+            ));
+        }
+    });
+
+    (url, requests)
+}
+
+async fn serve_connection(
+    mut stream: TcpStream,
+    chain_id: u64,
+    genesis_root: B256,
+    requests: Arc<AtomicUsize>,
+) {
+    let mut buf = Vec::new();
+    loop {
+        let (head, request) = loop {
+            if let Some(end) = buf.windows(4).position(|window| window == b"\r\n\r\n") {
+                let head = String::from_utf8_lossy(&buf[..end]).into_owned();
+                let length = head
+                    .to_lowercase()
+                    .lines()
+                    .find_map(|line| line.strip_prefix("content-length:"))
+                    .map_or(0, |length| length.trim().parse::<usize>().unwrap());
+                if buf.len() >= end + 4 + length {
+                    let body = buf[end + 4..end + 4 + length].to_vec();
+                    buf.drain(..end + 4 + length);
+                    break (head, body);
+                }
+            }
+
+            let mut chunk = [0; 4096];
+            match stream.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(read) => buf.extend_from_slice(&chunk[..read]),
+            }
+        };
+        requests.fetch_add(1, Ordering::SeqCst);
+
+        let body = if head.starts_with("GET /eth/v1/beacon/genesis") {
+            json!({
+                "data": {
+                    "genesis_time": "1606824023",
+                    "genesis_validators_root": genesis_root,
+                    "genesis_fork_version": "0x00000000",
+                }
+            })
+        } else {
+            let request: Value = serde_json::from_slice(&request).unwrap();
+            json!({"jsonrpc": "2.0", "id": request["id"], "result": format!("{chain_id:#x}")})
+        }
+        .to_string();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        if stream.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn mainnet(url: &str) -> EthereumClientBuilder {
+    EthereumClientBuilder::new()
+        .network(Network::Mainnet)
+        .execution_rpc(url)
+        .consensus_rpc(url)
+}
+
+fn rejected(builder: EthereumClientBuilder) -> ConfigError {
+    match builder.build::<ConfigDB>() {
+        Ok(_) => panic!("built a client from an invalid config"),
+        Err(err) => err,
+    }
+}
+
+async fn rejected_by_rpcs(builder: EthereumClientBuilder) -> ConfigError {
+    match builder.build_checked::<ConfigDB>().await {
+        Ok(_) => panic!("built a client against mismatched rpcs"),
+        Err(err) => err,
+    }
+}
+
+#[tokio::test]
+async fn test_preflight_accepts_rpcs_of_network() {
+    let genesis_root = networks::mainnet().chain.genesis_root;
+    let (url, requests) = serve(1, genesis_root).await;
+
+    mainnet(&url).preflight().await.unwrap();
+    assert_eq!(requests.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn test_execution_chain_id_mismatch() {
+    let genesis_root = networks::mainnet().chain.genesis_root;
+    let (url, _) = serve(11155111, genesis_root).await;
+
+    let err = rejected_by_rpcs(mainnet(&url)).await;
+    assert!(
+        matches!(
+            err,
+            ConfigError::ChainIdMismatch {
+                expected: 1,
+                got: 11155111
+            }
+        ),
+        "{err}"
+    );
+    assert_eq!(
+        err.to_string(),
+        "the execution rpc serves chain id 11155111, expected 1"
+    );
+}
+
+#[tokio::test]
+async fn test_consensus_genesis_root_mismatch() {
+    let expected = networks::mainnet().chain.genesis_root;
+    let sepolia = networks::sepolia().chain.genesis_root;
+    let (url, _) = serve(1, sepolia).await;
+
+    let err = rejected_by_rpcs(mainnet(&url)).await;
+    assert!(
+        matches!(err, ConfigError::GenesisRootMismatch { expected: e, got } if e == expected && got == sepolia),
+        "{err}"
+    );
+    assert!(!err.to_string().contains('\n'), "{err}");
+}
+
+#[tokio::test]
+async fn test_offline_skips_preflight() {
+    let (url, requests) = serve(11155111, B256::ZERO).await;
+
+    // still checked without the rpcs, before any client is made
+    let builder = mainnet(&url).offline().checkpoint(B256::ZERO);
+    let err = rejected_by_rpcs(builder).await;
+    assert!(matches!(err, ConfigError::InvalidCheckpoint(_)), "{err}");
+    assert_eq!(requests.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn test_supplied_database_requires_shared_db() {
+    let db = ConfigDB::new(&Config::default()).unwrap();
+    let err = rejected(mainnet("http://localhost:8545").with_database(db));
+    assert!(
+        matches!(err, ConfigError::DatabaseType(name) if name.contains("ConfigDB")),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_invalid_config_rejected() {
+    let err = rejected(EthereumClientBuilder::new().execution_rpc("http://localhost:8545"));
+    assert!(matches!(err, ConfigError::MissingField("network")), "{err}");
+
+    // the consensus rpc falls back to the one of the preset, the execution rpc does not
+    let err = rejected(EthereumClientBuilder::new().network(Network::Mainnet));
+    assert!(
+        matches!(err, ConfigError::MissingField("execution_rpc")),
+        "{err}"
+    );
+
+    for url in ["localhost:8545", "not a url", "ws://localhost:8546"] {
+        let err = rejected(mainnet("http://localhost:5052").execution_rpc(url));
+        assert!(
+            matches!(&err, ConfigError::InvalidUrl { field: "execution_rpc", url: u, .. } if u == url),
+            "{err}"
+        );
+    }
+    let err = rejected(mainnet("http://localhost:8545").consensus_rpc("localhost:5052"));
+    assert!(
+        matches!(
+            err,
+            ConfigError::InvalidUrl {
+                field: "consensus_rpc",
+                ..
+            }
+        ),
+        "{err}"
+    );
+
+    let err = rejected(mainnet("http://localhost:8545").checkpoint(B256::ZERO));
+    assert!(matches!(err, ConfigError::InvalidCheckpoint(_)), "{err}");
+}
+
+#[test]
+fn test_checkpoint_format() {
+    let root = networks::mainnet().default_checkpoint;
+    assert_eq!(parse_checkpoint(&format!("{root}")).unwrap(), root);
+    assert_eq!(parse_checkpoint(&hex::encode(root)).unwrap(), root);
+
+    let hex_root = root.to_string();
+    let short = &hex_root[..64];
+    let not_hex = format!("0x{}", "zz".repeat(32));
+    let zero = B256::ZERO.to_string();
+    for checkpoint in [short, not_hex.as_str(), zero.as_str(), ""] {
+        let err = parse_checkpoint(checkpoint).unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidCheckpoint(_)), "{err}");
+    }
+}
+
+#[test]
+fn test_network_from_chain_id() {
+    for network in Network::iter() {
+        let chain_id = network.to_base_config().chain.chain_id;
+        assert_eq!(Network::from_chain_id(chain_id).unwrap(), network);
+    }
+
+    let err = Network::from_chain_id(5).unwrap_err();
+    assert!(matches!(err, ConfigError::UnknownChainId(5)), "{err}");
+}
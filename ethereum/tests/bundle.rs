@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{address, hex, Address, Bytes, B256, U256};
+use alloy::rpc::types::TransactionRequest;
+
+use helios_core::execution::bundle::{AccountOverride, BundleOverrides, SimulatedTransaction};
+use helios_core::execution::evm::Evm;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::consensus::verified_client;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+/// An account outside the generated chain, so its storage is proven empty.
+const STORE: Address = address!("00000000000000000000000000000000000c0de5");
+
+/// Returns slot 0 when called without data, stores the first word of the data there
+/// otherwise, then reverts if the data is longer than a word.
+const STORE_CODE: [u8; 36] = hex!(
+    "3615601857600035600055604036106016576000"
+    "80fd5b005b60005460005260206000f3"
+);
+
+async fn simulate(
+    chain: &MockChain,
+    txs: &[TransactionRequest],
+    overrides: &BundleOverrides,
+) -> Vec<SimulatedTransaction> {
+    let (client, _, _) = verified_client::<Ethereum>(chain).await;
+
+    let mut evm = Evm::new(
+        Arc::new(client),
+        chain.chain_id(),
+        chain.fork_schedule(),
+        BlockTag::Latest,
+    );
+    evm.simulate_bundle(txs, overrides).await.unwrap()
+}
+
+fn store_overrides() -> BundleOverrides {
+    let account = AccountOverride {
+        code: Some(Bytes::from_static(&STORE_CODE)),
+        ..Default::default()
+    };
+    BundleOverrides {
+        state_overrides: HashMap::from([(STORE, account)]),
+        block_overrides: None,
+    }
+}
+
+fn store_tx(chain: &MockChain, input: Vec<u8>) -> TransactionRequest {
+    TransactionRequest::default()
+        .with_from(chain.accounts()[0])
+        .with_to(STORE)
+        .with_input(input)
+}
+
+#[tokio::test]
+async fn test_later_transaction_reads_earlier_write() {
+    let chain = ChainBuilder::new(70).length(3).build();
+    let value = B256::with_last_byte(7);
+    let txs = [
+        store_tx(&chain, value.to_vec()),
+        store_tx(&chain, Vec::new()),
+    ];
+
+    let results = simulate(&chain, &txs, &store_overrides()).await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results[0].success, "{:?}", results[0].revert_reason);
+    assert!(results[0].return_data.is_empty());
+    assert!(results[1].success, "{:?}", results[1].revert_reason);
+    assert_eq!(results[1].return_data, Bytes::from(value.to_vec()));
+    assert!(results[1].gas_used > 21_000);
+}
+
+#[tokio::test]
+async fn test_reverted_transaction_leaves_state_untouched() {
+    let chain = ChainBuilder::new(71).length(3).build();
+    let write_and_revert = [B256::with_last_byte(9).to_vec(), vec![0; 32]].concat();
+    let txs = [
+        store_tx(&chain, write_and_revert),
+        store_tx(&chain, Vec::new()),
+    ];
+
+    let results = simulate(&chain, &txs, &store_overrides()).await;
+
+    assert!(!results[0].success);
+    assert!(results[0].revert_reason.is_some());
+    assert!(results[0].logs.is_empty());
+    // the slot still holds its proven value
+    assert!(results[1].success, "{:?}", results[1].revert_reason);
+    assert_eq!(results[1].return_data, Bytes::from(B256::ZERO.to_vec()));
+}
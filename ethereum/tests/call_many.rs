@@ -0,0 +1,145 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{hex, Address, Bytes, B256, U256, U64};
+use alloy::rpc::types::{AccessList, AccessListItem, TransactionRequest};
+
+use helios_core::execution::bundle::{AccountOverride, StateOverride};
+use helios_core::execution::errors::EvmError;
+use helios_core::execution::evm::Evm;
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+const COUNTER: Address = Address::repeat_byte(0xc1);
+
+/// Returns the word stored at the slot named by the argument, as a token keeping the balance
+/// of each holder at the slot of its address would for `balanceOf`.
+const BALANCE_OF_CODE: [u8; 12] = hex!("6004355460005260206000f3");
+
+/// Increments slot 0 and returns the new value.
+const COUNTER_CODE: [u8; 18] = hex!("6000546001018060005560005260206000f3");
+
+const BALANCE_OF: [u8; 4] = hex!("70a08231");
+
+async fn evm(
+    chain: &MockChain,
+    rpc: ChainRpc,
+    overrides: StateOverride,
+) -> Evm<Ethereum, ChainRpc> {
+    let state = State::unsynced(64, rpc.clone());
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+    let client = ExecutionClient::<Ethereum, _>::with_rpc(rpc, state, chain.fork_schedule());
+
+    Evm::new(
+        Arc::new(client),
+        chain.chain_id(),
+        chain.fork_schedule(),
+        BlockTag::Latest,
+    )
+    .with_state_overrides(overrides)
+}
+
+fn sender(chain: &MockChain) -> Address {
+    let token = chain.token();
+    chain
+        .accounts()
+        .into_iter()
+        .find(|account| *account != token)
+        .unwrap()
+}
+
+fn call(chain: &MockChain, to: Address, input: Vec<u8>) -> TransactionRequest {
+    TransactionRequest::default()
+        .with_from(sender(chain))
+        .with_to(to)
+        .with_input(input)
+        .with_gas_limit(1_000_000)
+}
+
+#[tokio::test]
+async fn test_call_many_proves_shared_token_once() {
+    let chain = ChainBuilder::new(108).length(3).build();
+    let token = chain.token();
+    let balances = chain.storage(token);
+
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let token_proofs = Arc::new(AtomicUsize::new(0));
+    let counted = token_proofs.clone();
+    rpc.map_proofs(move |proof| {
+        if proof.address == token {
+            counted.fetch_add(1, Ordering::SeqCst);
+        }
+        proof
+    });
+    // every call touches the same holders of the token
+    rpc.serve_access_list(AccessList(vec![AccessListItem {
+        address: token,
+        storage_keys: balances.iter().map(|(slot, _)| *slot).collect(),
+    }]));
+
+    // the balance code replaces the whole account, so only the storage of the token is proven
+    let code = AccountOverride {
+        balance: Some(U256::ZERO),
+        nonce: Some(U64::ZERO),
+        code: Some(Bytes::from_static(&BALANCE_OF_CODE)),
+        ..Default::default()
+    };
+    let mut evm = evm(&chain, rpc, StateOverride::from([(token, code)])).await;
+
+    let holders = (0..20).map(|i| balances[i % balances.len()]);
+    let txs = holders
+        .clone()
+        .map(|(slot, _)| call(&chain, token, [&BALANCE_OF[..], slot.as_slice()].concat()))
+        .collect::<Vec<_>>();
+    let outcomes = evm.call_many(&txs).await.unwrap();
+
+    assert_eq!(outcomes.len(), 20);
+    for (outcome, (_, balance)) in outcomes.iter().zip(holders) {
+        assert!(outcome.success, "{outcome:?}");
+        assert_eq!(
+            outcome.return_data,
+            Bytes::from(balance.to_be_bytes::<32>().to_vec())
+        );
+    }
+    assert_eq!(token_proofs.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_call_many_isolates_calls_and_reports_failures() {
+    let chain = ChainBuilder::new(109).length(3).build();
+    let counter = AccountOverride {
+        code: Some(Bytes::from_static(&COUNTER_CODE)),
+        state_diff: Some([(B256::ZERO, B256::from(U256::from(5)))].into()),
+        ..Default::default()
+    };
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let mut evm = evm(&chain, rpc, StateOverride::from([(COUNTER, counter)])).await;
+
+    // the mock token reverts whatever it is called with
+    let txs = [
+        call(&chain, COUNTER, Vec::new()),
+        call(&chain, chain.token(), Vec::new()),
+        call(&chain, COUNTER, Vec::new()),
+    ];
+    let outcomes = evm.call_many(&txs).await.unwrap();
+
+    let six = Bytes::from(U256::from(6).to_be_bytes::<32>().to_vec());
+    assert!(outcomes[0].success);
+    assert_eq!(outcomes[0].return_data, six);
+    assert!(!outcomes[1].success);
+    assert_eq!(outcomes[1].error, None);
+    // the increment of the first call is not seen by the last
+    assert!(outcomes[2].success);
+    assert_eq!(outcomes[2].return_data, six);
+
+    let txs = vec![call(&chain, COUNTER, Vec::new()); 257];
+    let err = evm.call_many(&txs).await.unwrap_err();
+    assert!(matches!(err, EvmError::TooManyCalls(256)), "{err}");
+}
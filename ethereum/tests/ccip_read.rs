@@ -0,0 +1,254 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{hex, Address, Bytes, FixedBytes};
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol_types::SolError;
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use helios_core::execution::bundle::{AccountOverride, StateOverride};
+use helios_core::execution::ccip::{CcipError, CcipGateway, OffchainLookup};
+use helios_core::execution::errors::EvmError;
+use helios_core::execution::evm::Evm;
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+const OFFCHAIN: Address = Address::repeat_byte(0xc1);
+const CALLBACK: FixedBytes<4> = FixedBytes([0xca, 0x11, 0xba, 0xc4]);
+
+/// Returns its input when called with `callback`, and reverts with `revert` on any other
+/// call.
+fn offchain_code(callback: FixedBytes<4>, revert: &[u8]) -> Bytes {
+    let len = u16::try_from(revert.len()).unwrap().to_be_bytes();
+    let mut code = vec![0x63];
+    code.extend(callback);
+    // calls of the callback jump to the echo at 0x1d
+    code.extend(hex!("60003560e01c14601d57"));
+    // the revert data is copied from the code after the 40 byte prefix
+    code.extend([0x61, len[0], len[1]]);
+    code.extend(hex!("6028600039"));
+    code.extend([0x61, len[0], len[1]]);
+    code.extend(hex!("6000fd"));
+    code.extend(hex!("5b366000600037366000f3"));
+    code.extend(revert);
+    code.into()
+}
+
+/// Requests received by a gateway, as the request line and the body.
+type Requests = Arc<Mutex<Vec<(String, String)>>>;
+
+/// Serves every request with `status` and `body`, returning the base url of the gateway.
+async fn gateway(status: u16, body: String) -> (String, Requests) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Requests::default();
+    let recorded = requests.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let request = read_request(&mut stream).await;
+            recorded.lock().unwrap().push(request);
+            let response = format!(
+                "HTTP/1.1 {status} Gateway\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+
+    (url, requests)
+}
+
+async fn read_request(stream: &mut tokio::net::TcpStream) -> (String, String) {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let read = stream.read(&mut buf).await.unwrap_or_default();
+        request.extend_from_slice(&buf[..read]);
+        let text = String::from_utf8_lossy(&request).into_owned();
+        if let Some((head, body)) = text.split_once("\r\n\r\n") {
+            let length = head
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("content-length")
+                        .then(|| value.trim().parse::<usize>().ok())?
+                })
+                .unwrap_or_default();
+            if body.len() >= length || read == 0 {
+                let line = head.lines().next().unwrap_or_default().to_string();
+                return (line, body.to_string());
+            }
+        } else if read == 0 {
+            return (text, String::new());
+        }
+    }
+}
+
+fn lookup(urls: Vec<String>, callback: FixedBytes<4>) -> OffchainLookup {
+    OffchainLookup {
+        sender: OFFCHAIN,
+        urls,
+        callData: Bytes::from_static(b"query"),
+        callbackFunction: callback,
+        extraData: Bytes::from_static(b"extra"),
+    }
+}
+
+async fn evm(chain: &MockChain, lookup: &OffchainLookup) -> Evm<Ethereum, ChainRpc> {
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let state = State::unsynced(64, rpc.clone());
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+    let gateway = CcipGateway::new(Duration::from_secs(5)).allow_local_gateways();
+    let client = ExecutionClient::<Ethereum, _>::with_rpc(rpc, state, chain.fork_schedule())
+        .with_ccip_read(Some(gateway));
+
+    let code = AccountOverride {
+        code: Some(offchain_code(CALLBACK, &lookup.abi_encode())),
+        ..Default::default()
+    };
+    Evm::new(
+        Arc::new(client),
+        chain.chain_id(),
+        chain.fork_schedule(),
+        BlockTag::Latest,
+    )
+    .with_state_overrides(StateOverride::from([(OFFCHAIN, code)]))
+}
+
+fn query() -> TransactionRequest {
+    TransactionRequest::default()
+        .with_to(OFFCHAIN)
+        .with_input(Bytes::from_static(&[0x12, 0x34, 0x56, 0x78]))
+}
+
+#[tokio::test]
+async fn test_lookup_answered_by_next_gateway_after_failure() {
+    let chain = ChainBuilder::new(92).length(3).build();
+    let answer = Bytes::from_static(b"answer");
+    let (failing, failed) = gateway(503, "unavailable".to_string()).await;
+    let (answering, answered) = gateway(200, json!({ "data": answer }).to_string()).await;
+    let lookup = lookup(
+        vec![
+            format!("{failing}/{{sender}}/{{data}}.json"),
+            format!("{answering}/lookup"),
+        ],
+        CALLBACK,
+    );
+    let mut evm = evm(&chain, &lookup).await;
+
+    // the callback echoes its input, which carries the gateway answer and the extra data
+    let output = evm.call(&query()).await.unwrap();
+    assert_eq!(output, lookup.callback(answer));
+
+    let failed = failed.lock().unwrap();
+    let sender = format!("{OFFCHAIN:#x}");
+    assert_eq!(
+        failed[0].0,
+        format!("GET /{sender}/0x7175657279.json HTTP/1.1")
+    );
+    let answered = answered.lock().unwrap();
+    assert_eq!(answered[0].0, "POST /lookup HTTP/1.1");
+    let body = serde_json::from_str::<serde_json::Value>(&answered[0].1).unwrap();
+    assert_eq!(body, json!({ "data": "0x7175657279", "sender": sender }));
+}
+
+#[tokio::test]
+async fn test_lookup_fails_once_every_gateway_fails() {
+    let chain = ChainBuilder::new(93).length(3).build();
+    let (failing, _) = gateway(500, "error".to_string()).await;
+    let (garbled, _) = gateway(200, "not json".to_string()).await;
+    let lookup = lookup(vec![failing, garbled], CALLBACK);
+    let mut evm = evm(&chain, &lookup).await;
+
+    let err = evm.call(&query()).await.unwrap_err();
+    let EvmError::CcipRead(CcipError::GatewaysFailed(errors)) = &err else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("500"), "{}", errors[0]);
+    assert!(errors[1].contains("invalid response"), "{}", errors[1]);
+}
+
+#[tokio::test]
+async fn test_lookup_depth_limited() {
+    let chain = ChainBuilder::new(94).length(3).build();
+    let (answering, answered) = gateway(200, json!({ "data": "0x01" }).to_string()).await;
+    // the callback is not the one the contract answers, so it reverts with the lookup again
+    let lookup = lookup(vec![answering], FixedBytes([0xde, 0xad, 0xbe, 0xef]));
+    let mut evm = evm(&chain, &lookup).await;
+
+    let err = evm.call(&query()).await.unwrap_err();
+    assert!(
+        matches!(err, EvmError::CcipRead(CcipError::TooManyLookups(4))),
+        "{err}"
+    );
+    assert_eq!(answered.lock().unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn test_lookup_of_other_sender_is_a_revert() {
+    let chain = ChainBuilder::new(95).length(3).build();
+    let (answering, answered) = gateway(200, json!({ "data": "0x01" }).to_string()).await;
+    let lookup = OffchainLookup {
+        sender: Address::repeat_byte(0xc2),
+        ..lookup(vec![answering], CALLBACK)
+    };
+    let mut evm = evm(&chain, &lookup).await;
+
+    let err = evm.call(&query()).await.unwrap_err();
+    assert!(
+        matches!(&err, EvmError::Revert(Some(revert)) if *revert == lookup.abi_encode()),
+        "{err}"
+    );
+    assert!(answered.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_local_gateways_refused_by_default() {
+    let (answering, answered) = gateway(200, json!({ "data": "0x01" }).to_string()).await;
+    let port = answering.rsplit(':').next().unwrap();
+    let urls = vec![
+        answering.clone(),
+        format!("https://127.0.0.1:{port}/lookup"),
+        format!("https://localhost:{port}/lookup"),
+        "https://10.0.0.1/lookup".to_string(),
+        "https://[::1]/lookup".to_string(),
+        "https://[::ffff:192.168.1.1]/lookup".to_string(),
+    ];
+    let gateway = CcipGateway::new(Duration::from_secs(5));
+
+    let err = gateway.fetch(&lookup(urls, CALLBACK)).await.unwrap_err();
+    let CcipError::GatewaysFailed(errors) = &err else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(errors.len(), 6);
+    for error in errors {
+        assert!(error.contains("refused"), "{error}");
+    }
+    assert!(answered.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_oversized_gateway_response_cut_off() {
+    let data = Bytes::from(vec![0xab; 3 * 1024 * 1024]);
+    let (answering, _) = gateway(200, json!({ "data": data }).to_string()).await;
+    let gateway = CcipGateway::new(Duration::from_secs(5)).allow_local_gateways();
+
+    let err = gateway
+        .fetch(&lookup(vec![answering], CALLBACK))
+        .await
+        .unwrap_err();
+    let CcipError::GatewaysFailed(errors) = &err else {
+        panic!("unexpected error: {err}");
+    };
+    assert!(errors[0].contains("response over"), "{}", errors[0]);
+}
@@ -1,5 +1,82 @@
-use alloy::primitives::B256;
-use helios_ethereum::config::{checkpoints, networks};
+use std::sync::Arc;
+
+use alloy::primitives::{b256, B256};
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::{mpsc, watch};
+
+use helios_consensus_core::consensus_spec::MainnetConsensusSpec;
+use helios_core::types::CheckpointSource;
+use helios_ethereum::config::checkpoints::CheckpointFallbackService;
+use helios_ethereum::config::{checkpoints, networks, Config};
+use helios_ethereum::consensus::Inner;
+use helios_ethereum::database::Checkpoint;
+use helios_ethereum::rpc::mock_rpc::MockRpc;
+
+/// The root of the bootstrap in the testdata.
+const CHECKPOINT: B256 = b256!("5afc212a7924789b2bc86acad3ab3a6ffb1f6e97253ea50bee7f4f51422c9275");
+const BOOTSTRAP_SLOT: u64 = 7069376;
+
+/// Serves a checkpointz slots response with `root` at `epoch`, or no slots without a root.
+async fn checkpointz(name: &str, root: Option<B256>, epoch: u64) -> CheckpointFallbackService {
+    let slots = match root {
+        Some(root) => json!([{
+            "slot": (epoch * 32).to_string(),
+            "block_root": root,
+            "epoch": epoch.to_string(),
+            "time": { "start_time": "2024-01-01T00:00:00Z", "end_time": "2024-01-01T00:00:12Z" },
+        }]),
+        None => json!([]),
+    };
+    let body = json!({ "data": { "slots": slots } }).to_string();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = [0u8; 4096];
+            let _ = stream.read(&mut request).await;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+
+    CheckpointFallbackService {
+        endpoint,
+        name: name.to_string(),
+        state: true,
+        ..Default::default()
+    }
+}
+
+/// A client of the testdata that loads external fallbacks from `services`.
+fn fallback_client(
+    services: Vec<CheckpointFallbackService>,
+) -> Inner<MainnetConsensusSpec, MockRpc> {
+    let base_config = networks::mainnet();
+    let config = Config {
+        chain: base_config.chain,
+        forks: base_config.forks,
+        max_checkpoint_age: base_config.max_checkpoint_age,
+        load_external_fallback: true,
+        fallback_quorum: 2,
+        ..Default::default()
+    };
+
+    Inner::new(
+        "testdata/",
+        mpsc::channel(256).0,
+        watch::channel(None).0,
+        mpsc::channel(256).0,
+        watch::channel(None).0,
+        Arc::new(config),
+    )
+    .with_fallback_services(services)
+}
 
 #[tokio::test]
 async fn test_checkpoint_fallback() {
@@ -8,6 +85,7 @@ async fn test_checkpoint_fallback() {
     assert_eq!(cf.services.get(&networks::Network::Mainnet), None);
     assert_eq!(cf.services.get(&networks::Network::Sepolia), None);
     assert_eq!(cf.services.get(&networks::Network::Holesky), None);
+    assert_eq!(cf.services.get(&networks::Network::Hoodi), None);
 
     assert_eq!(
         cf.networks,
@@ -15,6 +93,7 @@ async fn test_checkpoint_fallback() {
             networks::Network::Mainnet,
             networks::Network::Sepolia,
             networks::Network::Holesky,
+            networks::Network::Hoodi,
         ]
         .to_vec()
     );
@@ -30,6 +109,7 @@ async fn test_construct_checkpoints() {
     assert!(cf.services[&networks::Network::Mainnet].len() > 1);
     assert!(cf.services[&networks::Network::Sepolia].len() > 1);
     assert!(cf.services[&networks::Network::Holesky].len() > 1);
+    assert!(cf.services[&networks::Network::Hoodi].len() > 1);
 }
 
 #[tokio::test]
@@ -82,3 +162,128 @@ async fn test_get_healthy_fallback_endpoints() {
     let urls = cf.get_healthy_fallback_endpoints(&networks::Network::Holesky);
     assert!(!urls.is_empty());
 }
+
+#[tokio::test]
+async fn test_survey_finds_majority_and_outliers() {
+    let root = B256::repeat_byte(0xaa);
+    let services = [
+        checkpointz("a", Some(root), 100).await,
+        checkpointz("b", Some(root), 100).await,
+        checkpointz("c", Some(B256::repeat_byte(0xbb)), 100).await,
+        checkpointz("d", None, 0).await,
+    ];
+
+    let survey = checkpoints::CheckpointFallback::survey(&services).await;
+    assert_eq!(survey.majority, Some(root));
+    assert!(survey.quorum);
+    assert_eq!(
+        survey
+            .outliers()
+            .map(|r| r.name.as_str())
+            .collect::<Vec<_>>(),
+        ["c"]
+    );
+
+    let report = &survey.reports[0];
+    assert_eq!((report.slot, report.epoch), (Some(3200), Some(100)));
+    assert!(report.age.unwrap() > 0);
+    assert!(survey.reports[3].root.is_none() && survey.reports[3].error.is_some());
+
+    let checkpoint =
+        checkpoints::CheckpointFallback::fetch_latest_checkpoint_from_services(&services)
+            .await
+            .unwrap();
+    assert_eq!(checkpoint, root);
+}
+
+#[tokio::test]
+async fn test_survey_without_quorum() {
+    let services = [
+        checkpointz("a", Some(B256::repeat_byte(0xaa)), 100).await,
+        checkpointz("b", Some(B256::repeat_byte(0xbb)), 100).await,
+        // a lagging service never makes up the majority
+        checkpointz("c", Some(B256::repeat_byte(0xcc)), 99).await,
+        checkpointz("d", Some(B256::repeat_byte(0xcc)), 99).await,
+    ];
+
+    let survey = checkpoints::CheckpointFallback::survey(&services).await;
+    assert_ne!(survey.majority, Some(B256::repeat_byte(0xcc)));
+    assert!(!survey.quorum);
+}
+
+#[tokio::test]
+async fn test_survey_agreement_requires_quorum() {
+    let root = B256::repeat_byte(0xaa);
+    let services = [
+        checkpointz("a", Some(root), 100).await,
+        checkpointz("b", Some(root), 100).await,
+        checkpointz("c", Some(B256::repeat_byte(0xbb)), 100).await,
+    ];
+
+    let survey = checkpoints::CheckpointFallback::survey(&services).await;
+    assert_eq!(survey.agreed(2).unwrap(), root);
+    let err = survey.agreed(3).unwrap_err();
+    assert!(err.to_string().contains("only 2 of 3"), "{err}");
+}
+
+#[tokio::test]
+async fn test_stale_stored_checkpoint_refreshed_from_fallback_quorum() {
+    let services = vec![
+        checkpointz("a", Some(CHECKPOINT), 100).await,
+        checkpointz("b", Some(CHECKPOINT), 100).await,
+        checkpointz("c", Some(B256::repeat_byte(0xbb)), 100).await,
+    ];
+    let mut client = fallback_client(services);
+
+    // stored after the configured checkpoint, but long before the max checkpoint age
+    let stored = Checkpoint {
+        root: B256::repeat_byte(0x01),
+        slot: BOOTSTRAP_SLOT + 32,
+    };
+    let (checkpoint, source, slot) = client
+        .select_checkpoint((CHECKPOINT, CheckpointSource::Configured), Some(stored))
+        .await;
+    assert_eq!(checkpoint, stored.root);
+    assert_eq!(source, CheckpointSource::Persisted);
+    assert_eq!(slot, Some(stored.slot));
+
+    let fallback = client.sync_or_fallback(checkpoint, slot).await.unwrap();
+    assert_eq!(fallback, Some(CHECKPOINT));
+    assert!(client.store.finalized_header.beacon().slot > BOOTSTRAP_SLOT);
+}
+
+#[tokio::test]
+async fn test_configured_checkpoint_kept_over_older_stored_one() {
+    let client = fallback_client(Vec::new());
+    let stored = Checkpoint {
+        root: B256::repeat_byte(0x01),
+        slot: BOOTSTRAP_SLOT - 32,
+    };
+
+    let selected = client
+        .select_checkpoint((CHECKPOINT, CheckpointSource::User), Some(stored))
+        .await;
+    assert_eq!(
+        selected,
+        (CHECKPOINT, CheckpointSource::User, Some(BOOTSTRAP_SLOT))
+    );
+}
+
+#[tokio::test]
+async fn test_fallback_disagreement_fails_startup() {
+    let services = vec![
+        checkpointz("a", Some(CHECKPOINT), 100).await,
+        checkpointz("b", Some(B256::repeat_byte(0xbb)), 100).await,
+        checkpointz("c", Some(B256::repeat_byte(0xcc)), 100).await,
+    ];
+    let mut client = fallback_client(services);
+
+    let err = client
+        .sync_or_fallback(B256::repeat_byte(0x01), Some(BOOTSTRAP_SLOT))
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("checkpoint services agree"),
+        "{err}"
+    );
+}
@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use tempfile::NamedTempFile;
+
+use helios_core::config::{ByteSize, ConfigDuration};
+use helios_core::execution::limits::PayloadLimits;
+use helios_core::execution::rpc::retry::RetryConfig;
+use helios_ethereum::config::{cli::CliConfig, Config};
+
+/// A config file holding `toml`, removed once the returned handle is dropped.
+fn write_config(toml: &str) -> NamedTempFile {
+    let file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+    std::fs::write(file.path(), toml).unwrap();
+    file
+}
+
+fn checkpoint_age_config(max_checkpoint_age: &str) -> NamedTempFile {
+    write_config(&format!(
+        "[mainnet]\nexecution_rpc = \"http://localhost:8545\"\nmax_checkpoint_age = {max_checkpoint_age}\n"
+    ))
+}
+
+#[test]
+fn test_duration_with_unit() {
+    let file = checkpoint_age_config("\"2d\"");
+    let config = Config::from_file(&file.path().to_path_buf(), "mainnet", &CliConfig::default());
+
+    assert_eq!(
+        config.max_checkpoint_age.as_duration(),
+        Duration::from_secs(172_800)
+    );
+}
+
+#[test]
+fn test_plain_integer_read_as_seconds() {
+    let file = checkpoint_age_config("86400");
+    let config = Config::from_file(&file.path().to_path_buf(), "mainnet", &CliConfig::default());
+
+    assert_eq!(config.max_checkpoint_age.as_secs(), 86_400);
+}
+
+#[test]
+fn test_base_config_serializes_units() {
+    let file = checkpoint_age_config("\"90m\"");
+    let config = Config::from_file(&file.path().to_path_buf(), "mainnet", &CliConfig::default());
+
+    let value = serde_json::to_value(config.to_base_config()).unwrap();
+    assert_eq!(value["max_checkpoint_age"], "90m");
+}
+
+#[test]
+fn test_retry_flags_merge_with_table() {
+    let file = write_config("[mainnet]\nexecution_rpc = \"http://localhost:8545\"\n\n[mainnet.execution_retry]\nmax_retries = 5\ninitial_backoff = \"1s\"\n");
+
+    let cli_config = CliConfig {
+        execution_max_retries: Some(2),
+        execution_max_backoff: Some(ConfigDuration::from_secs(10)),
+        ..Default::default()
+    };
+    let retry =
+        Config::from_file(&file.path().to_path_buf(), "mainnet", &cli_config).execution_retry;
+
+    assert_eq!(retry.max_retries, 2);
+    assert_eq!(retry.initial_backoff, ConfigDuration::from_secs(1));
+    assert_eq!(retry.max_backoff, ConfigDuration::from_secs(10));
+    assert_eq!(
+        retry.compute_units_per_second,
+        RetryConfig::default().compute_units_per_second
+    );
+}
+
+#[test]
+fn test_sizes_with_units() {
+    let file = write_config("[mainnet]\nexecution_rpc = \"http://localhost:8545\"\n\n[mainnet.payload_limits]\nmax_block_body_size = \"16MB\"\nmax_log_data_size = 65536\n\n[mainnet.code_cache]\nmax_size = \"100MB\"\n\n[mainnet.execution_http]\nmax_response_size = \"2GB\"\n");
+    let config = Config::from_file(&file.path().to_path_buf(), "mainnet", &CliConfig::default());
+
+    assert_eq!(
+        config.payload_limits.max_block_body_size,
+        ByteSize::from_mb(16)
+    );
+    assert_eq!(
+        config.payload_limits.max_log_data_size,
+        ByteSize::from_bytes(65_536)
+    );
+    assert_eq!(
+        config.payload_limits.max_proof_node_size,
+        PayloadLimits::default().max_proof_node_size
+    );
+    assert_eq!(config.code_cache.max_size, ByteSize::from_mb(100));
+    assert_eq!(
+        config.execution_http.max_response_size,
+        ByteSize::from_mb(2048)
+    );
+}
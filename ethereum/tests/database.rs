@@ -0,0 +1,315 @@
+use std::sync::Arc;
+use std::thread::sleep;
+use std::time::Duration;
+
+use alloy::primitives::{FixedBytes, B256};
+use helios_consensus_core::consensus_spec::MainnetConsensusSpec;
+use helios_core::config::ByteSize;
+use helios_core::execution::usage::{RequestCategory, UsageMeter};
+use helios_core::types::{
+    RetentionPolicy, SyncCommitteeInfo, SyncCommitteeProof, SyncCommitteeSource,
+};
+use helios_ethereum::config::Config;
+use helios_ethereum::database::gc::{
+    configured_namespaces, GarbageCollector, Namespace, CODE, LATEST_CHECKPOINT, NAMESPACES,
+    SYNC_COMMITTEES, USAGE, VERIFIED_HEADERS,
+};
+use helios_ethereum::database::sync_committees::{
+    committee_root, DbSyncCommitteeStore, SyncCommitteeStore,
+};
+use helios_ethereum::database::usage::DbUsageStore;
+use helios_ethereum::database::{
+    migrate_checkpoint_file, Checkpoint, Database, EmbeddedDB, FileDB, OpenDatabase, SharedDB,
+};
+
+fn file_db(name: &str) -> Arc<FileDB> {
+    let data_dir = std::env::temp_dir().join(format!("helios-{name}-{}", std::process::id()));
+    _ = std::fs::remove_dir_all(&data_dir);
+
+    let config = Config {
+        data_dir: Some(data_dir),
+        ..Default::default()
+    };
+    Arc::new(FileDB::new(&config).unwrap())
+}
+
+// file modification times are the entry ages, so spread the writes past their granularity
+fn fill(db: &FileDB, namespace: &str, count: u8, size: usize) {
+    for key in 0..count {
+        db.put(namespace, &[key], &vec![key; size]).unwrap();
+        sleep(Duration::from_millis(20));
+    }
+}
+
+#[test]
+fn test_namespace_over_entry_cap_evicts_oldest() {
+    let db = file_db("gc-entries");
+    let namespace = Namespace {
+        name: "receipts",
+        retention: RetentionPolicy::MaxEntries(3),
+    };
+
+    fill(&db, namespace.name, 6, 8);
+    db.set_pinned(namespace.name, &[0], true).unwrap();
+
+    let gc = GarbageCollector::new(db.clone(), &[namespace]);
+    gc.collect().unwrap();
+
+    for key in 0..6 {
+        let kept = db.get(namespace.name, &[key]).unwrap().is_some();
+        assert_eq!(kept, [0, 4, 5].contains(&key), "key {key}");
+    }
+
+    let stats = gc.stats().unwrap();
+    let receipts = &stats.namespaces[0];
+    assert_eq!(receipts.name, "receipts");
+    assert_eq!(receipts.entries, 3);
+    assert_eq!(receipts.pinned_entries, 1);
+    assert_eq!(receipts.bytes, 24);
+    assert_eq!(receipts.evicted, 3);
+}
+
+#[test]
+fn test_namespace_over_size_cap_evicts_oldest() {
+    let db = file_db("gc-bytes");
+    let namespace = Namespace {
+        name: "headers",
+        retention: RetentionPolicy::MaxBytes(ByteSize::from_bytes(1_000)),
+    };
+
+    fill(&db, namespace.name, 5, 300);
+    db.set_pinned(namespace.name, &[1], true).unwrap();
+
+    let gc = GarbageCollector::new(db.clone(), &[namespace]);
+    gc.collect().unwrap();
+
+    for key in 0..5 {
+        let kept = db.get(namespace.name, &[key]).unwrap().is_some();
+        assert_eq!(kept, [1, 3, 4].contains(&key), "key {key}");
+    }
+    assert_eq!(gc.stats().unwrap().namespaces[0].bytes, 900);
+}
+
+#[test]
+fn test_pinned_namespace_is_never_collected() {
+    let db = file_db("gc-pinned");
+    let namespace = Namespace {
+        name: "snapshots",
+        retention: RetentionPolicy::Pinned,
+    };
+
+    fill(&db, namespace.name, 3, 8);
+
+    let gc = GarbageCollector::new(db.clone(), &[namespace]);
+    gc.collect().unwrap();
+
+    let stats = gc.stats().unwrap();
+    assert_eq!(stats.namespaces[0].entries, 3);
+    assert_eq!(stats.namespaces[0].evicted, 0);
+}
+
+#[test]
+fn test_stats_report_stored_namespaces() {
+    let db = file_db("gc-stats");
+    db.save_checkpoint_with_slot(Checkpoint {
+        root: B256::repeat_byte(1),
+        slot: 64,
+    })
+    .unwrap();
+    db.put(CODE.name, &[1; 32], &[0x60; 100]).unwrap();
+
+    let gc = GarbageCollector::new(db.clone(), NAMESPACES);
+    gc.collect().unwrap();
+
+    let stats = gc.stats().unwrap();
+    let namespace = |name: &str| {
+        stats
+            .namespaces
+            .iter()
+            .find(|namespace| namespace.name == name)
+            .unwrap()
+    };
+    // the checkpoint and the code are kept by their owners, not collected
+    assert!(namespace(LATEST_CHECKPOINT.name).bytes >= 32);
+    assert_eq!(namespace(CODE.name).bytes, 100);
+    assert_eq!(namespace(CODE.name).evicted, 0);
+    assert_eq!(namespace(VERIFIED_HEADERS.name).entries, 0);
+}
+
+#[test]
+fn test_invalid_namespace_rejected() {
+    let db = file_db("gc-invalid");
+    assert!(db.put("../checkpoint", &[0], &[0]).is_err());
+    assert!(db.entries("").is_err());
+}
+
+#[test]
+fn test_checkpoint_round_trips_with_slot() {
+    let db = file_db("checkpoint-slot");
+    assert_eq!(db.load_checkpoint_with_slot().unwrap(), None);
+
+    let checkpoint = Checkpoint {
+        root: B256::repeat_byte(0x5a),
+        slot: 7069376,
+    };
+    db.save_checkpoint_with_slot(checkpoint).unwrap();
+    assert_eq!(db.load_checkpoint_with_slot().unwrap(), Some(checkpoint));
+    assert_eq!(db.load_checkpoint().unwrap(), checkpoint.root);
+
+    // a checkpoint saved without its slot has no known slot
+    db.save_checkpoint(B256::repeat_byte(0x5b)).unwrap();
+    assert_eq!(db.load_checkpoint_with_slot().unwrap(), None);
+    assert_eq!(db.load_checkpoint().unwrap(), B256::repeat_byte(0x5b));
+}
+
+/// A data dir holding only the checkpoint file earlier versions saved.
+fn legacy_data_dir(name: &str, checkpoint: Checkpoint) -> Config {
+    let data_dir = std::env::temp_dir().join(format!("helios-{name}-{}", std::process::id()));
+    _ = std::fs::remove_dir_all(&data_dir);
+    std::fs::create_dir_all(&data_dir).unwrap();
+
+    let mut buf = checkpoint.root.to_vec();
+    buf.extend(checkpoint.slot.to_be_bytes());
+    std::fs::write(data_dir.join("checkpoint"), buf).unwrap();
+
+    Config {
+        data_dir: Some(data_dir),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_checkpoint_file_migrated_on_first_start() {
+    let checkpoint = Checkpoint {
+        root: B256::repeat_byte(0x6c),
+        slot: 9_000_000,
+    };
+
+    let config = legacy_data_dir("migrate-file", checkpoint);
+    let db = FileDB::new(&config).unwrap();
+    assert_eq!(db.load_checkpoint_with_slot().unwrap(), Some(checkpoint));
+
+    let config = legacy_data_dir("migrate-embedded", checkpoint);
+    let data_dir = config.data_dir.clone().unwrap();
+    let db = EmbeddedDB::new(&config).unwrap();
+    assert_eq!(db.load_checkpoint_with_slot().unwrap(), Some(checkpoint));
+    assert!(!data_dir.join("checkpoint").exists());
+
+    // a checkpoint saved since is newer than a file left behind
+    let newer = Checkpoint {
+        root: B256::repeat_byte(0x6d),
+        slot: 9_000_100,
+    };
+    db.save_checkpoint_with_slot(newer).unwrap();
+    std::fs::write(data_dir.join("checkpoint"), checkpoint.root).unwrap();
+    migrate_checkpoint_file(&db, &data_dir).unwrap();
+    assert_eq!(db.load_checkpoint_with_slot().unwrap(), Some(newer));
+    assert!(!data_dir.join("checkpoint").exists());
+}
+
+#[test]
+fn test_supplied_database_shared() {
+    let embedded = EmbeddedDB::new(&legacy_data_dir(
+        "supplied",
+        Checkpoint {
+            root: B256::repeat_byte(0x01),
+            slot: 1,
+        },
+    ))
+    .unwrap();
+    let config = Config {
+        database: Some(Arc::new(embedded.clone())),
+        ..Default::default()
+    };
+
+    let db = SharedDB::new(&config).unwrap();
+    db.put(USAGE.name, &[1], &[2]).unwrap();
+    assert_eq!(embedded.get(USAGE.name, &[1]).unwrap(), Some(vec![2]));
+    assert_eq!(db.load_checkpoint().unwrap(), B256::repeat_byte(0x01));
+
+    assert!(SharedDB::new(&Config::default()).is_err());
+}
+
+#[test]
+fn test_usage_persisted_per_day() {
+    let db = file_db("usage");
+    // 2024-03-10 and 2024-03-11, at noon
+    let first = 1_710_072_000;
+    let second = first + 86_400;
+
+    let meter = UsageMeter::default().with_store(DbUsageStore::new((*db).clone()));
+    for now in [first, second, second] {
+        meter.record_at(now, RequestCategory::Sync, "eth_getBlockByNumber");
+    }
+    meter.record_at(second, RequestCategory::Bulk, "eth_getBlockReceipts");
+    meter.flush().unwrap();
+    assert_eq!(db.entries(USAGE.name).unwrap().len(), 2);
+
+    let resumed = UsageMeter::default().with_store(DbUsageStore::new((*db).clone()));
+    let report = resumed.report_at(second);
+    assert_eq!(report.date, "2024-03-11");
+    assert_eq!((report.today.requests, report.today.cost), (3, 532));
+    assert_eq!((report.month.requests, report.month.cost), (4, 548));
+    assert_eq!(report, meter.report_at(second));
+}
+
+fn sync_committee(period: u64) -> SyncCommitteeInfo {
+    let mut committee = SyncCommitteeInfo {
+        period,
+        pubkeys: vec![FixedBytes::repeat_byte(period as u8); 512],
+        aggregate_pubkey: FixedBytes::repeat_byte(0xaa),
+        root: B256::ZERO,
+        proof: SyncCommitteeProof {
+            source: SyncCommitteeSource::Update,
+            slot: period * 8192,
+            beacon_block_root: B256::repeat_byte(0x01),
+            state_root: B256::repeat_byte(0x02),
+            generalized_index: 55,
+            branch: vec![B256::repeat_byte(0x03); 5],
+        },
+    };
+    committee.root = committee_root::<MainnetConsensusSpec>(&committee).unwrap();
+    committee
+}
+
+#[test]
+fn test_sync_committees_retained_with_current_and_next_pinned() {
+    let db = file_db("sync-committees");
+    let store = DbSyncCommitteeStore::<MainnetConsensusSpec, _>::new((*db).clone());
+    for period in 10..16 {
+        store
+            .save(&sync_committee(period), [period - 1, period])
+            .unwrap();
+        sleep(Duration::from_millis(20));
+    }
+    assert_eq!(store.periods().unwrap(), (10..16).collect::<Vec<_>>());
+    assert_eq!(store.load(12).unwrap(), Some(sync_committee(12)));
+
+    let config = Config {
+        sync_committee_retention: 2,
+        ..Default::default()
+    };
+    let namespaces = configured_namespaces(&config);
+    let gc = GarbageCollector::new(db.clone(), &namespaces);
+    gc.collect().unwrap();
+
+    // only the pinned current and next committees fit the retention
+    assert_eq!(store.periods().unwrap(), [14, 15]);
+    let stats = gc.stats().unwrap();
+    let committees = stats
+        .namespaces
+        .iter()
+        .find(|namespace| namespace.name == SYNC_COMMITTEES.name)
+        .unwrap();
+    assert_eq!(committees.retention, RetentionPolicy::MaxEntries(2));
+    assert_eq!(committees.pinned_entries, 2);
+
+    // a committee that no longer hashes to its root is not served
+    let mut damaged = sync_committee(15);
+    damaged.pubkeys[0] = FixedBytes::repeat_byte(0xff);
+    let value = serde_json::to_vec(&damaged).unwrap();
+    db.put(SYNC_COMMITTEES.name, &15u64.to_be_bytes(), &value)
+        .unwrap();
+    assert_eq!(store.load(15).unwrap(), None);
+    assert_eq!(store.load(14).unwrap(), Some(sync_committee(14)));
+}
@@ -0,0 +1,73 @@
+use alloy::rpc::types::{Block, BlockTransactions, Withdrawals};
+use alloy_trie::EMPTY_ROOT_HASH;
+
+use helios_core::network_spec::NetworkSpec;
+use helios_core::testing::chain::{ChainBuilder, Fork};
+use helios_ethereum::spec::Ethereum;
+
+/// Mainnet block 1 as served by `eth_getBlockByNumber`, which has no transactions, no
+/// uncles and predates withdrawals.
+fn mainnet_block_1() -> Block {
+    let json = std::fs::read_to_string("testdata/execution/mainnet_block_1.json").unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn test_recorded_empty_block_is_valid() {
+    let block = mainnet_block_1();
+    assert!(block.transactions.is_empty());
+    assert!(block.withdrawals.is_none());
+    assert_eq!(block.header.transactions_root, EMPTY_ROOT_HASH);
+    assert_eq!(block.header.receipts_root, EMPTY_ROOT_HASH);
+
+    assert!(Ethereum::is_hash_valid(&block));
+
+    // the same block fetched without full transactions
+    let mut hashes_only = block.clone();
+    hashes_only.transactions = BlockTransactions::Hashes(vec![]);
+    assert!(Ethereum::is_hash_valid(&hashes_only));
+}
+
+#[test]
+fn test_empty_transactions_checked_against_root() {
+    let chain = ChainBuilder::new(1).length(2).txs_per_block(2).build();
+    let mut block = chain.head().clone();
+    assert!(!block.transactions.is_empty());
+
+    // an empty body is not accepted for a header committing to transactions
+    block.transactions = BlockTransactions::Hashes(vec![]);
+    assert!(!Ethereum::is_hash_valid(&block));
+
+    block.transactions = BlockTransactions::Full(vec![]);
+    assert!(!Ethereum::is_hash_valid(&block));
+}
+
+#[test]
+fn test_empty_and_absent_withdrawals() {
+    let chain = ChainBuilder::new(2)
+        .length(2)
+        .fork_at(0, Fork::Paris)
+        .fork_at(2, Fork::Shanghai)
+        .build();
+    let [paris, shanghai] = chain.blocks() else {
+        panic!("expected two blocks");
+    };
+
+    // after shanghai an empty list commits to the empty root
+    assert_eq!(shanghai.header.withdrawals_root, Some(EMPTY_ROOT_HASH));
+    assert_eq!(shanghai.withdrawals, Some(Withdrawals::new(vec![])));
+    assert!(Ethereum::is_hash_valid(shanghai));
+
+    // a response leaving the list out cannot be checked, but is not wrong either
+    let mut absent = shanghai.clone();
+    absent.withdrawals = None;
+    assert!(Ethereum::is_hash_valid(&absent));
+
+    // before shanghai the header has no withdrawals root, so even an empty list is not
+    // committed to
+    assert!(paris.header.withdrawals_root.is_none());
+    assert!(Ethereum::is_hash_valid(paris));
+    let mut claimed = paris.clone();
+    claimed.withdrawals = Some(Withdrawals::new(vec![]));
+    assert!(!Ethereum::is_hash_valid(&claimed));
+}
@@ -0,0 +1,190 @@
+use std::sync::Arc;
+
+use alloy::primitives::{hex, Address, Bytes, FixedBytes, B256, U256};
+use alloy::sol_types::SolError;
+
+use helios_core::client::ens::{namehash, reverse_name, EnsError, EnsResolver};
+use helios_core::errors::ClientError;
+use helios_core::execution::bundle::{AccountOverride, StateOverride};
+use helios_core::execution::ccip::OffchainLookup;
+use helios_core::execution::evm::Evm;
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+/// Returns the storage word at the key in the first argument, as for `resolver(node)`,
+/// `addr(node)` and `supportsInterface(id)`.
+const WORD_CODE: [u8; 12] = hex!("6004355460005260206000f3");
+
+/// Returns a string of at most 32 bytes, with the length at the key in the first argument
+/// and the bytes in the slot after it, as for `name(node)`.
+const NAME_CODE: [u8; 25] = hex!("602060005260043580546020526001015460405260606000f3");
+
+const REGISTRY: Address = Address::repeat_byte(0xe0);
+const RESOLVER: Address = Address::repeat_byte(0xe1);
+const REVERSE_RESOLVER: Address = Address::repeat_byte(0xe2);
+const OFFCHAIN_RESOLVER: Address = Address::repeat_byte(0xe3);
+
+/// Supports every interface, and reverts with `revert` on any other call.
+fn reverting_code(revert: &[u8]) -> Bytes {
+    let len = u16::try_from(revert.len()).unwrap().to_be_bytes();
+    let mut code = Vec::new();
+    // supportsInterface jumps to the return of true at 0x1d
+    code.extend(hex!("6301ffc9a760003560e01c14601d57"));
+    // the revert data is copied from the code after the 40 byte prefix
+    code.extend([0x61, len[0], len[1]]);
+    code.extend(hex!("6028600039"));
+    code.extend([0x61, len[0], len[1]]);
+    code.extend(hex!("6000fd"));
+    code.extend(hex!("5b600160005260206000f3"));
+    code.extend(revert);
+    code.into()
+}
+
+/// A contract running `code` with only `slots` in its storage.
+fn contract(code: &'static [u8], slots: Vec<(B256, B256)>) -> AccountOverride {
+    AccountOverride {
+        code: Some(Bytes::from_static(code)),
+        state: Some(slots.into_iter().collect()),
+        ..Default::default()
+    }
+}
+
+/// A registry pointing the nodes of `names` at their resolvers.
+fn registry(names: &[(&str, Address)]) -> (Address, AccountOverride) {
+    let slots = names
+        .iter()
+        .map(|(name, resolver)| (namehash(name), resolver.into_word()))
+        .collect();
+    (REGISTRY, contract(&WORD_CODE, slots))
+}
+
+fn resolver(addresses: &[(&str, Address)]) -> (Address, AccountOverride) {
+    let slots = addresses
+        .iter()
+        .map(|(name, address)| (namehash(name), address.into_word()))
+        .collect();
+    (RESOLVER, contract(&WORD_CODE, slots))
+}
+
+fn reverse_resolver(names: &[(Address, &str)]) -> (Address, AccountOverride) {
+    let slots = names
+        .iter()
+        .flat_map(|(address, name)| {
+            let node = namehash(&reverse_name(*address));
+            let next = B256::from(U256::from_be_bytes(node.0) + U256::from(1));
+            let mut bytes = B256::ZERO;
+            bytes[..name.len()].copy_from_slice(name.as_bytes());
+            [(node, B256::from(U256::from(name.len()))), (next, bytes)]
+        })
+        .collect();
+    (REVERSE_RESOLVER, contract(&NAME_CODE, slots))
+}
+
+async fn ens(chain: &MockChain, overrides: StateOverride) -> EnsResolver<Ethereum, ChainRpc> {
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let state = State::unsynced(64, rpc.clone());
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+    let client = Arc::new(ExecutionClient::<Ethereum, _>::with_rpc(
+        rpc,
+        state,
+        chain.fork_schedule(),
+    ));
+
+    let block = BlockTag::Number(chain.head().header.number);
+    let evm = Evm::new(client, chain.chain_id(), chain.fork_schedule(), block)
+        .with_state_overrides(overrides);
+    EnsResolver::new(evm, REGISTRY)
+}
+
+#[tokio::test]
+async fn test_resolve_registered_name() {
+    let chain = ChainBuilder::new(89).length(3).build();
+    let owner = chain.accounts()[0];
+    let overrides = StateOverride::from([
+        registry(&[("helios.eth", RESOLVER)]),
+        resolver(&[("helios.eth", owner)]),
+    ]);
+    let mut ens = ens(&chain, overrides).await;
+
+    assert_eq!(ens.resolve("Helios.eth").await.unwrap(), Some(owner));
+    assert_eq!(ens.resolve("unknown.eth").await.unwrap(), None);
+    // a resolver of a parent is only used for children if it is an extended resolver
+    assert_eq!(ens.resolve("sub.helios.eth").await.unwrap(), None);
+
+    let err = ens.resolve("helios..eth").await.unwrap_err();
+    assert!(
+        matches!(err, ClientError::EnsError(EnsError::InvalidName(_))),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_lookup_verifies_primary_name_resolves_back() {
+    let chain = ChainBuilder::new(90).length(3).build();
+    let owner = chain.accounts()[0];
+    let claimant = chain.accounts()[1];
+    let overrides = StateOverride::from([
+        registry(&[
+            ("helios.eth", RESOLVER),
+            (&reverse_name(owner), REVERSE_RESOLVER),
+            (&reverse_name(claimant), REVERSE_RESOLVER),
+        ]),
+        resolver(&[("helios.eth", owner)]),
+        reverse_resolver(&[(owner, "helios.eth"), (claimant, "helios.eth")]),
+    ]);
+    let mut ens = ens(&chain, overrides).await;
+
+    assert_eq!(
+        ens.lookup(owner).await.unwrap().as_deref(),
+        Some("helios.eth")
+    );
+    assert_eq!(ens.lookup(claimant).await.unwrap(), None);
+    assert_eq!(ens.lookup(chain.accounts()[2]).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn test_offchain_resolver_requires_ccip_read() {
+    let chain = ChainBuilder::new(91).length(3).build();
+    let lookup = OffchainLookup {
+        sender: OFFCHAIN_RESOLVER,
+        urls: vec!["https://gateway.example/{sender}/{data}.json".to_string()],
+        callData: Bytes::from_static(b"query"),
+        callbackFunction: FixedBytes([0xb4, 0xa8, 0x58, 0x01]),
+        extraData: Bytes::from_static(b"extra"),
+    };
+    let offchain = AccountOverride {
+        code: Some(reverting_code(&lookup.abi_encode())),
+        ..Default::default()
+    };
+    let overrides = StateOverride::from([
+        registry(&[("offchain.eth", OFFCHAIN_RESOLVER)]),
+        (OFFCHAIN_RESOLVER, offchain),
+    ]);
+    let mut ens = ens(&chain, overrides).await;
+
+    // resolved through the wildcard resolver of the parent
+    let err = ens.resolve("sub.offchain.eth").await.unwrap_err();
+    let ClientError::EnsError(EnsError::RequiresCcipRead {
+        name,
+        resolver,
+        urls,
+        call_data,
+        callback,
+        extra_data,
+    }) = &err
+    else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(name, "sub.offchain.eth");
+    assert_eq!(*resolver, OFFCHAIN_RESOLVER);
+    assert_eq!(*urls, lookup.urls);
+    assert_eq!(*call_data, lookup.callData);
+    assert_eq!(*callback, lookup.callbackFunction);
+    assert_eq!(*extra_data, lookup.extraData);
+}
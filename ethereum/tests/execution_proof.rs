@@ -0,0 +1,330 @@
+use alloy::primitives::{keccak256, Address, Bytes, FixedBytes, B256, U256};
+use alloy::rpc::types::{Block, Transaction};
+
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::proof::{beacon_header_root, merkle_root, verify_execution_proof};
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::consensus::verified_client;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::{
+    BeaconAttestation, BeaconBlockMapping, BeaconHeader, BlockTag, ExecutionProof, FinalityProof,
+    SyncAggregateInfo, EXECUTION_PROOF_VERSION,
+};
+use helios_ethereum::spec::Ethereum;
+
+type Client = ExecutionClient<Ethereum, ChainRpc>;
+
+async fn client(chain: &MockChain) -> (Client, State<Ethereum, ChainRpc>) {
+    let (client, state, _) = verified_client::<Ethereum>(chain).await;
+    (client, state)
+}
+
+fn nodes(seed: u8, count: u8) -> Vec<B256> {
+    (0..count).map(|i| keccak256([seed, i])).collect()
+}
+
+/// A beacon block carrying `block`, finalized by an attested header whose root is returned
+/// alongside it. The branches are made up, but consistent with the roots they prove.
+fn finalized_mapping(block: &Block<Transaction>, slot: u64) -> (BeaconBlockMapping, B256) {
+    let execution_branch = nodes(1, 9);
+    let header = BeaconHeader {
+        slot,
+        proposer_index: 7,
+        parent_root: keccak256(b"parent"),
+        state_root: keccak256(b"state"),
+        body_root: merkle_root(block.header.hash, &execution_branch, 812).unwrap(),
+    };
+    let root = beacon_header_root(&header);
+
+    let finality_branch = nodes(2, 6);
+    let attested_header = BeaconHeader {
+        slot: slot + 64,
+        proposer_index: 11,
+        parent_root: keccak256(b"attested parent"),
+        state_root: merkle_root(root, &finality_branch, 105).unwrap(),
+        body_root: keccak256(b"attested body"),
+    };
+    let attested_root = beacon_header_root(&attested_header);
+
+    let mapping = BeaconBlockMapping {
+        slot: header.slot,
+        epoch: slot / 32,
+        proposer_index: header.proposer_index,
+        parent_root: header.parent_root,
+        state_root: header.state_root,
+        body_root: header.body_root,
+        beacon_block_root: root,
+        execution_block_number: block.header.number,
+        execution_block_hash: block.header.hash,
+        attestation: Some(BeaconAttestation {
+            generalized_index: 812,
+            execution_branch,
+            finality: Some(FinalityProof {
+                attested_header,
+                generalized_index: 105,
+                branch: finality_branch,
+            }),
+            sync_aggregate: Some(SyncAggregateInfo {
+                sync_committee_bits: Bytes::from(vec![0xff; 64]),
+                sync_committee_signature: FixedBytes::repeat_byte(0xab),
+                signature_slot: slot + 65,
+            }),
+        }),
+    };
+    (mapping, attested_root)
+}
+
+async fn bundle(chain: &MockChain) -> (ExecutionProof, B256) {
+    let (client, state) = client(chain).await;
+    let (mapping, attested_root) = finalized_mapping(chain.head(), 9000);
+    state.push_beacon_mapping(mapping).await;
+
+    let token = chain.token();
+    let slots = chain
+        .storage(token)
+        .iter()
+        .map(|(slot, _)| *slot)
+        .collect::<Vec<_>>();
+    let proof = client
+        .get_execution_proof(token, &slots, BlockTag::Latest, true)
+        .await
+        .unwrap();
+    (proof, attested_root)
+}
+
+fn attestation(proof: &mut ExecutionProof) -> &mut BeaconAttestation {
+    proof.beacon.as_mut().unwrap().attestation.as_mut().unwrap()
+}
+
+#[tokio::test]
+async fn test_execution_proof_verifies() {
+    let chain = ChainBuilder::new(126).length(4).build();
+    let (proof, attested_root) = bundle(&chain).await;
+
+    assert_eq!(proof.version, EXECUTION_PROOF_VERSION);
+    assert_eq!(keccak256(&proof.header), chain.head().header.hash);
+    assert_eq!(proof.code.as_ref(), Some(&chain.code(chain.token())));
+    verify_execution_proof(&proof, attested_root).unwrap();
+
+    // the beacon block itself may be what the verifier trusts
+    let beacon_root = proof.beacon.as_ref().unwrap().beacon_block_root;
+    verify_execution_proof(&proof, beacon_root).unwrap();
+
+    let json = serde_json::to_string(&proof).unwrap();
+    let decoded: ExecutionProof = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, proof);
+    assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+    verify_execution_proof(&decoded, attested_root).unwrap();
+
+    let err = verify_execution_proof(&proof, B256::repeat_byte(0x11)).unwrap_err();
+    assert!(matches!(err, ExecutionError::UntrustedProof(..)), "{err}");
+}
+
+fn rejected(
+    proof: &ExecutionProof,
+    trusted_root: B256,
+    corrupt: impl FnOnce(&mut ExecutionProof),
+) -> ExecutionError {
+    let mut corrupted = proof.clone();
+    corrupt(&mut corrupted);
+    verify_execution_proof(&corrupted, trusted_root).unwrap_err()
+}
+
+#[tokio::test]
+async fn test_corrupted_execution_proof_rejected() {
+    let chain = ChainBuilder::new(127).length(4).build();
+    let (proof, root) = bundle(&chain).await;
+
+    let err = rejected(&proof, root, |proof| proof.version += 1);
+    assert!(
+        matches!(err, ExecutionError::UnsupportedProofVersion(2, 1)),
+        "{err}"
+    );
+    let err = rejected(&proof, root, |proof| {
+        proof.header = proof.header.slice(..proof.header.len() - 1)
+    });
+    assert!(
+        matches!(err, ExecutionError::InvalidProofHeader(_)),
+        "{err}"
+    );
+
+    // every link from the header to the trusted root
+    let links: &[(&str, fn(&mut ExecutionProof))] = &[
+        ("header", |proof| {
+            // inside the parent hash, the first field after the list prefix
+            let mut header = proof.header.to_vec();
+            header[5] ^= 1;
+            proof.header = header.into();
+        }),
+        ("execution block hash", |proof| {
+            proof.beacon.as_mut().unwrap().execution_block_hash = B256::ZERO
+        }),
+        ("execution branch", |proof| {
+            attestation(proof).execution_branch[3] = B256::ZERO
+        }),
+        ("execution generalized index", |proof| {
+            attestation(proof).generalized_index = 412
+        }),
+        ("body root", |proof| {
+            proof.beacon.as_mut().unwrap().body_root = B256::ZERO
+        }),
+        ("beacon slot", |proof| {
+            proof.beacon.as_mut().unwrap().slot += 1
+        }),
+        ("finality branch", |proof| {
+            attestation(proof).finality.as_mut().unwrap().branch[0] = B256::ZERO
+        }),
+        ("finality generalized index", |proof| {
+            attestation(proof)
+                .finality
+                .as_mut()
+                .unwrap()
+                .generalized_index = 169
+        }),
+        ("attested header", |proof| {
+            let finality = attestation(proof).finality.as_mut().unwrap();
+            finality.attested_header.proposer_index += 1;
+        }),
+        ("finality", |proof| attestation(proof).finality = None),
+        ("attestation", |proof| {
+            proof.beacon.as_mut().unwrap().attestation = None
+        }),
+        ("beacon block", |proof| proof.beacon = None),
+    ];
+    for (link, corrupt) in links {
+        let err = rejected(&proof, root, corrupt);
+        assert!(
+            matches!(err, ExecutionError::UntrustedProof(..)),
+            "{link}: {err}"
+        );
+    }
+
+    let address = proof.account.address;
+    for err in [
+        rejected(&proof, root, |proof| proof.account.balance += U256::from(1)),
+        rejected(&proof, root, |proof| {
+            proof.account.account_proof.pop();
+        }),
+        rejected(&proof, root, |proof| {
+            proof.account.address = Address::repeat_byte(0xee)
+        }),
+    ] {
+        assert!(
+            matches!(err, ExecutionError::InvalidAccountProof(_)),
+            "{err}"
+        );
+    }
+
+    let slot = proof.account.storage_proof[0].key.as_b256();
+    let err = rejected(&proof, root, |proof| {
+        proof.account.storage_proof[0].value += U256::from(1)
+    });
+    assert!(
+        matches!(err, ExecutionError::InvalidStorageProof(a, key) if a == address && key == slot),
+        "{err}"
+    );
+
+    let err = rejected(&proof, root, |proof| {
+        proof.code = Some(Bytes::from_static(&[0x60, 0x00]))
+    });
+    assert!(
+        matches!(err, ExecutionError::CodeHashMismatch(a, ..) if a == address),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_execution_proof_without_beacon_block() {
+    let chain = ChainBuilder::new(128).length(4).build();
+    let (client, _state) = client(&chain).await;
+    let token = chain.token();
+    let (slot, _) = chain.storage(token)[0];
+
+    let proof = client
+        .get_execution_proof(token, &[slot], BlockTag::Latest, false)
+        .await
+        .unwrap();
+    assert_eq!(proof.beacon, None);
+    assert_eq!(proof.code, None);
+
+    // without a beacon chain link only the block hash itself can be trusted
+    verify_execution_proof(&proof, chain.head().header.hash).unwrap();
+    let err = verify_execution_proof(&proof, B256::repeat_byte(0x11)).unwrap_err();
+    assert!(
+        matches!(err, ExecutionError::UntrustedProof(_, "no beacon block")),
+        "{err}"
+    );
+}
+
+/// A bundle for the parent of the head, which is held with a beacon block but no attestation
+/// of its own, while the head is attested.
+async fn ancestor_bundle(chain: &MockChain) -> (ExecutionProof, B256) {
+    let (client, state) = client(chain).await;
+    let parent = chain.block(chain.head().header.number - 1).unwrap();
+    let (mut mapping, _) = finalized_mapping(parent, 8999);
+    mapping.attestation = None;
+    state.push_beacon_mapping(mapping).await;
+    let (mapping, attested_root) = finalized_mapping(chain.head(), 9000);
+    state.push_beacon_mapping(mapping).await;
+
+    let token = chain.token();
+    let (slot, _) = chain.storage(token)[0];
+    let proof = client
+        .get_execution_proof(
+            token,
+            &[slot],
+            BlockTag::Number(parent.header.number),
+            false,
+        )
+        .await
+        .unwrap();
+    (proof, attested_root)
+}
+
+#[tokio::test]
+async fn test_ancestor_proof_carries_descendants() {
+    let chain = ChainBuilder::new(174).length(4).build();
+    let (proof, attested_root) = ancestor_bundle(&chain).await;
+
+    assert_eq!(proof.descendants.len(), 1);
+    assert_eq!(keccak256(&proof.descendants[0]), chain.head().header.hash);
+    assert_eq!(
+        proof.beacon.as_ref().unwrap().execution_block_hash,
+        chain.head().header.hash
+    );
+    verify_execution_proof(&proof, attested_root).unwrap();
+
+    let err = rejected(&proof, attested_root, |proof| proof.descendants.clear());
+    assert!(matches!(err, ExecutionError::UntrustedProof(..)), "{err}");
+    let err = rejected(&proof, attested_root, |proof| {
+        // inside the parent hash, the first field after the list prefix
+        let mut descendant = proof.descendants[0].to_vec();
+        descendant[5] ^= 1;
+        proof.descendants[0] = descendant.into();
+    });
+    assert!(matches!(err, ExecutionError::UntrustedProof(..)), "{err}");
+}
+
+#[tokio::test]
+async fn test_unattested_proof_without_attested_descendant_rejected() {
+    let chain = ChainBuilder::new(175).length(4).build();
+    let (client, state) = client(&chain).await;
+    let (mut mapping, _) = finalized_mapping(chain.head(), 9000);
+    mapping.attestation = None;
+    state.push_beacon_mapping(mapping).await;
+
+    let token = chain.token();
+    let err = client
+        .get_execution_proof(token, &[], BlockTag::Latest, false)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::NoAttestedDescendant(4))
+        ),
+        "{err}"
+    );
+}
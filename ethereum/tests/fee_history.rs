@@ -0,0 +1,221 @@
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::fee_history::{FeeHistoryConfig, GasPriceConfig};
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::harness;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+type Client = ExecutionClient<Ethereum, ChainRpc>;
+type Harness = harness::Harness<Ethereum>;
+
+/// An execution client holding only the last 4 blocks of a chain of 8, serving fee history
+/// with `config`.
+async fn fee_history_client(chain: &MockChain, config: FeeHistoryConfig) -> (Client, ChainRpc) {
+    let harness = Harness::with_history_length(chain, 4);
+    harness.push_chain(chain).await;
+    (harness.client.with_fee_history(config), harness.rpc)
+}
+
+#[tokio::test]
+async fn test_fee_history_verified() {
+    let chain = ChainBuilder::new(8).length(8).build();
+    let (client, _) = fee_history_client(&chain, FeeHistoryConfig::default()).await;
+    let head = chain.head().header.number;
+
+    let fees = client
+        .get_fee_history(3, BlockTag::Latest, &[25.0, 75.0])
+        .await
+        .unwrap();
+    assert_eq!(fees.history.oldest_block, head - 2);
+    assert_eq!(fees.history.base_fee_per_gas.len(), 4);
+    assert_eq!(fees.history.gas_used_ratio.len(), 3);
+    assert_eq!(fees.history.reward.unwrap().len(), 3);
+    assert_eq!(fees.verified_range, None);
+
+    // ranges before the verified blocks are cut short to them
+    let fees = client
+        .get_fee_history(8, BlockTag::Number(head - 1), &[])
+        .await
+        .unwrap();
+    assert_eq!(fees.history.oldest_block, head - 3);
+    assert_eq!(fees.history.gas_used_ratio.len(), 3);
+
+    // and past the head to it
+    let fees = client
+        .get_fee_history(2, BlockTag::Number(head + 10), &[])
+        .await
+        .unwrap();
+    assert_eq!(fees.history.oldest_block, head - 1);
+}
+
+#[tokio::test]
+async fn test_fee_history_partially_verified() {
+    let chain = ChainBuilder::new(8).length(8).build();
+    let config = FeeHistoryConfig {
+        allow_partial: true,
+    };
+    let (client, rpc) = fee_history_client(&chain, config).await;
+    let head = chain.head().header.number;
+
+    let fees = client
+        .get_fee_history(8, BlockTag::Latest, &[])
+        .await
+        .unwrap();
+    assert_eq!(fees.history.oldest_block, chain.blocks()[0].header.number);
+    assert_eq!(fees.history.gas_used_ratio.len(), 8);
+    assert_eq!(fees.verified_range, Some([head - 3, head]));
+    let value = serde_json::to_value(&fees).unwrap();
+    assert_eq!(value["verifiedRange"], serde_json::json!([head - 3, head]));
+
+    // entries before the verified blocks are served as reported
+    rpc.map_fee_history(|mut history| {
+        history.base_fee_per_gas[0] += 1;
+        history
+    });
+    client
+        .get_fee_history(8, BlockTag::Latest, &[])
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_fee_history_falsified_fees_rejected() {
+    let chain = ChainBuilder::new(8).length(8).build();
+    let (client, rpc) = fee_history_client(&chain, FeeHistoryConfig::default()).await;
+    let head = chain.head().header.number;
+
+    rpc.map_fee_history(|mut history| {
+        history.base_fee_per_gas[1] -= 1;
+        history
+    });
+    let err = client
+        .get_fee_history(3, BlockTag::Latest, &[])
+        .await
+        .unwrap_err();
+    let err = err.to_string();
+    assert!(err.contains("baseFeePerGas"), "{err}");
+    assert!(err.contains(&format!("block {}", head - 1)), "{err}");
+
+    // the base fee after the head follows from its header
+    rpc.map_fee_history(|mut history| {
+        *history.base_fee_per_gas.last_mut().unwrap() += 1;
+        history
+    });
+    let err = client
+        .get_fee_history(3, BlockTag::Latest, &[])
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains(&format!("block {}", head + 1)),
+        "{err}"
+    );
+
+    rpc.map_fee_history(|mut history| {
+        history.gas_used_ratio[0] = 0.99;
+        history
+    });
+    let err = client
+        .get_fee_history(3, BlockTag::Latest, &[])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("gasUsedRatio"), "{err}");
+
+    // as does a history leaving blocks out
+    rpc.map_fee_history(|mut history| {
+        history.oldest_block += 1;
+        history.base_fee_per_gas.remove(0);
+        history.gas_used_ratio.remove(0);
+        history
+    });
+    let err = client
+        .get_fee_history(3, BlockTag::Latest, &[])
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("not covering"), "{err}");
+}
+
+fn gwei(gwei: u128) -> u128 {
+    gwei * 1_000_000_000
+}
+
+#[tokio::test]
+async fn test_priority_fee_from_verified_fee_history() {
+    let chain = ChainBuilder::new(8).length(8).build();
+    let (client, rpc) = fee_history_client(&chain, FeeHistoryConfig::default()).await;
+    let client = client.with_gas_price(GasPriceConfig {
+        blocks: 20,
+        percentile: 60.0,
+    });
+
+    // only the 4 verified blocks are asked for, of the 20 configured
+    rpc.map_fee_history(|mut history| {
+        assert_eq!(history.gas_used_ratio.len(), 4);
+        history.reward = Some(vec![
+            vec![gwei(3)],
+            vec![gwei(1)],
+            vec![gwei(4)],
+            vec![gwei(2)],
+        ]);
+        history
+    });
+
+    assert_eq!(client.get_priority_fee().await.unwrap(), gwei(3));
+    let base_fee = chain.head().header.base_fee_per_gas.unwrap() as u128;
+    assert_eq!(client.get_gas_price().await.unwrap(), base_fee + gwei(3));
+
+    // kept until the head changes
+    rpc.disconnect();
+    assert_eq!(client.get_priority_fee().await.unwrap(), gwei(3));
+}
+
+#[tokio::test]
+async fn test_percentiles_out_of_range_rejected() {
+    let config = serde_json::from_value::<GasPriceConfig>(serde_json::json!({
+        "percentile": 101.0,
+    }));
+    assert!(config.is_err());
+
+    let chain = ChainBuilder::new(8).length(8).build();
+    let (client, rpc) = fee_history_client(&chain, FeeHistoryConfig::default()).await;
+    let client = client.with_gas_price(GasPriceConfig {
+        blocks: 20,
+        percentile: -1.0,
+    });
+    let err = client.get_priority_fee().await.unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::InvalidPercentile(_))
+        ),
+        "{err}"
+    );
+    assert_eq!(rpc.calls("get_fee_history"), 0);
+}
+
+#[tokio::test]
+async fn test_priority_fee_skips_empty_blocks() {
+    let chain = ChainBuilder::new(8).length(8).empty_block(8).build();
+    let (client, rpc) = fee_history_client(&chain, FeeHistoryConfig::default()).await;
+    let client = client.with_gas_price(GasPriceConfig {
+        blocks: 2,
+        percentile: 50.0,
+    });
+
+    rpc.map_fee_history(|mut history| {
+        history.reward = Some(vec![vec![gwei(5)], vec![0]]);
+        history
+    });
+    assert_eq!(client.get_priority_fee().await.unwrap(), gwei(5));
+
+    // the base fees are still checked
+    let chain = ChainBuilder::new(8).length(8).build();
+    let (client, rpc) = fee_history_client(&chain, FeeHistoryConfig::default()).await;
+    rpc.map_fee_history(|mut history| {
+        history.base_fee_per_gas[0] += 1;
+        history
+    });
+    let err = client.get_priority_fee().await.unwrap_err();
+    assert!(err.to_string().contains("baseFeePerGas"), "{err}");
+}
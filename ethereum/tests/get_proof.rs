@@ -0,0 +1,115 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::{keccak256, Address, B256, U256};
+use alloy::rpc::types::EIP1186AccountProofResponse;
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::Error;
+use jsonrpsee::rpc_params;
+use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
+
+use helios_core::client::node::Node;
+use helios_core::client::rpc::Rpc;
+use helios_core::errors::codes;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::consensus::{execution_config, MockConsensus};
+use helios_core::testing::rpc::ChainRpc;
+use helios_ethereum::spec::Ethereum;
+
+/// A client of an rpc server following every block of `chain`, and the provider behind it.
+async fn serve(seed: u64) -> (MockChain, WsClient, ChainRpc) {
+    // blocks from now on, so the head never looks out of sync
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let chain = ChainBuilder::new(seed)
+        .length(4)
+        .genesis_timestamp(now.as_secs())
+        .build();
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let (consensus, feed) = MockConsensus::new(chain.chain_id());
+    let node = Arc::new(Node::<Ethereum, _>::with_execution_rpc(
+        rpc.http(),
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+    ));
+    let mut server = Rpc::new(node.clone(), SocketAddr::from(([127, 0, 0, 1], 0)));
+    let addr = server.start().await.unwrap().unwrap();
+
+    for block in chain.blocks() {
+        feed.advance(block.clone()).await.unwrap();
+    }
+    let head = chain.head().header.number;
+    node.execution
+        .sync_progress()
+        .subscribe()
+        .wait_for(|progress| progress.current_block == Some(head))
+        .await
+        .unwrap();
+
+    let client = WsClientBuilder::default()
+        .build(format!("ws://{addr}"))
+        .await
+        .unwrap();
+    (chain, client, rpc)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_proof_verified() {
+    let (chain, client, _rpc) = serve(192).await;
+
+    let token = chain.token();
+    let storage = chain.storage(token);
+    let slots = storage
+        .iter()
+        .map(|(slot, _)| U256::from_be_bytes(slot.0))
+        .collect::<Vec<_>>();
+    let proof: EIP1186AccountProofResponse = client
+        .request("eth_getProof", rpc_params![token, slots, "latest"])
+        .await
+        .unwrap();
+    assert_eq!(proof.address, token);
+    assert_eq!(proof.code_hash, keccak256(chain.code(token)));
+    assert_eq!(proof.storage_proof.len(), storage.len());
+    for (storage_proof, (slot, value)) in proof.storage_proof.iter().zip(&storage) {
+        assert_eq!(storage_proof.key.as_b256(), *slot);
+        assert_eq!(storage_proof.value, *value);
+    }
+
+    // an account that does not exist, and a slot never written
+    let empty = Address::repeat_byte(0xee);
+    let proof: EIP1186AccountProofResponse = client
+        .request("eth_getProof", rpc_params![empty, [U256::ZERO], "latest"])
+        .await
+        .unwrap();
+    assert_eq!(proof.balance, U256::ZERO);
+    assert_eq!(proof.nonce, 0);
+    assert_eq!(proof.storage_proof[0].key.as_b256(), B256::ZERO);
+    assert_eq!(proof.storage_proof[0].value, U256::ZERO);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_proof_tampered_storage_rejected() {
+    let (chain, client, rpc) = serve(193).await;
+
+    let token = chain.token();
+    let (slot, _) = chain.storage(token)[0];
+    rpc.map_proofs(|mut proof| {
+        for storage_proof in &mut proof.storage_proof {
+            storage_proof.value += U256::from(1);
+        }
+        proof
+    });
+
+    let err = client
+        .request::<EIP1186AccountProofResponse, _>(
+            "eth_getProof",
+            rpc_params![token, [U256::from_be_bytes(slot.0)], "latest"],
+        )
+        .await
+        .unwrap_err();
+    let Error::Call(error) = err else {
+        panic!("{err}");
+    };
+    assert_eq!(error.code(), codes::VERIFICATION_FAILED, "{error}");
+}
@@ -0,0 +1,166 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy::primitives::B256;
+use serde_json::json;
+
+use helios_core::database::redb::RedbStore;
+use helios_core::database::Database;
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::historical::VERIFIED_HEADERS;
+use helios_core::execution::state::State;
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::consensus::verified_client;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+#[tokio::test]
+async fn test_balance_before_window_proven_against_walked_header() {
+    let chain = ChainBuilder::new(72).length(200).build();
+    let (client, state, rpc) = verified_client::<Ethereum>(&chain).await;
+    let oldest = state.oldest_block_number().await.unwrap();
+    let number = oldest - 100;
+    let address = chain.accounts()[0];
+
+    let account = client
+        .get_account(address, None, BlockTag::Number(number))
+        .await
+        .unwrap();
+    assert_eq!(account.balance, chain.proof(address, &[]).balance);
+
+    let header = state.get_verified_header(number).await.unwrap();
+    assert_eq!(header.hash, chain.block(number).unwrap().header.hash);
+
+    // the walked headers and the proven account are kept
+    rpc.disconnect();
+    let again = client
+        .get_account(address, None, BlockTag::Number(number))
+        .await
+        .unwrap();
+    assert_eq!(again.balance, account.balance);
+}
+
+#[tokio::test]
+async fn test_forged_intermediate_header_rejected() {
+    let chain = ChainBuilder::new(73).length(200).build();
+    let (client, state, rpc) = verified_client::<Ethereum>(&chain).await;
+    let oldest = state.oldest_block_number().await.unwrap();
+    let forged = oldest - 50;
+    rpc.map_blocks(move |block| {
+        if block["number"] == json!(format!("{forged:#x}")) {
+            block["stateRoot"] = json!(B256::repeat_byte(0xaa));
+        }
+    });
+
+    let err = client
+        .get_account(chain.accounts()[0], None, BlockTag::Number(oldest - 100))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::InvalidHeaderHash(_))
+        ),
+        "{err}"
+    );
+    assert!(state.get_verified_header(oldest - 100).await.is_none());
+}
+
+#[tokio::test]
+async fn test_walked_headers_survive_restart() {
+    let chain = ChainBuilder::new(149).length(200).build();
+    let dir = tempfile::tempdir().unwrap();
+    let db: Arc<dyn Database> = Arc::new(RedbStore::open(dir.path().join("db")).unwrap());
+
+    let (client, state, _) = verified_client::<Ethereum>(&chain).await;
+    state.persist_historical_headers(db.clone()).unwrap();
+    let number = state.oldest_block_number().await.unwrap() - 100;
+    client
+        .get_account(chain.accounts()[0], None, BlockTag::Number(number))
+        .await
+        .unwrap();
+
+    // the headers are written in the background
+    let key = number.to_be_bytes();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while db.get(VERIFIED_HEADERS, &key).unwrap().is_none() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("walked headers never stored");
+
+    // a restarted client takes them over without walking back again
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let state = State::<Ethereum, _>::unsynced(64, rpc.clone());
+    state.persist_historical_headers(db).unwrap();
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+    let header = state.get_verified_header(number).await.unwrap();
+    assert_eq!(header.hash, chain.block(number).unwrap().header.hash);
+}
+
+#[tokio::test]
+async fn test_forged_branch_rejected() {
+    let chain = ChainBuilder::new(170).length(200).build();
+    let (client, state, rpc) = verified_client::<Ethereum>(&chain).await;
+    let oldest = state.oldest_block_number().await.unwrap();
+    // headers that hash correctly and link to each other, but not to the verified window
+    rpc.set_chain(chain.reorg(100, 171));
+
+    let err = client
+        .get_account(chain.accounts()[0], None, BlockTag::Number(oldest - 50))
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::BrokenHeaderChain(number, _)) if *number == oldest - 1
+        ),
+        "{err}"
+    );
+    assert!(state.get_verified_header(oldest - 1).await.is_none());
+}
+
+#[tokio::test]
+async fn test_walked_headers_follow_window() {
+    let mut chain = ChainBuilder::new(172).length(200).build();
+    let (client, state, rpc) = verified_client::<Ethereum>(&chain).await;
+    let oldest = state.oldest_block_number().await.unwrap();
+    let number = oldest - 100;
+    client
+        .get_account(chain.accounts()[0], None, BlockTag::Number(number))
+        .await
+        .unwrap();
+
+    // the blocks leaving the window keep the walked headers linked to it
+    chain.extend(10);
+    rpc.set_chain(chain.clone());
+    for block in &chain.blocks()[200..] {
+        state.push_block(block.clone()).await;
+    }
+    assert_eq!(state.oldest_block_number().await, Some(oldest + 10));
+    let walked = rpc.calls("get_block_by_number");
+    client
+        .get_account(chain.accounts()[0], None, BlockTag::Number(number))
+        .await
+        .unwrap();
+    assert_eq!(rpc.calls("get_block_by_number"), walked);
+    let evicted = state.get_verified_header(oldest).await.unwrap();
+    assert_eq!(evicted.hash, chain.block(oldest).unwrap().header.hash);
+
+    // a branch forking off under the window unlinks the headers it replaces
+    let fork = chain.reorg(70, 173);
+    rpc.set_chain(fork.clone());
+    state.push_block(fork.head().clone()).await;
+    let original = chain.block(oldest).unwrap().header.hash;
+    assert_ne!(
+        state
+            .get_verified_header(oldest)
+            .await
+            .map(|header| header.hash),
+        Some(original)
+    );
+}
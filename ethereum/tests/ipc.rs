@@ -0,0 +1,154 @@
+#![cfg(unix)]
+
+use std::net::SocketAddr;
+use std::os::unix::fs::PermissionsExt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::network::Network;
+use jsonrpsee::core::server::Methods;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+use helios_core::client::ipc::{IpcConfig, IpcServer};
+use helios_core::client::node::Node;
+use helios_core::client::rpc::Rpc;
+use helios_core::execution::rpc::http_rpc::HttpRpc;
+use helios_core::execution::rpc::ExecutionRpc;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::consensus::{execution_config, ConsensusFeed, MockConsensus};
+use helios_ethereum::spec::Ethereum;
+
+type Block = <Ethereum as Network>::BlockResponse;
+
+fn socket_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("helios-{}-{name}.sock", std::process::id()))
+}
+
+/// A node following `chain` over the mock consensus. Its execution provider is unreachable,
+/// so only methods answered from the verified blocks succeed.
+fn node(
+    chain: &MockChain,
+) -> (
+    Arc<Node<Ethereum, MockConsensus<Block>>>,
+    ConsensusFeed<Block>,
+) {
+    let (consensus, feed) = MockConsensus::new(chain.chain_id());
+    let rpc = <HttpRpc<Ethereum> as ExecutionRpc<Ethereum>>::new("http://127.0.0.1:1").unwrap();
+    let node = Node::<Ethereum, _>::with_execution_rpc(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+    );
+    (Arc::new(node), feed)
+}
+
+fn request(id: u64, method: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": [] })
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_ipc_answers_like_http() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let chain = ChainBuilder::new(110)
+        .length(3)
+        .genesis_timestamp(now.as_secs())
+        .build();
+    let (node, feed) = node(&chain);
+    for block in chain.blocks() {
+        feed.advance(block.clone()).await.unwrap();
+    }
+    let mut heads = node.execution.head_updates();
+    heads.wait_for(Option::is_some).await.unwrap();
+
+    let path = socket_path("answers");
+    let mut server = Rpc::new(node, SocketAddr::from(([127, 0, 0, 1], 0)))
+        .with_ipc(IpcConfig::new(path.clone()));
+    let addr = server.start().await.unwrap().unwrap();
+
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    let stream = UnixStream::connect(&path).await.unwrap();
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let http = reqwest::Client::new();
+    for (id, method) in [(1, "eth_chainId"), (2, "eth_blockNumber")] {
+        let request = request(id, method);
+        writer
+            .write_all(format!("{request}\n").as_bytes())
+            .await
+            .unwrap();
+        let line = lines.next_line().await.unwrap().unwrap();
+        let over_ipc: Value = serde_json::from_str(&line).unwrap();
+
+        let over_http: Value = http
+            .post(format!("http://{addr}"))
+            .json(&request)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(over_ipc.get("result").is_some(), "{over_ipc}");
+        assert_eq!(over_ipc, over_http);
+    }
+
+    // a batch is answered on one line
+    let batch = json!([request(3, "eth_chainId"), request(4, "eth_blockNumber")]);
+    writer
+        .write_all(format!("{batch}\n").as_bytes())
+        .await
+        .unwrap();
+    let line = lines.next_line().await.unwrap().unwrap();
+    let responses: Vec<Value> = serde_json::from_str(&line).unwrap();
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["id"], 3);
+
+    // stopping closes the connection the client still holds open
+    server.stop(Duration::from_secs(1)).await;
+    assert!(!path.exists());
+    let closed = tokio::time::timeout(Duration::from_secs(5), lines.next_line())
+        .await
+        .expect("connection outlived the server");
+    assert!(matches!(closed, Ok(None) | Err(_)), "{closed:?}");
+}
+
+#[tokio::test]
+async fn test_stale_socket_replaced_and_live_one_kept() {
+    let path = socket_path("stale");
+    // an unclean shutdown leaves the file of a socket nothing listens on
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+    assert!(path.exists());
+
+    let (node, _feed) = node(&ChainBuilder::new(111).build());
+    let config = IpcConfig {
+        path: path.clone(),
+        permissions: 0o660,
+    };
+    let mut server = Rpc::ipc_only(node, config.clone());
+    assert_eq!(server.start().await.unwrap(), None);
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o660);
+    // the directory it was bound in is gone
+    let private_dir = path.with_file_name(format!(
+        ".{}.{}",
+        path.file_name().unwrap().to_string_lossy(),
+        std::process::id()
+    ));
+    assert!(!private_dir.exists());
+
+    let err = IpcServer::start(&config, Methods::new())
+        .await
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("another process"), "{err}");
+    assert!(path.exists());
+
+    server.stop(Duration::from_secs(1)).await;
+    assert!(!path.exists());
+}
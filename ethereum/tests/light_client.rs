@@ -0,0 +1,170 @@
+use alloy::primitives::B256;
+
+use helios_consensus_core::consensus_spec::MinimalConsensusSpec;
+use helios_consensus_core::errors::ConsensusError;
+use helios_consensus_core::testing::{MockBeaconBlock, MockBeaconChain};
+use helios_consensus_core::types::{FinalityUpdate, LightClientStore};
+use helios_consensus_core::{
+    apply_bootstrap, apply_finality_update, apply_optimistic_update, apply_update,
+    verify_bootstrap, verify_finality_update, verify_optimistic_update, verify_update,
+};
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+
+type Spec = MinimalConsensusSpec;
+
+/// Beacon blocks at the start of consecutive epochs carrying the blocks of `chain`, each
+/// finalizing the one before it.
+fn beacon_blocks(beacon: &MockBeaconChain<Spec>, chain: &MockChain) -> Vec<MockBeaconBlock> {
+    let mut blocks: Vec<MockBeaconBlock> = Vec::new();
+    for (epoch, block) in chain.blocks().iter().enumerate() {
+        let slot = 8 * (epoch as u64 + 1);
+        blocks.push(beacon.block(slot, &block.header, blocks.last()));
+    }
+    blocks
+}
+
+/// A store bootstrapped from `block`.
+fn store(beacon: &MockBeaconChain<Spec>, block: &MockBeaconBlock) -> LightClientStore<Spec> {
+    let bootstrap = beacon.bootstrap(block);
+    verify_bootstrap(&bootstrap, block.checkpoint(), beacon.forks()).unwrap();
+
+    let mut store = LightClientStore::default();
+    apply_bootstrap(&mut store, &bootstrap);
+    store
+}
+
+fn assert_rejected(
+    beacon: &MockBeaconChain<Spec>,
+    store: &LightClientStore<Spec>,
+    update: &FinalityUpdate<Spec>,
+    expected: ConsensusError,
+) {
+    let slot = *update.signature_slot();
+    let err = verify_finality_update(update, slot, store, beacon.genesis_root(), beacon.forks())
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ConsensusError>()
+            .map(ToString::to_string),
+        Some(expected.to_string()),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_finality_follows_mock_chain() {
+    let chain = ChainBuilder::new(180).length(4).build();
+    let beacon = MockBeaconChain::<Spec>::new(180);
+    let blocks = beacon_blocks(&beacon, &chain);
+    let mut store = store(&beacon, &blocks[0]);
+
+    let update = beacon.finality_update(&blocks[2], 25);
+    verify_finality_update(&update, 25, &store, beacon.genesis_root(), beacon.forks()).unwrap();
+    let checkpoint = apply_finality_update(&mut store, &update);
+
+    // the finalized header opens an epoch, so it becomes the new checkpoint
+    assert_eq!(checkpoint, Some(blocks[1].checkpoint()));
+    assert_eq!(
+        *store.finalized_header.execution().unwrap().block_hash(),
+        chain.block(2).unwrap().header.hash
+    );
+
+    let update = beacon.optimistic_update(&blocks[3], 33);
+    verify_optimistic_update(&update, 33, &store, beacon.genesis_root(), beacon.forks()).unwrap();
+    apply_optimistic_update(&mut store, &update);
+
+    assert_eq!(
+        *store.optimistic_header.execution().unwrap().block_hash(),
+        chain.head().header.hash
+    );
+    assert_eq!(blocks[3].execution_block_hash(), chain.head().header.hash);
+}
+
+#[test]
+fn test_tampered_updates_rejected() {
+    let chain = ChainBuilder::new(181).length(3).build();
+    let beacon = MockBeaconChain::<Spec>::new(181);
+    let blocks = beacon_blocks(&beacon, &chain);
+    let store = store(&beacon, &blocks[0]);
+
+    let bootstrap = beacon.bootstrap(&blocks[0]);
+    let err = verify_bootstrap(&bootstrap, blocks[1].checkpoint(), beacon.forks()).unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ConsensusError>(),
+            Some(ConsensusError::InvalidHeaderHash(..))
+        ),
+        "{err}"
+    );
+
+    // signed by a committee the store does not know
+    let other = MockBeaconChain::<Spec>::new(182);
+    let update = other.finality_update(&blocks[2], 25);
+    assert_rejected(&beacon, &store, &update, ConsensusError::InvalidSignature);
+
+    let mut update = beacon.finality_update(&blocks[2], 25);
+    let FinalityUpdate::Electra(inner) = &mut update else {
+        unreachable!()
+    };
+    inner.finality_branch[3] = B256::repeat_byte(1);
+    assert_rejected(
+        &beacon,
+        &store,
+        &update,
+        ConsensusError::InvalidFinalityProof,
+    );
+
+    // a header other than the one the attested state finalizes
+    let mut update = beacon.finality_update(&blocks[2], 25);
+    let FinalityUpdate::Electra(inner) = &mut update else {
+        unreachable!()
+    };
+    inner.finalized_header = blocks[0].header.clone();
+    assert_rejected(
+        &beacon,
+        &store,
+        &update,
+        ConsensusError::InvalidFinalityProof,
+    );
+
+    // an execution block other than the one the beacon block commits to
+    let mut update = beacon.finality_update(&blocks[2], 25);
+    let FinalityUpdate::Electra(inner) = &mut update else {
+        unreachable!()
+    };
+    *inner
+        .attested_header
+        .execution_mut()
+        .unwrap()
+        .block_hash_mut() = chain.block(1).unwrap().header.hash;
+    assert_rejected(
+        &beacon,
+        &store,
+        &update,
+        ConsensusError::InvalidExecutionPayloadProof,
+    );
+}
+
+#[test]
+fn test_sync_committee_rotates() {
+    let chain = ChainBuilder::new(183).length(4).build();
+    let beacon = MockBeaconChain::<Spec>::new(183);
+    let blocks = beacon_blocks(&beacon, &chain);
+    let mut store = store(&beacon, &blocks[0]);
+
+    // learns the committee of the next period from an update finalizing the bootstrap
+    let update = beacon.update(&blocks[1], 17);
+    verify_update(&update, 17, &store, beacon.genesis_root(), beacon.forks()).unwrap();
+    apply_update(&mut store, &update);
+    assert_eq!(store.next_sync_committee, Some(beacon.committee(1)));
+
+    // a period later, the next committee signs and the store moves on to it
+    let finalized = beacon.block(64, &chain.block(3).unwrap().header, None);
+    let attested = beacon.block(72, &chain.head().header, Some(&finalized));
+    let update = beacon.update(&attested, 73);
+    verify_update(&update, 73, &store, beacon.genesis_root(), beacon.forks()).unwrap();
+    let checkpoint = apply_update(&mut store, &update);
+
+    assert_eq!(checkpoint, Some(finalized.checkpoint()));
+    assert_eq!(store.current_sync_committee, beacon.committee(1));
+    assert_eq!(store.next_sync_committee, Some(beacon.committee(2)));
+}
@@ -0,0 +1,137 @@
+use alloy::primitives::{keccak256, Address};
+use alloy::rpc::types::{Filter, Log};
+
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::log_pages::{LogCursor, LogPage};
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::consensus::verified_client;
+use helios_core::testing::rpc::ChainRpc;
+use helios_ethereum::spec::Ethereum;
+
+type Client = ExecutionClient<Ethereum, ChainRpc>;
+
+/// A chain of six blocks with two `Transfer` logs each.
+fn chain(seed: u64) -> MockChain {
+    ChainBuilder::new(seed).length(6).txs_per_block(2).build()
+}
+
+async fn page(client: &Client, filter: &Filter, cursor: Option<&str>, size: usize) -> LogPage {
+    client.get_logs_paged(filter, cursor, size).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_range_spans_pages() {
+    let chain = chain(133);
+    let (client, _, rpc) = verified_client::<Ethereum>(&chain).await;
+    let filter = Filter::new().from_block(1).to_block(6);
+
+    let mut logs: Vec<Log> = Vec::new();
+    let mut sizes = Vec::new();
+    let mut cursor = None;
+    loop {
+        let page = page(&client, &filter, cursor.as_deref(), 5).await;
+        sizes.push(page.logs.len());
+        logs.extend(page.logs);
+        match page.cursor {
+            Some(next) => cursor = Some(next),
+            None => break,
+        }
+    }
+
+    assert_eq!(sizes, [5, 5, 2]);
+    assert_eq!(logs, chain.logs());
+    // later pages are served from the receipts verified for the first
+    assert_eq!(rpc.calls("get_block_receipts"), 6);
+}
+
+#[tokio::test]
+async fn test_stale_cursor_after_reorg() {
+    let chain = chain(134);
+    let (client, state, rpc) = verified_client::<Ethereum>(&chain).await;
+    let filter = Filter::new().from_block(1).to_block(6);
+
+    // ends within block 3, which the reorg replaces
+    let first = page(&client, &filter, None, 5).await;
+    let cursor = first.cursor.unwrap();
+
+    let fork = chain.reorg(4, 135);
+    rpc.set_chain(fork.clone());
+    for block in &fork.blocks()[2..] {
+        state.push_block(block.clone()).await;
+    }
+
+    let err = client
+        .get_logs_paged(&filter, Some(&cursor), 5)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::StaleLogCursor(3, 3))
+        ),
+        "{err}"
+    );
+
+    // restarting at the block named gives the logs of the new branch only
+    let rest = page(&client, &filter.clone().from_block(3), None, 10).await;
+    assert!(rest.cursor.is_none());
+    let logs = first
+        .logs
+        .into_iter()
+        .filter(|log| log.block_number < Some(3))
+        .chain(rest.logs)
+        .collect::<Vec<_>>();
+    assert_eq!(logs, fork.logs());
+}
+
+#[tokio::test]
+async fn test_unknown_stale_cursor_restarts_conservatively() {
+    let chain = chain(143);
+    let (client, _, _) = verified_client::<Ethereum>(&chain).await;
+    let filter = Filter::new().from_block(1).to_block(6);
+
+    // a cursor on a block the orphan cache never held cannot be followed back to the fork
+    let cursor = LogCursor {
+        block: 3,
+        log_index: 0,
+        block_hash: keccak256("unknown"),
+    }
+    .encode();
+    let err = client
+        .get_logs_paged(&filter, Some(&cursor), 5)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::StaleLogCursor(3, 1))
+        ),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_empty_range() {
+    let chain = chain(136);
+    let (client, _, rpc) = verified_client::<Ethereum>(&chain).await;
+    let filter = Filter::new()
+        .from_block(1)
+        .to_block(6)
+        .address(Address::repeat_byte(0x42));
+
+    let page = page(&client, &filter, None, 5).await;
+    assert!(page.logs.is_empty());
+    assert!(page.cursor.is_none());
+    // no block bloom matches, so no receipts are fetched
+    assert_eq!(rpc.calls("get_block_receipts"), 0);
+
+    let err = client.get_logs_paged(&filter, None, 0).await.unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::InvalidLogPageSize(0, _))
+        ),
+        "{err}"
+    );
+}
@@ -0,0 +1,421 @@
+use alloy::eips::BlockNumberOrTag;
+use alloy::primitives::{keccak256, Address};
+use alloy::rpc::types::{Filter, FilterChanges};
+
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::log_filter::{log_matches_filter, LogVerificationConfig};
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::harness;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+type Harness = harness::Harness<Ethereum>;
+
+#[tokio::test]
+async fn test_get_logs_verified() {
+    let chain = ChainBuilder::new(4).start_at(100).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let filter = Filter::new()
+        .address(chain.token())
+        .from_block(100)
+        .to_block(103);
+    let logs = client.get_logs(&filter).await.unwrap();
+
+    assert_eq!(logs, chain.logs());
+    assert!(!logs.is_empty());
+}
+
+#[tokio::test]
+async fn test_get_logs_resolves_tags() {
+    let chain = ChainBuilder::new(167).length(6).txs_per_block(1).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let finalized = chain.blocks()[2].clone();
+    let number = finalized.header.number;
+    harness.state.push_finalized_block(finalized).await;
+
+    // the range ends at the finalized block, not the latest one
+    let filter = Filter::new()
+        .from_block(BlockNumberOrTag::Earliest)
+        .to_block(BlockNumberOrTag::Finalized);
+    let logs = harness.client.get_logs(&filter).await.unwrap();
+    let expected = chain
+        .logs()
+        .into_iter()
+        .filter(|log| log.block_number <= Some(number))
+        .collect::<Vec<_>>();
+    assert_eq!(logs, expected);
+    assert!(!logs.is_empty());
+
+    let filter = Filter::new().from_block(BlockNumberOrTag::Finalized);
+    let logs = harness.client.get_logs(&filter).await.unwrap();
+    let expected = chain
+        .logs()
+        .into_iter()
+        .filter(|log| log.block_number >= Some(number))
+        .collect::<Vec<_>>();
+    assert_eq!(logs, expected);
+}
+
+#[tokio::test]
+async fn test_get_logs_rejects_provider_on_other_branch() {
+    let chain = ChainBuilder::new(5).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    // the provider switched to a branch the verified headers don't commit to
+    harness.rpc.set_chain(chain.reorg(2, 11));
+
+    let filter = Filter::new().from_block(3).to_block(4);
+    assert!(client.get_logs(&filter).await.is_err());
+}
+
+#[tokio::test]
+async fn test_get_logs_across_empty_blocks() {
+    let mut builder = ChainBuilder::new(9).length(6);
+    for number in 2..=5 {
+        builder = builder.empty_block(number);
+    }
+    let chain = builder.build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let logs = client
+        .get_logs(&Filter::new().from_block(2).to_block(5))
+        .await
+        .unwrap();
+    assert!(logs.is_empty());
+
+    let logs = client
+        .get_logs(&Filter::new().from_block(1).to_block(6))
+        .await
+        .unwrap();
+    assert_eq!(logs, chain.logs());
+    assert!(!logs.is_empty());
+}
+
+#[tokio::test]
+async fn test_log_claimed_in_empty_block_rejected() {
+    let chain = ChainBuilder::new(10).length(4).empty_block(3).build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    // the provider serves logs for block 3 from a chain where it has transactions
+    harness
+        .rpc
+        .set_chain(ChainBuilder::new(10).length(4).build());
+
+    let filter = Filter::new().from_block(3).to_block(3);
+    let err = harness.client.get_logs(&filter).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::MissingLog(..))
+    ));
+}
+
+/// Serves `eth_getLogs` for `chain` as a provider that only applies `honoured` of each
+/// filter.
+fn mishandle_filters(harness: &Harness, chain: &MockChain, honoured: fn(&Filter) -> Filter) {
+    let chain = chain.clone();
+    harness.rpc.map_logs(move |filter, _| {
+        let honoured = honoured(filter);
+        chain
+            .logs()
+            .into_iter()
+            .filter(|log| log_matches_filter(log, &honoured))
+            .collect()
+    });
+}
+
+#[tokio::test]
+async fn test_get_logs_drops_logs_outside_filter() {
+    let chain = ChainBuilder::new(4).length(2).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let logs = chain.logs();
+
+    // the provider ignores topics, returning every log of the token
+    mishandle_filters(&harness, &chain, |filter| {
+        Filter::new().address(filter.address.clone())
+    });
+
+    let filter = Filter::new()
+        .address(chain.token())
+        .topic2(logs[1].topics()[2])
+        .from_block(1)
+        .to_block(2);
+    let served = harness.client.get_logs(&filter).await.unwrap();
+
+    let expected = logs
+        .iter()
+        .filter(|log| log_matches_filter(log, &filter))
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(served, expected);
+    assert!(served.contains(&logs[1]));
+}
+
+#[tokio::test]
+async fn test_get_logs_restores_omitted_logs() {
+    let chain = ChainBuilder::new(4).length(2).build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    // the provider leaves out the first matching log
+    harness
+        .rpc
+        .map_logs(|_, logs| logs.into_iter().skip(1).collect());
+
+    let filter = Filter::new()
+        .address(chain.token())
+        .from_block(1)
+        .to_block(2);
+    let served = harness.client.get_logs(&filter).await.unwrap();
+
+    assert_eq!(served, chain.logs());
+    assert!(served.len() > 1);
+}
+
+#[tokio::test]
+async fn test_get_logs_injected_log_rejected() {
+    let chain = ChainBuilder::new(4).length(2).build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    // the provider adds a log of its own making to an existing transaction
+    harness.rpc.map_logs(|_, mut logs| {
+        let mut fake = logs[0].clone();
+        fake.inner.address = Address::repeat_byte(0x42);
+        fake.log_index = Some(1_000);
+        logs.push(fake);
+        logs
+    });
+
+    let filter = Filter::new().from_block(1).to_block(2);
+    let err = harness.client.get_logs(&filter).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::MissingLog(..))
+    ));
+}
+
+#[tokio::test]
+async fn test_get_logs_log_under_wrong_transaction_rejected() {
+    let chain = ChainBuilder::new(4).length(2).build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    // the provider moves a real log onto the transaction of the next one
+    harness.rpc.map_logs(|_, mut logs| {
+        logs[0].transaction_hash = logs[1].transaction_hash;
+        logs
+    });
+
+    let filter = Filter::new().from_block(1).to_block(2);
+    let err = harness.client.get_logs(&filter).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::MissingLog(..))
+    ));
+}
+
+#[tokio::test]
+async fn test_get_logs_log_under_wrong_index_rejected() {
+    let chain = ChainBuilder::new(4).length(2).build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    // the provider swaps the places of two real logs
+    harness.rpc.map_logs(|_, mut logs| {
+        let first = logs[0].log_index;
+        logs[0].log_index = logs[1].log_index;
+        logs[1].log_index = first;
+        logs
+    });
+
+    let filter = Filter::new().from_block(1).to_block(2);
+    let err = harness.client.get_logs(&filter).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::MissingLog(..))
+    ));
+}
+
+#[tokio::test]
+async fn test_get_logs_omitted_log_rejected_when_strict() {
+    let chain = ChainBuilder::new(4).length(2).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = harness
+        .client
+        .clone()
+        .with_log_verification(LogVerificationConfig {
+            strict: true,
+            ..Default::default()
+        });
+
+    harness
+        .rpc
+        .map_logs(|_, logs| logs.into_iter().skip(1).collect());
+
+    let filter = Filter::new()
+        .address(chain.token())
+        .from_block(1)
+        .to_block(2);
+    let err = client.get_logs(&filter).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::OmittedLogs(1))
+    ));
+}
+
+#[tokio::test]
+async fn test_get_logs_block_span_limited() {
+    let chain = ChainBuilder::new(4).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = harness
+        .client
+        .clone()
+        .with_log_verification(LogVerificationConfig {
+            max_blocks: 2,
+            ..Default::default()
+        });
+
+    let err = client
+        .get_logs(&Filter::new().from_block(1).to_block(4))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::TooManyLogBlocks(4, 2))
+    ));
+
+    // a filter within the limit is verified across each of its blocks
+    let filter = Filter::new().from_block(3).to_block(4);
+    let logs = client.get_logs(&filter).await.unwrap();
+    let expected = chain
+        .logs()
+        .into_iter()
+        .filter(|log| log_matches_filter(log, &filter))
+        .collect::<Vec<_>>();
+    assert_eq!(logs, expected);
+    let blocks = logs
+        .iter()
+        .map(|log| log.block_number)
+        .collect::<std::collections::BTreeSet<_>>();
+    assert_eq!(blocks.len(), 2);
+}
+
+#[tokio::test]
+async fn test_get_logs_tag_range_span_limited() {
+    let chain = ChainBuilder::new(177).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = harness
+        .client
+        .clone()
+        .with_log_verification(LogVerificationConfig {
+            max_blocks: 2,
+            ..Default::default()
+        });
+
+    // the tags are resolved to the blocks they name before the span is checked
+    let filter = Filter::new()
+        .from_block(1)
+        .to_block(BlockNumberOrTag::Latest);
+    let err = client.get_logs(&filter).await.unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::TooManyLogBlocks(4, 2))
+        ),
+        "{err}"
+    );
+    assert_eq!(harness.rpc.calls("get_logs"), 0);
+}
+
+#[tokio::test]
+async fn test_get_logs_without_blocks_not_found() {
+    let chain = ChainBuilder::new(178).length(2).build();
+    let harness = Harness::new(&chain);
+
+    let filter = Filter::new()
+        .from_block(BlockNumberOrTag::Latest)
+        .to_block(BlockNumberOrTag::Latest);
+    let err = harness.client.get_logs(&filter).await.unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::BlockNotFound(BlockTag::Latest))
+        ),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_get_logs_complex_filter_matched_exactly() {
+    let chain = ChainBuilder::new(4).length(2).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let logs = chain.logs();
+    let transfer = logs[0].topics()[0];
+    let recipients = [logs[0].topics()[2], logs[2].topics()[2]];
+
+    // alternatives in the first and last positions, with a wildcard between them
+    let filter: Filter = serde_json::from_value(serde_json::json!({
+        "fromBlock": "0x1",
+        "toBlock": "0x2",
+        "topics": [[transfer, keccak256("Approval(address,address,uint256)")], null, recipients],
+    }))
+    .unwrap();
+
+    // the provider gets nested alternatives wrong and only honours the block range
+    mishandle_filters(&harness, &chain, |filter| {
+        Filter::new().select(filter.block_option)
+    });
+
+    let served = harness.client.get_logs(&filter).await.unwrap();
+    let expected = logs
+        .iter()
+        .filter(|log| recipients.contains(&log.topics()[2]))
+        .cloned()
+        .collect::<Vec<_>>();
+    assert_eq!(served, expected);
+    assert!(expected.len() >= 2);
+}
+
+#[tokio::test]
+async fn test_filter_polling_across_empty_blocks() {
+    let mut chain = ChainBuilder::new(11)
+        .length(2)
+        .empty_block(3)
+        .empty_block(4)
+        .empty_block(5)
+        .build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let filter = Filter::new().from_block(3).to_block(100);
+    let logs_filter = client.new_filter(&filter).await.unwrap();
+    let block_filter = client.new_block_filter().await.unwrap();
+
+    chain.extend(3);
+    harness.rpc.set_chain(chain.clone());
+    for block in &chain.blocks()[2..] {
+        harness.state.push_block(block.clone()).await;
+    }
+
+    let changes = client.get_filter_changes(logs_filter).await.unwrap();
+    assert_eq!(changes, FilterChanges::Logs(vec![]));
+    let changes = client.get_filter_changes(block_filter).await.unwrap();
+    let hashes = chain.blocks()[2..].iter().map(|b| b.header.hash).collect();
+    assert_eq!(changes, FilterChanges::Hashes(hashes));
+
+    // the first block with transactions after the empty stretch is picked up
+    chain.extend(1);
+    harness.rpc.set_chain(chain.clone());
+    harness.state.push_block(chain.head().clone()).await;
+
+    let FilterChanges::Logs(logs) = client.get_filter_changes(logs_filter).await.unwrap() else {
+        panic!("expected logs");
+    };
+    let expected = chain
+        .logs()
+        .into_iter()
+        .filter(|log| log.block_number == Some(6))
+        .collect::<Vec<_>>();
+    assert_eq!(logs, expected);
+    assert!(!logs.is_empty());
+}
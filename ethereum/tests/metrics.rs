@@ -0,0 +1,185 @@
+//! Scrapes the prometheus endpoint after recording into it.
+#![cfg(feature = "metrics")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use alloy::primitives::{Address, U256};
+use alloy::providers::ProviderBuilder;
+use alloy::rpc::client::ClientBuilder;
+use alloy::rpc::json_rpc::{RequestPacket, Response, ResponsePacket, ResponsePayload};
+use alloy::transports::{TransportError, TransportErrorKind, TransportFut};
+use serde_json::value::RawValue;
+use tower::Service;
+
+use helios_core::client::supervisor::{Supervisor, SupervisorConfig};
+use helios_core::config::ConfigDuration;
+use helios_core::execution::rpc::retry::{RetryConfig, RetryLayer};
+use helios_core::execution::rpc::{http_rpc::HttpRpc, ExecutionRpc};
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::metrics::{serve, stop, Metrics, MetricsLayer};
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+/// A transport rate limiting its first request, then answering `eth_chainId` and failing
+/// everything else.
+#[derive(Clone, Default)]
+struct ProviderTransport {
+    calls: Arc<AtomicUsize>,
+}
+
+impl Service<RequestPacket> for ProviderTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let first = self.calls.fetch_add(1, Ordering::SeqCst) == 0;
+        let RequestPacket::Single(req) = req else {
+            panic!("unexpected batch request");
+        };
+
+        Box::pin(async move {
+            if first {
+                return Err(TransportErrorKind::http_error(
+                    429,
+                    "too many requests".to_string(),
+                ));
+            }
+            let payload = match req.method() {
+                "eth_chainId" => {
+                    ResponsePayload::Success(RawValue::from_string("\"0x1\"".to_string()).unwrap())
+                }
+                _ => {
+                    let error = r#"{"code":-32000,"message":"unavailable"}"#;
+                    ResponsePayload::Failure(serde_json::from_str(error).unwrap())
+                }
+            };
+            Ok(ResponsePacket::Single(Response {
+                id: req.id().clone(),
+                payload,
+            }))
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_metrics_scraped() {
+    let metrics = Arc::new(Metrics::new());
+    let retry = RetryConfig {
+        max_retries: 1,
+        initial_backoff: ConfigDuration::from_millis(1),
+        max_backoff: ConfigDuration::from_millis(1),
+        compute_units_per_second: 0,
+    };
+    let client = ClientBuilder::default()
+        .layer(MetricsLayer::new(metrics.clone()))
+        .layer(RetryLayer::new(retry).with_metrics(metrics.clone()))
+        .transport(ProviderTransport::default(), true);
+    let provider = ProviderBuilder::new()
+        .network::<Ethereum>()
+        .on_client(client);
+    let rpc = HttpRpc::<Ethereum>::from_provider("mock://metrics", provider)
+        .with_metrics(metrics.clone());
+
+    for _ in 0..3 {
+        ExecutionRpc::<Ethereum>::chain_id(&rpc).await.unwrap();
+    }
+    ExecutionRpc::<Ethereum>::get_code(&rpc, Address::ZERO, 1)
+        .await
+        .unwrap_err();
+
+    let chain = ChainBuilder::new(30).length(4).txs_per_block(1).build();
+    let chain_rpc = ChainRpc::from_chain(chain.clone()).with_metrics(metrics.clone());
+    let state = State::<Ethereum, _>::unsynced(64, chain_rpc.clone());
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+    let finalized = chain.blocks()[1].clone();
+    state.push_finalized_block(finalized.clone()).await;
+
+    let execution = ExecutionClient::with_rpc(chain_rpc.clone(), state, chain.fork_schedule());
+    let token = chain.token();
+    let (slot, _) = chain.storage(token)[0];
+    chain_rpc.map_proofs(|mut proof| {
+        for storage_proof in &mut proof.storage_proof {
+            storage_proof.value += U256::from(1);
+        }
+        proof
+    });
+    execution
+        .get_proof(token, &[slot], BlockTag::Latest)
+        .await
+        .unwrap_err();
+    chain_rpc.map_receipts(|json| json["status"] = "0x0".into());
+    let latest = chain.head().header.number;
+    let tx = chain.receipts(latest).unwrap()[0].transaction_hash;
+    execution.get_transaction_receipt(tx).await.unwrap_err();
+
+    let supervisor = Supervisor::new(SupervisorConfig::default());
+    let addr = serve("127.0.0.1:0".parse().unwrap(), &supervisor, metrics).unwrap();
+    let url = format!("http://{addr}/metrics");
+    let body = reqwest::get(&url).await.unwrap().text().await.unwrap();
+
+    for line in [
+        r#"helios_rpc_calls_total{method="eth_chainId"} 3"#.to_string(),
+        r#"helios_rpc_calls_total{method="eth_getCode"} 1"#.to_string(),
+        r#"helios_rpc_errors_total{method="eth_getCode"} 1"#.to_string(),
+        r#"helios_rpc_retries_total{method="eth_chainId"} 1"#.to_string(),
+        r#"helios_rpc_request_duration_seconds_count{method="eth_chainId"} 3"#.to_string(),
+        r#"helios_proof_verification_failures_total{kind="storage"} 1"#.to_string(),
+        r#"helios_proof_verification_failures_total{kind="receipts"} 1"#.to_string(),
+        format!("helios_latest_block_number {latest}"),
+        format!("helios_finalized_block_number {}", finalized.header.number),
+    ] {
+        assert!(
+            body.lines().any(|l| l == line),
+            "{line} missing from:\n{body}"
+        );
+    }
+    assert!(!body.contains(r#"helios_rpc_errors_total{method="eth_chainId"}"#));
+    assert!(body.contains("helios_seconds_since_consensus_update "));
+
+    // the listener closes once the server is stopped
+    stop(&supervisor);
+    let mut closed = false;
+    for _ in 0..50 {
+        if reqwest::get(&url).await.is_err() {
+            closed = true;
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    assert!(closed, "metrics still served after stop");
+}
+
+#[tokio::test]
+async fn test_clients_record_apart() {
+    let chain = ChainBuilder::new(179).length(3).build();
+    let (first, second) = (Arc::new(Metrics::new()), Arc::new(Metrics::new()));
+
+    // two clients following the chain to different heads in one process
+    for (metrics, block) in [(&first, &chain.blocks()[0]), (&second, chain.head())] {
+        let rpc = ChainRpc::from_chain(chain.clone()).with_metrics(metrics.clone());
+        let state = State::<Ethereum, _>::unsynced(64, rpc);
+        state.push_block(block.clone()).await;
+    }
+
+    for (metrics, block) in [(first, &chain.blocks()[0]), (second, chain.head())] {
+        let line = format!("helios_latest_block_number {}", block.header.number);
+        let body = metrics.encode();
+        assert!(
+            body.lines().any(|l| l == line),
+            "{line} missing from:\n{body}"
+        );
+    }
+}
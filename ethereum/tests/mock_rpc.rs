@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use alloy::eips::BlockId;
+use alloy::primitives::{b256, Bytes};
+use alloy::rpc::types::Filter;
+use serde_json::json;
+
+use helios_core::execution::rpc::mock_rpc::{MockRpc, Outcome};
+use helios_core::execution::rpc::ExecutionRpc;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::consensus::verified_client_with;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+async fn client(chain: &MockChain, rpc: MockRpc) -> ExecutionClient<Ethereum, MockRpc> {
+    verified_client_with::<Ethereum, _>(chain, rpc).await.0
+}
+
+#[tokio::test]
+async fn test_scripted_proof_failure_then_success() {
+    let chain = ChainBuilder::new(68).length(3).build();
+    let address = chain.accounts()[0];
+    let head = chain.head().header.number;
+    let proof = ExecutionRpc::<Ethereum>::get_proof(
+        &ChainRpc::from_chain(chain.clone()),
+        address,
+        &[],
+        BlockId::number(head),
+    )
+    .await
+    .unwrap();
+
+    let rpc = MockRpc::builder()
+        .script(
+            "get_proof",
+            [
+                Outcome::Err("header not found".to_string()),
+                Outcome::Ok(serde_json::to_value(&proof).unwrap()),
+            ],
+        )
+        .build();
+    let client = client(&chain, rpc.clone()).await;
+
+    let err = client
+        .get_account(address, None, BlockTag::Latest)
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("header not found"), "{err}");
+
+    let account = client
+        .get_account(address, None, BlockTag::Latest)
+        .await
+        .unwrap();
+    assert_eq!(account.balance, proof.balance);
+
+    let calls = rpc.calls_to("get_proof");
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].args, json!([address, [], BlockId::number(head)]));
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_delays_and_call_log() {
+    let chain = ChainBuilder::new(69).length(2).build();
+    let hash = b256!("0101010101010101010101010101010101010101010101010101010101010101");
+    let rpc = MockRpc::builder()
+        .script(
+            "send_raw_transaction",
+            [
+                Outcome::Delay(Duration::from_secs(3)),
+                Outcome::Err("nonce too low".to_string()),
+                Outcome::Ok(json!(hash)),
+            ],
+        )
+        .script(
+            "get_logs",
+            [
+                Outcome::Delay(Duration::from_secs(1)),
+                Outcome::Ok(json!([])),
+            ],
+        )
+        .build();
+    let client = client(&chain, rpc.clone()).await;
+
+    let err = client.send_raw_transaction(&[0xab]).await.unwrap_err();
+    assert!(err.to_string().contains("nonce too low"), "{err}");
+    assert_eq!(client.send_raw_transaction(&[0xcd]).await.unwrap(), hash);
+
+    let head = chain.head().header.number;
+    let filter = Filter::new().from_block(head).to_block(head);
+    let logs = ExecutionRpc::<Ethereum>::get_logs(&rpc, &filter)
+        .await
+        .unwrap();
+    assert!(logs.is_empty());
+
+    // calls are recorded when made, before their delay
+    let calls = rpc.calls();
+    let methods = calls.iter().map(|call| call.method).collect::<Vec<_>>();
+    assert_eq!(
+        methods,
+        ["send_raw_transaction", "send_raw_transaction", "get_logs"]
+    );
+    assert_eq!(calls[0].args, json!([Bytes::from_static(&[0xab])]));
+    assert_eq!(calls[1].at - calls[0].at, Duration::from_secs(3));
+    assert_eq!(calls[2].args, json!([filter]));
+}
+
+#[tokio::test]
+async fn test_unscripted_calls_read_fixtures() {
+    let rpc = MockRpc::builder()
+        .fixtures(concat!(env!("CARGO_MANIFEST_DIR"), "/../core/testdata"))
+        .script("get_transaction_receipt", [Outcome::Ok(json!(null))])
+        .build();
+    let hash = b256!("0202020202020202020202020202020202020202020202020202020202020202");
+
+    let receipt = ExecutionRpc::<Ethereum>::get_transaction_receipt(&rpc, hash)
+        .await
+        .unwrap();
+    assert!(receipt.is_none());
+
+    // the script ran out, so the fixture answers
+    let receipt = ExecutionRpc::<Ethereum>::get_transaction_receipt(&rpc, hash)
+        .await
+        .unwrap();
+    assert!(receipt.is_some());
+    assert_eq!(rpc.calls_to("get_transaction_receipt").len(), 2);
+}
+
+#[tokio::test]
+async fn test_receipts_requested_by_hash() {
+    let chain = ChainBuilder::new(166).length(3).txs_per_block(2).build();
+    let head = chain.head();
+    let receipts = chain.receipts(head.header.number).unwrap();
+    let rpc = MockRpc::builder()
+        .script("get_block_receipts", [Outcome::Ok(json!(receipts))])
+        .build();
+    let client = client(&chain, rpc.clone()).await;
+
+    // a block named by number is still fetched by the hash it resolved to
+    let raw = client
+        .get_raw_receipts(BlockId::number(head.header.number))
+        .await
+        .unwrap();
+    assert_eq!(raw.len(), receipts.len());
+
+    let calls = rpc.calls_to("get_block_receipts");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].args, json!([BlockId::hash(head.header.hash)]));
+}
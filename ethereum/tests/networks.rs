@@ -0,0 +1,157 @@
+use std::str::FromStr;
+
+use alloy::primitives::{b256, fixed_bytes, FixedBytes, B256};
+use strum::IntoEnumIterator;
+
+use helios_consensus_core::compute_fork_digest;
+use helios_ethereum::config::networks::Network;
+
+const SECONDS_PER_SLOT: u64 = 12;
+const SLOTS_PER_EPOCH: u64 = 32;
+
+struct Reference {
+    network: Network,
+    chain_id: u64,
+    genesis_time: u64,
+    genesis_root: B256,
+    /// Fork digests from genesis through electra.
+    fork_digests: [FixedBytes<4>; 6],
+}
+
+fn references() -> Vec<Reference> {
+    vec![
+        Reference {
+            network: Network::Mainnet,
+            chain_id: 1,
+            genesis_time: 1606824023,
+            genesis_root: b256!("4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"),
+            fork_digests: [
+                fixed_bytes!("b5303f2a"),
+                fixed_bytes!("afcaaba0"),
+                fixed_bytes!("4a26c58b"),
+                fixed_bytes!("bba4da96"),
+                fixed_bytes!("6a95a1a9"),
+                fixed_bytes!("ad532ceb"),
+            ],
+        },
+        Reference {
+            network: Network::Sepolia,
+            chain_id: 11155111,
+            genesis_time: 1655733600,
+            genesis_root: b256!("d8ea171f3c94aea21ebc42a1ed61052acf3f9209c00e4efbaaddac09ed9b8078"),
+            fork_digests: [
+                fixed_bytes!("a8fee8ee"),
+                fixed_bytes!("60725ca1"),
+                fixed_bytes!("36fa5013"),
+                fixed_bytes!("47eb72b3"),
+                fixed_bytes!("d31f6191"),
+                fixed_bytes!("14045b5a"),
+            ],
+        },
+        Reference {
+            network: Network::Holesky,
+            chain_id: 17000,
+            genesis_time: 1695902400,
+            genesis_root: b256!("9143aa7c615a7f7115e2b6aac319c03529df8242ae705fba9df39b79c59fa8b1"),
+            fork_digests: [
+                fixed_bytes!("219d34fe"),
+                fixed_bytes!("c4f705e4"),
+                fixed_bytes!("69b7d974"),
+                fixed_bytes!("17e2dad3"),
+                fixed_bytes!("69ae0e99"),
+                fixed_bytes!("019e21ad"),
+            ],
+        },
+        Reference {
+            network: Network::Hoodi,
+            chain_id: 560048,
+            genesis_time: 1742213400,
+            genesis_root: b256!("212f13fc4df078b6cb7db228f1c8307566dcecf900867401a92023d7ba99cb5f"),
+            fork_digests: [
+                fixed_bytes!("5df5c106"),
+                fixed_bytes!("5bc37505"),
+                fixed_bytes!("92579de7"),
+                fixed_bytes!("72bbc370"),
+                fixed_bytes!("d2f1997f"),
+                fixed_bytes!("82556a32"),
+            ],
+        },
+    ]
+}
+
+#[test]
+fn test_presets_match_reference_vectors() {
+    for reference in references() {
+        let network = reference.network;
+        let config = network.to_base_config();
+        let forks = &config.forks;
+
+        assert_eq!(config.chain.chain_id, reference.chain_id, "{network}");
+        assert_eq!(
+            config.chain.genesis_time, reference.genesis_time,
+            "{network}"
+        );
+        assert_eq!(
+            config.chain.genesis_root, reference.genesis_root,
+            "{network}"
+        );
+
+        let versions = [
+            forks.genesis.fork_version,
+            forks.altair.fork_version,
+            forks.bellatrix.fork_version,
+            forks.capella.fork_version,
+            forks.deneb.fork_version,
+            forks.electra.fork_version,
+        ];
+        let digests = versions.map(|version| compute_fork_digest(version, reference.genesis_root));
+        assert_eq!(digests, reference.fork_digests, "{network}");
+    }
+}
+
+#[test]
+fn test_testnet_prague_activates_with_electra() {
+    for network in [Network::Sepolia, Network::Holesky, Network::Hoodi] {
+        let config = network.to_base_config();
+        let electra_start = config.chain.genesis_time
+            + config.forks.electra.epoch * SLOTS_PER_EPOCH * SECONDS_PER_SLOT;
+
+        assert_eq!(
+            config.execution_forks.prague_timestamp, electra_start,
+            "{network}"
+        );
+    }
+}
+
+#[test]
+fn test_fork_epochs_are_ordered() {
+    for network in Network::iter() {
+        let forks = network.to_base_config().forks;
+        let epochs = [
+            forks.genesis.epoch,
+            forks.altair.epoch,
+            forks.bellatrix.epoch,
+            forks.capella.epoch,
+            forks.deneb.epoch,
+            forks.electra.epoch,
+        ];
+
+        let ordered = epochs.windows(2).all(|pair| pair[0] <= pair[1]);
+        assert!(ordered, "{network}: {epochs:?}");
+    }
+}
+
+#[test]
+fn test_network_names_round_trip() {
+    for network in Network::iter() {
+        assert_eq!(Network::from_str(&network.to_string()).unwrap(), network);
+    }
+
+    for reference in references() {
+        let network = Network::from_chain_id(reference.chain_id).unwrap();
+        assert_eq!(network, reference.network);
+    }
+
+    let err = Network::from_str("goerli").unwrap_err().to_string();
+    assert!(err.contains(&Network::names().join(", ")), "{err}");
+}
@@ -0,0 +1,307 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::{B256, U64};
+use futures::StreamExt;
+use serde_json::json;
+
+use helios_core::client::node::Node;
+use helios_core::client::supervisor::SupervisorConfig;
+use helios_core::client::Client;
+use helios_core::consensus::Consensus;
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::rpc::http_rpc::HttpRpc;
+use helios_core::execution::rpc::ExecutionRpc;
+use helios_core::execution::stream::{HeaderEvent, LagPolicy};
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::consensus::{execution_config, MockConsensus};
+use helios_core::types::{BlockTag, SyncState, SyncingInfo};
+use helios_ethereum::spec::Ethereum;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_latest_block_matches_verified_head_while_advancing() {
+    // blocks from now on, so the head never looks out of sync
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let chain = Arc::new(
+        ChainBuilder::new(17)
+            .length(200)
+            .genesis_timestamp(now.as_secs())
+            .build(),
+    );
+
+    // every block links to the one before, so the provider is never asked for a gap
+    let (consensus, feed) = MockConsensus::new(chain.chain_id());
+    let rpc = <HttpRpc<Ethereum> as ExecutionRpc<Ethereum>>::new("http://127.0.0.1:1").unwrap();
+    let node = Arc::new(Node::<Ethereum, _>::with_execution_rpc(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+    ));
+
+    feed.advance(chain.blocks()[0].clone()).await.unwrap();
+    node.execution
+        .head_updates()
+        .wait_for(Option::is_some)
+        .await
+        .unwrap();
+
+    let done = Arc::new(AtomicBool::new(false));
+    let readers = (0..8)
+        .map(|_| {
+            let (node, chain, done) = (node.clone(), chain.clone(), done.clone());
+            tokio::spawn(async move {
+                let mut served = 0;
+                while !done.load(Ordering::Relaxed) {
+                    let before = node.consensus.head().unwrap().header.number;
+                    let block = node
+                        .get_block_by_number(BlockTag::Latest, false)
+                        .await
+                        .unwrap()
+                        .unwrap();
+                    let after = node.consensus.head().unwrap().header.number;
+
+                    let number = block.header.number;
+                    assert!(
+                        (before..=after).contains(&number),
+                        "served block {number} while the verified head went {before} to {after}"
+                    );
+                    assert_eq!(block.header.hash, chain.block(number).unwrap().header.hash);
+                    served += 1;
+                }
+                served
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for block in &chain.blocks()[1..] {
+        feed.advance(block.clone()).await.unwrap();
+        tokio::task::yield_now().await;
+    }
+    done.store(true, Ordering::Relaxed);
+
+    for reader in readers {
+        assert!(reader.await.unwrap() > 0);
+    }
+    let health = node.health();
+    assert_eq!(health.head_block_hash, Some(chain.head().header.hash));
+}
+
+#[tokio::test]
+async fn test_syncing_reports_progress_while_behind() {
+    // generated blocks are far in the past, so the head never counts as recent
+    let chain = ChainBuilder::new(72).length(4).build();
+    let (consensus, feed) = MockConsensus::new(chain.chain_id());
+    let rpc = <HttpRpc<Ethereum> as ExecutionRpc<Ethereum>>::new("http://127.0.0.1:1").unwrap();
+    let node = Node::<Ethereum, _>::with_execution_rpc(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+    );
+
+    let status = node.syncing().await.unwrap();
+    assert_eq!(
+        serde_json::to_value(&status).unwrap(),
+        json!({
+            "startingBlock": "0x0",
+            "currentBlock": "0x0",
+            "highestBlock": "0x0",
+            "latestFinalizedBlock": null,
+            "secondsSinceLastUpdate": null,
+        })
+    );
+
+    for block in chain.blocks() {
+        feed.advance(block.clone()).await.unwrap();
+    }
+    feed.finalize(chain.blocks()[1].clone());
+    let head = chain.head().header.number;
+    node.execution
+        .sync_progress()
+        .subscribe()
+        .wait_for(|progress| {
+            progress.current_block == Some(head) && progress.finalized_block.is_some()
+        })
+        .await
+        .unwrap();
+
+    let status = node.syncing().await.unwrap();
+    let SyncState::Syncing(info) = &status else {
+        panic!("synced to a stale head");
+    };
+    assert!(info.seconds_since_last_update.is_some());
+    assert_eq!(
+        **info,
+        SyncingInfo {
+            starting_block: U64::from(chain.blocks()[0].header.number),
+            current_block: U64::from(head),
+            highest_block: U64::from(head),
+            latest_finalized_block: Some(U64::from(chain.blocks()[1].header.number)),
+            seconds_since_last_update: info.seconds_since_last_update,
+            backfill: None,
+            head_feed: None,
+        }
+    );
+    let parsed: SyncState = serde_json::from_value(serde_json::to_value(&status).unwrap()).unwrap();
+    assert_eq!(parsed, status);
+}
+
+#[tokio::test]
+async fn test_synced_once_recent_head_applied() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let chain = ChainBuilder::new(73)
+        .length(3)
+        .genesis_timestamp(now.as_secs())
+        .build();
+    let (consensus, feed) = MockConsensus::new(chain.chain_id());
+    let rpc = <HttpRpc<Ethereum> as ExecutionRpc<Ethereum>>::new("http://127.0.0.1:1").unwrap();
+    let client = Client::with_execution_rpc(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+        None,
+    );
+
+    let status = client.syncing().await.unwrap();
+    assert!(matches!(status, SyncState::Syncing(_)), "{status:?}");
+
+    let feed_blocks = async {
+        for block in chain.blocks() {
+            feed.advance(block.clone()).await.unwrap();
+        }
+    };
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        futures::future::join(client.wait_synced(), feed_blocks),
+    )
+    .await
+    .expect("never synced");
+
+    let status = client.syncing().await.unwrap();
+    assert_eq!(status, SyncState::Synced);
+    assert_eq!(serde_json::to_value(&status).unwrap(), json!(false));
+}
+
+#[tokio::test]
+async fn test_stuck_sync_detected_and_resumed() {
+    let chain = ChainBuilder::new(139).length(2).build();
+    let (consensus, feed) = MockConsensus::new(chain.chain_id());
+    let rpc = <HttpRpc<Ethereum> as ExecutionRpc<Ethereum>>::new("http://127.0.0.1:1").unwrap();
+    let supervisor = SupervisorConfig {
+        heartbeat_timeout: Duration::from_millis(200),
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(40),
+        max_consecutive_failures: 3,
+    };
+    let node = Node::<Ethereum, _>::with_supervisor_config(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+        supervisor,
+    );
+    let sync = |node: &Node<Ethereum, _>| {
+        let report = node.health();
+        report
+            .tasks
+            .into_iter()
+            .find(|task| task.name == "sync")
+            .unwrap()
+    };
+
+    feed.advance(chain.blocks()[0].clone()).await.unwrap();
+    let mut heads = node.execution.head_updates();
+    heads.wait_for(Option::is_some).await.unwrap();
+
+    // the consensus client goes quiet, which waiting on it must not hide
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let stuck = sync(&node);
+    assert!(stuck.restarts > 0);
+    assert!(stuck
+        .last_failure
+        .unwrap()
+        .reason
+        .starts_with("no heartbeat"));
+
+    // a restarted sync picks up the channel where it was left
+    let head = chain.head().header.number;
+    feed.advance(chain.head().clone()).await.unwrap();
+    tokio::time::timeout(
+        Duration::from_secs(1),
+        heads.wait_for(|update| *update == Some(head)),
+    )
+    .await
+    .expect("restarted sync never applied the block")
+    .unwrap();
+    assert!(sync(&node).running);
+}
+
+#[tokio::test]
+async fn test_streams_end_when_consensus_stops() {
+    let chain = ChainBuilder::new(148).length(2).build();
+    let (consensus, feed) = MockConsensus::new(chain.chain_id());
+    let rpc = <HttpRpc<Ethereum> as ExecutionRpc<Ethereum>>::new("http://127.0.0.1:1").unwrap();
+    let node = Node::<Ethereum, _>::with_execution_rpc(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+    );
+    let mut heads = std::pin::pin!(node.execution.block_stream(LagPolicy::Skip));
+    let mut finalized = std::pin::pin!(node.execution.finality_stream(LagPolicy::Skip));
+
+    for block in chain.blocks() {
+        feed.advance(block.clone()).await.unwrap();
+    }
+    for block in chain.blocks() {
+        let Some(HeaderEvent::Header(header)) = heads.next().await else {
+            panic!("stream ended before {}", block.header.number);
+        };
+        assert_eq!(header.hash, block.header.hash);
+    }
+
+    // the sync task of the node, not a state driven by hand, ends the streams
+    drop(feed);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        assert!(heads.next().await.is_none());
+        assert!(finalized.next().await.is_none());
+    })
+    .await
+    .expect("streams outlived the consensus client");
+}
+
+#[tokio::test]
+async fn test_finality_estimate_of_unknown_block_not_found() {
+    let chain = ChainBuilder::new(176).length(2).build();
+    let (consensus, feed) = MockConsensus::new(chain.chain_id());
+    let rpc = <HttpRpc<Ethereum> as ExecutionRpc<Ethereum>>::new("http://127.0.0.1:1").unwrap();
+    let node = Node::<Ethereum, _>::with_execution_rpc(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+    );
+    for block in chain.blocks() {
+        feed.advance(block.clone()).await.unwrap();
+    }
+    let head = chain.head().header.number;
+    node.execution
+        .sync_progress()
+        .subscribe()
+        .wait_for(|progress| progress.current_block == Some(head))
+        .await
+        .unwrap();
+
+    let hash = B256::repeat_byte(0x42);
+    let err = node.estimate_finality_time(hash).await.unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::BlockHashNotFound(h)) if *h == hash
+        ),
+        "{err}"
+    );
+}
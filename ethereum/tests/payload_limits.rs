@@ -0,0 +1,127 @@
+use alloy::network::TransactionResponse;
+use alloy::primitives::Bytes;
+use alloy::rpc::types::Filter;
+
+use helios_core::config::ByteSize;
+use helios_core::execution::backfill::BackfillConfig;
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::limits::PayloadLimits;
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::consensus::verified_client;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+type Client = ExecutionClient<Ethereum, ChainRpc>;
+
+/// A client holding every block of `chain`, checking provider payloads against `limits`.
+async fn client(chain: &MockChain, limits: PayloadLimits) -> (Client, ChainRpc) {
+    let (client, _, rpc) = verified_client::<Ethereum>(chain).await;
+    (client.with_payload_limits(limits), rpc)
+}
+
+fn assert_too_large(err: eyre::Report, kind: &str) {
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::ProviderPayloadTooLarge(k, ..)) if *k == kind
+        ),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_oversized_proof_rejected() {
+    let chain = ChainBuilder::new(150).length(2).build();
+    let limits = PayloadLimits {
+        max_proof_nodes: 16,
+        ..Default::default()
+    };
+    let (client, rpc) = client(&chain, limits).await;
+    rpc.map_proofs(|mut proof| {
+        proof.account_proof.extend(vec![Bytes::new(); 32]);
+        proof
+    });
+
+    let err = client
+        .get_account(chain.accounts()[0], None, BlockTag::Latest)
+        .await
+        .unwrap_err();
+    assert_too_large(err, "account proof");
+}
+
+#[tokio::test]
+async fn test_too_many_receipts_rejected() {
+    let chain = ChainBuilder::new(151).length(2).txs_per_block(3).build();
+    let limits = PayloadLimits {
+        max_receipts_per_block: 2,
+        ..Default::default()
+    };
+    let (client, _) = client(&chain, limits).await;
+
+    let err = client
+        .get_block_receipts(BlockTag::Latest)
+        .await
+        .unwrap_err();
+    assert_too_large(err, "receipts per block");
+}
+
+#[tokio::test]
+async fn test_too_many_receipt_logs_rejected() {
+    let chain = ChainBuilder::new(152).length(2).txs_per_block(1).build();
+    let limits = PayloadLimits {
+        max_logs_per_receipt: 0,
+        ..Default::default()
+    };
+    let (client, _) = client(&chain, limits).await;
+    let tx = chain.head().transactions.txns().next().unwrap().tx_hash();
+
+    let err = client.get_transaction_receipt(tx).await.unwrap_err();
+    assert_too_large(err, "logs per receipt");
+}
+
+#[tokio::test]
+async fn test_oversized_log_data_rejected() {
+    let chain = ChainBuilder::new(153).length(3).txs_per_block(1).build();
+    // a transfer log carries a 32 byte amount
+    let limits = PayloadLimits {
+        max_log_data_size: ByteSize::from_bytes(16),
+        ..Default::default()
+    };
+    let (client, _) = client(&chain, limits).await;
+
+    let filter = Filter::new().from_block(1).to_block(3);
+    let err = client.get_logs(&filter).await.unwrap_err();
+    assert_too_large(err, "log data size");
+}
+
+#[tokio::test]
+async fn test_oversized_backfilled_block_rejected() {
+    let chain = ChainBuilder::new(154).length(10).build();
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let state = State::<Ethereum, _>::unsynced(64, rpc.clone())
+        .with_backfill(BackfillConfig {
+            concurrency: 4,
+            max_retries: 0,
+            ..Default::default()
+        })
+        .with_payload_limits(PayloadLimits {
+            max_block_body_size: ByteSize::from_bytes(64),
+            ..Default::default()
+        });
+
+    state.push_block(chain.head().clone()).await;
+    state
+        .sync_progress()
+        .subscribe()
+        .wait_for(|progress| !progress.is_backfilling())
+        .await
+        .unwrap();
+
+    // the head came from consensus, the blocks behind it from the provider
+    let head = chain.head().header.number;
+    assert_eq!(state.oldest_block_number().await, Some(head));
+    assert!(rpc.calls("get_block_by_number") > 0);
+}
@@ -0,0 +1,179 @@
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::pins::RetentionReason;
+use helios_core::execution::state::State;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+fn small_state(chain: &MockChain, history_length: usize) -> State<Ethereum, ChainRpc> {
+    State::unsynced(history_length, ChainRpc::from_chain(chain.clone()))
+}
+
+#[tokio::test]
+async fn test_state_cache_evicts_least_recently_used() {
+    let chain = ChainBuilder::new(1).start_at(10).length(5).build();
+    let state = small_state(&chain, 4);
+    for block in &chain.blocks()[..4] {
+        state.push_block(block.clone()).await;
+    }
+
+    // reading the oldest block makes the next one the least recently used
+    assert!(state.get_block(BlockTag::Number(10)).await.is_some());
+    state.push_block(chain.head().clone()).await;
+
+    assert_eq!(
+        state.retention(10).await,
+        Some(vec![RetentionReason::RecentlyUsed])
+    );
+    assert_eq!(state.retention(11).await, None);
+    assert_eq!(state.oldest_block_number().await, Some(12));
+    assert_eq!(state.latest_block_number().await, Some(14));
+}
+
+#[tokio::test]
+async fn test_pinned_blocks_survive_eviction() {
+    let chain = ChainBuilder::new(1).start_at(10).length(13).build();
+    let state = small_state(&chain, 4);
+    let (head, blocks) = chain.blocks().split_last().unwrap();
+    for block in &blocks[..4] {
+        state.push_block(block.clone()).await;
+    }
+
+    let pin = state
+        .pin_block(BlockTag::Number(10), RetentionReason::Pin)
+        .await
+        .unwrap();
+    let _cursor = state
+        .pin_block(BlockTag::Number(11), RetentionReason::Cursor)
+        .await
+        .unwrap();
+    let _anchor = state
+        .pin_block(BlockTag::Number(11), RetentionReason::Anchor)
+        .await
+        .unwrap();
+    assert_eq!((pin.number(), pin.hash()), (10, blocks[0].header.hash));
+
+    for block in &blocks[4..] {
+        state.push_block(block.clone()).await;
+    }
+
+    assert_eq!(state.retention(10).await, Some(vec![RetentionReason::Pin]));
+    assert_eq!(
+        state.retention(11).await,
+        Some(vec![RetentionReason::Anchor, RetentionReason::Cursor])
+    );
+    for number in 12..18 {
+        assert_eq!(state.retention(number).await, None, "block {number}");
+    }
+    let pinned = state.get_block_by_hash(blocks[0].header.hash).await;
+    assert_eq!(pinned.unwrap().header.number, 10);
+
+    // pinned blocks are held on top of the recent ones, which keep their four slots
+    for number in 18..22 {
+        assert_eq!(
+            state.retention(number).await,
+            Some(vec![RetentionReason::RecentlyUsed]),
+            "block {number}"
+        );
+    }
+    // the retained window only covers the unbroken run up to the head
+    assert_eq!(state.oldest_block_number().await, Some(18));
+    assert_eq!(state.latest_block_number().await, Some(21));
+
+    drop(pin);
+    state.push_block(head.clone()).await;
+    assert_eq!(state.retention(10).await, None);
+    assert!(state.retention(11).await.is_some());
+    assert_eq!(state.oldest_block_number().await, Some(19));
+}
+
+#[tokio::test]
+async fn test_pin_budget_exceeded() {
+    let chain = ChainBuilder::new(1).start_at(10).length(9).build();
+    let state = small_state(&chain, 4).with_pin_budget(3);
+    for block in &chain.blocks()[..4] {
+        state.push_block(block.clone()).await;
+    }
+
+    let mut pins = Vec::new();
+    for number in 10..13 {
+        let tag = BlockTag::Number(number);
+        pins.push(state.pin_block(tag, RetentionReason::Pin).await.unwrap());
+    }
+    let err = state
+        .pin_block(BlockTag::Latest, RetentionReason::Pin)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ExecutionError::PinBudgetExceeded(3)));
+
+    // further pins of a pinned block take no extra slot
+    let anchor = state
+        .pin_block(BlockTag::Number(12), RetentionReason::Anchor)
+        .await
+        .unwrap();
+
+    for block in &chain.blocks()[4..] {
+        state.push_block(block.clone()).await;
+    }
+    for number in 10..13 {
+        assert!(state.retention(number).await.is_some(), "block {number}");
+    }
+    for number in 13..15 {
+        assert_eq!(state.retention(number).await, None, "block {number}");
+    }
+    for number in 15..19 {
+        assert!(state.retention(number).await.is_some(), "block {number}");
+    }
+    assert_eq!(state.latest_block_number().await, Some(18));
+
+    let err = state
+        .pin_block(BlockTag::Number(13), RetentionReason::Pin)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ExecutionError::BlockNotFound(_)));
+
+    // releasing a block frees its slot once all of its pins are dropped
+    pins.pop();
+    let err = state
+        .pin_block(BlockTag::Latest, RetentionReason::Pin)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ExecutionError::PinBudgetExceeded(3)));
+    drop(anchor);
+    let pin = state
+        .pin_block(BlockTag::Latest, RetentionReason::Pin)
+        .await;
+    assert_eq!(pin.unwrap().number(), 18);
+}
+
+#[tokio::test]
+async fn test_orphaned_pins_released() {
+    let chain = ChainBuilder::new(146).start_at(10).length(6).build();
+    let state = small_state(&chain, 4).with_pin_budget(1);
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+
+    let pin = state
+        .pin_block(BlockTag::Latest, RetentionReason::Pin)
+        .await
+        .unwrap();
+    let clone = pin.clone();
+    let fork = chain.reorg(2, 147);
+    for block in &fork.blocks()[4..] {
+        state.push_block(block.clone()).await;
+    }
+
+    // the orphaned head took its pins with it, so its slot is free again
+    let head = state
+        .pin_block(BlockTag::Latest, RetentionReason::Pin)
+        .await
+        .unwrap();
+    assert_eq!(head.hash(), fork.head().header.hash);
+    drop((pin, clone));
+    assert_eq!(
+        state.retention(head.number()).await,
+        Some(vec![RetentionReason::Pin])
+    );
+}
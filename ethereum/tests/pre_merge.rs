@@ -0,0 +1,179 @@
+use alloy::rpc::types::Filter;
+
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::ExecutionClient;
+use helios_core::fork_schedule::ForkSchedule;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::harness;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+type Client = ExecutionClient<Ethereum, ChainRpc>;
+type Harness = harness::Harness<Ethereum>;
+
+/// A client over `chain` that treats every block before 12 as pre-merge.
+fn straddling_merge(harness: &Harness, chain: &MockChain, passthrough: bool) -> Client {
+    let fork_schedule = ForkSchedule {
+        merge_block: 12,
+        ..chain.fork_schedule()
+    };
+    ExecutionClient::with_rpc(harness.rpc.clone(), harness.state.clone(), fork_schedule)
+        .with_premerge_passthrough(passthrough)
+}
+
+#[tokio::test]
+async fn test_pre_merge_block_rejected() {
+    let chain = ChainBuilder::new(31).start_at(10).length(6).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = straddling_merge(&harness, &chain, false);
+
+    let err = client
+        .get_block_by_number(BlockTag::Number(11), false)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::PreMergeBlock(11, 12))
+    ));
+    assert!(err.to_string().contains("premerge_passthrough"));
+
+    let block = client
+        .get_block_by_number(BlockTag::Number(12), false)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(block.header.hash, chain.block(12).unwrap().header.hash);
+}
+
+#[tokio::test]
+async fn test_pre_merge_block_passthrough() {
+    let chain = ChainBuilder::new(32).start_at(10).length(6).build();
+    let harness = Harness::new(&chain);
+    harness.state.push_block(chain.head().clone()).await;
+    let client = straddling_merge(&harness, &chain, true);
+
+    // served by the provider even though state never held it
+    let block = client
+        .get_block_by_number(BlockTag::Number(11), true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(block.header.hash, chain.block(11).unwrap().header.hash);
+}
+
+#[tokio::test]
+async fn test_pre_merge_passthrough_checks_block() {
+    let chain = ChainBuilder::new(144).start_at(10).length(6).build();
+    let harness = Harness::new(&chain);
+    harness.state.push_block(chain.head().clone()).await;
+    let client = straddling_merge(&harness, &chain, true);
+
+    harness
+        .rpc
+        .map_blocks(|block| block["extraData"] = serde_json::json!("0x01"));
+    let err = client
+        .get_block_by_number(BlockTag::Number(11), true)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::InvalidHeaderHash(_))
+        ),
+        "{err}"
+    );
+
+    // a consistent block, but another than the one asked for
+    let fork = chain.clone();
+    harness.rpc.map_blocks(move |block| {
+        *block = serde_json::to_value(fork.block(10).unwrap()).unwrap();
+    });
+    let err = client
+        .get_block_by_number(BlockTag::Number(11), true)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::BlockNumberMismatch(11, 10))
+        ),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_pre_merge_block_by_hash() {
+    let chain = ChainBuilder::new(145).start_at(10).length(6).build();
+    let harness = Harness::new(&chain);
+    harness.state.push_block(chain.head().clone()).await;
+    let pre_merge = chain.block(11).unwrap().header.hash;
+    let post_merge = chain.block(13).unwrap().header.hash;
+
+    let client = straddling_merge(&harness, &chain, false);
+    let block = client
+        .get_block_by_hash_or_pre_merge(pre_merge, false)
+        .await;
+    assert!(block.unwrap().is_none());
+
+    let client = straddling_merge(&harness, &chain, true);
+    let block = client
+        .get_block_by_hash_or_pre_merge(pre_merge, false)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(block.header.hash, pre_merge);
+
+    // the state does not hold it, and after the merge the provider's word is not enough
+    let block = client
+        .get_block_by_hash_or_pre_merge(post_merge, false)
+        .await
+        .unwrap();
+    assert!(block.is_none());
+}
+
+#[tokio::test]
+async fn test_pre_merge_logs() {
+    let chain = ChainBuilder::new(33).start_at(10).length(6).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let filter = Filter::new().from_block(10).to_block(13);
+
+    let client = straddling_merge(&harness, &chain, false);
+    let err = client.get_logs(&filter).await.unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::PreMergeBlock(10, 12))
+    ));
+
+    let client = straddling_merge(&harness, &chain, true);
+    let logs = client.get_logs(&filter).await.unwrap();
+    let expected = chain
+        .logs()
+        .into_iter()
+        .filter(|log| (10..=13).contains(&log.block_number.unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(logs, expected);
+    assert!(logs.iter().any(|log| log.block_number == Some(11)));
+    assert!(logs.iter().any(|log| log.block_number == Some(13)));
+}
+
+#[tokio::test]
+async fn test_pre_merge_state_rejected_with_passthrough() {
+    let chain = ChainBuilder::new(34).start_at(10).length(6).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = straddling_merge(&harness, &chain, true);
+
+    let err = client
+        .get_account(chain.token(), None, BlockTag::Number(11))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::PreMergeBlock(11, 12))
+    ));
+
+    client
+        .get_account(chain.token(), None, BlockTag::Number(12))
+        .await
+        .unwrap();
+}
@@ -0,0 +1,137 @@
+use alloy::consensus::Header as ConsensusHeader;
+use alloy::primitives::B256;
+use alloy::rpc::types::{Block, BlockTransactions, Header, Transaction};
+use tokio::sync::{
+    mpsc::{channel, Sender},
+    watch,
+};
+
+use helios_core::execution::rpc::{mock_rpc::MockRpc, ExecutionRpc};
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::fork_schedule::ForkSchedule;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+fn block(number: u64, parent_hash: B256) -> Block<Transaction> {
+    let inner = ConsensusHeader {
+        number,
+        parent_hash,
+        mix_hash: B256::repeat_byte(number as u8),
+        ..Default::default()
+    };
+
+    let header = Header {
+        hash: inner.hash_slow(),
+        inner,
+        total_difficulty: None,
+        size: None,
+    };
+
+    Block::new(header, BlockTransactions::Full(vec![]))
+}
+
+type Senders = (
+    Sender<Block<Transaction>>,
+    watch::Sender<Option<Block<Transaction>>>,
+);
+
+async fn setup(blocks: &[Block<Transaction>]) -> (ExecutionClient<Ethereum, MockRpc>, Senders) {
+    let (block_send, block_recv) = channel(256);
+    let (finalized_block_send, finalized_block_recv) = watch::channel(None);
+
+    // the mock rpc has no blocks, so anything not pushed here can't be served
+    let rpc = <MockRpc as ExecutionRpc<Ethereum>>::new("testdata/").unwrap();
+    let state = State::with_rpc(block_recv, finalized_block_recv, 64, rpc.clone());
+    for block in blocks {
+        state.push_block(block.clone()).await;
+    }
+
+    let fork_schedule = ForkSchedule {
+        prague_timestamp: u64::MAX,
+        merge_block: 0,
+    };
+
+    let client = ExecutionClient::with_rpc(rpc, state, fork_schedule);
+    (client, (block_send, finalized_block_send))
+}
+
+fn chain(start: u64, len: u64) -> Vec<Block<Transaction>> {
+    let mut parent_hash = B256::ZERO;
+    (start..start + len)
+        .map(|number| {
+            let block = block(number, parent_hash);
+            parent_hash = block.header.hash;
+            block
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_prev_randao_matches_header() {
+    let blocks = chain(100, 4);
+    let (client, _senders) = setup(&blocks).await;
+
+    for block in &blocks {
+        let value = client
+            .get_prev_randao(BlockTag::Number(block.header.number))
+            .await
+            .unwrap();
+
+        assert_eq!(value.prev_randao, block.header.mix_hash);
+        assert_eq!(value.block_hash, block.header.hash);
+    }
+
+    let latest = client.get_prev_randao(BlockTag::Latest).await.unwrap();
+    assert_eq!(latest.block_number, 103);
+}
+
+#[tokio::test]
+async fn test_prev_randao_outside_window() {
+    let (client, _senders) = setup(&chain(100, 4)).await;
+
+    let err = client
+        .get_prev_randao(BlockTag::Number(99))
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "block 99 is outside the retained window [100, 103]"
+    );
+
+    assert!(client.get_prev_randao_range(98, 101).await.is_err());
+    assert!(client.get_prev_randao_range(101, 104).await.is_err());
+    assert!(client.get_prev_randao_range(102, 101).await.is_err());
+}
+
+#[tokio::test]
+async fn test_prev_randao_range() {
+    let blocks = chain(100, 4);
+    let (client, _senders) = setup(&blocks).await;
+
+    let range = client.get_prev_randao_range(101, 103).await.unwrap();
+    assert_eq!(range.next_block, None);
+    assert_eq!(range.values.len(), 3);
+    for (value, block) in range.values.iter().zip(&blocks[1..]) {
+        assert_eq!(value.block_number, block.header.number);
+        assert_eq!(value.prev_randao, block.header.mix_hash);
+    }
+}
+
+#[tokio::test]
+async fn test_prev_randao_range_paged() {
+    let blocks = chain(100, 40);
+    let (client, _senders) = setup(&blocks).await;
+
+    let first = client.get_prev_randao_range(100, 139).await.unwrap();
+    assert_eq!(first.values.len(), 32);
+    assert_eq!(first.next_block, Some(132));
+
+    let rest = client.get_prev_randao_range(132, 139).await.unwrap();
+    assert_eq!(rest.next_block, None);
+    let values = first.values.into_iter().chain(rest.values);
+    for (value, block) in values.zip(&blocks) {
+        assert_eq!(value.block_number, block.header.number);
+        assert_eq!(value.prev_randao, block.header.mix_hash);
+    }
+}
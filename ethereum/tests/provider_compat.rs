@@ -0,0 +1,184 @@
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use alloy::primitives::{b256, B256};
+use alloy::providers::ProviderBuilder;
+use alloy::rpc::client::RpcClient;
+use alloy::rpc::json_rpc::{RequestPacket, Response, ResponsePacket, ResponsePayload};
+use alloy::rpc::types::TransactionReceipt;
+use alloy::transports::{TransportError, TransportFut};
+use serde_json::{value::RawValue, Value};
+use tower::Service;
+
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::rpc::compat::{is_canonical_receipt, normalize_receipt};
+use helios_core::execution::rpc::{http_rpc::HttpRpc, ExecutionRpc};
+use helios_core::network_spec::NetworkSpec;
+use helios_ethereum::spec::Ethereum;
+
+const CLIENTS: [&str; 4] = ["geth", "reth", "erigon", "nethermind"];
+const TX_HASH: B256 = b256!("2dac1b27ab58b493f902dda8b63979a112398d747f1761c0891777c0983e591f");
+
+/// A transport replaying the responses one execution client gave for the same transaction.
+#[derive(Clone)]
+struct FixtureTransport {
+    fixture: Arc<Value>,
+}
+
+impl FixtureTransport {
+    fn load(client: &str) -> Self {
+        let path = format!("testdata/compat/{client}.json");
+        let fixture = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        Self {
+            fixture: Arc::new(fixture),
+        }
+    }
+
+    fn without(mut self, response: &str, field: &str) -> Self {
+        let fixture = Arc::make_mut(&mut self.fixture);
+        fixture[response].as_object_mut().unwrap().remove(field);
+        self
+    }
+
+    fn rpc(self) -> HttpRpc<Ethereum> {
+        let client = RpcClient::new(self, true);
+        let provider = ProviderBuilder::new()
+            .network::<Ethereum>()
+            .on_client(client);
+        HttpRpc::from_provider("mock://compat", provider)
+    }
+}
+
+impl Service<RequestPacket> for FixtureTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let RequestPacket::Single(req) = req else {
+            panic!("unexpected batch request");
+        };
+
+        let result = match req.method() {
+            "eth_getTransactionByHash" => &self.fixture["transaction"],
+            "eth_getTransactionReceipt" => &self.fixture["receipt"],
+            "web3_clientVersion" => &self.fixture["clientVersion"],
+            method => panic!("unexpected method {method}"),
+        };
+
+        let id = req.id().clone();
+        let result = RawValue::from_string(result.to_string()).unwrap();
+        Box::pin(async move {
+            Ok(ResponsePacket::Single(Response {
+                id,
+                payload: ResponsePayload::Success(result),
+            }))
+        })
+    }
+}
+
+#[tokio::test]
+async fn test_clients_decode_to_same_transaction() {
+    let expected =
+        ExecutionRpc::<Ethereum>::get_transaction(&FixtureTransport::load("geth").rpc(), TX_HASH)
+            .await
+            .unwrap()
+            .unwrap();
+
+    for client in CLIENTS {
+        let rpc = FixtureTransport::load(client).rpc();
+        let tx = ExecutionRpc::<Ethereum>::get_transaction(&rpc, TX_HASH)
+            .await
+            .unwrap_or_else(|err| panic!("{client}: {err}"))
+            .unwrap();
+
+        assert_eq!(tx, expected, "{client}");
+    }
+}
+
+#[tokio::test]
+async fn test_clients_decode_to_same_receipt_encoding() {
+    let rpc = FixtureTransport::load("geth").rpc();
+    let expected = ExecutionRpc::<Ethereum>::get_transaction_receipt(&rpc, TX_HASH)
+        .await
+        .unwrap()
+        .unwrap();
+
+    for client in CLIENTS {
+        let rpc = FixtureTransport::load(client).rpc();
+        let receipt = ExecutionRpc::<Ethereum>::get_transaction_receipt(&rpc, TX_HASH)
+            .await
+            .unwrap_or_else(|err| panic!("{client}: {err}"))
+            .unwrap();
+
+        assert_eq!(
+            Ethereum::encode_receipt(&receipt),
+            Ethereum::encode_receipt(&expected),
+            "{client}"
+        );
+    }
+}
+
+#[test]
+fn test_canonical_receipts_decode_without_normalizing() {
+    let canonical = CLIENTS
+        .into_iter()
+        .filter(|client| {
+            let raw = FixtureTransport::load(client).fixture["receipt"].to_string();
+            if !is_canonical_receipt(&raw) {
+                return false;
+            }
+
+            let direct: TransactionReceipt = serde_json::from_str(&raw).unwrap();
+            let mut value = serde_json::from_str(&raw).unwrap();
+            normalize_receipt(&mut value);
+            let normalized: TransactionReceipt = serde_json::from_value(value).unwrap();
+            assert_eq!(direct, normalized, "{client}");
+            true
+        })
+        .collect::<Vec<_>>();
+
+    // the others send null optional fields or leave `removed` out of their logs
+    assert_eq!(canonical, ["geth"]);
+}
+
+#[tokio::test]
+async fn test_missing_verification_field_reported() {
+    let rpc = FixtureTransport::load("erigon")
+        .without("receipt", "logsBloom")
+        .rpc();
+
+    let err = ExecutionRpc::<Ethereum>::get_transaction_receipt(&rpc, TX_HASH)
+        .await
+        .unwrap_err();
+
+    match err.downcast_ref::<ExecutionError>() {
+        Some(ExecutionError::IncompleteProviderResponse {
+            kind,
+            missing,
+            client,
+        }) => {
+            assert_eq!(*kind, "receipt");
+            assert_eq!(missing, &["logsBloom"]);
+            assert!(client.starts_with("erigon/"));
+        }
+        _ => panic!("unexpected error: {err}"),
+    }
+}
+
+#[tokio::test]
+async fn test_missing_signature_parity_reported() {
+    let rpc = FixtureTransport::load("erigon")
+        .without("transaction", "v")
+        .rpc();
+
+    let err = ExecutionRpc::<Ethereum>::get_transaction(&rpc, TX_HASH)
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("yParity"), "{err}");
+}
@@ -0,0 +1,128 @@
+use alloy::consensus::{Block as ConsensusBlock, BlockBody};
+use alloy::eips::BlockId;
+use alloy::primitives::{keccak256, Bytes, B256};
+use alloy::rpc::types::{Block, BlockTransactions};
+use alloy_trie::root::ordered_trie_root_with_encoder;
+use serde::Deserialize;
+
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, Fork, MockChain};
+use helios_core::testing::rpc::ChainRpc;
+use helios_ethereum::spec::Ethereum;
+
+/// The `debug_getRaw*` responses for mainnet block 1. The header hashes to the block hash,
+/// and the block is the header followed by empty transaction and uncle lists.
+#[derive(Deserialize)]
+struct RawFixture {
+    #[serde(rename = "debug_getRawHeader")]
+    header: Bytes,
+    #[serde(rename = "debug_getRawBlock")]
+    block: Bytes,
+    #[serde(rename = "debug_getRawReceipts")]
+    receipts: Vec<Bytes>,
+}
+
+fn read_fixture<T: for<'de> Deserialize<'de>>(name: &str) -> T {
+    let json = std::fs::read_to_string(format!("testdata/execution/{name}.json")).unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+async fn client_with(
+    chain: &MockChain,
+    blocks: impl IntoIterator<Item = Block>,
+) -> ExecutionClient<Ethereum, ChainRpc> {
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let state = State::unsynced(64, rpc.clone());
+    for block in blocks {
+        state.push_block(block).await;
+    }
+    ExecutionClient::with_rpc(rpc, state, chain.fork_schedule())
+}
+
+#[tokio::test]
+async fn test_raw_responses_match_recorded_block() {
+    let block: Block = read_fixture("mainnet_block_1");
+    let expected: RawFixture = read_fixture("mainnet_block_1_raw");
+    assert_eq!(keccak256(&expected.header), block.header.hash);
+
+    let chain = ChainBuilder::new(1).length(1).build();
+    let client = client_with(&chain, [block]).await;
+    let id = BlockId::number(1);
+
+    assert_eq!(client.get_raw_header(id).await.unwrap(), expected.header);
+    assert_eq!(client.get_raw_block(id).await.unwrap(), expected.block);
+    assert_eq!(
+        client.get_raw_receipts(id).await.unwrap(),
+        expected.receipts
+    );
+}
+
+#[tokio::test]
+async fn test_raw_responses_match_consensus_encoding() {
+    let chain = ChainBuilder::new(3)
+        .length(3)
+        .txs_per_block(6)
+        .fork_at(0, Fork::Paris)
+        .fork_at(3, Fork::Cancun)
+        .build();
+    let client = client_with(&chain, chain.blocks().iter().cloned()).await;
+
+    for block in chain.blocks() {
+        let id = BlockId::hash(block.header.hash);
+
+        let header = client.get_raw_header(id).await.unwrap();
+        assert_eq!(keccak256(&header), block.header.hash);
+
+        // alloy's own block encoding, which is independent of the one used for serving
+        let consensus_block = ConsensusBlock {
+            header: block.header.inner.clone(),
+            body: BlockBody {
+                transactions: block
+                    .transactions
+                    .txns()
+                    .map(|tx| tx.inner.clone())
+                    .collect(),
+                ommers: Vec::new(),
+                withdrawals: block.withdrawals.clone(),
+            },
+        };
+        let raw_block = client.get_raw_block(id).await.unwrap();
+        assert_eq!(raw_block, alloy::rlp::encode(&consensus_block));
+
+        let receipts = client.get_raw_receipts(id).await.unwrap();
+        assert_eq!(receipts.len(), block.transactions.len());
+        let root = ordered_trie_root_with_encoder(&receipts, |receipt, out| {
+            out.extend_from_slice(receipt)
+        });
+        assert_eq!(root, block.header.receipts_root);
+    }
+}
+
+#[tokio::test]
+async fn test_raw_responses_for_unknown_block() {
+    let chain = ChainBuilder::new(4).length(2).build();
+    let client = client_with(&chain, chain.blocks().iter().cloned()).await;
+
+    let unknown = BlockId::hash(B256::repeat_byte(0x42));
+    let err = client.get_raw_header(unknown).await.unwrap_err();
+    assert!(err.to_string().contains("block not found"), "{err}");
+    assert!(client.get_raw_block(unknown).await.is_err());
+    assert!(client.get_raw_receipts(unknown).await.is_err());
+
+    let ahead = BlockId::number(chain.head().header.number + 1);
+    assert!(client.get_raw_header(ahead).await.is_err());
+}
+
+#[tokio::test]
+async fn test_raw_block_needs_full_transactions() {
+    let chain = ChainBuilder::new(5).length(1).txs_per_block(2).build();
+    let mut block = chain.head().clone();
+    block.transactions = BlockTransactions::Hashes(block.transactions.hashes().collect());
+    let client = client_with(&chain, [block]).await;
+
+    let id = BlockId::number(chain.head().header.number);
+    assert!(client.get_raw_header(id).await.is_ok());
+    let err = client.get_raw_block(id).await.unwrap_err();
+    assert!(err.to_string().contains("cannot encode block"), "{err}");
+}
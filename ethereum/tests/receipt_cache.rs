@@ -0,0 +1,108 @@
+use alloy::rpc::types::Filter;
+use serde_json::json;
+
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+type Client = ExecutionClient<Ethereum, ChainRpc>;
+
+/// A chain of six blocks with one `Transfer` log each, so a filter over up to five of them
+/// stays within the logs a query may prove.
+fn chain(seed: u64) -> MockChain {
+    ChainBuilder::new(seed).length(6).txs_per_block(1).build()
+}
+
+async fn verified_client(chain: &MockChain) -> (Client, State<Ethereum, ChainRpc>, ChainRpc) {
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let state = State::unsynced(64, rpc.clone());
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+    let client = ExecutionClient::with_rpc(rpc.clone(), state.clone(), chain.fork_schedule());
+    (client, state, rpc)
+}
+
+#[tokio::test]
+async fn test_overlapping_log_queries_fetch_each_block_once() {
+    let chain = chain(117);
+    let (client, _, rpc) = verified_client(&chain).await;
+
+    let first = client
+        .get_logs(&Filter::new().from_block(1).to_block(4))
+        .await
+        .unwrap();
+    let second = client
+        .get_logs(&Filter::new().from_block(3).to_block(6))
+        .await
+        .unwrap();
+    assert_eq!(first.len(), 4);
+    assert_eq!(second.len(), 4);
+    assert_eq!(rpc.calls("get_block_receipts"), 6);
+
+    // receipts of a transaction are proven from the same cached block receipts
+    let hash = chain.receipts(5).unwrap()[0].transaction_hash;
+    let receipt = client.get_transaction_receipt(hash).await.unwrap().unwrap();
+    assert_eq!(&receipt, chain.receipt(hash).unwrap());
+    assert_eq!(rpc.calls("get_block_receipts"), 6);
+}
+
+#[tokio::test]
+async fn test_reorg_evicts_orphaned_receipts() {
+    let chain = chain(118);
+    let (client, state, rpc) = verified_client(&chain).await;
+    let filter = Filter::new().from_block(2).to_block(6);
+    client.get_logs(&filter).await.unwrap();
+    assert_eq!(rpc.calls("get_block_receipts"), 5);
+
+    let fork = chain.reorg(2, 119);
+    rpc.set_chain(fork.clone());
+    for block in &fork.blocks()[4..] {
+        state.push_block(block.clone()).await;
+    }
+    for number in [5, 6] {
+        let orphaned = chain.block(number).unwrap().header.hash;
+        assert!(state.cached_receipts(orphaned).is_none());
+    }
+    assert!(state
+        .cached_receipts(chain.block(4).unwrap().header.hash)
+        .is_some());
+
+    // only the blocks of the new branch are fetched again
+    let logs = client.get_logs(&filter).await.unwrap();
+    let expected = fork
+        .logs()
+        .into_iter()
+        .filter(|log| log.block_number >= Some(2))
+        .collect::<Vec<_>>();
+    assert_eq!(logs, expected);
+    assert_eq!(rpc.calls("get_block_receipts"), 7);
+}
+
+#[tokio::test]
+async fn test_receipts_failing_root_not_cached() {
+    let chain = chain(120);
+    let (client, state, rpc) = verified_client(&chain).await;
+    let head = chain.head();
+
+    let mut tampered = chain.clone();
+    let hash = chain.receipts(head.header.number).unwrap()[0].transaction_hash;
+    let receipt = tampered.receipt_mut(hash).unwrap();
+    let mut receipt_json = serde_json::to_value(&*receipt).unwrap();
+    receipt_json["cumulativeGasUsed"] = json!("0x1");
+    *receipt = serde_json::from_value(receipt_json).unwrap();
+    rpc.set_chain(tampered);
+
+    let tag = BlockTag::Number(head.header.number);
+    assert!(client.get_block_receipts(tag).await.is_err());
+    assert!(state.cached_receipts(head.header.hash).is_none());
+
+    rpc.set_chain(chain.clone());
+    let receipts = client.get_block_receipts(tag).await.unwrap().unwrap();
+    assert_eq!(receipts, chain.receipts(head.header.number).unwrap());
+    assert_eq!(rpc.calls("get_block_receipts"), 2);
+    assert!(state.cached_receipts(head.header.hash).is_some());
+}
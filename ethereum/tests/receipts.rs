@@ -0,0 +1,229 @@
+use alloy::consensus::TxType;
+use alloy::primitives::{Address, B256};
+
+use helios_core::execution::errors::ExecutionError;
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::harness;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+type Harness = harness::Harness<Ethereum>;
+
+#[tokio::test]
+async fn test_block_receipts_verified() {
+    let chain = ChainBuilder::new(8).empty_block(3).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    for block in chain.blocks() {
+        let tag = BlockTag::Number(block.header.number);
+        let receipts = client.get_block_receipts(tag).await.unwrap().unwrap();
+        assert_eq!(receipts.len(), block.transactions.len());
+    }
+}
+
+#[tokio::test]
+async fn test_empty_block_receipts_answered_locally() {
+    let chain = ChainBuilder::new(8).length(4).empty_block(3).build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    // the header alone proves there are no receipts, so a provider returning null for
+    // the block is never asked
+    harness.rpc.disconnect();
+    let receipts = harness
+        .client
+        .get_block_receipts(BlockTag::Number(3))
+        .await
+        .unwrap();
+    assert_eq!(receipts, Some(vec![]));
+}
+
+#[tokio::test]
+async fn test_transaction_receipt_verified() {
+    let chain = ChainBuilder::new(35).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    for receipt in chain.receipts(chain.head().header.number).unwrap() {
+        let served = harness
+            .client
+            .get_transaction_receipt(receipt.transaction_hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(&served, receipt);
+    }
+}
+
+/// Serves the receipt of the `index`th transaction of the head block with `tamper` applied
+/// to its json, and returns the error of requesting it.
+async fn tampered_receipt_error(
+    index: usize,
+    tamper: impl FnOnce(&mut serde_json::Value),
+) -> eyre::Report {
+    let chain = ChainBuilder::new(36).length(4).txs_per_block(6).build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    let mut tampered = chain.clone();
+    let hash = chain.receipts(chain.head().header.number).unwrap()[index].transaction_hash;
+    let receipt = tampered.receipt_mut(hash).unwrap();
+    let mut json = serde_json::to_value(&*receipt).unwrap();
+    tamper(&mut json);
+    *receipt = serde_json::from_value(json).unwrap();
+    harness.rpc.set_chain(tampered);
+
+    harness
+        .client
+        .get_transaction_receipt(hash)
+        .await
+        .unwrap_err()
+}
+
+fn is_inconsistent_receipt(err: &eyre::Report) -> bool {
+    matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::InconsistentReceipt(..))
+    )
+}
+
+#[tokio::test]
+async fn test_receipt_with_wrong_transaction_index_rejected() {
+    let err = tampered_receipt_error(2, |json| json["transactionIndex"] = "0x3".into()).await;
+    assert!(is_inconsistent_receipt(&err), "{err}");
+}
+
+#[tokio::test]
+async fn test_receipt_with_wrong_log_index_rejected() {
+    // even transactions are token transfers with a single log
+    let err = tampered_receipt_error(2, |json| json["logs"][0]["logIndex"] = "0x0".into()).await;
+    assert!(is_inconsistent_receipt(&err), "{err}");
+}
+
+#[tokio::test]
+async fn test_receipt_with_wrong_block_hash_rejected() {
+    let err = tampered_receipt_error(2, |json| {
+        json["blockHash"] = serde_json::json!(B256::repeat_byte(7))
+    })
+    .await;
+    assert!(is_inconsistent_receipt(&err), "{err}");
+}
+
+#[tokio::test]
+async fn test_receipt_with_forged_contract_address_rejected() {
+    // the transaction is a call, so it created no contract
+    let err = tampered_receipt_error(2, |json| {
+        json["contractAddress"] = serde_json::json!(Address::repeat_byte(7))
+    })
+    .await;
+    assert!(is_inconsistent_receipt(&err), "{err}");
+}
+
+#[tokio::test]
+async fn test_receipt_with_wrong_sender_rejected() {
+    let err = tampered_receipt_error(2, |json| {
+        json["from"] = serde_json::json!(Address::repeat_byte(7))
+    })
+    .await;
+    assert!(is_inconsistent_receipt(&err), "{err}");
+}
+
+#[tokio::test]
+async fn test_receipt_with_wrong_cumulative_gas_rejected() {
+    // the receipts root commits to cumulative gas, so this fails before the neighbour checks
+    let err = tampered_receipt_error(2, |json| json["cumulativeGasUsed"] = "0x1".into()).await;
+    assert!(err.downcast_ref::<ExecutionError>().is_some(), "{err}");
+}
+
+#[tokio::test]
+async fn test_typed_transaction_receipts_verified() {
+    let chain = ChainBuilder::new(37).length(2).typed_txs().build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    let receipts = chain.receipts(chain.head().header.number).unwrap();
+    assert!(receipts
+        .iter()
+        .any(|receipt| receipt.transaction_type() == TxType::Legacy));
+    assert!(receipts
+        .iter()
+        .any(|receipt| receipt.transaction_type() == TxType::Eip1559));
+    for receipt in receipts {
+        let served = harness
+            .client
+            .get_transaction_receipt(receipt.transaction_hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(&served, receipt);
+    }
+}
+
+/// Requests the receipt of the `index`th transaction of the head block while the provider
+/// applies `tamper` only to its `eth_getTransactionReceipt` answer.
+async fn mismatched_receipt_error(
+    index: usize,
+    tamper: impl Fn(&mut serde_json::Value) + Send + Sync + 'static,
+) -> eyre::Report {
+    let chain = ChainBuilder::new(38).length(2).typed_txs().build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    harness.rpc.map_receipts(tamper);
+    let hash = chain.receipts(chain.head().header.number).unwrap()[index].transaction_hash;
+    harness
+        .client
+        .get_transaction_receipt(hash)
+        .await
+        .unwrap_err()
+}
+
+fn is_receipt_mismatch(err: &eyre::Report) -> bool {
+    matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::ReceiptMismatch(..))
+    )
+}
+
+#[tokio::test]
+async fn test_receipt_with_tampered_status_rejected() {
+    for index in [0, 1] {
+        let err = mismatched_receipt_error(index, |json| json["status"] = "0x0".into()).await;
+        assert!(is_receipt_mismatch(&err), "{err}");
+    }
+}
+
+#[tokio::test]
+async fn test_receipt_with_tampered_log_rejected() {
+    let err = mismatched_receipt_error(0, |json| {
+        json["logs"][0]["data"] = format!("0x{}", "00".repeat(32)).into()
+    })
+    .await;
+    assert!(is_receipt_mismatch(&err), "{err}");
+}
+
+#[tokio::test]
+async fn test_receipt_with_tampered_gas_used_rejected() {
+    // gas used is not part of the encoding, it follows from the cumulative gas instead
+    let err = mismatched_receipt_error(1, |json| json["gasUsed"] = "0x1".into()).await;
+    assert!(is_inconsistent_receipt(&err), "{err}");
+}
+
+#[tokio::test]
+async fn test_receipt_of_unverified_block_rejected() {
+    let chain = ChainBuilder::new(39).length(3).build();
+    let harness = Harness::new(&chain);
+    for block in &chain.blocks()[..2] {
+        harness.state.push_block(block.clone()).await;
+    }
+
+    let hash = chain.receipts(chain.head().header.number).unwrap()[0].transaction_hash;
+    let err = harness
+        .client
+        .get_transaction_receipt(hash)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::UnverifiedReceiptBlock(tx)) if *tx == hash
+        ),
+        "{err}"
+    );
+}
@@ -0,0 +1,345 @@
+use alloy::rpc::types::{Filter, FilterChanges, Log};
+
+use helios_core::execution::backfill::BackfillConfig;
+use helios_core::execution::orphans::OrphanCacheConfig;
+use helios_core::execution::state::State;
+use helios_core::execution::types::DeepReorg;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::harness;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::{BlockByHash, BlockTag, ReorgEvent};
+use helios_ethereum::spec::Ethereum;
+
+type Client = ExecutionClient<Ethereum, ChainRpc>;
+type Harness = harness::Harness<Ethereum>;
+
+#[tokio::test]
+async fn test_state_prunes_abandoned_branch() {
+    let chain = ChainBuilder::new(2).length(6).build();
+    let fork = chain.reorg(2, 42);
+    let harness = Harness::with_blocks(&chain).await;
+    let (client, state) = (&harness.client, &harness.state);
+
+    state.push_block(fork.head().clone()).await;
+
+    let head = client.get_block(BlockTag::Latest, false).await.unwrap();
+    assert_eq!(head.header.hash, fork.head().header.hash);
+
+    let parent = chain.blocks()[chain.blocks().len() - 2].header.number;
+    assert!(client
+        .get_block(BlockTag::Number(parent), false)
+        .await
+        .is_none());
+}
+
+/// Reorgs the last `depth` blocks of a chain of 8 under a confirmation depth of 2, returning
+/// the old and new branches and any deep reorg reported.
+async fn reorg_under_confirmation(
+    depth: u64,
+) -> (MockChain, MockChain, Harness, Option<DeepReorg>) {
+    let chain = ChainBuilder::new(6).length(8).build();
+    let fork = chain.reorg(depth, 60 + depth);
+    let mut harness = Harness::with_blocks(&chain).await;
+    harness.state = harness.state.clone().with_confirmation_depth(2);
+    harness.client = Client::with_rpc(
+        harness.rpc.clone(),
+        harness.state.clone(),
+        chain.fork_schedule(),
+    );
+    let mut deep_reorgs = harness.client.deep_reorgs();
+
+    harness.rpc.set_chain(fork.clone());
+    harness.state.push_block(fork.head().clone()).await;
+
+    let reported = deep_reorgs.try_recv().ok();
+    (chain, fork, harness, reported)
+}
+
+#[tokio::test]
+async fn test_reorg_at_confirmation_depth_keeps_confirmed_block() {
+    let (chain, fork, harness, reported) = reorg_under_confirmation(2).await;
+    assert_eq!(reported, None);
+
+    // the reorg stops just above the confirmed block, which both branches share
+    let confirmed = harness
+        .client
+        .get_block(BlockTag::Confirmed, false)
+        .await
+        .unwrap();
+    assert_eq!(confirmed.header.hash, chain.blocks()[5].header.hash);
+    assert_eq!(confirmed.header.hash, fork.blocks()[5].header.hash);
+
+    // the new branch is backfilled down to where it forks off
+    for block in fork.blocks() {
+        let stored = harness
+            .client
+            .get_block(BlockTag::Number(block.header.number), false)
+            .await
+            .unwrap();
+        assert_eq!(stored.header.hash, block.header.hash);
+    }
+}
+
+#[tokio::test]
+async fn test_reorg_past_confirmation_depth_reported() {
+    let (chain, fork, harness, reported) = reorg_under_confirmation(3).await;
+
+    assert_eq!(
+        reported,
+        Some(DeepReorg {
+            depth: 3,
+            confirmation_depth: 2,
+            orphaned_confirmed: chain.blocks()[5].header.hash,
+            old_head: chain.head().header.hash,
+            new_head: fork.head().header.hash,
+        })
+    );
+
+    // the tag moved to the new branch
+    let confirmed = harness
+        .client
+        .get_block(BlockTag::Confirmed, false)
+        .await
+        .unwrap();
+    assert_eq!(confirmed.header.hash, fork.blocks()[5].header.hash);
+    assert_ne!(confirmed.header.hash, chain.blocks()[5].header.hash);
+    assert!(harness
+        .client
+        .get_block_by_hash(chain.blocks()[5].header.hash, false)
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_orphaned_blocks_served_by_hash() {
+    let (chain, fork, harness, _) = reorg_under_confirmation(3).await;
+    let client = &harness.client;
+
+    for (old, new) in chain.blocks()[5..].iter().zip(&fork.blocks()[5..]) {
+        let number = old.header.number;
+        assert!(client
+            .get_block_by_hash(old.header.hash, false)
+            .await
+            .is_none());
+
+        let orphan = client.get_orphaned_block(old.header.hash, true).unwrap();
+        assert_eq!(orphan.header, old.header);
+        assert_eq!(orphan.transactions.len(), old.transactions.len());
+
+        // the new branch keeps the height
+        let stored = client
+            .get_block(BlockTag::Number(number), false)
+            .await
+            .unwrap();
+        assert_eq!(stored.header.hash, new.header.hash);
+        assert!(client.get_orphaned_block(new.header.hash, false).is_none());
+
+        let served = serde_json::to_value(BlockByHash {
+            block: orphan,
+            orphaned: true,
+        })
+        .unwrap();
+        assert_eq!(
+            served["hash"],
+            serde_json::to_value(old.header.hash).unwrap()
+        );
+        assert_eq!(served["orphaned"], true);
+    }
+
+    let canonical = serde_json::to_value(BlockByHash {
+        block: fork.head().clone(),
+        orphaned: false,
+    })
+    .unwrap();
+    assert!(canonical.get("orphaned").is_none());
+
+    assert_eq!(
+        harness.client.reorg_history(),
+        vec![ReorgEvent {
+            depth: 3,
+            fork_number: chain.blocks()[5].header.number,
+            old_chain: chain.blocks()[5..].iter().map(|b| b.header.hash).collect(),
+            new_chain: fork.blocks()[5..].iter().map(|b| b.header.hash).collect(),
+            deep: true,
+            detected_at: harness.client.reorg_history()[0].detected_at,
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_reorg_history_records_shallow_reorgs() {
+    let (chain, fork, harness, _) = reorg_under_confirmation(1).await;
+
+    let history = harness.client.reorg_history();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].depth, 1);
+    assert!(!history[0].deep);
+    assert_eq!(history[0].old_chain, vec![chain.head().header.hash]);
+    assert_eq!(history[0].new_chain, vec![fork.head().header.hash]);
+}
+
+#[tokio::test]
+async fn test_reorg_to_shorter_branch_evicts_descendants() {
+    let chain = ChainBuilder::new(96).length(8).build();
+    let fork = chain.reorg(3, 97);
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+    let block_filter = client.new_block_filter().await.unwrap();
+    client.get_filter_changes(block_filter).await.unwrap();
+
+    // the new head is on a branch one block shorter than the old one
+    let new_head = &fork.blocks()[6];
+    harness.rpc.set_chain(fork.clone());
+    harness.state.push_block(new_head.clone()).await;
+
+    let latest = client.get_block(BlockTag::Latest, false).await.unwrap();
+    assert_eq!(latest.header.hash, new_head.header.hash);
+    for block in &fork.blocks()[5..7] {
+        let stored = client
+            .get_block(BlockTag::Number(block.header.number), false)
+            .await
+            .unwrap();
+        assert_eq!(stored.header.hash, block.header.hash);
+    }
+    assert!(client
+        .get_block(BlockTag::Number(chain.head().header.number), false)
+        .await
+        .is_none());
+    assert!(client
+        .get_block_by_hash(chain.head().header.hash, false)
+        .await
+        .is_none());
+
+    let history = client.reorg_history();
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].depth, 3);
+    assert_eq!(
+        history[0].old_chain,
+        chain.blocks()[5..]
+            .iter()
+            .map(|b| b.header.hash)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        history[0].new_chain,
+        fork.blocks()[5..7]
+            .iter()
+            .map(|b| b.header.hash)
+            .collect::<Vec<_>>()
+    );
+
+    // the block filter reports the new branch from the lowest replaced block up
+    let changes = client.get_filter_changes(block_filter).await.unwrap();
+    let hashes = fork.blocks()[5..7].iter().map(|b| b.header.hash).collect();
+    assert_eq!(changes, FilterChanges::Hashes(hashes));
+
+    // a late delivery of a block below the head on the same branch changes nothing
+    harness.state.push_block(fork.blocks()[5].clone()).await;
+    let latest = client.get_block(BlockTag::Latest, false).await.unwrap();
+    assert_eq!(latest.header.hash, new_head.header.hash);
+    assert_eq!(client.reorg_history().len(), 1);
+}
+
+#[tokio::test]
+async fn test_filter_changes_report_orphaned_logs_as_removed() {
+    let chain = ChainBuilder::new(98).length(8).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let from = chain.blocks()[6].header.number;
+    let filter = Filter::new().from_block(from);
+    let logs_filter = client.new_filter(&filter).await.unwrap();
+    let FilterChanges::Logs(delivered) = client.get_filter_changes(logs_filter).await.unwrap()
+    else {
+        panic!("expected logs");
+    };
+    assert_eq!(delivered.len(), 4);
+
+    let fork = chain.reorg(2, 99);
+    harness.rpc.set_chain(fork.clone());
+    harness.state.push_block(fork.head().clone()).await;
+
+    let removed = delivered
+        .iter()
+        .cloned()
+        .map(|log| Log {
+            removed: true,
+            ..log
+        })
+        .collect::<Vec<_>>();
+    let changes = client.get_filter_changes(logs_filter).await.unwrap();
+    assert_eq!(changes, FilterChanges::Logs(removed));
+
+    // removed logs are only reported once
+    let changes = client.get_filter_changes(logs_filter).await.unwrap();
+    assert_eq!(changes, FilterChanges::Logs(vec![]));
+}
+
+#[tokio::test]
+async fn test_orphan_cache_limits() {
+    let (chain, _, harness, _) = reorg_under_confirmation(3).await;
+    let state = harness.state.clone().with_orphan_cache(OrphanCacheConfig {
+        size: 1,
+        ..Default::default()
+    });
+
+    // only the last block orphaned is kept
+    assert!(state
+        .get_orphaned_block(chain.blocks()[7].header.hash)
+        .is_none());
+    assert!(state
+        .get_orphaned_block(chain.blocks()[5].header.hash)
+        .is_some());
+}
+
+#[tokio::test]
+async fn test_conflicting_finalized_block_clears_state() {
+    let chain = ChainBuilder::new(3).length(4).build();
+    let fork = chain.reorg(2, 7);
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let finalized = fork.blocks()[2].clone();
+    harness
+        .finalized_block_send
+        .send(Some(finalized.clone()))
+        .unwrap();
+
+    let mut cleared = false;
+    for _ in 0..100 {
+        tokio::task::yield_now().await;
+        if client.get_block(BlockTag::Latest, false).await.is_none() {
+            cleared = true;
+            break;
+        }
+    }
+    assert!(cleared);
+
+    let stored = client.get_block(BlockTag::Finalized, false).await.unwrap();
+    assert_eq!(stored.header.hash, finalized.header.hash);
+
+    // the replaced blocks stay fetchable by hash, the shared ones are only dropped
+    for old in &chain.blocks()[2..] {
+        assert!(client.get_orphaned_block(old.header.hash, false).is_some());
+    }
+    assert!(client
+        .get_orphaned_block(chain.blocks()[1].header.hash, false)
+        .is_none());
+}
+
+#[tokio::test]
+async fn test_unlinked_parent_orphaned() {
+    let chain = ChainBuilder::new(190).length(6).build();
+    let fork = chain.reorg(2, 191);
+    let state = State::<Ethereum, _>::unsynced(64, ChainRpc::from_chain(fork.clone()))
+        .with_backfill(BackfillConfig::disabled());
+
+    // nothing is held before the old block 5 to link the new branch to
+    let old = chain.block(5).unwrap().clone();
+    state.push_block(old.clone()).await;
+    state.push_block(fork.head().clone()).await;
+
+    assert_eq!(state.oldest_block_number().await, Some(6));
+    assert_eq!(state.get_orphaned_block(old.header.hash), Some(old));
+}
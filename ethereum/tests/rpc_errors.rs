@@ -0,0 +1,118 @@
+use std::sync::Arc;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{Address, U256};
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol_types::{Revert, SolError};
+use eyre::WrapErr;
+use jsonrpsee::types::error::ErrorObjectOwned;
+
+use helios_core::errors::{codes, ClientError, RpcError, ServerError};
+use helios_core::execution::bundle::{AccountOverride, StateOverride};
+use helios_core::execution::evm::Evm;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::code::revert_code;
+use helios_core::testing::consensus::verified_client_with;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+const REVERTER: Address = Address::repeat_byte(0xe1);
+
+async fn evm(
+    chain: &MockChain,
+    rpc: ChainRpc,
+    overrides: StateOverride,
+) -> Evm<Ethereum, ChainRpc> {
+    let (client, _) = verified_client_with::<Ethereum, _>(chain, rpc).await;
+
+    Evm::new(
+        Arc::new(client),
+        chain.chain_id(),
+        chain.fork_schedule(),
+        BlockTag::Latest,
+    )
+    .with_state_overrides(overrides)
+}
+
+fn sender(chain: &MockChain) -> Address {
+    let token = chain.token();
+    chain
+        .accounts()
+        .into_iter()
+        .find(|account| *account != token)
+        .unwrap()
+}
+
+/// The error object the server answers with for `err`.
+fn served(err: impl Into<ServerError>) -> ErrorObjectOwned {
+    err.into().into()
+}
+
+#[tokio::test]
+async fn test_revert_served_with_code_and_data() {
+    let chain = ChainBuilder::new(106).length(3).build();
+    let revert = Revert {
+        reason: "not allowed".to_string(),
+    }
+    .abi_encode();
+    let code = AccountOverride {
+        code: Some(revert_code(&revert)),
+        ..Default::default()
+    };
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let mut evm = evm(&chain, rpc, StateOverride::from([(REVERTER, code)])).await;
+
+    let tx = TransactionRequest::default()
+        .with_from(sender(&chain))
+        .with_to(REVERTER);
+    let err = evm.call(&tx).await.unwrap_err();
+
+    let error = served(ClientError::EvmError(err));
+    assert_eq!(error.code(), codes::EXECUTION_REVERTED);
+    assert_eq!(error.message(), "execution reverted: not allowed");
+    let data = error.data().map(|data| data.get().to_string());
+    assert_eq!(data, Some(format!("\"0x{}\"", hex::encode(&revert))));
+}
+
+#[tokio::test]
+async fn test_tampered_proof_served_as_verification_failure() {
+    let chain = ChainBuilder::new(107).length(3).build();
+    let token = chain.token();
+    let rpc = ChainRpc::from_chain(chain.clone());
+    rpc.map_proofs(move |mut proof| {
+        if proof.address == token {
+            proof.balance += U256::from(1);
+        }
+        proof
+    });
+    let mut evm = evm(&chain, rpc, StateOverride::default()).await;
+
+    let tx = TransactionRequest::default()
+        .with_from(sender(&chain))
+        .with_to(token);
+    let err = evm.call(&tx).await.unwrap_err();
+
+    let error = served(ClientError::EvmError(err));
+    assert_eq!(error.code(), codes::VERIFICATION_FAILED);
+    assert!(
+        error.message().contains("invalid account proof"),
+        "{}",
+        error.message()
+    );
+    assert!(error.data().is_none());
+}
+
+#[test]
+fn test_provider_failure_served_as_upstream_error() {
+    let err = Err::<(), _>(RpcError::new("get_proof", "connection refused"))
+        .wrap_err("could not fetch the account")
+        .unwrap_err();
+
+    let error = served(err);
+    assert_eq!(error.code(), codes::UPSTREAM_ERROR);
+    assert_eq!(error.message(), "could not fetch the account");
+
+    let error = served(ClientError::BlockNotFound(BlockTag::Number(1 << 40)));
+    assert_eq!(error.code(), codes::RESOURCE_NOT_FOUND);
+}
@@ -0,0 +1,89 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::network::Network;
+use serde_json::{json, Value};
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+use helios_core::client::node::Node;
+use helios_core::client::rpc::Rpc;
+use helios_core::client::tls::TlsConfig;
+use helios_core::execution::rpc::http_rpc::HttpRpc;
+use helios_core::execution::rpc::ExecutionRpc;
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::consensus::{execution_config, MockConsensus};
+use helios_ethereum::spec::Ethereum;
+
+type Block = <Ethereum as Network>::BlockResponse;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_rpc_served_over_tls() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let chain = ChainBuilder::new(142)
+        .length(3)
+        .genesis_timestamp(now.as_secs())
+        .build();
+    let (consensus, feed) = MockConsensus::<Block>::new(chain.chain_id());
+    let rpc = <HttpRpc<Ethereum> as ExecutionRpc<Ethereum>>::new("http://127.0.0.1:1").unwrap();
+    let node = Node::<Ethereum, _>::with_execution_rpc(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+    );
+    for block in chain.blocks() {
+        feed.advance(block.clone()).await.unwrap();
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let rcgen::CertifiedKey { cert, key_pair } =
+        rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let tls = TlsConfig {
+        cert_path: dir.path().join("cert.pem"),
+        key_path: dir.path().join("key.pem"),
+    };
+    std::fs::write(&tls.cert_path, cert.pem()).unwrap();
+    std::fs::write(&tls.key_path, key_pair.serialize_pem()).unwrap();
+
+    let mut server = Rpc::new(Arc::new(node), SocketAddr::from(([127, 0, 0, 1], 0))).with_tls(tls);
+    let addr = server.start().await.unwrap().unwrap();
+
+    let client = reqwest::Client::builder()
+        .add_root_certificate(reqwest::Certificate::from_pem(cert.pem().as_bytes()).unwrap())
+        .build()
+        .unwrap();
+    let url = format!("https://localhost:{}", addr.port());
+    let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "eth_chainId", "params": [] });
+    let response: Value = client
+        .post(&url)
+        .json(&request)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(response["result"], format!("{:#x}", chain.chain_id()));
+
+    // plaintext requests are not answered on the tls address
+    assert!(reqwest::Client::new()
+        .post(format!("http://{addr}"))
+        .json(&request)
+        .send()
+        .await
+        .is_err());
+
+    // connections still open are closed as the server stops
+    let mut open = TcpStream::connect(addr).await.unwrap();
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    server.stop(Duration::from_secs(1)).await;
+    let mut buf = [0u8; 16];
+    let read = timeout(Duration::from_secs(5), open.read(&mut buf))
+        .await
+        .expect("connection left open");
+    assert!(matches!(read, Ok(0) | Err(_)), "{read:?}");
+    assert!(TcpStream::connect(addr).await.is_err());
+}
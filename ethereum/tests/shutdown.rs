@@ -0,0 +1,116 @@
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use alloy::primitives::U256;
+use alloy::providers::ProviderBuilder;
+use alloy::rpc::client::ClientBuilder;
+use alloy::rpc::json_rpc::{RequestPacket, Response, ResponsePacket, ResponsePayload};
+use alloy::rpc::types::Filter;
+use alloy::transports::{TransportError, TransportFut};
+use serde_json::value::RawValue;
+use tower::Service;
+
+use helios_core::client::Client;
+use helios_core::execution::rpc::http_rpc::HttpRpc;
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::consensus::{execution_config, MockConsensus};
+use helios_ethereum::spec::Ethereum;
+
+/// A provider installing filters under consecutive ids and recording which get uninstalled.
+#[derive(Clone, Default)]
+struct FilterTransport {
+    installed: Arc<Mutex<u64>>,
+    uninstalled: Arc<Mutex<Vec<U256>>>,
+}
+
+impl Service<RequestPacket> for FilterTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: RequestPacket) -> Self::Future {
+        let RequestPacket::Single(req) = req else {
+            panic!("unexpected batch request");
+        };
+
+        let result = match req.method() {
+            "eth_newFilter" | "eth_newBlockFilter" | "eth_newPendingTransactionFilter" => {
+                let mut installed = self.installed.lock().unwrap();
+                *installed += 1;
+                format!("\"{:#x}\"", *installed)
+            }
+            "eth_uninstallFilter" => {
+                let params = req.params().unwrap().get();
+                let (id,): (U256,) = serde_json::from_str(params).unwrap();
+                self.uninstalled.lock().unwrap().push(id);
+                "true".to_string()
+            }
+            method => panic!("unexpected request {method}"),
+        };
+
+        Box::pin(async move {
+            Ok(ResponsePacket::Single(Response {
+                id: req.id().clone(),
+                payload: ResponsePayload::Success(RawValue::from_string(result).unwrap()),
+            }))
+        })
+    }
+}
+
+fn free_address() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_shutdown_uninstalls_filters_and_releases_port() {
+    let chain = ChainBuilder::new(67).length(2).build();
+    let transport = FilterTransport::default();
+    let provider = ProviderBuilder::new()
+        .network::<Ethereum>()
+        .on_client(ClientBuilder::default().transport(transport.clone(), true));
+    let rpc = HttpRpc::<Ethereum>::from_provider("mock://filters", provider);
+    let (consensus, _feed) = MockConsensus::new(chain.chain_id());
+
+    let address = free_address();
+    let mut client = Client::with_execution_rpc(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+        Some(address),
+    );
+    client.start().await.unwrap();
+    assert!(TcpListener::bind(address).is_err());
+
+    let filters = [
+        client
+            .new_filter(&Filter::new().from_block(1).to_block(2))
+            .await
+            .unwrap(),
+        client.new_block_filter().await.unwrap(),
+        client.new_pending_transaction_filter().await.unwrap(),
+    ];
+    // filters uninstalled by the caller are not uninstalled again
+    assert!(client.uninstall_filter(filters[0]).await.unwrap());
+
+    client.shutdown().await;
+
+    let mut uninstalled = transport.uninstalled.lock().unwrap().clone();
+    uninstalled.sort();
+    assert_eq!(uninstalled, filters);
+    drop(TcpListener::bind(address).unwrap());
+
+    // the same client serves again after a restart
+    client.start().await.unwrap();
+    assert!(TcpListener::bind(address).is_err());
+    client.shutdown().await;
+    assert_eq!(transport.uninstalled.lock().unwrap().len(), 3);
+}
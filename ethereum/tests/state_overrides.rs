@@ -0,0 +1,207 @@
+use std::sync::{Arc, Mutex};
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{hex, Address, Bytes, B256, U256};
+use alloy::rpc::types::{AccessListItem, TransactionRequest};
+
+use helios_core::execution::bundle::{AccountOverride, StateOverride};
+use helios_core::execution::errors::{EvmError, ExecutionError};
+use helios_core::execution::evm::Evm;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::consensus::verified_client_with;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+/// Returns slot 0 when called without data.
+const STORE_CODE: [u8; 36] = hex!(
+    "3615601857600035600055604036106016576000"
+    "80fd5b005b60005460005260206000f3"
+);
+
+/// Reads the balance of the coinbase.
+const COINBASE_BALANCE_CODE: [u8; 4] = hex!("41315000");
+
+/// Proofs requested from the provider, by account and slots.
+type Proofs = Arc<Mutex<Vec<(Address, Vec<B256>)>>>;
+
+async fn evm(chain: &MockChain, overrides: StateOverride) -> (Evm<Ethereum, ChainRpc>, Proofs) {
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let proofs = Proofs::default();
+    let recorded = proofs.clone();
+    rpc.map_proofs(move |proof| {
+        let slots = proof
+            .storage_proof
+            .iter()
+            .map(|slot| slot.key.as_b256())
+            .collect();
+        recorded.lock().unwrap().push((proof.address, slots));
+        proof
+    });
+
+    let (client, _) = verified_client_with::<Ethereum, _>(chain, rpc).await;
+
+    let evm = Evm::new(
+        Arc::new(client),
+        chain.chain_id(),
+        chain.fork_schedule(),
+        BlockTag::Latest,
+    )
+    .with_state_overrides(overrides);
+    (evm, proofs)
+}
+
+fn store_code(state: Option<&[(u64, u64)]>, state_diff: Option<&[(u64, u64)]>) -> AccountOverride {
+    let slots = |slots: &[(u64, u64)]| {
+        slots
+            .iter()
+            .map(|(slot, value)| {
+                (
+                    B256::from(U256::from(*slot)),
+                    B256::from(U256::from(*value)),
+                )
+            })
+            .collect()
+    };
+    AccountOverride {
+        code: Some(Bytes::from_static(&STORE_CODE)),
+        state: state.map(slots),
+        state_diff: state_diff.map(slots),
+        ..Default::default()
+    }
+}
+
+fn read_slot(chain: &MockChain, to: Address) -> TransactionRequest {
+    TransactionRequest::default()
+        .with_from(chain.accounts()[0])
+        .with_to(to)
+}
+
+#[tokio::test]
+async fn test_code_override_on_eoa_runs_without_fetching_slots() {
+    let chain = ChainBuilder::new(85).length(3).build();
+    let eoa = chain.accounts()[1];
+    let overrides = StateOverride::from([(eoa, store_code(None, Some(&[(0, 7)])))]);
+    let (mut evm, proofs) = evm(&chain, overrides).await;
+
+    let output = evm.call(&read_slot(&chain, eoa)).await.unwrap();
+    assert_eq!(output, Bytes::from(B256::with_last_byte(7).to_vec()));
+    let gas = evm.estimate_gas(&read_slot(&chain, eoa)).await.unwrap();
+    assert!(gas > 21_000, "{gas}");
+
+    // the account is proven for the balance and nonce left to it, the overridden slot never
+    let proofs = proofs.lock().unwrap();
+    assert!(proofs.iter().any(|(address, _)| *address == eoa));
+    assert!(proofs
+        .iter()
+        .all(|(address, slots)| *address != eoa || slots.is_empty()));
+}
+
+#[tokio::test]
+async fn test_state_replaces_storage_and_state_diff_keeps_it() {
+    let chain = ChainBuilder::new(86).length(3).build();
+    let token = chain.token();
+    let (_, proven) = chain
+        .storage(token)
+        .into_iter()
+        .find(|(slot, _)| *slot == B256::ZERO)
+        .unwrap();
+    assert_ne!(proven, U256::ZERO);
+
+    let sparse = StateOverride::from([(token, store_code(None, Some(&[(1, 5)])))]);
+    let (mut evm_sparse, _) = evm(&chain, sparse).await;
+    let output = evm_sparse.call(&read_slot(&chain, token)).await.unwrap();
+    assert_eq!(output, Bytes::from(proven.to_be_bytes_vec()));
+
+    let full = StateOverride::from([(token, store_code(Some(&[(1, 5)]), None))]);
+    let (mut evm_full, proofs) = evm(&chain, full).await;
+    let output = evm_full.call(&read_slot(&chain, token)).await.unwrap();
+    assert_eq!(output, Bytes::from(B256::ZERO.to_vec()));
+    assert!(proofs
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|(address, slots)| *address != token || slots.is_empty()));
+}
+
+#[tokio::test]
+async fn test_balance_override_of_sender_covers_value() {
+    let chain = ChainBuilder::new(87).length(3).build();
+    let sender = Address::repeat_byte(0x99);
+    let value = U256::from(10).pow(U256::from(18));
+    let tx = TransactionRequest::default()
+        .with_from(sender)
+        .with_to(chain.accounts()[1])
+        .with_value(value);
+
+    let (mut unfunded, _) = evm(&chain, StateOverride::default()).await;
+    assert!(unfunded.call(&tx).await.is_err());
+
+    let funded = AccountOverride {
+        balance: Some(value * U256::from(2)),
+        ..Default::default()
+    };
+    let (mut evm, _) = evm(&chain, StateOverride::from([(sender, funded)])).await;
+    evm.call(&tx).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_state_and_state_diff_for_one_account_rejected() {
+    let chain = ChainBuilder::new(88).length(3).build();
+    let token = chain.token();
+    let overrides = StateOverride::from([(token, store_code(Some(&[(0, 1)]), Some(&[(1, 2)])))]);
+    let (mut evm, _) = evm(&chain, overrides).await;
+
+    let err = evm.call(&read_slot(&chain, token)).await.unwrap_err();
+    let EvmError::RpcError(err) = err else {
+        panic!("unexpected error: {err}");
+    };
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::ConflictingStateOverride(address)) if *address == token
+        ),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_access_list_lists_coinbase_once_reached() {
+    let chain = ChainBuilder::new(168).length(3).build();
+    let coinbase = chain.head().header.beneficiary;
+    let reader = Address::repeat_byte(0xcb);
+    let code = AccountOverride {
+        code: Some(Bytes::from_static(&COINBASE_BALANCE_CODE)),
+        ..Default::default()
+    };
+    let overrides = StateOverride::from([(reader, code)]);
+    let (mut evm, _) = evm(&chain, overrides).await;
+
+    // the coinbase is paid the fees of any transaction, which does not list it
+    let transfer = read_slot(&chain, chain.accounts()[1]);
+    let list = evm.create_access_list(&transfer).await.unwrap();
+    assert!(list.access_list.0.is_empty(), "{:?}", list.access_list);
+
+    let list = evm
+        .create_access_list(&read_slot(&chain, reader))
+        .await
+        .unwrap();
+    assert_eq!(list.error, None);
+    assert_eq!(
+        list.access_list.0,
+        vec![AccessListItem {
+            address: coinbase,
+            storage_keys: Vec::new(),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn test_access_list_without_sender_rejected() {
+    let chain = ChainBuilder::new(169).length(3).build();
+    let (mut evm, _) = evm(&chain, StateOverride::default()).await;
+    let tx = TransactionRequest::default().with_to(chain.token());
+
+    let err = evm.create_access_list(&tx).await.unwrap_err();
+    assert!(matches!(err, EvmError::InvalidTransaction(_)), "{err}");
+}
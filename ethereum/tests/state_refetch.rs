@@ -0,0 +1,148 @@
+use std::sync::{Arc, Mutex};
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{hex, Address, Bytes, B256, U256, U64};
+use alloy::rpc::types::{AccessList, AccessListItem, TransactionRequest};
+
+use helios_core::execution::bundle::{AccountOverride, StateOverride};
+use helios_core::execution::errors::EvmError;
+use helios_core::execution::evm::{Evm, EvmConfig};
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::consensus::verified_client_with;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+/// Returns the sum of the words stored at the three slots named by the arguments.
+const SUM_OF_CODE: [u8; 22] = hex!("600435546024355401604435540160005260206000f3");
+
+/// An evm over `chain` whose token sums its storage, so a call reads each slot it names.
+async fn evm(chain: &MockChain, rpc: ChainRpc, config: EvmConfig) -> Evm<Ethereum, ChainRpc> {
+    let (client, _) = verified_client_with::<Ethereum, _>(chain, rpc).await;
+    let client = client.with_evm_config(config);
+
+    // the code replaces the whole account, so only the storage of the token is proven
+    let code = AccountOverride {
+        balance: Some(U256::ZERO),
+        nonce: Some(U64::ZERO),
+        code: Some(Bytes::from_static(&SUM_OF_CODE)),
+        ..Default::default()
+    };
+    Evm::new(
+        Arc::new(client),
+        chain.chain_id(),
+        chain.fork_schedule(),
+        BlockTag::Latest,
+    )
+    .with_state_overrides(StateOverride::from([(chain.token(), code)]))
+}
+
+/// A call summing the balances at `slots` of the token.
+fn sum_of(chain: &MockChain, slots: &[B256]) -> TransactionRequest {
+    let token = chain.token();
+    let from = chain
+        .accounts()
+        .into_iter()
+        .find(|account| *account != token)
+        .unwrap();
+    let input = slots.iter().fold(vec![0; 4], |mut input, slot| {
+        input.extend_from_slice(slot.as_slice());
+        input
+    });
+
+    TransactionRequest::default()
+        .with_from(from)
+        .with_to(token)
+        .with_input(input)
+        .with_gas_limit(1_000_000)
+}
+
+/// The access list of a call reading `slots` of the token.
+fn token_access_list(chain: &MockChain, slots: &[B256]) -> AccessList {
+    AccessList(vec![AccessListItem {
+        address: chain.token(),
+        storage_keys: slots.to_vec(),
+    }])
+}
+
+fn balances(chain: &MockChain) -> (Vec<B256>, U256) {
+    let balances = chain.storage(chain.token());
+    assert!(balances.len() >= 3);
+    let slots = balances.iter().take(3).map(|(slot, _)| *slot).collect();
+    let sum = balances
+        .iter()
+        .take(3)
+        .fold(U256::ZERO, |sum, (_, balance)| sum + *balance);
+    (slots, sum)
+}
+
+async fn proofs_of_call(chain: &MockChain, listed: &[B256], slots: &[B256]) -> (Bytes, usize) {
+    let rpc = ChainRpc::from_chain(chain.clone());
+    rpc.serve_access_list(token_access_list(chain, listed));
+    let mut evm = evm(chain, rpc.clone(), EvmConfig::default()).await;
+
+    let output = evm.call(&sum_of(chain, slots)).await.unwrap();
+    (output, rpc.calls("get_proof"))
+}
+
+#[tokio::test]
+async fn test_slots_missing_from_access_list_fetched_on_demand() {
+    let chain = ChainBuilder::new(121).length(3).build();
+    let (slots, sum) = balances(&chain);
+    let expected = Bytes::from(sum.to_be_bytes::<32>().to_vec());
+
+    let (output, complete) = proofs_of_call(&chain, &slots, &slots).await;
+    assert_eq!(output, expected);
+
+    // the provider left two of the slots out, which are proven together in one round
+    let (output, truncated) = proofs_of_call(&chain, &slots[..1], &slots).await;
+    assert_eq!(output, expected);
+    assert_eq!(truncated, complete + 1);
+}
+
+#[tokio::test]
+async fn test_refetches_bounded() {
+    let chain = ChainBuilder::new(122).length(3).build();
+    let (slots, _) = balances(&chain);
+    let rpc = ChainRpc::from_chain(chain.clone());
+    rpc.serve_access_list(token_access_list(&chain, &slots[..1]));
+    let config = EvmConfig {
+        max_state_refetches: 0,
+    };
+    let mut evm = evm(&chain, rpc, config).await;
+
+    let err = evm.call(&sum_of(&chain, &slots)).await.unwrap_err();
+    let EvmError::MissingState(refetches, missing) = &err else {
+        panic!("expected missing state, got {err}");
+    };
+    assert_eq!(*refetches, 0);
+    // the run goes on past the first missing slot, so both are named
+    let token = chain.token();
+    assert_eq!(
+        missing,
+        &format!("slot {} of {token}, slot {} of {token}", slots[1], slots[2])
+    );
+}
+
+#[tokio::test]
+async fn test_creation_prefetch_skips_missing_recipient() {
+    let chain = ChainBuilder::new(163).length(3).build();
+    let (slots, _) = balances(&chain);
+    let rpc = ChainRpc::from_chain(chain.clone());
+    rpc.serve_access_list(token_access_list(&chain, &slots));
+    let proven = Arc::new(Mutex::new(Vec::new()));
+    let record = proven.clone();
+    rpc.map_proofs(move |proof| {
+        record.lock().unwrap().push(proof.address);
+        proof
+    });
+    let mut evm = evm(&chain, rpc, EvmConfig::default()).await;
+
+    // deploys empty code
+    let tx = sum_of(&chain, &[]).with_deploy_code(hex!("00"));
+    evm.estimate_gas(&tx).await.unwrap();
+
+    let proven = proven.lock().unwrap();
+    assert!(proven.contains(&tx.from.unwrap()));
+    assert!(!proven.contains(&Address::ZERO), "{proven:?}");
+}
@@ -0,0 +1,415 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::primitives::keccak256;
+use alloy::rpc::types::{Block, Filter, Header, Log, Transaction};
+use futures::StreamExt;
+use jsonrpsee::core::client::{Subscription, SubscriptionClientT};
+use jsonrpsee::rpc_params;
+use jsonrpsee::ws_client::WsClientBuilder;
+use tokio::time::timeout;
+
+use helios_core::client::node::Node;
+use helios_core::client::rpc::Rpc;
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::rpc::http_rpc::HttpRpc;
+use helios_core::execution::rpc::ExecutionRpc;
+use helios_core::execution::stream::{HeaderEvent, LagPolicy};
+use helios_core::execution::subscription::{
+    SubscriptionCursor, SubscriptionEvent, SubscriptionItem, SubscriptionTopic,
+};
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::consensus::{execution_config, MockConsensus};
+use helios_core::testing::harness;
+use helios_ethereum::spec::Ethereum;
+
+type Harness = harness::Harness<Ethereum>;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_new_heads_follow_verified_head() {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let chain = ChainBuilder::new(21)
+        .length(8)
+        .genesis_timestamp(now.as_secs())
+        .build();
+
+    // headers only come from the verified blocks, the provider is never reached
+    let (consensus, feed) = MockConsensus::new(chain.chain_id());
+    let rpc = <HttpRpc<Ethereum> as ExecutionRpc<Ethereum>>::new("http://127.0.0.1:1").unwrap();
+    let node = Arc::new(Node::<Ethereum, _>::with_execution_rpc(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+    ));
+    let mut server = Rpc::new(node.clone(), SocketAddr::from(([127, 0, 0, 1], 0)));
+    let addr = server.start().await.unwrap().unwrap();
+
+    feed.advance(chain.blocks()[0].clone()).await.unwrap();
+    let mut heads = node.execution.head_updates();
+    heads.wait_for(Option::is_some).await.unwrap();
+
+    let client = WsClientBuilder::default()
+        .build(format!("ws://{addr}"))
+        .await
+        .unwrap();
+    let err = client
+        .subscribe::<Header, _>("eth_subscribe", rpc_params!["syncing"], "eth_unsubscribe")
+        .await
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("unsupported subscription"),
+        "{err}"
+    );
+
+    let mut sub: Subscription<Header> = client
+        .subscribe("eth_subscribe", rpc_params!["newHeads"], "eth_unsubscribe")
+        .await
+        .unwrap();
+
+    // the subscription starts at whichever head it first sees, so advance until it notifies
+    let mut blocks = chain.blocks()[1..].iter();
+    let first = loop {
+        let block = blocks.next().expect("no head notified");
+        feed.advance(block.clone()).await.unwrap();
+        if let Ok(header) = timeout(Duration::from_millis(500), sub.next()).await {
+            break header.unwrap().unwrap();
+        }
+    };
+    assert_eq!(first, chain.block(first.number).unwrap().header);
+
+    // from then on every verified head is delivered in order
+    for block in blocks {
+        feed.advance(block.clone()).await.unwrap();
+    }
+    for number in first.number + 1..=chain.head().header.number {
+        let header = timeout(Duration::from_secs(5), sub.next())
+            .await
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        assert_eq!(header, chain.block(number).unwrap().header);
+    }
+
+    sub.unsubscribe().await.unwrap();
+}
+
+fn cursor(block: &Block<Transaction>) -> SubscriptionCursor {
+    SubscriptionCursor {
+        block_number: block.header.number,
+        block_hash: block.header.hash,
+        log_index: None,
+    }
+}
+
+#[tokio::test]
+async fn test_subscription_resumes_within_window() {
+    let chain = ChainBuilder::new(11).length(8).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let last_seen = cursor(&chain.blocks()[3]);
+    let batch = client
+        .subscription_events(&SubscriptionTopic::NewHeads, Some(last_seen), 64)
+        .await
+        .unwrap();
+
+    let delivered = batch
+        .events
+        .iter()
+        .map(|event| match event {
+            SubscriptionEvent::NewHead { cursor, result } => {
+                assert_eq!(cursor.block_hash, result.hash);
+                result.number
+            }
+            event => panic!("unexpected event: {event:?}"),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(delivered, [5, 6, 7, 8]);
+    assert_eq!(batch.cursor, Some(cursor(chain.head())));
+}
+
+#[tokio::test]
+async fn test_subscription_resumes_mid_block_logs() {
+    let chain = ChainBuilder::new(12).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    let logs = chain.logs();
+    let last_seen = SubscriptionCursor {
+        block_number: logs[0].block_number.unwrap(),
+        block_hash: logs[0].block_hash.unwrap(),
+        log_index: logs[0].log_index,
+    };
+
+    let topic = SubscriptionTopic::Logs(Filter::new().address(chain.token()));
+    let batch = client
+        .subscription_events(&topic, Some(last_seen), 64)
+        .await
+        .unwrap();
+
+    let delivered = batch
+        .events
+        .into_iter()
+        .map(|event| match event {
+            SubscriptionEvent::Log { cursor, result } => {
+                assert_eq!(cursor.log_index, result.log_index);
+                result
+            }
+            event => panic!("unexpected event: {event:?}"),
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(delivered, logs[1..]);
+}
+
+#[tokio::test]
+async fn test_subscription_reports_gap_beyond_window() {
+    let chain = ChainBuilder::new(13).start_at(20).length(8).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let client = &harness.client;
+
+    // the cursor predates every retained block
+    let last_seen = SubscriptionCursor {
+        block_number: 5,
+        block_hash: keccak256("unknown"),
+        log_index: None,
+    };
+    let batch = client
+        .subscription_events(&SubscriptionTopic::NewHeads, Some(last_seen), 64)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        batch.events[0],
+        SubscriptionEvent::Gap {
+            from_block: 5,
+            to_block: 19,
+        }
+    );
+    assert_eq!(batch.events.len(), 9);
+    // plain subscriptions end on a gap instead of skipping it
+    assert!(matches!(
+        batch.events[0].clone().into_item(),
+        Err(ExecutionError::MissedSubscriptionEvents(5, 19))
+    ));
+
+    // a retained cursor further back than the backfill budget
+    let last_seen = cursor(&chain.blocks()[0]);
+    let batch = client
+        .subscription_events(&SubscriptionTopic::NewHeads, Some(last_seen), 3)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        batch.events[0],
+        SubscriptionEvent::Gap {
+            from_block: 21,
+            to_block: 24,
+        }
+    );
+    assert_eq!(batch.events.len(), 4);
+    assert_eq!(batch.cursor, Some(cursor(chain.head())));
+}
+
+#[tokio::test]
+async fn test_subscription_resumes_across_reorg() {
+    let chain = ChainBuilder::new(14).length(6).build();
+    let fork = chain.reorg(2, 99);
+    let harness = Harness::with_blocks(&chain).await;
+    let (client, state) = (&harness.client, &harness.state);
+
+    let last_seen = cursor(chain.head());
+
+    harness.rpc.set_chain(fork.clone());
+    for block in &fork.blocks()[4..] {
+        state.push_block(block.clone()).await;
+    }
+
+    let batch = client
+        .subscription_events(&SubscriptionTopic::NewHeads, Some(last_seen), 64)
+        .await
+        .unwrap();
+
+    // every height from the fork point is delivered again from the new branch
+    let delivered = batch
+        .events
+        .iter()
+        .map(|event| match event {
+            SubscriptionEvent::NewHead { result, .. } => result.hash,
+            event => panic!("unexpected event: {event:?}"),
+        })
+        .collect::<Vec<_>>();
+    let replaced = fork.blocks()[4..]
+        .iter()
+        .map(|block| block.header.hash)
+        .collect::<Vec<_>>();
+    assert_eq!(delivered, replaced);
+    assert_eq!(batch.cursor, Some(cursor(fork.head())));
+}
+
+#[tokio::test]
+async fn test_subscription_removes_orphaned_logs() {
+    let chain = ChainBuilder::new(140).length(6).build();
+    let fork = chain.reorg(2, 141);
+    let harness = Harness::with_blocks(&chain).await;
+    let (client, state) = (&harness.client, &harness.state);
+
+    // the logs of the blocks the reorg replaces are delivered first
+    let topic = SubscriptionTopic::Logs(Filter::new().address(chain.token()));
+    let batch = client
+        .subscription_events(&topic, Some(cursor(&chain.blocks()[3])), 64)
+        .await
+        .unwrap();
+    let last_seen = batch.cursor.unwrap();
+    let orphaned = chain
+        .logs()
+        .into_iter()
+        .filter(|log| log.block_number > Some(4))
+        .collect::<Vec<_>>();
+    assert!(!orphaned.is_empty());
+
+    harness.rpc.set_chain(fork.clone());
+    for block in &fork.blocks()[4..] {
+        state.push_block(block.clone()).await;
+    }
+
+    let batch = client
+        .subscription_events(&topic, Some(last_seen), 64)
+        .await
+        .unwrap();
+    let delivered = batch
+        .events
+        .into_iter()
+        .map(|event| match event {
+            SubscriptionEvent::Log { cursor, result } => {
+                assert_eq!(cursor.block_hash, result.block_hash.unwrap());
+                result
+            }
+            event => panic!("unexpected event: {event:?}"),
+        })
+        .collect::<Vec<_>>();
+
+    let removed = orphaned
+        .into_iter()
+        .map(|log| Log {
+            removed: true,
+            ..log
+        })
+        .collect::<Vec<_>>();
+    let added = fork
+        .logs()
+        .into_iter()
+        .filter(|log| log.block_number > Some(4))
+        .collect::<Vec<_>>();
+    assert_eq!(delivered[..removed.len()], removed);
+    assert_eq!(delivered[removed.len()..], added);
+
+    // plain subscriptions deliver the removed logs as they are
+    let batch = client
+        .subscription_events(&topic, Some(last_seen), 64)
+        .await
+        .unwrap();
+    match batch.events[0].clone().into_item() {
+        Ok(SubscriptionItem::Log(log)) => assert_eq!(log, removed[0]),
+        item => panic!("unexpected item: {item:?}"),
+    }
+}
+
+fn header_hash(event: HeaderEvent<alloy::rpc::types::Header>) -> alloy::primitives::B256 {
+    match event {
+        HeaderEvent::Header(header) => header.hash,
+        event => panic!("expected a header, got {event:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_block_stream_follows_reorg() {
+    let chain = ChainBuilder::new(37).length(6).build();
+    let fork = chain.reorg(2, 38);
+    let harness = Harness::new(&chain);
+    let mut heads = std::pin::pin!(harness.client.block_stream(LagPolicy::Skip));
+
+    for block in chain.blocks() {
+        harness.state.push_block(block.clone()).await;
+    }
+    harness.rpc.set_chain(fork.clone());
+    harness.state.push_block(fork.head().clone()).await;
+
+    for block in chain.blocks() {
+        assert_eq!(header_hash(heads.next().await.unwrap()), block.header.hash);
+    }
+
+    // the new branch replaces the head at the same height
+    let reorged = header_hash(heads.next().await.unwrap());
+    assert_eq!(reorged, fork.head().header.hash);
+    assert_eq!(fork.head().header.number, chain.head().header.number);
+}
+
+#[tokio::test]
+async fn test_block_stream_lag_policies() {
+    let chain = ChainBuilder::new(39).length(70).build();
+    let harness = Harness::new(&chain);
+    let mut skipping = std::pin::pin!(harness.client.block_stream(LagPolicy::Skip));
+    let mut failing = std::pin::pin!(harness.client.block_stream(LagPolicy::Fail));
+
+    for block in chain.blocks() {
+        harness.state.push_block(block.clone()).await;
+    }
+
+    // the channel holds the 64 most recent heads
+    assert_eq!(
+        skipping.next().await.unwrap(),
+        HeaderEvent::Gap { missed: 6 }
+    );
+    assert_eq!(
+        header_hash(skipping.next().await.unwrap()),
+        chain.blocks()[6].header.hash
+    );
+
+    assert_eq!(
+        failing.next().await.unwrap(),
+        HeaderEvent::Lagged { missed: 6 }
+    );
+    assert!(failing.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_streams_end_on_shutdown() {
+    let chain = ChainBuilder::new(40).length(3).build();
+    let harness = Harness::new(&chain);
+    let heads = harness.client.block_stream(LagPolicy::Skip);
+    let finalized = harness.client.finality_stream(LagPolicy::Skip);
+
+    fn assert_send<T: Send>(_: &T) {}
+    assert_send(&heads);
+    let (mut heads, mut finalized) = (std::pin::pin!(heads), std::pin::pin!(finalized));
+
+    for block in chain.blocks() {
+        harness.block_send.send(block.clone()).await.unwrap();
+    }
+    let finalized_block = chain.blocks()[0].clone();
+    harness
+        .finalized_block_send
+        .send(Some(finalized_block.clone()))
+        .unwrap();
+    assert_eq!(
+        header_hash(finalized.next().await.unwrap()),
+        finalized_block.header.hash
+    );
+
+    drop(harness);
+
+    // heads verified before the shutdown are still delivered
+    let hashes = heads.as_mut().map(header_hash).collect::<Vec<_>>().await;
+    let expected = chain
+        .blocks()
+        .iter()
+        .map(|block| block.header.hash)
+        .collect::<Vec<_>>();
+    assert_eq!(hashes, expected);
+    assert!(heads.next().await.is_none());
+    assert!(finalized.next().await.is_none());
+}
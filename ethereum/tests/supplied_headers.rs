@@ -0,0 +1,164 @@
+use std::sync::Arc;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{hex, keccak256, Address, Bytes, B256, U256};
+use alloy::rpc::types::{Header, TransactionRequest};
+
+use helios_core::execution::bundle::AccountOverride;
+use helios_core::execution::errors::{EvmError, ExecutionError};
+use helios_core::execution::evm::Evm;
+use helios_core::execution::types::StateAnchor;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::harness;
+use helios_core::types::{AnchorTrust, BlockTag};
+use helios_ethereum::spec::Ethereum;
+
+type Harness = harness::Harness<Ethereum>;
+
+#[tokio::test]
+async fn test_account_at_supplied_header() {
+    let chain = ChainBuilder::new(15).length(8).build();
+    let harness = Harness::new(&chain);
+    harness.state.push_block(chain.head().clone()).await;
+    let client = &harness.client;
+
+    // the first block is not retained, so only the supplied header can anchor it
+    let header = chain.blocks()[0].header.clone();
+    let tag = BlockTag::Number(header.number);
+    let err = client
+        .get_account(chain.token(), None, tag)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::BlockNotFound(_))
+    ));
+
+    let token = chain.token();
+    let slots = chain
+        .storage(token)
+        .into_iter()
+        .map(|(slot, _)| slot)
+        .collect::<Vec<_>>();
+    let account = client
+        .get_account_at(token, Some(&slots), &StateAnchor::Header(header))
+        .await
+        .unwrap();
+    assert_eq!(account.code, chain.code(token).to_vec());
+    for (slot, value) in chain.storage(token) {
+        assert_eq!(account.slots.get(&slot), Some(&value));
+    }
+}
+
+#[tokio::test]
+async fn test_supplied_header_must_match_its_hash() {
+    let chain = ChainBuilder::new(16).length(4).build();
+    let harness = Harness::new(&chain);
+
+    let mut header = chain.blocks()[0].header.clone();
+    header.inner.state_root = keccak256(header.inner.state_root);
+
+    let err = harness
+        .client
+        .get_account_at(chain.token(), None, &StateAnchor::Header(header.clone()))
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::InvalidHeaderHash(hash)) if *hash == header.hash
+    ));
+}
+
+/// Returns the hash of the block numbered by the first word of the calldata.
+const BLOCKHASH_CODE: [u8; 12] = hex!("6000354060005260206000f3");
+
+/// Replays a call reading `BLOCKHASH(number)` against a checked supplied header, as
+/// `Node::call_with_header` does.
+async fn call_with_header(
+    harness: &Harness,
+    chain: &MockChain,
+    header: Header,
+    number: u64,
+) -> (Result<Bytes, EvmError>, AnchorTrust) {
+    let reader = Address::repeat_byte(0xbb);
+    let anchor = harness.client.verify_supplied_header(header).await.unwrap();
+    let (_, trust) = anchor.verified().unwrap();
+
+    let code = AccountOverride {
+        code: Some(Bytes::from_static(&BLOCKHASH_CODE)),
+        ..Default::default()
+    };
+    let tx = TransactionRequest::default()
+        .with_from(chain.accounts()[0])
+        .with_to(reader)
+        .with_input(U256::from(number).to_be_bytes_vec());
+    let mut evm = Evm::with_anchor(
+        Arc::new(harness.client.clone()),
+        chain.chain_id(),
+        chain.fork_schedule(),
+        anchor,
+    )
+    .with_state_overrides([(reader, code)].into());
+    (evm.call(&tx).await, trust)
+}
+
+#[tokio::test]
+async fn test_call_with_header_of_verified_block() {
+    let chain = ChainBuilder::new(185).length(6).build();
+    let harness = Harness::with_blocks(&chain).await;
+    let header = chain.block(4).unwrap().header.clone();
+
+    // the header is that of a block held in state, so blocks before its parent resolve too
+    let (output, trust) = call_with_header(&harness, &chain, header, 2).await;
+    assert_eq!(trust, AnchorTrust::Consensus);
+    assert_eq!(
+        B256::from_slice(&output.unwrap()),
+        chain.block(2).unwrap().header.hash
+    );
+}
+
+#[tokio::test]
+async fn test_call_with_header_only_vouches_for_parent() {
+    let chain = ChainBuilder::new(186).length(6).build();
+    let harness = Harness::new(&chain);
+    let header = chain.block(4).unwrap().header.clone();
+
+    let (output, trust) = call_with_header(&harness, &chain, header.clone(), 3).await;
+    assert_eq!(trust, AnchorTrust::UserSuppliedHeader);
+    assert_eq!(B256::from_slice(&output.unwrap()), header.parent_hash);
+
+    let (output, _) = call_with_header(&harness, &chain, header, 2).await;
+    let err = output.unwrap_err();
+    assert!(
+        matches!(
+            &err,
+            EvmError::RpcError(report) if matches!(
+                report.downcast_ref::<ExecutionError>(),
+                Some(ExecutionError::UnanchoredBlockHash(2))
+            )
+        ),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_call_with_doctored_header_rejected() {
+    let chain = ChainBuilder::new(187).length(4).build();
+    let harness = Harness::with_blocks(&chain).await;
+
+    let mut header = chain.block(3).unwrap().header.clone();
+    header.inner.state_root = keccak256(header.inner.state_root);
+
+    let err = harness
+        .client
+        .verify_supplied_header(header.clone())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::InvalidHeaderHash(hash)) if *hash == header.hash
+        ),
+        "{err}"
+    );
+}
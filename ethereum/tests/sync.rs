@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use alloy::primitives::b256;
 use helios_consensus_core::consensus_spec::MainnetConsensusSpec;
+use helios_core::config::ConfigDuration;
 use helios_ethereum::config::{networks, Config};
 use helios_ethereum::{consensus::ConsensusClient, database::ConfigDB, rpc::mock_rpc::MockRpc};
 
@@ -12,7 +13,7 @@ async fn setup() -> ConsensusClient<MainnetConsensusSpec, MockRpc, ConfigDB> {
         execution_rpc: String::new(),
         chain: base_config.chain,
         forks: base_config.forks,
-        max_checkpoint_age: 123123123,
+        max_checkpoint_age: ConfigDuration::from_secs(123123123),
         checkpoint: Some(b256!(
             "5afc212a7924789b2bc86acad3ab3a6ffb1f6e97253ea50bee7f4f51422c9275"
         )),
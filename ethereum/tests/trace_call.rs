@@ -0,0 +1,326 @@
+use std::sync::Arc;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{hex, Address, Bytes, U256, U64};
+use alloy::rpc::types::TransactionRequest;
+use alloy::sol_types::{Revert, SolError};
+
+use helios_core::execution::bundle::{AccountOverride, StateOverride};
+use helios_core::execution::constants::MAX_STRUCT_LOG_BYTES;
+use helios_core::execution::errors::EvmError;
+use helios_core::execution::evm::Evm;
+use helios_core::execution::trace::{CallKind, TraceOptions, TraceResult};
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::code::revert_code;
+use helios_core::testing::consensus::verified_client;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_ethereum::spec::Ethereum;
+
+const OUTER: Address = Address::repeat_byte(0xa1);
+const INNER: Address = Address::repeat_byte(0xa2);
+
+/// Returns 42.
+const ANSWER_CODE: [u8; 10] = hex!("602a60005260206000f3");
+
+fn revert(reason: &str) -> Vec<u8> {
+    Revert {
+        reason: reason.to_string(),
+    }
+    .abi_encode()
+}
+
+/// Calls `target` with no data through `opcode`, ignoring the result, then stops.
+fn caller_code(opcode: u8, target: Address) -> Bytes {
+    let mut code = hex!("6000600060006000").to_vec();
+    if opcode == 0xf1 {
+        // the value of the call
+        code.extend(hex!("6000"));
+    }
+    code.push(0x73);
+    code.extend(target);
+    code.extend([0x5a, opcode, 0x50, 0x00]);
+    code.into()
+}
+
+async fn evm(chain: &MockChain, code: Vec<(Address, Bytes)>) -> Evm<Ethereum, ChainRpc> {
+    let (client, _, _) = verified_client::<Ethereum>(chain).await;
+
+    let overrides = code.into_iter().map(|(address, code)| {
+        let account = AccountOverride {
+            code: Some(code),
+            ..Default::default()
+        };
+        (address, account)
+    });
+    Evm::new(
+        Arc::new(client),
+        chain.chain_id(),
+        chain.fork_schedule(),
+        BlockTag::Latest,
+    )
+    .with_state_overrides(StateOverride::from_iter(overrides))
+}
+
+fn call_tracer() -> TraceOptions {
+    TraceOptions {
+        tracer: Some("callTracer".to_string()),
+        ..Default::default()
+    }
+}
+
+/// The externally owned accounts of the chain.
+fn eoas(chain: &MockChain) -> Vec<Address> {
+    let token = chain.token();
+    chain
+        .accounts()
+        .into_iter()
+        .filter(|account| *account != token)
+        .collect()
+}
+
+fn call(chain: &MockChain, to: Address) -> TransactionRequest {
+    TransactionRequest::default()
+        .with_from(eoas(chain)[0])
+        .with_to(to)
+        .with_gas_limit(1_000_000)
+}
+
+#[tokio::test]
+async fn test_call_tracer_reports_inner_revert_reason() {
+    let chain = ChainBuilder::new(102).length(3).build();
+    let code = vec![
+        (OUTER, caller_code(0xf1, INNER)),
+        (INNER, revert_code(&revert("inner failed"))),
+    ];
+    let mut evm = evm(&chain, code).await;
+
+    let trace = evm
+        .trace_call(&call(&chain, OUTER), &call_tracer())
+        .await
+        .unwrap();
+    let TraceResult::Call(root) = trace else {
+        panic!("unexpected trace: {trace:?}");
+    };
+    assert_eq!(root.kind, CallKind::Call);
+    assert_eq!(root.from, eoas(&chain)[0]);
+    assert_eq!(root.to, Some(OUTER));
+    assert_eq!(root.gas, U64::from(1_000_000));
+    // the outer call ignores the failure of the inner one
+    assert_eq!(root.error, None);
+
+    let [inner] = root.calls.as_slice() else {
+        panic!("unexpected calls: {:?}", root.calls);
+    };
+    assert_eq!(inner.kind, CallKind::Call);
+    assert_eq!(inner.from, OUTER);
+    assert_eq!(inner.to, Some(INNER));
+    assert_eq!(inner.value, Some(U256::ZERO));
+    assert_eq!(inner.error.as_deref(), Some("execution reverted"));
+    assert_eq!(inner.revert_reason.as_deref(), Some("inner failed"));
+    assert_eq!(
+        inner.output.as_deref(),
+        Some(revert("inner failed").as_slice())
+    );
+}
+
+#[tokio::test]
+async fn test_call_tracer_reports_delegatecall_frame() {
+    let chain = ChainBuilder::new(103).length(3).build();
+    let code = vec![
+        (OUTER, caller_code(0xf4, INNER)),
+        (INNER, Bytes::from_static(&ANSWER_CODE)),
+    ];
+    let mut evm = evm(&chain, code).await;
+
+    let trace = evm
+        .trace_call(&call(&chain, OUTER), &call_tracer())
+        .await
+        .unwrap();
+    let TraceResult::Call(root) = trace else {
+        panic!("unexpected trace: {trace:?}");
+    };
+    let [inner] = root.calls.as_slice() else {
+        panic!("unexpected calls: {:?}", root.calls);
+    };
+    assert_eq!(inner.kind, CallKind::DelegateCall);
+    assert_eq!(inner.from, OUTER);
+    assert_eq!(inner.to, Some(INNER));
+    assert_eq!(inner.value, None);
+    assert_eq!(inner.error, None);
+    assert_eq!(
+        inner.output,
+        Some(U256::from(42).to_be_bytes::<32>().to_vec().into())
+    );
+
+    let json = serde_json::to_value(inner).unwrap();
+    assert_eq!(json["type"], "DELEGATECALL");
+    assert!(json.get("value").is_none());
+
+    let only_top = TraceOptions {
+        tracer_config: serde_json::from_str(r#"{"onlyTopCall":true}"#).unwrap(),
+        ..call_tracer()
+    };
+    let TraceResult::Call(root) = evm
+        .trace_call(&call(&chain, OUTER), &only_top)
+        .await
+        .unwrap()
+    else {
+        panic!("unexpected trace");
+    };
+    assert!(root.calls.is_empty());
+}
+
+#[tokio::test]
+async fn test_call_tracer_reports_value_transfer() {
+    let chain = ChainBuilder::new(104).length(3).accounts(2).build();
+    let mut evm = evm(&chain, Vec::new()).await;
+    let [from, to] = eoas(&chain)[..2] else {
+        unreachable!();
+    };
+    let tx = TransactionRequest::default()
+        .with_from(from)
+        .with_to(to)
+        .with_value(U256::from(1_000))
+        .with_gas_limit(21_000);
+
+    let trace = evm.trace_call(&tx, &call_tracer()).await.unwrap();
+    let TraceResult::Call(root) = trace else {
+        panic!("unexpected trace: {trace:?}");
+    };
+    assert_eq!(root.kind, CallKind::Call);
+    assert_eq!((root.from, root.to), (from, Some(to)));
+    assert_eq!(root.value, Some(U256::from(1_000)));
+    assert_eq!(root.gas_used, U64::from(21_000));
+    assert_eq!(root.output, None);
+    assert!(root.calls.is_empty());
+}
+
+#[tokio::test]
+async fn test_struct_logger_records_steps() {
+    let chain = ChainBuilder::new(105).length(3).build();
+    let code = vec![(INNER, Bytes::from_static(&ANSWER_CODE))];
+    let mut evm = evm(&chain, code).await;
+
+    let trace = evm
+        .trace_call(&call(&chain, INNER), &TraceOptions::default())
+        .await
+        .unwrap();
+    let TraceResult::StructLogs(trace) = trace else {
+        panic!("unexpected trace: {trace:?}");
+    };
+    assert!(!trace.failed);
+    assert_eq!(
+        trace.return_value,
+        hex::encode(U256::from(42).to_be_bytes::<32>())
+    );
+    let ops = trace
+        .struct_logs
+        .iter()
+        .map(|log| log.op.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ops,
+        ["PUSH1", "PUSH1", "MSTORE", "PUSH1", "PUSH1", "RETURN"]
+    );
+    assert_eq!(trace.struct_logs[0].gas_cost, 3);
+    assert_eq!(
+        trace.struct_logs[2].stack,
+        Some(vec![U256::from(42), U256::ZERO])
+    );
+
+    let limited = TraceOptions {
+        limit: Some(2),
+        disable_stack: true,
+        ..Default::default()
+    };
+    let TraceResult::StructLogs(trace) = evm
+        .trace_call(&call(&chain, INNER), &limited)
+        .await
+        .unwrap()
+    else {
+        panic!("unexpected trace");
+    };
+    assert_eq!(trace.struct_logs.len(), 2);
+    assert_eq!(trace.struct_logs[0].stack, None);
+
+    let unknown = TraceOptions {
+        tracer: Some("prestateTracer".to_string()),
+        ..Default::default()
+    };
+    let err = evm
+        .trace_call(&call(&chain, INNER), &unknown)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EvmError::UnsupportedTracer(_)), "{err}");
+}
+
+#[tokio::test]
+async fn test_struct_logger_caps_log_size() {
+    let chain = ChainBuilder::new(165).length(3).build();
+    // pushes one more stack item every pass until the stack overflows
+    let code = vec![(INNER, Bytes::from_static(&hex!("5b34600056")))];
+    let mut evm = evm(&chain, code).await;
+
+    let err = evm
+        .trace_call(&call(&chain, INNER), &TraceOptions::default())
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, EvmError::TraceTooLarge(MAX_STRUCT_LOG_BYTES)),
+        "{err}"
+    );
+
+    // the same steps fit once the stacks are left out
+    let without_stack = TraceOptions {
+        disable_stack: true,
+        ..Default::default()
+    };
+    let TraceResult::StructLogs(trace) = evm
+        .trace_call(&call(&chain, INNER), &without_stack)
+        .await
+        .unwrap()
+    else {
+        panic!("unexpected trace");
+    };
+    assert!(trace.failed);
+    assert!(trace.struct_logs.len() > 3 * 1024);
+}
+
+#[tokio::test]
+async fn test_trace_overrides_merged_over_call_overrides() {
+    let chain = ChainBuilder::new(164).length(3).build();
+    let code = vec![
+        (OUTER, caller_code(0xf1, INNER)),
+        (INNER, revert_code(&revert("inner failed"))),
+    ];
+    let mut evm = evm(&chain, code).await;
+
+    // replaces the code of the inner account only, the outer one keeps its override
+    let options = TraceOptions {
+        state_overrides: Some(StateOverride::from_iter([(
+            INNER,
+            AccountOverride {
+                code: Some(Bytes::from_static(&ANSWER_CODE)),
+                ..Default::default()
+            },
+        )])),
+        ..call_tracer()
+    };
+    let trace = evm
+        .trace_call(&call(&chain, OUTER), &options)
+        .await
+        .unwrap();
+    let TraceResult::Call(root) = trace else {
+        panic!("unexpected trace: {trace:?}");
+    };
+    let [inner] = root.calls.as_slice() else {
+        panic!("unexpected calls: {:?}", root.calls);
+    };
+    assert_eq!(inner.to, Some(INNER));
+    assert_eq!(inner.error, None);
+    assert_eq!(
+        inner.output.as_deref(),
+        Some(U256::from(42).to_be_bytes::<32>().as_slice())
+    );
+}
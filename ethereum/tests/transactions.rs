@@ -0,0 +1,348 @@
+use std::time::Duration;
+
+use alloy::consensus::{
+    SignableTransaction, Signed, TxEip1559, TxEip2930, TxEip4844, TxEip4844Variant, TxEnvelope,
+    TxLegacy,
+};
+use alloy::eips::eip2718::Encodable2718;
+use alloy::primitives::{b256, keccak256, Address, PrimitiveSignature, TxKind, B256, U256};
+use alloy::signers::local::PrivateKeySigner;
+use alloy::signers::SignerSync;
+use serde_json::json;
+
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::preflight::RawTransaction;
+use helios_core::execution::rpc::mock_rpc::{MockRpc, Outcome};
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::network_spec::NetworkSpec;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::rpc::ChainRpc;
+use helios_ethereum::spec::Ethereum;
+
+const TX_HASH: B256 = b256!("5c504ed432cb51138bcf09aa5e8a410dd4a1e204ef84bfed1be16dfba1b22060");
+
+fn rejecting(message: &str) -> MockRpc {
+    let outcome = Outcome::Err(message.to_string());
+    MockRpc::builder()
+        .script("send_raw_transaction", [outcome])
+        .build()
+}
+
+fn broadcasting(rpc: MockRpc, broadcast: Vec<MockRpc>) -> ExecutionClient<Ethereum, MockRpc> {
+    let state = State::unsynced(64, rpc.clone());
+    let chain = ChainBuilder::new(80).build();
+    ExecutionClient::with_rpc(rpc, state, chain.fork_schedule()).with_broadcast_rpcs(broadcast)
+}
+
+#[tokio::test]
+async fn test_broadcast_succeeds_if_any_endpoint_accepts() {
+    let accepting = MockRpc::builder()
+        .script("send_raw_transaction", [Outcome::Ok(json!(TX_HASH))])
+        .build();
+    let rpc = rejecting("transaction underpriced");
+    let others = vec![rejecting("rate limited"), accepting.clone()];
+    let client = broadcasting(rpc.clone(), others.clone());
+
+    let hash = client.send_raw_transaction(&[0x02, 0x01]).await.unwrap();
+
+    // the hash of the encoding, not the one the endpoint reports
+    assert_eq!(hash, keccak256([0x02, 0x01]));
+    for endpoint in [&rpc, &others[0], &accepting] {
+        assert_eq!(endpoint.calls_to("send_raw_transaction").len(), 1);
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_broadcast_returns_on_first_acceptance() {
+    let accepting = MockRpc::builder()
+        .script("send_raw_transaction", [Outcome::Ok(json!(TX_HASH))])
+        .build();
+    let slow = MockRpc::builder()
+        .script(
+            "send_raw_transaction",
+            [
+                Outcome::Delay(Duration::from_secs(60)),
+                Outcome::Ok(json!(TX_HASH)),
+            ],
+        )
+        .build();
+    let client = broadcasting(accepting, vec![slow.clone()]);
+
+    let start = tokio::time::Instant::now();
+    client.send_raw_transaction(&[0x02, 0x01]).await.unwrap();
+    assert!(start.elapsed() < Duration::from_secs(60));
+
+    // the slow endpoint still gets its copy
+    assert_eq!(slow.calls_to("send_raw_transaction").len(), 1);
+}
+
+#[tokio::test]
+async fn test_broadcast_failure_lists_every_endpoint() {
+    let client = broadcasting(
+        rejecting("transaction underpriced"),
+        vec![rejecting("rate limited")],
+    );
+
+    let err = client
+        .send_raw_transaction(&[0x02, 0x01])
+        .await
+        .unwrap_err();
+
+    let Some(ExecutionError::BroadcastRejected(errors)) = err.downcast_ref::<ExecutionError>()
+    else {
+        panic!("unexpected error: {err}");
+    };
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("underpriced"), "{}", errors[0]);
+    assert!(errors[1].contains("rate limited"), "{}", errors[1]);
+}
+
+/// A client holding the blocks of `chain`, which the provider serves.
+async fn client(
+    chain: &MockChain,
+) -> (
+    ExecutionClient<Ethereum, ChainRpc>,
+    State<Ethereum, ChainRpc>,
+    ChainRpc,
+) {
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let state = State::unsynced(64, rpc.clone());
+    for block in chain.blocks() {
+        state.push_block(block.clone()).await;
+    }
+    let client = ExecutionClient::with_rpc(rpc.clone(), state.clone(), chain.fork_schedule());
+    (client, state, rpc)
+}
+
+fn first_tx(chain: &MockChain, number: u64) -> B256 {
+    chain
+        .block(number)
+        .unwrap()
+        .transactions
+        .hashes()
+        .next()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_wait_returns_receipt_once_confirmed() {
+    let chain = ChainBuilder::new(81).length(4).build();
+    let mut included = chain.clone();
+    included.extend(2);
+    let tx = first_tx(&included, 5);
+    let (client, state, rpc) = client(&chain).await;
+
+    let wait = tokio::spawn(async move {
+        client
+            .wait_for_transaction_receipt(tx, 2, Duration::from_secs(10))
+            .await
+    });
+
+    // pending, then included with a single confirmation
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!wait.is_finished());
+    rpc.set_chain(included.clone());
+    state.push_block(included.block(5).unwrap().clone()).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!wait.is_finished());
+
+    state.push_block(included.block(6).unwrap().clone()).await;
+    let receipt = wait.await.unwrap().unwrap();
+    assert_eq!(receipt.transaction_hash, tx);
+    assert_eq!(
+        receipt.block_hash,
+        Some(included.block(5).unwrap().header.hash)
+    );
+}
+
+#[tokio::test]
+async fn test_wait_resets_confirmations_on_reorg() {
+    let chain = ChainBuilder::new(82).length(6).build();
+    let tx = first_tx(&chain, 5);
+    let (client, state, rpc) = client(&chain).await;
+
+    let wait = tokio::spawn(async move {
+        client
+            .wait_for_transaction_receipt(tx, 3, Duration::from_millis(500))
+            .await
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // the branch replacing the including block reaches 3 blocks past it without the tx
+    let mut branch = chain.reorg(2, 83);
+    branch.extend(1);
+    rpc.set_chain(branch.clone());
+    state.push_block(branch.head().clone()).await;
+
+    let err = wait.await.unwrap().unwrap_err();
+    assert!(
+        matches!(
+            err.downcast_ref::<ExecutionError>(),
+            Some(ExecutionError::ReceiptTimeout(hash, 3)) if *hash == tx
+        ),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_wait_counts_confirmations_from_block_including_again() {
+    let chain = ChainBuilder::new(84).length(6).build();
+    let tx = first_tx(&chain, 5);
+    let (client, state, rpc) = client(&chain).await;
+
+    let wait = tokio::spawn(async move {
+        client
+            .wait_for_transaction_receipt(tx, 3, Duration::from_secs(10))
+            .await
+    });
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!wait.is_finished());
+
+    // a branch including the tx again in its own block 5 brings a third head, but the
+    // count starts over from the new including block
+    let mut branch = chain.reorg_keeping_txs(2, 85);
+    rpc.set_chain(branch.clone());
+    state.push_block(branch.head().clone()).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!wait.is_finished());
+
+    branch.extend(1);
+    rpc.set_chain(branch.clone());
+    state.push_block(branch.head().clone()).await;
+    let receipt = wait.await.unwrap().unwrap();
+    assert_eq!(receipt.transaction_hash, tx);
+    assert_eq!(
+        receipt.block_hash,
+        Some(branch.block(5).unwrap().header.hash)
+    );
+    assert_ne!(
+        receipt.block_hash,
+        Some(chain.block(5).unwrap().header.hash)
+    );
+}
+
+const GWEI: u128 = 1_000_000_000;
+
+fn signer() -> PrivateKeySigner {
+    PrivateKeySigner::from_bytes(&B256::repeat_byte(0x11)).unwrap()
+}
+
+fn sign<T>(tx: T) -> Vec<u8>
+where
+    T: SignableTransaction<PrimitiveSignature>,
+    TxEnvelope: From<Signed<T>>,
+{
+    let signature = signer().sign_hash_sync(&tx.signature_hash()).unwrap();
+    TxEnvelope::from(tx.into_signed(signature)).encoded_2718()
+}
+
+fn transfer(chain_id: u64, nonce: u64, value: U256) -> Vec<u8> {
+    sign(TxEip1559 {
+        chain_id,
+        nonce,
+        gas_limit: 21_000,
+        max_fee_per_gas: 2 * GWEI,
+        max_priority_fee_per_gas: GWEI,
+        to: TxKind::Call(Address::repeat_byte(0x22)),
+        value,
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_decode_each_transaction_type() {
+    let to = Address::repeat_byte(0x22);
+    let value = U256::from(1000);
+    let gas = U256::from(21_000 * 2 * GWEI);
+
+    let legacy = sign(TxLegacy {
+        chain_id: None,
+        nonce: 1,
+        gas_price: 2 * GWEI,
+        gas_limit: 21_000,
+        to: TxKind::Call(to),
+        value,
+        ..Default::default()
+    });
+    let eip2930 = sign(TxEip2930 {
+        chain_id: 1,
+        nonce: 2,
+        gas_price: 2 * GWEI,
+        gas_limit: 21_000,
+        to: TxKind::Call(to),
+        value,
+        ..Default::default()
+    });
+    let eip1559 = transfer(1, 3, value);
+    let eip4844 = sign(TxEip4844Variant::TxEip4844(TxEip4844 {
+        chain_id: 1,
+        nonce: 4,
+        gas_limit: 21_000,
+        max_fee_per_gas: 2 * GWEI,
+        max_priority_fee_per_gas: GWEI,
+        to,
+        value,
+        blob_versioned_hashes: vec![B256::repeat_byte(0x01); 2],
+        max_fee_per_blob_gas: 3,
+        ..Default::default()
+    }));
+
+    let decoded =
+        [legacy, eip2930, eip1559, eip4844].map(|raw| Ethereum::decode_transaction(&raw).unwrap());
+    let expected = |chain_id, nonce, max_cost| RawTransaction {
+        sender: signer().address(),
+        chain_id,
+        nonce,
+        max_cost,
+    };
+    assert_eq!(
+        decoded,
+        [
+            expected(None, 1, value + gas),
+            expected(Some(1), 2, value + gas),
+            expected(Some(1), 3, value + gas),
+            // two blobs of 131072 blob gas at 3 wei
+            expected(Some(1), 4, value + gas + U256::from(2 * 131_072 * 3)),
+        ]
+    );
+
+    let err = Ethereum::decode_transaction(&[0x02, 0xc0]).unwrap_err();
+    assert!(
+        matches!(err, ExecutionError::InvalidRawTransaction(_)),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn test_transaction_checked_against_verified_state() {
+    let balance = U256::from(10u128 * 21_000 * 2 * GWEI);
+    let chain = ChainBuilder::new(84)
+        .account(signer().address(), 5, balance)
+        .build();
+    let (client, _, _) = client(&chain).await;
+    let check = |raw: Vec<u8>| {
+        let client = client.clone();
+        async move { client.check_raw_transaction(&raw, 1).await }
+    };
+    let rejection = |result: eyre::Result<()>| match result {
+        Err(err) => err.downcast::<ExecutionError>().unwrap(),
+        Ok(()) => panic!("transaction accepted"),
+    };
+
+    check(transfer(1, 5, U256::from(1))).await.unwrap();
+    // a nonce gap only delays inclusion
+    check(transfer(1, 9, U256::from(1))).await.unwrap();
+
+    let err = rejection(check(transfer(5, 5, U256::from(1))).await);
+    assert!(matches!(err, ExecutionError::InvalidChainId(5, 1)), "{err}");
+
+    let err = rejection(check(transfer(1, 4, U256::from(1))).await);
+    assert!(matches!(err, ExecutionError::NonceTooLow(5, 4)), "{err}");
+
+    let err = rejection(check(transfer(1, 5, balance)).await);
+    assert!(
+        matches!(err, ExecutionError::InsufficientFunds(have, _) if have == balance),
+        "{err}"
+    );
+}
@@ -0,0 +1,185 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::network::TransactionResponse;
+use alloy::primitives::{Address, Bytes, U256, U64};
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::params::ArrayParams;
+use jsonrpsee::rpc_params;
+use jsonrpsee::ws_client::{WsClient, WsClientBuilder};
+use serde_json::Value;
+
+use helios_core::client::node::Node;
+use helios_core::client::rpc::Rpc;
+use helios_core::client::trust::{trust_of, TRUST_MATRIX};
+use helios_core::execution::rpc::http_rpc::HttpRpc;
+use helios_core::execution::rpc::ExecutionRpc;
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::consensus::{execution_config, MockConsensus};
+use helios_core::testing::rpc::ChainRpc;
+use helios_ethereum::spec::Ethereum;
+
+async fn call(client: &WsClient, method: &str, params: ArrayParams) -> Value {
+    client
+        .request(method, params)
+        .await
+        .unwrap_or_else(|err| panic!("{method}: {err}"))
+}
+
+#[tokio::test]
+async fn test_matrix_lists_served_methods() {
+    let chain = ChainBuilder::new(162).length(2).build();
+    let (consensus, _feed) = MockConsensus::new(chain.chain_id());
+    let rpc = <HttpRpc<Ethereum> as ExecutionRpc<Ethereum>>::new("http://127.0.0.1:1").unwrap();
+    let node = Arc::new(Node::<Ethereum, _>::with_execution_rpc(
+        rpc,
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+    ));
+
+    let served = Rpc::new(node, SocketAddr::from(([127, 0, 0, 1], 0)))
+        .method_names()
+        .unwrap();
+    for method in &served {
+        assert!(trust_of(method).is_some(), "{method} has no trust entry");
+    }
+    for (method, _) in TRUST_MATRIX {
+        assert!(served.contains(method), "{method} is not served");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_local_methods_served_without_provider() {
+    // blocks from now on, so the head never looks out of sync
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    let chain = ChainBuilder::new(161)
+        .length(4)
+        .genesis_timestamp(now.as_secs())
+        .build();
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let (consensus, feed) = MockConsensus::new(chain.chain_id());
+    let node = Arc::new(Node::<Ethereum, _>::with_execution_rpc(
+        rpc.http(),
+        consensus,
+        chain.fork_schedule(),
+        execution_config(),
+    ));
+    let mut server = Rpc::new(node.clone(), SocketAddr::from(([127, 0, 0, 1], 0)));
+    let addr = server.start().await.unwrap().unwrap();
+
+    for block in chain.blocks() {
+        feed.advance(block.clone()).await.unwrap();
+    }
+    let head = chain.head();
+    node.execution
+        .sync_progress()
+        .subscribe()
+        .wait_for(|progress| progress.current_block == Some(head.header.number))
+        .await
+        .unwrap();
+
+    let client = WsClientBuilder::default()
+        .build(format!("ws://{addr}"))
+        .await
+        .unwrap();
+
+    let addresses = chain.accounts();
+    let reads = ["eth_getBalance", "eth_getTransactionCount", "eth_getCode"];
+    for address in &addresses {
+        for method in reads {
+            call(&client, method, rpc_params![address, "latest"]).await;
+        }
+    }
+
+    // from here on any provider call panics and fails the request
+    rpc.disconnect();
+
+    let mut called = Vec::new();
+    for address in &addresses {
+        let code = call(&client, "eth_getCode", rpc_params![address, "latest"]).await;
+        assert_eq!(
+            serde_json::from_value::<Bytes>(code).unwrap(),
+            chain.code(*address)
+        );
+        call(&client, "eth_getBalance", rpc_params![address, "latest"]).await;
+        call(
+            &client,
+            "eth_getTransactionCount",
+            rpc_params![address, "latest"],
+        )
+        .await;
+    }
+    called.extend(reads);
+
+    let chain_id = call(&client, "eth_chainId", rpc_params![]).await;
+    assert_eq!(
+        serde_json::from_value::<U64>(chain_id).unwrap(),
+        U64::from(chain.chain_id())
+    );
+    let number = call(&client, "eth_blockNumber", rpc_params![]).await;
+    assert_eq!(
+        serde_json::from_value::<U64>(number).unwrap(),
+        U64::from(head.header.number)
+    );
+    let block = call(
+        &client,
+        "eth_getBlockByNumber",
+        rpc_params!["latest", false],
+    )
+    .await;
+    assert_eq!(
+        block["hash"],
+        serde_json::to_value(head.header.hash).unwrap()
+    );
+    let parent = call(
+        &client,
+        "eth_getBlockByHash",
+        rpc_params![head.header.parent_hash, true],
+    )
+    .await;
+    assert_eq!(
+        parent["hash"],
+        serde_json::to_value(head.header.parent_hash).unwrap()
+    );
+    for tx in chain
+        .block(head.header.number - 1)
+        .unwrap()
+        .transactions
+        .txns()
+    {
+        let served = call(
+            &client,
+            "eth_getTransactionByHash",
+            rpc_params![tx.tx_hash()],
+        )
+        .await;
+        assert_eq!(
+            served["blockHash"],
+            serde_json::to_value(head.header.parent_hash).unwrap()
+        );
+    }
+    let coinbase = call(&client, "eth_coinbase", rpc_params![]).await;
+    assert!(serde_json::from_value::<Address>(coinbase).is_ok());
+    let blob_base_fee = call(&client, "eth_blobBaseFee", rpc_params!["latest"]).await;
+    assert_eq!(
+        serde_json::from_value::<U256>(blob_base_fee).unwrap(),
+        U256::from(1)
+    );
+    call(&client, "helios_getPrevRandao", rpc_params!["latest"]).await;
+    called.extend([
+        "eth_chainId",
+        "eth_blockNumber",
+        "eth_getBlockByNumber",
+        "eth_getBlockByHash",
+        "eth_getTransactionByHash",
+        "eth_coinbase",
+        "eth_blobBaseFee",
+        "helios_getPrevRandao",
+    ]);
+
+    for method in called {
+        assert!(trust_of(method).unwrap().is_local(), "{method}");
+    }
+}
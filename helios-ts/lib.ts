@@ -1,15 +1,103 @@
-import initWasm, { EthereumClient, OpStackClient } from "./pkg/index";
+import initWasm, {
+  EthereumClient,
+  OpStackClient,
+  verifyAccountProof as wasmVerifyAccountProof,
+  verifyReceiptProof as wasmVerifyReceiptProof,
+  verifyBlockHash as wasmVerifyBlockHash,
+} from "./pkg/index";
 
 export async function init() {
   const wasmData = require("./pkg/index_bg.wasm");
   await initWasm(wasmData);
 }
 
+/// Verifies an eth_getProof response against a state root you already trust, along with
+/// the storage proofs it carries. Only needs init(), not a running client.
+export function verifyAccountProof(
+  proof: string | object,
+  stateRoot: string
+): VerifiedAccount {
+  return verify(() => wasmVerifyAccountProof(toJson(proof), stateRoot));
+}
+
+/// Verifies a block against a block hash you already trust and all of its receipts against
+/// its receipts root, and returns the receipt of the bundle's transaction.
+export function verifyReceiptProof(
+  bundle: string | ReceiptBundle,
+  blockHash: string
+): any {
+  return verify(() => wasmVerifyReceiptProof(toJson(bundle), blockHash));
+}
+
+/// Verifies that a block hashes to the expected hash, and that its transactions and
+/// withdrawals match its header.
+export function verifyBlockHash(
+  block: string | object,
+  expectedHash: string
+): VerifiedBlock {
+  return verify(() => wasmVerifyBlockHash(toJson(block), expectedHash));
+}
+
+export type VerifiedAccount = {
+  address: string;
+  nonce: number;
+  balance: string;
+  codeHash: string;
+  storageHash: string;
+  /** Proven storage values by slot. */
+  storage: Record<string, string>;
+};
+
+export type ReceiptBundle = {
+  transactionHash: string;
+  /** The block of the transaction, with full transactions. */
+  block: object;
+  /** Every receipt of the block, as returned by eth_getBlockReceipts. */
+  receipts: object[];
+};
+
+export type VerifiedBlock = {
+  number: number;
+  hash: string;
+};
+
+export type VerificationErrorKind =
+  | "malformedInput"
+  | "invalidProof"
+  | "rootMismatch"
+  | "notIncluded";
+
+export class VerificationError extends Error {
+  kind: VerificationErrorKind;
+
+  constructor(kind: VerificationErrorKind, message: string) {
+    super(message);
+    this.name = "VerificationError";
+    this.kind = kind;
+  }
+}
+
+function verify<T>(run: () => T): T {
+  try {
+    return run();
+  } catch (err) {
+    if (err && typeof err.kind === "string") {
+      throw new VerificationError(err.kind, err.message);
+    }
+    throw err;
+  }
+}
+
+function toJson(value: string | object): string {
+  return typeof value === "string" ? value : JSON.stringify(value);
+}
+
 /// An EIP-1193 compliant Ethereum provider. Treat this the same as you
 /// would window.ethereum when constructing an ethers or web3 provider.
 export class HeliosProvider {
   #client;
   #chainId;
+  #nextHead: Promise<BlockHeader> | undefined;
 
   /// Do not use this constructor. Instead use the createHeliosProvider function.
   constructor(config: Config, kind: "ethereum" | "opstack") {
@@ -46,6 +134,56 @@ export class HeliosProvider {
     await this.#client.wait_synced();
   }
 
+  /// The address an ENS name resolves to, or null. Resolved through verified calls against
+  /// the latest block.
+  async resolveEns(name: string): Promise<string | null> {
+    return this.#client.resolve_ens(name);
+  }
+
+  /// The primary ENS name of an address, or null if it has none that resolves back to it.
+  async lookupEns(address: string): Promise<string | null> {
+    return this.#client.lookup_ens(address);
+  }
+
+  /// Resolves with the header of the next block to become the verified head, instead of
+  /// polling eth_blockNumber. Callers waiting at the same time get the same header. Each
+  /// wait subscribes anew, so heads verified between two waits are never served late.
+  nextVerifiedHead(): Promise<BlockHeader> {
+    if (this.#nextHead === undefined) {
+      const heads = this.#client.head_stream();
+      this.#nextHead = heads.next().then(
+        (header: BlockHeader | null) => {
+          this.#nextHead = undefined;
+          heads.free();
+          if (header === null) {
+            throw new Error("client stopped delivering blocks");
+          }
+          return header;
+        },
+        (err: any) => {
+          this.#nextHead = undefined;
+          heads.free();
+          throw err;
+        }
+      );
+    }
+    return this.#nextHead;
+  }
+
+  /// Iterates over the headers of the blocks that become the verified head from now on,
+  /// ending once the client stops. A consumer falling behind misses heads rather than
+  /// receiving stale ones.
+  async *verifiedHeads(): AsyncGenerator<BlockHeader> {
+    const heads = this.#client.head_stream();
+    try {
+      for (let header = await heads.next(); header !== null; header = await heads.next()) {
+        yield header;
+      }
+    } finally {
+      heads.free();
+    }
+  }
+
   async request(req: Request): Promise<any> {
     try {
       return await this.#req(req);
@@ -89,6 +227,9 @@ export class HeliosProvider {
       case "eth_call": {
         return this.#client.call(req.params[0], req.params[1]);
       }
+      case "helios_callMany": {
+        return this.#client.call_many(req.params[0], req.params[1]);
+      }
       case "eth_estimateGas": {
         return this.#client.estimate_gas(req.params[0]);
       }
@@ -128,15 +269,10 @@ export class HeliosProvider {
         return logs.map(mapToObj);
       }
       case "eth_getFilterChanges": {
-        const changes = await this.#client.get_filter_changes(req.params[0]);
-        if (changes.length > 0 && typeof changes[0] === "object") {
-          return changes.map(mapToObj);
-        }
-        return changes;
+        return this.#client.get_filter_changes(req.params[0]);
       }
       case "eth_getFilterLogs": {
-        const logs = await this.#client.get_filter_logs(req.params[0]);
-        return logs.map(mapToObj);
+        return this.#client.get_filter_logs(req.params[0]);
       }
       case "eth_uninstallFilter": {
         return this.#client.uninstall_filter(req.params[0]);
@@ -171,6 +307,21 @@ export class HeliosProvider {
   }
 }
 
+/// A verified block header as returned by eth_getBlockByNumber, with quantities as hex
+/// strings.
+export type BlockHeader = {
+  number: string;
+  hash: string;
+  parentHash: string;
+  timestamp: string;
+  stateRoot: string;
+  receiptsRoot: string;
+  gasUsed: string;
+  gasLimit: string;
+  baseFeePerGas?: string;
+  [field: string]: any;
+};
+
 export type Config = {
   executionRpc: string;
   consensusRpc?: string;
@@ -3,48 +3,80 @@ extern crate web_sys;
 
 use std::str::FromStr;
 
-use alloy::hex::FromHex;
 use alloy::primitives::{Address, B256, U256};
-use alloy::rpc::types::{Filter, TransactionRequest};
+use alloy::rpc::types::TransactionRequest;
 use eyre::Result;
 use wasm_bindgen::prelude::*;
 
-use helios_core::types::BlockTag;
-use helios_ethereum::config::{networks, Config};
-use helios_ethereum::database::{ConfigDB, Database};
+use helios_core::execution::stream::LagPolicy;
+use helios_core::types::{BlockTag, FilterParam};
+use helios_ethereum::config::{checkpoints::parse_checkpoint, networks::Network, Config};
+use helios_ethereum::database::{Checkpoint, ConfigDB, Database, EntryInfo, OpenDatabase};
 use helios_ethereum::EthereumClientBuilder;
 
-use crate::map_err;
+use crate::heads::HeadStream;
 use crate::storage::LocalStorageDB;
+use crate::{format_filter_id, map_err, parse_filter_id, to_js};
 
-#[derive(Clone)]
+#[derive(Debug, Clone)]
 pub enum DatabaseType {
     Memory(ConfigDB),
     LocalStorage(LocalStorageDB),
 }
 
-impl Database for DatabaseType {
+impl DatabaseType {
+    fn inner(&self) -> &dyn Database {
+        match self {
+            DatabaseType::Memory(db) => db,
+            DatabaseType::LocalStorage(db) => db,
+        }
+    }
+}
+
+impl OpenDatabase for DatabaseType {
     fn new(config: &Config) -> Result<Self> {
-        // Implement this method based on the behavior of ConfigDB and LocalStorageDB
         match config.database_type.as_deref() {
-            Some("config") => Ok(DatabaseType::Memory(ConfigDB::new(config)?)),
             Some("localstorage") => Ok(DatabaseType::LocalStorage(LocalStorageDB::new(config)?)),
             _ => Ok(DatabaseType::Memory(ConfigDB::new(config)?)),
         }
     }
+}
+
+impl Database for DatabaseType {
+    fn put(&self, namespace: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        self.inner().put(namespace, key, value)
+    }
+
+    fn get(&self, namespace: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        self.inner().get(namespace, key)
+    }
+
+    fn delete(&self, namespace: &str, keys: &[Vec<u8>]) -> Result<()> {
+        self.inner().delete(namespace, keys)
+    }
+
+    fn set_pinned(&self, namespace: &str, key: &[u8], pinned: bool) -> Result<()> {
+        self.inner().set_pinned(namespace, key, pinned)
+    }
+
+    fn entries(&self, namespace: &str) -> Result<Vec<EntryInfo>> {
+        self.inner().entries(namespace)
+    }
 
     fn load_checkpoint(&self) -> Result<B256> {
-        match self {
-            DatabaseType::Memory(db) => db.load_checkpoint(),
-            DatabaseType::LocalStorage(db) => db.load_checkpoint(),
-        }
+        self.inner().load_checkpoint()
     }
 
     fn save_checkpoint(&self, checkpoint: B256) -> Result<()> {
-        match self {
-            DatabaseType::Memory(db) => db.save_checkpoint(checkpoint),
-            DatabaseType::LocalStorage(db) => db.save_checkpoint(checkpoint),
-        }
+        self.inner().save_checkpoint(checkpoint)
+    }
+
+    fn load_checkpoint_with_slot(&self) -> Result<Option<Checkpoint>> {
+        self.inner().load_checkpoint_with_slot()
+    }
+
+    fn save_checkpoint_with_slot(&self, checkpoint: Checkpoint) -> Result<()> {
+        self.inner().save_checkpoint_with_slot(checkpoint)
     }
 }
 
@@ -66,22 +98,20 @@ impl EthereumClient {
     ) -> Result<EthereumClient, JsError> {
         console_error_panic_hook::set_once();
 
-        let base = match network.as_str() {
-            "mainnet" => networks::mainnet(),
-            "sepolia" => networks::sepolia(),
-            "holesky" => networks::holesky(),
-            other => Err(JsError::new(&format!("invalid network: {}", other)))?,
-        };
+        let network = Network::from_str(&network).map_err(|err| JsError::new(&err.to_string()))?;
+        // devnets come and go faster than releases reach the browser
+        if network == Network::PectraDevnet {
+            return Err(JsError::new(&format!("network {network} is not supported")));
+        }
+        let base = network.to_base_config();
 
         let chain_id = base.chain.chain_id;
 
-        let checkpoint = Some(
-            checkpoint
-                .as_ref()
-                .map(|c| c.strip_prefix("0x").unwrap_or(c.as_str()))
-                .and_then(|c| B256::from_hex(c).ok())
-                .unwrap_or(base.default_checkpoint),
-        );
+        // without one, the client starts from a stored or the default checkpoint
+        let checkpoint = checkpoint
+            .map(|checkpoint| parse_checkpoint(&checkpoint))
+            .transpose()
+            .map_err(|err| JsError::new(&err.to_string()))?;
 
         let consensus_rpc = if let Some(rpc) = consensus_rpc {
             rpc
@@ -94,15 +124,24 @@ impl EthereumClient {
             execution_rpc,
             consensus_rpc,
             checkpoint,
+            default_checkpoint: base.default_checkpoint,
 
             chain: base.chain,
             forks: base.forks,
 
             database_type: Some(db_type),
+            load_external_fallback: base.load_external_fallback,
+            fallback_quorum: base.fallback_quorum,
+            ens_registry: base.ens_registry,
+            ccip_read: base.ccip_read,
+            ccip_read_timeout: base.ccip_read_timeout,
             ..Default::default()
         };
 
-        let inner = map_err(EthereumClientBuilder::new().config(config).build())?;
+        let inner = EthereumClientBuilder::new()
+            .config(config)
+            .build()
+            .map_err(|err| JsError::new(&err.to_string()))?;
 
         Ok(Self { inner, chain_id })
     }
@@ -234,6 +273,19 @@ impl EthereumClient {
         Ok(format!("0x{}", hex::encode(code)))
     }
 
+    #[wasm_bindgen]
+    pub async fn resolve_ens(&self, name: String) -> Result<JsValue, JsError> {
+        let address = map_err(self.inner.resolve_ens(&name).await)?;
+        Ok(serde_wasm_bindgen::to_value(&address)?)
+    }
+
+    #[wasm_bindgen]
+    pub async fn lookup_ens(&self, address: JsValue) -> Result<JsValue, JsError> {
+        let address: Address = serde_wasm_bindgen::from_value(address)?;
+        let name = map_err(self.inner.lookup_ens(address).await)?;
+        Ok(serde_wasm_bindgen::to_value(&name)?)
+    }
+
     #[wasm_bindgen]
     pub async fn get_storage_at(
         &self,
@@ -256,6 +308,14 @@ impl EthereumClient {
         Ok(format!("0x{}", hex::encode(res)))
     }
 
+    #[wasm_bindgen]
+    pub async fn call_many(&self, txs: JsValue, block: JsValue) -> Result<JsValue, JsError> {
+        let txs: Vec<TransactionRequest> = serde_wasm_bindgen::from_value(txs)?;
+        let block: BlockTag = serde_wasm_bindgen::from_value(block)?;
+        let outcomes = map_err(self.inner.call_many(&txs, block).await)?;
+        Ok(serde_wasm_bindgen::to_value(&outcomes)?)
+    }
+
     #[wasm_bindgen]
     pub async fn estimate_gas(&self, opts: JsValue) -> Result<u32, JsError> {
         let opts: TransactionRequest = serde_wasm_bindgen::from_value(opts)?;
@@ -297,49 +357,58 @@ impl EthereumClient {
 
     #[wasm_bindgen]
     pub async fn get_logs(&self, filter: JsValue) -> Result<JsValue, JsError> {
-        let filter: Filter = serde_wasm_bindgen::from_value(filter)?;
+        let filter: FilterParam = serde_wasm_bindgen::from_value(filter)?;
+        let filter = map_err(self.inner.resolve_filter(filter).await)?;
         let logs = map_err(self.inner.get_logs(&filter).await)?;
         Ok(serde_wasm_bindgen::to_value(&logs)?)
     }
 
     #[wasm_bindgen]
-    pub async fn get_filter_changes(&self, filter_id: JsValue) -> Result<JsValue, JsError> {
-        let filter_id: U256 = serde_wasm_bindgen::from_value(filter_id)?;
+    pub async fn get_filter_changes(&self, filter_id: String) -> Result<JsValue, JsError> {
+        let filter_id = parse_filter_id(&filter_id)?;
         let filter_changes = map_err(self.inner.get_filter_changes(filter_id).await)?;
-        Ok(serde_wasm_bindgen::to_value(&filter_changes)?)
+        to_js(&filter_changes)
     }
 
     #[wasm_bindgen]
-    pub async fn get_filter_logs(&self, filter_id: JsValue) -> Result<JsValue, JsError> {
-        let filter_id: U256 = serde_wasm_bindgen::from_value(filter_id)?;
+    pub async fn get_filter_logs(&self, filter_id: String) -> Result<JsValue, JsError> {
+        let filter_id = parse_filter_id(&filter_id)?;
         let logs = map_err(self.inner.get_filter_logs(filter_id).await)?;
-        Ok(serde_wasm_bindgen::to_value(&logs)?)
+        to_js(&logs)
     }
 
     #[wasm_bindgen]
-    pub async fn uninstall_filter(&self, filter_id: JsValue) -> Result<bool, JsError> {
-        let filter_id: U256 = serde_wasm_bindgen::from_value(filter_id)?;
+    pub async fn uninstall_filter(&self, filter_id: String) -> Result<bool, JsError> {
+        let filter_id = parse_filter_id(&filter_id)?;
         let uninstalled = map_err(self.inner.uninstall_filter(filter_id).await)?;
         Ok(uninstalled)
     }
 
     #[wasm_bindgen]
-    pub async fn new_filter(&self, filter: JsValue) -> Result<JsValue, JsError> {
-        let filter: Filter = serde_wasm_bindgen::from_value(filter)?;
+    pub async fn new_filter(&self, filter: JsValue) -> Result<String, JsError> {
+        let filter: FilterParam = serde_wasm_bindgen::from_value(filter)?;
+        let filter = map_err(self.inner.resolve_filter(filter).await)?;
         let filter_id = map_err(self.inner.new_filter(&filter).await)?;
-        Ok(serde_wasm_bindgen::to_value(&filter_id)?)
+        Ok(format_filter_id(filter_id))
     }
 
     #[wasm_bindgen]
-    pub async fn new_block_filter(&self) -> Result<JsValue, JsError> {
+    pub async fn new_block_filter(&self) -> Result<String, JsError> {
         let filter_id = map_err(self.inner.new_block_filter().await)?;
-        Ok(serde_wasm_bindgen::to_value(&filter_id)?)
+        Ok(format_filter_id(filter_id))
     }
 
     #[wasm_bindgen]
-    pub async fn new_pending_transaction_filter(&self) -> Result<JsValue, JsError> {
+    pub async fn new_pending_transaction_filter(&self) -> Result<String, JsError> {
         let filter_id = map_err(self.inner.new_pending_transaction_filter().await)?;
-        Ok(serde_wasm_bindgen::to_value(&filter_id)?)
+        Ok(format_filter_id(filter_id))
+    }
+
+    /// Follows the verified heads from the next one on. Each stream is read on its own, so
+    /// waiting on it does not hold up other calls to the client.
+    #[wasm_bindgen]
+    pub fn head_stream(&self) -> HeadStream {
+        HeadStream::new(self.inner.block_stream(LagPolicy::Skip))
     }
 
     #[wasm_bindgen]
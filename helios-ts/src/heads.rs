@@ -0,0 +1,46 @@
+use std::pin::Pin;
+
+use futures::future::ready;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use helios_core::execution::stream::HeaderEvent;
+
+use crate::to_js;
+
+/// The headers of blocks as they become the verified head, the way native clients follow
+/// them with `block_stream`.
+#[wasm_bindgen]
+pub struct HeadStream {
+    heads: Pin<Box<dyn Stream<Item = Result<JsValue, JsError>>>>,
+}
+
+impl HeadStream {
+    pub(crate) fn new<H: Serialize + 'static>(
+        events: impl Stream<Item = HeaderEvent<H>> + 'static,
+    ) -> Self {
+        // a consumer that falls behind misses heads rather than falling further behind
+        let heads = events.filter_map(|event| {
+            ready(match event {
+                HeaderEvent::Header(header) => Some(to_js(&header)),
+                HeaderEvent::Gap { .. } | HeaderEvent::Lagged { .. } => None,
+            })
+        });
+        Self {
+            heads: Box::pin(heads),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl HeadStream {
+    /// Resolves with the next verified head, or null once the client stops delivering blocks.
+    #[wasm_bindgen]
+    pub async fn next(&mut self) -> Result<JsValue, JsError> {
+        match self.heads.next().await {
+            Some(header) => header,
+            None => Ok(JsValue::NULL),
+        }
+    }
+}
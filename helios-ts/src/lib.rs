@@ -1,11 +1,21 @@
 #![allow(unexpected_cfgs)]
 
+use std::str::FromStr;
+
+use alloy::primitives::U256;
 use eyre::Result;
-use wasm_bindgen::JsError;
+use serde::Serialize;
+use wasm_bindgen::{JsError, JsValue};
 
+#[cfg(feature = "ethereum")]
 pub mod ethereum;
+pub mod heads;
+#[cfg(feature = "opstack")]
 pub mod opstack;
+#[cfg(feature = "ethereum")]
 pub mod storage;
+#[cfg(feature = "ethereum")]
+pub mod verify;
 
 #[allow(unused_macros)]
 macro_rules! log {
@@ -17,3 +27,19 @@ macro_rules! log {
 fn map_err<T>(val: Result<T>) -> Result<T, JsError> {
     val.map_err(|err| JsError::new(&err.to_string()))
 }
+
+/// Converts `value` to plain JS objects and arrays rather than `Map`s. Quantities stay the hex
+/// strings they serialize to, where JS numbers would lose the precision of large ones.
+fn to_js<T: Serialize + ?Sized>(value: &T) -> Result<JsValue, JsError> {
+    Ok(value.serialize(&serde_wasm_bindgen::Serializer::json_compatible())?)
+}
+
+/// Parses a filter id as handed out by the `new_*filter` methods.
+fn parse_filter_id(filter_id: &str) -> Result<U256, JsError> {
+    U256::from_str(filter_id)
+        .map_err(|err| JsError::new(&format!("invalid filter id {filter_id}: {err}")))
+}
+
+fn format_filter_id(filter_id: U256) -> String {
+    format!("{filter_id:#x}")
+}
@@ -4,16 +4,17 @@ extern crate web_sys;
 use std::str::FromStr;
 
 use alloy::primitives::{Address, B256, U256};
-use alloy::rpc::types::Filter;
 use wasm_bindgen::prelude::*;
 
 use op_alloy_rpc_types::OpTransactionRequest;
 
-use helios_core::types::BlockTag;
+use helios_core::execution::stream::LagPolicy;
+use helios_core::types::{BlockTag, FilterParam};
 use helios_opstack::config::{Config, Network, NetworkConfig};
 use helios_opstack::OpStackClientBuilder;
 
-use crate::map_err;
+use crate::heads::HeadStream;
+use crate::{format_filter_id, map_err, parse_filter_id, to_js};
 
 #[wasm_bindgen]
 pub struct OpStackClient {
@@ -45,9 +46,17 @@ impl OpStackClient {
             consensus_rpc,
             chain: network_config.chain,
             rpc_socket: None,
+            rpc_tls_cert: None,
+            rpc_tls_key: None,
+            ipc_path: None,
             load_external_fallback: None,
             checkpoint: None,
             verify_unsafe_signer: false,
+            l1_execution_rpc: None,
+            strict_deposits: false,
+            sequencer: Default::default(),
+            backfill: Default::default(),
+            execution_rate_limit: Default::default(),
         };
 
         let inner = map_err(OpStackClientBuilder::new().config(config).build())?;
@@ -182,6 +191,19 @@ impl OpStackClient {
         Ok(format!("0x{}", hex::encode(code)))
     }
 
+    #[wasm_bindgen]
+    pub async fn resolve_ens(&self, name: String) -> Result<JsValue, JsError> {
+        let address = map_err(self.inner.resolve_ens(&name).await)?;
+        Ok(serde_wasm_bindgen::to_value(&address)?)
+    }
+
+    #[wasm_bindgen]
+    pub async fn lookup_ens(&self, address: JsValue) -> Result<JsValue, JsError> {
+        let address: Address = serde_wasm_bindgen::from_value(address)?;
+        let name = map_err(self.inner.lookup_ens(address).await)?;
+        Ok(serde_wasm_bindgen::to_value(&name)?)
+    }
+
     #[wasm_bindgen]
     pub async fn get_storage_at(
         &self,
@@ -204,6 +226,14 @@ impl OpStackClient {
         Ok(format!("0x{}", hex::encode(res)))
     }
 
+    #[wasm_bindgen]
+    pub async fn call_many(&self, txs: JsValue, block: JsValue) -> Result<JsValue, JsError> {
+        let txs: Vec<OpTransactionRequest> = serde_wasm_bindgen::from_value(txs)?;
+        let block: BlockTag = serde_wasm_bindgen::from_value(block)?;
+        let outcomes = map_err(self.inner.call_many(&txs, block).await)?;
+        Ok(serde_wasm_bindgen::to_value(&outcomes)?)
+    }
+
     #[wasm_bindgen]
     pub async fn estimate_gas(&self, opts: JsValue) -> Result<u32, JsError> {
         let opts: OpTransactionRequest = serde_wasm_bindgen::from_value(opts)?;
@@ -245,49 +275,58 @@ impl OpStackClient {
 
     #[wasm_bindgen]
     pub async fn get_logs(&self, filter: JsValue) -> Result<JsValue, JsError> {
-        let filter: Filter = serde_wasm_bindgen::from_value(filter)?;
+        let filter: FilterParam = serde_wasm_bindgen::from_value(filter)?;
+        let filter = map_err(self.inner.resolve_filter(filter).await)?;
         let logs = map_err(self.inner.get_logs(&filter).await)?;
         Ok(serde_wasm_bindgen::to_value(&logs)?)
     }
 
     #[wasm_bindgen]
-    pub async fn get_filter_changes(&self, filter_id: JsValue) -> Result<JsValue, JsError> {
-        let filter_id: U256 = serde_wasm_bindgen::from_value(filter_id)?;
+    pub async fn get_filter_changes(&self, filter_id: String) -> Result<JsValue, JsError> {
+        let filter_id = parse_filter_id(&filter_id)?;
         let filter_changes = map_err(self.inner.get_filter_changes(filter_id).await)?;
-        Ok(serde_wasm_bindgen::to_value(&filter_changes)?)
+        to_js(&filter_changes)
     }
 
     #[wasm_bindgen]
-    pub async fn get_filter_logs(&self, filter_id: JsValue) -> Result<JsValue, JsError> {
-        let filter_id: U256 = serde_wasm_bindgen::from_value(filter_id)?;
+    pub async fn get_filter_logs(&self, filter_id: String) -> Result<JsValue, JsError> {
+        let filter_id = parse_filter_id(&filter_id)?;
         let logs = map_err(self.inner.get_filter_logs(filter_id).await)?;
-        Ok(serde_wasm_bindgen::to_value(&logs)?)
+        to_js(&logs)
     }
 
     #[wasm_bindgen]
-    pub async fn uninstall_filter(&self, filter_id: JsValue) -> Result<bool, JsError> {
-        let filter_id: U256 = serde_wasm_bindgen::from_value(filter_id)?;
+    pub async fn uninstall_filter(&self, filter_id: String) -> Result<bool, JsError> {
+        let filter_id = parse_filter_id(&filter_id)?;
         let uninstalled = map_err(self.inner.uninstall_filter(filter_id).await)?;
         Ok(uninstalled)
     }
 
     #[wasm_bindgen]
-    pub async fn new_filter(&self, filter: JsValue) -> Result<JsValue, JsError> {
-        let filter: Filter = serde_wasm_bindgen::from_value(filter)?;
+    pub async fn new_filter(&self, filter: JsValue) -> Result<String, JsError> {
+        let filter: FilterParam = serde_wasm_bindgen::from_value(filter)?;
+        let filter = map_err(self.inner.resolve_filter(filter).await)?;
         let filter_id = map_err(self.inner.new_filter(&filter).await)?;
-        Ok(serde_wasm_bindgen::to_value(&filter_id)?)
+        Ok(format_filter_id(filter_id))
     }
 
     #[wasm_bindgen]
-    pub async fn new_block_filter(&self) -> Result<JsValue, JsError> {
+    pub async fn new_block_filter(&self) -> Result<String, JsError> {
         let filter_id = map_err(self.inner.new_block_filter().await)?;
-        Ok(serde_wasm_bindgen::to_value(&filter_id)?)
+        Ok(format_filter_id(filter_id))
     }
 
     #[wasm_bindgen]
-    pub async fn new_pending_transaction_filter(&self) -> Result<JsValue, JsError> {
+    pub async fn new_pending_transaction_filter(&self) -> Result<String, JsError> {
         let filter_id = map_err(self.inner.new_pending_transaction_filter().await)?;
-        Ok(serde_wasm_bindgen::to_value(&filter_id)?)
+        Ok(format_filter_id(filter_id))
+    }
+
+    /// Follows the verified heads from the next one on. Each stream is read on its own, so
+    /// waiting on it does not hold up other calls to the client.
+    #[wasm_bindgen]
+    pub fn head_stream(&self) -> HeadStream {
+        HeadStream::new(self.inner.block_stream(LagPolicy::Skip))
     }
 
     #[wasm_bindgen]
@@ -4,47 +4,190 @@ extern crate web_sys;
 use alloy::{hex::FromHex, primitives::B256};
 use eyre::Result;
 use wasm_bindgen::prelude::*;
+use web_sys::Storage;
 
-use helios_ethereum::{config::Config, database::Database};
+use helios_core::database::check_namespace;
+use helios_core::time::{SystemTime, UNIX_EPOCH};
+use helios_ethereum::{
+    config::Config,
+    database::{Checkpoint, Database, EntryInfo, OpenDatabase},
+};
 
-#[derive(Clone)]
+const PREFIX: &str = "helios";
+const PIN_SUFFIX: &str = ".pinned";
+
+/// Entries in the local storage of the page, one item per entry named
+/// `helios/<namespace>/<hex key>` and holding the time it was written and the hex value.
+#[derive(Debug, Clone)]
 pub struct LocalStorageDB;
 
-impl Database for LocalStorageDB {
+impl OpenDatabase for LocalStorageDB {
     fn new(_config: &Config) -> Result<Self> {
         console_error_panic_hook::set_once();
-        let window = web_sys::window().unwrap();
-        if let Ok(Some(_local_storage)) = window.local_storage() {
-            return Ok(Self {});
+        let db = Self;
+        db.migrate_checkpoint()?;
+        Ok(db)
+    }
+}
+
+impl LocalStorageDB {
+    /// Moves the checkpoint earlier versions kept in the `checkpoint` and `checkpoint_slot`
+    /// items into the database, unless it already holds a newer one.
+    fn migrate_checkpoint(&self) -> Result<()> {
+        let storage = storage()?;
+        let Some(root) = storage.get_item("checkpoint").map_err(js_err)? else {
+            return Ok(());
+        };
+
+        if self.load_checkpoint().is_err() {
+            let root = B256::from_hex(root.strip_prefix("0x").unwrap_or(&root))
+                .map_err(|_| eyre::eyre!("Failed to decode checkpoint"))?;
+            let slot = storage.get_item("checkpoint_slot").map_err(js_err)?;
+            match slot.and_then(|slot| slot.parse().ok()) {
+                Some(slot) => self.save_checkpoint_with_slot(Checkpoint { root, slot })?,
+                None => self.save_checkpoint(root)?,
+            }
         }
 
-        eyre::bail!("local_storage not available")
+        storage.remove_item("checkpoint").map_err(js_err)?;
+        storage.remove_item("checkpoint_slot").map_err(js_err)?;
+        Ok(())
     }
+}
 
-    fn load_checkpoint(&self) -> Result<B256> {
-        let window = web_sys::window().unwrap();
-        if let Ok(Some(local_storage)) = window.local_storage() {
-            let checkpoint = local_storage.get_item("checkpoint");
-            if let Ok(Some(checkpoint)) = checkpoint {
-                let checkpoint = checkpoint.strip_prefix("0x").unwrap_or(&checkpoint);
-                return B256::from_hex(checkpoint)
-                    .map_err(|_| eyre::eyre!("Failed to decode checkpoint"));
-            }
-            eyre::bail!("checkpoint not found")
+impl Database for LocalStorageDB {
+    fn put(&self, namespace: &str, key: &[u8], value: &[u8]) -> Result<()> {
+        let item = item_name(namespace, key)?;
+        let written_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64);
+        let value = format!("{written_at}:{}", hex::encode(value));
+        storage()?.set_item(&item, &value).map_err(js_err)
+    }
+
+    fn get(&self, namespace: &str, key: &[u8]) -> Result<Option<Vec<u8>>> {
+        let item = item_name(namespace, key)?;
+        match storage()?.get_item(&item).map_err(js_err)? {
+            Some(value) => Ok(Some(decode(&value)?.1)),
+            None => Ok(None),
         }
+    }
 
-        eyre::bail!("local_storage not available")
+    fn delete(&self, namespace: &str, keys: &[Vec<u8>]) -> Result<()> {
+        let storage = storage()?;
+        for key in keys {
+            let item = item_name(namespace, key)?;
+            storage.remove_item(&item).map_err(js_err)?;
+            storage.remove_item(&(item + PIN_SUFFIX)).map_err(js_err)?;
+        }
+        Ok(())
     }
 
-    fn save_checkpoint(&self, checkpoint: B256) -> Result<()> {
-        let window = web_sys::window().unwrap();
-        if let Ok(Some(local_storage)) = window.local_storage() {
-            local_storage
-                .set_item("checkpoint", &hex::encode(checkpoint))
-                .unwrap_throw();
-            return Ok(());
+    fn set_pinned(&self, namespace: &str, key: &[u8], pinned: bool) -> Result<()> {
+        let marker = item_name(namespace, key)? + PIN_SUFFIX;
+        let storage = storage()?;
+        if pinned {
+            storage.set_item(&marker, "").map_err(js_err)
+        } else {
+            storage.remove_item(&marker).map_err(js_err)
+        }
+    }
+
+    /// Sizes are those of the decoded values, not of the hex strings stored.
+    fn entries(&self, namespace: &str) -> Result<Vec<EntryInfo>> {
+        check_namespace(namespace)?;
+        let prefix = format!("{PREFIX}/{namespace}/");
+        let storage = storage()?;
+
+        let mut names = Vec::new();
+        for index in 0..storage.length().map_err(js_err)? {
+            if let Some(name) = storage.key(index).map_err(js_err)? {
+                if let Some(name) = name.strip_prefix(&prefix) {
+                    names.push(name.to_string());
+                }
+            }
         }
 
-        eyre::bail!("local_storage not available")
+        let mut entries = Vec::new();
+        for name in &names {
+            if name.ends_with(PIN_SUFFIX) {
+                continue;
+            }
+            let Ok(key) = hex::decode(name) else {
+                continue;
+            };
+            let Some(value) = storage
+                .get_item(&format!("{prefix}{name}"))
+                .map_err(js_err)?
+            else {
+                continue;
+            };
+            let (written_at, value) = decode(&value)?;
+            entries.push(EntryInfo {
+                key,
+                size: value.len() as u64,
+                written_at,
+                pinned: names.contains(&(name.clone() + PIN_SUFFIX)),
+            });
+        }
+        Ok(entries)
+    }
+}
+
+fn storage() -> Result<Storage> {
+    let window = web_sys::window().ok_or_else(|| eyre::eyre!("window not available"))?;
+    match window.local_storage() {
+        Ok(Some(storage)) => Ok(storage),
+        _ => eyre::bail!("local_storage not available"),
+    }
+}
+
+fn item_name(namespace: &str, key: &[u8]) -> Result<String> {
+    check_namespace(namespace)?;
+    Ok(format!("{PREFIX}/{namespace}/{}", hex::encode(key)))
+}
+
+fn decode(value: &str) -> Result<(u64, Vec<u8>)> {
+    let (written_at, value) = value
+        .split_once(':')
+        .ok_or_else(|| eyre::eyre!("malformed local storage entry"))?;
+    Ok((written_at.parse()?, hex::decode(value)?))
+}
+
+fn js_err(err: JsValue) -> eyre::Report {
+    eyre::eyre!("local storage: {:?}", err)
+}
+
+#[cfg(test)]
+mod tests {
+    use wasm_bindgen_test::*;
+
+    use helios_core::testing::database::check_conformance;
+
+    use super::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[wasm_bindgen_test]
+    fn test_conformance() {
+        storage().unwrap().clear().unwrap();
+        check_conformance(&LocalStorageDB);
+    }
+
+    #[wasm_bindgen_test]
+    fn test_legacy_checkpoint_migrated() {
+        let storage = storage().unwrap();
+        storage.clear().unwrap();
+        let root = B256::repeat_byte(0x5a);
+        storage.set_item("checkpoint", &hex::encode(root)).unwrap();
+        storage.set_item("checkpoint_slot", "7069376").unwrap();
+
+        let db = LocalStorageDB::new(&Config::default()).unwrap();
+        let checkpoint = Checkpoint {
+            root,
+            slot: 7069376,
+        };
+        assert_eq!(db.load_checkpoint_with_slot().unwrap(), Some(checkpoint));
+        assert_eq!(storage.get_item("checkpoint").unwrap(), None);
     }
 }
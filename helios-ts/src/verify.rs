@@ -0,0 +1,201 @@
+//! Standalone verification of data served by Helios, for apps that check a proof against a
+//! root they already trust without running a light client.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use alloy::network::TransactionResponse;
+use alloy::primitives::{Address, B256, U256};
+use alloy::rpc::types::{Block, EIP1186AccountProofResponse, TransactionReceipt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use helios_core::execution::proof::{
+    receipts_root, verify_account_proof as verify_account, verify_storage_proof,
+};
+use helios_core::network_spec::NetworkSpec;
+use helios_ethereum::spec::Ethereum;
+
+/// An account whose proof verified, with the storage slots proven alongside it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VerifiedAccount {
+    address: Address,
+    nonce: u64,
+    balance: U256,
+    code_hash: B256,
+    storage_hash: B256,
+    storage: BTreeMap<B256, U256>,
+}
+
+/// A block with its full transactions, all of its receipts as returned by
+/// `eth_getBlockReceipts`, and the transaction to prove the receipt of.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReceiptBundle {
+    transaction_hash: B256,
+    block: Block,
+    receipts: Vec<TransactionReceipt>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VerifiedBlock {
+    number: u64,
+    hash: B256,
+}
+
+/// Thrown to JavaScript when verification fails, with `kind` telling a malformed input apart
+/// from data that does not match its root.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct VerificationError {
+    kind: ErrorKind,
+    message: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+enum ErrorKind {
+    MalformedInput,
+    InvalidProof,
+    RootMismatch,
+    NotIncluded,
+}
+
+impl VerificationError {
+    fn new(kind: ErrorKind, message: impl ToString) -> JsValue {
+        to_js(&Self {
+            kind,
+            message: message.to_string(),
+        })
+        .unwrap_or_else(|err| err)
+    }
+}
+
+/// Verifies an `eth_getProof` response against `stateRoot`, including any storage proofs it
+/// carries, and returns the proven account.
+#[wasm_bindgen(js_name = verifyAccountProof)]
+pub fn verify_account_proof(proof_json: &str, state_root: &str) -> Result<JsValue, JsValue> {
+    let proof: EIP1186AccountProofResponse = parse("account proof", proof_json)?;
+    let state_root = parse_hash("state root", state_root)?;
+
+    verify_account(&proof, state_root)
+        .map_err(|err| VerificationError::new(ErrorKind::InvalidProof, err))?;
+    let storage = verify_storage_proof(&proof)
+        .map_err(|err| VerificationError::new(ErrorKind::InvalidProof, err))?;
+
+    to_js(&VerifiedAccount {
+        address: proof.address,
+        nonce: proof.nonce,
+        balance: proof.balance,
+        code_hash: proof.code_hash,
+        storage_hash: proof.storage_hash,
+        storage: storage.into_iter().collect(),
+    })
+}
+
+/// Verifies a receipt bundle against the hash of its block and returns the receipt of the
+/// bundle's transaction.
+///
+/// The receipts root commits to neither the transaction hash nor the index a receipt is
+/// labelled with, so the receipt is picked by the place of the transaction in the verified
+/// block instead.
+#[wasm_bindgen(js_name = verifyReceiptProof)]
+pub fn verify_receipt_proof(bundle_json: &str, block_hash_hex: &str) -> Result<JsValue, JsValue> {
+    let bundle: ReceiptBundle = parse("receipt bundle", bundle_json)?;
+    let expected = parse_hash("block hash", block_hash_hex)?;
+
+    let block = &bundle.block;
+    let Some(transactions) = block.transactions.as_transactions() else {
+        let msg = "the block of a receipt bundle must come with its full transactions";
+        return Err(VerificationError::new(ErrorKind::MalformedInput, msg));
+    };
+    check_block(block, expected)?;
+
+    let computed = receipts_root::<Ethereum>(&bundle.receipts);
+    if computed != block.header.receipts_root {
+        let msg = format!(
+            "receipts root mismatch: computed {computed}, expected {}",
+            block.header.receipts_root
+        );
+        return Err(VerificationError::new(ErrorKind::RootMismatch, msg));
+    }
+
+    let index = transactions
+        .iter()
+        .position(|tx| tx.tx_hash() == bundle.transaction_hash)
+        .ok_or_else(|| {
+            let msg = format!(
+                "transaction {} is not in block {}",
+                bundle.transaction_hash, block.header.number
+            );
+            VerificationError::new(ErrorKind::NotIncluded, msg)
+        })?;
+    let receipt = bundle.receipts.get(index).ok_or_else(|| {
+        let msg = format!("no receipt at index {index}");
+        VerificationError::new(ErrorKind::NotIncluded, msg)
+    })?;
+    let relabelled = receipt.transaction_hash != bundle.transaction_hash
+        || receipt.transaction_index.is_some_and(|i| i != index as u64);
+    if relabelled {
+        let msg = format!(
+            "the receipt at index {index} is labelled for transaction {}",
+            receipt.transaction_hash
+        );
+        return Err(VerificationError::new(ErrorKind::InvalidProof, msg));
+    }
+
+    to_js(receipt)
+}
+
+/// Verifies that a block hashes to `expectedHash` and that its transactions and withdrawals
+/// match the roots in its header.
+#[wasm_bindgen(js_name = verifyBlockHash)]
+pub fn verify_block_hash(block_json: &str, expected_hash: &str) -> Result<JsValue, JsValue> {
+    let block: Block = parse("block", block_json)?;
+    let expected = parse_hash("block hash", expected_hash)?;
+    check_block(&block, expected)?;
+
+    to_js(&VerifiedBlock {
+        number: block.header.number,
+        hash: block.header.hash,
+    })
+}
+
+fn check_block(block: &Block, expected: B256) -> Result<(), JsValue> {
+    if !Ethereum::is_hash_valid(block) {
+        let msg = format!(
+            "block {} does not hash to its hash field or its body does not match its header",
+            block.header.number
+        );
+        return Err(VerificationError::new(ErrorKind::InvalidProof, msg));
+    }
+
+    if block.header.hash != expected {
+        let msg = format!(
+            "block hash mismatch: computed {}, expected {expected}",
+            block.header.hash
+        );
+        return Err(VerificationError::new(ErrorKind::RootMismatch, msg));
+    }
+    Ok(())
+}
+
+fn parse<T: DeserializeOwned>(what: &str, json: &str) -> Result<T, JsValue> {
+    serde_json::from_str(json).map_err(|err| {
+        VerificationError::new(ErrorKind::MalformedInput, format!("invalid {what}: {err}"))
+    })
+}
+
+fn parse_hash(what: &str, hex: &str) -> Result<B256, JsValue> {
+    B256::from_str(hex).map_err(|err| {
+        VerificationError::new(ErrorKind::MalformedInput, format!("invalid {what}: {err}"))
+    })
+}
+
+/// Plain objects with hex strings for quantities, as the JSON-RPC would return them.
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    let serializer = serde_wasm_bindgen::Serializer::json_compatible();
+    value.serialize(&serializer).map_err(JsValue::from)
+}
@@ -0,0 +1,112 @@
+// Run with `npm test`, which builds the bindings for node first. The client never syncs in
+// these tests: the consensus endpoints of the mock never answer, so only filters over explicit
+// block ranges, which need no verified head, reach the mock execution provider.
+const test = require("node:test");
+const assert = require("node:assert");
+const http = require("node:http");
+
+const { EthereumClient } = require("../pkg-node/index.js");
+
+// above 2^53, so a filter id passed through a JS number would come back changed
+const FILTER_ID = "0x1f00000000000000000000000000ff";
+const TOKEN = "0x6b175474e89094c44da98b954eedeac495271d0f";
+
+const filter = {
+  address: TOKEN,
+  fromBlock: "0x1312d00",
+  toBlock: "0x1312d0a",
+};
+
+const log = {
+  address: TOKEN,
+  topics: ["0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"],
+  data: "0x",
+  blockNumber: "0x1312d05",
+  blockHash: "0x8fd5c7f5a4c3a5a3b4de40a5f4e3a3d8f8b0d1b4c4f1a6e8e4c1d7d7b6a2c9e1",
+  transactionHash: "0x3f2a1a61c0fcfb2e5b6a6e3b4b6d0e1b9e1c2a3d4f5e6a7b8c9d0e1f2a3b4c5d",
+  transactionIndex: "0x0",
+  logIndex: "0x0",
+  removed: false,
+};
+
+/// An execution provider answering the filter methods from `changes`, recording each
+/// request. Anything else, the consensus endpoints included, is left unanswered.
+function mockProvider(changes) {
+  const requests = [];
+  const server = http.createServer((req, res) => {
+    if (req.method !== "POST") {
+      return;
+    }
+
+    let body = "";
+    req.on("data", (chunk) => (body += chunk));
+    req.on("end", () => {
+      const request = JSON.parse(body);
+      requests.push(request);
+      const results = {
+        eth_newFilter: FILTER_ID,
+        eth_getFilterChanges: changes.shift() ?? [],
+        eth_uninstallFilter: true,
+      };
+      const response =
+        request.method in results
+          ? { jsonrpc: "2.0", id: request.id, result: results[request.method] }
+          : { jsonrpc: "2.0", id: request.id, error: { code: -32601, message: "not found" } };
+      res.setHeader("Content-Type", "application/json");
+      res.end(JSON.stringify(response));
+    });
+  });
+
+  return new Promise((resolve) => {
+    server.listen(0, "127.0.0.1", () => {
+      const url = `http://127.0.0.1:${server.address().port}`;
+      resolve({ url, requests, server });
+    });
+  });
+}
+
+function client(url) {
+  return new EthereumClient(url, `${url}/consensus`, "mainnet", undefined, "config");
+}
+
+test("creates, polls and uninstalls a log filter", async () => {
+  const { url, requests, server } = await mockProvider([[]]);
+  const helios = client(url);
+
+  const id = await helios.new_filter(filter);
+  assert.strictEqual(id, FILTER_ID);
+  const created = requests.find((request) => request.method === "eth_newFilter");
+  assert.strictEqual(created.params[0].fromBlock, filter.fromBlock);
+
+  assert.deepStrictEqual(await helios.get_filter_changes(id), []);
+  const polled = requests.find((request) => request.method === "eth_getFilterChanges");
+  assert.strictEqual(polled.params[0], FILTER_ID);
+
+  assert.strictEqual(await helios.uninstall_filter(id), true);
+  await assert.rejects(helios.get_filter_changes(id), /filter not found/i);
+
+  helios.free();
+  server.unref();
+});
+
+test("logs of a filter are verified before they are returned", async () => {
+  const { url, server } = await mockProvider([[log]]);
+  const helios = client(url);
+
+  const id = await helios.new_filter(filter);
+  // the block of the log was never verified, so the provider's word for it is not taken
+  await assert.rejects(helios.get_filter_changes(id));
+
+  helios.free();
+  server.unref();
+});
+
+test("rejects a filter id that is not a number", async () => {
+  const { url, server } = await mockProvider([]);
+  const helios = client(url);
+
+  await assert.rejects(helios.get_filter_changes("latest"), /invalid filter id/);
+
+  helios.free();
+  server.unref();
+});
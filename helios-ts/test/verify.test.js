@@ -0,0 +1,139 @@
+// Run with `npm test`, which builds the bindings for node first.
+const test = require("node:test");
+const assert = require("node:assert");
+
+const {
+  verifyAccountProof,
+  verifyReceiptProof,
+  verifyBlockHash,
+} = require("../pkg-node/index.js");
+
+const account = require("./fixtures/account_proof.json");
+const receipts = require("./fixtures/receipts.json");
+const block = require("./fixtures/block.json");
+
+function verificationError(kind) {
+  return (err) => err.kind === kind && typeof err.message === "string";
+}
+
+function flipLastByte(hex) {
+  const last = parseInt(hex.slice(-2), 16) ^ 1;
+  return hex.slice(0, -2) + last.toString(16).padStart(2, "0");
+}
+
+test("verifies an account proof with storage", () => {
+  const verified = verifyAccountProof(JSON.stringify(account.proof), account.stateRoot);
+
+  assert.strictEqual(verified.address, account.proof.address);
+  assert.strictEqual(verified.nonce, 5);
+  assert.strictEqual(verified.balance, account.proof.balance);
+  assert.strictEqual(verified.storageHash, account.proof.storageHash);
+
+  const [slot] = account.proof.storageProof;
+  assert.strictEqual(verified.storage[slot.key], slot.value);
+});
+
+test("rejects an account proof against another state root", () => {
+  const root = flipLastByte(account.stateRoot);
+  assert.throws(
+    () => verifyAccountProof(JSON.stringify(account.proof), root),
+    verificationError("invalidProof")
+  );
+});
+
+test("rejects a tampered account balance", () => {
+  const proof = { ...account.proof, balance: "0x1" };
+  assert.throws(
+    () => verifyAccountProof(JSON.stringify(proof), account.stateRoot),
+    verificationError("invalidProof")
+  );
+});
+
+function receiptBundle() {
+  return structuredClone({ ...receipts.bundle, block: block.block });
+}
+
+test("verifies a receipt bundle", () => {
+  const receipt = verifyReceiptProof(JSON.stringify(receiptBundle()), block.hash);
+
+  assert.strictEqual(receipt.transactionHash, receipts.bundle.transactionHash);
+  assert.strictEqual(receipt.logs.length, 1);
+});
+
+test("rejects a receipt bundle that does not match the root", () => {
+  const bundle = receiptBundle();
+  bundle.receipts[0].cumulativeGasUsed = "0x1";
+  assert.throws(
+    () => verifyReceiptProof(JSON.stringify(bundle), block.hash),
+    verificationError("rootMismatch")
+  );
+});
+
+test("rejects a bundle without the transaction", () => {
+  const bundle = { ...receiptBundle(), transactionHash: block.hash };
+  assert.throws(
+    () => verifyReceiptProof(JSON.stringify(bundle), block.hash),
+    verificationError("notIncluded")
+  );
+});
+
+test("rejects the receipt of another transaction relabelled", () => {
+  // the root commits to neither label, so swapping them leaves it unchanged
+  const bundle = receiptBundle();
+  const [first, second] = bundle.receipts;
+  [first.transactionHash, second.transactionHash] = [
+    second.transactionHash,
+    first.transactionHash,
+  ];
+  bundle.transactionHash = second.transactionHash;
+  for (const log of second.logs) {
+    log.transactionHash = second.transactionHash;
+  }
+  assert.throws(
+    () => verifyReceiptProof(JSON.stringify(bundle), block.hash),
+    verificationError("invalidProof")
+  );
+});
+
+test("rejects a receipt bundle without full transactions", () => {
+  const bundle = receiptBundle();
+  bundle.block.transactions = bundle.block.transactions.map((tx) => tx.hash);
+  assert.throws(
+    () => verifyReceiptProof(JSON.stringify(bundle), block.hash),
+    verificationError("malformedInput")
+  );
+});
+
+test("verifies a block hash", () => {
+  const verified = verifyBlockHash(JSON.stringify(block.block), block.hash);
+
+  assert.strictEqual(verified.hash, block.hash);
+  assert.strictEqual(verified.number, 0x1312d00);
+  assert.strictEqual(block.block.receiptsRoot, receipts.receiptsRoot);
+});
+
+test("rejects a block with a tampered header", () => {
+  const tampered = { ...block.block, gasUsed: "0x1" };
+  assert.throws(
+    () => verifyBlockHash(JSON.stringify(tampered), block.hash),
+    verificationError("invalidProof")
+  );
+});
+
+test("rejects a block with another hash", () => {
+  assert.throws(
+    () => verifyBlockHash(JSON.stringify(block.block), flipLastByte(block.hash)),
+    verificationError("rootMismatch")
+  );
+});
+
+test("rejects malformed input", () => {
+  assert.throws(
+    () => verifyBlockHash("{}", block.hash),
+    verificationError("malformedInput")
+  );
+  assert.throws(
+    () => verifyAccountProof(JSON.stringify(account.proof), "0x1234"),
+    verificationError("malformedInput")
+  );
+});
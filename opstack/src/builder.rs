@@ -1,12 +1,30 @@
+use std::sync::Arc;
+
+use alloy::providers::RootProvider;
+use alloy::transports::{BoxTransport, Transport};
 use eyre::Result;
+#[cfg(not(target_arch = "wasm32"))]
+use helios_core::client::ipc::IpcConfig;
+#[cfg(not(target_arch = "wasm32"))]
+use helios_core::client::tls::TlsConfig;
+use helios_core::database::Database;
+use helios_core::execution::config::ExecutionConfig;
+use helios_core::execution::rpc::headers::HeaderConfig;
+use helios_core::execution::rpc::http_rpc::HttpRpc;
+use helios_core::execution::rpc::rate_limit::RateLimitConfig;
+use helios_core::execution::rpc::retry::RetryConfig;
+use helios_core::execution::rpc::timeout::HttpClientConfig;
 use helios_core::fork_schedule::ForkSchedule;
 use reqwest::{IntoUrl, Url};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 
 use crate::{
     config::Network,
     config::{Config, NetworkConfig},
     consensus::ConsensusClient,
+    sequencer::SequencerConfig,
+    spec::OpStack,
     OpStackClient,
 };
 
@@ -16,8 +34,16 @@ pub struct OpStackClientBuilder {
     network: Option<Network>,
     consensus_rpc: Option<Url>,
     execution_rpc: Option<Url>,
+    execution_provider: Option<RootProvider<BoxTransport, OpStack>>,
     rpc_socket: Option<SocketAddr>,
+    rpc_tls: Option<(PathBuf, PathBuf)>,
+    ipc_path: Option<PathBuf>,
     verify_unsafe_signer: Option<bool>,
+    l1_execution_rpc: Option<Url>,
+    strict_deposits: Option<bool>,
+    sequencer: Option<SequencerConfig>,
+    execution_rate_limit: Option<RateLimitConfig>,
+    database: Option<Arc<dyn Database>>,
 }
 
 impl OpStackClientBuilder {
@@ -40,11 +66,33 @@ impl OpStackClientBuilder {
         self
     }
 
+    /// Sends all execution requests through the given provider instead of building a new one
+    /// from the execution rpc url, so that it can be shared between several clients.
+    pub fn execution_provider<T: Transport + Clone>(
+        mut self,
+        provider: RootProvider<T, OpStack>,
+    ) -> Self {
+        self.execution_provider = Some(provider.boxed());
+        self
+    }
+
     pub fn rpc_socket(mut self, socket: SocketAddr) -> Self {
         self.rpc_socket = Some(socket);
         self
     }
 
+    /// Serves the rpc over TLS using a PEM encoded certificate chain and private key.
+    pub fn rpc_tls(mut self, cert_path: PathBuf, key_path: PathBuf) -> Self {
+        self.rpc_tls = Some((cert_path, key_path));
+        self
+    }
+
+    /// Also serves the rpc over a Unix domain socket, or a named pipe on Windows, at `path`.
+    pub fn ipc_path(mut self, path: PathBuf) -> Self {
+        self.ipc_path = Some(path);
+        self
+    }
+
     pub fn network(mut self, network: Network) -> Self {
         self.network = Some(network);
         self
@@ -55,8 +103,53 @@ impl OpStackClientBuilder {
         self
     }
 
+    /// Checks the deposits of unsafe blocks against the receipts of the L1 blocks verified
+    /// alongside the unsafe signer, fetched from this L1 execution rpc.
+    pub fn l1_execution_rpc<T: IntoUrl>(mut self, l1_execution_rpc: T) -> Self {
+        self.l1_execution_rpc = Some(l1_execution_rpc.into_url().unwrap());
+        self
+    }
+
+    /// Rejects unsafe blocks with deposits not matched to a verified L1 event.
+    pub fn strict_deposits(mut self, value: bool) -> Self {
+        self.strict_deposits = Some(value);
+        self
+    }
+
+    /// Watches the feed of unsafe heads for stalls, failing over to the replicas configured.
+    pub fn sequencer(mut self, sequencer: SequencerConfig) -> Self {
+        self.sequencer = Some(sequencer);
+        self
+    }
+
+    /// Holds execution rpc requests to a budget, overriding the `execution_rate_limit` table
+    /// of the config. Requests of a provider given with
+    /// [`OpStackClientBuilder::execution_provider`] are not held to it.
+    pub fn execution_rate_limit(mut self, rate_limit: RateLimitConfig) -> Self {
+        self.execution_rate_limit = Some(rate_limit);
+        self
+    }
+
+    /// Keeps verified headers and cached contract code in `database`, which the client
+    /// otherwise only holds in memory.
+    pub fn with_database(mut self, database: impl Database) -> Self {
+        self.database = Some(Arc::new(database));
+        self
+    }
+
     pub fn build(self) -> Result<OpStackClient> {
-        let config = if let Some(config) = self.config {
+        let rpc_tls = self.rpc_tls.clone();
+        let config = if let Some(mut config) = self.config {
+            if let Some((cert_path, key_path)) = rpc_tls {
+                config.rpc_tls_cert = Some(cert_path);
+                config.rpc_tls_key = Some(key_path);
+            }
+            if let Some(ipc_path) = self.ipc_path {
+                config.ipc_path = Some(ipc_path);
+            }
+            if let Some(rate_limit) = self.execution_rate_limit {
+                config.execution_rate_limit = rate_limit;
+            }
             config
         } else {
             let Some(network) = self.network else {
@@ -75,24 +168,82 @@ impl OpStackClientBuilder {
                 consensus_rpc,
                 execution_rpc,
                 rpc_socket: self.rpc_socket,
+                rpc_tls_cert: rpc_tls.as_ref().map(|(cert_path, _)| cert_path.clone()),
+                rpc_tls_key: rpc_tls.as_ref().map(|(_, key_path)| key_path.clone()),
+                ipc_path: self.ipc_path,
                 chain: NetworkConfig::from(network).chain,
                 load_external_fallback: None,
                 checkpoint: None,
                 verify_unsafe_signer: self.verify_unsafe_signer.unwrap_or_default(),
+                l1_execution_rpc: self.l1_execution_rpc,
+                strict_deposits: self.strict_deposits.unwrap_or_default(),
+                sequencer: self.sequencer.unwrap_or_default(),
+                backfill: Default::default(),
+                execution_rate_limit: self.execution_rate_limit.unwrap_or_default(),
             }
         };
 
         let fork_schedule = ForkSchedule {
             prague_timestamp: u64::MAX,
+            merge_block: 0,
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let tls = match (&config.rpc_tls_cert, &config.rpc_tls_key) {
+            (Some(cert_path), Some(key_path)) => Some(TlsConfig {
+                cert_path: cert_path.clone(),
+                key_path: key_path.clone(),
+            }),
+            (None, None) => None,
+            _ => eyre::bail!("rpc_tls_cert and rpc_tls_key must be set together"),
         };
 
         let consensus = ConsensusClient::new(&config);
-        OpStackClient::new(
-            config.execution_rpc.as_ref(),
+        let execution = ExecutionConfig {
+            database: self.database,
+            backfill: config.backfill,
+            broadcast_rate_limit: config.execution_rate_limit.clone(),
+            ..Default::default()
+        };
+
+        let execution_rpc = match self.execution_provider {
+            Some(provider) => HttpRpc::from_provider(config.execution_rpc.as_str(), provider),
+            None => HttpRpc::connect(
+                config.execution_rpc.as_str(),
+                true,
+                RetryConfig::default(),
+                &config.execution_rate_limit,
+                HttpClientConfig::default(),
+                &HeaderConfig::default(),
+            )?,
+        };
+        let client = OpStackClient::with_execution_rpc(
+            execution_rpc,
             consensus,
             fork_schedule,
+            execution,
             #[cfg(not(target_arch = "wasm32"))]
             config.rpc_socket,
-        )
+        );
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let client = match tls {
+            Some(tls) => client.with_rpc_tls(tls),
+            None => client,
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let client = match config.ipc_path.clone() {
+            Some(path) => client.with_rpc_ipc(IpcConfig::new(path)),
+            None => client,
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let client = {
+            let methods = crate::rpc::methods(&client);
+            client.with_rpc_methods(methods)?
+        };
+
+        Ok(client)
     }
 }
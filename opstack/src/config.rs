@@ -9,19 +9,47 @@ use figment::{
     value::Value,
     Figment,
 };
+use helios_core::execution::backfill::BackfillConfig;
+use helios_core::execution::rpc::rate_limit::RateLimitConfig;
 use helios_ethereum::config::networks::Network as EthNetwork;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
+use crate::sequencer::SequencerConfig;
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
     pub consensus_rpc: Url,
     pub execution_rpc: Url,
     pub rpc_socket: Option<SocketAddr>,
+    /// PEM certificate chain, serves the rpc over TLS together with `rpc_tls_key`.
+    pub rpc_tls_cert: Option<PathBuf>,
+    pub rpc_tls_key: Option<PathBuf>,
+    /// Unix domain socket, or named pipe on Windows, the rpc is also served on.
+    #[serde(default)]
+    pub ipc_path: Option<PathBuf>,
     pub chain: ChainConfig,
     pub load_external_fallback: Option<bool>,
     pub checkpoint: Option<B256>,
     pub verify_unsafe_signer: bool,
+    /// Execution rpc of the L1 chain, for the L1 receipts deposits are checked against. Only
+    /// used along with `verify_unsafe_signer`, which runs the L1 light client.
+    pub l1_execution_rpc: Option<Url>,
+    /// Rejects unsafe blocks with deposits that could not be matched to a verified L1 event,
+    /// instead of applying them as unverified.
+    #[serde(default)]
+    pub strict_deposits: bool,
+    /// How a stalled feed of unsafe heads is noticed and failed over, from the `sequencer`
+    /// table.
+    #[serde(default)]
+    pub sequencer: SequencerConfig,
+    /// Concurrency and retries of the startup backfill of recent blocks, from the `backfill`
+    /// table.
+    #[serde(default)]
+    pub backfill: BackfillConfig,
+    /// The budget execution rpc requests are held to, from the `execution_rate_limit` table.
+    #[serde(default)]
+    pub execution_rate_limit: RateLimitConfig,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -29,6 +57,8 @@ pub struct ChainConfig {
     pub chain_id: u64,
     pub unsafe_signer: Address,
     pub system_config_contract: Address,
+    /// The `OptimismPortal` emitting the deposit events of the chain on L1.
+    pub optimism_portal: Option<Address>,
     pub eth_network: EthNetwork,
 }
 
@@ -72,6 +102,18 @@ impl FromStr for Network {
     }
 }
 
+impl Network {
+    pub fn from_chain_id(id: u64) -> Result<Self> {
+        match id {
+            10 => Ok(Self::OpMainnet),
+            8453 => Ok(Self::Base),
+            480 => Ok(Self::Worldchain),
+            7777777 => Ok(Self::Zora),
+            _ => Err(eyre::eyre!("chain id not known")),
+        }
+    }
+}
+
 impl From<Network> for NetworkConfig {
     fn from(value: Network) -> Self {
         match value {
@@ -85,6 +127,7 @@ impl From<Network> for NetworkConfig {
                     chain_id: 10,
                     unsafe_signer: address!("AAAA45d9549EDA09E70937013520214382Ffc4A2"),
                     system_config_contract: address!("229047fed2591dbec1eF1118d64F7aF3dB9EB290"),
+                    optimism_portal: Some(address!("bEb5Fc579115071764c7423A4f12eDde41f106Ed")),
                     eth_network: EthNetwork::Mainnet,
                 },
                 verify_unsafe_signer: false,
@@ -95,6 +138,7 @@ impl From<Network> for NetworkConfig {
                     chain_id: 8453,
                     unsafe_signer: address!("Af6E19BE0F9cE7f8afd49a1824851023A8249e8a"),
                     system_config_contract: address!("73a79Fab69143498Ed3712e519A88a918e1f4072"),
+                    optimism_portal: Some(address!("49048044D57e1C92A77f79988d21Fa8fAF74E97e")),
                     eth_network: EthNetwork::Mainnet,
                 },
                 verify_unsafe_signer: false,
@@ -109,6 +153,7 @@ impl From<Network> for NetworkConfig {
                     chain_id: 480,
                     unsafe_signer: address!("2270d6eC8E760daA317DD978cFB98C8f144B1f3A"),
                     system_config_contract: address!("6ab0777fD0e609CE58F939a7F70Fe41F5Aa6300A"),
+                    optimism_portal: Some(address!("d5ec14a83B7d95BE1E2Ac12523e2dEE12Cbeea6C")),
                     eth_network: EthNetwork::Mainnet,
                 },
                 verify_unsafe_signer: false,
@@ -119,6 +164,7 @@ impl From<Network> for NetworkConfig {
                     chain_id: 7777777,
                     unsafe_signer: address!("3Dc8Dfd0709C835cAd15a6A27e089FF4cF4C9228"),
                     system_config_contract: address!("A3cAB0126d5F504B071b81a3e8A2BBBF17930d86"),
+                    optimism_portal: Some(address!("1a0ad011913A150f69f6A19DF447A0CfD9551054")),
                     eth_network: EthNetwork::Mainnet,
                 },
                 verify_unsafe_signer: false,
@@ -19,45 +19,98 @@ use tokio::sync::{
     mpsc::{channel, Receiver},
     watch,
 };
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
+use url::Url;
 
 use helios_consensus_core::consensus_spec::MainnetConsensusSpec;
 use helios_core::consensus::Consensus;
 use helios_core::execution::proof::{verify_account_proof, verify_mpt_proof};
+use helios_core::execution::rpc::{http_rpc::HttpRpc as ExecutionHttpRpc, ExecutionRpc};
+use helios_core::network_spec::NetworkSpec;
 use helios_core::time::{interval, SystemTime, UNIX_EPOCH};
+use helios_core::types::{redact_url, BlockTag, HeadFeed, L1Info, NetworkInfo};
 use helios_ethereum::consensus::ConsensusClient as EthConsensusClient;
+use helios_ethereum::spec::Ethereum;
 
 use helios_ethereum::database::ConfigDB;
 use helios_ethereum::rpc::http_rpc::HttpRpc;
 
-use crate::{config::Config, types::ExecutionPayload, SequencerCommitment};
+use crate::{
+    config::{Config, Network},
+    deposits::{check_deposits, index_l1_blocks, L1DepositIndex, L1_DEPOSIT_LOOKBACK},
+    l1_origin::VerifiedL1Blocks,
+    sequencer::SequencerFeed,
+    spec::OpStack,
+    types::ExecutionPayload,
+    SequencerCommitment,
+};
 
 // Storage slot containing the unsafe signer address in all superchain system config contracts
 const UNSAFE_SIGNER_SLOT: &str =
     "0x65a7ed542fb37fe237fdfbdd70b31598523fe5b32879e307bae27a0bd9581c08";
 
+// Seconds between checks of the safe block the execution rpc reports
+const SAFE_BLOCK_INTERVAL: u64 = 12;
+
 pub struct ConsensusClient {
     block_recv: Option<Receiver<Block<Transaction>>>,
     finalized_block_recv: Option<watch::Receiver<Option<Block<Transaction>>>>,
+    safe_block_recv: Option<watch::Receiver<Option<Block<Transaction>>>>,
+    head_feed_recv: Option<watch::Receiver<HeadFeed>>,
     chain_id: u64,
+    unsafe_signer: Arc<Mutex<Address>>,
+    verified_l1_blocks: Option<VerifiedL1Blocks>,
+    config: Config,
+    shutdown_send: watch::Sender<bool>,
 }
 
 impl ConsensusClient {
     pub fn new(config: &Config) -> Self {
         let (block_send, block_recv) = channel(256);
         let (finalized_block_send, finalized_block_recv) = watch::channel(None);
+        let (safe_block_send, safe_block_recv) = watch::channel(None);
+        let (shutdown_send, shutdown_recv) = watch::channel(false);
+
+        let verified_l1_blocks = config.verify_unsafe_signer.then(VerifiedL1Blocks::default);
+        // deposits are checked against the L1 blocks the L1 light client verifies
+        let deposits = verified_l1_blocks
+            .as_ref()
+            .and(config.l1_execution_rpc.as_ref())
+            .and(config.chain.optimism_portal)
+            .map(L1DepositIndex::new);
+
+        let feed = SequencerFeed::new(&config.consensus_rpc, &config.sequencer, unix_now());
+        let head_feed_recv = feed.head_feed();
+        // safe blocks and replica ancestors are fetched from it, and only ever checked
+        // against the verified heads
+        let execution = ExecutionHttpRpc::<OpStack>::new(config.execution_rpc.as_str())
+            .map_err(|err| warn!(target: "helios::opstack", "no execution rpc for failover: {err}"))
+            .ok();
 
         let mut inner = Inner {
-            server_url: config.consensus_rpc.to_string(),
+            feed,
+            execution,
             unsafe_signer: Arc::new(Mutex::new(config.chain.unsafe_signer)),
             chain_id: config.chain.chain_id,
-            latest_block: None,
+            deposits: deposits.clone(),
+            verified_l1_blocks: verified_l1_blocks.clone(),
+            strict_deposits: config.strict_deposits,
             block_send,
             finalized_block_send,
+            safe_block_send,
+            safe_block: None,
+            safe_checked_at: 0,
         };
 
-        if config.verify_unsafe_signer {
-            verify_unsafe_signer(config.clone(), inner.unsafe_signer.clone());
+        let unsafe_signer = inner.unsafe_signer.clone();
+        if let Some(l1_blocks) = &verified_l1_blocks {
+            verify_unsafe_signer(
+                config.clone(),
+                unsafe_signer.clone(),
+                l1_blocks.clone(),
+                deposits,
+                shutdown_recv.clone(),
+            );
         }
 
         #[cfg(not(target_arch = "wasm32"))]
@@ -66,22 +119,51 @@ impl ConsensusClient {
         #[cfg(target_arch = "wasm32")]
         let run = wasm_bindgen_futures::spawn_local;
 
+        let mut shutdown_recv = shutdown_recv;
         run(async move {
             let mut interval = interval(Duration::from_secs(1));
             loop {
-                if let Err(e) = inner.advance().await {
-                    error!(target: "helios::opstack", "failed to advance: {}", e);
+                tokio::select! {
+                    changed = shutdown_recv.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        if *shutdown_recv.borrow_and_update() {
+                            info!(target: "helios::opstack", "shutting down consensus client");
+                            // paused until restarted, or for good once the client is dropped
+                            if shutdown_recv.wait_for(|shutdown| !shutdown).await.is_err() {
+                                break;
+                            }
+                            info!(target: "helios::opstack", "restarting consensus client");
+                        }
+                    }
+                    _ = interval.tick() => {
+                        if let Err(e) = inner.advance().await {
+                            error!(target: "helios::opstack", "failed to advance: {}", e);
+                        }
+                    }
                 }
-                interval.tick().await;
             }
         });
 
         Self {
             block_recv: Some(block_recv),
             finalized_block_recv: Some(finalized_block_recv),
+            safe_block_recv: Some(safe_block_recv),
+            head_feed_recv: Some(head_feed_recv),
             chain_id: config.chain.chain_id,
+            unsafe_signer,
+            verified_l1_blocks,
+            config: config.clone(),
+            shutdown_send,
         }
     }
+
+    /// L1 blocks verified by the in-process L1 light client, which only runs when the
+    /// unsafe signer is verified against L1.
+    pub fn verified_l1_blocks(&self) -> Option<VerifiedL1Blocks> {
+        self.verified_l1_blocks.clone()
+    }
 }
 
 impl Consensus<Block<Transaction>> for ConsensusClient {
@@ -89,7 +171,40 @@ impl Consensus<Block<Transaction>> for ConsensusClient {
         self.chain_id
     }
 
+    fn network_info(&self) -> NetworkInfo {
+        let network = Network::from_chain_id(self.chain_id)
+            .ok()
+            .map(|network| network.to_string());
+
+        // L1 linkage is only meaningful when the signer is verified against L1 state
+        let l1 = self.config.verify_unsafe_signer.then(|| L1Info {
+            network: self.config.chain.eth_network.to_string(),
+            system_config_contract: self.config.chain.system_config_contract,
+            unsafe_signer: *self.unsafe_signer.lock().unwrap(),
+        });
+
+        NetworkInfo {
+            chain_id: self.chain_id,
+            network,
+            genesis_time: None,
+            genesis_validators_root: None,
+            forks: Vec::new(),
+            current_fork: None,
+            weak_subjectivity_period: None,
+            checkpoint: None,
+            consensus_rpc: Some(redact_url(self.config.consensus_rpc.as_str())),
+            execution_rpc: Some(redact_url(self.config.execution_rpc.as_str())),
+            l1,
+        }
+    }
+
     fn shutdown(&self) -> eyre::Result<()> {
+        self.shutdown_send.send(true)?;
+        Ok(())
+    }
+
+    fn restart(&self) -> eyre::Result<()> {
+        self.shutdown_send.send(false)?;
         Ok(())
     }
 
@@ -101,6 +216,14 @@ impl Consensus<Block<Transaction>> for ConsensusClient {
         self.finalized_block_recv.take()
     }
 
+    fn safe_block_recv(&mut self) -> Option<watch::Receiver<Option<Block<Transaction>>>> {
+        self.safe_block_recv.take()
+    }
+
+    fn head_feed_recv(&mut self) -> Option<watch::Receiver<HeadFeed>> {
+        self.head_feed_recv.take()
+    }
+
     fn expected_highest_block(&self) -> u64 {
         u64::MAX
     }
@@ -108,17 +231,64 @@ impl Consensus<Block<Transaction>> for ConsensusClient {
 
 #[allow(dead_code)]
 struct Inner {
-    server_url: String,
+    feed: SequencerFeed,
+    execution: Option<ExecutionHttpRpc<OpStack>>,
     unsafe_signer: Arc<Mutex<Address>>,
     chain_id: u64,
-    latest_block: Option<u64>,
+    deposits: Option<L1DepositIndex>,
+    verified_l1_blocks: Option<VerifiedL1Blocks>,
+    strict_deposits: bool,
     block_send: Sender<Block<Transaction>>,
     finalized_block_send: watch::Sender<Option<Block<Transaction>>>,
+    safe_block_send: watch::Sender<Option<Block<Transaction>>>,
+    safe_block: Option<u64>,
+    safe_checked_at: u64,
 }
 
 impl Inner {
     pub async fn advance(&mut self) -> Result<()> {
-        let req = format!("{}latest", self.server_url);
+        let followed = self.follow().await;
+
+        let now = unix_now();
+        if self.feed.check(now) {
+            for index in self.feed.failover_candidates(now) {
+                if let Err(err) = self.fail_over(index).await {
+                    let endpoint = redact_url(self.feed.candidate(index).as_str());
+                    warn!(target: "helios::opstack", %endpoint, "rejected sequencer replica: {err}");
+                } else {
+                    break;
+                }
+            }
+        }
+
+        if let Err(err) = self.update_safe_block(now).await {
+            debug!(target: "helios::opstack", "failed to update the safe block: {err}");
+        }
+
+        followed
+    }
+
+    async fn follow(&mut self) -> Result<()> {
+        let endpoint = self.feed.endpoint().clone();
+        let Some(block) = self.fetch_head(&endpoint).await? else {
+            return Ok(());
+        };
+
+        if self
+            .feed
+            .latest()
+            .map(|latest| block.header.number > latest)
+            .unwrap_or(true)
+        {
+            self.apply(block).await;
+        }
+
+        Ok(())
+    }
+
+    /// The head `endpoint` serves, unless it is not signed by the unsafe signer.
+    async fn fetch_head(&self, endpoint: &Url) -> Result<Option<Block<Transaction>>> {
+        let req = format!("{endpoint}latest");
         let commitment = reqwest::get(req)
             .await?
             .json::<SequencerCommitment>()
@@ -128,41 +298,106 @@ impl Inner {
             .unsafe_signer
             .lock()
             .map_err(|_| eyre!("failed to lock signer"))?;
-        if commitment.verify(curr_signer, self.chain_id).is_ok() {
-            let payload = ExecutionPayload::try_from(&commitment)?;
-            if self
-                .latest_block
-                .map(|latest| payload.block_number > latest)
-                .unwrap_or(true)
-            {
-                let now = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap_or_else(|_| panic!("unreachable"));
-
-                let timestamp = Duration::from_secs(payload.timestamp);
-                let age = now.saturating_sub(timestamp);
-                let number = payload.block_number;
-
-                if let Ok(block) = payload_to_block(payload) {
-                    self.latest_block = Some(block.header.number);
-                    _ = self.block_send.send(block).await;
-
-                    tracing::info!(
-                        "unsafe head updated: block={} age={}s",
-                        number,
-                        age.as_secs()
-                    );
-                } else {
-                    tracing::warn!("invalid block received");
+        if commitment.verify(curr_signer, self.chain_id).is_err() {
+            return Ok(None);
+        }
+
+        let payload = ExecutionPayload::try_from(&commitment)?;
+        match payload_to_block(payload) {
+            Ok(block) => Ok(Some(block)),
+            Err(_) => {
+                tracing::warn!("invalid block received");
+                Ok(None)
+            }
+        }
+    }
+
+    async fn apply(&mut self, block: Block<Transaction>) {
+        let number = block.header.number;
+        if let (Some(deposits), Some(l1_blocks)) = (&self.deposits, &self.verified_l1_blocks) {
+            match check_deposits(&block, deposits, l1_blocks, self.strict_deposits) {
+                Ok(check) => {
+                    // later blocks only include the deposits of this origin or later ones
+                    if let Some(info) = check.l1_info {
+                        deposits.prune_before(info.number.saturating_sub(L1_DEPOSIT_LOOKBACK));
+                    }
+                }
+                Err(err) => {
+                    warn!(target: "helios::opstack", block = number, "rejected block: {err}");
+                    return;
                 }
             }
         }
 
+        let age = unix_now().saturating_sub(block.header.timestamp);
+        self.feed.accept(&block.header);
+        _ = self.block_send.send(block).await;
+
+        tracing::info!("unsafe head updated: block={} age={}s", number, age);
+    }
+
+    /// Follows the endpoint at `index` from now on if it serves a head newer than the
+    /// verified ones that chains onto them, walking its ancestors down to a verified height.
+    async fn fail_over(&mut self, index: usize) -> Result<()> {
+        let endpoint = self.feed.candidate(index).clone();
+        let block = self
+            .fetch_head(&endpoint)
+            .await?
+            .ok_or_eyre("head not signed by the unsafe signer")?;
+        let number = block.header.number;
+        if self.feed.latest().is_some_and(|latest| number <= latest) {
+            eyre::bail!("head {number} is no newer than the verified one");
+        }
+
+        let rpc = self.execution.as_ref().ok_or_eyre("no execution rpc")?;
+        let branch = self.feed.fetch_ancestry(rpc, block.header.clone()).await?;
+        self.feed.switch_to(index, &branch)?;
+        info!(target: "helios::opstack", endpoint = %redact_url(endpoint.as_str()), "failed over to sequencer replica");
+        self.apply(block).await;
+        Ok(())
+    }
+
+    /// Publishes the safe block the execution rpc reports once it is one of the verified
+    /// heads, so the rpc only vouches for how far derivation got.
+    async fn update_safe_block(&mut self, now: u64) -> Result<()> {
+        if now.saturating_sub(self.safe_checked_at) < SAFE_BLOCK_INTERVAL {
+            return Ok(());
+        }
+        self.safe_checked_at = now;
+
+        let rpc = self.execution.as_ref().ok_or_eyre("no execution rpc")?;
+        let Some(block) = rpc.get_block_by_number(BlockTag::Safe, true).await? else {
+            return Ok(());
+        };
+        let number = block.header.number;
+        if self.safe_block.is_some_and(|safe| number <= safe) {
+            return Ok(());
+        }
+
+        let verified = self.feed.verified_hash(number) == Some(block.header.hash);
+        if !verified || !OpStack::is_hash_valid(&block) {
+            eyre::bail!("safe block {number} is not a verified head");
+        }
+        self.safe_block = Some(number);
+        self.safe_block_send.send_replace(Some(block));
         Ok(())
     }
 }
 
-fn verify_unsafe_signer(config: Config, signer: Arc<Mutex<Address>>) {
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_else(|_| panic!("unreachable"))
+        .as_secs()
+}
+
+fn verify_unsafe_signer(
+    config: Config,
+    signer: Arc<Mutex<Address>>,
+    l1_blocks: VerifiedL1Blocks,
+    deposits: Option<L1DepositIndex>,
+    mut shutdown_recv: watch::Receiver<bool>,
+) {
     #[cfg(not(target_arch = "wasm32"))]
     let run = tokio::spawn;
 
@@ -187,12 +422,22 @@ fn verify_unsafe_signer(config: Config, signer: Arc<Mutex<Address>>) {
                     Arc::new(eth_config.into()),
                 )?;
 
-            let block = eth_consensus
-                .block_recv()
-                .unwrap()
+            let mut block_recv = eth_consensus.block_recv().unwrap();
+            let l1_execution = match (&deposits, &config.l1_execution_rpc) {
+                (Some(deposits), Some(rpc)) => {
+                    Some((deposits, ExecutionHttpRpc::<Ethereum>::new(rpc.as_str())?))
+                }
+                _ => None,
+            };
+
+            let block = block_recv
                 .recv()
                 .await
                 .ok_or_eyre("failed to receive block")?;
+            l1_blocks.insert(block.header.number, block.header.hash);
+            if let Some((deposits, rpc)) = &l1_execution {
+                index_deposits(deposits, &l1_blocks, rpc, &block.header).await;
+            }
 
             // Query proof from op consensus server
             let req = format!(
@@ -242,7 +487,33 @@ fn verify_unsafe_signer(config: Config, signer: Arc<Mutex<Address>>) {
                 }
             }
 
-            // Shutdown eth consensus client
+            // Keep following L1 so the origins and deposits of L2 blocks can be checked
+            // against it, pausing the L1 light client along with this client
+            loop {
+                tokio::select! {
+                    changed = shutdown_recv.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                        if *shutdown_recv.borrow_and_update() {
+                            eth_consensus.shutdown()?;
+                        } else {
+                            eth_consensus.restart()?;
+                        }
+                    }
+                    block = block_recv.recv() => {
+                        let Some(block) = block else {
+                            break;
+                        };
+                        l1_blocks.insert(block.header.number, block.header.hash);
+
+                        if let Some((deposits, rpc)) = &l1_execution {
+                            index_deposits(deposits, &l1_blocks, rpc, &block.header).await;
+                        }
+                    }
+                }
+            }
+
             eth_consensus.shutdown()?;
 
             Ok(())
@@ -252,6 +523,19 @@ fn verify_unsafe_signer(config: Config, signer: Arc<Mutex<Address>>) {
     });
 }
 
+/// Indexes the deposits of a verified L1 head and of the blocks the light client skipped
+/// before it, leaving what fails to the next head.
+async fn index_deposits(
+    deposits: &L1DepositIndex,
+    l1_blocks: &VerifiedL1Blocks,
+    rpc: &ExecutionHttpRpc<Ethereum>,
+    head: &Header,
+) {
+    if let Err(err) = index_l1_blocks(deposits, l1_blocks, rpc, head).await {
+        warn!(target: "helios::opstack", l1_block = head.number, "failed to index deposits: {err}");
+    }
+}
+
 fn payload_to_block(value: ExecutionPayload) -> Result<Block<Transaction>> {
     let empty_nonce = fixed_bytes!("0000000000000000");
     let empty_uncle_hash =
@@ -0,0 +1,417 @@
+//! Verification of the deposit transactions of unsafe blocks against L1.
+//!
+//! Deposits are derived from L1, so the sequencer signature alone does not prove them: every
+//! user deposit must match a `TransactionDeposited` event of the `OptimismPortal`, found
+//! through its source hash, which commits to the L1 block and log the event came from. The
+//! events are indexed from the receipts of L1 blocks verified by the in-process L1 light
+//! client, after checking the receipts against the receipts root of the block. L1 blocks the
+//! light client skipped, or that came before its first head, are fetched by the parent hash
+//! of the block after them, so that a deposit is never left unmatched for want of its block.
+//!
+//! The L1 attributes deposit leading every block names its L1 origin, which is checked
+//! against the verified L1 block at that height. Deposits of an L1 block are kept until the
+//! L2 chain has moved well past it as its origin.
+//!
+//! Network upgrade transactions, which are deposits without an L1 event, are never matched.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use alloy::eips::BlockId;
+use alloy::primitives::{keccak256, Address, Bytes, TxKind, B256, U256};
+use alloy::rpc::types::{Block, Header, Log, TransactionReceipt};
+use alloy::sol;
+use alloy::sol_types::SolEvent;
+use eyre::{eyre, Result};
+use op_alloy_consensus::{OpTxEnvelope, TxDeposit};
+use op_alloy_rpc_types::Transaction;
+use tracing::warn;
+
+use helios_core::execution::proof::receipts_root;
+use helios_core::execution::rpc::ExecutionRpc;
+use helios_core::network_spec::NetworkSpec;
+use helios_ethereum::spec::Ethereum;
+
+use crate::l1_origin::{L1BlockInfo, L1OriginCheck, VerifiedL1Blocks, L1_INFO_DEPOSITOR};
+
+sol! {
+    event TransactionDeposited(
+        address indexed from,
+        address indexed to,
+        uint256 indexed version,
+        bytes opaqueData
+    );
+}
+
+/// Source hash domain of deposits of `TransactionDeposited` events.
+const USER_DEPOSIT_DOMAIN: u8 = 0;
+
+/// Source hash domain of the L1 attributes deposit.
+const L1_INFO_DEPOSIT_DOMAIN: u8 = 1;
+
+/// Length of the packed `mint`, `value`, `gasLimit` and `isCreation` leading the opaque data.
+const OPAQUE_DATA_PREFIX_LEN: usize = 73;
+
+/// L1 blocks indexed at most behind a verified head, walking down to the one indexed last or
+/// to this depth behind the first head. Deposits of an L1 block are likewise kept until the
+/// origin of the L2 chain is this many blocks past it.
+pub const L1_DEPOSIT_LOOKBACK: u64 = 64;
+
+fn source_hash(domain: u8, l1_block_hash: B256, index: u64) -> B256 {
+    let index = B256::from(U256::from(index));
+    let deposit_id = keccak256([l1_block_hash.as_slice(), index.as_slice()].concat());
+    let domain = B256::with_last_byte(domain);
+    keccak256([domain.as_slice(), deposit_id.as_slice()].concat())
+}
+
+/// The source hash of the deposit of the event at `log_index` of the L1 block.
+pub fn user_deposit_source_hash(l1_block_hash: B256, log_index: u64) -> B256 {
+    source_hash(USER_DEPOSIT_DOMAIN, l1_block_hash, log_index)
+}
+
+/// The source hash of the L1 attributes deposit of the L2 block at `sequence_number` in the
+/// epoch of the L1 block.
+pub fn l1_info_source_hash(l1_block_hash: B256, sequence_number: u64) -> B256 {
+    source_hash(L1_INFO_DEPOSIT_DOMAIN, l1_block_hash, sequence_number)
+}
+
+/// A deposit as requested by a `TransactionDeposited` event on L1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct L1Deposit {
+    pub l1_block_number: u64,
+    pub source_hash: B256,
+    pub from: Address,
+    pub to: TxKind,
+    pub mint: u128,
+    pub value: U256,
+    pub gas_limit: u64,
+    pub input: Bytes,
+}
+
+impl L1Deposit {
+    /// Decodes the deposit of a version 0 `TransactionDeposited` event of an L1 block.
+    pub fn from_log(log: &Log, l1_block_number: u64, l1_block_hash: B256) -> Result<Self> {
+        let event = TransactionDeposited::decode_log_data(log.data(), true)?;
+        if event.version != U256::ZERO {
+            eyre::bail!("unknown deposit version {}", event.version);
+        }
+        let log_index = log
+            .log_index
+            .ok_or_else(|| eyre!("deposit event without a log index"))?;
+
+        let data = &event.opaqueData;
+        if data.len() < OPAQUE_DATA_PREFIX_LEN {
+            eyre::bail!("deposit data is {} bytes", data.len());
+        }
+        let mint = U256::from_be_slice(&data[..32]);
+        let value = U256::from_be_slice(&data[32..64]);
+        let gas_limit = u64::from_be_bytes(data[64..72].try_into().unwrap());
+        let to = match data[72] {
+            0 => TxKind::Call(event.to),
+            _ => TxKind::Create,
+        };
+
+        Ok(Self {
+            l1_block_number,
+            source_hash: user_deposit_source_hash(l1_block_hash, log_index),
+            from: event.from,
+            to,
+            mint: mint.try_into().map_err(|_| eyre!("deposit mints {mint}"))?,
+            value,
+            gas_limit,
+            input: Bytes::copy_from_slice(&data[OPAQUE_DATA_PREFIX_LEN..]),
+        })
+    }
+
+    /// Checks that `tx` carries out this deposit, naming the first field that differs.
+    pub fn check(&self, tx: &TxDeposit) -> Result<()> {
+        let mismatch = if tx.from != self.from {
+            "from"
+        } else if tx.to != self.to {
+            "to"
+        } else if tx.mint.unwrap_or_default() != self.mint {
+            "mint"
+        } else if tx.value != self.value {
+            "value"
+        } else if tx.gas_limit != self.gas_limit {
+            "gas limit"
+        } else if tx.is_system_transaction {
+            "system flag"
+        } else if tx.input != self.input {
+            "input"
+        } else {
+            return Ok(());
+        };
+
+        Err(eyre!(
+            "deposit {} does not match the l1 event: {mismatch} differs",
+            self.source_hash
+        ))
+    }
+}
+
+/// Deposits of verified L1 blocks, by source hash.
+///
+/// Clones share the same deposits.
+#[derive(Debug, Clone)]
+pub struct L1DepositIndex {
+    portal: Address,
+    inner: Arc<Mutex<IndexInner>>,
+}
+
+#[derive(Debug, Default)]
+struct IndexInner {
+    deposits: HashMap<B256, L1Deposit>,
+    blocks: BTreeMap<u64, Vec<B256>>,
+}
+
+impl L1DepositIndex {
+    /// Indexes the deposits of the `OptimismPortal` at `portal`.
+    pub fn new(portal: Address) -> Self {
+        Self {
+            portal,
+            inner: Arc::default(),
+        }
+    }
+
+    /// Indexes the deposits of the L1 block `hash`, after checking its receipts against the
+    /// verified `receipts_root` of the block. Returns the number of deposits found.
+    pub fn insert_block(
+        &self,
+        number: u64,
+        hash: B256,
+        receipts_root: B256,
+        receipts: &[TransactionReceipt],
+    ) -> Result<usize> {
+        if receipts_root::<Ethereum>(receipts) != receipts_root {
+            eyre::bail!("receipts of l1 block {number} do not match its receipts root");
+        }
+
+        let deposits = receipts
+            .iter()
+            .flat_map(|receipt| receipt.inner.logs())
+            .filter(|log| {
+                log.address() == self.portal
+                    && log.topics().first() == Some(&TransactionDeposited::SIGNATURE_HASH)
+            })
+            .map(|log| L1Deposit::from_log(log, number, hash))
+            .collect::<Result<Vec<_>>>()?;
+        let count = deposits.len();
+
+        let mut inner = self.inner.lock().unwrap();
+        let hashes = deposits.iter().map(|deposit| deposit.source_hash).collect();
+        if let Some(replaced) = inner.blocks.insert(number, hashes) {
+            // the block at this height was reorged out
+            for source_hash in replaced {
+                inner.deposits.remove(&source_hash);
+            }
+        }
+        for deposit in deposits {
+            inner.deposits.insert(deposit.source_hash, deposit);
+        }
+
+        Ok(count)
+    }
+
+    pub fn get(&self, source_hash: &B256) -> Option<L1Deposit> {
+        self.inner
+            .lock()
+            .unwrap()
+            .deposits
+            .get(source_hash)
+            .cloned()
+    }
+
+    /// The number of the highest L1 block indexed.
+    pub fn latest_block(&self) -> Option<u64> {
+        let inner = self.inner.lock().unwrap();
+        inner.blocks.last_key_value().map(|(number, _)| *number)
+    }
+
+    /// Forgets the deposits of L1 blocks before `number`. Deposits are included in the first
+    /// L2 block with their L1 block as its origin, so once the origin of applied L2 blocks is
+    /// past a block its deposits are no longer looked up, however far behind the L1
+    /// finalized block that origin is.
+    pub fn prune_before(&self, number: u64) {
+        let mut inner = self.inner.lock().unwrap();
+        let kept = inner.blocks.split_off(&number);
+        let pruned = std::mem::replace(&mut inner.blocks, kept);
+        for source_hash in pruned.into_values().flatten() {
+            inner.deposits.remove(&source_hash);
+        }
+    }
+
+    /// The number of deposits indexed.
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().deposits.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Indexes the deposits of the verified L1 block `head`, and of the blocks behind it not
+/// indexed yet, oldest first.
+///
+/// The blocks behind are fetched by the parent hash of the block after them, down to the
+/// block after the one indexed last, or [`L1_DEPOSIT_LOOKBACK`] blocks behind the first
+/// head, which makes them as verified as `head` and adds them to `l1_blocks` too. Stops at
+/// the first block whose receipts cannot be indexed, leaving it and the blocks after it to
+/// be walked down to again with the next head.
+pub async fn index_l1_blocks<R: ExecutionRpc<Ethereum>>(
+    index: &L1DepositIndex,
+    l1_blocks: &VerifiedL1Blocks,
+    rpc: &R,
+    head: &Header,
+) -> Result<()> {
+    let lowest = match index.latest_block() {
+        Some(latest) if latest < head.number => latest + 1,
+        Some(_) => head.number,
+        None => head.number.saturating_sub(L1_DEPOSIT_LOOKBACK),
+    }
+    .max(head.number.saturating_sub(L1_DEPOSIT_LOOKBACK));
+
+    let mut headers = vec![head.clone()];
+    while let Some(child) = headers.last().filter(|header| header.number > lowest) {
+        let parent_hash = child.parent_hash;
+        let parent = rpc.get_block(parent_hash, false).await?.header;
+        if parent.hash != parent_hash || !Ethereum::is_header_hash_valid(&parent) {
+            eyre::bail!(
+                "l1 block {} is not the parent of block {}",
+                parent.number,
+                child.number
+            );
+        }
+        headers.push(parent);
+    }
+
+    for header in headers.iter().rev() {
+        l1_blocks.insert(header.number, header.hash);
+        let receipts = rpc
+            .get_block_receipts(BlockId::hash(header.hash))
+            .await?
+            .ok_or_else(|| eyre!("receipts of l1 block {} not found", header.number))?;
+        index.insert_block(header.number, header.hash, header.receipts_root, &receipts)?;
+    }
+    Ok(())
+}
+
+/// The outcome of checking the deposits of a block against L1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DepositCheck {
+    /// The L1 attributes of the block, unless its leading deposit could not be decoded.
+    pub l1_info: Option<L1BlockInfo>,
+    /// The origin the attributes name against the verified L1 chain. Unavailable without
+    /// the attributes, and never a mismatch, which is an error.
+    pub origin: L1OriginCheck,
+    /// Source hashes of the user deposits no indexed L1 event accounts for.
+    pub unmatched: Vec<B256>,
+}
+
+impl DepositCheck {
+    /// Whether the origin and every deposit matched the verified L1 chain.
+    pub fn is_verified(&self) -> bool {
+        self.origin == L1OriginCheck::Verified && self.unmatched.is_empty()
+    }
+}
+
+/// Checks the deposits of a block with full transactions against the indexed L1 events, and
+/// its L1 origin against the verified L1 blocks.
+///
+/// Deposits contradicting the event of their source hash, an origin other than the verified
+/// L1 block at its height, or an L1 attributes deposit with a source hash not derived from
+/// the origin it names, are errors. What the verified chain cannot account for yet is
+/// reported in the [`DepositCheck`].
+pub fn verify_deposits(
+    block: &Block<Transaction>,
+    index: &L1DepositIndex,
+    l1_blocks: &VerifiedL1Blocks,
+) -> Result<DepositCheck> {
+    let mut check = DepositCheck {
+        l1_info: None,
+        origin: L1OriginCheck::Unavailable,
+        unmatched: Vec::new(),
+    };
+    for tx in block.transactions.txns() {
+        let OpTxEnvelope::Deposit(deposit) = &tx.inner.inner else {
+            continue;
+        };
+        let deposit = deposit.inner();
+
+        if deposit.from == L1_INFO_DEPOSITOR {
+            let info = match L1BlockInfo::decode(&deposit.input) {
+                Ok(info) => info,
+                Err(err) => {
+                    warn!(
+                        target: "helios::opstack",
+                        block = block.header.number,
+                        "undecodable l1 attributes: {err}"
+                    );
+                    continue;
+                }
+            };
+
+            let expected = l1_info_source_hash(info.hash, info.sequence_number);
+            if deposit.source_hash != expected {
+                eyre::bail!(
+                    "l1 attributes deposit has source hash {}, expected {expected}",
+                    deposit.source_hash
+                );
+            }
+            check.origin = l1_blocks.check(info.number, info.hash);
+            if let L1OriginCheck::Mismatch { verified_hash } = check.origin {
+                eyre::bail!(
+                    "l1 origin {} at height {} is not the verified l1 block {verified_hash}",
+                    info.hash,
+                    info.number
+                );
+            }
+            check.l1_info = Some(info);
+            continue;
+        }
+
+        match index.get(&deposit.source_hash) {
+            Some(event) => event.check(deposit)?,
+            None => check.unmatched.push(deposit.source_hash),
+        }
+    }
+
+    Ok(check)
+}
+
+/// Decides whether a block may be applied given its deposits, returning the check. An
+/// origin or deposits the verified L1 chain cannot account for reject the block when
+/// `strict`, and are otherwise logged as unverified.
+pub fn check_deposits(
+    block: &Block<Transaction>,
+    index: &L1DepositIndex,
+    l1_blocks: &VerifiedL1Blocks,
+    strict: bool,
+) -> Result<DepositCheck> {
+    let check = verify_deposits(block, index, l1_blocks)?;
+    if check.is_verified() {
+        return Ok(check);
+    }
+
+    let number = block.header.number;
+    let origin = match (&check.l1_info, check.origin) {
+        (None, _) => "l1 attributes that could not be decoded",
+        (Some(_), L1OriginCheck::Verified) => "a verified l1 origin",
+        (Some(_), _) => "an l1 origin not verified yet",
+    };
+    if strict {
+        eyre::bail!(
+            "block {number} has {origin} and {} deposits without a verified l1 event",
+            check.unmatched.len()
+        );
+    }
+
+    warn!(
+        target: "helios::opstack",
+        block = number,
+        deposits = check.unmatched.len(),
+        "block has {origin}, its unmatched deposits are unverified"
+    );
+    Ok(check)
+}
@@ -0,0 +1,382 @@
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+use alloy::consensus::BlockHeader;
+use alloy::network::primitives::HeaderResponse;
+use alloy::network::BlockResponse;
+use alloy::primitives::{address, Address, Bytes, TxKind, B256, U256};
+use alloy::rpc::types::Block;
+use alloy::sol;
+use alloy::sol_types::SolCall;
+use eyre::{eyre, Result};
+use op_alloy_consensus::OpTxEnvelope;
+use op_alloy_rpc_types::Transaction;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use helios_core::execution::rpc::ExecutionRpc;
+use helios_core::execution::ExecutionClient;
+use helios_core::types::BlockTag;
+
+use crate::spec::OpStack;
+use crate::OpStackClient;
+
+/// Predeploy holding the attributes of the current L1 origin.
+pub const L1_BLOCK: Address = address!("4200000000000000000000000000000000000015");
+
+/// Sender of the L1 attributes deposit at the start of every L2 block.
+pub const L1_INFO_DEPOSITOR: Address = address!("deaddeaddeaddeaddeaddeaddeaddeaddead0001");
+
+/// Number of verified L1 block hashes kept for checking origins against.
+pub const MAX_VERIFIED_L1_BLOCKS: usize = 256;
+
+sol! {
+    function setL1BlockValues(
+        uint64 _number,
+        uint64 _timestamp,
+        uint256 _basefee,
+        bytes32 _hash,
+        uint64 _sequenceNumber,
+        bytes32 _batcherHash,
+        uint256 _l1FeeOverhead,
+        uint256 _l1FeeScalar
+    );
+}
+
+// `setL1BlockValuesEcotone()` and `setL1BlockValuesIsthmus()` take no abi arguments, the
+// values are packed after the selector
+const ECOTONE_SELECTOR: [u8; 4] = [0x44, 0x0a, 0x5e, 0x20];
+const ECOTONE_CALLDATA_LEN: usize = 164;
+const ISTHMUS_SELECTOR: [u8; 4] = [0x09, 0x89, 0x99, 0xbe];
+/// The ecotone fields followed by the operator fee scalar and constant.
+const ISTHMUS_CALLDATA_LEN: usize = ECOTONE_CALLDATA_LEN + 12;
+
+/// The L1 attributes an L2 block was derived with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1BlockInfo {
+    pub number: u64,
+    pub timestamp: u64,
+    pub base_fee: U256,
+    pub hash: B256,
+    /// Position of the L2 block within the epoch of its L1 origin.
+    pub sequence_number: u64,
+    pub batcher_hash: B256,
+    pub fee_params: L1FeeParams,
+}
+
+/// Fee parameters of the L1 attributes, which changed shape with Ecotone and gained the
+/// operator fee with Isthmus.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "format", rename_all = "camelCase")]
+pub enum L1FeeParams {
+    #[serde(rename_all = "camelCase")]
+    Bedrock {
+        l1_fee_overhead: U256,
+        l1_fee_scalar: U256,
+    },
+    #[serde(rename_all = "camelCase")]
+    Ecotone {
+        base_fee_scalar: u32,
+        blob_base_fee_scalar: u32,
+        blob_base_fee: U256,
+    },
+    #[serde(rename_all = "camelCase")]
+    Isthmus {
+        base_fee_scalar: u32,
+        blob_base_fee_scalar: u32,
+        blob_base_fee: U256,
+        operator_fee_scalar: u32,
+        operator_fee_constant: u64,
+    },
+}
+
+impl L1BlockInfo {
+    /// Decodes the calldata of an L1 attributes deposit, in the Bedrock, Ecotone or Isthmus
+    /// format.
+    pub fn decode(calldata: &[u8]) -> Result<Self> {
+        let selector = calldata
+            .get(..4)
+            .ok_or_else(|| eyre!("l1 info calldata too short"))?;
+
+        if selector == setL1BlockValuesCall::SELECTOR {
+            let call = setL1BlockValuesCall::abi_decode(calldata, true)?;
+            return Ok(Self {
+                number: call._number,
+                timestamp: call._timestamp,
+                base_fee: call._basefee,
+                hash: call._hash,
+                sequence_number: call._sequenceNumber,
+                batcher_hash: call._batcherHash,
+                fee_params: L1FeeParams::Bedrock {
+                    l1_fee_overhead: call._l1FeeOverhead,
+                    l1_fee_scalar: call._l1FeeScalar,
+                },
+            });
+        }
+
+        let (format, expected_len) = if selector == ECOTONE_SELECTOR {
+            ("ecotone", ECOTONE_CALLDATA_LEN)
+        } else if selector == ISTHMUS_SELECTOR {
+            ("isthmus", ISTHMUS_CALLDATA_LEN)
+        } else {
+            eyre::bail!(
+                "unknown l1 info selector 0x{}",
+                alloy::primitives::hex::encode(selector)
+            );
+        };
+        if calldata.len() != expected_len {
+            eyre::bail!(
+                "{format} l1 info calldata is {} bytes, expected {expected_len}",
+                calldata.len()
+            );
+        }
+
+        let mut reader = PackedReader(&calldata[4..]);
+        let base_fee_scalar = u32::from_be_bytes(reader.take());
+        let blob_base_fee_scalar = u32::from_be_bytes(reader.take());
+        let sequence_number = u64::from_be_bytes(reader.take());
+        let timestamp = u64::from_be_bytes(reader.take());
+        let number = u64::from_be_bytes(reader.take());
+        let base_fee = U256::from_be_bytes(reader.take());
+        let blob_base_fee = U256::from_be_bytes(reader.take());
+        let hash = B256::from(reader.take::<32>());
+        let batcher_hash = B256::from(reader.take::<32>());
+
+        let fee_params = if selector == ISTHMUS_SELECTOR {
+            L1FeeParams::Isthmus {
+                base_fee_scalar,
+                blob_base_fee_scalar,
+                blob_base_fee,
+                operator_fee_scalar: u32::from_be_bytes(reader.take()),
+                operator_fee_constant: u64::from_be_bytes(reader.take()),
+            }
+        } else {
+            L1FeeParams::Ecotone {
+                base_fee_scalar,
+                blob_base_fee_scalar,
+                blob_base_fee,
+            }
+        };
+
+        Ok(Self {
+            number,
+            timestamp,
+            base_fee,
+            hash,
+            sequence_number,
+            batcher_hash,
+            fee_params,
+        })
+    }
+
+    /// Encodes the calldata of the L1 attributes deposit in the format of the fee
+    /// parameters, as [`L1BlockInfo::decode`] reads it.
+    pub fn encode(&self) -> Bytes {
+        let (selector, base_fee_scalar, blob_base_fee_scalar, blob_base_fee, operator_fee) =
+            match self.fee_params {
+                L1FeeParams::Bedrock {
+                    l1_fee_overhead,
+                    l1_fee_scalar,
+                } => {
+                    let call = setL1BlockValuesCall {
+                        _number: self.number,
+                        _timestamp: self.timestamp,
+                        _basefee: self.base_fee,
+                        _hash: self.hash,
+                        _sequenceNumber: self.sequence_number,
+                        _batcherHash: self.batcher_hash,
+                        _l1FeeOverhead: l1_fee_overhead,
+                        _l1FeeScalar: l1_fee_scalar,
+                    };
+                    return call.abi_encode().into();
+                }
+                L1FeeParams::Ecotone {
+                    base_fee_scalar,
+                    blob_base_fee_scalar,
+                    blob_base_fee,
+                } => (
+                    ECOTONE_SELECTOR,
+                    base_fee_scalar,
+                    blob_base_fee_scalar,
+                    blob_base_fee,
+                    None,
+                ),
+                L1FeeParams::Isthmus {
+                    base_fee_scalar,
+                    blob_base_fee_scalar,
+                    blob_base_fee,
+                    operator_fee_scalar,
+                    operator_fee_constant,
+                } => (
+                    ISTHMUS_SELECTOR,
+                    base_fee_scalar,
+                    blob_base_fee_scalar,
+                    blob_base_fee,
+                    Some((operator_fee_scalar, operator_fee_constant)),
+                ),
+            };
+
+        let mut calldata = Vec::with_capacity(ISTHMUS_CALLDATA_LEN);
+        calldata.extend_from_slice(&selector);
+        calldata.extend_from_slice(&base_fee_scalar.to_be_bytes());
+        calldata.extend_from_slice(&blob_base_fee_scalar.to_be_bytes());
+        calldata.extend_from_slice(&self.sequence_number.to_be_bytes());
+        calldata.extend_from_slice(&self.timestamp.to_be_bytes());
+        calldata.extend_from_slice(&self.number.to_be_bytes());
+        calldata.extend_from_slice(&self.base_fee.to_be_bytes::<32>());
+        calldata.extend_from_slice(&blob_base_fee.to_be_bytes::<32>());
+        calldata.extend_from_slice(self.hash.as_slice());
+        calldata.extend_from_slice(self.batcher_hash.as_slice());
+        if let Some((scalar, constant)) = operator_fee {
+            calldata.extend_from_slice(&scalar.to_be_bytes());
+            calldata.extend_from_slice(&constant.to_be_bytes());
+        }
+        calldata.into()
+    }
+
+    /// Reads the L1 attributes from the deposit leading a block with full transactions.
+    pub fn from_block(block: &Block<Transaction>) -> Result<Self> {
+        let tx = block
+            .transactions
+            .txns()
+            .next()
+            .ok_or_else(|| eyre!("block {} has no transactions", block.header.number))?;
+
+        let OpTxEnvelope::Deposit(deposit) = &tx.inner.inner else {
+            eyre::bail!("first transaction is not a deposit");
+        };
+
+        let deposit = deposit.inner();
+        if deposit.from != L1_INFO_DEPOSITOR || deposit.to != TxKind::Call(L1_BLOCK) {
+            eyre::bail!("first transaction is not the l1 attributes deposit");
+        }
+
+        Self::decode(&deposit.input)
+    }
+}
+
+/// Reads fixed size fields in order from packed calldata of a known length.
+struct PackedReader<'a>(&'a [u8]);
+
+impl PackedReader<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let (field, rest) = self.0.split_at(N);
+        self.0 = rest;
+        field.try_into().unwrap()
+    }
+}
+
+/// Hashes of L1 blocks verified by the in-process L1 light client, by number.
+///
+/// Only the most recent [`MAX_VERIFIED_L1_BLOCKS`] are kept. Clones share the same blocks.
+#[derive(Debug, Clone, Default)]
+pub struct VerifiedL1Blocks {
+    blocks: Arc<Mutex<BTreeMap<u64, B256>>>,
+}
+
+impl VerifiedL1Blocks {
+    pub fn insert(&self, number: u64, hash: B256) {
+        let mut blocks = self.blocks.lock().unwrap();
+        blocks.insert(number, hash);
+        while blocks.len() > MAX_VERIFIED_L1_BLOCKS {
+            blocks.pop_first();
+        }
+    }
+
+    pub fn get(&self, number: u64) -> Option<B256> {
+        self.blocks.lock().unwrap().get(&number).copied()
+    }
+
+    /// Compares an origin against the verified block at its height.
+    pub fn check(&self, number: u64, hash: B256) -> L1OriginCheck {
+        match self.get(number) {
+            None => L1OriginCheck::Unavailable,
+            Some(verified) if verified == hash => L1OriginCheck::Verified,
+            Some(verified) => L1OriginCheck::Mismatch {
+                verified_hash: verified,
+            },
+        }
+    }
+}
+
+/// The outcome of checking an L1 origin against the L1 light client.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum L1OriginCheck {
+    /// No in-process L1 client is running, so the origin is only as good as the L2 block.
+    Unchecked,
+    /// The L1 client has not verified a block at the origin height, or no longer retains it.
+    Unavailable,
+    Verified,
+    /// The L1 client verified a different block at the origin height.
+    #[serde(rename_all = "camelCase")]
+    Mismatch {
+        verified_hash: B256,
+    },
+}
+
+/// The L1 origin of a verified L2 block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct L1Origin {
+    pub l2_block_number: u64,
+    pub l2_block_hash: B256,
+    pub number: u64,
+    pub hash: B256,
+    pub timestamp: u64,
+    pub sequence_number: u64,
+    pub check: L1OriginCheck,
+}
+
+/// Reads the L1 origin of the verified L2 block at `tag`, checking it against `l1` when an
+/// L1 light client is running. Mismatches are logged as consistency alerts.
+pub async fn get_l1_origin<R: ExecutionRpc<OpStack>>(
+    execution: &ExecutionClient<OpStack, R>,
+    tag: BlockTag,
+    l1: Option<&VerifiedL1Blocks>,
+) -> Result<L1Origin> {
+    let block = execution
+        .get_block(tag, true)
+        .await
+        .ok_or_else(|| eyre!("block not found: {tag}"))?;
+    let info = L1BlockInfo::from_block(&block)?;
+
+    let check = l1.map_or(L1OriginCheck::Unchecked, |l1| {
+        l1.check(info.number, info.hash)
+    });
+    if let L1OriginCheck::Mismatch { verified_hash } = check {
+        warn!(
+            target: "helios::opstack",
+            l2_block = block.header().number(),
+            l1_block = info.number,
+            origin = %info.hash,
+            verified = %verified_hash,
+            "consistency alert: l1 origin does not match the verified l1 chain"
+        );
+    }
+
+    Ok(L1Origin {
+        l2_block_number: block.header().number(),
+        l2_block_hash: block.header().hash(),
+        number: info.number,
+        hash: info.hash,
+        timestamp: info.timestamp,
+        sequence_number: info.sequence_number,
+        check,
+    })
+}
+
+/// L1 origin lookups on top of [`OpStackClient`].
+pub trait L1OriginReader {
+    fn get_l1_origin(&self, block: BlockTag) -> impl Future<Output = Result<L1Origin>> + Send;
+}
+
+impl L1OriginReader for OpStackClient {
+    async fn get_l1_origin(&self, block: BlockTag) -> Result<L1Origin> {
+        let node = self.node();
+        let l1 = node.consensus.verified_l1_blocks();
+        get_l1_origin(&node.execution, block, l1.as_ref()).await
+    }
+}
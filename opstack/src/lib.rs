@@ -15,10 +15,16 @@ use types::ExecutionPayload;
 mod builder;
 pub mod config;
 pub mod consensus;
+pub mod deposits;
+pub mod l1_origin;
+#[cfg(not(target_arch = "wasm32"))]
+mod rpc;
+pub mod sequencer;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod server;
 pub mod spec;
 pub mod types;
+pub mod withdrawal;
 
 pub use builder::OpStackClientBuilder;
 pub type OpStackClient = Client<OpStack, ConsensusClient>;
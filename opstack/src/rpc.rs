@@ -0,0 +1,63 @@
+use std::sync::Arc;
+
+use jsonrpsee::{
+    core::{async_trait, server::Methods},
+    proc_macros::rpc,
+    types::error::{ErrorObject, ErrorObjectOwned},
+};
+
+use helios_core::client::node::Node;
+use helios_core::types::BlockTag;
+
+use crate::consensus::ConsensusClient;
+use crate::l1_origin::{get_l1_origin, L1Origin, VerifiedL1Blocks};
+use crate::spec::OpStack;
+use crate::withdrawal::{get_withdrawal_proof, WithdrawalId, WithdrawalProof};
+use crate::OpStackClient;
+
+#[rpc(server, namespace = "helios")]
+trait OpStackRpc {
+    #[method(name = "getWithdrawalProofForL1")]
+    async fn get_withdrawal_proof_for_l1(
+        &self,
+        id: WithdrawalId,
+        block: BlockTag,
+    ) -> Result<WithdrawalProof, ErrorObjectOwned>;
+
+    #[method(name = "getL1Origin")]
+    async fn get_l1_origin(&self, block: BlockTag) -> Result<L1Origin, ErrorObjectOwned>;
+}
+
+struct RpcInner {
+    node: Arc<Node<OpStack, ConsensusClient>>,
+    l1_blocks: Option<VerifiedL1Blocks>,
+}
+
+#[async_trait]
+impl OpStackRpcServer for RpcInner {
+    async fn get_withdrawal_proof_for_l1(
+        &self,
+        id: WithdrawalId,
+        block: BlockTag,
+    ) -> Result<WithdrawalProof, ErrorObjectOwned> {
+        get_withdrawal_proof(&self.node, id, block)
+            .await
+            .map_err(|err| ErrorObject::owned(1, err.to_string(), None::<()>))
+    }
+
+    async fn get_l1_origin(&self, block: BlockTag) -> Result<L1Origin, ErrorObjectOwned> {
+        get_l1_origin(&self.node.execution, block, self.l1_blocks.as_ref())
+            .await
+            .map_err(|err| ErrorObject::owned(1, err.to_string(), None::<()>))
+    }
+}
+
+/// OP Stack specific methods served next to the common namespaces.
+pub fn methods(client: &OpStackClient) -> Methods {
+    let rpc = RpcInner {
+        node: client.node().clone(),
+        l1_blocks: client.node().consensus.verified_l1_blocks(),
+    };
+
+    OpStackRpcServer::into_rpc(rpc).into()
+}
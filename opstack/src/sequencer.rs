@@ -0,0 +1,247 @@
+//! Watches the feed of unsafe heads and fails over to replicas of it.
+//!
+//! Unsafe heads come from a single consensus endpoint, so a stalled sequencer or server used
+//! to leave the client silently serving an ever older head. Once no payload is accepted for
+//! longer than `stale_after` the feed is reported stalled, and the other configured
+//! endpoints are tried in turn. One is only followed from then on once the head it serves
+//! chains onto a block already verified, so a replica on another branch is never switched
+//! to.
+
+use std::collections::BTreeMap;
+
+use alloy::primitives::B256;
+use alloy::rpc::types::Header;
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tracing::{info, warn};
+use url::Url;
+
+use helios_core::config::ConfigDuration;
+use helios_core::execution::rpc::ExecutionRpc;
+use helios_core::types::{redact_url, HeadFeed};
+
+use crate::spec::OpStack;
+
+/// Number of verified unsafe heads kept for checking replicas and safe blocks against.
+pub const MAX_VERIFIED_HEADS: usize = 1024;
+
+/// Ancestors of a replica head fetched at most on the way down to a verified block.
+pub const MAX_ANCESTRY_DEPTH: usize = 64;
+
+const DEFAULT_STALE_AFTER: ConfigDuration = ConfigDuration::from_secs(30);
+
+/// How the feed of unsafe heads is watched, read from the `sequencer` config table.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SequencerConfig {
+    /// Time without an accepted unsafe payload after which the feed is stalled.
+    pub stale_after: ConfigDuration,
+    /// Consensus endpoints serving the same feed as `consensus_rpc`, in the order they are
+    /// failed over to.
+    pub replicas: Vec<Url>,
+    /// Serves the tags following the head at the safe block while the feed is stalled.
+    pub safe_only: bool,
+}
+
+impl Default for SequencerConfig {
+    fn default() -> Self {
+        Self {
+            stale_after: DEFAULT_STALE_AFTER,
+            replicas: Vec::new(),
+            safe_only: false,
+        }
+    }
+}
+
+/// The endpoint unsafe heads are followed from, and the heads verified from it so far.
+/// Times are unix seconds.
+#[derive(Debug)]
+pub struct SequencerFeed {
+    endpoints: Vec<Url>,
+    current: usize,
+    config: SequencerConfig,
+    verified: BTreeMap<u64, B256>,
+    /// Timestamp of the latest payload accepted, or the start of the client before one is.
+    fresh_at: u64,
+    failover_at: Option<u64>,
+    head_feed: watch::Sender<HeadFeed>,
+}
+
+impl SequencerFeed {
+    pub fn new(consensus_rpc: &Url, config: &SequencerConfig, now: u64) -> Self {
+        let endpoints = std::iter::once(consensus_rpc.clone())
+            .chain(config.replicas.iter().cloned())
+            .collect();
+
+        Self {
+            endpoints,
+            current: 0,
+            config: config.clone(),
+            verified: BTreeMap::new(),
+            fresh_at: now,
+            failover_at: None,
+            head_feed: watch::channel(HeadFeed::Live).0,
+        }
+    }
+
+    /// The endpoint heads are currently followed from.
+    pub fn endpoint(&self) -> &Url {
+        &self.endpoints[self.current]
+    }
+
+    pub fn candidate(&self, index: usize) -> &Url {
+        &self.endpoints[index]
+    }
+
+    /// Notifies of the feed stalling and coming back.
+    pub fn head_feed(&self) -> watch::Receiver<HeadFeed> {
+        self.head_feed.subscribe()
+    }
+
+    /// The number of the latest head accepted.
+    pub fn latest(&self) -> Option<u64> {
+        self.verified.last_key_value().map(|(number, _)| *number)
+    }
+
+    /// The hash of the head accepted at `number`, while it is kept.
+    pub fn verified_hash(&self, number: u64) -> Option<B256> {
+        self.verified.get(&number).copied()
+    }
+
+    /// Records a head accepted from the current endpoint, which brings a stalled feed back.
+    pub fn accept(&mut self, header: &Header) {
+        self.verified.insert(header.number, header.hash);
+        while self.verified.len() > MAX_VERIFIED_HEADS {
+            self.verified.pop_first();
+        }
+        self.fresh_at = self.fresh_at.max(header.timestamp);
+
+        let endpoint = redact_url(self.endpoint().as_str());
+        self.head_feed.send_if_modified(|head_feed| {
+            let resumed = *head_feed != HeadFeed::Live;
+            if resumed {
+                info!(target: "helios::opstack", %endpoint, "unsafe head feed resumed");
+            }
+            *head_feed = HeadFeed::Live;
+            resumed
+        });
+    }
+
+    /// Marks the feed stalled once no payload was accepted for longer than `stale_after`
+    /// before `now`, and returns whether it is.
+    pub fn check(&mut self, now: u64) -> bool {
+        let age = now.saturating_sub(self.fresh_at);
+        if age <= self.config.stale_after.as_secs() {
+            return false;
+        }
+
+        let stalled = if self.config.safe_only {
+            HeadFeed::SafeOnly
+        } else {
+            HeadFeed::Stalled
+        };
+        let endpoint = redact_url(self.endpoint().as_str());
+        self.head_feed.send_if_modified(|head_feed| {
+            let changed = *head_feed != stalled;
+            if changed {
+                warn!(target: "helios::opstack", %endpoint, "no unsafe head for {age}s");
+            }
+            *head_feed = stalled;
+            changed
+        });
+        true
+    }
+
+    /// The endpoints to fail over to, the one after the current first, while the feed is
+    /// stalled. Failovers are attempted at most once every `stale_after`.
+    pub fn failover_candidates(&mut self, now: u64) -> Vec<usize> {
+        let stale_after = self.config.stale_after.as_secs();
+        let due = self
+            .failover_at
+            .map_or(true, |at| now.saturating_sub(at) >= stale_after);
+        if !due || self.endpoints.len() < 2 || !self.check(now) {
+            return Vec::new();
+        }
+
+        self.failover_at = Some(now);
+        (1..self.endpoints.len())
+            .map(|offset| (self.current + offset) % self.endpoints.len())
+            .collect()
+    }
+
+    /// Whether the walk down the ancestors of a replica head can stop at `header`, being at
+    /// a verified height or below every height kept.
+    pub fn reaches_verified(&self, header: &Header) -> bool {
+        self.verified.contains_key(&header.number)
+            || self
+                .verified
+                .first_key_value()
+                .map_or(true, |(oldest, _)| header.number < *oldest)
+    }
+
+    /// The replica head `head` followed by its ancestors, newest first, fetched from `rpc`
+    /// by parent hash down to where [`SequencerFeed::reaches_verified`] lets the walk stop,
+    /// or once [`MAX_ANCESTRY_DEPTH`] blocks were fetched. The branch is only checked by
+    /// [`SequencerFeed::check_ancestry`].
+    pub async fn fetch_ancestry<R: ExecutionRpc<OpStack>>(
+        &self,
+        rpc: &R,
+        head: Header,
+    ) -> Result<Vec<Header>> {
+        let mut branch = vec![head];
+        loop {
+            let last = &branch[branch.len() - 1];
+            if self.reaches_verified(last) || branch.len() > MAX_ANCESTRY_DEPTH {
+                return Ok(branch);
+            }
+            let parent = rpc.get_block(last.parent_hash, false).await?;
+            branch.push(parent.header);
+        }
+    }
+
+    /// Checks that `branch`, a head followed by its ancestors, newest first, is linked by
+    /// parent hashes and that the highest of its blocks at a verified height is the one
+    /// verified there. Blocks below that one are then verified ancestors as well.
+    pub fn check_ancestry(&self, branch: &[Header]) -> Result<()> {
+        for header in branch {
+            if header.inner.hash_slow() != header.hash {
+                eyre::bail!("block {} does not hash to {}", header.number, header.hash);
+            }
+        }
+        for pair in branch.windows(2) {
+            if pair[0].parent_hash != pair[1].hash || pair[0].number != pair[1].number + 1 {
+                eyre::bail!(
+                    "block {} is not the parent of block {}",
+                    pair[1].number,
+                    pair[0].number
+                );
+            }
+        }
+
+        // before the first head there is nothing a replica could contradict
+        if self.verified.is_empty() {
+            return Ok(());
+        }
+
+        let shared = branch
+            .iter()
+            .find_map(|header| Some((header, self.verified_hash(header.number)?)));
+        match shared {
+            Some((header, hash)) if header.hash == hash => Ok(()),
+            Some((header, _)) => eyre::bail!(
+                "diverges from the verified chain at block {}",
+                header.number
+            ),
+            None => eyre::bail!("no verified ancestor within {} blocks", branch.len()),
+        }
+    }
+
+    /// Follows the endpoint at `index` from now on, once `branch` leading to its head passes
+    /// [`SequencerFeed::check_ancestry`].
+    pub fn switch_to(&mut self, index: usize, branch: &[Header]) -> Result<()> {
+        self.check_ancestry(branch)?;
+        self.current = index;
+        Ok(())
+    }
+}
@@ -1,13 +1,16 @@
 use alloy::{
-    consensus::{
-        proofs::{calculate_transaction_root, calculate_withdrawals_root},
-        BlockHeader, Receipt, ReceiptWithBloom, Transaction as TxTrait, TxReceipt, TxType,
-    },
-    primitives::{Address, Bytes, ChainId, TxKind, U256},
-    rpc::types::{AccessList, Log, TransactionRequest},
+    consensus::{BlockHeader, Receipt, ReceiptWithBloom, Transaction as _, TxReceipt, TxType},
+    eips::eip2718::Decodable2718,
+    primitives::{keccak256, Address, Bytes, ChainId, TxKind, U256},
+    rpc::types::{AccessList, BlockTransactions, Log, TransactionRequest},
 };
+use alloy_trie::EMPTY_ROOT_HASH;
 
-use helios_core::{fork_schedule::ForkSchedule, network_spec::NetworkSpec};
+use helios_core::execution::blob::BlobParams;
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::preflight::RawTransaction;
+use helios_core::fork_schedule::ForkSchedule;
+use helios_core::network_spec::{check_transactions, encode_block_parts, NetworkSpec};
 use op_alloy_consensus::{
     OpDepositReceipt, OpDepositReceiptWithBloom, OpReceiptEnvelope, OpTxEnvelope, OpTxType,
     OpTypedTransaction,
@@ -70,31 +73,60 @@ impl NetworkSpec for OpStack {
         }
     }
 
-    fn is_hash_valid(block: &Self::BlockResponse) -> bool {
-        if block.header.hash_slow() != block.header.hash {
-            return false;
+    fn encode_header(header: &Self::HeaderResponse) -> Vec<u8> {
+        alloy::rlp::encode(&header.inner)
+    }
+
+    fn encode_block(block: &Self::BlockResponse) -> Option<Vec<u8>> {
+        if !block.uncles.is_empty() {
+            return None;
         }
 
+        let transactions = match &block.transactions {
+            BlockTransactions::Full(txs) => txs.iter().map(|tx| &tx.inner.inner).collect(),
+            BlockTransactions::Hashes(hashes) if hashes.is_empty() => Vec::new(),
+            _ => return None,
+        };
+        let withdrawals = block.withdrawals.as_ref().map(|w| w.0.as_slice());
+
+        Some(encode_block_parts(
+            &block.header.inner,
+            &transactions,
+            withdrawals,
+        ))
+    }
+
+    fn check_body(block: &Self::BlockResponse) -> Result<(), ExecutionError> {
+        let hash = block.header.hash;
         if let Some(txs) = block.transactions.as_transactions() {
-            let txs_root = calculate_transaction_root(
-                &txs.iter()
-                    .map(|t| t.clone().inner.inner)
-                    .collect::<Vec<_>>(),
-            );
-            if txs_root != block.header.transactions_root {
-                return false;
+            check_transactions(hash, block.header.transactions_root, txs, |tx| {
+                tx.inner.inner.clone()
+            })?;
+        } else if block
+            .transactions
+            .as_hashes()
+            .is_some_and(|hashes| hashes.is_empty())
+        {
+            // a hashes only block can be checked when it has no transactions
+            if block.header.transactions_root != EMPTY_ROOT_HASH {
+                return Err(ExecutionError::BlockBodyMismatch(hash, "transactions root"));
             }
         }
 
-        if let Some(withdrawals) = &block.withdrawals {
-            let withdrawals_root =
-                calculate_withdrawals_root(&withdrawals.iter().cloned().collect::<Vec<_>>());
-            if Some(withdrawals_root) != block.header.withdrawals_root {
-                return false;
-            }
+        // withdrawals are never processed on the op stack, and from isthmus the withdrawals
+        // root commits to the storage of the message passer instead of the empty list
+        if block
+            .withdrawals
+            .as_ref()
+            .is_some_and(|withdrawals| !withdrawals.is_empty())
+        {
+            return Err(ExecutionError::BlockBodyMismatch(hash, "withdrawals root"));
         }
+        Ok(())
+    }
 
-        true
+    fn is_header_hash_valid(header: &Self::HeaderResponse) -> bool {
+        keccak256(Self::encode_header(header)) == header.hash
     }
 
     fn receipt_contains(list: &[Self::ReceiptResponse], elem: &Self::ReceiptResponse) -> bool {
@@ -111,6 +143,36 @@ impl NetworkSpec for OpStack {
         receipt.inner.inner.logs().to_vec()
     }
 
+    fn created_address(tx: &Self::TransactionResponse) -> Option<Address> {
+        // a deposit runs at the state nonce of its sender, which it does not carry
+        match &tx.inner.inner {
+            OpTxEnvelope::Deposit(_) => None,
+            envelope => Some(tx.inner.from.create(envelope.nonce())),
+        }
+    }
+
+    fn decode_transaction(mut bytes: &[u8]) -> Result<RawTransaction, ExecutionError> {
+        let invalid = ExecutionError::InvalidRawTransaction;
+        let tx = OpTxEnvelope::decode_2718(&mut bytes).map_err(|err| invalid(err.to_string()))?;
+        if !bytes.is_empty() {
+            return Err(invalid("trailing bytes after the transaction".to_string()));
+        }
+        let sender = match &tx {
+            OpTxEnvelope::Legacy(tx) => tx.recover_signer(),
+            OpTxEnvelope::Eip2930(tx) => tx.recover_signer(),
+            OpTxEnvelope::Eip1559(tx) => tx.recover_signer(),
+            OpTxEnvelope::Eip7702(tx) => tx.recover_signer(),
+            // deposits are derived from l1, never submitted to the sequencer
+            OpTxEnvelope::Deposit(_) => {
+                return Err(invalid("deposit transactions cannot be sent".to_string()))
+            }
+            _ => return Err(invalid("unsupported transaction type".to_string())),
+        }
+        .map_err(|err| invalid(err.to_string()))?;
+
+        Ok(RawTransaction::new(&tx, sender))
+    }
+
     fn tx_env(tx: &Self::TransactionRequest) -> TxEnv {
         TxEnv {
             caller: <OpTransactionRequest as TransactionBuilder<Self>>::from(tx)
@@ -135,39 +197,48 @@ impl NetworkSpec for OpStack {
             gas_priority_fee:
                 <OpTransactionRequest as TransactionBuilder<Self>>::max_priority_fee_per_gas(tx)
                     .map(U256::from),
-            max_fee_per_blob_gas:
-                <OpTransactionRequest as TransactionBuilder<Self>>::max_fee_per_gas(tx)
-                    .map(U256::from),
+            max_fee_per_blob_gas: tx.as_ref().max_fee_per_blob_gas.map(U256::from),
             blob_hashes: tx
-                .clone()
-                .build_typed_tx()
-                .unwrap()
-                .blob_versioned_hashes()
                 .as_ref()
-                .map(|v| v.to_vec())
+                .blob_versioned_hashes
+                .clone()
                 .unwrap_or_default(),
             authorization_list: None,
         }
     }
 
-    fn block_env(block: &Self::BlockResponse, fork_schedule: &ForkSchedule) -> BlockEnv {
-        let is_prague = block.header.timestamp >= fork_schedule.prague_timestamp;
-        let blob_excess_gas_and_price = block
-            .header
+    fn block_env(header: &Self::HeaderResponse, fork_schedule: &ForkSchedule) -> BlockEnv {
+        let is_prague = header.timestamp >= fork_schedule.prague_timestamp;
+        let blob_excess_gas_and_price = header
             .excess_blob_gas()
             .map(|v| BlobExcessGasAndPrice::new(v, is_prague));
 
         BlockEnv {
-            number: U256::from(block.header.number()),
-            coinbase: block.header.beneficiary(),
-            timestamp: U256::from(block.header.timestamp()),
-            gas_limit: U256::from(block.header.gas_limit()),
-            basefee: U256::from(block.header.base_fee_per_gas().unwrap_or(0_u64)),
-            difficulty: block.header.difficulty(),
-            prevrandao: block.header.mix_hash(),
+            number: U256::from(header.number()),
+            coinbase: header.beneficiary(),
+            timestamp: U256::from(header.timestamp()),
+            gas_limit: U256::from(header.gas_limit()),
+            basefee: U256::from(header.base_fee_per_gas().unwrap_or(0_u64)),
+            difficulty: header.difficulty(),
+            prevrandao: header.mix_hash(),
             blob_excess_gas_and_price,
         }
     }
+
+    /// The EIP-1559 parameters differ between OP Stack chains and, since Holocene, between
+    /// blocks, so the base fee is only known from the next header.
+    fn next_base_fee(_header: &Self::HeaderResponse) -> Option<u64> {
+        None
+    }
+
+    /// OP Stack chains carry no blob transactions. Their headers keep the blob fields since
+    /// ecotone, but these do not price anything.
+    fn blob_params(
+        _header: &Self::HeaderResponse,
+        _fork_schedule: &ForkSchedule,
+    ) -> Option<BlobParams> {
+        None
+    }
 }
 
 impl Network for OpStack {
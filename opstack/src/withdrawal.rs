@@ -0,0 +1,252 @@
+use std::future::Future;
+
+use alloy::consensus::BlockHeader;
+use alloy::network::{primitives::HeaderResponse, BlockResponse, ReceiptResponse};
+use alloy::primitives::{address, keccak256, Address, Bytes, B256, U256};
+use alloy::rpc::types::Log;
+use alloy::sol;
+use alloy::sol_types::{SolEvent, SolValue};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+use helios_core::client::node::Node;
+use helios_core::consensus::Consensus;
+use helios_core::execution::proof::verify_mpt_proof;
+use helios_core::network_spec::NetworkSpec;
+use helios_core::types::BlockTag;
+
+use crate::spec::OpStack;
+use crate::OpStackClient;
+
+/// Predeploy recording every withdrawal initiated on L2.
+pub const L2_TO_L1_MESSAGE_PASSER: Address = address!("4200000000000000000000000000000000000016");
+
+sol! {
+    event MessagePassed(
+        uint256 indexed nonce,
+        address indexed sender,
+        address indexed target,
+        uint256 value,
+        uint256 gasLimit,
+        bytes data,
+        bytes32 withdrawalHash
+    );
+}
+
+/// Identifies a withdrawal either directly or by the L2 transaction that initiated it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WithdrawalId {
+    WithdrawalHash(B256),
+    TransactionHash(B256),
+}
+
+/// The withdrawal as passed to `OptimismPortal.proveWithdrawalTransaction`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalTransaction {
+    pub nonce: U256,
+    pub sender: Address,
+    pub target: Address,
+    pub value: U256,
+    pub gas_limit: U256,
+    pub data: Bytes,
+}
+
+impl WithdrawalTransaction {
+    /// Hash the message passer stores the withdrawal under.
+    pub fn hash(&self) -> B256 {
+        let encoded = (
+            self.nonce,
+            self.sender,
+            self.target,
+            self.value,
+            self.gas_limit,
+            self.data.clone(),
+        )
+            .abi_encode_params();
+
+        keccak256(encoded)
+    }
+
+    /// Decodes a `MessagePassed` log, checking the hash it carries.
+    pub fn from_log(log: &Log) -> Result<Self> {
+        if log.address() != L2_TO_L1_MESSAGE_PASSER {
+            eyre::bail!("log not emitted by the message passer");
+        }
+
+        let event = MessagePassed::decode_log(&log.inner, true)?.data;
+        let withdrawal = Self {
+            nonce: event.nonce,
+            sender: event.sender,
+            target: event.target,
+            value: event.value,
+            gas_limit: event.gasLimit,
+            data: event.data,
+        };
+
+        if withdrawal.hash() != event.withdrawalHash {
+            eyre::bail!("withdrawal hash mismatch");
+        }
+
+        Ok(withdrawal)
+    }
+}
+
+/// Preimage of an L2 output root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutputRootProof {
+    pub version: B256,
+    pub state_root: B256,
+    pub message_passer_storage_root: B256,
+    pub latest_blockhash: B256,
+}
+
+impl OutputRootProof {
+    pub fn output_root(&self) -> B256 {
+        keccak256(
+            [
+                self.version.as_slice(),
+                self.state_root.as_slice(),
+                self.message_passer_storage_root.as_slice(),
+                self.latest_blockhash.as_slice(),
+            ]
+            .concat(),
+        )
+    }
+}
+
+/// Everything needed to prove a withdrawal on L1 against the output proposed for
+/// `l2_block_number`. Selecting the matching output index or dispute game is left to
+/// the caller since it lives on L1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WithdrawalProof {
+    pub withdrawal_hash: B256,
+    /// Only known when the withdrawal was looked up by transaction hash.
+    pub withdrawal: Option<WithdrawalTransaction>,
+    pub l2_block_number: u64,
+    pub output_root_proof: OutputRootProof,
+    pub output_root: B256,
+    pub withdrawal_proof: Vec<Bytes>,
+}
+
+impl WithdrawalProof {
+    /// Checks the bundle is self consistent, without trusting whoever produced it.
+    pub fn verify(&self) -> Result<()> {
+        if let Some(withdrawal) = &self.withdrawal {
+            if withdrawal.hash() != self.withdrawal_hash {
+                eyre::bail!("withdrawal hash mismatch");
+            }
+        }
+
+        if self.output_root_proof.output_root() != self.output_root {
+            eyre::bail!("output root mismatch");
+        }
+
+        verify_mpt_proof(
+            self.output_root_proof.message_passer_storage_root,
+            withdrawal_slot(self.withdrawal_hash),
+            U256::from(1),
+            &self.withdrawal_proof,
+        )
+        .map_err(|_| eyre!("invalid withdrawal proof"))
+    }
+}
+
+/// Storage slot of `sentMessages[withdrawal_hash]` in the message passer.
+pub fn withdrawal_slot(withdrawal_hash: B256) -> B256 {
+    keccak256([withdrawal_hash.as_slice(), B256::ZERO.as_slice()].concat())
+}
+
+/// Builds a verified withdrawal proof anchored at the L2 block `tag`, which fails like
+/// other reads while a tag following the head is too old.
+///
+/// When given a transaction hash, the withdrawal is decoded from its verified receipt,
+/// which must be included at or before the anchor block.
+pub async fn get_withdrawal_proof<C: Consensus<<OpStack as NetworkSpec>::BlockResponse>>(
+    node: &Node<OpStack, C>,
+    id: WithdrawalId,
+    tag: BlockTag,
+) -> Result<WithdrawalProof> {
+    node.check_blocktag_age(&tag).await?;
+
+    let execution = &node.execution;
+    let block = execution
+        .get_block(tag, false)
+        .await
+        .ok_or_else(|| eyre!("block not found: {tag}"))?;
+    let header = block.header();
+    let number = header.number();
+
+    let (withdrawal_hash, withdrawal) = match id {
+        WithdrawalId::WithdrawalHash(hash) => (hash, None),
+        WithdrawalId::TransactionHash(tx_hash) => {
+            let receipt = execution
+                .get_transaction_receipt(tx_hash)
+                .await?
+                .ok_or_else(|| eyre!("receipt not found: {tx_hash}"))?;
+
+            // a receipt without a block cannot be shown to be included at the anchor
+            if !receipt.block_number().is_some_and(|n| n <= number) {
+                eyre::bail!("transaction {tx_hash} is not included at block {number}");
+            }
+
+            let withdrawal = OpStack::receipt_logs(&receipt)
+                .iter()
+                .find_map(|log| WithdrawalTransaction::from_log(log).ok())
+                .ok_or_else(|| eyre!("no withdrawal in transaction {tx_hash}"))?;
+
+            (withdrawal.hash(), Some(withdrawal))
+        }
+    };
+
+    let slot = withdrawal_slot(withdrawal_hash);
+    let proof = execution
+        .get_proof(L2_TO_L1_MESSAGE_PASSER, &[slot], BlockTag::Number(number))
+        .await?;
+
+    let storage_proof = proof
+        .storage_proof
+        .first()
+        .ok_or_else(|| eyre!("missing withdrawal storage proof"))?;
+    if storage_proof.value != U256::from(1) {
+        eyre::bail!("withdrawal {withdrawal_hash} not found at block {number}");
+    }
+
+    let output_root_proof = OutputRootProof {
+        version: B256::ZERO,
+        state_root: header.state_root(),
+        message_passer_storage_root: proof.storage_hash,
+        latest_blockhash: header.hash(),
+    };
+
+    Ok(WithdrawalProof {
+        withdrawal_hash,
+        withdrawal,
+        l2_block_number: number,
+        output_root: output_root_proof.output_root(),
+        output_root_proof,
+        withdrawal_proof: storage_proof.proof.clone(),
+    })
+}
+
+/// Withdrawal proving on top of [`OpStackClient`].
+pub trait WithdrawalProver {
+    fn get_withdrawal_proof(
+        &self,
+        id: WithdrawalId,
+        block: BlockTag,
+    ) -> impl Future<Output = Result<WithdrawalProof>> + Send;
+}
+
+impl WithdrawalProver for OpStackClient {
+    fn get_withdrawal_proof(
+        &self,
+        id: WithdrawalId,
+        block: BlockTag,
+    ) -> impl Future<Output = Result<WithdrawalProof>> + Send {
+        get_withdrawal_proof(self.node(), id, block)
+    }
+}
@@ -0,0 +1,357 @@
+use alloy::consensus::Header as ConsensusHeader;
+use alloy::primitives::{Address, Bytes, Sealable, TxKind, B256, U256};
+use alloy::rpc::types::{
+    Block, BlockTransactions, Header, Transaction as EthTransaction, TransactionReceipt,
+};
+use alloy::sol_types::SolEvent;
+use op_alloy_consensus::{OpTxEnvelope, TxDeposit};
+use op_alloy_rpc_types::Transaction;
+
+use helios_core::execution::proof::receipts_root;
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::rpc::ChainRpc;
+use helios_ethereum::spec::Ethereum;
+use helios_opstack::deposits::{
+    check_deposits, index_l1_blocks, l1_info_source_hash, user_deposit_source_hash,
+    verify_deposits, L1DepositIndex, TransactionDeposited,
+};
+use helios_opstack::l1_origin::{
+    L1BlockInfo, L1FeeParams, L1OriginCheck, VerifiedL1Blocks, L1_BLOCK, L1_INFO_DEPOSITOR,
+};
+
+const PORTAL: Address = Address::repeat_byte(0x0b);
+const DEPOSITOR: Address = Address::repeat_byte(0xd0);
+const RECIPIENT: Address = Address::repeat_byte(0xd1);
+
+/// An L1 block whose first log is replaced by a deposit of `mint` to the recipient, indexed,
+/// returning the source hash of the deposit.
+fn indexed_deposit(mint: u128) -> (L1DepositIndex, B256) {
+    let chain = ChainBuilder::new(100).length(2).build();
+    let block = chain.head();
+    let mut receipts = chain.receipts(block.header.number).unwrap().to_vec();
+
+    let event = TransactionDeposited {
+        from: DEPOSITOR,
+        to: RECIPIENT,
+        version: U256::ZERO,
+        opaqueData: opaque_data(mint, 100_000).into(),
+    };
+    let log = event.encode_log_data();
+    let mut json = serde_json::to_value(&receipts[0]).unwrap();
+    json["logs"][0]["address"] = serde_json::to_value(PORTAL).unwrap();
+    json["logs"][0]["topics"] = serde_json::to_value(log.topics()).unwrap();
+    json["logs"][0]["data"] = serde_json::to_value(&log.data).unwrap();
+    receipts[0] = serde_json::from_value(json).unwrap();
+
+    let index = L1DepositIndex::new(PORTAL);
+    let root = receipts_root::<Ethereum>(&receipts);
+    let count = index
+        .insert_block(block.header.number, block.header.hash, root, &receipts)
+        .unwrap();
+    assert_eq!(count, 1);
+
+    let log_index = receipts[0].inner.logs()[0].log_index.unwrap();
+    let source_hash = user_deposit_source_hash(block.header.hash, log_index);
+    assert!(index.get(&source_hash).is_some());
+    (index, source_hash)
+}
+
+/// The packed mint, value, gas limit and creation flag of a call without data.
+fn opaque_data(mint: u128, gas_limit: u64) -> Vec<u8> {
+    let mut data = U256::from(mint).to_be_bytes::<32>().to_vec();
+    data.extend(U256::from(mint).to_be_bytes::<32>());
+    data.extend(gas_limit.to_be_bytes());
+    data.push(0);
+    data
+}
+
+fn deposit_tx(source_hash: B256, mint: u128) -> TxDeposit {
+    TxDeposit {
+        source_hash,
+        from: DEPOSITOR,
+        to: TxKind::Call(RECIPIENT),
+        mint: Some(mint),
+        value: U256::from(mint),
+        gas_limit: 100_000,
+        is_system_transaction: false,
+        input: Bytes::new(),
+    }
+}
+
+/// The ecotone L1 attributes of the blocks built by [`l2_block`].
+fn origin() -> L1BlockInfo {
+    L1BlockInfo {
+        number: 19_424_680,
+        timestamp: 1_710_374_459,
+        base_fee: U256::from(35_882_298_859u64),
+        hash: B256::repeat_byte(0x4f),
+        sequence_number: 5,
+        batcher_hash: B256::repeat_byte(0x68),
+        fee_params: L1FeeParams::Ecotone {
+            base_fee_scalar: 1_368,
+            blob_base_fee_scalar: 810_949,
+            blob_base_fee: U256::from(1),
+        },
+    }
+}
+
+/// The verified L1 blocks, holding the origin of the blocks built by [`l2_block`].
+fn verified_origin() -> VerifiedL1Blocks {
+    let l1_blocks = VerifiedL1Blocks::default();
+    let info = origin();
+    l1_blocks.insert(info.number, info.hash);
+    l1_blocks
+}
+
+/// An L2 block leading with the L1 attributes deposit of [`origin`], followed by `deposits`.
+fn l2_block(deposits: Vec<TxDeposit>) -> Block<Transaction> {
+    l2_block_with_input(origin().encode(), deposits)
+}
+
+/// An L2 block leading with an L1 attributes deposit of `input` for the source hash of
+/// [`origin`], followed by `deposits`.
+fn l2_block_with_input(input: Bytes, deposits: Vec<TxDeposit>) -> Block<Transaction> {
+    let info = origin();
+    let l1_info = TxDeposit {
+        source_hash: l1_info_source_hash(info.hash, info.sequence_number),
+        from: L1_INFO_DEPOSITOR,
+        to: TxKind::Call(L1_BLOCK),
+        mint: None,
+        value: U256::ZERO,
+        gas_limit: 1_000_000,
+        is_system_transaction: false,
+        input,
+    };
+
+    let txs = [l1_info]
+        .into_iter()
+        .chain(deposits)
+        .map(|deposit| Transaction {
+            inner: EthTransaction {
+                from: deposit.from,
+                inner: OpTxEnvelope::Deposit(deposit.seal_slow()),
+                block_hash: None,
+                block_number: Some(1),
+                transaction_index: None,
+                effective_gas_price: None,
+            },
+            deposit_nonce: None,
+            deposit_receipt_version: None,
+        })
+        .collect();
+
+    let header = Header {
+        hash: B256::repeat_byte(0x12),
+        inner: ConsensusHeader {
+            number: 1,
+            ..Default::default()
+        },
+        total_difficulty: None,
+        size: None,
+    };
+    Block::new(header, BlockTransactions::Full(txs))
+}
+
+#[test]
+fn test_deposit_matching_l1_event_verified() {
+    let (index, source_hash) = indexed_deposit(5_000);
+    let block = l2_block(vec![deposit_tx(source_hash, 5_000)]);
+    let l1_blocks = verified_origin();
+
+    let check = verify_deposits(&block, &index, &l1_blocks).unwrap();
+    assert!(check.is_verified());
+    assert_eq!(check.l1_info, Some(origin()));
+    check_deposits(&block, &index, &l1_blocks, true).unwrap();
+}
+
+#[test]
+fn test_deposit_with_tampered_mint_rejected() {
+    let (index, source_hash) = indexed_deposit(5_000);
+    let block = l2_block(vec![deposit_tx(source_hash, 5_001)]);
+
+    let l1_blocks = verified_origin();
+
+    let err = verify_deposits(&block, &index, &l1_blocks).unwrap_err();
+    assert!(err.to_string().contains("mint differs"), "{err}");
+    // a contradicted deposit is rejected however strict the check
+    assert!(check_deposits(&block, &index, &l1_blocks, false).is_err());
+}
+
+#[test]
+fn test_deposit_with_unknown_source_hash_unmatched() {
+    let (index, _) = indexed_deposit(5_000);
+    let unknown = B256::repeat_byte(0x77);
+    let block = l2_block(vec![deposit_tx(unknown, 5_000)]);
+    let l1_blocks = verified_origin();
+
+    let check = verify_deposits(&block, &index, &l1_blocks).unwrap();
+    assert_eq!(check.origin, L1OriginCheck::Verified);
+    assert_eq!(check.unmatched, vec![unknown]);
+    check_deposits(&block, &index, &l1_blocks, false).unwrap();
+    let err = check_deposits(&block, &index, &l1_blocks, true).unwrap_err();
+    assert!(
+        err.to_string().contains("without a verified l1 event"),
+        "{err}"
+    );
+}
+
+#[test]
+fn test_l1_info_deposit_with_wrong_source_hash_rejected() {
+    let index = L1DepositIndex::new(PORTAL);
+    let mut block = l2_block(Vec::new());
+    let BlockTransactions::Full(txs) = &mut block.transactions else {
+        unreachable!();
+    };
+    let OpTxEnvelope::Deposit(deposit) = &txs[0].inner.inner else {
+        unreachable!();
+    };
+    let tampered = TxDeposit {
+        source_hash: B256::repeat_byte(0x01),
+        ..deposit.inner().clone()
+    };
+    txs[0].inner.inner = OpTxEnvelope::Deposit(tampered.seal_slow());
+
+    let err = verify_deposits(&block, &index, &verified_origin()).unwrap_err();
+    assert!(err.to_string().contains("l1 attributes deposit"), "{err}");
+}
+
+#[test]
+fn test_origin_checked_against_verified_l1_blocks() {
+    let index = L1DepositIndex::new(PORTAL);
+    let block = l2_block(Vec::new());
+
+    // a source hash derived from the origin it names proves nothing about that origin
+    let l1_blocks = VerifiedL1Blocks::default();
+    let check = verify_deposits(&block, &index, &l1_blocks).unwrap();
+    assert_eq!(check.origin, L1OriginCheck::Unavailable);
+    assert!(!check.is_verified());
+    check_deposits(&block, &index, &l1_blocks, false).unwrap();
+    let err = check_deposits(&block, &index, &l1_blocks, true).unwrap_err();
+    assert!(err.to_string().contains("not verified yet"), "{err}");
+
+    l1_blocks.insert(origin().number, B256::repeat_byte(0x50));
+    let err = verify_deposits(&block, &index, &l1_blocks).unwrap_err();
+    assert!(
+        err.to_string().contains("is not the verified l1 block"),
+        "{err}"
+    );
+    assert!(check_deposits(&block, &index, &l1_blocks, false).is_err());
+}
+
+#[test]
+fn test_isthmus_l1_info_verified() {
+    let info = L1BlockInfo {
+        fee_params: L1FeeParams::Isthmus {
+            base_fee_scalar: 1_368,
+            blob_base_fee_scalar: 810_949,
+            blob_base_fee: U256::from(1),
+            operator_fee_scalar: 0,
+            operator_fee_constant: 0,
+        },
+        ..origin()
+    };
+    let block = l2_block_with_input(info.encode(), Vec::new());
+
+    let check = verify_deposits(&block, &L1DepositIndex::new(PORTAL), &verified_origin()).unwrap();
+    assert!(check.is_verified());
+    assert_eq!(check.l1_info, Some(info));
+}
+
+#[test]
+fn test_undecodable_l1_info_only_rejected_when_strict() {
+    let index = L1DepositIndex::new(PORTAL);
+    let l1_blocks = verified_origin();
+    let block = l2_block_with_input(Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]), Vec::new());
+
+    let check = check_deposits(&block, &index, &l1_blocks, false).unwrap();
+    assert_eq!(check.l1_info, None);
+    assert_eq!(check.origin, L1OriginCheck::Unavailable);
+    let err = check_deposits(&block, &index, &l1_blocks, true).unwrap_err();
+    assert!(err.to_string().contains("could not be decoded"), "{err}");
+}
+
+#[tokio::test]
+async fn test_skipped_l1_blocks_indexed_from_parent_hashes() {
+    let chain = ChainBuilder::new(138).start_at(100).length(6).build();
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let index = L1DepositIndex::new(chain.token());
+    let l1_blocks = VerifiedL1Blocks::default();
+
+    // a first head far into the chain walks down to the start of the lookback, and the
+    // missing block there is retried with the next head
+    let head = &chain.blocks()[5].header;
+    assert!(index_l1_blocks(&index, &l1_blocks, &rpc, head)
+        .await
+        .is_err());
+    assert_eq!(index.latest_block(), None);
+    let fetched = rpc.calls("get_block");
+
+    let number = 101;
+    let block = chain.block(number).unwrap();
+    let receipts = chain.receipts(number).unwrap();
+    index
+        .insert_block(
+            number,
+            block.header.hash,
+            block.header.receipts_root,
+            receipts,
+        )
+        .unwrap();
+
+    // the light client skipped blocks 102 to 104
+    index_l1_blocks(&index, &l1_blocks, &rpc, head)
+        .await
+        .unwrap();
+    assert_eq!(index.latest_block(), Some(105));
+    for number in 102..=105 {
+        let hash = chain.block(number).unwrap().header.hash;
+        assert_eq!(l1_blocks.check(number, hash), L1OriginCheck::Verified);
+    }
+    assert_eq!(rpc.calls("get_block") - fetched, 3);
+    assert_eq!(rpc.calls("get_block_receipts"), 4);
+
+    // a parent that is not the one the verified head commits to is rejected
+    let mut forged = head.clone();
+    forged.inner.parent_hash = B256::repeat_byte(0x03);
+    forged.inner.number = 107;
+    assert!(index_l1_blocks(&index, &l1_blocks, &rpc, &forged)
+        .await
+        .is_err());
+    assert_eq!(index.latest_block(), Some(105));
+}
+
+#[test]
+fn test_index_checks_receipts_root_and_prunes() {
+    let chain = ChainBuilder::new(101).length(3).build();
+    let index = L1DepositIndex::new(chain.token());
+    let block = &chain.blocks()[1];
+    let receipts: Vec<TransactionReceipt> = chain.receipts(block.header.number).unwrap().to_vec();
+
+    let err = index
+        .insert_block(
+            block.header.number,
+            block.header.hash,
+            B256::repeat_byte(0x02),
+            &receipts,
+        )
+        .unwrap_err();
+    assert!(err.to_string().contains("receipts root"), "{err}");
+
+    // the transfer logs of the token are not deposit events
+    let count = index
+        .insert_block(
+            block.header.number,
+            block.header.hash,
+            block.header.receipts_root,
+            &receipts,
+        )
+        .unwrap();
+    assert_eq!(count, 0);
+
+    let (index, source_hash) = indexed_deposit(1);
+    let number = index.latest_block().unwrap();
+    index.prune_before(number);
+    assert!(index.get(&source_hash).is_some());
+    index.prune_before(number + 1);
+    assert!(index.is_empty());
+}
@@ -0,0 +1,290 @@
+use alloy::consensus::Header as ConsensusHeader;
+use alloy::primitives::{b256, Address, FixedBytes, TxKind, B256, U256};
+use alloy::rpc::types::{Block, BlockTransactions, Header, Transaction as EthTransaction};
+use alloy::sol_types::SolValue;
+use op_alloy_consensus::{OpTxEnvelope, TxDeposit};
+use op_alloy_rpc_types::Transaction;
+
+use helios_core::execution::backfill::BackfillConfig;
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::testing::chain::ChainBuilder;
+use helios_core::testing::rpc::ChainRpc;
+use helios_core::types::BlockTag;
+use helios_opstack::deposits::l1_info_source_hash;
+use helios_opstack::l1_origin::{
+    get_l1_origin, L1BlockInfo, L1FeeParams, L1OriginCheck, VerifiedL1Blocks, L1_BLOCK,
+    L1_INFO_DEPOSITOR, MAX_VERIFIED_L1_BLOCKS,
+};
+use helios_opstack::spec::OpStack;
+
+const BATCHER_HASH: B256 =
+    b256!("0000000000000000000000006887246668a3b87f54deb3b94ba47a6f63f32985");
+
+fn info(fee_params: L1FeeParams) -> L1BlockInfo {
+    L1BlockInfo {
+        number: 21_800_000,
+        timestamp: 1_739_000_000,
+        base_fee: U256::from(3_170_000_000u64),
+        hash: B256::repeat_byte(0x4f),
+        sequence_number: 5,
+        batcher_hash: BATCHER_HASH,
+        fee_params,
+    }
+}
+
+fn ecotone() -> L1BlockInfo {
+    info(L1FeeParams::Ecotone {
+        base_fee_scalar: 5_227,
+        blob_base_fee_scalar: 1_014_213,
+        blob_base_fee: U256::from(1),
+    })
+}
+
+fn isthmus() -> L1BlockInfo {
+    info(L1FeeParams::Isthmus {
+        base_fee_scalar: 5_227,
+        blob_base_fee_scalar: 1_014_213,
+        blob_base_fee: U256::from(1),
+        operator_fee_scalar: 7,
+        operator_fee_constant: 300,
+    })
+}
+
+/// The calldata of `setL1BlockValuesEcotone()` and `setL1BlockValuesIsthmus()` as the
+/// `L1Block` predeploy reads it, the fields abi packed after the selector in the order the
+/// contract loads them.
+fn packed_calldata(info: &L1BlockInfo) -> Vec<u8> {
+    let (selector, base_fee_scalar, blob_base_fee_scalar, blob_base_fee) = match info.fee_params {
+        L1FeeParams::Ecotone {
+            base_fee_scalar,
+            blob_base_fee_scalar,
+            blob_base_fee,
+        } => (
+            [0x44, 0x0a, 0x5e, 0x20],
+            base_fee_scalar,
+            blob_base_fee_scalar,
+            blob_base_fee,
+        ),
+        L1FeeParams::Isthmus {
+            base_fee_scalar,
+            blob_base_fee_scalar,
+            blob_base_fee,
+            ..
+        } => (
+            [0x09, 0x89, 0x99, 0xbe],
+            base_fee_scalar,
+            blob_base_fee_scalar,
+            blob_base_fee,
+        ),
+        L1FeeParams::Bedrock { .. } => unreachable!(),
+    };
+
+    let mut calldata = (
+        FixedBytes::<4>::from(selector),
+        base_fee_scalar,
+        blob_base_fee_scalar,
+        info.sequence_number,
+        info.timestamp,
+        info.number,
+        info.base_fee,
+        blob_base_fee,
+        info.hash,
+        info.batcher_hash,
+    )
+        .abi_encode_packed();
+    if let L1FeeParams::Isthmus {
+        operator_fee_scalar,
+        operator_fee_constant,
+        ..
+    } = info.fee_params
+    {
+        calldata.extend((operator_fee_scalar, operator_fee_constant).abi_encode_packed());
+    }
+    calldata
+}
+
+/// An L2 block at `number` leading with the L1 attributes deposit `first`.
+fn l2_block(number: u64, first: TxDeposit) -> Block<Transaction> {
+    let tx = Transaction {
+        inner: EthTransaction {
+            from: first.from,
+            inner: OpTxEnvelope::Deposit(first.seal_slow()),
+            block_hash: None,
+            block_number: Some(number),
+            transaction_index: Some(0),
+            effective_gas_price: None,
+        },
+        deposit_nonce: None,
+        deposit_receipt_version: None,
+    };
+    let inner = ConsensusHeader {
+        number,
+        ..Default::default()
+    };
+    let header = Header {
+        hash: inner.hash_slow(),
+        inner,
+        total_difficulty: None,
+        size: None,
+    };
+    Block::new(header, BlockTransactions::Full(vec![tx]))
+}
+
+fn l1_info_deposit(info: &L1BlockInfo) -> TxDeposit {
+    TxDeposit {
+        source_hash: l1_info_source_hash(info.hash, info.sequence_number),
+        from: L1_INFO_DEPOSITOR,
+        to: TxKind::Call(L1_BLOCK),
+        mint: None,
+        value: U256::ZERO,
+        gas_limit: 1_000_000,
+        is_system_transaction: false,
+        input: info.encode(),
+    }
+}
+
+#[test]
+fn test_decode_bedrock_l1_info() {
+    let info = info(L1FeeParams::Bedrock {
+        l1_fee_overhead: U256::from(188),
+        l1_fee_scalar: U256::from(684_000),
+    });
+    let calldata = info.encode();
+
+    // `setL1BlockValues` is abi encoded, one word for each of its eight arguments
+    assert_eq!(calldata[..4], [0x01, 0x5d, 0x8e, 0xb9]);
+    assert_eq!(calldata.len(), 4 + 8 * 32);
+    assert_eq!(L1BlockInfo::decode(&calldata).unwrap(), info);
+}
+
+#[test]
+fn test_decode_packed_l1_info() {
+    for info in [ecotone(), isthmus()] {
+        let calldata = packed_calldata(&info);
+        assert_eq!(L1BlockInfo::decode(&calldata).unwrap(), info);
+        assert_eq!(info.encode().as_ref(), calldata.as_slice());
+    }
+    assert_eq!(packed_calldata(&ecotone()).len(), 164);
+    assert_eq!(packed_calldata(&isthmus()).len(), 176);
+}
+
+#[test]
+fn test_decode_rejects_malformed_l1_info() {
+    for info in [ecotone(), isthmus()] {
+        let calldata = info.encode();
+        let truncated = &calldata[..calldata.len() - 1];
+        assert!(L1BlockInfo::decode(truncated).is_err(), "{info:?}");
+    }
+
+    // an isthmus selector on ecotone calldata is short of the operator fee
+    let mut calldata = ecotone().encode().to_vec();
+    calldata[..4].copy_from_slice(&[0x09, 0x89, 0x99, 0xbe]);
+    let err = L1BlockInfo::decode(&calldata).unwrap_err();
+    assert!(err.to_string().contains("isthmus"), "{err}");
+
+    calldata[..4].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+    let err = L1BlockInfo::decode(&calldata).unwrap_err();
+    assert!(err.to_string().contains("0xdeadbeef"));
+
+    assert!(L1BlockInfo::decode(&[0x44, 0x0a]).is_err());
+}
+
+#[test]
+fn test_l1_info_read_from_block() {
+    let info = isthmus();
+    let block = l2_block(1, l1_info_deposit(&info));
+    assert_eq!(L1BlockInfo::from_block(&block).unwrap(), info);
+
+    // only the deposit of the depositor account to the predeploy is read
+    let mut forged = l1_info_deposit(&info);
+    forged.from = Address::repeat_byte(1);
+    let err = L1BlockInfo::from_block(&l2_block(1, forged)).unwrap_err();
+    assert!(err.to_string().contains("l1 attributes deposit"), "{err}");
+
+    let mut empty = block;
+    empty.transactions = BlockTransactions::Full(Vec::new());
+    assert!(L1BlockInfo::from_block(&empty).is_err());
+}
+
+#[tokio::test]
+async fn test_get_l1_origin_of_verified_block() {
+    let chain = ChainBuilder::new(137).length(2).build();
+    let rpc = ChainRpc::from_chain(chain.clone());
+    let state =
+        State::<OpStack, _>::unsynced(64, rpc.clone()).with_backfill(BackfillConfig::disabled());
+    let info = isthmus();
+    let block = l2_block(1, l1_info_deposit(&info));
+    state.push_block(block.clone()).await;
+    let execution = ExecutionClient::with_rpc(rpc, state, chain.fork_schedule());
+
+    let origin = get_l1_origin(&execution, BlockTag::Latest, None)
+        .await
+        .unwrap();
+    assert_eq!(origin.l2_block_hash, block.header.hash);
+    assert_eq!((origin.number, origin.hash), (info.number, info.hash));
+    assert_eq!(origin.sequence_number, info.sequence_number);
+    assert_eq!(origin.check, L1OriginCheck::Unchecked);
+
+    let l1 = VerifiedL1Blocks::default();
+    l1.insert(info.number, B256::repeat_byte(0x50));
+    let origin = get_l1_origin(&execution, BlockTag::Number(1), Some(&l1))
+        .await
+        .unwrap();
+    assert_eq!(
+        origin.check,
+        L1OriginCheck::Mismatch {
+            verified_hash: B256::repeat_byte(0x50)
+        }
+    );
+}
+
+#[test]
+fn test_origin_checked_against_verified_l1() {
+    let info = ecotone();
+    let blocks = VerifiedL1Blocks::default();
+    assert_eq!(
+        blocks.check(info.number, info.hash),
+        L1OriginCheck::Unavailable
+    );
+
+    blocks.insert(info.number, info.hash);
+    assert_eq!(
+        blocks.check(info.number, info.hash),
+        L1OriginCheck::Verified
+    );
+
+    let other = B256::repeat_byte(7);
+    assert_eq!(
+        blocks.check(info.number, other),
+        L1OriginCheck::Mismatch {
+            verified_hash: info.hash
+        }
+    );
+}
+
+#[test]
+fn test_verified_l1_blocks_bounded() {
+    let blocks = VerifiedL1Blocks::default();
+    let count = MAX_VERIFIED_L1_BLOCKS as u64 + 10;
+    for number in 0..count {
+        blocks.insert(number, B256::with_last_byte(number as u8));
+    }
+
+    assert!(blocks.get(9).is_none());
+    assert!(blocks.get(10).is_some());
+    assert!(blocks.get(count - 1).is_some());
+}
+
+#[test]
+fn test_origin_check_serialization() {
+    let value = serde_json::to_value(L1OriginCheck::Mismatch {
+        verified_hash: B256::ZERO,
+    })
+    .unwrap();
+    assert_eq!(value["status"], "mismatch");
+    assert_eq!(value["verifiedHash"], B256::ZERO.to_string());
+
+    let value = serde_json::to_value(L1OriginCheck::Unchecked).unwrap();
+    assert_eq!(value["status"], "unchecked");
+}
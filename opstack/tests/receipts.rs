@@ -0,0 +1,112 @@
+use alloy::primitives::B256;
+use tokio::sync::{mpsc, watch};
+
+use helios_core::execution::errors::ExecutionError;
+use helios_core::execution::state::State;
+use helios_core::execution::ExecutionClient;
+use helios_core::network_spec::NetworkSpec;
+use helios_core::testing::chain::{ChainBuilder, MockChain};
+use helios_core::testing::rpc::ChainRpc;
+use helios_opstack::spec::OpStack;
+
+type Block = <OpStack as NetworkSpec>::BlockResponse;
+
+/// An execution client verifying the blocks of a mock chain as OP Stack blocks.
+struct Harness {
+    client: ExecutionClient<OpStack, ChainRpc>,
+    rpc: ChainRpc,
+    // dropping the senders stops the state task
+    _block_send: mpsc::Sender<Block>,
+    _finalized_block_send: watch::Sender<Option<Block>>,
+}
+
+impl Harness {
+    async fn new(chain: &MockChain) -> Self {
+        let (block_send, block_recv) = mpsc::channel(256);
+        let (finalized_block_send, finalized_block_recv) = watch::channel(None);
+
+        let rpc = ChainRpc::from_chain(chain.clone());
+        let state = State::with_rpc(block_recv, finalized_block_recv, 64, rpc.clone());
+        for block in chain.blocks() {
+            let json = serde_json::to_value(block).unwrap();
+            state
+                .push_block(serde_json::from_value(json).unwrap())
+                .await;
+        }
+
+        Self {
+            client: ExecutionClient::with_rpc(rpc.clone(), state, chain.fork_schedule()),
+            rpc,
+            _block_send: block_send,
+            _finalized_block_send: finalized_block_send,
+        }
+    }
+}
+
+/// Hashes of the head block's transactions, alternating token transfers with a single log
+/// and plain EIP-1559 value transfers.
+fn head_tx_hashes(chain: &MockChain) -> Vec<B256> {
+    let receipts = chain.receipts(chain.head().header.number).unwrap();
+    receipts
+        .iter()
+        .map(|receipt| receipt.transaction_hash)
+        .collect()
+}
+
+async fn mismatched_receipt_error(
+    index: usize,
+    tamper: impl Fn(&mut serde_json::Value) + Send + Sync + 'static,
+) -> eyre::Report {
+    let chain = ChainBuilder::new(41).length(2).typed_txs().build();
+    let harness = Harness::new(&chain).await;
+
+    harness.rpc.map_receipts(tamper);
+    let hash = head_tx_hashes(&chain)[index];
+    harness
+        .client
+        .get_transaction_receipt(hash)
+        .await
+        .unwrap_err()
+}
+
+fn is_receipt_mismatch(err: &eyre::Report) -> bool {
+    matches!(
+        err.downcast_ref::<ExecutionError>(),
+        Some(ExecutionError::ReceiptMismatch(..))
+    )
+}
+
+#[tokio::test]
+async fn test_receipts_verified() {
+    let chain = ChainBuilder::new(40).length(2).typed_txs().build();
+    let harness = Harness::new(&chain).await;
+
+    for (hash, expected) in head_tx_hashes(&chain)
+        .into_iter()
+        .zip(chain.receipts(chain.head().header.number).unwrap())
+    {
+        let receipt = harness
+            .client
+            .get_transaction_receipt(hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(receipt.inner.transaction_hash, hash);
+        assert_eq!(receipt.inner.inner.logs(), expected.inner.logs());
+    }
+}
+
+#[tokio::test]
+async fn test_receipt_with_tampered_status_rejected() {
+    let err = mismatched_receipt_error(1, |json| json["status"] = "0x0".into()).await;
+    assert!(is_receipt_mismatch(&err), "{err}");
+}
+
+#[tokio::test]
+async fn test_receipt_with_tampered_log_rejected() {
+    let err = mismatched_receipt_error(0, |json| {
+        json["logs"][0]["address"] = format!("0x{}", "42".repeat(20)).into()
+    })
+    .await;
+    assert!(is_receipt_mismatch(&err), "{err}");
+}
@@ -0,0 +1,198 @@
+use alloy::consensus::Header as ConsensusHeader;
+use alloy::primitives::B256;
+use alloy::rpc::types::Header;
+use serde_json::{json, Value};
+use url::Url;
+
+use helios_core::config::ConfigDuration;
+use helios_core::execution::rpc::mock_rpc::{MockRpc, Outcome};
+use helios_core::types::HeadFeed;
+use helios_opstack::sequencer::{SequencerConfig, SequencerFeed};
+
+const START: u64 = 1_700_000_000;
+
+/// `length` linked headers from `number` onto `parent`, two seconds apart, and told apart
+/// from other branches by `branch`. Oldest first.
+fn headers(number: u64, length: u64, parent: B256, branch: u8) -> Vec<Header> {
+    let mut parent_hash = parent;
+    (number..number + length)
+        .map(|number| {
+            let inner = ConsensusHeader {
+                number,
+                parent_hash,
+                timestamp: START + (number - 100) * 2,
+                extra_data: vec![branch].into(),
+                ..Default::default()
+            };
+            let header = Header {
+                hash: inner.hash_slow(),
+                inner,
+                total_difficulty: None,
+                size: None,
+            };
+            parent_hash = header.hash;
+            header
+        })
+        .collect()
+}
+
+fn newest_first(headers: &[Header]) -> Vec<Header> {
+    headers.iter().rev().cloned().collect()
+}
+
+/// `header` as the execution rpc serves its block, without transactions.
+fn block(header: &Header) -> Value {
+    let mut block = serde_json::to_value(header).unwrap();
+    block["transactions"] = json!([]);
+    block["uncles"] = json!([]);
+    block
+}
+
+fn url(url: &str) -> Url {
+    url.parse().unwrap()
+}
+
+/// A feed from a primary and one replica that verified blocks 100 to 109, the last of them
+/// accepted at `START + 18`.
+fn feed(safe_only: bool) -> (SequencerFeed, Vec<Header>) {
+    let config = SequencerConfig {
+        stale_after: ConfigDuration::from_secs(30),
+        replicas: vec![url("https://replica.example/")],
+        safe_only,
+    };
+    let mut feed = SequencerFeed::new(&url("https://primary.example/"), &config, START);
+    let verified = headers(100, 10, B256::ZERO, 0);
+    for header in &verified {
+        feed.accept(header);
+    }
+    (feed, verified)
+}
+
+#[test]
+fn test_stalled_primary_fails_over_to_replica() {
+    let (mut feed, verified) = feed(false);
+    let head_feed = feed.head_feed();
+
+    assert!(!feed.check(START + 40));
+    assert!(feed.failover_candidates(START + 40).is_empty());
+    assert_eq!(*head_feed.borrow(), HeadFeed::Live);
+
+    assert!(feed.check(START + 60));
+    assert_eq!(*head_feed.borrow(), HeadFeed::Stalled);
+    assert_eq!(feed.failover_candidates(START + 60), [1]);
+    // not tried again before another stale period passed
+    assert!(feed.failover_candidates(START + 61).is_empty());
+
+    // the replica extends the verified chain, fetched down to the verified block 109
+    let extension = headers(110, 3, verified[9].hash, 0);
+    let mut branch = newest_first(&extension);
+    branch.push(verified[9].clone());
+    feed.switch_to(1, &branch).unwrap();
+    assert_eq!(feed.endpoint(), &url("https://replica.example/"));
+
+    feed.accept(&extension[2]);
+    assert_eq!(feed.latest(), Some(112));
+    assert!(!feed.check(START + 40));
+    assert_eq!(*head_feed.borrow(), HeadFeed::Live);
+}
+
+#[test]
+fn test_diverged_replica_rejected() {
+    let (mut feed, verified) = feed(false);
+    assert!(feed.check(START + 60));
+
+    // forked off after block 104, so its walk down ends at its own block 109
+    let fork = headers(105, 8, verified[4].hash, 1);
+    let branch = newest_first(&fork[4..]);
+    assert!(feed.reaches_verified(branch.last().unwrap()));
+    let err = feed.switch_to(1, &branch).unwrap_err();
+    assert!(err.to_string().contains("diverges"), "{err}");
+    assert_eq!(feed.endpoint(), &url("https://primary.example/"));
+
+    // nor is a branch accepted that stops short of a verified height or is not linked
+    let err = feed.check_ancestry(&branch[..2]).unwrap_err();
+    assert!(err.to_string().contains("no verified ancestor"), "{err}");
+    let extension = headers(110, 3, verified[8].hash, 0);
+    let mut branch = newest_first(&extension);
+    branch.push(verified[9].clone());
+    assert!(feed.check_ancestry(&branch).is_err());
+
+    let mut forged = newest_first(&headers(110, 3, verified[9].hash, 0));
+    forged[0].inner.gas_used += 1;
+    forged.push(verified[9].clone());
+    assert!(feed.check_ancestry(&forged).is_err());
+}
+
+#[test]
+fn test_safe_only_without_replicas() {
+    let config = SequencerConfig {
+        safe_only: true,
+        ..Default::default()
+    };
+    let mut feed = SequencerFeed::new(&url("https://primary.example/"), &config, START);
+    let head_feed = feed.head_feed();
+    let verified = headers(100, 1, B256::ZERO, 0);
+    feed.accept(&verified[0]);
+
+    assert!(feed.check(START + 31));
+    assert_eq!(*head_feed.borrow(), HeadFeed::SafeOnly);
+    assert!(feed.failover_candidates(START + 31).is_empty());
+
+    feed.accept(&headers(101, 1, verified[0].hash, 0)[0]);
+    assert_eq!(*head_feed.borrow(), HeadFeed::Live);
+}
+
+#[tokio::test]
+async fn test_replica_ancestry_fetched_from_execution_rpc() {
+    let (mut feed, verified) = feed(false);
+    assert!(feed.check(START + 60));
+
+    // the replica serves block 112, whose ancestors down to the verified 109 are fetched
+    let extension = headers(110, 3, verified[9].hash, 0);
+    let rpc = MockRpc::builder()
+        .script(
+            "get_block",
+            [
+                Outcome::Ok(block(&extension[1])),
+                Outcome::Ok(block(&extension[0])),
+                Outcome::Ok(block(&verified[9])),
+            ],
+        )
+        .build();
+    let branch = feed
+        .fetch_ancestry(&rpc, extension[2].clone())
+        .await
+        .unwrap();
+
+    let hashes = branch.iter().map(|header| header.hash).collect::<Vec<_>>();
+    let expected = [
+        extension[2].hash,
+        extension[1].hash,
+        extension[0].hash,
+        verified[9].hash,
+    ];
+    assert_eq!(hashes, expected);
+    let requested = rpc
+        .calls_to("get_block")
+        .into_iter()
+        .map(|call| call.args[0].clone())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        requested,
+        vec![json!(expected[1]), json!(expected[2]), json!(expected[3])]
+    );
+    feed.switch_to(1, &branch).unwrap();
+    assert_eq!(feed.endpoint(), &url("https://replica.example/"));
+
+    // an ancestor the rpc cannot serve fails the failover
+    let (feed, verified) = self::feed(false);
+    let extension = headers(110, 2, verified[9].hash, 0);
+    let rpc = MockRpc::builder()
+        .script("get_block", [Outcome::Err("unavailable".to_string())])
+        .build();
+    let err = feed
+        .fetch_ancestry(&rpc, extension[1].clone())
+        .await
+        .unwrap_err();
+    assert!(format!("{err:#}").contains("unavailable"), "{err:#}");
+}
@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::time::{sleep, timeout};
+
+use helios_core::consensus::Consensus;
+use helios_opstack::config::Config;
+use helios_opstack::consensus::ConsensusClient;
+
+/// Answers every request with not found, counting those for the latest unsafe head.
+async fn consensus_server() -> (String, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let heads = Arc::new(AtomicUsize::new(0));
+    let counted = heads.clone();
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+                match stream.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => request.extend_from_slice(&buf[..read]),
+                }
+            }
+            if request.starts_with(b"GET /latest ") {
+                counted.fetch_add(1, Ordering::SeqCst);
+            }
+            let response =
+                "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n";
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    });
+
+    (url, heads)
+}
+
+fn config(url: &str) -> Config {
+    serde_json::from_value(json!({
+        "consensus_rpc": url,
+        "execution_rpc": url,
+        "chain": {
+            "chain_id": 10,
+            "unsafe_signer": "0xaaaa45d9549eda09e70937013520214382ffc4a2",
+            "system_config_contract": "0x229047fed2591dbec1ef1118d64f7af3db9eb290",
+            "eth_network": "Mainnet",
+        },
+        "verify_unsafe_signer": false,
+    }))
+    .unwrap()
+}
+
+async fn wait_for_head_request(heads: &AtomicUsize, after: usize) {
+    timeout(Duration::from_secs(5), async {
+        while heads.load(Ordering::SeqCst) <= after {
+            sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_shutdown_pauses_following_until_restart() {
+    let (url, heads) = consensus_server().await;
+    let consensus = ConsensusClient::new(&config(&url));
+    wait_for_head_request(&heads, 0).await;
+
+    consensus.shutdown().unwrap();
+    // a request in flight when shutting down may still land
+    sleep(Duration::from_millis(200)).await;
+    let paused = heads.load(Ordering::SeqCst);
+    sleep(Duration::from_millis(2_500)).await;
+    assert_eq!(heads.load(Ordering::SeqCst), paused);
+
+    consensus.restart().unwrap();
+    wait_for_head_request(&heads, paused).await;
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+
+use alloy::primitives::{address, b256, keccak256, Bytes, Log as PrimitiveLog, B256, U256};
+use alloy::rpc::types::Log;
+use alloy::sol_types::SolEvent;
+use alloy_trie::proof::ProofRetainer;
+use alloy_trie::{HashBuilder, Nibbles};
+
+use helios_opstack::withdrawal::{
+    withdrawal_slot, MessagePassed, OutputRootProof, WithdrawalId, WithdrawalProof,
+    WithdrawalTransaction, L2_TO_L1_MESSAGE_PASSER,
+};
+
+fn withdrawal() -> WithdrawalTransaction {
+    WithdrawalTransaction {
+        // versioned nonce, the version lives in the top two bytes
+        nonce: (U256::from(1) << 240) | U256::from(7),
+        sender: address!("4200000000000000000000000000000000000010"),
+        target: address!("99c9fc46f92e8a1c0dec1b1747d010903e884be1"),
+        value: U256::from(1_000_000_000_000_000u64),
+        gas_limit: U256::from(200_000),
+        data: Bytes::from_static(&[0xde, 0xad, 0xbe, 0xef]),
+    }
+}
+
+fn message_passed_log(withdrawal: &WithdrawalTransaction, hash: B256) -> Log {
+    let event = MessagePassed {
+        nonce: withdrawal.nonce,
+        sender: withdrawal.sender,
+        target: withdrawal.target,
+        value: withdrawal.value,
+        gasLimit: withdrawal.gas_limit,
+        data: withdrawal.data.clone(),
+        withdrawalHash: hash,
+    };
+
+    Log {
+        inner: PrimitiveLog {
+            address: L2_TO_L1_MESSAGE_PASSER,
+            data: event.encode_log_data(),
+        },
+        ..Default::default()
+    }
+}
+
+/// Message passer storage with a few unrelated withdrawals next to `hash`, returning
+/// the storage root and the proof for `hash`.
+fn message_passer_storage(hash: B256) -> (B256, Vec<Bytes>) {
+    let mut leaves = BTreeMap::new();
+    for seed in 0..8u8 {
+        leaves.insert(keccak256(withdrawal_slot(keccak256([seed]))), vec![1]);
+    }
+    let target = keccak256(withdrawal_slot(hash));
+    leaves.insert(target, vec![1]);
+
+    let retainer = ProofRetainer::new(vec![Nibbles::unpack(target)]);
+    let mut builder = HashBuilder::default().with_proof_retainer(retainer);
+    for (key, value) in &leaves {
+        builder.add_leaf(Nibbles::unpack(key), value);
+    }
+
+    let root = builder.root();
+    let proof = builder
+        .take_proof_nodes()
+        .matching_nodes_sorted(&Nibbles::unpack(target))
+        .into_iter()
+        .map(|(_, node)| node)
+        .collect();
+
+    (root, proof)
+}
+
+fn proof_bundle(withdrawal: WithdrawalTransaction) -> WithdrawalProof {
+    let withdrawal_hash = withdrawal.hash();
+    let (storage_root, withdrawal_proof) = message_passer_storage(withdrawal_hash);
+    let output_root_proof = OutputRootProof {
+        version: B256::ZERO,
+        state_root: keccak256("state"),
+        message_passer_storage_root: storage_root,
+        latest_blockhash: keccak256("block"),
+    };
+
+    WithdrawalProof {
+        withdrawal_hash,
+        withdrawal: Some(withdrawal),
+        l2_block_number: 1234,
+        output_root: output_root_proof.output_root(),
+        output_root_proof,
+        withdrawal_proof,
+    }
+}
+
+#[test]
+fn test_withdrawal_decoded_from_log() {
+    let withdrawal = withdrawal();
+    let log = message_passed_log(&withdrawal, withdrawal.hash());
+
+    assert_eq!(WithdrawalTransaction::from_log(&log).unwrap(), withdrawal);
+}
+
+#[test]
+fn test_withdrawal_log_with_wrong_hash_rejected() {
+    let withdrawal = withdrawal();
+    let log = message_passed_log(&withdrawal, keccak256("other"));
+
+    assert!(WithdrawalTransaction::from_log(&log).is_err());
+}
+
+#[test]
+fn test_withdrawal_log_from_other_contract_rejected() {
+    let withdrawal = withdrawal();
+    let mut log = message_passed_log(&withdrawal, withdrawal.hash());
+    log.inner.address = address!("4200000000000000000000000000000000000007");
+
+    assert!(WithdrawalTransaction::from_log(&log).is_err());
+}
+
+#[test]
+fn test_withdrawal_slot() {
+    let hash = b256!("0101010101010101010101010101010101010101010101010101010101010101");
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(hash.as_slice());
+
+    assert_eq!(withdrawal_slot(hash), keccak256(preimage));
+}
+
+#[test]
+fn test_proof_bundle_verifies() {
+    proof_bundle(withdrawal()).verify().unwrap();
+}
+
+#[test]
+fn test_proof_bundle_for_other_withdrawal_rejected() {
+    let mut bundle = proof_bundle(withdrawal());
+    let mut other = withdrawal();
+    other.nonce += U256::from(1);
+    bundle.withdrawal_hash = other.hash();
+    bundle.withdrawal = Some(other);
+
+    assert!(bundle.verify().is_err());
+}
+
+#[test]
+fn test_proof_bundle_with_wrong_output_root_rejected() {
+    let mut bundle = proof_bundle(withdrawal());
+    bundle.output_root_proof.state_root = keccak256("forged");
+
+    assert!(bundle.verify().is_err());
+}
+
+#[test]
+fn test_proof_bundle_json_round_trip() {
+    let bundle = proof_bundle(withdrawal());
+    let json = serde_json::to_value(&bundle).unwrap();
+
+    assert!(json.get("outputRootProof").is_some());
+    assert!(json["outputRootProof"]
+        .get("messagePasserStorageRoot")
+        .is_some());
+    assert_eq!(
+        serde_json::from_value::<WithdrawalProof>(json).unwrap(),
+        bundle
+    );
+
+    let id: WithdrawalId =
+        serde_json::from_str(&format!(r#"{{"transactionHash":"{}"}}"#, B256::ZERO)).unwrap();
+    assert_eq!(id, WithdrawalId::TransactionHash(B256::ZERO));
+}
@@ -13,6 +13,10 @@
 //!
 //! The entire size of Helios's binary is 13Mb and compiles into WebAssembly. This makes it a perfect target to embed directly inside wallets and dapps.
 //!
+//! Network support is split into the `ethereum` and `opstack` cargo features, both enabled by
+//! default. With neither, only the generic [`core`] is built, for embedding with a custom
+//! [`NetworkSpec`](core::network_spec::NetworkSpec).
+//!
 //! Examples on how you can use helios can be found in the [`examples` directory of the repository](https://github.com/a16z/helios/tree/master/examples) and in the `tests/` directories of each crate.
 //!
 
@@ -20,10 +24,12 @@ pub mod core {
     pub use helios_core::*;
 }
 
+#[cfg(feature = "ethereum")]
 pub mod ethereum {
     pub use helios_ethereum::*;
 }
 
+#[cfg(feature = "opstack")]
 pub mod opstack {
     pub use helios_opstack::*;
 }
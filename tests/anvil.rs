@@ -0,0 +1,318 @@
+//! End to end tests of the execution client against a local anvil node, covering proof,
+//! receipt and log handling with responses from a real node.
+//!
+//! They are ignored by default, run them with `cargo test --test anvil -- --ignored`. Tests
+//! are skipped when no anvil binary is found, see `Anvil::binary` for where it is looked up.
+
+use std::sync::Arc;
+
+use alloy::network::TransactionBuilder;
+use alloy::primitives::{hex, Address, Bytes, B256, U256};
+use alloy::providers::{Provider, ProviderBuilder, RootProvider};
+use alloy::rpc::types::{
+    AccessList, AccessListItem, AccessListResult, Filter, FilterChanges, TransactionReceipt,
+    TransactionRequest,
+};
+use alloy::transports::http::{Client as ReqwestClient, Http};
+use helios::core::execution::evm::Evm;
+use helios::core::execution::rpc::{http_rpc::HttpRpc, ExecutionRpc};
+use helios::core::execution::ExecutionClient;
+use helios::core::fork_schedule::ForkSchedule;
+use helios::core::testing::anvil::Anvil;
+use helios::core::testing::consensus::ManualConsensus;
+use helios::core::types::BlockTag;
+use helios::ethereum::spec::Ethereum;
+
+type Rpc = HttpRpc<Ethereum>;
+
+const CHAIN_ID: u64 = 31337;
+
+// anvil runs cancun, and its blocks are all proof of stake
+const FORK_SCHEDULE: ForkSchedule = ForkSchedule {
+    prague_timestamp: u64::MAX,
+    merge_block: 0,
+};
+
+/// Stores its calldata word in slot 0 and logs it under topic 0x2a. Called without
+/// calldata, returns slot 0 instead.
+const STORE_INIT: &str = "602380600b6000396000f3\
+    36156017576000358060005560005260\
+    2a60206000a1005b6000546000526020\
+    6000f3";
+
+/// Returns what the address in its calldata returns when called without calldata, so a
+/// call proves two accounts and a storage slot.
+const READER_INIT: &str = "601380600b6000396000f3\
+    6020600060006000600035\
+    5afa5060206000f3";
+
+/// The topic the store contract logs under.
+const STORE_TOPIC: B256 = B256::with_last_byte(0x2a);
+
+struct Node {
+    _anvil: Anvil,
+    provider: RootProvider<Http<ReqwestClient>>,
+    consensus: ManualConsensus<Ethereum, Rpc>,
+    client: Arc<ExecutionClient<Ethereum, Rpc>>,
+    from: Address,
+}
+
+impl Node {
+    async fn start() -> Option<Self> {
+        let Some(anvil) = Anvil::spawn().unwrap() else {
+            eprintln!("skipping: no anvil binary found, set ANVIL_BIN or install foundry");
+            return None;
+        };
+
+        let provider = ProviderBuilder::new().on_http(anvil.endpoint().parse().unwrap());
+        let from = provider.get_accounts().await.unwrap()[0];
+
+        let rpc = Rpc::new(anvil.endpoint()).unwrap();
+        let consensus = ManualConsensus::new(rpc.clone(), 64);
+        let client = ExecutionClient::with_rpc(rpc, consensus.state(), FORK_SCHEDULE);
+        client.check_rpc(CHAIN_ID).await.unwrap();
+
+        Some(Self {
+            _anvil: anvil,
+            provider,
+            consensus,
+            client: Arc::new(client),
+            from,
+        })
+    }
+
+    /// Sends a transaction, which anvil mines right away, and hands the new block to the
+    /// client.
+    async fn send(&self, tx: TransactionRequest) -> TransactionReceipt {
+        let receipt = self
+            .provider
+            .send_transaction(tx.with_from(self.from))
+            .await
+            .unwrap()
+            .get_receipt()
+            .await
+            .unwrap();
+        assert!(receipt.status(), "transaction reverted: {receipt:?}");
+
+        self.consensus.advance().await.unwrap();
+        receipt
+    }
+
+    async fn deploy(&self, init_code: &str) -> Address {
+        let code = Bytes::from(hex::decode(init_code).unwrap());
+        let tx = TransactionRequest::default().with_deploy_code(code);
+        self.send(tx).await.contract_address.unwrap()
+    }
+
+    async fn store(&self, store: Address, value: u64) -> TransactionReceipt {
+        let data = B256::from(U256::from(value));
+        let tx = TransactionRequest::default()
+            .with_to(store)
+            .with_input(Bytes::copy_from_slice(data.as_slice()));
+        self.send(tx).await
+    }
+
+    async fn call(&self, tx: &TransactionRequest) -> Bytes {
+        let mut evm = Evm::new(
+            self.client.clone(),
+            CHAIN_ID,
+            FORK_SCHEDULE,
+            BlockTag::Latest,
+        );
+        evm.call(tx).await.unwrap()
+    }
+
+    async fn create_access_list(&self, tx: &TransactionRequest) -> AccessListResult {
+        let mut evm = Evm::new(
+            self.client.clone(),
+            CHAIN_ID,
+            FORK_SCHEDULE,
+            BlockTag::Latest,
+        );
+        evm.create_access_list(tx).await.unwrap()
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs anvil, run with --ignored"]
+async fn test_call_across_contracts() {
+    let Some(node) = Node::start().await else {
+        return;
+    };
+    let store = node.deploy(STORE_INIT).await;
+    let reader = node.deploy(READER_INIT).await;
+    node.store(store, 42).await;
+
+    let tx = TransactionRequest::default()
+        .with_from(node.from)
+        .with_to(reader)
+        .with_input(Bytes::copy_from_slice(store.into_word().as_slice()));
+    let output = node.call(&tx).await;
+    assert_eq!(U256::from_be_slice(&output), U256::from(42));
+
+    let value = node
+        .client
+        .get_storage_at(store, U256::ZERO, BlockTag::Latest)
+        .await
+        .unwrap();
+    assert_eq!(value, B256::from(U256::from(42)));
+
+    let balance = node
+        .client
+        .get_account(node.from, None, BlockTag::Latest)
+        .await
+        .unwrap()
+        .balance;
+    let expected = node.provider.get_balance(node.from).await.unwrap();
+    assert_eq!(balance, expected);
+}
+
+#[tokio::test]
+#[ignore = "needs anvil, run with --ignored"]
+async fn test_create_access_list() {
+    let Some(node) = Node::start().await else {
+        return;
+    };
+    let store = node.deploy(STORE_INIT).await;
+    let reader = node.deploy(READER_INIT).await;
+    node.store(store, 42).await;
+    let slot_0 = AccessList(vec![AccessListItem {
+        address: store,
+        storage_keys: vec![B256::ZERO],
+    }]);
+
+    // writing slot 0 of the recipient lists the slot, but neither the sender nor anything
+    // else the call only reads
+    let write = TransactionRequest::default()
+        .with_from(node.from)
+        .with_to(store)
+        .with_input(Bytes::copy_from_slice(B256::from(U256::from(7)).as_slice()));
+    let local = node.create_access_list(&write).await;
+    assert_eq!(local.access_list, slot_0);
+    assert_eq!(local.error, None);
+
+    let remote = node.provider.create_access_list(&write).await.unwrap();
+    assert_eq!(local.access_list, remote.access_list);
+    assert_eq!(local.gas_used, remote.gas_used);
+
+    // reading through another contract lists the account read from, not the recipient
+    let read = TransactionRequest::default()
+        .with_from(node.from)
+        .with_to(reader)
+        .with_input(Bytes::copy_from_slice(store.into_word().as_slice()));
+    let local = node.create_access_list(&read).await;
+    assert_eq!(local.access_list, slot_0);
+
+    let remote = node.provider.create_access_list(&read).await.unwrap();
+    assert_eq!(local.access_list, remote.access_list);
+    assert_eq!(local.gas_used, remote.gas_used);
+}
+
+#[tokio::test]
+#[ignore = "needs anvil, run with --ignored"]
+async fn test_proofs() {
+    let Some(node) = Node::start().await else {
+        return;
+    };
+    let store = node.deploy(STORE_INIT).await;
+    node.store(store, 7).await;
+
+    let proof = node
+        .client
+        .get_proof(store, &[B256::ZERO], BlockTag::Latest)
+        .await
+        .unwrap();
+    assert_eq!(proof.storage_proof[0].value, U256::from(7));
+
+    // an empty account is proven through an exclusion proof
+    let empty = Address::repeat_byte(0xee);
+    let proof = node
+        .client
+        .get_proof(empty, &[], BlockTag::Latest)
+        .await
+        .unwrap();
+    assert_eq!(proof.balance, U256::ZERO);
+
+    // proofs for a block the client has not seen yet are refused
+    let head = node.provider.get_block_number().await.unwrap();
+    let err = node
+        .client
+        .get_proof(store, &[], BlockTag::Number(head + 1))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("block not found"), "{err}");
+}
+
+#[tokio::test]
+#[ignore = "needs anvil, run with --ignored"]
+async fn test_receipts_and_logs() {
+    let Some(node) = Node::start().await else {
+        return;
+    };
+    let store = node.deploy(STORE_INIT).await;
+
+    let mut receipts = Vec::new();
+    for value in 1..=3 {
+        receipts.push(node.store(store, value).await);
+    }
+
+    for receipt in &receipts {
+        let verified = node
+            .client
+            .get_transaction_receipt(receipt.transaction_hash)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(verified.transaction_hash, receipt.transaction_hash);
+
+        let tag = BlockTag::Number(receipt.block_number.unwrap());
+        let block_receipts = node.client.get_block_receipts(tag).await.unwrap().unwrap();
+        assert_eq!(block_receipts.len(), 1);
+    }
+
+    let filter = Filter::new()
+        .address(store)
+        .event_signature(STORE_TOPIC)
+        .from_block(receipts[0].block_number.unwrap())
+        .to_block(receipts[2].block_number.unwrap());
+    let logs = node.client.get_logs(&filter).await.unwrap();
+    let values = logs
+        .iter()
+        .map(|log| U256::from_be_slice(&log.data().data))
+        .collect::<Vec<_>>();
+    assert_eq!(values, [1, 2, 3].map(U256::from));
+}
+
+#[tokio::test]
+#[ignore = "needs anvil, run with --ignored"]
+async fn test_filters() {
+    let Some(node) = Node::start().await else {
+        return;
+    };
+    let store = node.deploy(STORE_INIT).await;
+
+    let logs_filter = node
+        .client
+        .new_filter(&Filter::new().address(store))
+        .await
+        .unwrap();
+    let block_filter = node.client.new_block_filter().await.unwrap();
+
+    let receipt = node.store(store, 5).await;
+
+    let FilterChanges::Logs(logs) = node.client.get_filter_changes(logs_filter).await.unwrap()
+    else {
+        panic!("expected logs");
+    };
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].transaction_hash, Some(receipt.transaction_hash));
+
+    let FilterChanges::Hashes(hashes) = node.client.get_filter_changes(block_filter).await.unwrap()
+    else {
+        panic!("expected block hashes");
+    };
+    assert_eq!(hashes, [receipt.block_hash.unwrap()]);
+
+    assert!(node.client.uninstall_filter(logs_filter).await.unwrap());
+    assert!(node.client.get_filter_changes(logs_filter).await.is_err());
+}